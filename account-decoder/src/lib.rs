@@ -44,6 +44,9 @@ pub struct UiAccount {
     pub executable: bool,
     pub rent_epoch: Epoch,
     pub space: Option<u64>,
+    /// set by lazy-state validators when the account was fetched from their lazy account source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lazy_fetched: Option<bool>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -131,6 +134,7 @@ impl UiAccount {
             executable: account.executable(),
             rent_epoch: account.rent_epoch(),
             space: Some(space as u64),
+            lazy_fetched: None,
         }
     }
 
@@ -0,0 +1,215 @@
+use {
+    crate::{
+        error::BlockstoreClientError,
+        protocol::{
+            read_frame, write_frame, EncodedTransactionWithSlot, Request, Response,
+            MAX_SLOT_METAS_PER_REQUEST,
+        },
+        BlockstoreReader,
+    },
+    prost::Message,
+    solana_entry::entry::Entry,
+    solana_ledger::blockstore_meta::SlotMeta,
+    solana_sdk::{clock::Slot, signature::Signature},
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransactionWithStatusMeta, TransactionWithStatusMeta,
+        VersionedConfirmedBlock,
+    },
+    std::{
+        collections::VecDeque,
+        io,
+        os::unix::net::UnixStream,
+        path::{Path, PathBuf},
+        sync::Mutex,
+    },
+};
+
+/// A client of a blockstore server listening on a Unix domain socket
+pub struct BlockstoreClient {
+    socket_path: PathBuf,
+    stream: Mutex<UnixStream>,
+}
+
+impl BlockstoreClient {
+    pub fn connect(socket_path: impl AsRef<Path>) -> Result<Self, BlockstoreClientError> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+        let stream = UnixStream::connect(&socket_path)?;
+        Ok(Self {
+            socket_path,
+            stream: Mutex::new(stream),
+        })
+    }
+
+    fn request(&self, request: &Request) -> Result<Response, BlockstoreClientError> {
+        let mut stream = self.stream.lock().unwrap();
+        write_frame(&mut *stream, request)?;
+        match read_frame(&mut *stream)? {
+            Response::Error(err) => Err(BlockstoreClientError::Server(err)),
+            response => Ok(response),
+        }
+    }
+
+    /// Returns up to `limit` slot metas, in slot order, starting at
+    /// `start_slot`. The server caps `limit` at [`MAX_SLOT_METAS_PER_REQUEST`].
+    pub fn get_slot_metas(
+        &self,
+        start_slot: Slot,
+        limit: usize,
+    ) -> Result<Vec<(Slot, SlotMeta)>, BlockstoreClientError> {
+        match self.request(&Request::GetSlotMetas { start_slot, limit })? {
+            Response::SlotMetas(slot_metas) => Ok(slot_metas),
+            _ => Err(BlockstoreClientError::UnexpectedResponse),
+        }
+    }
+
+    /// Subscribes to the slots rooted from now on. The subscription uses a
+    /// connection of its own, so this client remains usable for requests.
+    pub fn subscribe_rooted_slots(&self) -> Result<RootedSlotSubscription, BlockstoreClientError> {
+        let mut stream = UnixStream::connect(&self.socket_path)?;
+        write_frame(&mut stream, &Request::SubscribeRootedSlots)?;
+        Ok(RootedSlotSubscription {
+            stream,
+            done: false,
+        })
+    }
+}
+
+impl BlockstoreReader for BlockstoreClient {
+    type Error = BlockstoreClientError;
+
+    fn get_slot_entries(
+        &self,
+        slot: Slot,
+        shred_start_index: u64,
+    ) -> Result<Vec<Entry>, Self::Error> {
+        match self.request(&Request::GetSlotEntries {
+            slot,
+            shred_start_index,
+        })? {
+            Response::SlotEntries(entries) => Ok(entries),
+            _ => Err(BlockstoreClientError::UnexpectedResponse),
+        }
+    }
+
+    fn get_rooted_block(
+        &self,
+        slot: Slot,
+        require_previous_blockhash: bool,
+    ) -> Result<VersionedConfirmedBlock, Self::Error> {
+        match self.request(&Request::GetRootedBlock {
+            slot,
+            require_previous_blockhash,
+        })? {
+            Response::RootedBlock(bytes) => {
+                let block = generated::ConfirmedBlock::decode(bytes.as_slice())?;
+                let block = ConfirmedBlock::try_from(block)?;
+                Ok(VersionedConfirmedBlock::try_from(block)?)
+            }
+            _ => Err(BlockstoreClientError::UnexpectedResponse),
+        }
+    }
+
+    fn get_complete_transaction(
+        &self,
+        signature: Signature,
+        highest_confirmed_slot: Slot,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>, Self::Error> {
+        match self.request(&Request::GetCompleteTransaction {
+            signature,
+            highest_confirmed_slot,
+        })? {
+            Response::CompleteTransaction(None) => Ok(None),
+            Response::CompleteTransaction(Some(EncodedTransactionWithSlot {
+                slot,
+                block_time,
+                transaction,
+            })) => {
+                let transaction = generated::ConfirmedTransaction::decode(transaction.as_slice())?;
+                Ok(Some(ConfirmedTransactionWithStatusMeta {
+                    slot,
+                    tx_with_meta: TransactionWithStatusMeta::try_from(transaction)?,
+                    block_time,
+                }))
+            }
+            _ => Err(BlockstoreClientError::UnexpectedResponse),
+        }
+    }
+
+    fn max_root(&self) -> Result<Slot, Self::Error> {
+        match self.request(&Request::GetMaxRoot)? {
+            Response::MaxRoot(slot) => Ok(slot),
+            _ => Err(BlockstoreClientError::UnexpectedResponse),
+        }
+    }
+
+    fn slot_meta_iterator<'a>(
+        &'a self,
+        slot: Slot,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Slot, SlotMeta), Self::Error>> + 'a>, Self::Error>
+    {
+        Ok(Box::new(SlotMetaIterator {
+            client: self,
+            next_slot: Some(slot),
+            page: VecDeque::new(),
+        }))
+    }
+}
+
+/// Iterates over the slot metas of a server, fetching them a page at a time
+pub struct SlotMetaIterator<'a> {
+    client: &'a BlockstoreClient,
+    /// Start of the next page to fetch, `None` once the last page is fetched
+    next_slot: Option<Slot>,
+    page: VecDeque<(Slot, SlotMeta)>,
+}
+
+impl Iterator for SlotMetaIterator<'_> {
+    type Item = Result<(Slot, SlotMeta), BlockstoreClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.page.is_empty() {
+            let start_slot = self.next_slot.take()?;
+            let page = match self
+                .client
+                .get_slot_metas(start_slot, MAX_SLOT_METAS_PER_REQUEST)
+            {
+                Ok(page) => page,
+                Err(err) => return Some(Err(err)),
+            };
+            if page.len() == MAX_SLOT_METAS_PER_REQUEST {
+                self.next_slot = page.last().map(|(slot, _)| slot + 1);
+            }
+            self.page = page.into();
+        }
+        self.page.pop_front().map(Ok)
+    }
+}
+
+/// The slots rooted by the node since the subscription was made, in order
+pub struct RootedSlotSubscription {
+    stream: UnixStream,
+    done: bool,
+}
+
+impl Iterator for RootedSlotSubscription {
+    type Item = Result<Slot, BlockstoreClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match read_frame(&mut self.stream) {
+            Ok(Response::RootedSlot(slot)) => return Some(Ok(slot)),
+            Ok(Response::Error(err)) => Err(BlockstoreClientError::Server(err)),
+            Ok(_) => Err(BlockstoreClientError::UnexpectedResponse),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                self.done = true;
+                return None;
+            }
+            Err(err) => Err(err.into()),
+        };
+        self.done = true;
+        Some(result)
+    }
+}
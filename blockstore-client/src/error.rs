@@ -0,0 +1,23 @@
+use {solana_transaction_status::ConvertBlockError, std::io, thiserror::Error};
+
+/// Errors from BlockstoreClient
+#[derive(Error, Debug)]
+pub enum BlockstoreClientError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialization(#[from] bincode::Error),
+
+    #[error("protobuf decode error: {0}")]
+    ProtobufDecode(#[from] prost::DecodeError),
+
+    #[error("block conversion error: {0}")]
+    ConvertBlock(#[from] ConvertBlockError),
+
+    #[error("server error: {0}")]
+    Server(String),
+
+    #[error("unexpected response from server")]
+    UnexpectedResponse,
+}
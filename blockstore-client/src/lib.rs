@@ -0,0 +1,98 @@
+//! Read-only access to a blockstore, either opened locally or shared by a
+//! `solana-blockstore-server` with the other processes of a node.
+//!
+//! [`BlockstoreReader`] is implemented by both [`Blockstore`] and
+//! [`BlockstoreClient`], so that consumers can switch between the two.
+#![allow(clippy::integer_arithmetic)]
+
+#[cfg(unix)]
+pub use crate::client::{BlockstoreClient, RootedSlotSubscription, SlotMetaIterator};
+pub use crate::error::BlockstoreClientError;
+use {
+    solana_entry::entry::Entry,
+    solana_ledger::{
+        blockstore::Blockstore, blockstore_db::BlockstoreError, blockstore_meta::SlotMeta,
+    },
+    solana_sdk::{clock::Slot, signature::Signature},
+    solana_transaction_status::{ConfirmedTransactionWithStatusMeta, VersionedConfirmedBlock},
+};
+
+#[cfg(unix)]
+mod client;
+mod error;
+pub mod protocol;
+
+/// The read-only `Blockstore` APIs that are also served by a blockstore server
+pub trait BlockstoreReader {
+    type Error: std::error::Error;
+
+    fn get_slot_entries(
+        &self,
+        slot: Slot,
+        shred_start_index: u64,
+    ) -> Result<Vec<Entry>, Self::Error>;
+
+    fn get_rooted_block(
+        &self,
+        slot: Slot,
+        require_previous_blockhash: bool,
+    ) -> Result<VersionedConfirmedBlock, Self::Error>;
+
+    fn get_complete_transaction(
+        &self,
+        signature: Signature,
+        highest_confirmed_slot: Slot,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>, Self::Error>;
+
+    /// Returns the max root or 0 if it does not exist
+    fn max_root(&self) -> Result<Slot, Self::Error>;
+
+    /// Iterates over the slot metas, in slot order, starting at `slot`
+    #[allow(clippy::type_complexity)]
+    fn slot_meta_iterator<'a>(
+        &'a self,
+        slot: Slot,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Slot, SlotMeta), Self::Error>> + 'a>, Self::Error>;
+}
+
+impl BlockstoreReader for Blockstore {
+    type Error = BlockstoreError;
+
+    fn get_slot_entries(
+        &self,
+        slot: Slot,
+        shred_start_index: u64,
+    ) -> Result<Vec<Entry>, Self::Error> {
+        Blockstore::get_slot_entries(self, slot, shred_start_index)
+    }
+
+    fn get_rooted_block(
+        &self,
+        slot: Slot,
+        require_previous_blockhash: bool,
+    ) -> Result<VersionedConfirmedBlock, Self::Error> {
+        Blockstore::get_rooted_block(self, slot, require_previous_blockhash)
+    }
+
+    fn get_complete_transaction(
+        &self,
+        signature: Signature,
+        highest_confirmed_slot: Slot,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>, Self::Error> {
+        Blockstore::get_complete_transaction(self, signature, highest_confirmed_slot)
+    }
+
+    fn max_root(&self) -> Result<Slot, Self::Error> {
+        Ok(Blockstore::max_root(self))
+    }
+
+    fn slot_meta_iterator<'a>(
+        &'a self,
+        slot: Slot,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Slot, SlotMeta), Self::Error>> + 'a>, Self::Error>
+    {
+        Ok(Box::new(
+            Blockstore::slot_meta_iterator(self, slot)?.map(Ok),
+        ))
+    }
+}
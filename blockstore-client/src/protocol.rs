@@ -0,0 +1,140 @@
+//! The wire protocol between a blockstore server and its clients.
+//!
+//! Every message is a frame holding a little-endian `u32` length followed by
+//! a bincode encoded [`Request`] or [`Response`]. A client sends one request
+//! and reads one response at a time, except after
+//! [`Request::SubscribeRootedSlots`], which turns the connection into a feed
+//! of [`Response::RootedSlot`] frames.
+//!
+//! Blocks and transactions are sent encoded as the protobuf messages of
+//! `solana-storage-proto`, since their status metadata is not serde
+//! serializable.
+
+use {
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+    solana_entry::entry::Entry,
+    solana_ledger::blockstore_meta::SlotMeta,
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        signature::Signature,
+    },
+    std::io::{self, Read, Write},
+};
+
+/// Largest frame accepted by either end of a connection
+pub const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
+/// Most slot metas returned by a single [`Request::GetSlotMetas`]
+pub const MAX_SLOT_METAS_PER_REQUEST: usize = 10_000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Request {
+    GetSlotEntries {
+        slot: Slot,
+        shred_start_index: u64,
+    },
+    GetRootedBlock {
+        slot: Slot,
+        require_previous_blockhash: bool,
+    },
+    GetCompleteTransaction {
+        signature: Signature,
+        highest_confirmed_slot: Slot,
+    },
+    /// Up to `limit` slot metas, in slot order, starting at `start_slot`
+    GetSlotMetas {
+        start_slot: Slot,
+        limit: usize,
+    },
+    GetMaxRoot,
+    /// Streams every slot rooted from now on over this connection
+    SubscribeRootedSlots,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodedTransactionWithSlot {
+    pub slot: Slot,
+    pub block_time: Option<UnixTimestamp>,
+    /// Protobuf encoded `generated::ConfirmedTransaction`
+    pub transaction: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Response {
+    SlotEntries(Vec<Entry>),
+    /// Protobuf encoded `generated::ConfirmedBlock`
+    RootedBlock(Vec<u8>),
+    CompleteTransaction(Option<EncodedTransactionWithSlot>),
+    SlotMetas(Vec<(Slot, SlotMeta)>),
+    MaxRoot(Slot),
+    RootedSlot(Slot),
+    Error(String),
+}
+
+pub fn write_frame<W: Write, T: Serialize>(writer: &mut W, message: &T) -> io::Result<()> {
+    let bytes =
+        bincode::serialize(message).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    if bytes.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes exceeds {MAX_FRAME_LEN}", bytes.len()),
+        ));
+    }
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+pub fn read_frame<R: Read, T: DeserializeOwned>(reader: &mut R) -> io::Result<T> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds {MAX_FRAME_LEN}"),
+        ));
+    }
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    bincode::deserialize(&bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Cursor};
+
+    #[test]
+    fn test_frame_round_trip() {
+        let mut buffer = vec![];
+        let requests = vec![
+            Request::GetSlotEntries {
+                slot: 5,
+                shred_start_index: 0,
+            },
+            Request::GetMaxRoot,
+            Request::SubscribeRootedSlots,
+        ];
+        for request in &requests {
+            write_frame(&mut buffer, request).unwrap();
+        }
+
+        let mut reader = Cursor::new(buffer);
+        for request in requests {
+            assert_eq!(read_frame::<_, Request>(&mut reader).unwrap(), request);
+        }
+        assert_eq!(
+            read_frame::<_, Request>(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_read_frame_too_large() {
+        let mut reader = Cursor::new(((MAX_FRAME_LEN + 1) as u32).to_le_bytes().to_vec());
+        assert_eq!(
+            read_frame::<_, Request>(&mut reader).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Serves the read-only `Blockstore` APIs over a Unix domain socket, so that
+//! several processes of a node can share a single blockstore instance.
+//!
+//! When the blockstore is opened as a secondary, the server catches it up with
+//! the primary periodically, and each new root found is sent to the clients
+//! subscribed to rooted slots. See [`solana_blockstore_client::protocol`] for
+//! the wire protocol.
+
+use {
+    crossbeam_channel::{unbounded, RecvTimeoutError, Sender},
+    log::*,
+    prost::Message,
+    solana_blockstore_client::protocol::{
+        read_frame, write_frame, EncodedTransactionWithSlot, Request, Response,
+        MAX_SLOT_METAS_PER_REQUEST,
+    },
+    solana_ledger::blockstore::Blockstore,
+    solana_sdk::clock::Slot,
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::ConfirmedTransactionWithStatusMeta,
+    std::{
+        fs, io,
+        os::unix::net::{UnixListener, UnixStream},
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, sleep, Builder, JoinHandle},
+        time::Duration,
+    },
+};
+
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+type RootedSlotSubscribers = Arc<Mutex<Vec<Sender<Slot>>>>;
+
+pub struct BlockstoreServer {
+    thread_hdls: Vec<JoinHandle<()>>,
+}
+
+impl BlockstoreServer {
+    /// Listens on `socket_path`, replacing any stale socket file left there.
+    ///
+    /// The blockstore is caught up with its primary, and checked for new
+    /// roots, every `catch_up_interval`.
+    pub fn new(
+        blockstore: Arc<Blockstore>,
+        socket_path: &Path,
+        catch_up_interval: Duration,
+        exit: Arc<AtomicBool>,
+    ) -> io::Result<Self> {
+        if socket_path.exists() {
+            fs::remove_file(socket_path)?;
+        }
+        let listener = UnixListener::bind(socket_path)?;
+        listener.set_nonblocking(true)?;
+        let subscribers = RootedSlotSubscribers::default();
+
+        let accept_thread = {
+            let blockstore = blockstore.clone();
+            let subscribers = subscribers.clone();
+            let exit = exit.clone();
+            Builder::new()
+                .name("solBstoreSrvAcc".to_string())
+                .spawn(move || Self::accept_loop(listener, blockstore, subscribers, exit))
+                .unwrap()
+        };
+        let root_thread = Builder::new()
+            .name("solBstoreSrvRt".to_string())
+            .spawn(move || Self::watch_roots(blockstore, subscribers, catch_up_interval, exit))
+            .unwrap();
+
+        Ok(Self {
+            thread_hdls: vec![accept_thread, root_thread],
+        })
+    }
+
+    /// Waits for the server to exit. Connection threads are not joined, they
+    /// end when their client disconnects.
+    pub fn join(self) -> thread::Result<()> {
+        for thread_hdl in self.thread_hdls {
+            thread_hdl.join()?;
+        }
+        Ok(())
+    }
+
+    fn accept_loop(
+        listener: UnixListener,
+        blockstore: Arc<Blockstore>,
+        subscribers: RootedSlotSubscribers,
+        exit: Arc<AtomicBool>,
+    ) {
+        while !exit.load(Ordering::Relaxed) {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    sleep(ACCEPT_POLL_INTERVAL);
+                    continue;
+                }
+                Err(err) => {
+                    warn!("blockstore server failed to accept a connection: {err}");
+                    sleep(ACCEPT_POLL_INTERVAL);
+                    continue;
+                }
+            };
+            let blockstore = blockstore.clone();
+            let subscribers = subscribers.clone();
+            let exit = exit.clone();
+            let spawned = Builder::new()
+                .name("solBstoreSrvCon".to_string())
+                .spawn(move || {
+                    if let Err(err) =
+                        Self::serve_connection(stream, &blockstore, &subscribers, &exit)
+                    {
+                        debug!("blockstore server connection closed: {err}");
+                    }
+                });
+            if let Err(err) = spawned {
+                warn!("blockstore server failed to spawn a connection thread: {err}");
+            }
+        }
+    }
+
+    fn serve_connection(
+        mut stream: UnixStream,
+        blockstore: &Blockstore,
+        subscribers: &RootedSlotSubscribers,
+        exit: &AtomicBool,
+    ) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        loop {
+            let request = match read_frame(&mut stream) {
+                Ok(request) => request,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(err) => return Err(err),
+            };
+            if request == Request::SubscribeRootedSlots {
+                return Self::serve_rooted_slots(stream, subscribers, exit);
+            }
+            write_frame(&mut stream, &Self::process_request(blockstore, request))?;
+        }
+    }
+
+    fn serve_rooted_slots(
+        mut stream: UnixStream,
+        subscribers: &RootedSlotSubscribers,
+        exit: &AtomicBool,
+    ) -> io::Result<()> {
+        let (sender, receiver) = unbounded();
+        subscribers.lock().unwrap().push(sender);
+        while !exit.load(Ordering::Relaxed) {
+            match receiver.recv_timeout(SUBSCRIPTION_POLL_INTERVAL) {
+                Ok(slot) => write_frame(&mut stream, &Response::RootedSlot(slot))?,
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn process_request(blockstore: &Blockstore, request: Request) -> Response {
+        let response = match request {
+            Request::GetSlotEntries {
+                slot,
+                shred_start_index,
+            } => blockstore
+                .get_slot_entries(slot, shred_start_index)
+                .map(Response::SlotEntries),
+            Request::GetRootedBlock {
+                slot,
+                require_previous_blockhash,
+            } => blockstore
+                .get_rooted_block(slot, require_previous_blockhash)
+                .map(|block| {
+                    Response::RootedBlock(generated::ConfirmedBlock::from(block).encode_to_vec())
+                }),
+            Request::GetCompleteTransaction {
+                signature,
+                highest_confirmed_slot,
+            } => blockstore
+                .get_complete_transaction(signature, highest_confirmed_slot)
+                .map(|transaction| {
+                    Response::CompleteTransaction(transaction.map(
+                        |ConfirmedTransactionWithStatusMeta {
+                             slot,
+                             tx_with_meta,
+                             block_time,
+                         }| EncodedTransactionWithSlot {
+                            slot,
+                            block_time,
+                            transaction:
+                                generated::ConfirmedTransaction::from(tx_with_meta).encode_to_vec(),
+                        },
+                    ))
+                }),
+            Request::GetSlotMetas { start_slot, limit } => {
+                blockstore.slot_meta_iterator(start_slot).map(|slot_metas| {
+                    Response::SlotMetas(
+                        slot_metas
+                            .take(limit.min(MAX_SLOT_METAS_PER_REQUEST))
+                            .collect(),
+                    )
+                })
+            }
+            Request::GetMaxRoot => Ok(Response::MaxRoot(blockstore.max_root())),
+            Request::SubscribeRootedSlots => {
+                return Response::Error("unexpected subscription request".to_string())
+            }
+        };
+        response.unwrap_or_else(|err| Response::Error(format!("{err:?}")))
+    }
+
+    fn watch_roots(
+        blockstore: Arc<Blockstore>,
+        subscribers: RootedSlotSubscribers,
+        catch_up_interval: Duration,
+        exit: Arc<AtomicBool>,
+    ) {
+        let mut last_root = blockstore.max_root();
+        while !exit.load(Ordering::Relaxed) {
+            sleep(catch_up_interval);
+            if !blockstore.is_primary_access() {
+                if let Err(err) = blockstore.try_catch_up_with_primary() {
+                    warn!("blockstore server failed to catch up with the primary: {err:?}");
+                    continue;
+                }
+            }
+            let max_root = blockstore.max_root();
+            if max_root <= last_root {
+                continue;
+            }
+            let new_roots: Vec<Slot> = match blockstore.rooted_slot_iterator(last_root + 1) {
+                Ok(rooted_slots) => rooted_slots.take_while(|slot| *slot <= max_root).collect(),
+                Err(err) => {
+                    warn!("blockstore server failed to iterate over roots: {err:?}");
+                    continue;
+                }
+            };
+            last_root = max_root;
+            // Subscribers whose connection is gone are dropped here
+            subscribers
+                .lock()
+                .unwrap()
+                .retain(|subscriber| new_roots.iter().all(|slot| subscriber.send(*slot).is_ok()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_blockstore_client::{BlockstoreClient, BlockstoreReader},
+        solana_ledger::{blockstore::make_many_slot_entries, get_tmp_ledger_path_auto_delete},
+    };
+
+    #[test]
+    fn test_blockstore_server() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Arc::new(Blockstore::open(ledger_path.path()).unwrap());
+        let (shreds, _) = make_many_slot_entries(0, 3, 4);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([0, 1].iter()).unwrap();
+
+        let socket_path = ledger_path.path().join("blockstore.sock");
+        let exit = Arc::new(AtomicBool::new(false));
+        let server = BlockstoreServer::new(
+            blockstore.clone(),
+            &socket_path,
+            Duration::from_millis(10),
+            exit.clone(),
+        )
+        .unwrap();
+        let client = BlockstoreClient::connect(&socket_path).unwrap();
+
+        assert_eq!(client.max_root().unwrap(), 1);
+        assert_eq!(
+            client.get_slot_entries(2, 0).unwrap(),
+            blockstore.get_slot_entries(2, 0).unwrap()
+        );
+        let slot_metas: Vec<_> = client
+            .slot_meta_iterator(1)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let expected_slot_metas: Vec<_> = blockstore.slot_meta_iterator(1).unwrap().collect();
+        assert_eq!(slot_metas, expected_slot_metas);
+        assert!(client.get_rooted_block(2, false).is_err());
+
+        let mut rooted_slots = client.subscribe_rooted_slots().unwrap();
+        // Give the server time to register the subscription
+        sleep(Duration::from_millis(100));
+        blockstore.set_roots([2].iter()).unwrap();
+        assert_eq!(rooted_slots.next().unwrap().unwrap(), 2);
+
+        exit.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,3 @@
+#![allow(clippy::integer_arithmetic)]
+#[cfg(unix)]
+pub mod blockstore_server;
@@ -0,0 +1,98 @@
+//! A command-line executable that shares the blockstore of a node with other
+//! local processes over a Unix domain socket
+#![allow(clippy::integer_arithmetic)]
+
+#[cfg(unix)]
+use {
+    clap::{crate_description, crate_name, value_t_or_exit, App, Arg},
+    log::*,
+    solana_blockstore_server::blockstore_server::BlockstoreServer,
+    solana_clap_utils::input_validators::is_parsable,
+    solana_ledger::{
+        blockstore::Blockstore,
+        blockstore_options::{AccessType, BlockstoreOptions},
+    },
+    std::{
+        path::PathBuf,
+        process::exit,
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
+    },
+};
+
+#[cfg(unix)]
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("ledger_path")
+                .short("l")
+                .long("ledger")
+                .value_name("DIR")
+                .takes_value(true)
+                .default_value("ledger")
+                .help("Use DIR as ledger location"),
+        )
+        .arg(
+            Arg::with_name("socket_path")
+                .long("socket")
+                .value_name("PATH")
+                .takes_value(true)
+                .help(
+                    "Listen on the Unix domain socket at PATH [default: <LEDGER>/blockstore.sock]",
+                ),
+        )
+        .arg(
+            Arg::with_name("catch_up_interval_ms")
+                .long("catch-up-interval-ms")
+                .value_name("MILLISECONDS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .default_value("400")
+                .help("How often to catch up with the validator and check for new roots"),
+        )
+        .get_matches();
+
+    let ledger_path = PathBuf::from(matches.value_of("ledger_path").unwrap());
+    let socket_path = matches
+        .value_of("socket_path")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| ledger_path.join("blockstore.sock"));
+    let catch_up_interval =
+        Duration::from_millis(value_t_or_exit!(matches, "catch_up_interval_ms", u64));
+
+    let blockstore = Blockstore::open_with_options(
+        &ledger_path,
+        BlockstoreOptions {
+            access_type: AccessType::Secondary,
+            enforce_ulimit_nofile: false,
+            ..BlockstoreOptions::default()
+        },
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to open ledger at {ledger_path:?}: {err:?}");
+        exit(1);
+    });
+
+    let server = BlockstoreServer::new(
+        Arc::new(blockstore),
+        &socket_path,
+        catch_up_interval,
+        Arc::new(AtomicBool::new(false)),
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to listen on {socket_path:?}: {err}");
+        exit(1);
+    });
+    info!("Serving the blockstore at {ledger_path:?} on {socket_path:?}");
+    server.join().unwrap();
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("solana-blockstore-server is only supported on Unix");
+    std::process::exit(1);
+}
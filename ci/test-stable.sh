@@ -225,6 +225,15 @@ test-wasm)
       popd
     fi
   done
+
+  # solana-rpc-client-wasm and its dependencies must keep building for wasm32
+  _ rustup target add --toolchain "$rust_stable" wasm32-unknown-unknown
+  _ cargo check --target wasm32-unknown-unknown -p solana-rpc-client-wasm
+  # The test runner must match the wasm-bindgen version the tests are built with
+  wasm_bindgen_version=$(cargo pkgid wasm-bindgen | sed -e 's/.*[#@]//')
+  _ "$cargo" install wasm-bindgen-cli --version "$wasm_bindgen_version"
+  CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+    _ cargo test --target wasm32-unknown-unknown -p solana-rpc-client-wasm --test wasm_transport
   exit 0
   ;;
 test-docs)
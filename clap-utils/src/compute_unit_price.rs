@@ -1,15 +1,32 @@
-use {crate::ArgConstant, clap::Arg};
+use {
+    crate::{input_validators::is_parsable, ArgConstant},
+    clap::Arg,
+};
 
 pub const COMPUTE_UNIT_PRICE_ARG: ArgConstant<'static> = ArgConstant {
     name: "compute_unit_price",
     long: "--with-compute-unit-price",
-    help: "Set compute unit price for transaction, in increments of 0.000001 lamports per compute unit.",
+    help: "Set compute unit price for transaction, in increments of 0.000001 lamports per compute unit. \
+           Use \"auto\" to set it from the 75th percentile of the prioritization fees paid in recent \
+           blocks by transactions writing to the signers' accounts.",
 };
 
+/// Value of `COMPUTE_UNIT_PRICE_ARG` requesting a price estimated by the cluster
+pub const COMPUTE_UNIT_PRICE_AUTO: &str = "auto";
+
 pub fn compute_unit_price_arg<'a, 'b>() -> Arg<'a, 'b> {
     Arg::with_name(COMPUTE_UNIT_PRICE_ARG.name)
         .long(COMPUTE_UNIT_PRICE_ARG.long)
         .takes_value(true)
         .value_name("COMPUTE-UNIT-PRICE")
+        .validator(is_compute_unit_price)
         .help(COMPUTE_UNIT_PRICE_ARG.help)
 }
+
+fn is_compute_unit_price(string: String) -> Result<(), String> {
+    if string == COMPUTE_UNIT_PRICE_AUTO {
+        Ok(())
+    } else {
+        is_parsable::<u64>(string)
+    }
+}
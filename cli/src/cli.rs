@@ -445,6 +445,78 @@ pub enum CliCommand {
     },
 }
 
+impl CliCommand {
+    /// The compute unit price of the transactions sent by this command, if it sends any
+    pub fn compute_unit_price_mut(&mut self) -> Option<&mut Option<u64>> {
+        match self {
+            Self::Ping {
+                compute_unit_price, ..
+            }
+            | Self::AuthorizeNonceAccount {
+                compute_unit_price, ..
+            }
+            | Self::CreateNonceAccount {
+                compute_unit_price, ..
+            }
+            | Self::NewNonce {
+                compute_unit_price, ..
+            }
+            | Self::WithdrawFromNonceAccount {
+                compute_unit_price, ..
+            }
+            | Self::UpgradeNonceAccount {
+                compute_unit_price, ..
+            }
+            | Self::CreateStakeAccount {
+                compute_unit_price, ..
+            }
+            | Self::DeactivateStake {
+                compute_unit_price, ..
+            }
+            | Self::DelegateStake {
+                compute_unit_price, ..
+            }
+            | Self::SplitStake {
+                compute_unit_price, ..
+            }
+            | Self::MergeStake {
+                compute_unit_price, ..
+            }
+            | Self::StakeAuthorize {
+                compute_unit_price, ..
+            }
+            | Self::StakeSetLockup {
+                compute_unit_price, ..
+            }
+            | Self::WithdrawStake {
+                compute_unit_price, ..
+            }
+            | Self::CreateVoteAccount {
+                compute_unit_price, ..
+            }
+            | Self::WithdrawFromVoteAccount {
+                compute_unit_price, ..
+            }
+            | Self::CloseVoteAccount {
+                compute_unit_price, ..
+            }
+            | Self::VoteAuthorize {
+                compute_unit_price, ..
+            }
+            | Self::VoteUpdateValidator {
+                compute_unit_price, ..
+            }
+            | Self::VoteUpdateCommission {
+                compute_unit_price, ..
+            }
+            | Self::Transfer {
+                compute_unit_price, ..
+            } => Some(compute_unit_price),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct CliCommandInfo {
     pub command: CliCommand,
@@ -1,4 +1,12 @@
-use solana_sdk::{compute_budget::ComputeBudgetInstruction, instruction::Instruction};
+use {
+    crate::cli::CliConfig,
+    clap::ArgMatches,
+    solana_clap_utils::compute_unit_price::{COMPUTE_UNIT_PRICE_ARG, COMPUTE_UNIT_PRICE_AUTO},
+    solana_rpc_client::rpc_client::RpcClient,
+    solana_rpc_client_api::config::RpcPriorityFeeEstimateConfig,
+    solana_sdk::{compute_budget::ComputeBudgetInstruction, instruction::Instruction},
+    std::error,
+};
 
 pub trait WithComputeUnitPrice {
     fn with_compute_unit_price(self, compute_unit_price: Option<&u64>) -> Self;
@@ -14,3 +22,42 @@ impl WithComputeUnitPrice for Vec<Instruction> {
         self
     }
 }
+
+/// Whether the subcommand in `matches` was given `--with-compute-unit-price auto`
+pub fn is_compute_unit_price_auto(matches: &ArgMatches<'_>) -> bool {
+    matches
+        .subcommand()
+        .1
+        .and_then(|matches| matches.value_of(COMPUTE_UNIT_PRICE_ARG.name))
+        == Some(COMPUTE_UNIT_PRICE_AUTO)
+}
+
+/// Set the compute unit price of `config.command` from the cluster's estimate of the
+/// prioritization fees paid by transactions writing to the signers' accounts
+pub fn resolve_auto_compute_unit_price(
+    config: &mut CliConfig,
+) -> Result<(), Box<dyn error::Error>> {
+    let account_keys = config
+        .signers
+        .iter()
+        .map(|signer| signer.try_pubkey().map(|pubkey| pubkey.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let Some(compute_unit_price) = config.command.compute_unit_price_mut() else {
+        return Ok(());
+    };
+    let rpc_client = match &config.rpc_client {
+        Some(rpc_client) => rpc_client.clone(),
+        None => RpcClient::new_with_timeout_and_commitment(
+            config.json_rpc_url.to_string(),
+            config.rpc_timeout,
+            config.commitment,
+        )
+        .into(),
+    };
+    let estimate = rpc_client.get_priority_fee_estimate(RpcPriorityFeeEstimateConfig {
+        account_keys: Some(account_keys),
+        ..RpcPriorityFeeEstimateConfig::default()
+    })?;
+    *compute_unit_price = Some(estimate.p75);
+    Ok(())
+}
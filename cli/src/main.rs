@@ -9,6 +9,7 @@ use {
     solana_cli::{
         clap_app::get_clap_app,
         cli::{parse_command, process_command, CliCommandInfo, CliConfig},
+        compute_unit_price::{is_compute_unit_price_auto, resolve_auto_compute_unit_price},
     },
     solana_cli_config::{Config, ConfigInput},
     solana_cli_output::{
@@ -253,6 +254,9 @@ fn do_main(matches: &ArgMatches<'_>) -> Result<(), Box<dyn error::Error>> {
 
         let (mut config, signers) = parse_args(matches, &mut wallet_manager)?;
         config.signers = signers.iter().map(|s| s.as_ref()).collect();
+        if is_compute_unit_price_auto(matches) {
+            resolve_auto_compute_unit_price(&mut config)?;
+        }
         let result = process_command(&config)?;
         println!("{result}");
     };
@@ -890,6 +890,7 @@ impl BankingStage {
     ) -> ExecuteAndCommitTransactionsOutput {
         let mut execute_and_commit_timings = LeaderExecuteAndCommitTimings::default();
 
+        bank.load_lazy_accounts_for_batch(batch);
         let mut pre_balance_info = PreBalanceInfo::default();
         let (_, collect_balances_time) = measure!(
             {
@@ -2756,6 +2757,7 @@ mod tests {
                 None,
                 blockstore.clone(),
                 false,
+                false,
                 &Arc::new(AtomicBool::new(false)),
             );
 
@@ -2925,6 +2927,7 @@ mod tests {
                 None,
                 blockstore.clone(),
                 false,
+                false,
                 &Arc::new(AtomicBool::new(false)),
             );
 
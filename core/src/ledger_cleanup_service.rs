@@ -228,6 +228,15 @@ impl LedgerCleanupService {
             let _t_purge = Builder::new()
                 .name("solLedgerPurge".to_string())
                 .spawn(move || {
+                    // Archive the rooted blocks before they become unreadable, purging them
+                    // regardless as the compaction filter would remove them later anyway
+                    if let Err(err) = blockstore.archive_rooted_slots(lowest_cleanup_slot) {
+                        error!(
+                            "Error: {:?}; Archiving failed up to slot {}",
+                            err, lowest_cleanup_slot
+                        );
+                    }
+
                     let mut slot_update_time = Measure::start("slot_update");
                     *blockstore.lowest_cleanup_slot.write().unwrap() = lowest_cleanup_slot;
                     slot_update_time.stop();
@@ -298,7 +307,10 @@ mod tests {
     use {
         super::*,
         crossbeam_channel::unbounded,
-        solana_ledger::{blockstore::make_many_slot_entries, get_tmp_ledger_path_auto_delete},
+        solana_ledger::{
+            blockstore::make_many_slot_entries, blockstore_options::BlockstoreOptions,
+            get_tmp_ledger_path_auto_delete,
+        },
     };
 
     fn flush_blockstore_contents_to_disk(blockstore: Blockstore) -> Blockstore {
@@ -405,6 +417,44 @@ mod tests {
             .for_each(|(slot, _)| assert!(slot > 40));
     }
 
+    #[test]
+    fn test_cleanup_archives_rooted_slots() {
+        solana_logger::setup();
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let archive_dir = tempfile::TempDir::new().unwrap();
+        let options = || BlockstoreOptions {
+            archive_path: Some(archive_dir.path().to_path_buf()),
+            ..BlockstoreOptions::default()
+        };
+        let blockstore = Blockstore::open_with_options(ledger_path.path(), options()).unwrap();
+        let (shreds, _) = make_many_slot_entries(0, 50, 5);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        let roots: Vec<Slot> = (0..50).collect();
+        blockstore.set_roots(roots.iter()).unwrap();
+        let block = blockstore.get_rooted_block(10, false).unwrap();
+
+        // Initiate a flush so inserted shreds found by find_slots_to_clean()
+        drop(blockstore);
+        let blockstore =
+            Arc::new(Blockstore::open_with_options(ledger_path.path(), options()).unwrap());
+        let (sender, receiver) = unbounded();
+
+        let mut last_purge_slot = 0;
+        sender.send(50).unwrap();
+        LedgerCleanupService::cleanup_ledger(&receiver, &blockstore, 5, &mut last_purge_slot, 10)
+            .unwrap();
+        let lowest_cleanup_slot = *blockstore.lowest_cleanup_slot.read().unwrap();
+        assert!(lowest_cleanup_slot >= 10);
+        assert!(blockstore.meta(10).unwrap().is_none());
+
+        // The purged rooted blocks are read back from the archive
+        assert_eq!(
+            blockstore.archive().unwrap().last_slot(),
+            Some(lowest_cleanup_slot)
+        );
+        assert_eq!(blockstore.get_rooted_block(10, false).unwrap(), block);
+    }
+
     #[test]
     fn test_cleanup_speed() {
         solana_logger::setup();
@@ -1,10 +1,13 @@
 use {
+    crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender, TrySendError},
+    log::*,
     solana_gossip::cluster_info::{
         ClusterInfo, MAX_INCREMENTAL_SNAPSHOT_HASHES, MAX_SNAPSHOT_HASHES,
     },
     solana_perf::thread::renice_this_thread,
     solana_runtime::{
-        snapshot_archive_info::SnapshotArchiveInfoGetter,
+        snapshot_archive_info::{SnapshotArchiveInfo, SnapshotArchiveInfoGetter},
+        snapshot_archive_store::{publish_snapshot_archive, SnapshotArchiveStore},
         snapshot_config::SnapshotConfig,
         snapshot_hash::{
             FullSnapshotHash, FullSnapshotHashes, IncrementalSnapshotHash,
@@ -24,8 +27,14 @@ use {
     },
 };
 
+/// Archives waiting to be published before newer ones are dropped instead
+const MAX_PENDING_PUBLISHES: usize = 2;
+
+type PublishSnapshotArchive = (SnapshotArchiveInfo, SnapshotType);
+
 pub struct SnapshotPackagerService {
     t_snapshot_packager: JoinHandle<()>,
+    t_snapshot_publisher: Option<JoinHandle<()>>,
 }
 
 impl SnapshotPackagerService {
@@ -48,6 +57,23 @@ impl SnapshotPackagerService {
             snapshot_config.maximum_incremental_snapshot_archives_to_retain,
         );
 
+        // Uploads can take much longer than archiving, so they run on their own thread so as not
+        // to hold up the next snapshot packages
+        let (publish_sender, t_snapshot_publisher) = match snapshot_config.archive_store.clone() {
+            Some(archive_store) => {
+                let (publish_sender, publish_receiver) = bounded(MAX_PENDING_PUBLISHES);
+                let t_snapshot_publisher = Self::start_publisher(
+                    publish_receiver,
+                    archive_store,
+                    exit.clone(),
+                    snapshot_config.maximum_full_snapshot_archives_to_retain,
+                    snapshot_config.maximum_incremental_snapshot_archives_to_retain,
+                );
+                (Some(publish_sender), Some(t_snapshot_publisher))
+            }
+            None => (None, None),
+        };
+
         let t_snapshot_packager = Builder::new()
             .name("solSnapshotPkgr".to_string())
             .spawn(move || {
@@ -97,17 +123,82 @@ impl SnapshotPackagerService {
                             (snapshot_package.slot(), snapshot_package.hash().0),
                         );
                     }
+
+                    // Publishing is best effort: the archive remains available from this node
+                    if let Some(publish_sender) = publish_sender.as_ref() {
+                        Self::queue_publish(
+                            publish_sender,
+                            snapshot_package.snapshot_archive_info().clone(),
+                            snapshot_package.snapshot_type,
+                        );
+                    }
                 }
             })
             .unwrap();
 
         Self {
             t_snapshot_packager,
+            t_snapshot_publisher,
+        }
+    }
+
+    fn queue_publish(
+        publish_sender: &Sender<PublishSnapshotArchive>,
+        snapshot_archive_info: SnapshotArchiveInfo,
+        snapshot_type: SnapshotType,
+    ) {
+        match publish_sender.try_send((snapshot_archive_info, snapshot_type)) {
+            Ok(()) => (),
+            Err(TrySendError::Full((snapshot_archive_info, _))) => warn!(
+                "Not publishing snapshot archive {}: {MAX_PENDING_PUBLISHES} archives are \
+                 already waiting to be published",
+                snapshot_archive_info.path().display()
+            ),
+            Err(TrySendError::Disconnected(_)) => (),
         }
     }
 
+    fn start_publisher(
+        publish_receiver: Receiver<PublishSnapshotArchive>,
+        archive_store: Arc<dyn SnapshotArchiveStore>,
+        exit: Arc<AtomicBool>,
+        maximum_full_snapshot_archives_to_retain: usize,
+        maximum_incremental_snapshot_archives_to_retain: usize,
+    ) -> JoinHandle<()> {
+        Builder::new()
+            .name("solSnapshotPub".to_string())
+            .spawn(move || loop {
+                if exit.load(Ordering::Relaxed) {
+                    break;
+                }
+                let (snapshot_archive_info, snapshot_type) =
+                    match publish_receiver.recv_timeout(Duration::from_millis(100)) {
+                        Ok(publish) => publish,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+                if let Err(err) = publish_snapshot_archive(
+                    archive_store.as_ref(),
+                    &snapshot_archive_info,
+                    snapshot_type,
+                    maximum_full_snapshot_archives_to_retain,
+                    maximum_incremental_snapshot_archives_to_retain,
+                ) {
+                    warn!(
+                        "Failed to publish snapshot archive {}: {err}",
+                        snapshot_archive_info.path().display()
+                    );
+                }
+            })
+            .unwrap()
+    }
+
     pub fn join(self) -> thread::Result<()> {
-        self.t_snapshot_packager.join()
+        self.t_snapshot_packager.join()?;
+        if let Some(t_snapshot_publisher) = self.t_snapshot_publisher {
+            t_snapshot_publisher.join()?;
+        }
+        Ok(())
     }
 }
 
@@ -256,16 +347,24 @@ mod tests {
     #[test]
     fn test_package_snapshots_relative_ledger_path() {
         let temp_dir = make_tmp_dir_path();
-        create_and_verify_snapshot(&temp_dir);
+        create_and_verify_snapshot(&temp_dir, ArchiveFormat::TarBzip2);
         remove_dir_all(temp_dir).expect("should remove tmp dir");
     }
 
     #[test]
     fn test_package_snapshots() {
-        create_and_verify_snapshot(TempDir::new().unwrap().path())
+        create_and_verify_snapshot(TempDir::new().unwrap().path(), ArchiveFormat::TarBzip2)
+    }
+
+    #[test]
+    fn test_package_chunked_snapshots() {
+        create_and_verify_snapshot(
+            TempDir::new().unwrap().path(),
+            ArchiveFormat::TarZstdChunked,
+        )
     }
 
-    fn create_and_verify_snapshot(temp_dir: &Path) {
+    fn create_and_verify_snapshot(temp_dir: &Path, archive_format: ArchiveFormat) {
         let accounts_dir = temp_dir.join("accounts");
         let snapshots_dir = temp_dir.join("snapshots");
         let full_snapshot_archives_dir = temp_dir.join("full_snapshot_archives");
@@ -311,7 +410,6 @@ mod tests {
         // Create a packageable snapshot
         let slot = 42;
         let hash = SnapshotHash(Hash::default());
-        let archive_format = ArchiveFormat::TarBzip2;
         let output_tar_path = snapshot_utils::build_full_snapshot_archive_path(
             &full_snapshot_archives_dir,
             slot,
@@ -175,6 +175,7 @@ pub struct ValidatorConfig {
     pub accounts_shrink_ratio: AccountShrinkThreshold,
     pub wait_to_vote_slot: Option<Slot>,
     pub ledger_column_options: LedgerColumnOptions,
+    pub ledger_archive_path: Option<PathBuf>,
     pub runtime_config: RuntimeConfig,
     pub replay_slots_concurrently: bool,
     pub banking_trace_dir_byte_limit: banking_trace::DirByteLimit,
@@ -238,6 +239,7 @@ impl Default for ValidatorConfig {
             accounts_db_config: None,
             wait_to_vote_slot: None,
             ledger_column_options: LedgerColumnOptions::default(),
+            ledger_archive_path: None,
             runtime_config: RuntimeConfig::default(),
             replay_slots_concurrently: false,
             banking_trace_dir_byte_limit: 0,
@@ -1411,6 +1413,7 @@ fn load_blockstore(
             recovery_mode: config.wal_recovery_mode.clone(),
             column_options: config.ledger_column_options.clone(),
             enforce_ulimit_nofile: config.enforce_ulimit_nofile,
+            archive_path: config.ledger_archive_path.clone(),
             ..BlockstoreOptions::default()
         },
     )
@@ -1450,6 +1453,7 @@ fn load_blockstore(
                 exit,
                 enable_rpc_transaction_history,
                 config.rpc_config.enable_extended_tx_metadata_storage,
+                config.rpc_config.enable_program_instruction_index,
                 transaction_notifier,
             )
         } else {
@@ -1799,6 +1803,7 @@ fn initialize_rpc_transaction_history_services(
     exit: &Arc<AtomicBool>,
     enable_rpc_transaction_history: bool,
     enable_extended_tx_metadata_storage: bool,
+    enable_program_instruction_index: bool,
     transaction_notifier: Option<TransactionNotifierLock>,
 ) -> TransactionHistoryServices {
     let max_complete_transaction_status_slot = Arc::new(AtomicU64::new(blockstore.max_root()));
@@ -1813,6 +1818,7 @@ fn initialize_rpc_transaction_history_services(
         transaction_notifier,
         blockstore.clone(),
         enable_extended_tx_metadata_storage,
+        enable_program_instruction_index,
         exit,
     ));
 
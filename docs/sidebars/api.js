@@ -193,6 +193,11 @@ module.exports = {
           href: "#getsignaturesforaddress",
           label: "getSignaturesForAddress",
         },
+        {
+          type: "link",
+          href: "#getsignaturesforprograminstruction",
+          label: "getSignaturesForProgramInstruction",
+        },
         {
           type: "link",
           href: "#getsignaturestatuses",
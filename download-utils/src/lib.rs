@@ -4,13 +4,16 @@ use {
     indicatif::{ProgressBar, ProgressStyle},
     log::*,
     solana_runtime::{
+        snapshot_archive_store::{
+            fetch_snapshot_archive, load_snapshot_archive_manifest, SnapshotArchiveStore,
+        },
         snapshot_hash::SnapshotHash,
         snapshot_package::SnapshotType,
         snapshot_utils::{self, ArchiveFormat},
     },
     solana_sdk::{clock::Slot, genesis_config::DEFAULT_GENESIS_ARCHIVE},
     std::{
-        fs::{self, File},
+        fs::{self, OpenOptions},
         io::{self, Read},
         net::SocketAddr,
         path::{Path, PathBuf},
@@ -18,6 +21,8 @@ use {
     },
 };
 
+pub mod s3_snapshot_archive_store;
+
 static TRUCK: Emoji = Emoji("🚚 ", "");
 static SPARKLE: Emoji = Emoji("✨ ", "");
 
@@ -76,29 +81,51 @@ pub fn download_file<'a, 'b>(
     fs::create_dir_all(destination_file.parent().expect("parent"))
         .map_err(|err| err.to_string())?;
 
-    let mut temp_destination_file = destination_file.to_path_buf();
-    temp_destination_file.set_file_name(format!(
-        "tmp-{}",
-        destination_file
-            .file_name()
-            .expect("file_name")
-            .to_str()
-            .expect("to_str")
-    ));
+    let file_name = destination_file
+        .file_name()
+        .expect("file_name")
+        .to_str()
+        .expect("to_str");
+    let temp_destination_file = destination_file.with_file_name(format!("tmp-{file_name}"));
+    // Records the url a partial download was started from. It is only resumed from the same url,
+    // as archives of the same snapshot created by different nodes are not identical
+    let temp_source_file = destination_file.with_file_name(format!("tmp-{file_name}.url"));
+    let partial_download_size =
+        if fs::read_to_string(&temp_source_file).ok().as_deref() == Some(url) {
+            fs::metadata(&temp_destination_file)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0)
+        } else {
+            0
+        };
 
     let progress_bar = new_spinner_progress_bar();
     if use_progress_bar {
         progress_bar.set_message(format!("{TRUCK}Downloading {url}..."));
     }
 
-    let response = reqwest::blocking::Client::new()
-        .get(url)
+    let mut request = reqwest::blocking::Client::new().get(url);
+    if partial_download_size > 0 {
+        request = request.header(
+            reqwest::header::RANGE,
+            format!("bytes={partial_download_size}-"),
+        );
+    }
+    let response = request
         .send()
         .and_then(|response| response.error_for_status())
         .map_err(|err| {
             progress_bar.finish_and_clear();
             err.to_string()
         })?;
+    // Servers that do not support ranges send the whole file
+    let resume = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    if resume {
+        info!("Resuming download of {url} from byte {partial_download_size}");
+    } else {
+        fs::write(&temp_source_file, url)
+            .map_err(|err| format!("Unable to write {temp_source_file:?}: {err:?}"))?;
+    }
 
     let download_size = {
         response
@@ -210,7 +237,14 @@ pub fn download_file<'a, 'b>(
         notification_count: 0,
     };
 
-    File::create(&temp_destination_file)
+    let mut open_options = OpenOptions::new();
+    if resume {
+        open_options.append(true);
+    } else {
+        open_options.create(true).write(true).truncate(true);
+    }
+    open_options
+        .open(&temp_destination_file)
         .and_then(|mut file| std::io::copy(&mut source, &mut file))
         .map_err(|err| format!("Unable to write {temp_destination_file:?}: {err:?}"))?;
 
@@ -228,6 +262,7 @@ pub fn download_file<'a, 'b>(
 
     std::fs::rename(temp_destination_file, destination_file)
         .map_err(|err| format!("Unable to rename: {err:?}"))?;
+    let _ = fs::remove_file(temp_source_file);
 
     Ok(())
 }
@@ -283,6 +318,7 @@ pub fn download_snapshot_archive(
     fs::create_dir_all(&snapshot_archives_remote_dir).unwrap();
 
     for archive_format in [
+        ArchiveFormat::TarZstdChunked,
         ArchiveFormat::TarZstd,
         ArchiveFormat::TarGzip,
         ArchiveFormat::TarBzip2,
@@ -330,3 +366,59 @@ pub fn download_snapshot_archive(
         desired_snapshot_hash.0, rpc_addr
     ))
 }
+
+/// Download a snapshot archive from a snapshot archive store, verifying it against the store's
+/// manifest.  The archive must have been published with the desired snapshot hash, which the
+/// caller is responsible for trusting.
+pub fn download_snapshot_archive_from_store(
+    archive_store: &dyn SnapshotArchiveStore,
+    full_snapshot_archives_dir: &Path,
+    incremental_snapshot_archives_dir: &Path,
+    desired_snapshot_hash: (Slot, SnapshotHash),
+    snapshot_type: SnapshotType,
+    maximum_full_snapshot_archives_to_retain: usize,
+    maximum_incremental_snapshot_archives_to_retain: usize,
+) -> Result<PathBuf, String> {
+    let manifest = load_snapshot_archive_manifest(archive_store)
+        .map_err(|err| format!("Failed to load the snapshot archive manifest: {err}"))?;
+    let stored_archive = manifest
+        .find(snapshot_type, desired_snapshot_hash.0, &desired_snapshot_hash.1 .0)
+        .ok_or_else(|| {
+            format!(
+                "The snapshot archive store does not have a snapshot archive for slot {} with hash {}",
+                desired_snapshot_hash.0, desired_snapshot_hash.1 .0
+            )
+        })?;
+
+    snapshot_utils::purge_old_snapshot_archives(
+        full_snapshot_archives_dir,
+        incremental_snapshot_archives_dir,
+        maximum_full_snapshot_archives_to_retain,
+        maximum_incremental_snapshot_archives_to_retain,
+    );
+    let snapshot_archives_remote_dir =
+        snapshot_utils::build_snapshot_archives_remote_dir(match snapshot_type {
+            SnapshotType::FullSnapshot => full_snapshot_archives_dir,
+            SnapshotType::IncrementalSnapshot(_) => incremental_snapshot_archives_dir,
+        });
+
+    info!(
+        "Downloading {} from the snapshot archive store",
+        stored_archive.file_name
+    );
+    let download_start = Instant::now();
+    let path = fetch_snapshot_archive(archive_store, stored_archive, &snapshot_archives_remote_dir)
+        .map_err(|err| {
+            format!(
+                "Failed to download {} from the snapshot archive store: {err}",
+                stored_archive.file_name
+            )
+        })?;
+    info!(
+        "Downloaded {} ({} bytes) in {:?}",
+        stored_archive.file_name,
+        stored_archive.size,
+        download_start.elapsed()
+    );
+    Ok(path)
+}
@@ -14,7 +14,7 @@ use {
     solana_entry::poh::compute_hashes_per_tick,
     solana_genesis::{genesis_accounts::add_genesis_accounts, Base64Account},
     solana_ledger::{blockstore::create_new_ledger, blockstore_options::LedgerColumnOptions},
-    solana_runtime::hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
+    solana_runtime::{account_set::AccountSet, hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
     solana_sdk::{
         account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
         clock,
@@ -347,6 +347,15 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .multiple(true)
                 .help("The location of pubkey for primordial accounts and balance"),
         )
+        .arg(
+            Arg::with_name("account_set")
+                .long("account-set")
+                .value_name("FILENAME")
+                .takes_value(true)
+                .multiple(true)
+                .help("Add all the accounts of an account set file \
+                       (see `solana-ledger-tool export-account-set`)"),
+        )
         .arg(
             Arg::with_name("cluster_type")
                 .long("cluster-type")
@@ -574,6 +583,14 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         }
     }
 
+    if let Some(files) = matches.values_of("account_set") {
+        for file in files {
+            AccountSet::read_from_file(file)
+                .map_err(|err| format!("Unable to load account set {file}: {err}"))?
+                .add_to_genesis(&mut genesis_config);
+        }
+    }
+
     let max_genesis_archive_unpacked_size =
         value_t_or_exit!(matches, "max_genesis_archive_unpacked_size", u64);
 
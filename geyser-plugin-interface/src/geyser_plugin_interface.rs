@@ -151,6 +151,21 @@ pub enum ReplicaBlockInfoVersions<'a> {
     V0_0_2(&'a ReplicaBlockInfoV2<'a>),
 }
 
+/// The accounts written in a slot, notified once the slot is rooted
+#[derive(Clone, Debug)]
+pub struct ReplicaRootedAccountDelta<'a> {
+    pub slot: u64,
+    /// The previous rooted slot
+    pub parent: Option<u64>,
+    /// The last write of each account in the slot, ordered by write_version
+    pub accounts: &'a [ReplicaAccountInfoV2<'a>],
+}
+
+/// A wrapper to future-proof ReplicaRootedAccountDelta handling.
+pub enum ReplicaRootedAccountDeltaVersions<'a> {
+    V0_0_1(&'a ReplicaRootedAccountDelta<'a>),
+}
+
 /// Errors returned by plugin calls
 #[derive(Error, Debug)]
 pub enum GeyserPluginError {
@@ -274,6 +289,16 @@ pub trait GeyserPlugin: Any + Send + Sync + std::fmt::Debug {
         Ok(())
     }
 
+    /// Called when a slot is rooted, with the accounts written in the slot.
+    /// Slots are notified in order, and slots on abandoned forks are never notified.
+    #[allow(unused_variables)]
+    fn notify_rooted_account_delta(
+        &mut self,
+        delta: ReplicaRootedAccountDeltaVersions,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     /// Check if the plugin is interested in account data
     /// Default is true -- if the plugin is not interested in
     /// account data, please return false.
@@ -287,4 +312,11 @@ pub trait GeyserPlugin: Any + Send + Sync + std::fmt::Debug {
     fn transaction_notifications_enabled(&self) -> bool {
         false
     }
+
+    /// Check if the plugin is interested in the account writes of rooted slots
+    /// Default is false -- if the plugin is interested in
+    /// rooted account deltas, please return true.
+    fn rooted_account_delta_notifications_enabled(&self) -> bool {
+        false
+    }
 }
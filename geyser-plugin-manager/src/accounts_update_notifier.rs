@@ -3,13 +3,14 @@ use {
     crate::geyser_plugin_manager::GeyserPluginManager,
     log::*,
     solana_geyser_plugin_interface::geyser_plugin_interface::{
-        ReplicaAccountInfoV2, ReplicaAccountInfoVersions,
+        ReplicaAccountInfoV2, ReplicaAccountInfoVersions, ReplicaRootedAccountDelta,
+        ReplicaRootedAccountDeltaVersions,
     },
     solana_measure::measure::Measure,
     solana_metrics::*,
     solana_runtime::{
         accounts_update_notifier_interface::AccountsUpdateNotifierInterface,
-        append_vec::StoredAccountMeta,
+        append_vec::StoredAccountMeta, rooted_account_deltas::RootedAccountDelta,
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount},
@@ -22,6 +23,7 @@ use {
 #[derive(Debug)]
 pub(crate) struct AccountsUpdateNotifierImpl {
     plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+    account_data_notifications_enabled: bool,
 }
 
 impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
@@ -97,11 +99,88 @@ impl AccountsUpdateNotifierInterface for AccountsUpdateNotifierImpl {
             );
         }
     }
+
+    fn notify_rooted_account_delta(&self, delta: &RootedAccountDelta) {
+        let txn_signatures: Vec<_> = delta
+            .accounts
+            .iter()
+            .map(|account| account.txn_signature.as_ref())
+            .collect();
+        let accounts: Vec<_> = delta
+            .accounts
+            .iter()
+            .zip(txn_signatures.iter())
+            .filter_map(|(account, txn_signature)| {
+                self.accountinfo_from_shared_account_data(
+                    &account.account,
+                    txn_signature,
+                    &account.pubkey,
+                    account.write_version,
+                )
+            })
+            .collect();
+        let delta = ReplicaRootedAccountDelta {
+            slot: delta.slot,
+            parent: delta.parent,
+            accounts: &accounts,
+        };
+
+        let mut plugin_manager = self.plugin_manager.write().unwrap();
+        for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.rooted_account_delta_notifications_enabled() {
+                continue;
+            }
+            let mut measure = Measure::start("geyser-plugin-notify-rooted-account-delta");
+            match plugin
+                .notify_rooted_account_delta(ReplicaRootedAccountDeltaVersions::V0_0_1(&delta))
+            {
+                Err(err) => {
+                    error!(
+                        "Failed to notify rooted account delta of slot {}, error: {} to plugin {}",
+                        delta.slot,
+                        err,
+                        plugin.name()
+                    )
+                }
+                Ok(_) => {
+                    trace!(
+                        "Successfully notified rooted account delta of slot {} to plugin {}",
+                        delta.slot,
+                        plugin.name()
+                    );
+                }
+            }
+            measure.stop();
+            inc_new_counter_debug!(
+                "geyser-plugin-notify-rooted-account-delta-us",
+                measure.as_us() as usize,
+                100000,
+                100000
+            );
+        }
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        self.account_data_notifications_enabled
+    }
+
+    fn rooted_account_delta_notifications_enabled(&self) -> bool {
+        self.plugin_manager
+            .read()
+            .unwrap()
+            .rooted_account_delta_notifications_enabled()
+    }
 }
 
 impl AccountsUpdateNotifierImpl {
-    pub fn new(plugin_manager: Arc<RwLock<GeyserPluginManager>>) -> Self {
-        AccountsUpdateNotifierImpl { plugin_manager }
+    pub fn new(
+        plugin_manager: Arc<RwLock<GeyserPluginManager>>,
+        account_data_notifications_enabled: bool,
+    ) -> Self {
+        AccountsUpdateNotifierImpl {
+            plugin_manager,
+            account_data_notifications_enabled,
+        }
     }
 
     fn accountinfo_from_shared_account_data<'a>(
@@ -152,6 +231,9 @@ impl AccountsUpdateNotifierImpl {
             return;
         }
         for plugin in plugin_manager.plugins.iter_mut() {
+            if !plugin.account_data_notifications_enabled() {
+                continue;
+            }
             let mut measure = Measure::start("geyser-plugin-update-account");
             match plugin.update_account(
                 ReplicaAccountInfoVersions::V0_0_2(&account),
@@ -72,4 +72,14 @@ impl GeyserPluginManager {
         }
         false
     }
+
+    /// Check if there is any plugin interested in the account writes of rooted slots
+    pub fn rooted_account_delta_notifications_enabled(&self) -> bool {
+        for plugin in &self.plugins {
+            if plugin.rooted_account_delta_notifications_enabled() {
+                return true;
+            }
+        }
+        false
+    }
 }
@@ -83,13 +83,19 @@ impl GeyserPluginService {
         let account_data_notifications_enabled =
             plugin_manager.account_data_notifications_enabled();
         let transaction_notifications_enabled = plugin_manager.transaction_notifications_enabled();
+        let rooted_account_delta_notifications_enabled =
+            plugin_manager.rooted_account_delta_notifications_enabled();
 
         let plugin_manager = Arc::new(RwLock::new(plugin_manager));
 
+        // A plugin may only want rooted account deltas: account data is then neither scanned at
+        // snapshot restore nor notified at every write
         let accounts_update_notifier: Option<AccountsUpdateNotifier> =
-            if account_data_notifications_enabled {
-                let accounts_update_notifier =
-                    AccountsUpdateNotifierImpl::new(plugin_manager.clone());
+            if account_data_notifications_enabled || rooted_account_delta_notifications_enabled {
+                let accounts_update_notifier = AccountsUpdateNotifierImpl::new(
+                    plugin_manager.clone(),
+                    account_data_notifications_enabled,
+                );
                 Some(Arc::new(RwLock::new(accounts_update_notifier)))
             } else {
                 None
@@ -0,0 +1,546 @@
+//! The `bank-hash-diff` subcommand's data
+//!
+//! [`BankHashDetails`] holds what a replayed slot's bank hash is computed from: the parent bank
+//! hash, the signature count, the last blockhash, and every account stored in the slot with the
+//! hash it contributes to the accounts delta hash. It can be dumped to a JSON file so that replays
+//! by different binaries, or with different feature sets, can be compared with
+//! [`BankHashDiff::new()`].
+use {
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_cli_output::{QuietDisplay, VerboseDisplay},
+    solana_ledger::blockstore::Blockstore,
+    solana_runtime::{accounts_hash::AccountsHasher, bank::Bank},
+    solana_sdk::{
+        account::ReadableAccount,
+        clock::Slot,
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::{MessageHash, SanitizedTransaction},
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        fmt::{self, Display, Formatter},
+        fs::File,
+        io::{BufReader, BufWriter},
+        ops::Range,
+        path::Path,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDetails {
+    pub pubkey: String,
+    pub hash: String,
+    pub lamports: u64,
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    /// Base64 encoded account data
+    pub data: String,
+    /// Signatures of the slot's transactions that wrote the account
+    pub writers: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankHashDetails {
+    pub slot: Slot,
+    pub bank_hash: String,
+    pub parent_bank_hash: String,
+    pub accounts_delta_hash: String,
+    pub signature_count: u64,
+    pub last_blockhash: String,
+    /// Sorted by pubkey
+    pub accounts: Vec<AccountDetails>,
+}
+
+impl BankHashDetails {
+    /// Collects the details of the frozen `bank`, along with the transactions of its slot in
+    /// `blockstore` that wrote each account.
+    pub fn new(bank: &Bank, blockstore: &Blockstore) -> Self {
+        let slot = bank.slot();
+        let writers = transaction_writers(bank, blockstore);
+        let pubkey_hash_accounts = bank
+            .rc
+            .accounts
+            .accounts_db
+            .get_pubkey_hash_account_for_slot(slot);
+        let accounts_delta_hash = AccountsHasher::accumulate_account_hashes(
+            pubkey_hash_accounts
+                .iter()
+                .map(|item| (item.pubkey, item.hash))
+                .collect(),
+        );
+        let accounts = pubkey_hash_accounts
+            .into_iter()
+            .map(|item| AccountDetails {
+                pubkey: item.pubkey.to_string(),
+                hash: item.hash.to_string(),
+                lamports: item.account.lamports(),
+                owner: item.account.owner().to_string(),
+                executable: item.account.executable(),
+                rent_epoch: item.account.rent_epoch(),
+                data: base64::encode(item.account.data()),
+                writers: writers
+                    .get(&item.pubkey)
+                    .map(|signatures| signatures.iter().map(ToString::to_string).collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Self {
+            slot,
+            bank_hash: bank.hash().to_string(),
+            parent_bank_hash: bank.parent_hash().to_string(),
+            accounts_delta_hash: accounts_delta_hash.to_string(),
+            signature_count: bank.signature_count(),
+            last_blockhash: bank.last_blockhash().to_string(),
+            accounts,
+        }
+    }
+
+    pub fn read_from_file(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| format!("Unable to open {path:?}: {err}"))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|err| format!("Unable to read bank hash details from {path:?}: {err}"))
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), String> {
+        let file = File::create(path).map_err(|err| format!("Unable to create {path:?}: {err}"))?;
+        serde_json::to_writer(BufWriter::new(file), self)
+            .map_err(|err| format!("Unable to write bank hash details to {path:?}: {err}"))
+    }
+}
+
+/// Returns the signatures of the transactions of `bank`'s slot that wrote each account: all
+/// the writable accounts of the successful transactions, and the fee payer of the failed ones.
+fn transaction_writers(bank: &Bank, blockstore: &Blockstore) -> HashMap<Pubkey, Vec<Signature>> {
+    let mut writers: HashMap<Pubkey, Vec<Signature>> = HashMap::new();
+    let entries = blockstore
+        .get_slot_entries(bank.slot(), 0)
+        .unwrap_or_else(|err| {
+            warn!("Unable to read the entries of slot {}: {err}", bank.slot());
+            vec![]
+        });
+    for transaction in entries.into_iter().flat_map(|entry| entry.transactions) {
+        let transaction =
+            match SanitizedTransaction::try_create(transaction, MessageHash::Compute, None, bank) {
+                Ok(transaction) => transaction,
+                Err(err) => {
+                    warn!(
+                        "Unable to sanitize a transaction of slot {}: {err}",
+                        bank.slot()
+                    );
+                    continue;
+                }
+            };
+        let signature = *transaction.signature();
+        let succeeded = match bank.get_signature_status_slot(&signature) {
+            Some((slot, status)) if slot == bank.slot() => status.is_ok(),
+            // not executed, such as a duplicate
+            _ => continue,
+        };
+        let message = transaction.message();
+        for (index, pubkey) in message.account_keys().iter().enumerate() {
+            if message.is_writable(index) && (succeeded || index == 0) {
+                writers.entry(*pubkey).or_default().push(signature);
+            }
+        }
+    }
+    writers
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "field")]
+pub enum AccountFieldDiff {
+    Lamports {
+        left: u64,
+        right: u64,
+    },
+    Owner {
+        left: String,
+        right: String,
+    },
+    Executable {
+        left: bool,
+        right: bool,
+    },
+    RentEpoch {
+        left: u64,
+        right: u64,
+    },
+    /// The byte ranges that differ, including the bytes past the end of the shorter data
+    Data {
+        left_len: usize,
+        right_len: usize,
+        ranges: Vec<Range<usize>>,
+    },
+}
+
+impl Display for AccountFieldDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Lamports { left, right } => write!(f, "lamports: {left} != {right}"),
+            Self::Owner { left, right } => write!(f, "owner: {left} != {right}"),
+            Self::Executable { left, right } => write!(f, "executable: {left} != {right}"),
+            Self::RentEpoch { left, right } => write!(f, "rent_epoch: {left} != {right}"),
+            Self::Data {
+                left_len,
+                right_len,
+                ranges,
+            } => {
+                write!(f, "data")?;
+                if left_len != right_len {
+                    write!(f, " length: {left_len} != {right_len},")?;
+                }
+                write!(f, " bytes:")?;
+                for range in ranges {
+                    write!(f, " [{}..{})", range.start, range.end)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountDiff {
+    pub pubkey: String,
+    /// `None` if the account was not stored in the left slot
+    pub left_hash: Option<String>,
+    /// `None` if the account was not stored in the right slot
+    pub right_hash: Option<String>,
+    /// Empty if the account is only stored in one slot, or if only its hash differs
+    pub fields: Vec<AccountFieldDiff>,
+    pub left_writers: Vec<String>,
+    pub right_writers: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashInputDiff {
+    pub name: &'static str,
+    pub left: String,
+    pub right: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankHashDiff {
+    pub left_label: String,
+    pub right_label: String,
+    pub left_slot: Slot,
+    pub right_slot: Slot,
+    /// The bank hash inputs that differ, other than the accounts
+    pub hash_inputs: Vec<HashInputDiff>,
+    /// The accounts whose hash differs, sorted by pubkey
+    pub accounts: Vec<AccountDiff>,
+}
+
+impl BankHashDiff {
+    pub fn new(
+        left_label: String,
+        left: &BankHashDetails,
+        right_label: String,
+        right: &BankHashDetails,
+    ) -> Self {
+        let mut hash_inputs = vec![];
+        let mut diff_hash_input = |name, left: &dyn ToString, right: &dyn ToString| {
+            let (left, right) = (left.to_string(), right.to_string());
+            if left != right {
+                hash_inputs.push(HashInputDiff { name, left, right });
+            }
+        };
+        diff_hash_input("bank_hash", &left.bank_hash, &right.bank_hash);
+        diff_hash_input(
+            "parent_bank_hash",
+            &left.parent_bank_hash,
+            &right.parent_bank_hash,
+        );
+        diff_hash_input(
+            "accounts_delta_hash",
+            &left.accounts_delta_hash,
+            &right.accounts_delta_hash,
+        );
+        diff_hash_input(
+            "signature_count",
+            &left.signature_count,
+            &right.signature_count,
+        );
+        diff_hash_input(
+            "last_blockhash",
+            &left.last_blockhash,
+            &right.last_blockhash,
+        );
+
+        let mut accounts: BTreeMap<&str, (Option<&AccountDetails>, Option<&AccountDetails>)> =
+            BTreeMap::new();
+        for account in &left.accounts {
+            accounts.entry(account.pubkey.as_str()).or_default().0 = Some(account);
+        }
+        for account in &right.accounts {
+            accounts.entry(account.pubkey.as_str()).or_default().1 = Some(account);
+        }
+        let accounts = accounts
+            .into_iter()
+            .filter_map(|(pubkey, accounts)| {
+                let (left_hash, right_hash, fields) = match accounts {
+                    (Some(left), Some(right)) if left.hash == right.hash => return None,
+                    (Some(left), Some(right)) => (
+                        Some(left.hash.clone()),
+                        Some(right.hash.clone()),
+                        diff_account_fields(left, right),
+                    ),
+                    (left, right) => (
+                        left.map(|account| account.hash.clone()),
+                        right.map(|account| account.hash.clone()),
+                        vec![],
+                    ),
+                };
+                let writers = |account: Option<&AccountDetails>| {
+                    account
+                        .map(|account| account.writers.clone())
+                        .unwrap_or_default()
+                };
+                Some(AccountDiff {
+                    pubkey: pubkey.to_string(),
+                    left_hash,
+                    right_hash,
+                    fields,
+                    left_writers: writers(accounts.0),
+                    right_writers: writers(accounts.1),
+                })
+            })
+            .collect();
+
+        Self {
+            left_label,
+            right_label,
+            left_slot: left.slot,
+            right_slot: right.slot,
+            hash_inputs,
+            accounts,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hash_inputs.is_empty() && self.accounts.is_empty()
+    }
+}
+
+fn diff_account_fields(left: &AccountDetails, right: &AccountDetails) -> Vec<AccountFieldDiff> {
+    let mut fields = vec![];
+    if left.lamports != right.lamports {
+        fields.push(AccountFieldDiff::Lamports {
+            left: left.lamports,
+            right: right.lamports,
+        });
+    }
+    if left.owner != right.owner {
+        fields.push(AccountFieldDiff::Owner {
+            left: left.owner.clone(),
+            right: right.owner.clone(),
+        });
+    }
+    if left.executable != right.executable {
+        fields.push(AccountFieldDiff::Executable {
+            left: left.executable,
+            right: right.executable,
+        });
+    }
+    if left.rent_epoch != right.rent_epoch {
+        fields.push(AccountFieldDiff::RentEpoch {
+            left: left.rent_epoch,
+            right: right.rent_epoch,
+        });
+    }
+    if left.data != right.data {
+        let left_data = base64::decode(&left.data).unwrap_or_default();
+        let right_data = base64::decode(&right.data).unwrap_or_default();
+        fields.push(AccountFieldDiff::Data {
+            left_len: left_data.len(),
+            right_len: right_data.len(),
+            ranges: diff_byte_ranges(&left_data, &right_data),
+        });
+    }
+    fields
+}
+
+/// Returns the ranges of bytes that differ between `left` and `right`, merged so that each range
+/// is followed by at least one equal byte
+fn diff_byte_ranges(left: &[u8], right: &[u8]) -> Vec<Range<usize>> {
+    let len = left.len().max(right.len());
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for i in 0..len {
+        if left.get(i) == right.get(i) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end == i => range.end = i + 1,
+            _ => ranges.push(i..i + 1),
+        }
+    }
+    ranges
+}
+
+impl VerboseDisplay for BankHashDiff {}
+impl QuietDisplay for BankHashDiff {}
+
+impl Display for BankHashDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (left, right) = (&self.left_label, &self.right_label);
+        writeln!(
+            f,
+            "Comparing slot {} of {left} (left) with slot {} of {right} (right)",
+            self.left_slot, self.right_slot
+        )?;
+        if self.is_empty() {
+            return writeln!(f, "Bank hashes match");
+        }
+        for input in &self.hash_inputs {
+            writeln!(f, "{}: {} != {}", input.name, input.left, input.right)?;
+        }
+        writeln!(f, "{} accounts differ", self.accounts.len())?;
+        for account in &self.accounts {
+            writeln!(f, "{}", account.pubkey)?;
+            match (&account.left_hash, &account.right_hash) {
+                (Some(left_hash), Some(right_hash)) => {
+                    writeln!(f, "  hash: {left_hash} != {right_hash}")?;
+                    if account.fields.is_empty() {
+                        writeln!(f, "  no field differs, only the account hash")?;
+                    }
+                    for field in &account.fields {
+                        writeln!(f, "  {field}")?;
+                    }
+                }
+                (Some(_), None) => writeln!(f, "  only stored by {left}")?,
+                (None, Some(_)) => writeln!(f, "  only stored by {right}")?,
+                (None, None) => {}
+            }
+            for (label, writers) in [
+                (left, &account.left_writers),
+                (right, &account.right_writers),
+            ] {
+                if !writers.is_empty() {
+                    writeln!(f, "  written by {label}: {}", writers.join(", "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(pubkey: &str, hash: &str, lamports: u64, data: &[u8]) -> AccountDetails {
+        AccountDetails {
+            pubkey: pubkey.to_string(),
+            hash: hash.to_string(),
+            lamports,
+            owner: Pubkey::default().to_string(),
+            executable: false,
+            rent_epoch: 0,
+            data: base64::encode(data),
+            writers: vec![format!("{pubkey}-writer")],
+        }
+    }
+
+    fn details(accounts: Vec<AccountDetails>) -> BankHashDetails {
+        BankHashDetails {
+            slot: 42,
+            bank_hash: "bank".to_string(),
+            parent_bank_hash: "parent".to_string(),
+            accounts_delta_hash: "delta".to_string(),
+            signature_count: 3,
+            last_blockhash: "blockhash".to_string(),
+            accounts,
+        }
+    }
+
+    #[test]
+    fn test_diff_byte_ranges() {
+        assert_eq!(diff_byte_ranges(&[1, 2, 3], &[1, 2, 3]), vec![]);
+        assert_eq!(
+            diff_byte_ranges(&[0, 1, 2, 3, 4, 5], &[0, 9, 9, 3, 9, 5]),
+            vec![1..3, 4..5]
+        );
+        assert_eq!(diff_byte_ranges(&[0, 1], &[0, 1, 2, 3]), vec![2..4]);
+        assert_eq!(diff_byte_ranges(&[7, 1, 2], &[0]), vec![0..3]);
+    }
+
+    #[test]
+    fn test_bank_hash_diff() {
+        let left = details(vec![
+            account("a", "same", 1, &[1]),
+            account("b", "b-left", 2, &[0, 1, 2, 3]),
+            account("c", "c-left", 3, &[]),
+            account("d", "d-left", 4, &[]),
+        ]);
+        let mut right = details(vec![
+            account("a", "same", 1, &[1]),
+            account("b", "b-right", 5, &[0, 1, 9, 3, 4]),
+            account("d", "d-right", 4, &[]),
+            account("e", "e-right", 5, &[]),
+        ]);
+        assert!(BankHashDiff::new("left".into(), &left, "left".into(), &left).is_empty());
+
+        right.bank_hash = "other bank".to_string();
+        let diff = BankHashDiff::new("left".into(), &left, "right".into(), &right);
+        assert_eq!(
+            diff.hash_inputs,
+            vec![HashInputDiff {
+                name: "bank_hash",
+                left: "bank".to_string(),
+                right: "other bank".to_string(),
+            }]
+        );
+        let pubkeys: Vec<_> = diff.accounts.iter().map(|a| a.pubkey.as_str()).collect();
+        assert_eq!(pubkeys, vec!["b", "c", "d", "e"]);
+
+        let b = &diff.accounts[0];
+        assert_eq!(b.left_hash.as_deref(), Some("b-left"));
+        assert_eq!(b.right_hash.as_deref(), Some("b-right"));
+        assert_eq!(
+            b.fields,
+            vec![
+                AccountFieldDiff::Lamports { left: 2, right: 5 },
+                AccountFieldDiff::Data {
+                    left_len: 4,
+                    right_len: 5,
+                    ranges: vec![2..3, 4..5],
+                },
+            ]
+        );
+        assert_eq!(b.left_writers, vec!["b-writer".to_string()]);
+
+        let c = &diff.accounts[1];
+        assert_eq!(c.right_hash, None);
+        assert!(c.fields.is_empty());
+        assert!(c.right_writers.is_empty());
+
+        // only the hash differs
+        assert!(diff.accounts[2].fields.is_empty());
+        assert_eq!(diff.accounts[3].left_hash, None);
+
+        let report = diff.to_string();
+        assert!(report.contains("4 accounts differ"));
+        assert!(report.contains("data length: 4 != 5, bytes: [2..3) [4..5)"));
+        assert!(report.contains("only stored by left"));
+        assert!(report.contains("written by right: e-writer"));
+    }
+
+    #[test]
+    fn test_bank_hash_details_file() {
+        let path = tempfile::NamedTempFile::new().unwrap();
+        let details = details(vec![account("a", "hash", 1, &[1, 2, 3])]);
+        details.write_to_file(path.path()).unwrap();
+        assert_eq!(
+            BankHashDetails::read_from_file(path.path()).unwrap(),
+            details
+        );
+    }
+}
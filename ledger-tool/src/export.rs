@@ -0,0 +1,1227 @@
+//! The `export` subcommand, which writes the rooted blocks of a ledger to Parquet tables
+//!
+//! Every table is split into partitions of `--partition-slots` consecutive slots, each written to
+//! `<output-dir>/<table>/partition_slot=<first slot of the partition>/data.parquet`. A partition
+//! is recorded in `<output-dir>/export_manifest.json` once all of its rooted slots have been
+//! exported, and is skipped by later exports to the same directory, so that an interrupted
+//! export resumes from the first partition that was not complete. A partition with blocks that
+//! failed to be read is not recorded as complete, and the slots of these blocks are listed in the
+//! manifest until an export of the partition reads them all.
+use {
+    arrow_array::{
+        builder::{
+            BinaryBuilder, BooleanBuilder, Int64Builder, ListBuilder, StringBuilder, UInt32Builder,
+            UInt64Builder, UInt8Builder,
+        },
+        ArrayRef, RecordBatch,
+    },
+    arrow_schema::{DataType, Field, Schema, SchemaRef},
+    clap::{value_t, value_t_or_exit, App, Arg, ArgMatches, SubCommand},
+    log::*,
+    parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties},
+    serde::{Deserialize, Serialize},
+    solana_clap_utils::input_validators::{is_parsable, is_slot},
+    solana_ledger::{blockstore::Blockstore, rooted_slot_iterator::RootedSlotIterator},
+    solana_sdk::{
+        clock::Slot,
+        instruction::CompiledInstruction,
+        message::{v0::LoadedMessage, VersionedMessage},
+    },
+    solana_transaction_status::{
+        parse_accounts::{
+            parse_legacy_message_accounts, parse_v0_message_accounts, ParsedAccountSource,
+        },
+        Reward, TransactionTokenBalance, VersionedConfirmedBlock,
+        VersionedTransactionWithStatusMeta,
+    },
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        error::Error,
+        fs::{self, File},
+        path::{Path, PathBuf},
+        process::exit,
+        sync::Arc,
+    },
+};
+
+const MANIFEST_FILE: &str = "export_manifest.json";
+const DEFAULT_PARTITION_SLOTS: &str = "100000";
+
+/// Rows buffered per table before they are written out as a record batch
+const MAX_BATCH_ROWS: usize = 64 * 1024;
+
+pub struct ExportConfig {
+    pub output_dir: PathBuf,
+    pub starting_slot: Slot,
+    pub ending_slot: Slot,
+    pub partition_slots: Slot,
+}
+
+/// The outcome of `export_ledger`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub num_blocks: u64,
+    /// rooted slots whose blocks failed to be read, leaving their partitions incomplete
+    pub skipped_slots: Vec<Slot>,
+}
+
+/// Partitions that have been completely exported to an output directory
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportManifest {
+    partition_slots: Slot,
+    complete_partitions: BTreeSet<Slot>,
+    /// slots whose blocks failed to be read in the last export of their partition, by partition
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    skipped_slots: BTreeMap<Slot, Vec<Slot>>,
+}
+
+impl ExportManifest {
+    fn load(output_dir: &Path, partition_slots: Slot) -> Result<Self, Box<dyn Error>> {
+        let path = output_dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(Self {
+                partition_slots,
+                ..Self::default()
+            });
+        }
+        let manifest: Self = serde_json::from_reader(File::open(&path)?)?;
+        if manifest.partition_slots != partition_slots {
+            return Err(format!(
+                "{} was exported with --partition-slots {}",
+                output_dir.display(),
+                manifest.partition_slots
+            )
+            .into());
+        }
+        Ok(manifest)
+    }
+
+    fn save(&self, output_dir: &Path) -> Result<(), Box<dyn Error>> {
+        let path = output_dir.join(MANIFEST_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        serde_json::to_writer_pretty(File::create(&tmp_path)?, self)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// The rows of a table, accumulated in Arrow builders until they are written out
+trait TableRows {
+    const NAME: &'static str;
+
+    fn new() -> Self;
+    fn schema() -> Schema;
+    fn num_rows(&self) -> usize;
+    /// Take the buffered rows as columns, in the order of `schema()`
+    fn finish(&mut self) -> Vec<ArrayRef>;
+}
+
+fn string_list_type() -> DataType {
+    DataType::List(Box::new(Field::new("item", DataType::Utf8, true)))
+}
+
+fn u32_list_type() -> DataType {
+    DataType::List(Box::new(Field::new("item", DataType::UInt32, true)))
+}
+
+struct BlockRows {
+    num_rows: usize,
+    slot: UInt64Builder,
+    parent_slot: UInt64Builder,
+    blockhash: StringBuilder,
+    previous_blockhash: StringBuilder,
+    block_time: Int64Builder,
+    block_height: UInt64Builder,
+    transaction_count: UInt64Builder,
+}
+
+impl BlockRows {
+    fn append(&mut self, slot: Slot, block: &VersionedConfirmedBlock) {
+        self.num_rows += 1;
+        self.slot.append_value(slot);
+        self.parent_slot.append_value(block.parent_slot);
+        self.blockhash.append_value(&block.blockhash);
+        self.previous_blockhash
+            .append_value(&block.previous_blockhash);
+        self.block_time.append_option(block.block_time);
+        self.block_height.append_option(block.block_height);
+        self.transaction_count
+            .append_value(block.transactions.len() as u64);
+    }
+}
+
+impl TableRows for BlockRows {
+    const NAME: &'static str = "blocks";
+
+    fn new() -> Self {
+        Self {
+            num_rows: 0,
+            slot: UInt64Builder::new(),
+            parent_slot: UInt64Builder::new(),
+            blockhash: StringBuilder::new(),
+            previous_blockhash: StringBuilder::new(),
+            block_time: Int64Builder::new(),
+            block_height: UInt64Builder::new(),
+            transaction_count: UInt64Builder::new(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("parent_slot", DataType::UInt64, false),
+            Field::new("blockhash", DataType::Utf8, false),
+            Field::new("previous_blockhash", DataType::Utf8, false),
+            Field::new("block_time", DataType::Int64, true),
+            Field::new("block_height", DataType::UInt64, true),
+            Field::new("transaction_count", DataType::UInt64, false),
+        ])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.num_rows = 0;
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.parent_slot.finish()),
+            Arc::new(self.blockhash.finish()),
+            Arc::new(self.previous_blockhash.finish()),
+            Arc::new(self.block_time.finish()),
+            Arc::new(self.block_height.finish()),
+            Arc::new(self.transaction_count.finish()),
+        ]
+    }
+}
+
+struct TransactionRows {
+    num_rows: usize,
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    signature: StringBuilder,
+    version: StringBuilder,
+    recent_blockhash: StringBuilder,
+    fee: UInt64Builder,
+    success: BooleanBuilder,
+    err: StringBuilder,
+    compute_units_consumed: UInt64Builder,
+    log_messages: ListBuilder<StringBuilder>,
+}
+
+impl TransactionRows {
+    fn append(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        tx_with_meta: &VersionedTransactionWithStatusMeta,
+    ) {
+        let VersionedTransactionWithStatusMeta { transaction, meta } = tx_with_meta;
+        self.num_rows += 1;
+        self.slot.append_value(slot);
+        self.transaction_index.append_value(transaction_index);
+        self.signature.append_value(
+            transaction
+                .signatures
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        );
+        self.version.append_value(match transaction.message {
+            VersionedMessage::Legacy(_) => "legacy",
+            VersionedMessage::V0(_) => "0",
+        });
+        self.recent_blockhash
+            .append_value(transaction.message.recent_blockhash().to_string());
+        self.fee.append_value(meta.fee);
+        self.success.append_value(meta.status.is_ok());
+        self.err
+            .append_option(meta.status.as_ref().err().map(ToString::to_string));
+        self.compute_units_consumed
+            .append_option(meta.compute_units_consumed);
+        match &meta.log_messages {
+            Some(log_messages) => {
+                for log_message in log_messages {
+                    self.log_messages.values().append_value(log_message);
+                }
+                self.log_messages.append(true);
+            }
+            None => self.log_messages.append(false),
+        }
+    }
+}
+
+impl TableRows for TransactionRows {
+    const NAME: &'static str = "transactions";
+
+    fn new() -> Self {
+        Self {
+            num_rows: 0,
+            slot: UInt64Builder::new(),
+            transaction_index: UInt32Builder::new(),
+            signature: StringBuilder::new(),
+            version: StringBuilder::new(),
+            recent_blockhash: StringBuilder::new(),
+            fee: UInt64Builder::new(),
+            success: BooleanBuilder::new(),
+            err: StringBuilder::new(),
+            compute_units_consumed: UInt64Builder::new(),
+            log_messages: ListBuilder::new(StringBuilder::new()),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("signature", DataType::Utf8, false),
+            Field::new("version", DataType::Utf8, false),
+            Field::new("recent_blockhash", DataType::Utf8, false),
+            Field::new("fee", DataType::UInt64, false),
+            Field::new("success", DataType::Boolean, false),
+            Field::new("err", DataType::Utf8, true),
+            Field::new("compute_units_consumed", DataType::UInt64, true),
+            Field::new("log_messages", string_list_type(), true),
+        ])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.num_rows = 0;
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.signature.finish()),
+            Arc::new(self.version.finish()),
+            Arc::new(self.recent_blockhash.finish()),
+            Arc::new(self.fee.finish()),
+            Arc::new(self.success.finish()),
+            Arc::new(self.err.finish()),
+            Arc::new(self.compute_units_consumed.finish()),
+            Arc::new(self.log_messages.finish()),
+        ]
+    }
+}
+
+/// Top-level and inner instructions; `accounts` are indexes into the transaction's rows of the
+/// `account_keys` table
+struct InstructionRows {
+    num_rows: usize,
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    instruction_index: UInt32Builder,
+    inner_instruction_index: UInt32Builder,
+    stack_height: UInt32Builder,
+    program_id: StringBuilder,
+    accounts: ListBuilder<UInt32Builder>,
+    data: BinaryBuilder,
+}
+
+impl InstructionRows {
+    fn append(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        tx_with_meta: &VersionedTransactionWithStatusMeta,
+    ) {
+        let account_keys = tx_with_meta.account_keys();
+        let mut append_instruction =
+            |instruction_index: u8,
+             inner_instruction_index: Option<u32>,
+             stack_height: Option<u32>,
+             instruction: &CompiledInstruction| {
+                self.num_rows += 1;
+                self.slot.append_value(slot);
+                self.transaction_index.append_value(transaction_index);
+                self.instruction_index
+                    .append_value(u32::from(instruction_index));
+                self.inner_instruction_index
+                    .append_option(inner_instruction_index);
+                self.stack_height.append_option(stack_height);
+                self.program_id.append_option(
+                    account_keys
+                        .get(usize::from(instruction.program_id_index))
+                        .map(ToString::to_string),
+                );
+                for account_index in &instruction.accounts {
+                    self.accounts
+                        .values()
+                        .append_value(u32::from(*account_index));
+                }
+                self.accounts.append(true);
+                self.data.append_value(&instruction.data);
+            };
+
+        for (instruction_index, instruction) in tx_with_meta
+            .transaction
+            .message
+            .instructions()
+            .iter()
+            .enumerate()
+        {
+            append_instruction(instruction_index as u8, None, None, instruction);
+        }
+        for inner_instructions in tx_with_meta.meta.inner_instructions.iter().flatten() {
+            for (inner_instruction_index, inner_instruction) in
+                inner_instructions.instructions.iter().enumerate()
+            {
+                append_instruction(
+                    inner_instructions.index,
+                    Some(inner_instruction_index as u32),
+                    inner_instruction.stack_height,
+                    &inner_instruction.instruction,
+                );
+            }
+        }
+    }
+}
+
+impl TableRows for InstructionRows {
+    const NAME: &'static str = "instructions";
+
+    fn new() -> Self {
+        Self {
+            num_rows: 0,
+            slot: UInt64Builder::new(),
+            transaction_index: UInt32Builder::new(),
+            instruction_index: UInt32Builder::new(),
+            inner_instruction_index: UInt32Builder::new(),
+            stack_height: UInt32Builder::new(),
+            program_id: StringBuilder::new(),
+            accounts: ListBuilder::new(UInt32Builder::new()),
+            data: BinaryBuilder::new(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("instruction_index", DataType::UInt32, false),
+            Field::new("inner_instruction_index", DataType::UInt32, true),
+            Field::new("stack_height", DataType::UInt32, true),
+            Field::new("program_id", DataType::Utf8, true),
+            Field::new("accounts", u32_list_type(), true),
+            Field::new("data", DataType::Binary, false),
+        ])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.num_rows = 0;
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.instruction_index.finish()),
+            Arc::new(self.inner_instruction_index.finish()),
+            Arc::new(self.stack_height.finish()),
+            Arc::new(self.program_id.finish()),
+            Arc::new(self.accounts.finish()),
+            Arc::new(self.data.finish()),
+        ]
+    }
+}
+
+/// The static and address-table-loaded account keys of each transaction, with their balances
+struct AccountKeyRows {
+    num_rows: usize,
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    key_index: UInt32Builder,
+    pubkey: StringBuilder,
+    is_signer: BooleanBuilder,
+    is_writable: BooleanBuilder,
+    from_lookup_table: BooleanBuilder,
+    pre_balance: UInt64Builder,
+    post_balance: UInt64Builder,
+}
+
+impl AccountKeyRows {
+    fn append(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        tx_with_meta: &VersionedTransactionWithStatusMeta,
+    ) {
+        let VersionedTransactionWithStatusMeta { transaction, meta } = tx_with_meta;
+        let accounts = match &transaction.message {
+            VersionedMessage::Legacy(message) => parse_legacy_message_accounts(message),
+            VersionedMessage::V0(message) => parse_v0_message_accounts(
+                &LoadedMessage::new_borrowed(message, &meta.loaded_addresses),
+            ),
+        };
+        for (key_index, account) in accounts.into_iter().enumerate() {
+            self.num_rows += 1;
+            self.slot.append_value(slot);
+            self.transaction_index.append_value(transaction_index);
+            self.key_index.append_value(key_index as u32);
+            self.pubkey.append_value(account.pubkey);
+            self.is_signer.append_value(account.signer);
+            self.is_writable.append_value(account.writable);
+            self.from_lookup_table
+                .append_value(account.source == Some(ParsedAccountSource::LookupTable));
+            self.pre_balance
+                .append_option(meta.pre_balances.get(key_index).copied());
+            self.post_balance
+                .append_option(meta.post_balances.get(key_index).copied());
+        }
+    }
+}
+
+impl TableRows for AccountKeyRows {
+    const NAME: &'static str = "account_keys";
+
+    fn new() -> Self {
+        Self {
+            num_rows: 0,
+            slot: UInt64Builder::new(),
+            transaction_index: UInt32Builder::new(),
+            key_index: UInt32Builder::new(),
+            pubkey: StringBuilder::new(),
+            is_signer: BooleanBuilder::new(),
+            is_writable: BooleanBuilder::new(),
+            from_lookup_table: BooleanBuilder::new(),
+            pre_balance: UInt64Builder::new(),
+            post_balance: UInt64Builder::new(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("key_index", DataType::UInt32, false),
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("is_signer", DataType::Boolean, false),
+            Field::new("is_writable", DataType::Boolean, false),
+            Field::new("from_lookup_table", DataType::Boolean, false),
+            Field::new("pre_balance", DataType::UInt64, true),
+            Field::new("post_balance", DataType::UInt64, true),
+        ])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.num_rows = 0;
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.key_index.finish()),
+            Arc::new(self.pubkey.finish()),
+            Arc::new(self.is_signer.finish()),
+            Arc::new(self.is_writable.finish()),
+            Arc::new(self.from_lookup_table.finish()),
+            Arc::new(self.pre_balance.finish()),
+            Arc::new(self.post_balance.finish()),
+        ]
+    }
+}
+
+/// Pre- and post-transaction balances of each token account, in raw token amounts
+struct TokenBalanceChangeRows {
+    num_rows: usize,
+    slot: UInt64Builder,
+    transaction_index: UInt32Builder,
+    account_index: UInt32Builder,
+    account: StringBuilder,
+    mint: StringBuilder,
+    owner: StringBuilder,
+    program_id: StringBuilder,
+    decimals: UInt8Builder,
+    pre_amount: UInt64Builder,
+    post_amount: UInt64Builder,
+}
+
+impl TokenBalanceChangeRows {
+    fn append(
+        &mut self,
+        slot: Slot,
+        transaction_index: u32,
+        tx_with_meta: &VersionedTransactionWithStatusMeta,
+    ) {
+        let meta = &tx_with_meta.meta;
+        let mut balances: BTreeMap<u8, (Option<_>, Option<_>)> = BTreeMap::new();
+        for balance in meta.pre_token_balances.iter().flatten() {
+            balances.entry(balance.account_index).or_default().0 = Some(balance);
+        }
+        for balance in meta.post_token_balances.iter().flatten() {
+            balances.entry(balance.account_index).or_default().1 = Some(balance);
+        }
+        if balances.is_empty() {
+            return;
+        }
+
+        let account_keys = tx_with_meta.account_keys();
+        let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        let amount = |balance: Option<&TransactionTokenBalance>| -> Option<u64> {
+            balance?.ui_token_amount.amount.parse().ok()
+        };
+        for (account_index, (pre, post)) in balances {
+            // At least one of the balances is present
+            let balance = post.or(pre).unwrap();
+            self.num_rows += 1;
+            self.slot.append_value(slot);
+            self.transaction_index.append_value(transaction_index);
+            self.account_index.append_value(u32::from(account_index));
+            self.account.append_option(
+                account_keys
+                    .get(usize::from(account_index))
+                    .map(ToString::to_string),
+            );
+            self.mint.append_value(&balance.mint);
+            self.owner.append_option(non_empty(&balance.owner));
+            self.program_id
+                .append_option(non_empty(&balance.program_id));
+            self.decimals.append_value(balance.ui_token_amount.decimals);
+            self.pre_amount.append_option(amount(pre));
+            self.post_amount.append_option(amount(post));
+        }
+    }
+}
+
+impl TableRows for TokenBalanceChangeRows {
+    const NAME: &'static str = "token_balance_changes";
+
+    fn new() -> Self {
+        Self {
+            num_rows: 0,
+            slot: UInt64Builder::new(),
+            transaction_index: UInt32Builder::new(),
+            account_index: UInt32Builder::new(),
+            account: StringBuilder::new(),
+            mint: StringBuilder::new(),
+            owner: StringBuilder::new(),
+            program_id: StringBuilder::new(),
+            decimals: UInt8Builder::new(),
+            pre_amount: UInt64Builder::new(),
+            post_amount: UInt64Builder::new(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            Field::new("account_index", DataType::UInt32, false),
+            Field::new("account", DataType::Utf8, true),
+            Field::new("mint", DataType::Utf8, false),
+            Field::new("owner", DataType::Utf8, true),
+            Field::new("program_id", DataType::Utf8, true),
+            Field::new("decimals", DataType::UInt8, false),
+            Field::new("pre_amount", DataType::UInt64, true),
+            Field::new("post_amount", DataType::UInt64, true),
+        ])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.num_rows = 0;
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.transaction_index.finish()),
+            Arc::new(self.account_index.finish()),
+            Arc::new(self.account.finish()),
+            Arc::new(self.mint.finish()),
+            Arc::new(self.owner.finish()),
+            Arc::new(self.program_id.finish()),
+            Arc::new(self.decimals.finish()),
+            Arc::new(self.pre_amount.finish()),
+            Arc::new(self.post_amount.finish()),
+        ]
+    }
+}
+
+struct RewardRows {
+    num_rows: usize,
+    slot: UInt64Builder,
+    pubkey: StringBuilder,
+    lamports: Int64Builder,
+    post_balance: UInt64Builder,
+    reward_type: StringBuilder,
+    commission: UInt8Builder,
+}
+
+impl RewardRows {
+    fn append(&mut self, slot: Slot, rewards: &[Reward]) {
+        for reward in rewards {
+            self.num_rows += 1;
+            self.slot.append_value(slot);
+            self.pubkey.append_value(&reward.pubkey);
+            self.lamports.append_value(reward.lamports);
+            self.post_balance.append_value(reward.post_balance);
+            self.reward_type
+                .append_option(reward.reward_type.as_ref().map(ToString::to_string));
+            self.commission.append_option(reward.commission);
+        }
+    }
+}
+
+impl TableRows for RewardRows {
+    const NAME: &'static str = "rewards";
+
+    fn new() -> Self {
+        Self {
+            num_rows: 0,
+            slot: UInt64Builder::new(),
+            pubkey: StringBuilder::new(),
+            lamports: Int64Builder::new(),
+            post_balance: UInt64Builder::new(),
+            reward_type: StringBuilder::new(),
+            commission: UInt8Builder::new(),
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("lamports", DataType::Int64, false),
+            Field::new("post_balance", DataType::UInt64, false),
+            Field::new("reward_type", DataType::Utf8, true),
+            Field::new("commission", DataType::UInt8, true),
+        ])
+    }
+
+    fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    fn finish(&mut self) -> Vec<ArrayRef> {
+        self.num_rows = 0;
+        vec![
+            Arc::new(self.slot.finish()),
+            Arc::new(self.pubkey.finish()),
+            Arc::new(self.lamports.finish()),
+            Arc::new(self.post_balance.finish()),
+            Arc::new(self.reward_type.finish()),
+            Arc::new(self.commission.finish()),
+        ]
+    }
+}
+
+/// Writes the rows of a table in one partition to a temporary file, which is moved into place
+/// once the partition is closed
+struct TableWriter<T> {
+    rows: T,
+    schema: SchemaRef,
+    writer: ArrowWriter<File>,
+    tmp_path: PathBuf,
+    path: PathBuf,
+}
+
+impl<T: TableRows> TableWriter<T> {
+    fn new(output_dir: &Path, partition_slot: Slot) -> Result<Self, Box<dyn Error>> {
+        let dir = output_dir
+            .join(T::NAME)
+            .join(format!("partition_slot={partition_slot}"));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join("data.parquet");
+        let tmp_path = dir.join("data.parquet.tmp");
+        let schema = Arc::new(T::schema());
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer =
+            ArrowWriter::try_new(File::create(&tmp_path)?, schema.clone(), Some(properties))?;
+        Ok(Self {
+            rows: T::new(),
+            schema,
+            writer,
+            tmp_path,
+            path,
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.rows.num_rows() > 0 {
+            let batch = RecordBatch::try_new(self.schema.clone(), self.rows.finish())?;
+            self.writer.write(&batch)?;
+        }
+        Ok(())
+    }
+
+    fn flush_if_full(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.rows.num_rows() >= MAX_BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn close(mut self) -> Result<(), Box<dyn Error>> {
+        self.flush()?;
+        self.writer.close()?;
+        fs::rename(&self.tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+struct PartitionWriter {
+    partition_slot: Slot,
+    skipped_slots: Vec<Slot>,
+    blocks: TableWriter<BlockRows>,
+    transactions: TableWriter<TransactionRows>,
+    instructions: TableWriter<InstructionRows>,
+    account_keys: TableWriter<AccountKeyRows>,
+    token_balance_changes: TableWriter<TokenBalanceChangeRows>,
+    rewards: TableWriter<RewardRows>,
+}
+
+impl PartitionWriter {
+    fn new(output_dir: &Path, partition_slot: Slot) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            partition_slot,
+            skipped_slots: Vec::default(),
+            blocks: TableWriter::new(output_dir, partition_slot)?,
+            transactions: TableWriter::new(output_dir, partition_slot)?,
+            instructions: TableWriter::new(output_dir, partition_slot)?,
+            account_keys: TableWriter::new(output_dir, partition_slot)?,
+            token_balance_changes: TableWriter::new(output_dir, partition_slot)?,
+            rewards: TableWriter::new(output_dir, partition_slot)?,
+        })
+    }
+
+    fn append_block(
+        &mut self,
+        slot: Slot,
+        block: &VersionedConfirmedBlock,
+    ) -> Result<(), Box<dyn Error>> {
+        self.blocks.rows.append(slot, block);
+        for (transaction_index, tx_with_meta) in block.transactions.iter().enumerate() {
+            let transaction_index = transaction_index as u32;
+            self.transactions
+                .rows
+                .append(slot, transaction_index, tx_with_meta);
+            self.instructions
+                .rows
+                .append(slot, transaction_index, tx_with_meta);
+            self.account_keys
+                .rows
+                .append(slot, transaction_index, tx_with_meta);
+            self.token_balance_changes
+                .rows
+                .append(slot, transaction_index, tx_with_meta);
+        }
+        self.rewards.rows.append(slot, &block.rewards);
+
+        self.blocks.flush_if_full()?;
+        self.transactions.flush_if_full()?;
+        self.instructions.flush_if_full()?;
+        self.account_keys.flush_if_full()?;
+        self.token_balance_changes.flush_if_full()?;
+        self.rewards.flush_if_full()
+    }
+
+    fn close(self) -> Result<(), Box<dyn Error>> {
+        self.blocks.close()?;
+        self.transactions.close()?;
+        self.instructions.close()?;
+        self.account_keys.close()?;
+        self.token_balance_changes.close()?;
+        self.rewards.close()
+    }
+}
+
+/// Export the rooted blocks from `config.starting_slot` to `config.ending_slot`, skipping the
+/// partitions already completely exported to `config.output_dir`. Returns the number of blocks
+/// exported, and the slots whose blocks failed to be read.
+pub fn export_ledger(
+    blockstore: &Blockstore,
+    config: &ExportConfig,
+) -> Result<ExportSummary, Box<dyn Error>> {
+    let ExportConfig {
+        output_dir,
+        starting_slot,
+        ending_slot,
+        partition_slots,
+    } = config;
+    let (ending_slot, partition_slots) = (*ending_slot, *partition_slots);
+    let partition_of = |slot: Slot| slot - slot % partition_slots;
+    let partition_end = |partition_slot: Slot| partition_slot.saturating_add(partition_slots - 1);
+
+    fs::create_dir_all(output_dir)?;
+    let mut manifest = ExportManifest::load(output_dir, partition_slots)?;
+    let mut starting_slot = *starting_slot;
+    while manifest
+        .complete_partitions
+        .contains(&partition_of(starting_slot))
+    {
+        starting_slot = partition_end(partition_of(starting_slot)).saturating_add(1);
+    }
+    let first_root = match blockstore.rooted_slot_iterator(starting_slot)?.next() {
+        Some(slot) if slot <= ending_slot => slot,
+        _ => {
+            info!("No rooted slots left to export from slot {starting_slot}");
+            return Ok(ExportSummary::default());
+        }
+    };
+    info!(
+        "Exporting rooted slots from {first_root} to {}",
+        output_dir.display()
+    );
+
+    // A partition is complete once every rooted slot in its range has been exported, that is
+    // when the export started at or before its first slot, and went past its last slot.
+    let mut partition: Option<PartitionWriter> = None;
+    let mut summary = ExportSummary::default();
+    let mut reached_ending_slot = false;
+    for (slot, _slot_meta) in RootedSlotIterator::new(first_root, blockstore)? {
+        if slot > ending_slot {
+            reached_ending_slot = true;
+            break;
+        }
+        let partition_slot = partition_of(slot);
+        if manifest.complete_partitions.contains(&partition_slot) {
+            continue;
+        }
+        if partition.as_ref().map(|partition| partition.partition_slot) != Some(partition_slot) {
+            if let Some(previous) = partition.take() {
+                let complete = previous.partition_slot >= starting_slot;
+                close_partition(previous, complete, &mut manifest, output_dir, &mut summary)?;
+            }
+            partition = Some(PartitionWriter::new(output_dir, partition_slot)?);
+        }
+        let partition = partition.as_mut().unwrap();
+        match blockstore.get_rooted_block(slot, false) {
+            Ok(block) => {
+                partition.append_block(slot, &block)?;
+                summary.num_blocks += 1;
+            }
+            Err(err) => {
+                warn!("Failed to read rooted block {slot}: {err}");
+                partition.skipped_slots.push(slot);
+            }
+        }
+    }
+    if let Some(last) = partition {
+        let complete = last.partition_slot >= starting_slot
+            && reached_ending_slot
+            && partition_end(last.partition_slot) <= ending_slot;
+        close_partition(last, complete, &mut manifest, output_dir, &mut summary)?;
+    }
+    Ok(summary)
+}
+
+/// Record the partition in the manifest as complete if `complete` and all of its blocks were
+/// read, or else with the slots whose blocks failed to be read
+fn close_partition(
+    mut partition: PartitionWriter,
+    complete: bool,
+    manifest: &mut ExportManifest,
+    output_dir: &Path,
+    summary: &mut ExportSummary,
+) -> Result<(), Box<dyn Error>> {
+    let partition_slot = partition.partition_slot;
+    let skipped_slots = std::mem::take(&mut partition.skipped_slots);
+    partition.close()?;
+    let complete = complete && skipped_slots.is_empty();
+    if complete {
+        manifest.complete_partitions.insert(partition_slot);
+    }
+    if skipped_slots.is_empty() {
+        manifest.skipped_slots.remove(&partition_slot);
+    } else {
+        warn!(
+            "Partition {partition_slot} is missing the blocks of slots {:?}",
+            skipped_slots
+        );
+        manifest
+            .skipped_slots
+            .insert(partition_slot, skipped_slots.clone());
+        summary.skipped_slots.extend(skipped_slots);
+    }
+    manifest.save(output_dir)?;
+    info!(
+        "Exported partition {partition_slot}{}",
+        if complete { "" } else { " (incomplete)" }
+    );
+    Ok(())
+}
+
+pub trait ExportSubCommand {
+    fn export_subcommand(self) -> Self;
+}
+
+impl ExportSubCommand for App<'_, '_> {
+    fn export_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("export")
+                .about(
+                    "Export rooted blocks to Parquet tables of blocks, transactions, \
+                     instructions, account keys, token balance changes and rewards",
+                )
+                .arg(
+                    Arg::with_name("output_dir")
+                        .long("output-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "Directory to write the tables to. An interrupted export \
+                             resumes when run again with the same directory",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("starting_slot")
+                        .long("starting-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("Start exporting at this slot"),
+                )
+                .arg(
+                    Arg::with_name("ending_slot")
+                        .long("ending-slot")
+                        .value_name("SLOT")
+                        .validator(is_slot)
+                        .takes_value(true)
+                        .help("Stop exporting at this slot [default: last root]"),
+                )
+                .arg(
+                    Arg::with_name("partition_slots")
+                        .long("partition-slots")
+                        .value_name("NUM_SLOTS")
+                        .validator(is_parsable::<Slot>)
+                        .takes_value(true)
+                        .default_value(DEFAULT_PARTITION_SLOTS)
+                        .help("Number of consecutive slots written to each partition of a table"),
+                ),
+        )
+    }
+}
+
+pub fn export_process_command(blockstore: &Blockstore, matches: &ArgMatches<'_>) {
+    let config = ExportConfig {
+        output_dir: PathBuf::from(value_t_or_exit!(matches, "output_dir", String)),
+        starting_slot: value_t_or_exit!(matches, "starting_slot", Slot),
+        ending_slot: value_t!(matches, "ending_slot", Slot).unwrap_or(Slot::MAX),
+        partition_slots: value_t_or_exit!(matches, "partition_slots", Slot),
+    };
+    if config.partition_slots == 0 {
+        eprintln!("--partition-slots must be greater than 0");
+        exit(1);
+    }
+    match export_ledger(blockstore, &config) {
+        Ok(summary) => {
+            println!(
+                "Exported {} blocks to {}",
+                summary.num_blocks,
+                config.output_dir.display()
+            );
+            if !summary.skipped_slots.is_empty() {
+                eprintln!(
+                    "Failed to read the blocks of slots {:?}, their partitions will be exported \
+                     again by the next export",
+                    summary.skipped_slots
+                );
+                exit(1);
+            }
+        }
+        Err(err) => {
+            eprintln!("Export failed: {err}");
+            exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        parquet::file::reader::{FileReader, SerializedFileReader},
+        solana_account_decoder::parse_token::UiTokenAmount,
+        solana_ledger::{blockstore::make_slot_entries, get_tmp_ledger_path_auto_delete},
+        solana_sdk::{
+            message::Message, pubkey::Pubkey, system_instruction, transaction::Transaction,
+        },
+        solana_transaction_status::{InnerInstruction, InnerInstructions, TransactionStatusMeta},
+    };
+
+    fn num_rows(output_dir: &Path, table: &str, partition_slot: Slot) -> i64 {
+        let path = output_dir
+            .join(table)
+            .join(format!("partition_slot={partition_slot}"))
+            .join("data.parquet");
+        SerializedFileReader::new(File::open(path).unwrap())
+            .unwrap()
+            .metadata()
+            .file_metadata()
+            .num_rows()
+    }
+
+    #[test]
+    fn test_export_ledger_resumes() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        for slot in 1..=5 {
+            let (shreds, _) = make_slot_entries(slot, slot - 1, 8, true);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+        }
+        blockstore.set_roots([1, 2, 3, 4, 5].iter()).unwrap();
+
+        let output_dir = tempfile::TempDir::new().unwrap();
+        let config = ExportConfig {
+            output_dir: output_dir.path().to_path_buf(),
+            starting_slot: 0,
+            ending_slot: Slot::MAX,
+            partition_slots: 4,
+        };
+        assert_eq!(export_ledger(&blockstore, &config).unwrap().num_blocks, 5);
+        assert_eq!(num_rows(output_dir.path(), "blocks", 0), 3);
+        assert_eq!(num_rows(output_dir.path(), "blocks", 4), 2);
+        assert_eq!(num_rows(output_dir.path(), "transactions", 4), 0);
+        let manifest = ExportManifest::load(output_dir.path(), 4).unwrap();
+        assert_eq!(manifest.complete_partitions, BTreeSet::from([0]));
+
+        // Only the partition that may still get more roots is exported again
+        assert_eq!(export_ledger(&blockstore, &config).unwrap().num_blocks, 2);
+        assert_eq!(num_rows(output_dir.path(), "blocks", 4), 2);
+
+        // A partition is not complete unless the export covered all of its slots
+        let config = ExportConfig {
+            ending_slot: 6,
+            ..config
+        };
+        export_ledger(&blockstore, &config).unwrap();
+        let manifest = ExportManifest::load(output_dir.path(), 4).unwrap();
+        assert_eq!(manifest.complete_partitions, BTreeSet::from([0]));
+
+        assert!(ExportManifest::load(output_dir.path(), 8).is_err());
+    }
+
+    #[test]
+    fn test_export_ledger_skipped_slots() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        for slot in [1, 3, 4, 5] {
+            let (shreds, _) = make_slot_entries(slot, slot - 1, 8, true);
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+        }
+        // The block of slot 2 can't be read until its last shred is inserted
+        let (mut shreds, _) = make_slot_entries(2, 1, 100, true);
+        assert!(shreds.len() > 1);
+        let last_shred = shreds.pop().unwrap();
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([1, 2, 3, 4, 5].iter()).unwrap();
+
+        let output_dir = tempfile::TempDir::new().unwrap();
+        let config = ExportConfig {
+            output_dir: output_dir.path().to_path_buf(),
+            starting_slot: 0,
+            ending_slot: 5,
+            partition_slots: 4,
+        };
+        assert_eq!(
+            export_ledger(&blockstore, &config).unwrap(),
+            ExportSummary {
+                num_blocks: 4,
+                skipped_slots: vec![2],
+            }
+        );
+        let manifest = ExportManifest::load(output_dir.path(), 4).unwrap();
+        assert!(manifest.complete_partitions.is_empty());
+        assert_eq!(manifest.skipped_slots, BTreeMap::from([(0, vec![2])]));
+
+        // The incomplete partition is exported again once its blocks can be read
+        blockstore
+            .insert_shreds(vec![last_shred], None, false)
+            .unwrap();
+        assert_eq!(
+            export_ledger(&blockstore, &config).unwrap(),
+            ExportSummary {
+                num_blocks: 5,
+                skipped_slots: vec![],
+            }
+        );
+        assert_eq!(num_rows(output_dir.path(), "blocks", 0), 3);
+        let manifest = ExportManifest::load(output_dir.path(), 4).unwrap();
+        assert_eq!(manifest.complete_partitions, BTreeSet::from([0]));
+        assert!(manifest.skipped_slots.is_empty());
+    }
+
+    #[test]
+    fn test_transaction_rows() {
+        let from = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let transaction = Transaction::new_unsigned(Message::new(
+            &[system_instruction::transfer(&from, &to, 1)],
+            Some(&from),
+        ));
+        let token_balance = |amount: &str| TransactionTokenBalance {
+            account_index: 1,
+            mint: mint.to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals: 2,
+                amount: amount.to_string(),
+                ui_amount_string: String::default(),
+            },
+            owner: String::default(),
+            program_id: String::default(),
+        };
+        let tx_with_meta = VersionedTransactionWithStatusMeta {
+            transaction: transaction.into(),
+            meta: TransactionStatusMeta {
+                pre_balances: vec![10, 0, 1],
+                post_balances: vec![4, 1, 1],
+                inner_instructions: Some(vec![InnerInstructions {
+                    index: 0,
+                    instructions: vec![InnerInstruction {
+                        instruction: CompiledInstruction::new_from_raw_parts(2, vec![], vec![0]),
+                        stack_height: Some(2),
+                    }],
+                }]),
+                pre_token_balances: Some(vec![token_balance("100")]),
+                post_token_balances: Some(vec![token_balance("90")]),
+                ..TransactionStatusMeta::default()
+            },
+        };
+
+        let mut transactions = TransactionRows::new();
+        transactions.append(1, 0, &tx_with_meta);
+        assert_eq!(transactions.num_rows(), 1);
+        let columns = transactions.finish();
+        assert_eq!(transactions.num_rows(), 0);
+        RecordBatch::try_new(Arc::new(TransactionRows::schema()), columns).unwrap();
+
+        let mut instructions = InstructionRows::new();
+        instructions.append(1, 0, &tx_with_meta);
+        assert_eq!(instructions.num_rows(), 2);
+        RecordBatch::try_new(Arc::new(InstructionRows::schema()), instructions.finish()).unwrap();
+
+        let mut account_keys = AccountKeyRows::new();
+        account_keys.append(1, 0, &tx_with_meta);
+        assert_eq!(account_keys.num_rows(), 3);
+        RecordBatch::try_new(Arc::new(AccountKeyRows::schema()), account_keys.finish()).unwrap();
+
+        let mut token_balance_changes = TokenBalanceChangeRows::new();
+        token_balance_changes.append(1, 0, &tx_with_meta);
+        assert_eq!(token_balance_changes.num_rows(), 1);
+        let batch = RecordBatch::try_new(
+            Arc::new(TokenBalanceChangeRows::schema()),
+            token_balance_changes.finish(),
+        )
+        .unwrap();
+        let post_amounts = batch
+            .column(9)
+            .as_any()
+            .downcast_ref::<arrow_array::UInt64Array>()
+            .unwrap();
+        assert_eq!(post_amounts.value(0), 90);
+    }
+}
@@ -0,0 +1,120 @@
+//! The `extract` subcommand's copy of a slot range into a new ledger
+use {solana_ledger::blockstore::Blockstore, solana_sdk::clock::Slot, std::error::Error};
+
+/// The number of slots and roots copied by `copy_slot_range`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CopiedSlotRange {
+    pub num_slots: usize,
+    pub num_roots: usize,
+}
+
+/// Copies the data and coding shreds of the slots in \[`starting_slot`, `ending_slot`\] from
+/// `source` into `target`, along with the roots among them. The `SlotMeta`, `Index` and
+/// `ErasureMeta` entries of the copied slots are rebuilt by the insertion of their shreds.
+pub fn copy_slot_range(
+    source: &Blockstore,
+    target: &Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<CopiedSlotRange, Box<dyn Error>> {
+    let mut copied = CopiedSlotRange::default();
+    for (slot, _meta) in source.slot_meta_iterator(starting_slot)? {
+        if slot > ending_slot {
+            break;
+        }
+        let data_shreds = source
+            .get_data_shreds_for_slot(slot, 0)
+            .map_err(|err| format!("Unable to read the data shreds of slot {slot}: {err}"))?;
+        let coding_shreds = source
+            .get_coding_shreds_for_slot(slot, 0)
+            .map_err(|err| format!("Unable to read the coding shreds of slot {slot}: {err}"))?;
+        target
+            .insert_shreds([data_shreds, coding_shreds].concat(), None, true)
+            .map_err(|err| format!("Unable to insert the shreds of slot {slot}: {err}"))?;
+        copied.num_slots += 1;
+    }
+    let roots: Vec<_> = source
+        .rooted_slot_iterator(starting_slot)?
+        .take_while(|slot| *slot <= ending_slot)
+        .collect();
+    target.set_roots(roots.iter())?;
+    copied.num_roots = roots.len();
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::create_ticks,
+        solana_ledger::{
+            get_tmp_ledger_path_auto_delete,
+            shred::{ProcessShredsStats, ReedSolomonCache, Shredder},
+        },
+        solana_sdk::{hash::Hash, signature::Keypair},
+    };
+
+    fn insert_slot(blockstore: &Blockstore, slot: Slot, parent_slot: Slot) {
+        let entries = create_ticks(10, 0, Hash::default());
+        let shredder = Shredder::new(slot, parent_slot, 0, 0).unwrap();
+        let (data_shreds, coding_shreds) = shredder.entries_to_shreds(
+            &Keypair::new(),
+            &entries,
+            true, // is_last_in_slot
+            0,    // next_shred_index
+            0,    // next_code_index
+            true, // merkle_variant
+            &ReedSolomonCache::default(),
+            &mut ProcessShredsStats::default(),
+        );
+        assert!(!coding_shreds.is_empty());
+        blockstore
+            .insert_shreds([data_shreds, coding_shreds].concat(), None, true)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_copy_slot_range() {
+        let source_path = get_tmp_ledger_path_auto_delete!();
+        let source = Blockstore::open(source_path.path()).unwrap();
+        for slot in 1..=4 {
+            insert_slot(&source, slot, slot - 1);
+        }
+        source.set_roots([1, 2, 3, 4].iter()).unwrap();
+
+        let target_path = get_tmp_ledger_path_auto_delete!();
+        let target = Blockstore::open(target_path.path()).unwrap();
+        assert_eq!(
+            copy_slot_range(&source, &target, 2, 3).unwrap(),
+            CopiedSlotRange {
+                num_slots: 2,
+                num_roots: 2,
+            }
+        );
+
+        for slot in 2..=3 {
+            assert!(target.is_full(slot));
+            assert!(target.is_root(slot));
+            assert_eq!(
+                target.get_data_shreds_for_slot(slot, 0).unwrap(),
+                source.get_data_shreds_for_slot(slot, 0).unwrap()
+            );
+            assert_eq!(
+                target.get_coding_shreds_for_slot(slot, 0).unwrap(),
+                source.get_coding_shreds_for_slot(slot, 0).unwrap()
+            );
+        }
+        // The erasure metas and indexes were rebuilt along with the shreds
+        let report = target.check_consistency(2, 3, false).unwrap();
+        assert_eq!(report.inconsistencies, vec![]);
+
+        for slot in [1, 4] {
+            assert!(!target.is_root(slot));
+            assert!(target.get_data_shreds_for_slot(slot, 0).unwrap().is_empty());
+            assert!(target
+                .get_coding_shreds_for_slot(slot, 0)
+                .unwrap()
+                .is_empty());
+        }
+    }
+}
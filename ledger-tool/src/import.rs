@@ -0,0 +1,572 @@
+//! The `import` subcommand's shred sources
+//!
+//! Shreds are read from one of:
+//! * raw shred dumps, where each shred payload is preceded by its length as a little-endian `u16`
+//! * confirmed blocks, as stored in Bigtable: one protobuf encoded `ConfirmedBlock` per file,
+//!   named after the slot of the block, e.g. `123456.pb`
+//! * pcap captures of the traffic to the TVU port
+//!
+//! A confirmed block only records its transactions and blockhash, so [`shred_confirmed_block()`]
+//! rebuilds entries and ticks that replay to the same bank hash, but whose proof of history does
+//! not verify: the imported slots must be replayed with `--skip-poh-verify`.
+use {
+    prost::Message,
+    solana_entry::entry::{next_hash, Entry},
+    solana_ledger::shred::{ProcessShredsStats, ReedSolomonCache, Shred, Shredder},
+    solana_sdk::{
+        clock::Slot, hash::Hash, signer::keypair::keypair_from_seed,
+        transaction::VersionedTransaction,
+    },
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::ConfirmedBlock,
+    std::{
+        fs::{self, File},
+        io::{self, BufReader, BufWriter, ErrorKind, Read, Write},
+        path::{Path, PathBuf},
+        str::FromStr,
+    },
+};
+
+const PCAP_GLOBAL_HEADER_LEN: usize = 24;
+const PCAP_RECORD_HEADER_LEN: usize = 16;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86dd;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const IPPROTO_UDP: u8 = 17;
+const UDP_HEADER_LEN: usize = 8;
+
+/// Number of shreds read from a shred dump or pcap capture before they are inserted
+pub const IMPORT_SHRED_BATCH_SIZE: usize = 1024;
+
+/// Seed of the keypair signing the shreds of re-shredded blocks, fixed so that a block is always
+/// shredded the same way
+const RESHRED_KEYPAIR_SEED: [u8; 32] = [0u8; 32];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    Shreds,
+    Blocks,
+    Pcap,
+}
+
+impl ImportFormat {
+    pub const VALUES: &'static [&'static str] = &["shreds", "blocks", "pcap"];
+}
+
+impl FromStr for ImportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shreds" => Ok(Self::Shreds),
+            "blocks" => Ok(Self::Blocks),
+            "pcap" => Ok(Self::Pcap),
+            _ => Err(format!("unknown import format: {s}")),
+        }
+    }
+}
+
+/// Expands directories into the files they contain, sorted by name
+pub fn source_files(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.retain(|path| path.is_file());
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Fills `buf` from `reader`, returning `false` if the reader is at its end before the first byte
+fn read_exact_or_eof(reader: &mut impl Read, buf: &mut [u8], what: &str) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => {
+                return Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("truncated {what}"),
+                ))
+            }
+            Ok(len) => filled += len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+/// Iterates over the shred payloads of a raw shred dump, one record at a time
+pub struct ShredDumpReader<R> {
+    reader: R,
+    failed: bool,
+}
+
+impl<R: Read> ShredDumpReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            failed: false,
+        }
+    }
+
+    fn read_payload(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut len = [0u8; 2];
+        if !read_exact_or_eof(&mut self.reader, &mut len, "shred length")? {
+            return Ok(None);
+        }
+        let mut payload = vec![0u8; usize::from(u16::from_le_bytes(len))];
+        if !payload.is_empty()
+            && !read_exact_or_eof(&mut self.reader, &mut payload, "shred payload")?
+        {
+            return Err(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "truncated shred payload",
+            ));
+        }
+        Ok(Some(payload))
+    }
+}
+
+impl<R: Read> Iterator for ShredDumpReader<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let payload = self.read_payload().transpose();
+        self.failed = matches!(payload, Some(Err(_)));
+        payload
+    }
+}
+
+/// Opens a raw shred dump to read its shred payloads
+pub fn read_shred_dump(path: &Path) -> io::Result<ShredDumpReader<BufReader<File>>> {
+    Ok(ShredDumpReader::new(BufReader::new(File::open(path)?)))
+}
+
+/// Writes shred payloads in the raw shred dump format read by [`read_shred_dump()`]
+pub fn write_shred_dump<'a>(
+    path: &Path,
+    payloads: impl IntoIterator<Item = &'a [u8]>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for payload in payloads {
+        let len = u16::try_from(payload.len())
+            .map_err(|_| io::Error::new(ErrorKind::InvalidInput, "shred payload too large"))?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(payload)?;
+    }
+    writer.flush()
+}
+
+/// Iterates over the UDP payloads of a pcap capture, one record at a time, keeping those sent to
+/// `port` if any
+pub struct PcapReader<R> {
+    reader: R,
+    big_endian: bool,
+    link_type: u32,
+    port: Option<u16>,
+    frame: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> PcapReader<R> {
+    pub fn new(mut reader: R, port: Option<u16>) -> io::Result<Self> {
+        let mut header = [0u8; PCAP_GLOBAL_HEADER_LEN];
+        if !read_exact_or_eof(&mut reader, &mut header, "pcap header")? {
+            return Err(invalid_data("truncated pcap header"));
+        }
+        let big_endian = match header[..4] {
+            [0xd4, 0xc3, 0xb2, 0xa1] | [0x4d, 0x3c, 0xb2, 0xa1] => false,
+            [0xa1, 0xb2, 0xc3, 0xd4] | [0xa1, 0xb2, 0x3c, 0x4d] => true,
+            _ => return Err(invalid_data("not a pcap file")),
+        };
+        let link_type = read_u32(&header[20..24], big_endian);
+        if ![LINKTYPE_ETHERNET, LINKTYPE_RAW, LINKTYPE_LINUX_SLL].contains(&link_type) {
+            return Err(invalid_data(&format!(
+                "unsupported pcap link type {link_type}"
+            )));
+        }
+        Ok(Self {
+            reader,
+            big_endian,
+            link_type,
+            port,
+            frame: vec![],
+            failed: false,
+        })
+    }
+
+    fn read_payload(&mut self) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            let mut record_header = [0u8; PCAP_RECORD_HEADER_LEN];
+            if !read_exact_or_eof(&mut self.reader, &mut record_header, "pcap record header")? {
+                return Ok(None);
+            }
+            let captured_len = read_u32(&record_header[8..12], self.big_endian) as usize;
+            self.frame.resize(captured_len, 0);
+            if captured_len > 0
+                && !read_exact_or_eof(&mut self.reader, &mut self.frame, "pcap record")?
+            {
+                return Err(invalid_data("truncated pcap record"));
+            }
+            if let Some((dst_port, payload)) = udp_payload(self.link_type, &self.frame) {
+                if self.port.map(|port| port == dst_port).unwrap_or(true) {
+                    return Ok(Some(payload.to_vec()));
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for PcapReader<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let payload = self.read_payload().transpose();
+        self.failed = matches!(payload, Some(Err(_)));
+        payload
+    }
+}
+
+/// Opens a pcap capture to read its UDP payloads, keeping those sent to `port` if any
+pub fn read_pcap(path: &Path, port: Option<u16>) -> io::Result<PcapReader<BufReader<File>>> {
+    PcapReader::new(BufReader::new(File::open(path)?), port)
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.to_string())
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    if big_endian {
+        u32::from_be_bytes(bytes)
+    } else {
+        u32::from_le_bytes(bytes)
+    }
+}
+
+/// Returns the destination port and payload of a captured UDP datagram
+fn udp_payload(link_type: u32, frame: &[u8]) -> Option<(u16, &[u8])> {
+    let read_u16 = |bytes: &[u8], offset: usize| {
+        bytes
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+    let (ethertype, mut offset) = match link_type {
+        LINKTYPE_ETHERNET => (read_u16(frame, 12)?, 14),
+        LINKTYPE_LINUX_SLL => (read_u16(frame, 14)?, 16),
+        LINKTYPE_RAW => match frame.first()? >> 4 {
+            4 => (ETHERTYPE_IPV4, 0),
+            6 => (ETHERTYPE_IPV6, 0),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let ethertype = if ethertype == ETHERTYPE_VLAN {
+        offset += 4;
+        read_u16(frame, offset - 2)?
+    } else {
+        ethertype
+    };
+    let ip = frame.get(offset..)?;
+    let udp = match ethertype {
+        ETHERTYPE_IPV4 => {
+            let header_len = usize::from(ip.first()? & 0x0f) * 4;
+            let flags_and_fragment_offset = read_u16(ip, 6)?;
+            // Shreds fit in a single datagram, fragments are not reassembled
+            if *ip.get(9)? != IPPROTO_UDP || flags_and_fragment_offset & 0x3fff != 0 {
+                return None;
+            }
+            ip.get(header_len..)?
+        }
+        ETHERTYPE_IPV6 => {
+            if *ip.get(6)? != IPPROTO_UDP {
+                return None;
+            }
+            ip.get(40..)?
+        }
+        _ => return None,
+    };
+    let dst_port = read_u16(udp, 2)?;
+    let udp_len = usize::from(read_u16(udp, 4)?);
+    let payload = udp.get(UDP_HEADER_LEN..udp_len.min(udp.len()))?;
+    Some((dst_port, payload))
+}
+
+/// Reads a confirmed block file, returning the slot in its name along with the block
+pub fn read_confirmed_block(path: &Path) -> Result<(Slot, ConfirmedBlock), String> {
+    let slot = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .and_then(|stem| stem.parse::<Slot>().ok())
+        .ok_or_else(|| format!("{} is not named after a slot", path.display()))?;
+    let bytes = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let block = generated::ConfirmedBlock::decode(bytes.as_slice())
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    let block =
+        ConfirmedBlock::try_from(block).map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok((slot, block))
+}
+
+/// Rebuilds the entries of a block from its transactions: one entry per transaction, followed by
+/// `num_ticks` ticks, the last of which carries the blockhash. Transaction entries are spread
+/// between the ticks so that every tick counts `hashes_per_tick` hashes.
+pub fn entries_from_transactions(
+    transactions: Vec<VersionedTransaction>,
+    previous_blockhash: Hash,
+    blockhash: Hash,
+    num_ticks: u64,
+    hashes_per_tick: u64,
+) -> Vec<Entry> {
+    let mut entries = vec![];
+    let mut hash = previous_blockhash;
+    let mut tick_hash_count = 0;
+    let mut num_ticks_left = num_ticks;
+    let push_tick = |entries: &mut Vec<Entry>, hash: &mut Hash, tick_hash_count: &mut u64| {
+        let num_hashes = if hashes_per_tick > *tick_hash_count {
+            hashes_per_tick - *tick_hash_count
+        } else {
+            1
+        };
+        *hash = next_hash(hash, 1, &[]);
+        entries.push(Entry::new_tick(num_hashes, hash));
+        *tick_hash_count = 0;
+    };
+    for transaction in transactions {
+        if hashes_per_tick > 0 && tick_hash_count + 1 >= hashes_per_tick && num_ticks_left > 1 {
+            push_tick(&mut entries, &mut hash, &mut tick_hash_count);
+            num_ticks_left -= 1;
+        }
+        let transactions = vec![transaction];
+        hash = next_hash(&hash, 1, &transactions);
+        entries.push(Entry {
+            num_hashes: 1,
+            hash,
+            transactions,
+        });
+        tick_hash_count += 1;
+    }
+    for _ in 0..num_ticks_left {
+        push_tick(&mut entries, &mut hash, &mut tick_hash_count);
+    }
+    if let Some(last_tick) = entries.last_mut().filter(|entry| entry.is_tick()) {
+        last_tick.hash = blockhash;
+    }
+    entries
+}
+
+/// Shreds a confirmed block of `slot`, signing the shreds with a fixed keypair
+pub fn shred_confirmed_block(
+    slot: Slot,
+    block: ConfirmedBlock,
+    ticks_per_slot: u64,
+    hashes_per_tick: u64,
+    shred_version: u16,
+) -> Result<Vec<Shred>, String> {
+    let parse_hash = |hash: &str| {
+        Hash::from_str(hash).map_err(|err| format!("slot {slot}: invalid blockhash {hash}: {err}"))
+    };
+    let previous_blockhash = parse_hash(&block.previous_blockhash)?;
+    let blockhash = parse_hash(&block.blockhash)?;
+    let shredder = Shredder::new(slot, block.parent_slot, 0, shred_version)
+        .map_err(|err| format!("slot {slot}: {err}"))?;
+    let num_ticks = slot.saturating_sub(block.parent_slot).max(1) * ticks_per_slot;
+    let transactions = block
+        .transactions
+        .iter()
+        .map(|transaction| transaction.get_transaction())
+        .collect();
+    let entries = entries_from_transactions(
+        transactions,
+        previous_blockhash,
+        blockhash,
+        num_ticks,
+        hashes_per_tick,
+    );
+    let keypair = keypair_from_seed(&RESHRED_KEYPAIR_SEED).unwrap();
+    let (data_shreds, _coding_shreds) = shredder.entries_to_shreds(
+        &keypair,
+        &entries,
+        true, // is_last_in_slot
+        0,    // next_shred_index
+        0,    // next_code_index
+        true, // merkle_variant
+        &ReedSolomonCache::default(),
+        &mut ProcessShredsStats::default(),
+    );
+    Ok(data_shreds)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_entry::entry::EntrySlice,
+        solana_ledger::{blockstore::Blockstore, get_tmp_ledger_path_auto_delete},
+        solana_sdk::{
+            hash::hash,
+            signature::{Keypair, Signer},
+            system_transaction,
+            transaction::Transaction,
+        },
+        solana_transaction_status::TransactionWithStatusMeta,
+    };
+
+    fn test_transaction(lamports: u64) -> Transaction {
+        let keypair = Keypair::new();
+        system_transaction::transfer(&keypair, &keypair.pubkey(), lamports, Hash::default())
+    }
+
+    #[test]
+    fn test_shred_dump_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shreds.bin");
+        let payloads = vec![vec![1u8; 1203], vec![], vec![2u8; 1228]];
+        write_shred_dump(&path, payloads.iter().map(Vec::as_slice)).unwrap();
+        assert_eq!(
+            read_shred_dump(&path)
+                .unwrap()
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap(),
+            payloads
+        );
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.pop();
+        fs::write(&path, bytes).unwrap();
+        let mut reader = read_shred_dump(&path).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), payloads[0]);
+        assert_eq!(reader.next().unwrap().unwrap(), payloads[1]);
+        assert_eq!(
+            reader.next().unwrap().unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+        assert!(reader.next().is_none());
+    }
+
+    fn ethernet_ipv4_udp_frame(dst_port: u16, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&ETHERTYPE_IPV4.to_be_bytes());
+        let total_len = (20 + UDP_HEADER_LEN + payload.len()) as u16;
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&total_len.to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0, 64, IPPROTO_UDP, 0, 0]);
+        frame.extend_from_slice(&[127, 0, 0, 1, 127, 0, 0, 1]);
+        frame.extend_from_slice(&8000u16.to_be_bytes());
+        frame.extend_from_slice(&dst_port.to_be_bytes());
+        frame.extend_from_slice(&((UDP_HEADER_LEN + payload.len()) as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    #[test]
+    fn test_read_pcap() {
+        let mut bytes = vec![0xd4, 0xc3, 0xb2, 0xa1, 2, 0, 4, 0];
+        bytes.extend_from_slice(&[0u8; 8]);
+        bytes.extend_from_slice(&65535u32.to_le_bytes());
+        bytes.extend_from_slice(&LINKTYPE_ETHERNET.to_le_bytes());
+        for (dst_port, payload) in [(8001, &[1u8; 1203][..]), (8002, &[2u8; 10][..])] {
+            let frame = ethernet_ipv4_udp_frame(dst_port, payload);
+            bytes.extend_from_slice(&[0u8; 8]);
+            bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&frame);
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tvu.pcap");
+        fs::write(&path, bytes).unwrap();
+
+        let read_all = |port| {
+            read_pcap(&path, port)
+                .unwrap()
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(read_all(None), vec![vec![1u8; 1203], vec![2u8; 10]]);
+        assert_eq!(read_all(Some(8001)), vec![vec![1u8; 1203]]);
+        assert_eq!(read_all(Some(8003)), Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn test_entries_from_transactions() {
+        let previous_blockhash = hash(&[1]);
+        let blockhash = hash(&[2]);
+        let transactions: Vec<VersionedTransaction> = (0..10)
+            .map(|lamports| test_transaction(lamports).into())
+            .collect();
+
+        for hashes_per_tick in [0, 4, 100] {
+            let entries = entries_from_transactions(
+                transactions.clone(),
+                previous_blockhash,
+                blockhash,
+                8,
+                hashes_per_tick,
+            );
+            assert_eq!(entries.tick_count(), 8);
+            assert_eq!(entries.last().unwrap().hash, blockhash);
+            assert_eq!(
+                entries
+                    .iter()
+                    .flat_map(|entry| entry.transactions.iter().cloned())
+                    .collect::<Vec<_>>(),
+                transactions
+            );
+            let mut tick_hash_count = 0;
+            assert!(entries.verify_tick_hash_count(&mut tick_hash_count, hashes_per_tick));
+        }
+    }
+
+    #[test]
+    fn test_shred_confirmed_block() {
+        let block = ConfirmedBlock {
+            previous_blockhash: hash(&[1]).to_string(),
+            blockhash: hash(&[2]).to_string(),
+            parent_slot: 3,
+            transactions: (0..4)
+                .map(|lamports| {
+                    TransactionWithStatusMeta::MissingMetadata(test_transaction(lamports))
+                })
+                .collect(),
+            rewards: vec![],
+            block_time: None,
+            block_height: None,
+        };
+        let shreds = shred_confirmed_block(5, block.clone(), 4, 0, 42).unwrap();
+        assert!(shreds.last().unwrap().last_in_slot());
+        assert!(shreds.iter().all(|shred| shred.slot() == 5
+            && shred.parent().unwrap() == 3
+            && shred.version() == 42));
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        blockstore
+            .insert_shreds(shreds.clone(), None, true)
+            .unwrap();
+        assert!(blockstore.is_full(5));
+        let entries = blockstore.get_slot_entries(5, 0).unwrap();
+        assert_eq!(entries.tick_count(), 8);
+        assert_eq!(entries.last().unwrap().hash, hash(&[2]));
+
+        // Re-shredding is deterministic
+        assert_eq!(shred_confirmed_block(5, block, 4, 0, 42).unwrap(), shreds);
+    }
+}
@@ -1,6 +1,16 @@
 #![allow(clippy::integer_arithmetic)]
 use {
-    crate::{bigtable::*, ledger_path::*, output::*},
+    crate::{
+        bank_hash_diff::{BankHashDetails, BankHashDiff},
+        bigtable::*,
+        export::*,
+        extract::*,
+        import::*,
+        ledger_path::*,
+        output::*,
+        snapshot_accounts::*,
+        snapshot_diff::*,
+    },
     chrono::{DateTime, Utc},
     clap::{
         crate_description, crate_name, value_t, value_t_or_exit, values_t_or_exit, App,
@@ -30,17 +40,22 @@ use {
     solana_ledger::{
         ancestor_iterator::AncestorIterator,
         bank_forks_utils,
-        blockstore::{create_new_ledger, Blockstore, BlockstoreError, PurgeType},
+        blockstore::{
+            create_new_ledger, Blockstore, BlockstoreError, CompressibleColumn, PurgeType,
+        },
+        blockstore_compression::{DEFAULT_DICTIONARY_MAX_SAMPLES, DEFAULT_DICTIONARY_SIZE},
         blockstore_db::{self, columns as cf, Column, ColumnName, Database},
         blockstore_options::{
             AccessType, BlockstoreOptions, BlockstoreRecoveryMode, LedgerColumnOptions,
             ShredStorageType, BLOCKSTORE_DIRECTORY_ROCKS_FIFO,
         },
         blockstore_processor::{self, BlockstoreProcessorError, ProcessOptions},
+        leader_schedule_cache::LeaderScheduleCache,
         shred::Shred,
     },
     solana_measure::{measure, measure::Measure},
     solana_runtime::{
+        account_set::AccountSetFilter,
         accounts::Accounts,
         accounts_background_service::{
             AbsRequestHandlers, AbsRequestSender, AccountsBackgroundService,
@@ -48,7 +63,9 @@ use {
         },
         accounts_db::{
             AccountsDb, AccountsDbConfig, CalcAccountsHashDataSource, FillerAccountsConfig,
+            PUBKEY_BINS_FOR_CALCULATING_HASHES,
         },
+        accounts_hash_dump::AccountsHashDump,
         accounts_index::{AccountsIndexConfig, IndexLimitMb, ScanConfig},
         accounts_update_notifier_interface::AccountsUpdateNotifier,
         bank::{Bank, RewardCalculationEvent, TotalAccountsStats},
@@ -65,6 +82,7 @@ use {
             self, move_and_async_delete_path, ArchiveFormat, SnapshotVersion,
             DEFAULT_ARCHIVE_COMPRESSION, SUPPORTED_ARCHIVE_COMPRESSION,
         },
+        transaction_trace::{TransactionTrace, TransactionTraceConfig},
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount, WritableAccount},
@@ -79,6 +97,7 @@ use {
         pubkey::Pubkey,
         rent::Rent,
         shred_version::compute_shred_version,
+        signature::Signature,
         stake::{self, state::StakeState},
         system_program,
         transaction::{MessageHash, SanitizedTransaction, SimpleAddressLoader},
@@ -91,8 +110,8 @@ use {
     std::{
         collections::{BTreeMap, BTreeSet, HashMap, HashSet},
         ffi::OsStr,
-        fs::File,
-        io::{self, stdout, BufRead, BufReader, Write},
+        fs::{self, File},
+        io::{self, stdout, BufRead, BufReader, BufWriter, Write},
         path::{Path, PathBuf},
         process::{exit, Command, Stdio},
         str::FromStr,
@@ -100,13 +119,20 @@ use {
             atomic::{AtomicBool, Ordering},
             Arc, RwLock,
         },
+        thread::{Builder, JoinHandle},
         time::{Duration, UNIX_EPOCH},
     },
 };
 
+mod bank_hash_diff;
 mod bigtable;
+mod export;
+mod extract;
+mod import;
 mod ledger_path;
 mod output;
+mod snapshot_accounts;
+mod snapshot_diff;
 
 #[derive(PartialEq, Eq)]
 enum LedgerOutputMethod {
@@ -827,6 +853,7 @@ fn analyze_storage(database: &Database) {
     analyze_column::<ShredCode>(database, "ShredCode");
     analyze_column::<TransactionStatus>(database, "TransactionStatus");
     analyze_column::<AddressSignatures>(database, "AddressSignatures");
+    analyze_column::<ProgramInstructionSignatures>(database, "ProgramInstructionSignatures");
     analyze_column::<TransactionMemos>(database, "TransactionMemos");
     analyze_column::<TransactionStatusIndex>(database, "TransactionStatusIndex");
     analyze_column::<Rewards>(database, "Rewards");
@@ -835,6 +862,7 @@ fn analyze_storage(database: &Database) {
     analyze_column::<BlockHeight>(database, "BlockHeight");
     analyze_column::<ProgramCosts>(database, "ProgramCosts");
     analyze_column::<OptimisticSlots>(database, "OptimisticSlots");
+    analyze_column::<CompressionDictionary>(database, "CompressionDictionary");
 }
 
 /// Open blockstore with temporary primary access to allow necessary,
@@ -906,6 +934,7 @@ fn open_blockstore(
                 shred_storage_type: shred_storage_type.clone(),
                 ..LedgerColumnOptions::default()
             },
+            ..BlockstoreOptions::default()
         },
     ) {
         Ok(blockstore) => blockstore,
@@ -963,6 +992,9 @@ fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
         cf::AddressSignatures::NAME => Some(cf::AddressSignatures::slot(
             cf::AddressSignatures::index(key),
         )),
+        cf::ProgramInstructionSignatures::NAME => Some(cf::ProgramInstructionSignatures::slot(
+            cf::ProgramInstructionSignatures::index(key),
+        )),
         cf::TransactionMemos::NAME => None, // does not implement slot()
         cf::TransactionStatusIndex::NAME => None, // does not implement slot()
         cf::Rewards::NAME => Some(cf::Rewards::slot(cf::Rewards::index(key))),
@@ -973,6 +1005,7 @@ fn raw_key_to_slot(key: &[u8], column_name: &str) -> Option<Slot> {
         cf::OptimisticSlots::NAME => {
             Some(cf::OptimisticSlots::slot(cf::OptimisticSlots::index(key)))
         }
+        cf::CompressionDictionary::NAME => None, // does not implement slot()
         &_ => None,
     }
 }
@@ -1012,6 +1045,22 @@ fn print_blockstore_file_metadata(
     Ok(())
 }
 
+/// The most bins `PubkeyBinCalculator24` can break the pubkeys down into
+const MAX_ACCOUNTS_HASH_BINS: usize = 1 << 24;
+
+fn is_accounts_hash_bins(bins: String) -> Result<(), String> {
+    is_pow2(&bins)?;
+    let bins = bins
+        .parse::<usize>()
+        .map_err(|err| format!("Unable to parse {bins}, provided: {err}"))?;
+    if bins > MAX_ACCOUNTS_HASH_BINS {
+        return Err(format!(
+            "Must be at most {MAX_ACCOUNTS_HASH_BINS}, provided: {bins}"
+        ));
+    }
+    Ok(())
+}
+
 // This function is duplicated in validator/src/main.rs...
 fn hardforks_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Slot>> {
     if matches.is_present(name) {
@@ -1021,6 +1070,110 @@ fn hardforks_of(matches: &ArgMatches<'_>, name: &str) -> Option<Vec<Slot>> {
     }
 }
 
+/// Parses the `--trace-transactions` arguments of `matches`, and spawns the thread that writes
+/// the traces as JSON lines to `--trace-output`, or to stdout. The thread returns once the
+/// sender of the returned config and all its clones are dropped.
+fn transaction_trace_config_of(
+    matches: &ArgMatches<'_>,
+) -> Option<(TransactionTraceConfig, JoinHandle<()>)> {
+    let values = matches.values_of("trace_transactions")?;
+    let mut transaction_trace_config = TransactionTraceConfig {
+        record_vm_trace: matches.is_present("trace_vm"),
+        ..TransactionTraceConfig::default()
+    };
+    for value in values {
+        if let Ok(signature) = Signature::from_str(value) {
+            transaction_trace_config.signatures.insert(signature);
+        } else if let Ok(pubkey) = Pubkey::from_str(value) {
+            transaction_trace_config.addresses.insert(pubkey);
+        } else {
+            eprintln!("{value} is neither a transaction signature nor an address");
+            exit(1);
+        }
+    }
+
+    let mut output: Box<dyn Write + Send> = match matches.value_of("trace_output") {
+        Some(path) => Box::new(BufWriter::new(File::create(path).unwrap_or_else(|err| {
+            eprintln!("Unable to create {path}: {err}");
+            exit(1);
+        }))),
+        None => Box::new(stdout()),
+    };
+    let (sender, receiver) = unbounded::<TransactionTrace>();
+    transaction_trace_config.sender = Some(sender);
+    let writer = Builder::new()
+        .name("solTraceWriter".to_string())
+        .spawn(move || {
+            for trace in receiver.iter() {
+                serde_json::to_writer(&mut output, &trace).expect("serialize trace");
+                writeln!(output).expect("write trace");
+            }
+            output.flush().expect("flush traces");
+        })
+        .unwrap();
+    Some((transaction_trace_config, writer))
+}
+
+/// Replays `ledger_path` up to `slot`, and returns the details of the bank hash of `slot`
+#[allow(clippy::too_many_arguments)]
+fn replay_bank_hash_details(
+    arg_matches: &ArgMatches,
+    ledger_path: &Path,
+    slot: Slot,
+    deactivated_features: HashSet<Pubkey>,
+    snapshot_archive_path: Option<PathBuf>,
+    incremental_snapshot_archive_path: Option<PathBuf>,
+    wal_recovery_mode: Option<BlockstoreRecoveryMode>,
+    force_update_to_open: bool,
+) -> BankHashDetails {
+    let shred_storage_type = get_shred_storage_type(
+        ledger_path,
+        "Shred storage type cannot be inferred, the default RocksLevel will be used",
+    );
+    let blockstore = open_blockstore(
+        ledger_path,
+        AccessType::Secondary,
+        wal_recovery_mode,
+        &shred_storage_type,
+        force_update_to_open,
+    );
+    let genesis_config = open_genesis_config_by(ledger_path, arg_matches);
+    let process_options = ProcessOptions {
+        new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+        halt_at_slot: Some(slot),
+        poh_verify: false,
+        allow_dead_slots: arg_matches.is_present("allow_dead_slots"),
+        runtime_config: RuntimeConfig {
+            bpf_jit: !arg_matches.is_present("no_bpf_jit"),
+            deactivated_features,
+            ..RuntimeConfig::default()
+        },
+        ..ProcessOptions::default()
+    };
+    let (bank_forks, ..) = load_bank_forks(
+        arg_matches,
+        &genesis_config,
+        &blockstore,
+        process_options,
+        snapshot_archive_path,
+        incremental_snapshot_archive_path,
+    )
+    .unwrap_or_else(|err| {
+        eprintln!("Failed to replay {ledger_path:?}: {err:?}");
+        exit(1);
+    });
+    let bank = bank_forks
+        .read()
+        .unwrap()
+        .get(slot)
+        .filter(|bank| bank.is_frozen())
+        .unwrap_or_else(|| {
+            eprintln!("Slot {slot} of {ledger_path:?} was not replayed");
+            exit(1);
+        });
+    BankHashDetails::new(&bank, &blockstore)
+}
+
 fn load_bank_forks(
     arg_matches: &ArgMatches,
     genesis_config: &GenesisConfig,
@@ -1257,6 +1410,149 @@ fn open_genesis_config_by(ledger_path: &Path, matches: &ArgMatches<'_>) -> Genes
     open_genesis_config(ledger_path, max_genesis_archive_unpacked_size)
 }
 
+/// Loads the bank of `slot`, replaying the ledger up to it, or exits
+#[allow(clippy::too_many_arguments)]
+fn load_bank_for_snapshot(
+    arg_matches: &ArgMatches,
+    genesis_config: &GenesisConfig,
+    blockstore: &Blockstore,
+    slot: Slot,
+    new_hard_forks: Option<Vec<Slot>>,
+    accounts_db_config: Option<AccountsDbConfig>,
+    snapshot_archive_path: Option<PathBuf>,
+    incremental_snapshot_archive_path: Option<PathBuf>,
+) -> (Arc<Bank>, Option<StartingSnapshotHashes>) {
+    match load_bank_forks(
+        arg_matches,
+        genesis_config,
+        blockstore,
+        ProcessOptions {
+            new_hard_forks,
+            halt_at_slot: Some(slot),
+            poh_verify: false,
+            accounts_db_config,
+            ..ProcessOptions::default()
+        },
+        snapshot_archive_path,
+        incremental_snapshot_archive_path,
+    ) {
+        Ok((bank_forks, starting_snapshot_hashes)) => {
+            let bank = bank_forks.read().unwrap().get(slot).unwrap_or_else(|| {
+                eprintln!("Error: Slot {slot} is not available");
+                exit(1);
+            });
+            (bank, starting_snapshot_hashes)
+        }
+        Err(err) => {
+            eprintln!("Failed to load ledger: {err:?}");
+            exit(1);
+        }
+    }
+}
+
+/// Writes a snapshot archive of `bank` to `output_directory`, and prints it along with the
+/// shred version, or exits. The archive is an incremental snapshot on top of
+/// `full_snapshot_slot` if given, otherwise a full snapshot, which is first minimized to the
+/// accounts needed to replay `minimized_range` if given.
+#[allow(clippy::too_many_arguments)]
+fn write_snapshot_archive(
+    bank_snapshots_dir: &Path,
+    blockstore: &Blockstore,
+    genesis_config: &GenesisConfig,
+    bank: &Bank,
+    full_snapshot_slot: Option<Slot>,
+    minimized_range: Option<(Slot, Slot)>,
+    snapshot_version: SnapshotVersion,
+    snapshot_archive_format: ArchiveFormat,
+    output_directory: &Path,
+    maximum_full_snapshot_archives_to_retain: usize,
+    maximum_incremental_snapshot_archives_to_retain: usize,
+) {
+    if let Some((snapshot_slot, ending_slot)) = minimized_range {
+        minimize_bank_for_snapshot(blockstore, bank, snapshot_slot, ending_slot);
+    }
+
+    let snapshot_type_str = if full_snapshot_slot.is_some() {
+        "incremental "
+    } else if minimized_range.is_some() {
+        "minimized "
+    } else {
+        ""
+    };
+    println!(
+        "Creating a version {} {}snapshot of slot {}",
+        snapshot_version,
+        snapshot_type_str,
+        bank.slot(),
+    );
+
+    if let Some(full_snapshot_slot) = full_snapshot_slot {
+        let incremental_snapshot_archive_info =
+            snapshot_utils::bank_to_incremental_snapshot_archive(
+                bank_snapshots_dir,
+                bank,
+                full_snapshot_slot,
+                Some(snapshot_version),
+                output_directory,
+                output_directory,
+                snapshot_archive_format,
+                maximum_full_snapshot_archives_to_retain,
+                maximum_incremental_snapshot_archives_to_retain,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("Unable to create incremental snapshot: {err}");
+                exit(1);
+            });
+
+        println!(
+            "Successfully created incremental snapshot for slot {}, hash {}, base slot: {}: {}",
+            bank.slot(),
+            bank.hash(),
+            full_snapshot_slot,
+            incremental_snapshot_archive_info.path().display(),
+        );
+    } else {
+        let full_snapshot_archive_info = snapshot_utils::bank_to_full_snapshot_archive(
+            bank_snapshots_dir,
+            bank,
+            Some(snapshot_version),
+            output_directory,
+            output_directory,
+            snapshot_archive_format,
+            maximum_full_snapshot_archives_to_retain,
+            maximum_incremental_snapshot_archives_to_retain,
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Unable to create snapshot: {err}");
+            exit(1);
+        });
+
+        println!(
+            "Successfully created snapshot for slot {}, hash {}: {}",
+            bank.slot(),
+            bank.hash(),
+            full_snapshot_archive_info.path().display(),
+        );
+
+        if let Some((snapshot_slot, ending_slot)) = minimized_range {
+            let starting_epoch = bank.epoch_schedule().get_epoch(snapshot_slot);
+            let ending_epoch = bank.epoch_schedule().get_epoch(ending_slot);
+            if starting_epoch != ending_epoch {
+                warn!("Minimized snapshot range crosses epoch boundary ({} to {}). Bank hashes after {} will not match replays from a full snapshot",
+                    starting_epoch, ending_epoch, bank.epoch_schedule().get_last_slot_in_epoch(starting_epoch));
+            }
+        }
+    }
+
+    println!(
+        "Shred version: {}",
+        compute_shred_version(
+            &genesis_config.hash(),
+            Some(&bank.hard_forks().read().unwrap())
+        )
+    );
+}
+
 /// Finds the accounts needed to replay slots `snapshot_slot` to `ending_slot`.
 /// Removes all other accounts from accounts_db, and updates the accounts hash
 /// and capitalization. This is used by the --minimize option in create-snapshot
@@ -1488,6 +1784,17 @@ fn main() {
     .default_value(default_max_incremental_snapshot_archives_to_retain)
     .help("The maximum number of incremental snapshot archives to hold on to when purging older snapshots.");
 
+    let compressible_column_arg = Arg::with_name("column")
+        .long("column")
+        .value_name("COLUMN")
+        .takes_value(true)
+        .required(true)
+        .possible_values(&[cf::TransactionStatus::NAME, cf::Rewards::NAME])
+        .help("Column whose values are compressed with the dictionary");
+    let default_dictionary_max_samples = &DEFAULT_DICTIONARY_MAX_SAMPLES.to_string();
+    let default_accounts_hash_bins = &PUBKEY_BINS_FOR_CALCULATING_HASHES.to_string();
+    let default_dictionary_size = &DEFAULT_DICTIONARY_SIZE.to_string();
+
     let geyser_plugin_args = Arg::with_name("geyser_plugin_config")
         .long("geyser-plugin-config")
         .value_name("FILE")
@@ -1588,6 +1895,9 @@ fn main() {
                 .help("Show additional information where supported"),
         )
         .bigtable_subcommand()
+        .export_subcommand()
+        .snapshot_accounts_subcommand()
+        .snapshot_diff_subcommand()
         .subcommand(
             SubCommand::with_name("print")
             .about("Print the ledger")
@@ -1812,6 +2122,89 @@ fn main() {
                     .takes_value(false)
                     .help("After verifying the ledger, print some information about the account stores"),
             )
+            .arg(
+                Arg::with_name("trace_transactions")
+                    .long("trace-transactions")
+                    .value_name("SIGNATURE_OR_ADDRESS")
+                    .takes_value(true)
+                    .multiple(true)
+                    .help("Trace the execution of the replayed transactions with the given \
+                           signature, or that reference the given address such as a program id. \
+                           Each trace is written as a JSON line that holds every instruction, \
+                           including CPIs, and syscall with their compute units, and the state \
+                           of the transaction accounts before and after execution"),
+            )
+            .arg(
+                Arg::with_name("trace_output")
+                    .long("trace-output")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .requires("trace_transactions")
+                    .help("Write the transaction traces to FILE [default: stdout]"),
+            )
+            .arg(
+                Arg::with_name("trace_vm")
+                    .long("trace-vm")
+                    .takes_value(false)
+                    .requires("trace_transactions")
+                    .help("Also trace the VM registers before each BPF instruction of the \
+                           traced transactions. Traced programs are interpreted rather than JIT \
+                           compiled"),
+            )
+        ).subcommand(
+            SubCommand::with_name("bank-hash-diff")
+            .about("Replay a slot and report how its bank hash differs from another replay \
+                    of the slot, in another ledger or dumped to a file")
+            .arg(&no_snapshot_arg)
+            .arg(&hard_forks_arg)
+            .arg(&no_bpf_jit_arg)
+            .arg(&allow_dead_slots_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(
+                Arg::with_name("slot")
+                    .long("slot")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Slot to replay"),
+            )
+            .arg(
+                Arg::with_name("other_ledger")
+                    .long("other-ledger")
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .conflicts_with("against")
+                    .help("Also replay the slot in the ledger in DIR, and compare both replays"),
+            )
+            .arg(
+                Arg::with_name("against")
+                    .long("against")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .help("Compare the replay with the one dumped to FILE by --dump, such as by \
+                           another ledger-tool build or with another feature set"),
+            )
+            .arg(
+                Arg::with_name("deactivate_feature")
+                    .long("deactivate-feature")
+                    .value_name("FEATURE_PUBKEY")
+                    .validator(is_pubkey)
+                    .takes_value(true)
+                    .multiple(true)
+                    .help("Keep this feature inactive in the replay of the slot in --ledger, \
+                           from the genesis or snapshot bank on, even if it was activated"),
+            )
+            .arg(
+                Arg::with_name("dump")
+                    .long("dump")
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .required_unless_one(&["other_ledger", "against"])
+                    .help("Write the details of the replay to FILE: the bank hash inputs and \
+                           every account stored in the slot, with its hash and the transactions \
+                           that wrote it"),
+            )
         ).subcommand(
             SubCommand::with_name("graph")
             .about("Create a Graphviz rendering of the ledger")
@@ -2018,6 +2411,111 @@ fn main() {
                     .help("Snapshot archive format to use.")
                     .conflicts_with("no_snapshot")
             )
+        ).subcommand(
+            SubCommand::with_name("extract")
+            .about("Extract a slot range into a new standalone ledger, with the genesis, \
+                    a snapshot at the starting slot and the data and coding shreds of the range, \
+                    that can be replayed with the verify command")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&accounts_db_skip_initial_hash_calc_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(&snapshot_version_arg)
+            .arg(&geyser_plugin_args)
+            .arg(
+                Arg::with_name("starting_slot")
+                    .long("start")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Slot of the snapshot of the new ledger")
+            )
+            .arg(
+                Arg::with_name("ending_slot")
+                    .long("end")
+                    .value_name("SLOT")
+                    .validator(is_slot)
+                    .takes_value(true)
+                    .required(true)
+                    .help("Last slot of the shreds copied into the new ledger")
+            )
+            .arg(
+                Arg::with_name("output_directory")
+                    .long("out")
+                    .value_name("DIR")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Empty or new directory to create the new ledger in")
+            )
+            .arg(
+                Arg::with_name("minimized")
+                    .long("minimized")
+                    .takes_value(false)
+                    .help("Minimize the snapshot to the accounts needed to replay the slot range")
+            )
+            .arg(
+                Arg::with_name("snapshot_archive_format")
+                    .long("snapshot-archive-format")
+                    .possible_values(SUPPORTED_ARCHIVE_COMPRESSION)
+                    .default_value(DEFAULT_ARCHIVE_COMPRESSION)
+                    .value_name("ARCHIVE_TYPE")
+                    .takes_value(true)
+                    .help("Snapshot archive format to use.")
+            )
+        ).subcommand(
+            SubCommand::with_name("import")
+            .about("Import shreds from files into the ledger, verifying them against the \
+                    leader schedule of the ledger's snapshot")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(
+                Arg::with_name("source")
+                    .index(1)
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .multiple(true)
+                    .required(true)
+                    .help("Files to import, directories are expanded to the files they contain")
+            )
+            .arg(
+                Arg::with_name("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .takes_value(true)
+                    .possible_values(ImportFormat::VALUES)
+                    .default_value("shreds")
+                    .help("Format of the source files: \
+                           shreds: raw shred payloads, each preceded by its length as a little-endian u16; \
+                           blocks: protobuf encoded confirmed blocks, as stored in Bigtable, \
+                           one per file named after its slot, re-shredded deterministically; \
+                           pcap: pcap captures of the traffic to the TVU port")
+            )
+            .arg(
+                Arg::with_name("tvu_port")
+                    .long("tvu-port")
+                    .value_name("PORT")
+                    .takes_value(true)
+                    .validator(is_parsable::<u16>)
+                    .help("Only import the datagrams of pcap captures sent to this port")
+            )
+            .arg(
+                Arg::with_name("skip_signature_verification")
+                    .long("skip-signature-verification")
+                    .takes_value(false)
+                    .help("Import shreds without verifying that they are signed by the slot leader. \
+                           Re-shredded blocks are never verified, as they cannot be signed by the leader")
+            )
+            .arg(
+                Arg::with_name("mark_roots")
+                    .long("mark-roots")
+                    .takes_value(false)
+                    .help("After importing, mark the highest full slot and its ancestors \
+                           down to the snapshot slot as roots")
+            )
         ).subcommand(
             SubCommand::with_name("accounts")
             .about("Print account stats and contents after processing the ledger")
@@ -2045,6 +2543,96 @@ fn main() {
                 .help("Do not print account data when printing account contents."),
             )
             .arg(&max_genesis_archive_unpacked_size_arg)
+        ).subcommand(
+            SubCommand::with_name("export-account-set")
+            .about("Export accounts to an account set file after processing the ledger, \
+                    to be loaded by solana-genesis, solana-test-validator or ProgramTest")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&halt_at_slot_arg)
+            .arg(&hard_forks_arg)
+            .arg(&geyser_plugin_args)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(
+                Arg::with_name("owner")
+                    .long("owner")
+                    .takes_value(true)
+                    .value_name("PUBKEY")
+                    .multiple(true)
+                    .validator(is_pubkey)
+                    .help("Export all the accounts owned by this program"),
+            )
+            .arg(
+                Arg::with_name("account")
+                    .long("account")
+                    .takes_value(true)
+                    .value_name("PUBKEY")
+                    .multiple(true)
+                    .validator(is_pubkey)
+                    .help("Export this account. The program data account of an upgradeable \
+                           program is exported along with the program"),
+            )
+            .arg(
+                Arg::with_name("output_file")
+                    .long("output-file")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("Write the account set to this file"),
+            )
+        ).subcommand(
+            SubCommand::with_name("accounts-hash")
+            .about("Calculate the accounts hash of the working bank after processing the ledger \
+                    and dump the hash, lamports and number of accounts of each pubkey bin")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&halt_at_slot_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(
+                Arg::with_name("bins")
+                    .long("bins")
+                    .takes_value(true)
+                    .value_name("NUMBER")
+                    .default_value(default_accounts_hash_bins)
+                    .validator(is_accounts_hash_bins)
+                    .help("Number of pubkey bins to break the accounts hash down into"),
+            )
+            .arg(
+                Arg::with_name("include_accounts_in_bin")
+                    .long("include-accounts-in-bin")
+                    .takes_value(true)
+                    .value_name("BIN")
+                    .multiple(true)
+                    .validator(is_parsable::<usize>)
+                    .help("Dump the lamports and hash of each account in this bin"),
+            )
+            .arg(
+                Arg::with_name("output_file")
+                    .long("output-file")
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .help("Write the dump to this file [default: stdout]"),
+            )
+        ).subcommand(
+            SubCommand::with_name("accounts-hash-diff")
+            .about("Compare two dumps written by the accounts-hash command")
+            .arg(
+                Arg::with_name("dump1")
+                    .index(1)
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("The first dump"),
+            )
+            .arg(
+                Arg::with_name("dump2")
+                    .index(2)
+                    .takes_value(true)
+                    .value_name("FILE")
+                    .required(true)
+                    .help("The second dump"),
+            )
         ).subcommand(
             SubCommand::with_name("capitalization")
             .about("Print capitalization (aka, total supply) while checksumming it")
@@ -2186,11 +2774,56 @@ fn main() {
                 )
         )
         .subcommand(
-            SubCommand::with_name("repair-roots")
-                .about("Traverses the AncestorIterator backward from a last known root \
-                        to restore missing roots to the Root column")
+            SubCommand::with_name("program-instruction-signatures")
+                .about("Output the most recent rooted transactions that invoked a program, \
+                        from the program instruction index of the ledger")
                 .arg(
-                    Arg::with_name("start_root")
+                    Arg::with_name("program_id")
+                        .index(1)
+                        .value_name("PROGRAM_ID")
+                        .takes_value(true)
+                        .validator(is_pubkey)
+                        .required(true)
+                        .help("Program id to look up"),
+                )
+                .arg(
+                    Arg::with_name("discriminator")
+                        .long("discriminator")
+                        .value_name("BASE58")
+                        .takes_value(true)
+                        .help("Only output the transactions invoking the program with \
+                               instruction data starting with these bytes, at most 8"),
+                )
+                .arg(
+                    Arg::with_name("before")
+                        .long("before")
+                        .value_name("SIGNATURE")
+                        .takes_value(true)
+                        .help("Start searching backwards from this transaction"),
+                )
+                .arg(
+                    Arg::with_name("until")
+                        .long("until")
+                        .value_name("SIGNATURE")
+                        .takes_value(true)
+                        .help("Search until this transaction"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .value_name("NUM")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value("1000")
+                        .help("Maximum number of transactions in the output"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("repair-roots")
+                .about("Traverses the AncestorIterator backward from a last known root \
+                        to restore missing roots to the Root column")
+                .arg(
+                    Arg::with_name("start_root")
                         .long("before")
                         .value_name("NUM")
                         .takes_value(true)
@@ -2213,6 +2846,61 @@ fn main() {
                         .help("Override the maximum number of slots to check for root repair")
                 )
         )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("Cross-check the blockstore columns for inconsistencies: SlotMeta, Index \
+                        and ErasureMeta against the stored shreds, Root continuity, \
+                        TransactionStatusIndex ranges and AddressSignatures entries")
+                .arg(&starting_slot_arg)
+                .arg(&ending_slot_arg)
+                .arg(
+                    Arg::with_name("fix")
+                        .long("fix")
+                        .takes_value(false)
+                        .help("Repair the inconsistencies that can be safely re-derived, such as \
+                               SlotMeta and Index from the stored shreds. \
+                               Requires exclusive access to the ledger")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("train-compression-dictionary")
+                .about("Train a zstd dictionary from a sample of the values of a column, and \
+                        store it in the blockstore as a new dictionary version. New values of \
+                        the column are compressed with the latest dictionary")
+                .arg(&compressible_column_arg)
+                .arg(
+                    Arg::with_name("max_samples")
+                        .long("max-samples")
+                        .value_name("NUMBER")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value(default_dictionary_max_samples)
+                        .help("Maximum number of values to sample from the column")
+                )
+                .arg(
+                    Arg::with_name("dictionary_size")
+                        .long("dictionary-size")
+                        .value_name("BYTES")
+                        .takes_value(true)
+                        .validator(is_parsable::<usize>)
+                        .default_value(default_dictionary_size)
+                        .help("Maximum size of the dictionary")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("recompress")
+                .about("Rewrite the values of a column that are not compressed with its latest \
+                        dictionary, then compact the column. \
+                        Requires exclusive access to the ledger")
+                .arg(&compressible_column_arg)
+                .arg(
+                    Arg::with_name("skip_compaction")
+                        .long("skip-compaction")
+                        .takes_value(false)
+                        .help("Skip compacting the column, leaving the previous values \
+                               to be reclaimed by automatic compactions")
+                )
+        )
         .subcommand(
             SubCommand::with_name("analyze-storage")
                 .about("Output statistics in JSON format about \
@@ -2271,6 +2959,10 @@ fn main() {
 
     if let ("bigtable", Some(arg_matches)) = matches.subcommand() {
         bigtable_process_command(&ledger_path, arg_matches, &shred_storage_type)
+    } else if let ("snapshot-accounts", Some(arg_matches)) = matches.subcommand() {
+        snapshot_accounts_process_command(arg_matches)
+    } else if let ("snapshot-diff", Some(arg_matches)) = matches.subcommand() {
+        snapshot_diff_process_command(arg_matches)
     } else {
         let ledger_path = canonicalize_ledger_path(&ledger_path);
 
@@ -2561,6 +3253,16 @@ fn main() {
                     true,
                 );
             }
+            ("export", Some(arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Secondary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                export_process_command(&blockstore, arg_matches);
+            }
             ("dead-slots", Some(arg_matches)) => {
                 let blockstore = open_blockstore(
                     &ledger_path,
@@ -2760,6 +3462,8 @@ fn main() {
 
                 let debug_keys = pubkeys_of(arg_matches, "debug_key")
                     .map(|pubkeys| Arc::new(pubkeys.into_iter().collect::<HashSet<_>>()));
+                let (transaction_trace_config, trace_writer) =
+                    transaction_trace_config_of(arg_matches).unzip();
 
                 let process_options = ProcessOptions {
                     new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
@@ -2786,6 +3490,7 @@ fn main() {
                         bpf_jit: !arg_matches.is_present("no_bpf_jit"),
                         ..RuntimeConfig::default()
                     },
+                    transaction_trace_config,
                     ..ProcessOptions::default()
                 };
                 let print_accounts_stats = arg_matches.is_present("print_accounts_stats");
@@ -2811,6 +3516,17 @@ fn main() {
                     eprintln!("Ledger verification failed: {err:?}");
                     exit(1);
                 });
+                if let Some(trace_writer) = trace_writer {
+                    // drop the sender shared by the banks so that the writer returns
+                    *bank_forks
+                        .read()
+                        .unwrap()
+                        .root_bank()
+                        .transaction_trace_config
+                        .write()
+                        .unwrap() = TransactionTraceConfig::default();
+                    trace_writer.join().unwrap();
+                }
                 if print_accounts_stats {
                     let working_bank = bank_forks.read().unwrap().working_bank();
                     working_bank.print_accounts_stats();
@@ -2818,6 +3534,69 @@ fn main() {
                 exit_signal.store(true, Ordering::Relaxed);
                 system_monitor_service.join().unwrap();
             }
+            ("bank-hash-diff", Some(arg_matches)) => {
+                let slot = value_t_or_exit!(arg_matches, "slot", Slot);
+                let deactivated_features = pubkeys_of(arg_matches, "deactivate_feature")
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect();
+                let details = replay_bank_hash_details(
+                    arg_matches,
+                    &ledger_path,
+                    slot,
+                    deactivated_features,
+                    snapshot_archive_path,
+                    incremental_snapshot_archive_path,
+                    wal_recovery_mode,
+                    force_update_to_open,
+                );
+                if let Some(path) = arg_matches.value_of("dump") {
+                    details
+                        .write_to_file(Path::new(path))
+                        .unwrap_or_else(|err| {
+                            eprintln!("{err}");
+                            exit(1);
+                        });
+                    println!("Wrote the bank hash details of slot {slot} to {path}");
+                }
+
+                let (other_label, other_details) =
+                    if let Some(other_ledger_path) = arg_matches.value_of("other_ledger") {
+                        let other_ledger_path =
+                            canonicalize_ledger_path(&PathBuf::from(other_ledger_path));
+                        let other_details = replay_bank_hash_details(
+                            arg_matches,
+                            &other_ledger_path,
+                            slot,
+                            HashSet::default(),
+                            None,
+                            None,
+                            wal_recovery_mode,
+                            force_update_to_open,
+                        );
+                        (other_ledger_path.display().to_string(), other_details)
+                    } else if let Some(path) = arg_matches.value_of("against") {
+                        let other_details = BankHashDetails::read_from_file(Path::new(path))
+                            .unwrap_or_else(|err| {
+                                eprintln!("{err}");
+                                exit(1);
+                            });
+                        (path.to_string(), other_details)
+                    } else {
+                        return;
+                    };
+                let diff = BankHashDiff::new(
+                    ledger_path.display().to_string(),
+                    &details,
+                    other_label,
+                    &other_details,
+                );
+                let output_format = OutputFormat::from_matches(arg_matches, "output_format", false);
+                println!("{}", output_format.formatted_string(&diff));
+                if !diff.is_empty() {
+                    exit(1);
+                }
+            }
             ("graph", Some(arg_matches)) => {
                 let output_file = value_t_or_exit!(arg_matches, "graph_filename", String);
                 let graph_config = GraphConfig {
@@ -3021,361 +3800,580 @@ fn main() {
                     ..AccountsDbConfig::default()
                 });
 
-                match load_bank_forks(
+                let (mut bank, starting_snapshot_hashes) = load_bank_for_snapshot(
                     arg_matches,
                     &genesis_config,
                     &blockstore,
-                    ProcessOptions {
-                        new_hard_forks,
-                        halt_at_slot: Some(snapshot_slot),
-                        poh_verify: false,
-                        accounts_db_config,
-                        ..ProcessOptions::default()
-                    },
+                    snapshot_slot,
+                    new_hard_forks,
+                    accounts_db_config,
                     snapshot_archive_path,
                     incremental_snapshot_archive_path,
-                ) {
-                    Ok((bank_forks, starting_snapshot_hashes)) => {
-                        let mut bank = bank_forks
-                            .read()
-                            .unwrap()
-                            .get(snapshot_slot)
-                            .unwrap_or_else(|| {
-                                eprintln!("Error: Slot {snapshot_slot} is not available");
-                                exit(1);
-                            });
-
-                        let child_bank_required = rent_burn_percentage.is_ok()
-                            || hashes_per_tick.is_some()
-                            || remove_stake_accounts
-                            || !accounts_to_remove.is_empty()
-                            || !vote_accounts_to_destake.is_empty()
-                            || faucet_pubkey.is_some()
-                            || bootstrap_validator_pubkeys.is_some();
-
-                        if child_bank_required {
-                            let mut child_bank =
-                                Bank::new_from_parent(&bank, bank.collector_id(), bank.slot() + 1);
+                );
 
-                            if let Ok(rent_burn_percentage) = rent_burn_percentage {
-                                child_bank.set_rent_burn_percentage(rent_burn_percentage);
-                            }
+                let child_bank_required = rent_burn_percentage.is_ok()
+                    || hashes_per_tick.is_some()
+                    || remove_stake_accounts
+                    || !accounts_to_remove.is_empty()
+                    || !vote_accounts_to_destake.is_empty()
+                    || faucet_pubkey.is_some()
+                    || bootstrap_validator_pubkeys.is_some();
 
-                            if let Some(hashes_per_tick) = hashes_per_tick {
-                                child_bank.set_hashes_per_tick(match hashes_per_tick {
-                                    // Note: Unlike `solana-genesis`, "auto" is not supported here.
-                                    "sleep" => None,
-                                    _ => {
-                                        Some(value_t_or_exit!(arg_matches, "hashes_per_tick", u64))
-                                    }
-                                });
-                            }
-                            bank = Arc::new(child_bank);
-                        }
+                if child_bank_required {
+                    let mut child_bank =
+                        Bank::new_from_parent(&bank, bank.collector_id(), bank.slot() + 1);
 
-                        if let Some(faucet_pubkey) = faucet_pubkey {
-                            bank.store_account(
-                                &faucet_pubkey,
-                                &AccountSharedData::new(faucet_lamports, 0, &system_program::id()),
-                            );
-                        }
+                    if let Ok(rent_burn_percentage) = rent_burn_percentage {
+                        child_bank.set_rent_burn_percentage(rent_burn_percentage);
+                    }
 
-                        if remove_stake_accounts {
-                            for (address, mut account) in bank
-                                .get_program_accounts(&stake::program::id(), &ScanConfig::default())
-                                .unwrap()
-                                .into_iter()
-                            {
-                                account.set_lamports(0);
-                                bank.store_account(&address, &account);
-                            }
-                        }
+                    if let Some(hashes_per_tick) = hashes_per_tick {
+                        child_bank.set_hashes_per_tick(match hashes_per_tick {
+                            // Note: Unlike `solana-genesis`, "auto" is not supported here.
+                            "sleep" => None,
+                            _ => Some(value_t_or_exit!(arg_matches, "hashes_per_tick", u64)),
+                        });
+                    }
+                    bank = Arc::new(child_bank);
+                }
 
-                        for address in accounts_to_remove {
-                            let mut account = bank.get_account(&address).unwrap_or_else(|| {
-                                eprintln!(
-                                    "Error: Account does not exist, unable to remove it: {address}"
-                                );
-                                exit(1);
-                            });
+                if let Some(faucet_pubkey) = faucet_pubkey {
+                    bank.store_account(
+                        &faucet_pubkey,
+                        &AccountSharedData::new(faucet_lamports, 0, &system_program::id()),
+                    );
+                }
 
-                            account.set_lamports(0);
-                            bank.store_account(&address, &account);
-                        }
+                if remove_stake_accounts {
+                    for (address, mut account) in bank
+                        .get_program_accounts(&stake::program::id(), &ScanConfig::default())
+                        .unwrap()
+                        .into_iter()
+                    {
+                        account.set_lamports(0);
+                        bank.store_account(&address, &account);
+                    }
+                }
 
-                        if !vote_accounts_to_destake.is_empty() {
-                            for (address, mut account) in bank
-                                .get_program_accounts(&stake::program::id(), &ScanConfig::default())
-                                .unwrap()
-                                .into_iter()
-                            {
-                                if let Ok(StakeState::Stake(meta, stake)) = account.state() {
-                                    if vote_accounts_to_destake
-                                        .contains(&stake.delegation.voter_pubkey)
-                                    {
-                                        if verbose_level > 0 {
-                                            warn!(
-                                                "Undelegating stake account {} from {}",
-                                                address, stake.delegation.voter_pubkey,
-                                            );
-                                        }
-                                        account.set_state(&StakeState::Initialized(meta)).unwrap();
-                                        bank.store_account(&address, &account);
-                                    }
-                                }
-                            }
-                        }
+                for address in accounts_to_remove {
+                    let mut account = bank.get_account(&address).unwrap_or_else(|| {
+                        eprintln!("Error: Account does not exist, unable to remove it: {address}");
+                        exit(1);
+                    });
 
-                        if let Some(bootstrap_validator_pubkeys) = bootstrap_validator_pubkeys {
-                            assert_eq!(bootstrap_validator_pubkeys.len() % 3, 0);
+                    account.set_lamports(0);
+                    bank.store_account(&address, &account);
+                }
 
-                            // Ensure there are no duplicated pubkeys in the --bootstrap-validator list
-                            {
-                                let mut v = bootstrap_validator_pubkeys.clone();
-                                v.sort();
-                                v.dedup();
-                                if v.len() != bootstrap_validator_pubkeys.len() {
-                                    eprintln!(
-                                        "Error: --bootstrap-validator pubkeys cannot be duplicated"
+                if !vote_accounts_to_destake.is_empty() {
+                    for (address, mut account) in bank
+                        .get_program_accounts(&stake::program::id(), &ScanConfig::default())
+                        .unwrap()
+                        .into_iter()
+                    {
+                        if let Ok(StakeState::Stake(meta, stake)) = account.state() {
+                            if vote_accounts_to_destake.contains(&stake.delegation.voter_pubkey) {
+                                if verbose_level > 0 {
+                                    warn!(
+                                        "Undelegating stake account {} from {}",
+                                        address, stake.delegation.voter_pubkey,
                                     );
-                                    exit(1);
                                 }
-                            }
-
-                            // Delete existing vote accounts
-                            for (address, mut account) in bank
-                                .get_program_accounts(
-                                    &solana_vote_program::id(),
-                                    &ScanConfig::default(),
-                                )
-                                .unwrap()
-                                .into_iter()
-                            {
-                                account.set_lamports(0);
+                                account.set_state(&StakeState::Initialized(meta)).unwrap();
                                 bank.store_account(&address, &account);
                             }
+                        }
+                    }
+                }
 
-                            // Add a new identity/vote/stake account for each of the provided bootstrap
-                            // validators
-                            let mut bootstrap_validator_pubkeys_iter =
-                                bootstrap_validator_pubkeys.iter();
-                            loop {
-                                let identity_pubkey = match bootstrap_validator_pubkeys_iter.next()
-                                {
-                                    None => break,
-                                    Some(identity_pubkey) => identity_pubkey,
-                                };
-                                let vote_pubkey = bootstrap_validator_pubkeys_iter.next().unwrap();
-                                let stake_pubkey = bootstrap_validator_pubkeys_iter.next().unwrap();
-
-                                bank.store_account(
-                                    identity_pubkey,
-                                    &AccountSharedData::new(
-                                        bootstrap_validator_lamports,
-                                        0,
-                                        &system_program::id(),
-                                    ),
-                                );
-
-                                let vote_account = vote_state::create_account_with_authorized(
-                                    identity_pubkey,
-                                    identity_pubkey,
-                                    identity_pubkey,
-                                    100,
-                                    VoteState::get_rent_exempt_reserve(&rent).max(1),
-                                );
-
-                                bank.store_account(
-                                    stake_pubkey,
-                                    &stake_state::create_account(
-                                        bootstrap_stake_authorized_pubkey
-                                            .as_ref()
-                                            .unwrap_or(identity_pubkey),
-                                        vote_pubkey,
-                                        &vote_account,
-                                        &rent,
-                                        bootstrap_validator_stake_lamports,
-                                    ),
-                                );
-                                bank.store_account(vote_pubkey, &vote_account);
-                            }
-
-                            // Warp ahead at least two epochs to ensure that the leader schedule will be
-                            // updated to reflect the new bootstrap validator(s)
-                            let minimum_warp_slot =
-                                genesis_config.epoch_schedule.get_first_slot_in_epoch(
-                                    genesis_config.epoch_schedule.get_epoch(snapshot_slot) + 2,
-                                );
+                if let Some(bootstrap_validator_pubkeys) = bootstrap_validator_pubkeys {
+                    assert_eq!(bootstrap_validator_pubkeys.len() % 3, 0);
 
-                            if let Some(warp_slot) = warp_slot {
-                                if warp_slot < minimum_warp_slot {
-                                    eprintln!(
-                                        "Error: --warp-slot too close.  Must be >= {minimum_warp_slot}"
-                                    );
-                                    exit(1);
-                                }
-                            } else {
-                                warn!("Warping to slot {}", minimum_warp_slot);
-                                warp_slot = Some(minimum_warp_slot);
-                            }
+                    // Ensure there are no duplicated pubkeys in the --bootstrap-validator list
+                    {
+                        let mut v = bootstrap_validator_pubkeys.clone();
+                        v.sort();
+                        v.dedup();
+                        if v.len() != bootstrap_validator_pubkeys.len() {
+                            eprintln!("Error: --bootstrap-validator pubkeys cannot be duplicated");
+                            exit(1);
                         }
+                    }
 
-                        if child_bank_required {
-                            while !bank.is_complete() {
-                                bank.register_tick(&Hash::new_unique());
-                            }
-                        }
+                    // Delete existing vote accounts
+                    for (address, mut account) in bank
+                        .get_program_accounts(&solana_vote_program::id(), &ScanConfig::default())
+                        .unwrap()
+                        .into_iter()
+                    {
+                        account.set_lamports(0);
+                        bank.store_account(&address, &account);
+                    }
 
-                        bank.set_capitalization();
-
-                        let bank = if let Some(warp_slot) = warp_slot {
-                            // need to flush the write cache in order to use Storages to calculate
-                            // the accounts hash, and need to root `bank` before flushing the cache
-                            bank.rc.accounts.accounts_db.add_root(bank.slot());
-                            bank.force_flush_accounts_cache();
-                            Arc::new(Bank::warp_from_parent(
-                                &bank,
-                                bank.collector_id(),
-                                warp_slot,
-                                CalcAccountsHashDataSource::Storages,
-                            ))
-                        } else {
-                            bank
+                    // Add a new identity/vote/stake account for each of the provided bootstrap
+                    // validators
+                    let mut bootstrap_validator_pubkeys_iter = bootstrap_validator_pubkeys.iter();
+                    loop {
+                        let identity_pubkey = match bootstrap_validator_pubkeys_iter.next() {
+                            None => break,
+                            Some(identity_pubkey) => identity_pubkey,
                         };
+                        let vote_pubkey = bootstrap_validator_pubkeys_iter.next().unwrap();
+                        let stake_pubkey = bootstrap_validator_pubkeys_iter.next().unwrap();
+
+                        bank.store_account(
+                            identity_pubkey,
+                            &AccountSharedData::new(
+                                bootstrap_validator_lamports,
+                                0,
+                                &system_program::id(),
+                            ),
+                        );
 
-                        if is_minimized {
-                            minimize_bank_for_snapshot(
-                                &blockstore,
-                                &bank,
-                                snapshot_slot,
-                                ending_slot.unwrap(),
-                            );
-                        }
-
-                        println!(
-                            "Creating a version {} {}snapshot of slot {}",
-                            snapshot_version,
-                            snapshot_type_str,
-                            bank.slot(),
+                        let vote_account = vote_state::create_account_with_authorized(
+                            identity_pubkey,
+                            identity_pubkey,
+                            identity_pubkey,
+                            100,
+                            VoteState::get_rent_exempt_reserve(&rent).max(1),
                         );
 
-                        if is_incremental {
-                            if starting_snapshot_hashes.is_none() {
-                                eprintln!("Unable to create incremental snapshot without a base full snapshot");
-                                exit(1);
-                            }
-                            let full_snapshot_slot = starting_snapshot_hashes.unwrap().full.hash.0;
-                            if bank.slot() <= full_snapshot_slot {
-                                eprintln!(
-                                    "Unable to create incremental snapshot: Slot must be greater than full snapshot slot. slot: {}, full snapshot slot: {}",
-                                    bank.slot(),
-                                    full_snapshot_slot,
-                                );
-                                exit(1);
-                            }
+                        bank.store_account(
+                            stake_pubkey,
+                            &stake_state::create_account(
+                                bootstrap_stake_authorized_pubkey
+                                    .as_ref()
+                                    .unwrap_or(identity_pubkey),
+                                vote_pubkey,
+                                &vote_account,
+                                &rent,
+                                bootstrap_validator_stake_lamports,
+                            ),
+                        );
+                        bank.store_account(vote_pubkey, &vote_account);
+                    }
 
-                            let incremental_snapshot_archive_info =
-                                snapshot_utils::bank_to_incremental_snapshot_archive(
-                                    ledger_path,
-                                    &bank,
-                                    full_snapshot_slot,
-                                    Some(snapshot_version),
-                                    output_directory.clone(),
-                                    output_directory,
-                                    snapshot_archive_format,
-                                    maximum_full_snapshot_archives_to_retain,
-                                    maximum_incremental_snapshot_archives_to_retain,
-                                )
-                                .unwrap_or_else(|err| {
-                                    eprintln!("Unable to create incremental snapshot: {err}");
-                                    exit(1);
-                                });
+                    // Warp ahead at least two epochs to ensure that the leader schedule will be
+                    // updated to reflect the new bootstrap validator(s)
+                    let minimum_warp_slot = genesis_config.epoch_schedule.get_first_slot_in_epoch(
+                        genesis_config.epoch_schedule.get_epoch(snapshot_slot) + 2,
+                    );
 
-                            println!(
-                                "Successfully created incremental snapshot for slot {}, hash {}, base slot: {}: {}",
-                                bank.slot(),
-                                bank.hash(),
-                                full_snapshot_slot,
-                                incremental_snapshot_archive_info.path().display(),
+                    if let Some(warp_slot) = warp_slot {
+                        if warp_slot < minimum_warp_slot {
+                            eprintln!(
+                                "Error: --warp-slot too close.  Must be >= {minimum_warp_slot}"
                             );
-                        } else {
-                            let full_snapshot_archive_info =
-                                snapshot_utils::bank_to_full_snapshot_archive(
-                                    ledger_path,
-                                    &bank,
-                                    Some(snapshot_version),
-                                    output_directory.clone(),
-                                    output_directory,
-                                    snapshot_archive_format,
-                                    maximum_full_snapshot_archives_to_retain,
-                                    maximum_incremental_snapshot_archives_to_retain,
-                                )
-                                .unwrap_or_else(|err| {
-                                    eprintln!("Unable to create snapshot: {err}");
-                                    exit(1);
-                                });
+                            exit(1);
+                        }
+                    } else {
+                        warn!("Warping to slot {}", minimum_warp_slot);
+                        warp_slot = Some(minimum_warp_slot);
+                    }
+                }
 
-                            println!(
-                                "Successfully created snapshot for slot {}, hash {}: {}",
-                                bank.slot(),
-                                bank.hash(),
-                                full_snapshot_archive_info.path().display(),
-                            );
+                if child_bank_required {
+                    while !bank.is_complete() {
+                        bank.register_tick(&Hash::new_unique());
+                    }
+                }
 
-                            if is_minimized {
-                                let starting_epoch = bank.epoch_schedule().get_epoch(snapshot_slot);
-                                let ending_epoch =
-                                    bank.epoch_schedule().get_epoch(ending_slot.unwrap());
-                                if starting_epoch != ending_epoch {
-                                    warn!("Minimized snapshot range crosses epoch boundary ({} to {}). Bank hashes after {} will not match replays from a full snapshot",
-                                        starting_epoch, ending_epoch, bank.epoch_schedule().get_last_slot_in_epoch(starting_epoch));
-                                }
-                            }
-                        }
+                bank.set_capitalization();
+
+                let bank = if let Some(warp_slot) = warp_slot {
+                    // need to flush the write cache in order to use Storages to calculate
+                    // the accounts hash, and need to root `bank` before flushing the cache
+                    bank.rc.accounts.accounts_db.add_root(bank.slot());
+                    bank.force_flush_accounts_cache();
+                    Arc::new(Bank::warp_from_parent(
+                        &bank,
+                        bank.collector_id(),
+                        warp_slot,
+                        CalcAccountsHashDataSource::Storages,
+                    ))
+                } else {
+                    bank
+                };
 
-                        println!(
-                            "Shred version: {}",
-                            compute_shred_version(
-                                &genesis_config.hash(),
-                                Some(&bank.hard_forks().read().unwrap())
-                            )
+                let full_snapshot_slot = if is_incremental {
+                    if starting_snapshot_hashes.is_none() {
+                        eprintln!(
+                            "Unable to create incremental snapshot without a base full snapshot"
                         );
+                        exit(1);
                     }
-                    Err(err) => {
-                        eprintln!("Failed to load ledger: {err:?}");
+                    let full_snapshot_slot = starting_snapshot_hashes.unwrap().full.hash.0;
+                    if bank.slot() <= full_snapshot_slot {
+                        eprintln!(
+                            "Unable to create incremental snapshot: Slot must be greater than full snapshot slot. slot: {}, full snapshot slot: {}",
+                            bank.slot(),
+                            full_snapshot_slot,
+                        );
                         exit(1);
                     }
-                }
-            }
-            ("accounts", Some(arg_matches)) => {
-                let halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
-                let process_options = ProcessOptions {
-                    new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
-                    halt_at_slot,
-                    poh_verify: false,
-                    ..ProcessOptions::default()
+                    Some(full_snapshot_slot)
+                } else {
+                    None
                 };
-                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
-                let include_sysvars = arg_matches.is_present("include_sysvars");
-                let blockstore = open_blockstore(
+
+                write_snapshot_archive(
                     &ledger_path,
-                    AccessType::Secondary,
-                    wal_recovery_mode,
-                    &shred_storage_type,
-                    force_update_to_open,
-                );
-                let (bank_forks, ..) = load_bank_forks(
-                    arg_matches,
-                    &genesis_config,
                     &blockstore,
-                    process_options,
-                    snapshot_archive_path,
-                    incremental_snapshot_archive_path,
-                )
-                .unwrap_or_else(|err| {
-                    eprintln!("Failed to load ledger: {err:?}");
+                    &genesis_config,
+                    &bank,
+                    full_snapshot_slot,
+                    ending_slot.map(|ending_slot| (snapshot_slot, ending_slot)),
+                    snapshot_version,
+                    snapshot_archive_format,
+                    &output_directory,
+                    maximum_full_snapshot_archives_to_retain,
+                    maximum_incremental_snapshot_archives_to_retain,
+                );
+            }
+            ("extract", Some(arg_matches)) => {
+                let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+                let ending_slot = value_t_or_exit!(arg_matches, "ending_slot", Slot);
+                let output_directory = value_t_or_exit!(arg_matches, "output_directory", PathBuf);
+                let is_minimized = arg_matches.is_present("minimized");
+                if ending_slot <= starting_slot {
+                    eprintln!(
+                        "Error: ending slot ({ending_slot}) must be greater than starting slot ({starting_slot})"
+                    );
                     exit(1);
-                });
+                }
+                if fs::read_dir(&output_directory)
+                    .map(|mut entries| entries.next().is_some())
+                    .unwrap_or(false)
+                {
+                    eprintln!(
+                        "Error: output directory {} is not empty",
+                        output_directory.display()
+                    );
+                    exit(1);
+                }
+                let snapshot_version = arg_matches.value_of("snapshot_version").map_or(
+                    SnapshotVersion::default(),
+                    |s| {
+                        s.parse::<SnapshotVersion>().unwrap_or_else(|e| {
+                            eprintln!("Error: {e}");
+                            exit(1)
+                        })
+                    },
+                );
+                let snapshot_archive_format = {
+                    let archive_format_str =
+                        value_t_or_exit!(arg_matches, "snapshot_archive_format", String);
+                    ArchiveFormat::from_cli_arg(&archive_format_str).unwrap_or_else(|| {
+                        panic!("Archive format not recognized: {archive_format_str}")
+                    })
+                };
+
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Secondary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                if !blockstore.slot_range_connected(starting_slot, ending_slot) {
+                    eprintln!(
+                        "Error: slots {starting_slot} to {ending_slot} are not connected by full slots"
+                    );
+                    exit(1);
+                }
+
+                genesis_config
+                    .write(&output_directory)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Unable to write genesis: {err}");
+                        exit(1);
+                    });
+
+                let target = open_blockstore(
+                    &output_directory,
+                    AccessType::Primary,
+                    None,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let copied = copy_slot_range(&blockstore, &target, starting_slot, ending_slot)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Unable to copy slots {starting_slot} to {ending_slot}: {err}");
+                        exit(1);
+                    });
+                drop(target);
+                println!(
+                    "Copied the shreds of {} slots and {} roots",
+                    copied.num_slots, copied.num_roots
+                );
+
+                let (bank, _) = load_bank_for_snapshot(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    starting_slot,
+                    hardforks_of(arg_matches, "hard_forks"),
+                    Some(AccountsDbConfig {
+                        skip_initial_hash_calc: arg_matches
+                            .is_present("accounts_db_skip_initial_hash_calculation"),
+                        ..AccountsDbConfig::default()
+                    }),
+                    snapshot_archive_path,
+                    incremental_snapshot_archive_path,
+                );
+                write_snapshot_archive(
+                    &output_directory,
+                    &blockstore,
+                    &genesis_config,
+                    &bank,
+                    None,
+                    is_minimized.then_some((starting_slot, ending_slot)),
+                    snapshot_version,
+                    snapshot_archive_format,
+                    &output_directory,
+                    snapshot_utils::DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                    snapshot_utils::DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                );
+                println!(
+                    "Replay the extracted ledger with: solana-ledger-tool --ledger {} verify --halt-at-slot {ending_slot}",
+                    output_directory.display(),
+                );
+            }
+            ("import", Some(arg_matches)) => {
+                let format = value_t_or_exit!(arg_matches, "format", ImportFormat);
+                let tvu_port = value_t!(arg_matches, "tvu_port", u16).ok();
+                let skip_signature_verification = format == ImportFormat::Blocks
+                    || arg_matches.is_present("skip_signature_verification");
+                let source_paths = values_t_or_exit!(arg_matches, "source", PathBuf);
+                let source_files = source_files(&source_paths).unwrap_or_else(|err| {
+                    eprintln!("Unable to list source files: {err}");
+                    exit(1);
+                });
+
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Primary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let process_options = ProcessOptions {
+                    new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                    halt_at_slot: Some(0),
+                    poh_verify: false,
+                    ..ProcessOptions::default()
+                };
+                let bank = match load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    process_options,
+                    snapshot_archive_path,
+                    incremental_snapshot_archive_path,
+                ) {
+                    Ok((bank_forks, ..)) => bank_forks.read().unwrap().root_bank(),
+                    Err(err) => {
+                        eprintln!("Failed to load ledger: {err:?}");
+                        exit(1);
+                    }
+                };
+                let leader_schedule_cache = LeaderScheduleCache::new_from_bank(&bank);
+                let shred_version = compute_shred_version(
+                    &genesis_config.hash(),
+                    Some(&bank.hard_forks().read().unwrap()),
+                );
+                let hashes_per_tick = bank.hashes_per_tick().unwrap_or(0);
+
+                let mut imported_slots = BTreeSet::new();
+                let mut num_imported = 0;
+                let mut num_malformed = 0;
+                let mut num_wrong_version = 0;
+                let mut num_unverified = 0;
+                let mut unknown_leader_slots = BTreeSet::new();
+                let mut num_unknown_leader = 0;
+                let mut import_shreds = |shreds: Vec<Shred>, path: &Path| {
+                    let shreds: Vec<_> = shreds
+                        .into_iter()
+                        .filter(|shred| {
+                            if shred.version() != shred_version {
+                                num_wrong_version += 1;
+                                return false;
+                            }
+                            if skip_signature_verification {
+                                return true;
+                            }
+                            // Leader schedules past the snapshot bank's leader schedule epoch
+                            // are unknown until the epochs before them are replayed
+                            let leader = match leader_schedule_cache
+                                .slot_leader_at(shred.slot(), Some(&bank))
+                            {
+                                Some(leader) => leader,
+                                None => {
+                                    num_unknown_leader += 1;
+                                    unknown_leader_slots.insert(shred.slot());
+                                    return false;
+                                }
+                            };
+                            let verified = shred.verify(&leader);
+                            if !verified {
+                                num_unverified += 1;
+                            }
+                            verified
+                        })
+                        .collect();
+                    num_imported += shreds.len();
+                    imported_slots.extend(shreds.iter().map(Shred::slot));
+                    blockstore
+                        .insert_shreds(shreds, Some(&leader_schedule_cache), false)
+                        .unwrap_or_else(|err| {
+                            eprintln!("Unable to insert shreds from {}: {err}", path.display());
+                            exit(1);
+                        });
+                };
+                for path in &source_files {
+                    match format {
+                        ImportFormat::Blocks => {
+                            let (slot, block) = read_confirmed_block(path).unwrap_or_else(|err| {
+                                eprintln!("Unable to read block: {err}");
+                                exit(1);
+                            });
+                            let shreds = shred_confirmed_block(
+                                slot,
+                                block,
+                                genesis_config.ticks_per_slot,
+                                hashes_per_tick,
+                                shred_version,
+                            )
+                            .unwrap_or_else(|err| {
+                                eprintln!("Unable to shred block: {err}");
+                                exit(1);
+                            });
+                            import_shreds(shreds, path);
+                        }
+                        ImportFormat::Shreds | ImportFormat::Pcap => {
+                            let payloads: Box<dyn Iterator<Item = std::io::Result<Vec<u8>>>> =
+                                if format == ImportFormat::Shreds {
+                                    read_shred_dump(path).map(|reader| Box::new(reader) as _)
+                                } else {
+                                    read_pcap(path, tvu_port).map(|reader| Box::new(reader) as _)
+                                }
+                                .unwrap_or_else(|err| {
+                                    eprintln!("Unable to read {}: {err}", path.display());
+                                    exit(1);
+                                });
+                            // Captures are read and inserted a batch of shreds at a time
+                            for payloads in &payloads.chunks(IMPORT_SHRED_BATCH_SIZE) {
+                                let mut shreds = Vec::with_capacity(IMPORT_SHRED_BATCH_SIZE);
+                                for payload in payloads {
+                                    let payload = payload.unwrap_or_else(|err| {
+                                        eprintln!("Unable to read {}: {err}", path.display());
+                                        exit(1);
+                                    });
+                                    match Shred::new_from_serialized_shred(payload) {
+                                        Ok(shred) => shreds.push(shred),
+                                        Err(_) => num_malformed += 1,
+                                    }
+                                }
+                                import_shreds(shreds, path);
+                            }
+                        }
+                    }
+                }
+                println!(
+                    "Imported {num_imported} shreds of {} slots from {} files",
+                    imported_slots.len(),
+                    source_files.len(),
+                );
+                if num_malformed + num_wrong_version + num_unverified > 0 {
+                    println!(
+                        "Skipped {num_malformed} malformed shreds, \
+                         {num_wrong_version} shreds of another shred version than {shred_version} \
+                         and {num_unverified} shreds not signed by their slot leader"
+                    );
+                }
+                if num_unknown_leader > 0 {
+                    eprintln!(
+                        "Error: skipped {num_unknown_leader} shreds of slots {} to {} whose leader \
+                         is unknown: the snapshot at slot {} only knows the leader schedules up to \
+                         epoch {}. Import these slots on top of a later snapshot, or pass \
+                         --skip-signature-verification",
+                        unknown_leader_slots.first().unwrap(),
+                        unknown_leader_slots.last().unwrap(),
+                        bank.slot(),
+                        bank.get_leader_schedule_epoch(bank.slot()),
+                    );
+                    exit(1);
+                }
+
+                if arg_matches.is_present("mark_roots") {
+                    let root_slot = bank.slot();
+                    let Some(highest_full_slot) = imported_slots
+                        .iter()
+                        .rev()
+                        .find(|slot| **slot > root_slot && blockstore.is_full(**slot))
+                        .copied()
+                    else {
+                        println!("No full slot above the snapshot slot {root_slot} was imported, no roots marked");
+                        return;
+                    };
+                    let mut roots: Vec<_> =
+                        AncestorIterator::new_inclusive(highest_full_slot, &blockstore)
+                            .take_while(|slot| *slot >= root_slot)
+                            .collect();
+                    if roots.pop() != Some(root_slot) {
+                        eprintln!(
+                            "Error: slot {highest_full_slot} does not descend from the snapshot slot {root_slot}"
+                        );
+                        exit(1);
+                    }
+                    if let Some(slot) = roots.iter().find(|slot| !blockstore.is_full(**slot)) {
+                        eprintln!(
+                            "Error: slot {slot}, an ancestor of slot {highest_full_slot}, is not full"
+                        );
+                        exit(1);
+                    }
+                    blockstore.set_roots(roots.iter()).unwrap_or_else(|err| {
+                        eprintln!("Unable to set roots: {err}");
+                        exit(1);
+                    });
+                    println!(
+                        "Marked {} slots as roots, from slot {} to {highest_full_slot}",
+                        roots.len(),
+                        roots.last().unwrap(),
+                    );
+                }
+            }
+            ("accounts", Some(arg_matches)) => {
+                let halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
+                let process_options = ProcessOptions {
+                    new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                    halt_at_slot,
+                    poh_verify: false,
+                    ..ProcessOptions::default()
+                };
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let include_sysvars = arg_matches.is_present("include_sysvars");
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Secondary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let (bank_forks, ..) = load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    process_options,
+                    snapshot_archive_path,
+                    incremental_snapshot_archive_path,
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to load ledger: {err:?}");
+                    exit(1);
+                });
 
                 let bank = bank_forks.read().unwrap().working_bank();
                 let mut serializer = serde_json::Serializer::new(stdout());
@@ -3437,6 +4435,178 @@ fn main() {
                     println!("\n{total_accounts_stats:#?}");
                 }
             }
+            ("export-account-set", Some(arg_matches)) => {
+                let halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
+                let process_options = ProcessOptions {
+                    new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                    halt_at_slot,
+                    poh_verify: false,
+                    ..ProcessOptions::default()
+                };
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let filter = AccountSetFilter {
+                    owners: pubkeys_of(arg_matches, "owner")
+                        .unwrap_or_default()
+                        .into_iter()
+                        .collect(),
+                    pubkeys: pubkeys_of(arg_matches, "account")
+                        .unwrap_or_default()
+                        .into_iter()
+                        .collect(),
+                };
+                if filter == AccountSetFilter::default() {
+                    eprintln!("Error: at least one --owner or --account is required");
+                    exit(1);
+                }
+                let output_file = value_t_or_exit!(arg_matches, "output_file", String);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Secondary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let (bank_forks, ..) = load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    process_options,
+                    snapshot_archive_path,
+                    incremental_snapshot_archive_path,
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to load ledger: {err:?}");
+                    exit(1);
+                });
+
+                let bank = bank_forks.read().unwrap().working_bank();
+                let account_set = bank.export_account_set(&filter).unwrap_or_else(|err| {
+                    eprintln!("Failed to export accounts: {err:?}");
+                    exit(1);
+                });
+                account_set
+                    .write_to_file(&output_file)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to write account set {output_file}: {err}");
+                        exit(1);
+                    });
+                println!(
+                    "Exported {} accounts at slot {} to {}",
+                    account_set.len(),
+                    account_set.slot,
+                    output_file
+                );
+            }
+            ("accounts-hash", Some(arg_matches)) => {
+                let halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
+                let process_options = ProcessOptions {
+                    new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                    halt_at_slot,
+                    poh_verify: false,
+                    ..ProcessOptions::default()
+                };
+                let bins = value_t_or_exit!(arg_matches, "bins", usize);
+                let account_bins: BTreeSet<usize> =
+                    values_t!(arg_matches, "include_accounts_in_bin", usize)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .collect();
+                if let Some(bin) = account_bins.iter().find(|bin| **bin >= bins) {
+                    eprintln!("Error: bin {bin} is out of range, there are {bins} bins");
+                    exit(1);
+                }
+                let genesis_config = open_genesis_config_by(&ledger_path, arg_matches);
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Secondary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let (bank_forks, ..) = load_bank_forks(
+                    arg_matches,
+                    &genesis_config,
+                    &blockstore,
+                    process_options,
+                    snapshot_archive_path,
+                    incremental_snapshot_archive_path,
+                )
+                .unwrap_or_else(|err| {
+                    eprintln!("Failed to load ledger: {err:?}");
+                    exit(1);
+                });
+
+                let bank = bank_forks.read().unwrap().working_bank();
+                let dump = bank.dump_accounts_hash(bins, &account_bins);
+                let result = match arg_matches.value_of("output_file") {
+                    Some(output_file) => File::create(output_file).and_then(|file| {
+                        let mut writer = BufWriter::new(file);
+                        dump.write(&mut writer)?;
+                        writer.flush()
+                    }),
+                    None => dump.write(&mut stdout().lock()),
+                };
+                result.unwrap_or_else(|err| {
+                    eprintln!("Failed to write accounts hash dump: {err}");
+                    exit(1);
+                });
+                eprintln!(
+                    "Accounts hash at slot {}: {}, total lamports: {}",
+                    dump.slot, dump.accounts_hash, dump.total_lamports
+                );
+            }
+            ("accounts-hash-diff", Some(arg_matches)) => {
+                let read_dump = |name| {
+                    let path = value_t_or_exit!(arg_matches, name, String);
+                    File::open(&path)
+                        .and_then(|file| AccountsHashDump::read(BufReader::new(file)))
+                        .unwrap_or_else(|err| {
+                            eprintln!("Failed to read accounts hash dump {path}: {err}");
+                            exit(1);
+                        })
+                };
+                let dump1 = read_dump("dump1");
+                let dump2 = read_dump("dump2");
+                let diff = dump1.diff(&dump2).unwrap_or_else(|err| {
+                    eprintln!("Error: {err}");
+                    exit(1);
+                });
+                println!(
+                    "slot {}: accounts hash {}, total lamports {}",
+                    dump1.slot, dump1.accounts_hash, dump1.total_lamports
+                );
+                println!(
+                    "slot {}: accounts hash {}, total lamports {}",
+                    dump2.slot, dump2.accounts_hash, dump2.total_lamports
+                );
+                if diff.mismatched_bins.is_empty() {
+                    println!("All {} bins match", dump1.bins.len());
+                }
+                for bin in &diff.mismatched_bins {
+                    let (bin1, bin2) = (&dump1.bins[*bin], &dump2.bins[*bin]);
+                    println!(
+                        "bin {bin} mismatch: count {} vs {}, lamports {} vs {}, hash {} vs {}",
+                        bin1.count, bin2.count, bin1.lamports, bin2.lamports, bin1.hash, bin2.hash
+                    );
+                    if !dump1.account_bins.contains(bin) || !dump2.account_bins.contains(bin) {
+                        println!(
+                            "  accounts not in both dumps, rerun accounts-hash with \
+                             --include-accounts-in-bin {bin}"
+                        );
+                    }
+                }
+                let format_account = |account: Option<(u64, Hash)>| match account {
+                    Some((lamports, hash)) => format!("lamports {lamports} hash {hash}"),
+                    None => "missing".to_string(),
+                };
+                for (pubkey, account1, account2) in &diff.mismatched_accounts {
+                    println!(
+                        "account {pubkey} mismatch: {} vs {}",
+                        format_account(*account1),
+                        format_account(*account2)
+                    );
+                }
+            }
             ("capitalization", Some(arg_matches)) => {
                 let halt_at_slot = value_t!(arg_matches, "halt_at_slot", Slot).ok();
                 let process_options = ProcessOptions {
@@ -4130,6 +5300,57 @@ fn main() {
                     println!("{:>20} {:>44} {:>32}", slot, &hash_str, &time_str);
                 }
             }
+            ("program-instruction-signatures", Some(arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Secondary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let program_id = pubkey_of(arg_matches, "program_id").unwrap();
+                let discriminator = arg_matches
+                    .value_of("discriminator")
+                    .map(|discriminator| {
+                        bs58::decode(discriminator)
+                            .into_vec()
+                            .unwrap_or_else(|err| {
+                                eprintln!("Invalid discriminator {discriminator}: {err}");
+                                exit(1);
+                            })
+                    })
+                    .unwrap_or_default();
+                let signature_of = |name| {
+                    arg_matches.value_of(name).map(|value| {
+                        Signature::from_str(value).unwrap_or_else(|err| {
+                            eprintln!("Invalid signature {value}: {err}");
+                            exit(1);
+                        })
+                    })
+                };
+                let limit = value_t_or_exit!(arg_matches, "limit", usize);
+                let infos = blockstore
+                    .get_confirmed_signatures_for_program_instruction(
+                        program_id,
+                        &discriminator,
+                        blockstore.max_root(),
+                        signature_of("before"),
+                        signature_of("until"),
+                        limit,
+                    )
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to read the program instruction index: {err:?}");
+                        exit(1);
+                    });
+                println!("{:>20} {:>88} Status", "Slot", "Signature");
+                for info in infos {
+                    let status = info
+                        .err
+                        .map(|err| err.to_string())
+                        .unwrap_or_else(|| "Ok".to_string());
+                    println!("{:>20} {:>88} {}", info.slot, info.signature, status);
+                }
+            }
             ("repair-roots", Some(arg_matches)) => {
                 let blockstore = open_blockstore(
                     &ledger_path,
@@ -4180,6 +5401,105 @@ fn main() {
                     println!("No missing roots found in range {end_root} to {start_root}");
                 }
             }
+            ("check", Some(arg_matches)) => {
+                let fix = arg_matches.is_present("fix");
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    if fix {
+                        AccessType::Primary
+                    } else {
+                        AccessType::Secondary
+                    },
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+                let ending_slot = value_t!(arg_matches, "ending_slot", Slot).unwrap_or(Slot::MAX);
+                let report = blockstore
+                    .check_consistency(starting_slot, ending_slot, fix)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to check the blockstore: {err:?}");
+                        exit(1);
+                    });
+                for (inconsistency, fixed) in &report.inconsistencies {
+                    let status = match (*fixed, inconsistency.is_fixable(), fix) {
+                        (true, _, _) => "fixed",
+                        (false, true, false) => "fixable",
+                        (false, true, true) => "not fixed",
+                        (false, false, _) => "unfixable",
+                    };
+                    println!("{inconsistency} [{status}]");
+                }
+                println!(
+                    "Checked {} slots, {} roots, {} transaction statuses and {} address signatures: \
+                     {} inconsistencies, {} fixed",
+                    report.num_slots,
+                    report.num_roots,
+                    report.num_transaction_statuses,
+                    report.num_address_signatures,
+                    report.inconsistencies.len(),
+                    report.num_fixed(),
+                );
+                if report.inconsistencies.len() > report.num_fixed() {
+                    exit(1);
+                }
+            }
+            ("train-compression-dictionary", Some(arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Primary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let column = value_t_or_exit!(arg_matches, "column", CompressibleColumn);
+                let max_samples = value_t_or_exit!(arg_matches, "max_samples", usize);
+                let dictionary_size = value_t_or_exit!(arg_matches, "dictionary_size", usize);
+                let version = blockstore
+                    .train_compression_dictionary(column, max_samples, dictionary_size)
+                    .unwrap_or_else(|err| {
+                        eprintln!(
+                            "Failed to train a dictionary for {}: {err:?}",
+                            column.name()
+                        );
+                        exit(1);
+                    });
+                println!("Stored compression dictionary version {version}");
+                for (version, meta) in blockstore.compression_dictionaries().unwrap() {
+                    println!(
+                        "  version {version}: {}, {} bytes, trained from {} values",
+                        meta.column,
+                        meta.dictionary.len(),
+                        meta.num_samples,
+                    );
+                }
+            }
+            ("recompress", Some(arg_matches)) => {
+                let blockstore = open_blockstore(
+                    &ledger_path,
+                    AccessType::Primary,
+                    wal_recovery_mode,
+                    &shred_storage_type,
+                    force_update_to_open,
+                );
+                let column = value_t_or_exit!(arg_matches, "column", CompressibleColumn);
+                let compact = !arg_matches.is_present("skip_compaction");
+                let stats = blockstore
+                    .recompress_column(column, compact)
+                    .unwrap_or_else(|err| {
+                        eprintln!("Failed to recompress {}: {err:?}", column.name());
+                        exit(1);
+                    });
+                println!(
+                    "Recompressed {} of {} values of {}: {} bytes before, {} bytes after",
+                    stats.num_recompressed,
+                    stats.num_values,
+                    column.name(),
+                    stats.bytes_before,
+                    stats.bytes_after,
+                );
+            }
             ("bounds", Some(arg_matches)) => {
                 let blockstore = open_blockstore(
                     &ledger_path,
@@ -0,0 +1,419 @@
+//! The `snapshot-accounts` subcommand, which extracts accounts from snapshot archives
+//!
+//! Accounts are streamed out of the archives' storages, without building a bank, so that the
+//! accounts of a program can be extracted on a machine that could not load the snapshot.
+use {
+    arrow_array::{
+        builder::{BinaryBuilder, BooleanBuilder, StringBuilder, UInt64Builder},
+        ArrayRef, RecordBatch,
+    },
+    arrow_schema::{DataType, Field, Schema, SchemaRef},
+    clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
+    parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties},
+    serde::Serialize,
+    solana_clap_utils::{input_parsers::pubkeys_of, input_validators::is_pubkey},
+    solana_runtime::{
+        append_vec::StoredAccountMeta,
+        snapshot_utils::{scan_snapshot_archive_accounts, SnapshotAccountsFilter},
+    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        error::Error,
+        fs::{self, File},
+        io::{self, BufWriter, Write},
+        path::{Path, PathBuf},
+        process::exit,
+        str::FromStr,
+        sync::Arc,
+    },
+    tempfile::TempDir,
+};
+
+/// Rows buffered before they are written out as a Parquet record batch
+const MAX_BATCH_ROWS: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AccountsOutputFormat {
+    Csv,
+    Json,
+    Parquet,
+}
+
+impl AccountsOutputFormat {
+    const VALUES: &'static [&'static str] = &["csv", "json", "parquet"];
+}
+
+impl FromStr for AccountsOutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(format!("unknown output format: {s}")),
+        }
+    }
+}
+
+/// An account as written to CSV and JSON, with its data encoded in base64
+#[derive(Debug, Serialize)]
+struct AccountRow {
+    pubkey: String,
+    slot: Slot,
+    lamports: u64,
+    owner: String,
+    executable: bool,
+    rent_epoch: u64,
+    data_len: u64,
+    data: String,
+}
+
+impl AccountRow {
+    fn new(pubkey: &Pubkey, slot: Slot, account: &StoredAccountMeta) -> Self {
+        Self {
+            pubkey: pubkey.to_string(),
+            slot,
+            lamports: account.account_meta.lamports,
+            owner: account.account_meta.owner.to_string(),
+            executable: account.account_meta.executable,
+            rent_epoch: account.account_meta.rent_epoch,
+            data_len: account.meta.data_len,
+            data: base64::encode(account.data),
+        }
+    }
+}
+
+trait AccountsWriter {
+    fn write(
+        &mut self,
+        pubkey: &Pubkey,
+        slot: Slot,
+        account: &StoredAccountMeta,
+    ) -> Result<(), Box<dyn Error>>;
+    fn finish(self: Box<Self>) -> Result<(), Box<dyn Error>>;
+}
+
+struct CsvAccountsWriter<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> AccountsWriter for CsvAccountsWriter<W> {
+    fn write(
+        &mut self,
+        pubkey: &Pubkey,
+        slot: Slot,
+        account: &StoredAccountMeta,
+    ) -> Result<(), Box<dyn Error>> {
+        self.writer
+            .serialize(AccountRow::new(pubkey, slot, account))?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes one JSON object per line
+struct JsonAccountsWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> AccountsWriter for JsonAccountsWriter<W> {
+    fn write(
+        &mut self,
+        pubkey: &Pubkey,
+        slot: Slot,
+        account: &StoredAccountMeta,
+    ) -> Result<(), Box<dyn Error>> {
+        serde_json::to_writer(&mut self.writer, &AccountRow::new(pubkey, slot, account))?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Box<dyn Error>> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+struct ParquetAccountsWriter {
+    writer: ArrowWriter<File>,
+    schema: SchemaRef,
+    num_rows: usize,
+    pubkey: StringBuilder,
+    slot: UInt64Builder,
+    lamports: UInt64Builder,
+    owner: StringBuilder,
+    executable: BooleanBuilder,
+    rent_epoch: UInt64Builder,
+    data_len: UInt64Builder,
+    data: BinaryBuilder,
+}
+
+impl ParquetAccountsWriter {
+    fn new(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("pubkey", DataType::Utf8, false),
+            Field::new("slot", DataType::UInt64, false),
+            Field::new("lamports", DataType::UInt64, false),
+            Field::new("owner", DataType::Utf8, false),
+            Field::new("executable", DataType::Boolean, false),
+            Field::new("rent_epoch", DataType::UInt64, false),
+            Field::new("data_len", DataType::UInt64, false),
+            Field::new("data", DataType::Binary, false),
+        ]));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), Some(properties))?;
+        Ok(Self {
+            writer,
+            schema,
+            num_rows: 0,
+            pubkey: StringBuilder::new(),
+            slot: UInt64Builder::new(),
+            lamports: UInt64Builder::new(),
+            owner: StringBuilder::new(),
+            executable: BooleanBuilder::new(),
+            rent_epoch: UInt64Builder::new(),
+            data_len: UInt64Builder::new(),
+            data: BinaryBuilder::new(),
+        })
+    }
+
+    fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.num_rows == 0 {
+            return Ok(());
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.pubkey.finish()),
+            Arc::new(self.slot.finish()),
+            Arc::new(self.lamports.finish()),
+            Arc::new(self.owner.finish()),
+            Arc::new(self.executable.finish()),
+            Arc::new(self.rent_epoch.finish()),
+            Arc::new(self.data_len.finish()),
+            Arc::new(self.data.finish()),
+        ];
+        self.writer
+            .write(&RecordBatch::try_new(self.schema.clone(), columns)?)?;
+        self.num_rows = 0;
+        Ok(())
+    }
+}
+
+impl AccountsWriter for ParquetAccountsWriter {
+    fn write(
+        &mut self,
+        pubkey: &Pubkey,
+        slot: Slot,
+        account: &StoredAccountMeta,
+    ) -> Result<(), Box<dyn Error>> {
+        self.num_rows += 1;
+        self.pubkey.append_value(pubkey.to_string());
+        self.slot.append_value(slot);
+        self.lamports.append_value(account.account_meta.lamports);
+        self.owner
+            .append_value(account.account_meta.owner.to_string());
+        self.executable
+            .append_value(account.account_meta.executable);
+        self.rent_epoch
+            .append_value(account.account_meta.rent_epoch);
+        self.data_len.append_value(account.meta.data_len);
+        self.data.append_value(account.data);
+        if self.num_rows >= MAX_BATCH_ROWS {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<(), Box<dyn Error>> {
+        self.flush()?;
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+fn new_accounts_writer(
+    format: AccountsOutputFormat,
+    output: Option<&Path>,
+) -> Result<Box<dyn AccountsWriter>, Box<dyn Error>> {
+    let output: Box<dyn Write> = match (format, output) {
+        (AccountsOutputFormat::Parquet, Some(path)) => {
+            return Ok(Box::new(ParquetAccountsWriter::new(path)?));
+        }
+        (AccountsOutputFormat::Parquet, None) => {
+            return Err("--output-file is required by the parquet format".into());
+        }
+        (_, Some(path)) => Box::new(BufWriter::new(File::create(path)?)),
+        (_, None) => Box::new(BufWriter::new(io::stdout())),
+    };
+    Ok(match format {
+        AccountsOutputFormat::Csv => Box::new(CsvAccountsWriter {
+            writer: csv::Writer::from_writer(output),
+        }),
+        AccountsOutputFormat::Json => Box::new(JsonAccountsWriter { writer: output }),
+        AccountsOutputFormat::Parquet => unreachable!(),
+    })
+}
+
+/// Writes the accounts of the snapshot archives that match `filter`, returning how many were
+/// written
+fn write_snapshot_accounts(
+    snapshot_archive_paths: &[PathBuf],
+    unpack_dir: &Path,
+    filter: &SnapshotAccountsFilter,
+    mut writer: Box<dyn AccountsWriter>,
+) -> Result<usize, Box<dyn Error>> {
+    let mut num_accounts = 0;
+    let mut write_result = Ok(());
+    scan_snapshot_archive_accounts(
+        snapshot_archive_paths,
+        unpack_dir,
+        filter,
+        |pubkey, slot, account| {
+            if write_result.is_ok() {
+                write_result = writer.write(pubkey, slot, account);
+                num_accounts += 1;
+            }
+        },
+    )?;
+    write_result?;
+    writer.finish()?;
+    Ok(num_accounts)
+}
+
+/// Builds a filter from the `account` and `owner` arguments
+pub(crate) fn snapshot_accounts_filter_of(matches: &ArgMatches<'_>) -> SnapshotAccountsFilter {
+    SnapshotAccountsFilter {
+        pubkeys: pubkeys_of(matches, "account").map(|pubkeys| pubkeys.into_iter().collect()),
+        owners: pubkeys_of(matches, "owner").map(|owners| owners.into_iter().collect()),
+    }
+}
+
+/// Creates a directory to unpack snapshot archives in, within the `unpack_dir` argument if set,
+/// that is removed when dropped
+pub(crate) fn unpack_dir_of(matches: &ArgMatches<'_>) -> TempDir {
+    match matches.value_of("unpack_dir") {
+        Some(unpack_dir) => {
+            let unpack_dir = PathBuf::from(unpack_dir);
+            if let Err(err) = fs::create_dir_all(&unpack_dir) {
+                eprintln!("Unable to create {}: {err}", unpack_dir.display());
+                exit(1);
+            }
+            TempDir::new_in(unpack_dir)
+        }
+        None => TempDir::new(),
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("Unable to create an unpack directory: {err}");
+        exit(1);
+    })
+}
+
+pub trait SnapshotAccountsSubCommand {
+    fn snapshot_accounts_subcommand(self) -> Self;
+}
+
+impl SnapshotAccountsSubCommand for App<'_, '_> {
+    fn snapshot_accounts_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("snapshot-accounts")
+                .about(
+                    "Extract the accounts of snapshot archives, without loading a bank. \
+                     Does not require a ledger",
+                )
+                .arg(
+                    Arg::with_name("snapshot_archive")
+                        .index(1)
+                        .value_name("SNAPSHOT_ARCHIVE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Full snapshot archive to extract the accounts of"),
+                )
+                .arg(
+                    Arg::with_name("incremental_snapshot_archive")
+                        .long("incremental-snapshot-archive")
+                        .value_name("INCREMENTAL_SNAPSHOT_ARCHIVE")
+                        .takes_value(true)
+                        .help(
+                            "Incremental snapshot archive based on SNAPSHOT_ARCHIVE, whose \
+                             accounts take precedence",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .long("owner")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(is_pubkey)
+                        .help("Only extract the accounts owned by this program"),
+                )
+                .arg(
+                    Arg::with_name("account")
+                        .long("account")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(is_pubkey)
+                        .help("Only extract this account"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .takes_value(true)
+                        .possible_values(AccountsOutputFormat::VALUES)
+                        .default_value("csv")
+                        .help("Format to write the accounts in"),
+                )
+                .arg(
+                    Arg::with_name("output_file")
+                        .long("output-file")
+                        .value_name("FILE")
+                        .takes_value(true)
+                        .help("Write the accounts to FILE [default: stdout]"),
+                )
+                .arg(
+                    Arg::with_name("unpack_dir")
+                        .long("unpack-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help(
+                            "Directory to unpack the archives in, one storage at a time \
+                             [default: a temporary directory]",
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn snapshot_accounts_process_command(matches: &ArgMatches<'_>) {
+    let mut snapshot_archive_paths = vec![PathBuf::from(value_t_or_exit!(
+        matches,
+        "snapshot_archive",
+        String
+    ))];
+    if let Some(path) = matches.value_of("incremental_snapshot_archive") {
+        snapshot_archive_paths.push(PathBuf::from(path));
+    }
+    let filter = snapshot_accounts_filter_of(matches);
+    let format = value_t_or_exit!(matches, "format", AccountsOutputFormat);
+    let output = matches.value_of("output_file").map(PathBuf::from);
+    let unpack_dir = unpack_dir_of(matches);
+
+    let result = new_accounts_writer(format, output.as_deref()).and_then(|writer| {
+        write_snapshot_accounts(&snapshot_archive_paths, unpack_dir.path(), &filter, writer)
+    });
+    match result {
+        Ok(num_accounts) => eprintln!("Extracted {num_accounts} accounts"),
+        Err(err) => {
+            eprintln!("Failed to extract the snapshot accounts: {err}");
+            exit(1);
+        }
+    }
+}
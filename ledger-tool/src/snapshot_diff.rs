@@ -0,0 +1,346 @@
+//! The `snapshot-diff` subcommand, which reports the accounts that differ between two snapshots
+//!
+//! The target snapshot is either another full snapshot archive, or an incremental snapshot
+//! archive based on the base full snapshot archive. Accounts are streamed out of the archives,
+//! without building a bank.
+use {
+    crate::snapshot_accounts::{snapshot_accounts_filter_of, unpack_dir_of},
+    clap::{value_t_or_exit, App, Arg, ArgMatches, SubCommand},
+    serde::Serialize,
+    solana_clap_utils::input_validators::is_pubkey,
+    solana_cli_output::{CliAccount, CliAccountNewConfig, OutputFormat},
+    solana_runtime::{
+        snapshot_archive_info::{
+            FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
+        },
+        snapshot_utils::{diff_snapshot_archives, SnapshotAccountDiff},
+    },
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::HashMap,
+        error::Error,
+        fs::{self, File},
+        path::{Path, PathBuf},
+        process::exit,
+    },
+};
+
+/// An account that differs between the two snapshots
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountDiffRow {
+    pubkey: String,
+    change: &'static str,
+    /// Owner in the target snapshot, or in the base snapshot for removed accounts
+    owner: String,
+    /// Slot the account was last written at in the target snapshot
+    slot: Option<Slot>,
+    lamports_delta: i128,
+    data_len_delta: i128,
+}
+
+/// The changes to the accounts of an owner program
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnerDiffSummary {
+    owner: String,
+    added: usize,
+    removed: usize,
+    modified: usize,
+    lamports_delta: i128,
+    data_len_delta: i128,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotDiffReport {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    accounts: Vec<AccountDiffRow>,
+    owners: Vec<OwnerDiffSummary>,
+    total: OwnerDiffSummary,
+}
+
+impl SnapshotDiffReport {
+    fn print(&self, output_format: OutputFormat) {
+        match output_format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(self).unwrap()),
+            OutputFormat::JsonCompact => println!("{}", serde_json::to_string(self).unwrap()),
+            _ => {
+                for row in &self.accounts {
+                    println!(
+                        "{:<9}{:<45}owner: {:<45}lamports: {:+} data: {:+}",
+                        row.change, row.pubkey, row.owner, row.lamports_delta, row.data_len_delta,
+                    );
+                }
+                if !self.accounts.is_empty() {
+                    println!();
+                }
+                println!(
+                    "{:<45}{:>10}{:>10}{:>10}{:>24}{:>16}",
+                    "Owner", "Added", "Removed", "Modified", "Lamports", "Data"
+                );
+                for summary in self.owners.iter().chain(std::iter::once(&self.total)) {
+                    println!(
+                        "{:<45}{:>10}{:>10}{:>10}{:>+24}{:>+16}",
+                        summary.owner,
+                        summary.added,
+                        summary.removed,
+                        summary.modified,
+                        summary.lamports_delta,
+                        summary.data_len_delta,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The snapshot archives to scan for the target snapshot
+fn target_snapshot_archive_paths(
+    base_snapshot_archive: &Path,
+    target_snapshot_archive: &Path,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let base = FullSnapshotArchiveInfo::new_from_path(base_snapshot_archive.to_path_buf())?;
+    if let Ok(incremental) =
+        IncrementalSnapshotArchiveInfo::new_from_path(target_snapshot_archive.to_path_buf())
+    {
+        if incremental.base_slot() != base.slot() {
+            return Err(format!(
+                "{} is based on slot {}, not on the base snapshot slot {}",
+                target_snapshot_archive.display(),
+                incremental.base_slot(),
+                base.slot(),
+            )
+            .into());
+        }
+        return Ok(vec![
+            base_snapshot_archive.to_path_buf(),
+            target_snapshot_archive.to_path_buf(),
+        ]);
+    }
+    FullSnapshotArchiveInfo::new_from_path(target_snapshot_archive.to_path_buf())?;
+    Ok(vec![target_snapshot_archive.to_path_buf()])
+}
+
+fn write_account_file(
+    account_dir: &Path,
+    pubkey: &Pubkey,
+    cli_account: &CliAccount,
+) -> Result<(), Box<dyn Error>> {
+    let file = File::create(account_dir.join(format!("{pubkey}.json")))?;
+    serde_json::to_writer_pretty(file, cli_account)?;
+    Ok(())
+}
+
+pub trait SnapshotDiffSubCommand {
+    fn snapshot_diff_subcommand(self) -> Self;
+}
+
+impl SnapshotDiffSubCommand for App<'_, '_> {
+    fn snapshot_diff_subcommand(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("snapshot-diff")
+                .about(
+                    "Report the accounts added, removed and modified between two snapshots, \
+                     without loading a bank. Does not require a ledger",
+                )
+                .arg(
+                    Arg::with_name("base_snapshot_archive")
+                        .index(1)
+                        .value_name("BASE_SNAPSHOT_ARCHIVE")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Full snapshot archive to compare from"),
+                )
+                .arg(
+                    Arg::with_name("target_snapshot_archive")
+                        .index(2)
+                        .value_name("TARGET_SNAPSHOT_ARCHIVE")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "Full snapshot archive to compare to, or incremental snapshot \
+                             archive based on BASE_SNAPSHOT_ARCHIVE",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("owner")
+                        .long("owner")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(is_pubkey)
+                        .help("Only compare the accounts owned by this program"),
+                )
+                .arg(
+                    Arg::with_name("account")
+                        .long("account")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(is_pubkey)
+                        .help("Only compare this account"),
+                )
+                .arg(
+                    Arg::with_name("summary_only")
+                        .long("summary-only")
+                        .takes_value(false)
+                        .help("Only report the changes per owner program"),
+                )
+                .arg(
+                    Arg::with_name("account_dir")
+                        .long("account-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help(
+                            "Write the added and modified accounts, as of the target snapshot, \
+                             to DIR in the format loaded by `solana-test-validator --account-dir`",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("unpack_dir")
+                        .long("unpack-dir")
+                        .value_name("DIR")
+                        .takes_value(true)
+                        .help(
+                            "Directory to unpack the archives in, one storage at a time \
+                             [default: a temporary directory]",
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn snapshot_diff_process_command(matches: &ArgMatches<'_>) {
+    let base_snapshot_archive =
+        PathBuf::from(value_t_or_exit!(matches, "base_snapshot_archive", String));
+    let target_snapshot_archive =
+        PathBuf::from(value_t_or_exit!(matches, "target_snapshot_archive", String));
+    let target_snapshot_archive_paths =
+        target_snapshot_archive_paths(&base_snapshot_archive, &target_snapshot_archive)
+            .unwrap_or_else(|err| {
+                eprintln!("Invalid snapshot archives: {err}");
+                exit(1);
+            });
+    let filter = snapshot_accounts_filter_of(matches);
+    let summary_only = matches.is_present("summary_only");
+    let output_format = OutputFormat::from_matches(matches, "output_format", false);
+    let account_dir = matches.value_of("account_dir").map(PathBuf::from);
+    if let Some(account_dir) = &account_dir {
+        if let Err(err) = fs::create_dir_all(account_dir) {
+            eprintln!("Unable to create {}: {err}", account_dir.display());
+            exit(1);
+        }
+    }
+    let unpack_dir = unpack_dir_of(matches);
+
+    let mut report = SnapshotDiffReport::default();
+    let mut owners = HashMap::<Pubkey, OwnerDiffSummary>::new();
+    let mut write_result = Ok(());
+    let cli_account_new_config = CliAccountNewConfig::default();
+    let result = diff_snapshot_archives(
+        &[base_snapshot_archive],
+        &target_snapshot_archive_paths,
+        unpack_dir.path(),
+        &filter,
+        |pubkey, diff| {
+            let lamports_delta = diff.lamports_delta();
+            let data_len_delta = diff.data_len_delta();
+            let (change, owner, slot) = match &diff {
+                SnapshotAccountDiff::Added { slot, account } => {
+                    ("added", account.account_meta.owner, Some(*slot))
+                }
+                SnapshotAccountDiff::Removed { base } => ("removed", base.owner, None),
+                SnapshotAccountDiff::Modified {
+                    base,
+                    slot,
+                    account,
+                } => {
+                    // Changes of owner move the account from the base owner to the target owner
+                    if base.owner != account.account_meta.owner {
+                        let base_owner = owners.entry(base.owner).or_default();
+                        base_owner.lamports_delta -= base.lamports as i128;
+                        base_owner.data_len_delta -= base.data_len as i128;
+                    }
+                    ("modified", account.account_meta.owner, Some(*slot))
+                }
+            };
+            let summary = owners.entry(owner).or_default();
+            match &diff {
+                SnapshotAccountDiff::Added { .. } => summary.added += 1,
+                SnapshotAccountDiff::Removed { .. } => summary.removed += 1,
+                SnapshotAccountDiff::Modified { base, account, .. } => {
+                    summary.modified += 1;
+                    if base.owner != account.account_meta.owner {
+                        summary.lamports_delta += base.lamports as i128;
+                        summary.data_len_delta += base.data_len as i128;
+                    }
+                }
+            }
+            summary.lamports_delta += lamports_delta;
+            summary.data_len_delta += data_len_delta;
+
+            if let (
+                Some(account_dir),
+                SnapshotAccountDiff::Added { account, .. }
+                | SnapshotAccountDiff::Modified { account, .. },
+            ) = (&account_dir, &diff)
+            {
+                if write_result.is_ok() {
+                    let cli_account = CliAccount::new_with_config(
+                        pubkey,
+                        &account.clone_account(),
+                        &cli_account_new_config,
+                    );
+                    write_result = write_account_file(account_dir, pubkey, &cli_account);
+                }
+            }
+
+            if !summary_only {
+                report.accounts.push(AccountDiffRow {
+                    pubkey: pubkey.to_string(),
+                    change,
+                    owner: owner.to_string(),
+                    slot,
+                    lamports_delta,
+                    data_len_delta,
+                });
+            }
+        },
+    );
+    if let Err(err) = result {
+        eprintln!("Failed to compare the snapshots: {err}");
+        exit(1);
+    }
+    if let Err(err) = write_result {
+        eprintln!("Failed to write the changed accounts: {err}");
+        exit(1);
+    }
+
+    report.accounts.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+    report.owners = owners
+        .into_iter()
+        .map(|(owner, summary)| OwnerDiffSummary {
+            owner: owner.to_string(),
+            ..summary
+        })
+        .collect();
+    report
+        .owners
+        .sort_by_key(|summary| std::cmp::Reverse(summary.lamports_delta.unsigned_abs()));
+    report.total = report.owners.iter().fold(
+        OwnerDiffSummary {
+            owner: "Total".to_string(),
+            ..OwnerDiffSummary::default()
+        },
+        |mut total, summary| {
+            total.added += summary.added;
+            total.removed += summary.removed;
+            total.modified += summary.modified;
+            total.lamports_delta += summary.lamports_delta;
+            total.data_len_delta += summary.data_len_delta;
+            total
+        },
+    );
+    report.print(output_format);
+}
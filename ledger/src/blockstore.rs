@@ -5,6 +5,7 @@
 use {
     crate::{
         ancestor_iterator::AncestorIterator,
+        blockstore_archive::BlockstoreArchive,
         blockstore_db::{
             columns as cf, Column, Database, IteratorDirection, IteratorMode, LedgerColumn, Result,
             WriteBatch,
@@ -44,6 +45,8 @@ use {
         clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND, MS_PER_TICK},
         genesis_config::{GenesisConfig, DEFAULT_GENESIS_ARCHIVE, DEFAULT_GENESIS_FILE},
         hash::Hash,
+        instruction::CompiledInstruction,
+        message::AccountKeys,
         pubkey::Pubkey,
         signature::{Keypair, Signature, Signer},
         timing::timestamp,
@@ -51,15 +54,17 @@ use {
     },
     solana_storage_proto::{StoredExtendedRewards, StoredTransactionStatusMeta},
     solana_transaction_status::{
-        ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithStatusMeta, Rewards,
-        TransactionStatusMeta, TransactionWithStatusMeta, VersionedConfirmedBlock,
-        VersionedTransactionWithStatusMeta,
+        ConfirmedTransactionStatusWithSignature, ConfirmedTransactionWithStatusMeta,
+        InnerInstructions, Rewards, TransactionStatusMeta, TransactionWithStatusMeta,
+        VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
     },
     std::{
         borrow::Cow,
         cell::RefCell,
-        cmp,
-        collections::{hash_map::Entry as HashMapEntry, BTreeSet, HashMap, HashSet, VecDeque},
+        cmp::{self, Reverse},
+        collections::{
+            hash_map::Entry as HashMapEntry, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque,
+        },
         convert::TryInto,
         fmt::Write,
         fs,
@@ -76,6 +81,8 @@ use {
     thiserror::Error,
     trees::{Tree, TreeWalk},
 };
+pub mod blockstore_check;
+pub mod blockstore_dictionary;
 pub mod blockstore_purge;
 pub use {
     crate::{
@@ -83,6 +90,8 @@ pub use {
         blockstore_meta::{OptimisticSlotMetaVersioned, SlotMeta},
         blockstore_metrics::BlockstoreInsertionMetrics,
     },
+    blockstore_check::{BlockstoreCheckReport, BlockstoreInconsistency},
+    blockstore_dictionary::{CompressibleColumn, RecompressStats},
     blockstore_purge::PurgeType,
     rocksdb::properties as RocksProperties,
 };
@@ -113,6 +122,12 @@ pub const MAX_TURBINE_DELAY_IN_TICKS: u64 =
 // (32K shreds per slot * 4 TX per shred * 2.5 slots per sec)
 pub const MAX_DATA_SHREDS_PER_SLOT: usize = 32_768;
 
+/// Number of leading bytes of instruction data indexed by the
+/// `ProgramInstructionSignatures` column
+pub const PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN: usize = 8;
+/// Most index entries a single `get_confirmed_signatures_for_program_instruction` query scans
+pub const MAX_PROGRAM_INSTRUCTION_INDEX_SCAN: usize = 100_000;
+
 pub type CompletedSlotsSender = Sender<Vec<Slot>>;
 pub type CompletedSlotsReceiver = Receiver<Vec<Slot>>;
 type CompletedRanges = Vec<(u32, u32)>;
@@ -163,6 +178,7 @@ pub struct Blockstore {
     code_shred_cf: LedgerColumn<cf::ShredCode>,
     transaction_status_cf: LedgerColumn<cf::TransactionStatus>,
     address_signatures_cf: LedgerColumn<cf::AddressSignatures>,
+    program_instruction_signatures_cf: LedgerColumn<cf::ProgramInstructionSignatures>,
     transaction_memos_cf: LedgerColumn<cf::TransactionMemos>,
     transaction_status_index_cf: LedgerColumn<cf::TransactionStatusIndex>,
     active_transaction_status_index: RwLock<u64>,
@@ -173,6 +189,7 @@ pub struct Blockstore {
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     optimistic_slots_cf: LedgerColumn<cf::OptimisticSlots>,
+    compression_dictionary_cf: LedgerColumn<cf::CompressionDictionary>,
     last_root: RwLock<Slot>,
     insert_shreds_lock: Mutex<()>,
     new_shreds_signals: Mutex<Vec<Sender<bool>>>,
@@ -180,6 +197,7 @@ pub struct Blockstore {
     pub shred_timing_point_sender: Option<PohTimingSender>,
     pub lowest_cleanup_slot: RwLock<Slot>,
     pub slots_stats: SlotsStats,
+    archive: Option<BlockstoreArchive>,
 }
 
 pub struct IndexMetaWorkingSetEntry {
@@ -225,6 +243,12 @@ impl Blockstore {
         &self.ledger_path
     }
 
+    /// The archive of purged rooted blocks, if enabled with
+    /// [`BlockstoreOptions::archive_path`]
+    pub fn archive(&self) -> Option<&BlockstoreArchive> {
+        self.archive.as_ref()
+    }
+
     pub fn banking_trace_path(&self) -> PathBuf {
         self.ledger_path.join("banking_trace")
     }
@@ -249,6 +273,12 @@ impl Blockstore {
 
         adjust_ulimit_nofile(options.enforce_ulimit_nofile)?;
 
+        let archive = options
+            .archive_path
+            .as_deref()
+            .map(BlockstoreArchive::open)
+            .transpose()?;
+
         // Open the database
         let mut measure = Measure::start("open");
         info!("Opening database at {:?}", blockstore_path);
@@ -272,6 +302,7 @@ impl Blockstore {
         let code_shred_cf = db.column();
         let transaction_status_cf = db.column();
         let address_signatures_cf = db.column();
+        let program_instruction_signatures_cf = db.column();
         let transaction_memos_cf = db.column();
         let transaction_status_index_cf = db.column();
         let rewards_cf = db.column();
@@ -281,6 +312,7 @@ impl Blockstore {
         let program_costs_cf = db.column();
         let bank_hash_cf = db.column();
         let optimistic_slots_cf = db.column();
+        let compression_dictionary_cf = db.column();
 
         let db = Arc::new(db);
 
@@ -323,6 +355,7 @@ impl Blockstore {
             code_shred_cf,
             transaction_status_cf,
             address_signatures_cf,
+            program_instruction_signatures_cf,
             transaction_memos_cf,
             transaction_status_index_cf,
             active_transaction_status_index: RwLock::new(active_transaction_status_index),
@@ -333,6 +366,7 @@ impl Blockstore {
             program_costs_cf,
             bank_hash_cf,
             optimistic_slots_cf,
+            compression_dictionary_cf,
             new_shreds_signals: Mutex::default(),
             completed_slots_senders: Mutex::default(),
             shred_timing_point_sender: None,
@@ -340,6 +374,7 @@ impl Blockstore {
             last_root,
             lowest_cleanup_slot: RwLock::<Slot>::default(),
             slots_stats: SlotsStats::default(),
+            archive,
         };
         if initialize_transaction_status_index {
             blockstore.initialize_transaction_status_index()?;
@@ -682,6 +717,8 @@ impl Blockstore {
         self.code_shred_cf.submit_rocksdb_cf_metrics();
         self.transaction_status_cf.submit_rocksdb_cf_metrics();
         self.address_signatures_cf.submit_rocksdb_cf_metrics();
+        self.program_instruction_signatures_cf
+            .submit_rocksdb_cf_metrics();
         self.transaction_memos_cf.submit_rocksdb_cf_metrics();
         self.transaction_status_index_cf.submit_rocksdb_cf_metrics();
         self.rewards_cf.submit_rocksdb_cf_metrics();
@@ -691,6 +728,7 @@ impl Blockstore {
         self.program_costs_cf.submit_rocksdb_cf_metrics();
         self.bank_hash_cf.submit_rocksdb_cf_metrics();
         self.optimistic_slots_cf.submit_rocksdb_cf_metrics();
+        self.compression_dictionary_cf.submit_rocksdb_cf_metrics();
     }
 
     fn try_shred_recovery(
@@ -1923,14 +1961,26 @@ impl Blockstore {
         require_previous_blockhash: bool,
     ) -> Result<VersionedConfirmedBlock> {
         datapoint_info!("blockstore-rpc-api", ("method", "get_rooted_block", String));
-        let _lock = self.check_lowest_cleanup_slot(slot)?;
+        let lock = self.check_lowest_cleanup_slot(slot);
 
-        if self.is_root(slot) {
+        if lock.is_ok() && self.is_root(slot) {
             return self.get_complete_block(slot, require_previous_blockhash);
         }
+        // Purged rooted blocks may have been archived
+        if let Some(block) = self.get_archived_block(slot)? {
+            return Ok(block);
+        }
+        lock?;
         Err(BlockstoreError::SlotNotRooted)
     }
 
+    fn get_archived_block(&self, slot: Slot) -> Result<Option<VersionedConfirmedBlock>> {
+        match &self.archive {
+            Some(archive) => archive.get_block(slot),
+            None => Ok(None),
+        }
+    }
+
     pub fn get_complete_block(
         &self,
         slot: Slot,
@@ -2174,6 +2224,35 @@ impl Blockstore {
         Ok(())
     }
 
+    /// Indexes a transaction under the `(program id, discriminator)` keys of
+    /// its instructions, see [`program_instruction_keys`].
+    pub fn write_program_instruction_signatures(
+        &self,
+        slot: Slot,
+        transaction_index: u32,
+        signature: Signature,
+        keys: &BTreeSet<(Pubkey, [u8; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN])>,
+    ) -> Result<()> {
+        // Same as `write_transaction_status`, gate writes on the active primary index
+        let w_active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+        let primary_index =
+            self.get_primary_index_to_write(slot, &w_active_transaction_status_index)?;
+        for (program_id, discriminator) in keys {
+            self.program_instruction_signatures_cf.put(
+                (
+                    primary_index,
+                    *program_id,
+                    *discriminator,
+                    slot,
+                    transaction_index,
+                ),
+                &signature,
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn read_transaction_memos(&self, signature: Signature) -> Result<Option<String>> {
         self.transaction_memos_cf.get(signature)
     }
@@ -2331,6 +2410,8 @@ impl Blockstore {
                 ),
                 block_time,
             }))
+        } else if let Some(archive) = &self.archive {
+            archive.get_transaction(&signature)
         } else {
             Ok(None)
         }
@@ -2705,6 +2786,174 @@ impl Blockstore {
         })
     }
 
+    // Returns the slot of a transaction and its index among the transactions of the block
+    fn get_transaction_position(
+        &self,
+        signature: Signature,
+        confirmed_unrooted_slots: &[Slot],
+    ) -> Result<Option<(Slot, u32)>> {
+        let slot = match self.get_transaction_status(signature, confirmed_unrooted_slots)? {
+            Some((slot, _)) => slot,
+            None => return Ok(None),
+        };
+        let transaction_index = self
+            .get_slot_entries(slot, 0)?
+            .into_iter()
+            .flat_map(|entry| entry.transactions)
+            .position(|transaction| transaction.signatures.first() == Some(&signature));
+        Ok(transaction_index.map(|transaction_index| (slot, transaction_index as u32)))
+    }
+
+    /// Returns the rooted or confirmed transactions that invoked `program_id`
+    /// with instruction data starting with `discriminator`, in top-level or
+    /// inner instructions, most recent first.
+    ///
+    /// Transactions are ordered by slot and then by their index in the
+    /// block. `before` and `until` paginate the results as in
+    /// [`Self::get_confirmed_signatures_for_address2`]. Queries with a
+    /// discriminator shorter than [`PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN`]
+    /// bytes scan every indexed discriminator with that prefix.
+    pub fn get_confirmed_signatures_for_program_instruction(
+        &self,
+        program_id: Pubkey,
+        discriminator: &[u8],
+        highest_slot: Slot, // highest_confirmed_root or highest_confirmed_slot
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<ConfirmedTransactionStatusWithSignature>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            (
+                "method",
+                "get_confirmed_signatures_for_program_instruction",
+                String
+            )
+        );
+        if discriminator.len() > PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN {
+            return Err(BlockstoreError::Io(IoError::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "discriminator is longer than {PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN} bytes"
+                ),
+            )));
+        }
+        let last_root = self.last_root();
+        let confirmed_unrooted_slots: Vec<_> = AncestorIterator::new_inclusive(highest_slot, self)
+            .take_while(|&slot| slot > last_root)
+            .collect();
+
+        // Results are strictly between the positions of `until` and `before`
+        let upper_bound = match before {
+            None => (highest_slot.saturating_add(1), 0),
+            Some(before) => {
+                match self.get_transaction_position(before, &confirmed_unrooted_slots)? {
+                    Some(position) => position,
+                    None => return Ok(vec![]),
+                }
+            }
+        };
+        let lower_bound = match until {
+            None => None,
+            Some(until) => self.get_transaction_position(until, &confirmed_unrooted_slots)?,
+        };
+
+        let mut last_key_discriminator = [u8::MAX; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN];
+        last_key_discriminator[..discriminator.len()].copy_from_slice(discriminator);
+
+        let first_available_block = self.get_first_available_block()?;
+        let (lock, lowest_available_slot) = self.ensure_lowest_cleanup_slot();
+        let lowest_slot = lowest_available_slot.max(first_available_block);
+        // Min-heap of the `limit` most recent matches
+        let mut matches = BinaryHeap::new();
+        let mut num_scanned = 0;
+        for transaction_status_cf_primary_index in 0..=1 {
+            // Positions only decrease within a discriminator, so each discriminator matching
+            // the prefix is scanned from `upper_bound` down to the first position that is too
+            // old, most recent discriminator first
+            let mut next_discriminator = Some(last_key_discriminator);
+            while let Some(seek_discriminator) = next_discriminator.take() {
+                let index_iterator =
+                    self.program_instruction_signatures_cf
+                        .iter(IteratorMode::From(
+                            (
+                                transaction_status_cf_primary_index,
+                                program_id,
+                                seek_discriminator,
+                                upper_bound.0,
+                                upper_bound.1,
+                            ),
+                            IteratorDirection::Reverse,
+                        ))?;
+                for ((i, key_program_id, key_discriminator, slot, transaction_index), value) in
+                    index_iterator
+                {
+                    if i != transaction_status_cf_primary_index
+                        || key_program_id != program_id
+                        || !key_discriminator.starts_with(discriminator)
+                    {
+                        break;
+                    }
+                    if key_discriminator != seek_discriminator {
+                        // Seek the next discriminator from `upper_bound`
+                        next_discriminator = Some(key_discriminator);
+                        break;
+                    }
+                    num_scanned += 1;
+                    if num_scanned > MAX_PROGRAM_INSTRUCTION_INDEX_SCAN {
+                        return Err(BlockstoreError::Io(IoError::new(
+                            ErrorKind::Other,
+                            format!(
+                                "scanned more than {MAX_PROGRAM_INSTRUCTION_INDEX_SCAN} index \
+                                 entries, use a longer discriminator or a lower limit"
+                            ),
+                        )));
+                    }
+                    let position = (slot, transaction_index);
+                    if position >= upper_bound {
+                        continue;
+                    }
+                    let is_full = matches.len() >= limit;
+                    let is_too_old = slot < lowest_slot
+                        || lower_bound.map_or(false, |lower_bound| position <= lower_bound)
+                        || (is_full
+                            && matches.peek().map_or(false, |Reverse((min_position, _))| {
+                                position < *min_position
+                            }));
+                    if is_too_old {
+                        next_discriminator = previous_discriminator(key_discriminator);
+                        break;
+                    }
+                    if self.is_root(slot) || confirmed_unrooted_slots.contains(&slot) {
+                        let signature = deserialize::<Signature>(&value)?;
+                        matches.push(Reverse((position, signature)));
+                        if matches.len() > limit {
+                            matches.pop();
+                        }
+                    }
+                }
+            }
+        }
+        drop(lock);
+
+        let mut infos = vec![];
+        for Reverse(((slot, _), signature)) in matches.into_sorted_vec() {
+            let transaction_status =
+                self.get_transaction_status(signature, &confirmed_unrooted_slots)?;
+            let err = transaction_status.and_then(|(_slot, status)| status.status.err());
+            let memo = self.read_transaction_memos(signature)?;
+            let block_time = self.get_block_time(slot)?;
+            infos.push(ConfirmedTransactionStatusWithSignature {
+                signature,
+                slot,
+                err,
+                memo,
+                block_time,
+            });
+        }
+        Ok(infos)
+    }
+
     pub fn read_rewards(&self, index: Slot) -> Result<Option<Rewards>> {
         self.rewards_cf
             .get_protobuf_or_bincode::<Rewards>(index)
@@ -3287,6 +3536,14 @@ impl Blockstore {
         self.db.is_primary_access()
     }
 
+    /// Catches a secondary blockstore up with the writes of the primary.
+    ///
+    /// Only the column families opened by this blockstore are updated, and
+    /// the primary is expected to be alive while this runs.
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary()
+    }
+
     pub fn scan_and_fix_roots(&self, exit: &AtomicBool) -> Result<()> {
         let ancestor_iterator = AncestorIterator::new(self.last_root(), self)
             .take_while(|&slot| slot >= self.lowest_cleanup_slot());
@@ -3332,6 +3589,50 @@ impl Blockstore {
     }
 }
 
+/// The discriminator right before `discriminator` in the index order, if any
+fn previous_discriminator(
+    mut discriminator: [u8; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN],
+) -> Option<[u8; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN]> {
+    for byte in discriminator.iter_mut().rev() {
+        if *byte > 0 {
+            *byte -= 1;
+            return Some(discriminator);
+        }
+        *byte = u8::MAX;
+    }
+    None
+}
+
+/// Returns the `ProgramInstructionSignatures` keys of a transaction: the id
+/// of each program invoked by its top-level and inner instructions, with the
+/// first [`PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN`] bytes of the instruction
+/// data, zero-padded.
+pub fn program_instruction_keys(
+    account_keys: &AccountKeys,
+    instructions: &[CompiledInstruction],
+    inner_instructions: Option<&[InnerInstructions]>,
+) -> BTreeSet<(Pubkey, [u8; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN])> {
+    let inner_instructions = inner_instructions
+        .unwrap_or_default()
+        .iter()
+        .flat_map(|inner_instructions| &inner_instructions.instructions)
+        .map(|inner_instruction| &inner_instruction.instruction);
+    instructions
+        .iter()
+        .chain(inner_instructions)
+        .filter_map(|instruction| {
+            let program_id = account_keys.get(usize::from(instruction.program_id_index))?;
+            let mut discriminator = [0; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN];
+            let len = instruction
+                .data
+                .len()
+                .min(PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN);
+            discriminator[..len].copy_from_slice(&instruction.data[..len]);
+            Some((*program_id, discriminator))
+        })
+        .collect()
+}
+
 // Update the `completed_data_indexes` with a new shred `new_shred_index`. If a
 // data set is complete, return the range of shred indexes [start_index, end_index]
 // for that completed data set.
@@ -3884,6 +4185,7 @@ pub fn create_new_ledger(
             recovery_mode: None,
             enforce_ulimit_nofile: false,
             column_options: column_options.clone(),
+            archive_path: None,
         },
     )?;
     let ticks_per_slot = genesis_config.ticks_per_slot;
@@ -8422,6 +8724,234 @@ pub mod tests {
         assert!(sig_infos.infos.is_empty());
     }
 
+    #[test]
+    fn test_get_confirmed_signatures_for_program_instruction() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let (shreds, _) = make_slot_entries(1, 0, 4, /*merkle_variant:*/ true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+
+        // Each slot holds two transactions invoking the program, with instruction data
+        // [0, 1] and then [1]
+        let program_id = solana_sdk::pubkey::new_rand();
+        let mut block_signatures = vec![];
+        for slot in 2..=5 {
+            let transactions = [vec![0, 1], vec![1]]
+                .into_iter()
+                .map(|data| {
+                    Transaction::new_with_compiled_instructions(
+                        &[&Keypair::new()],
+                        &[],
+                        Hash::default(),
+                        vec![program_id],
+                        vec![CompiledInstruction::new_from_raw_parts(1, data, vec![])],
+                    )
+                })
+                .collect();
+            let mut entries = vec![next_entry_mut(&mut Hash::default(), 0, transactions)];
+            entries.append(&mut create_ticks(1, 0, Hash::default()));
+            let shreds = entries_to_test_shreds(
+                &entries,
+                slot,
+                slot - 1, // parent_slot
+                true,     // is_full_slot
+                0,        // version
+                true,     // merkle_variant
+            );
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+
+            let transactions = entries.into_iter().flat_map(|entry| entry.transactions);
+            for (transaction_index, transaction) in transactions.enumerate() {
+                let signature = transaction.signatures[0];
+                let account_keys =
+                    AccountKeys::new(transaction.message.static_account_keys(), None);
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        signature,
+                        account_keys.iter().collect(),
+                        vec![],
+                        TransactionStatusMeta::default(),
+                    )
+                    .unwrap();
+                let keys = program_instruction_keys(
+                    &account_keys,
+                    transaction.message.instructions(),
+                    None,
+                );
+                blockstore
+                    .write_program_instruction_signatures(
+                        slot,
+                        transaction_index as u32,
+                        signature,
+                        &keys,
+                    )
+                    .unwrap();
+                block_signatures.push((slot, signature));
+            }
+        }
+        // Leave slot 5 unrooted
+        blockstore.set_roots(vec![1, 2, 3, 4].iter()).unwrap();
+        let highest_confirmed_root = 4;
+        let query = |discriminator: &[u8], highest_slot, before, until, limit| {
+            blockstore
+                .get_confirmed_signatures_for_program_instruction(
+                    program_id,
+                    discriminator,
+                    highest_slot,
+                    before,
+                    until,
+                    limit,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|info| (info.slot, info.signature))
+                .collect::<Vec<_>>()
+        };
+
+        // All rooted transactions, most recent first
+        let all: Vec<_> = block_signatures
+            .iter()
+            .filter(|(slot, _)| *slot <= highest_confirmed_root)
+            .rev()
+            .copied()
+            .collect();
+        assert_eq!(all.len(), 6);
+        assert_eq!(
+            query(&[], highest_confirmed_root, None, None, usize::MAX),
+            all
+        );
+
+        // Exact and prefix discriminators
+        let first_instructions: Vec<_> = all.iter().skip(1).step_by(2).copied().collect();
+        let second_instructions: Vec<_> = all.iter().step_by(2).copied().collect();
+        assert_eq!(
+            query(
+                &[0, 1, 0, 0, 0, 0, 0, 0],
+                highest_confirmed_root,
+                None,
+                None,
+                usize::MAX
+            ),
+            first_instructions
+        );
+        assert_eq!(
+            query(&[0, 1], highest_confirmed_root, None, None, usize::MAX),
+            first_instructions
+        );
+        assert_eq!(
+            query(&[1], highest_confirmed_root, None, None, usize::MAX),
+            second_instructions
+        );
+        assert_eq!(
+            query(&[0, 2], highest_confirmed_root, None, None, usize::MAX),
+            vec![]
+        );
+
+        // Limit and pagination
+        assert_eq!(
+            query(&[], highest_confirmed_root, None, None, 2),
+            all[..2].to_vec()
+        );
+        assert_eq!(
+            query(&[0, 1], highest_confirmed_root, None, None, 2),
+            first_instructions[..2].to_vec()
+        );
+        assert_eq!(
+            query(&[], highest_confirmed_root, Some(all[1].1), None, 3),
+            all[2..5].to_vec()
+        );
+        assert_eq!(
+            query(
+                &[],
+                highest_confirmed_root,
+                None,
+                Some(all[4].1),
+                usize::MAX
+            ),
+            all[..4].to_vec()
+        );
+        assert_eq!(
+            query(
+                &[],
+                highest_confirmed_root,
+                Some(all[0].1),
+                Some(all[3].1),
+                usize::MAX
+            ),
+            all[1..3].to_vec()
+        );
+
+        // Confirmed but unrooted slots are included up to the highest slot
+        let with_unrooted = query(&[], 5, None, None, usize::MAX);
+        assert_eq!(with_unrooted.len(), 8);
+        assert_eq!(with_unrooted[2..], all[..]);
+
+        // Discriminators are at most 8 bytes
+        assert!(blockstore
+            .get_confirmed_signatures_for_program_instruction(
+                program_id,
+                &[0; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN + 1],
+                highest_confirmed_root,
+                None,
+                None,
+                usize::MAX,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_previous_discriminator() {
+        assert_eq!(
+            previous_discriminator([0, 1, 0, 0, 0, 0, 0, 2]),
+            Some([0, 1, 0, 0, 0, 0, 0, 1])
+        );
+        assert_eq!(
+            previous_discriminator([0, 1, 0, 0, 0, 0, 0, 0]),
+            Some([0, 0, 255, 255, 255, 255, 255, 255])
+        );
+        assert_eq!(previous_discriminator([0; 8]), None);
+    }
+
+    #[test]
+    fn test_program_instruction_keys() {
+        let program0 = Pubkey::new_unique();
+        let program1 = Pubkey::new_unique();
+        let static_keys = vec![Pubkey::new_unique(), program0, program1];
+        let account_keys = AccountKeys::new(&static_keys, None);
+        let instructions = vec![
+            CompiledInstruction::new_from_raw_parts(1, vec![1, 2, 3, 4, 5, 6, 7, 8, 9], vec![]),
+            CompiledInstruction::new_from_raw_parts(2, vec![], vec![]),
+            // Duplicate key
+            CompiledInstruction::new_from_raw_parts(1, vec![1, 2, 3, 4, 5, 6, 7, 8], vec![]),
+            // Out of bounds program id index
+            CompiledInstruction::new_from_raw_parts(3, vec![1], vec![]),
+        ];
+        let inner_instructions = vec![InnerInstructions {
+            index: 0,
+            instructions: vec![InnerInstruction {
+                instruction: CompiledInstruction::new_from_raw_parts(2, vec![7, 7], vec![]),
+                stack_height: Some(2),
+            }],
+        }];
+
+        let top_level_keys = BTreeSet::from([
+            (program0, [1, 2, 3, 4, 5, 6, 7, 8]),
+            (program1, [0; PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN]),
+        ]);
+        assert_eq!(
+            program_instruction_keys(&account_keys, &instructions, None),
+            top_level_keys
+        );
+        let mut all_keys = top_level_keys;
+        all_keys.insert((program1, [7, 7, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(
+            program_instruction_keys(&account_keys, &instructions, Some(&inner_instructions)),
+            all_keys
+        );
+    }
+
     #[test]
     fn test_get_last_hash() {
         let entries: Vec<Entry> = vec![];
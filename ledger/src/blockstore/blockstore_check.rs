@@ -0,0 +1,826 @@
+//! Cross-column consistency checks of the blockstore, and repairs of the
+//! inconsistencies that can be safely re-derived from the stored shreds.
+
+use {
+    super::*,
+    crate::blockstore_db::ColumnName,
+    std::{collections::BTreeMap, fmt},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlockstoreInconsistency {
+    /// The shred can't be deserialized, or doesn't match its key
+    InvalidShred {
+        slot: Slot,
+        index: u64,
+        shred_type: ShredType,
+    },
+    /// Data shreds are stored for a slot without a `SlotMeta`
+    MissingSlotMeta { slot: Slot },
+    /// The `SlotMeta` fields don't match the stored data shreds
+    SlotMetaMismatch {
+        slot: Slot,
+        fields: Vec<&'static str>,
+    },
+    /// The parent slot of the `SlotMeta` isn't the one of its data shreds
+    SlotParentMismatch {
+        slot: Slot,
+        parent_slot: Slot,
+        shred_parent_slot: Slot,
+    },
+    /// Shreds are stored for a slot without an `Index`
+    MissingIndex { slot: Slot },
+    /// The `Index` doesn't match the stored shreds
+    IndexMismatch {
+        slot: Slot,
+        shred_type: ShredType,
+        num_unindexed: usize,
+        num_missing: usize,
+    },
+    /// Coding shreds are stored for an erasure set without an `ErasureMeta`
+    MissingErasureMeta { slot: Slot, fec_set_index: u64 },
+    /// The `ErasureMeta` doesn't match the coding shreds of its erasure set
+    ErasureMetaMismatch { slot: Slot, fec_set_index: u64 },
+    /// An `ErasureMeta` is stored for an erasure set without coding shreds
+    OrphanedErasureMeta { slot: Slot, fec_set_index: u64 },
+    /// A root without a `SlotMeta`, other than the lowest one checked
+    MissingRootSlotMeta { slot: Slot },
+    /// A root whose slot isn't full
+    IncompleteRoot { slot: Slot },
+    /// The parent of a root isn't the previous root
+    RootParentMismatch {
+        slot: Slot,
+        parent_slot: Slot,
+        previous_root: Slot,
+    },
+    /// Transaction statuses are stored under a primary index other than 0 and 1
+    UnknownPrimaryIndex { primary_index: u64 },
+    /// The `TransactionStatusIndex` entry of a primary index is missing
+    MissingTransactionStatusIndex { primary_index: u64 },
+    /// Transaction statuses are stored under a primary index for slots above its `max_slot`,
+    /// so that they could be purged early
+    TransactionStatusIndexMaxSlot {
+        primary_index: u64,
+        max_slot: Slot,
+        highest_slot: Slot,
+    },
+    /// An `AddressSignatures` entry without a `TransactionStatus`
+    MissingTransactionStatus {
+        primary_index: u64,
+        address: Pubkey,
+        slot: Slot,
+        signature: Signature,
+    },
+}
+
+impl BlockstoreInconsistency {
+    /// Whether `Blockstore::check_consistency()` repairs the inconsistency when asked to
+    pub fn is_fixable(&self) -> bool {
+        matches!(
+            self,
+            Self::MissingSlotMeta { .. }
+                | Self::SlotMetaMismatch { .. }
+                | Self::MissingIndex { .. }
+                | Self::IndexMismatch { .. }
+                | Self::MissingErasureMeta { .. }
+                | Self::MissingTransactionStatusIndex { .. }
+                | Self::TransactionStatusIndexMaxSlot { .. }
+                | Self::MissingTransactionStatus { .. }
+        )
+    }
+}
+
+impl fmt::Display for BlockstoreInconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidShred {
+                slot,
+                index,
+                shred_type,
+            } => write!(f, "slot {slot}: invalid {shred_type:?} shred {index}"),
+            Self::MissingSlotMeta { slot } => {
+                write!(f, "slot {slot}: data shreds without a SlotMeta")
+            }
+            Self::SlotMetaMismatch { slot, fields } => write!(
+                f,
+                "slot {slot}: SlotMeta {} don't match the data shreds",
+                fields.join(", ")
+            ),
+            Self::SlotParentMismatch {
+                slot,
+                parent_slot,
+                shred_parent_slot,
+            } => write!(
+                f,
+                "slot {slot}: SlotMeta parent {parent_slot} but data shreds parent {shred_parent_slot}"
+            ),
+            Self::MissingIndex { slot } => write!(f, "slot {slot}: shreds without an Index"),
+            Self::IndexMismatch {
+                slot,
+                shred_type,
+                num_unindexed,
+                num_missing,
+            } => write!(
+                f,
+                "slot {slot}: Index is missing {num_unindexed} stored {shred_type:?} shreds \
+                 and has {num_missing} {shred_type:?} shreds that aren't stored"
+            ),
+            Self::MissingErasureMeta {
+                slot,
+                fec_set_index,
+            } => write!(
+                f,
+                "slot {slot}: coding shreds of erasure set {fec_set_index} without an ErasureMeta"
+            ),
+            Self::ErasureMetaMismatch {
+                slot,
+                fec_set_index,
+            } => write!(
+                f,
+                "slot {slot}: ErasureMeta of erasure set {fec_set_index} doesn't match its coding shreds"
+            ),
+            Self::OrphanedErasureMeta {
+                slot,
+                fec_set_index,
+            } => write!(
+                f,
+                "slot {slot}: ErasureMeta of erasure set {fec_set_index} without coding shreds"
+            ),
+            Self::MissingRootSlotMeta { slot } => write!(f, "root {slot}: no SlotMeta"),
+            Self::IncompleteRoot { slot } => write!(f, "root {slot}: slot isn't full"),
+            Self::RootParentMismatch {
+                slot,
+                parent_slot,
+                previous_root,
+            } => write!(
+                f,
+                "root {slot}: parent {parent_slot} isn't the previous root {previous_root}"
+            ),
+            Self::UnknownPrimaryIndex { primary_index } => write!(
+                f,
+                "transaction statuses stored under unknown primary index {primary_index}"
+            ),
+            Self::MissingTransactionStatusIndex { primary_index } => write!(
+                f,
+                "no TransactionStatusIndex entry for primary index {primary_index}"
+            ),
+            Self::TransactionStatusIndexMaxSlot {
+                primary_index,
+                max_slot,
+                highest_slot,
+            } => write!(
+                f,
+                "TransactionStatusIndex max slot {max_slot} of primary index {primary_index} \
+                 is below its highest transaction status slot {highest_slot}"
+            ),
+            Self::MissingTransactionStatus {
+                primary_index,
+                address,
+                slot,
+                signature,
+            } => write!(
+                f,
+                "slot {slot}: AddressSignatures entry for {address} and {signature} \
+                 without a TransactionStatus in primary index {primary_index}"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BlockstoreCheckReport {
+    pub num_slots: usize,
+    pub num_roots: usize,
+    pub num_transaction_statuses: usize,
+    pub num_address_signatures: usize,
+    /// The inconsistencies found, each with whether it was repaired
+    pub inconsistencies: Vec<(BlockstoreInconsistency, bool)>,
+}
+
+impl BlockstoreCheckReport {
+    /// The number of inconsistencies that were repaired
+    pub fn num_fixed(&self) -> usize {
+        self.inconsistencies
+            .iter()
+            .filter(|(_, fixed)| *fixed)
+            .count()
+    }
+
+    fn push(&mut self, inconsistency: BlockstoreInconsistency, fixed: bool) {
+        self.inconsistencies.push((inconsistency, fixed));
+    }
+}
+
+/// What the `SlotMeta` and `Index` of a slot are expected to hold, derived from its shreds
+#[derive(Default)]
+struct StoredShreds {
+    data_indexes: BTreeSet<u64>,
+    coding_indexes: BTreeSet<u64>,
+    last_index: Option<u64>,
+    completed_data_indexes: BTreeSet<u32>,
+    parent_slot: Option<Slot>,
+    /// A coding shred of each erasure set, by FEC set index
+    erasure_sets: BTreeMap<u64, Shred>,
+}
+
+impl StoredShreds {
+    fn consumed(&self) -> u64 {
+        self.data_indexes
+            .iter()
+            .zip(0..)
+            .take_while(|(index, expected)| **index == *expected)
+            .count() as u64
+    }
+
+    fn received(&self) -> u64 {
+        self.data_indexes
+            .iter()
+            .next_back()
+            .map_or(0, |index| index + 1)
+    }
+
+    /// Sets the fields of `slot_meta` derived from the shreds, returning the
+    /// names of the ones that didn't match
+    fn update_slot_meta(&self, slot_meta: &mut SlotMeta) -> Vec<&'static str> {
+        let mut fields = vec![];
+        if slot_meta.consumed != self.consumed() {
+            slot_meta.consumed = self.consumed();
+            fields.push("consumed");
+        }
+        if slot_meta.received != self.received() {
+            slot_meta.received = self.received();
+            fields.push("received");
+        }
+        if slot_meta.last_index != self.last_index {
+            slot_meta.last_index = self.last_index;
+            fields.push("last_index");
+        }
+        if slot_meta.completed_data_indexes != self.completed_data_indexes {
+            slot_meta.completed_data_indexes = self.completed_data_indexes.clone();
+            fields.push("completed_data_indexes");
+        }
+        fields
+    }
+
+    fn index(&self, slot: Slot) -> Index {
+        let mut index = Index::new(slot);
+        for shred_index in &self.data_indexes {
+            index.data_mut().insert(*shred_index);
+        }
+        for shred_index in &self.coding_indexes {
+            index.coding_mut().insert(*shred_index);
+        }
+        index
+    }
+}
+
+impl Blockstore {
+    /// Cross-checks the blockstore columns for the slots in
+    /// \[`starting_slot`, `ending_slot`\]:
+    ///
+    /// * `SlotMeta` and `Index` against the `ShredData` and `ShredCode` columns
+    /// * `ErasureMeta` against the coding shreds of each erasure set
+    /// * `Root` continuity, each root being full and the child of the previous one
+    /// * `TransactionStatusIndex` against the primary indexes of the whole
+    ///   `TransactionStatus` column
+    /// * that each `AddressSignatures` entry has a `TransactionStatus`
+    ///
+    /// With `fix`, the inconsistencies that are [`fixable`] are repaired by
+    /// re-deriving the entries from the shreds, or by dropping the dangling
+    /// `AddressSignatures` entries.
+    ///
+    /// Note that shreds evicted by FIFO compaction, and columns cleaned up by
+    /// the compaction filter at different times, are reported as well.
+    ///
+    /// [`fixable`]: BlockstoreInconsistency::is_fixable
+    pub fn check_consistency(
+        &self,
+        starting_slot: Slot,
+        ending_slot: Slot,
+        fix: bool,
+    ) -> Result<BlockstoreCheckReport> {
+        let mut report = BlockstoreCheckReport::default();
+
+        let mut slots: BTreeSet<Slot> = self
+            .db
+            .iter::<cf::SlotMeta>(IteratorMode::From(
+                starting_slot,
+                IteratorDirection::Forward,
+            ))?
+            .map(|(slot, _)| slot)
+            .take_while(|slot| *slot <= ending_slot)
+            .collect();
+        slots.extend(self.column_slots::<cf::ShredData>(starting_slot, ending_slot)?);
+        slots.extend(self.column_slots::<cf::ShredCode>(starting_slot, ending_slot)?);
+        slots.extend(self.column_slots::<cf::ErasureMeta>(starting_slot, ending_slot)?);
+        for slot in slots {
+            self.check_slot(slot, fix, &mut report)?;
+        }
+
+        self.check_roots(starting_slot, ending_slot, &mut report)?;
+        self.check_transaction_status_index(fix, &mut report)?;
+        self.check_address_signatures(starting_slot, ending_slot, fix, &mut report)?;
+        Ok(report)
+    }
+
+    /// The distinct slots of the keys of a column indexed by slot and shred
+    /// or FEC set index, within \[`starting_slot`, `ending_slot`\]
+    fn column_slots<C>(&self, starting_slot: Slot, ending_slot: Slot) -> Result<Vec<Slot>>
+    where
+        C: Column<Index = (Slot, u64)> + ColumnName,
+    {
+        let mut slots = vec![];
+        let mut slot = starting_slot;
+        while slot <= ending_slot {
+            let Some(((next_slot, _), _)) = self
+                .db
+                .iter::<C>(IteratorMode::From((slot, 0), IteratorDirection::Forward))?
+                .next()
+            else {
+                break;
+            };
+            if next_slot > ending_slot {
+                break;
+            }
+            slots.push(next_slot);
+            match next_slot.checked_add(1) {
+                Some(next_slot) => slot = next_slot,
+                None => break,
+            }
+        }
+        Ok(slots)
+    }
+
+    fn check_slot(&self, slot: Slot, fix: bool, report: &mut BlockstoreCheckReport) -> Result<()> {
+        report.num_slots += 1;
+        let mut shreds = StoredShreds::default();
+        for ((_, index), bytes) in self.slot_data_iterator(slot, 0)? {
+            shreds.data_indexes.insert(index);
+            match Shred::new_from_serialized_shred(bytes.to_vec()) {
+                Ok(shred)
+                    if shred.slot() == slot
+                        && u64::from(shred.index()) == index
+                        && shred.shred_type() == ShredType::Data =>
+                {
+                    if shred.last_in_slot() && shreds.last_index.is_none() {
+                        shreds.last_index = Some(index);
+                    }
+                    if shred.last_in_slot() || shred.data_complete() {
+                        shreds.completed_data_indexes.insert(shred.index());
+                    }
+                    if shreds.parent_slot.is_none() {
+                        shreds.parent_slot = shred.parent().ok();
+                    }
+                }
+                _ => report.push(
+                    BlockstoreInconsistency::InvalidShred {
+                        slot,
+                        index,
+                        shred_type: ShredType::Data,
+                    },
+                    false,
+                ),
+            }
+        }
+        for ((_, index), bytes) in self.slot_coding_iterator(slot, 0)? {
+            shreds.coding_indexes.insert(index);
+            match Shred::new_from_serialized_shred(bytes.to_vec()) {
+                Ok(shred)
+                    if shred.slot() == slot
+                        && u64::from(shred.index()) == index
+                        && shred.shred_type() == ShredType::Code =>
+                {
+                    shreds
+                        .erasure_sets
+                        .entry(u64::from(shred.fec_set_index()))
+                        .or_insert(shred);
+                }
+                _ => report.push(
+                    BlockstoreInconsistency::InvalidShred {
+                        slot,
+                        index,
+                        shred_type: ShredType::Code,
+                    },
+                    false,
+                ),
+            }
+        }
+
+        // Coding shreds alone don't create a SlotMeta
+        match self.meta_cf.get(slot)? {
+            None if !shreds.data_indexes.is_empty() => {
+                // The parent slot comes from the parent offset of the data
+                // shreds, so the SlotMeta can't be rebuilt if none is valid
+                let fixed = match shreds.parent_slot {
+                    Some(parent_slot) if fix => {
+                        let mut slot_meta = SlotMeta::new(slot, Some(parent_slot));
+                        shreds.update_slot_meta(&mut slot_meta);
+                        self.meta_cf.put(slot, &slot_meta)?;
+                        true
+                    }
+                    _ => false,
+                };
+                report.push(BlockstoreInconsistency::MissingSlotMeta { slot }, fixed);
+            }
+            None => (),
+            Some(mut slot_meta) => {
+                let fields = shreds.update_slot_meta(&mut slot_meta);
+                if !fields.is_empty() {
+                    if fix {
+                        self.meta_cf.put(slot, &slot_meta)?;
+                    }
+                    report.push(
+                        BlockstoreInconsistency::SlotMetaMismatch { slot, fields },
+                        fix,
+                    );
+                }
+                if let (Some(parent_slot), Some(shred_parent_slot)) =
+                    (slot_meta.parent_slot, shreds.parent_slot)
+                {
+                    if parent_slot != shred_parent_slot {
+                        report.push(
+                            BlockstoreInconsistency::SlotParentMismatch {
+                                slot,
+                                parent_slot,
+                                shred_parent_slot,
+                            },
+                            false,
+                        );
+                    }
+                }
+            }
+        }
+
+        let expected_index = shreds.index(slot);
+        match self.index_cf.get(slot)? {
+            None if !shreds.data_indexes.is_empty() || !shreds.coding_indexes.is_empty() => {
+                if fix {
+                    self.index_cf.put(slot, &expected_index)?;
+                }
+                report.push(BlockstoreInconsistency::MissingIndex { slot }, fix);
+            }
+            None => (),
+            Some(index) => {
+                let mut mismatches = vec![];
+                for (shred_type, stored, indexed) in [
+                    (ShredType::Data, &shreds.data_indexes, index.data()),
+                    (ShredType::Code, &shreds.coding_indexes, index.coding()),
+                ] {
+                    let indexed: BTreeSet<u64> = indexed.range(..).copied().collect();
+                    if &indexed != stored {
+                        mismatches.push(BlockstoreInconsistency::IndexMismatch {
+                            slot,
+                            shred_type,
+                            num_unindexed: stored.difference(&indexed).count(),
+                            num_missing: indexed.difference(stored).count(),
+                        });
+                    }
+                }
+                if !mismatches.is_empty() && fix {
+                    self.index_cf.put(slot, &expected_index)?;
+                }
+                for mismatch in mismatches {
+                    report.push(mismatch, fix);
+                }
+            }
+        }
+
+        for (fec_set_index, shred) in &shreds.erasure_sets {
+            match self.erasure_meta_cf.get((slot, *fec_set_index))? {
+                None => {
+                    let fixed = match ErasureMeta::from_coding_shred(shred) {
+                        Some(erasure_meta) if fix => {
+                            self.erasure_meta_cf
+                                .put((slot, *fec_set_index), &erasure_meta)?;
+                            true
+                        }
+                        _ => false,
+                    };
+                    report.push(
+                        BlockstoreInconsistency::MissingErasureMeta {
+                            slot,
+                            fec_set_index: *fec_set_index,
+                        },
+                        fixed,
+                    );
+                }
+                Some(erasure_meta) if !erasure_meta.check_coding_shred(shred) => {
+                    report.push(
+                        BlockstoreInconsistency::ErasureMetaMismatch {
+                            slot,
+                            fec_set_index: *fec_set_index,
+                        },
+                        false,
+                    );
+                }
+                Some(_) => (),
+            }
+        }
+        for ((_, fec_set_index), _) in self
+            .erasure_meta_cf
+            .iter(IteratorMode::From((slot, 0), IteratorDirection::Forward))?
+            .take_while(|((erasure_slot, _), _)| *erasure_slot == slot)
+        {
+            if !shreds.erasure_sets.contains_key(&fec_set_index) {
+                report.push(
+                    BlockstoreInconsistency::OrphanedErasureMeta {
+                        slot,
+                        fec_set_index,
+                    },
+                    false,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn check_roots(
+        &self,
+        starting_slot: Slot,
+        ending_slot: Slot,
+        report: &mut BlockstoreCheckReport,
+    ) -> Result<()> {
+        let mut previous_root = None;
+        for root in self
+            .rooted_slot_iterator(starting_slot)?
+            .take_while(|slot| *slot <= ending_slot)
+        {
+            report.num_roots += 1;
+            match self.meta_cf.get(root)? {
+                // The lowest root, such as a snapshot slot, may not have shreds
+                None if previous_root.is_none() => (),
+                None => report.push(
+                    BlockstoreInconsistency::MissingRootSlotMeta { slot: root },
+                    false,
+                ),
+                Some(slot_meta) => {
+                    if !slot_meta.is_full() {
+                        report.push(
+                            BlockstoreInconsistency::IncompleteRoot { slot: root },
+                            false,
+                        );
+                    }
+                    if let (Some(parent_slot), Some(previous_root)) =
+                        (slot_meta.parent_slot, previous_root)
+                    {
+                        if parent_slot != previous_root {
+                            report.push(
+                                BlockstoreInconsistency::RootParentMismatch {
+                                    slot: root,
+                                    parent_slot,
+                                    previous_root,
+                                },
+                                false,
+                            );
+                        }
+                    }
+                }
+            }
+            previous_root = Some(root);
+        }
+        Ok(())
+    }
+
+    fn check_transaction_status_index(
+        &self,
+        fix: bool,
+        report: &mut BlockstoreCheckReport,
+    ) -> Result<()> {
+        // Keep the primary indexes from being frozen or purged while checking them
+        let _active_transaction_status_index =
+            self.active_transaction_status_index.write().unwrap();
+
+        let mut highest_slots = BTreeMap::<u64, Slot>::new();
+        for ((primary_index, _, slot), _) in self.transaction_status_cf.iter(IteratorMode::Start)? {
+            report.num_transaction_statuses += 1;
+            let highest_slot = highest_slots.entry(primary_index).or_default();
+            *highest_slot = (*highest_slot).max(slot);
+        }
+
+        for primary_index in highest_slots.keys() {
+            if *primary_index > 1 {
+                report.push(
+                    BlockstoreInconsistency::UnknownPrimaryIndex {
+                        primary_index: *primary_index,
+                    },
+                    false,
+                );
+            }
+        }
+        for primary_index in 0..=1 {
+            let highest_slot = highest_slots.get(&primary_index).copied();
+            match self.transaction_status_index_cf.get(primary_index)? {
+                None => {
+                    if fix {
+                        self.transaction_status_index_cf.put(
+                            primary_index,
+                            &TransactionStatusIndexMeta {
+                                max_slot: highest_slot.unwrap_or_default(),
+                                frozen: false,
+                            },
+                        )?;
+                    }
+                    report.push(
+                        BlockstoreInconsistency::MissingTransactionStatusIndex { primary_index },
+                        fix,
+                    );
+                }
+                Some(mut index_meta) => {
+                    let Some(highest_slot) =
+                        highest_slot.filter(|slot| *slot > index_meta.max_slot)
+                    else {
+                        continue;
+                    };
+                    let max_slot = index_meta.max_slot;
+                    if fix {
+                        index_meta.max_slot = highest_slot;
+                        self.transaction_status_index_cf
+                            .put(primary_index, &index_meta)?;
+                    }
+                    report.push(
+                        BlockstoreInconsistency::TransactionStatusIndexMaxSlot {
+                            primary_index,
+                            max_slot,
+                            highest_slot,
+                        },
+                        fix,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_address_signatures(
+        &self,
+        starting_slot: Slot,
+        ending_slot: Slot,
+        fix: bool,
+        report: &mut BlockstoreCheckReport,
+    ) -> Result<()> {
+        for ((primary_index, address, slot, signature), _) in
+            self.address_signatures_cf.iter(IteratorMode::Start)?
+        {
+            if slot < starting_slot || slot > ending_slot {
+                continue;
+            }
+            report.num_address_signatures += 1;
+            if self
+                .transaction_status_cf
+                .get_bytes((primary_index, signature, slot))?
+                .is_some()
+            {
+                continue;
+            }
+            if fix {
+                self.address_signatures_cf
+                    .delete((primary_index, address, slot, signature))?;
+            }
+            report.push(
+                BlockstoreInconsistency::MissingTransactionStatus {
+                    primary_index,
+                    address,
+                    slot,
+                    signature,
+                },
+                fix,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use {
+        super::*,
+        crate::{
+            blockstore::tests::make_slot_entries_with_transactions, get_tmp_ledger_path_auto_delete,
+        },
+    };
+
+    #[test]
+    fn test_check_consistency() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        let entries = make_slot_entries_with_transactions(10);
+        let shredder = Shredder::new(1, 0, 0, 0).unwrap();
+        let (data_shreds, coding_shreds) = shredder.entries_to_shreds(
+            &Keypair::new(),
+            &entries,
+            true, // is_last_in_slot
+            0,    // next_shred_index
+            0,    // next_code_index
+            true, // merkle_variant
+            &ReedSolomonCache::default(),
+            &mut ProcessShredsStats::default(),
+        );
+        let num_data_shreds = data_shreds.len() as u64;
+        let fec_set_index = u64::from(coding_shreds[0].fec_set_index());
+        blockstore
+            .insert_shreds([data_shreds, coding_shreds].concat(), None, false)
+            .unwrap();
+        let (shreds, _) = make_slot_entries(2, 1, 10, /*merkle_variant:*/ true);
+        blockstore.insert_shreds(shreds, None, false).unwrap();
+        blockstore.set_roots([1, 2].iter()).unwrap();
+        let signature = entries[0].transactions[0].signatures[0];
+        let address = Pubkey::new_unique();
+        blockstore
+            .write_transaction_status(
+                1,
+                signature,
+                vec![&address],
+                vec![],
+                TransactionStatusMeta::default(),
+            )
+            .unwrap();
+
+        let report = blockstore.check_consistency(0, Slot::MAX, false).unwrap();
+        assert_eq!(report.inconsistencies, vec![]);
+        // Slot 0 only has the SlotMeta created as the parent of slot 1
+        assert_eq!(report.num_slots, 3);
+        assert_eq!(report.num_roots, 2);
+        assert_eq!(report.num_transaction_statuses, 1);
+        assert_eq!(report.num_address_signatures, 1);
+
+        // Break each column
+        let mut slot_meta = blockstore.meta(1).unwrap().unwrap();
+        slot_meta.consumed = 1;
+        blockstore.meta_cf.put(1, &slot_meta).unwrap();
+        let slot_meta2 = blockstore.meta(2).unwrap().unwrap();
+        blockstore.meta_cf.delete(2).unwrap();
+        blockstore.index_cf.delete(2).unwrap();
+        blockstore
+            .erasure_meta_cf
+            .delete((1, fec_set_index))
+            .unwrap();
+        let mut write_batch = blockstore.db.batch().unwrap();
+        write_batch
+            .delete::<cf::TransactionStatus>((0, signature, 1))
+            .unwrap();
+        blockstore.db.write(write_batch).unwrap();
+        blockstore.set_roots([4].iter()).unwrap();
+
+        let report = blockstore.check_consistency(0, Slot::MAX, true).unwrap();
+        assert_eq!(
+            report.inconsistencies,
+            vec![
+                (
+                    BlockstoreInconsistency::SlotMetaMismatch {
+                        slot: 1,
+                        fields: vec!["consumed"],
+                    },
+                    true
+                ),
+                (
+                    BlockstoreInconsistency::MissingErasureMeta {
+                        slot: 1,
+                        fec_set_index,
+                    },
+                    true
+                ),
+                (BlockstoreInconsistency::MissingSlotMeta { slot: 2 }, true),
+                (BlockstoreInconsistency::MissingIndex { slot: 2 }, true),
+                (
+                    BlockstoreInconsistency::MissingRootSlotMeta { slot: 4 },
+                    false
+                ),
+                (
+                    BlockstoreInconsistency::MissingTransactionStatus {
+                        primary_index: 0,
+                        address,
+                        slot: 1,
+                        signature,
+                    },
+                    true
+                ),
+            ]
+        );
+        assert_eq!(report.num_fixed(), 5);
+        assert_eq!(
+            blockstore.meta(1).unwrap().unwrap().consumed,
+            num_data_shreds
+        );
+        let rebuilt_slot_meta2 = blockstore.meta(2).unwrap().unwrap();
+        assert_eq!(rebuilt_slot_meta2.parent_slot, Some(1));
+        assert_eq!(rebuilt_slot_meta2.consumed, slot_meta2.consumed);
+        assert_eq!(rebuilt_slot_meta2.received, slot_meta2.received);
+        assert_eq!(rebuilt_slot_meta2.last_index, slot_meta2.last_index);
+        assert_eq!(
+            rebuilt_slot_meta2.completed_data_indexes,
+            slot_meta2.completed_data_indexes
+        );
+
+        // Only the inconsistencies that can't be fixed remain
+        let report = blockstore.check_consistency(0, Slot::MAX, false).unwrap();
+        assert_eq!(
+            report.inconsistencies,
+            vec![(
+                BlockstoreInconsistency::MissingRootSlotMeta { slot: 4 },
+                false
+            )]
+        );
+        assert_eq!(report.num_address_signatures, 0);
+    }
+}
@@ -0,0 +1,342 @@
+//! Training of the zstd dictionaries that compress the values of the
+//! `TransactionStatus` and `Rewards` columns, and migration of the values
+//! already stored to the latest dictionary (see [`crate::blockstore_compression`]).
+
+use {
+    super::*,
+    crate::{
+        blockstore_compression::{self, compressed_value_version},
+        blockstore_db::{ColumnName, ProtobufColumn},
+    },
+    prost::Message,
+    rand::Rng,
+    serde::de::DeserializeOwned,
+    std::str::FromStr,
+};
+
+// Number of rewritten values per write batch when recompressing a column
+const RECOMPRESS_WRITE_BATCH_SIZE: usize = 10_000;
+
+/// The columns whose values can be compressed with a trained dictionary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompressibleColumn {
+    TransactionStatus,
+    Rewards,
+}
+
+impl CompressibleColumn {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TransactionStatus => cf::TransactionStatus::NAME,
+            Self::Rewards => cf::Rewards::NAME,
+        }
+    }
+}
+
+impl FromStr for CompressibleColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            cf::TransactionStatus::NAME => Ok(Self::TransactionStatus),
+            cf::Rewards::NAME => Ok(Self::Rewards),
+            _ => Err(format!(
+                "column {s} does not support dictionary compression"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecompressStats {
+    pub num_values: u64,
+    /// Values that were rewritten, as opposed to already compressed with the
+    /// latest dictionary
+    pub num_recompressed: u64,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl Blockstore {
+    /// Trains a zstd dictionary of at most `dictionary_size` bytes from up to
+    /// `max_samples` values of `column`, sampled uniformly, and stores it as a
+    /// new dictionary version. New values of `column` are then compressed with
+    /// it, including by other processes once they read a value compressed with
+    /// it. Returns the version of the dictionary.
+    pub fn train_compression_dictionary(
+        &self,
+        column: CompressibleColumn,
+        max_samples: usize,
+        dictionary_size: usize,
+    ) -> Result<u64> {
+        let samples = match column {
+            CompressibleColumn::TransactionStatus => {
+                sample_values::<_, StoredTransactionStatusMeta>(
+                    &self.transaction_status_cf,
+                    max_samples,
+                )?
+            }
+            CompressibleColumn::Rewards => {
+                sample_values::<_, StoredExtendedRewards>(&self.rewards_cf, max_samples)?
+            }
+        };
+        if samples.is_empty() {
+            return Err(BlockstoreError::Io(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("no values to train a dictionary from in {}", column.name()),
+            )));
+        }
+        let dictionary = blockstore_compression::train_dictionary(&samples, dictionary_size)?;
+
+        let version = self
+            .compression_dictionary_cf
+            .iter(IteratorMode::End)?
+            .next()
+            .map(|(version, _)| version + 1)
+            .unwrap_or(1);
+        let meta = CompressionDictionaryMeta {
+            column: column.name().to_string(),
+            num_samples: samples.len() as u64,
+            dictionary,
+        };
+        self.compression_dictionary_cf.put(version, &meta)?;
+        self.db
+            .compression_dictionaries()
+            .insert(version, &meta.column, &meta.dictionary);
+        Ok(version)
+    }
+
+    /// Returns all the compression dictionaries, by increasing version.
+    pub fn compression_dictionaries(&self) -> Result<Vec<(u64, CompressionDictionaryMeta)>> {
+        self.compression_dictionary_cf
+            .iter(IteratorMode::Start)?
+            .map(|(version, value)| Ok((version, deserialize(&value)?)))
+            .collect()
+    }
+
+    /// Rewrites the values of `column` that are not compressed with its latest
+    /// dictionary, then compacts the column if `compact` is set so that the
+    /// space of the previous values is reclaimed.
+    pub fn recompress_column(
+        &self,
+        column: CompressibleColumn,
+        compact: bool,
+    ) -> Result<RecompressStats> {
+        match column {
+            CompressibleColumn::TransactionStatus => {
+                recompress_values::<_, StoredTransactionStatusMeta>(
+                    &self.db,
+                    &self.transaction_status_cf,
+                    compact,
+                )
+            }
+            CompressibleColumn::Rewards => {
+                recompress_values::<_, StoredExtendedRewards>(&self.db, &self.rewards_cf, compact)
+            }
+        }
+    }
+}
+
+/// Returns up to `max_samples` uniformly sampled values of `column`, as
+/// uncompressed protobuf messages.
+fn sample_values<C, T>(column: &LedgerColumn<C>, max_samples: usize) -> Result<Vec<Vec<u8>>>
+where
+    C: ProtobufColumn + ColumnName,
+    T: DeserializeOwned + Into<C::Type>,
+{
+    let mut rng = rand::thread_rng();
+    let mut samples = Vec::with_capacity(max_samples);
+    for (i, (_, value)) in column.iter(IteratorMode::Start)?.enumerate() {
+        // reservoir sampling, to only decode the values that are kept
+        let position = if samples.len() < max_samples {
+            samples.len()
+        } else {
+            rng.gen_range(0, i + 1)
+        };
+        if position < max_samples {
+            let sample = column
+                .deserialize_protobuf_or_bincode::<T>(&value)?
+                .encode_to_vec();
+            if position == samples.len() {
+                samples.push(sample);
+            } else {
+                samples[position] = sample;
+            }
+        }
+    }
+    Ok(samples)
+}
+
+fn recompress_values<C, T>(
+    db: &Database,
+    column: &LedgerColumn<C>,
+    compact: bool,
+) -> Result<RecompressStats>
+where
+    C: ProtobufColumn + ColumnName,
+    C::Index: PartialOrd + Copy,
+    T: DeserializeOwned + Into<C::Type>,
+{
+    let compression_dictionaries = db.compression_dictionaries();
+    let latest_version = compression_dictionaries
+        .latest_version(C::NAME)
+        .ok_or_else(|| {
+            BlockstoreError::Io(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("no compression dictionary for {}", C::NAME),
+            ))
+        })?;
+
+    let mut stats = RecompressStats::default();
+    let mut write_batch = db.batch()?;
+    let mut write_batch_len = 0;
+    for (index, value) in column.iter(IteratorMode::Start)? {
+        stats.num_values += 1;
+        stats.bytes_before += value.len() as u64;
+        if compressed_value_version(&value) == Some(latest_version) {
+            stats.bytes_after += value.len() as u64;
+            continue;
+        }
+
+        let mut buf = column
+            .deserialize_protobuf_or_bincode::<T>(&value)?
+            .encode_to_vec();
+        if let Some(compressed) = compression_dictionaries.compress(C::NAME, &buf)? {
+            buf = compressed;
+        }
+        write_batch.put_bytes::<C>(index, &buf)?;
+        stats.num_recompressed += 1;
+        stats.bytes_after += buf.len() as u64;
+
+        write_batch_len += 1;
+        if write_batch_len == RECOMPRESS_WRITE_BATCH_SIZE {
+            db.write(write_batch)?;
+            write_batch = db.batch()?;
+            write_batch_len = 0;
+        }
+    }
+    db.write(write_batch)?;
+
+    if compact {
+        column.compact_range(0, Slot::MAX)?;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use {
+        super::*,
+        crate::get_tmp_ledger_path_auto_delete,
+        solana_transaction_status::{Reward, RewardType},
+    };
+
+    fn make_status(i: u64) -> TransactionStatusMeta {
+        TransactionStatusMeta {
+            fee: 5_000 + i % 3,
+            pre_balances: vec![1_000_000 + i, 2_000_000],
+            post_balances: vec![1_000_000 + i - 5_000, 2_000_000],
+            log_messages: Some(vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                format!("Program log: Instruction: Transfer {}", i * 7919),
+                "Program 11111111111111111111111111111111 success".to_string(),
+            ]),
+            // the protobuf encoding doesn't distinguish `None` from empty lists
+            pre_token_balances: Some(vec![]),
+            post_token_balances: Some(vec![]),
+            rewards: Some(vec![]),
+            ..TransactionStatusMeta::default()
+        }
+    }
+
+    #[test]
+    fn test_train_and_recompress() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        let signatures: Vec<_> = (0..1_000u64)
+            .map(|i| {
+                let signature = Signature::new_unique();
+                blockstore
+                    .write_transaction_status(i / 10, signature, vec![], vec![], make_status(i))
+                    .unwrap();
+                (signature, i / 10)
+            })
+            .collect();
+        let rewards = vec![Reward {
+            pubkey: Pubkey::new_unique().to_string(),
+            lamports: 42,
+            post_balance: 84,
+            reward_type: Some(RewardType::Staking),
+            commission: Some(10),
+        }];
+        blockstore.write_rewards(1, rewards.clone()).unwrap();
+
+        assert!(blockstore
+            .recompress_column(CompressibleColumn::TransactionStatus, false)
+            .is_err());
+        let version = blockstore
+            .train_compression_dictionary(CompressibleColumn::TransactionStatus, 500, 4096)
+            .unwrap();
+        assert_eq!(version, 1);
+        let dictionaries = blockstore.compression_dictionaries().unwrap();
+        assert_eq!(dictionaries.len(), 1);
+        assert_eq!(dictionaries[0].1.column, cf::TransactionStatus::NAME);
+        assert_eq!(dictionaries[0].1.num_samples, 500);
+
+        // new values are compressed, existing ones are left as is
+        let signature = Signature::new_unique();
+        blockstore
+            .write_transaction_status(100, signature, vec![], vec![], make_status(1_000))
+            .unwrap();
+        let value = blockstore
+            .transaction_status_cf
+            .get_bytes((0, signature, 100))
+            .unwrap()
+            .unwrap();
+        assert_eq!(compressed_value_version(&value), Some(1));
+        let (signature, slot) = signatures[0];
+        let value = blockstore
+            .transaction_status_cf
+            .get_bytes((0, signature, slot))
+            .unwrap()
+            .unwrap();
+        assert_eq!(compressed_value_version(&value), None);
+
+        let stats = blockstore
+            .recompress_column(CompressibleColumn::TransactionStatus, true)
+            .unwrap();
+        assert_eq!(stats.num_values, 1_001);
+        assert_eq!(stats.num_recompressed, 1_000);
+        assert!(stats.bytes_after < stats.bytes_before);
+        let stats = blockstore
+            .recompress_column(CompressibleColumn::TransactionStatus, false)
+            .unwrap();
+        assert_eq!(stats.num_recompressed, 0);
+
+        // the Rewards column has no dictionary
+        let value = blockstore.rewards_cf.get_bytes(1).unwrap().unwrap();
+        assert_eq!(compressed_value_version(&value), None);
+        assert_eq!(blockstore.read_rewards(1).unwrap(), Some(rewards));
+
+        // values compressed with an older dictionary stay readable, including
+        // after reopening the blockstore
+        let version = blockstore
+            .train_compression_dictionary(CompressibleColumn::TransactionStatus, 500, 4096)
+            .unwrap();
+        assert_eq!(version, 2);
+        drop(blockstore);
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+        for (i, (signature, slot)) in signatures.into_iter().enumerate() {
+            assert_eq!(
+                blockstore
+                    .read_transaction_status((signature, slot))
+                    .unwrap(),
+                Some(make_status(i as u64))
+            );
+        }
+        let stats = blockstore
+            .recompress_column(CompressibleColumn::TransactionStatus, false)
+            .unwrap();
+        assert_eq!(stats.num_recompressed, 1_001);
+    }
+}
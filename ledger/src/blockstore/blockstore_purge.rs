@@ -37,6 +37,8 @@ impl Blockstore {
     /// while the non-slot-id based column families, `cf::TransactionStatus`,
     /// `AddressSignature`, and `cf::TransactionStatusIndex`, are cleaned-up
     /// based on the `purge_type` setting.
+    ///
+    /// Blocks are not archived here, see `archive_rooted_slots()`.
     pub fn purge_slots(&self, from_slot: Slot, to_slot: Slot, purge_type: PurgeType) {
         let mut purge_stats = PurgeStats::default();
         let purge_result =
@@ -62,6 +64,44 @@ impl Blockstore {
         }
     }
 
+    /// Seals the complete rooted blocks up to `to_slot` that are not archived
+    /// yet into a new segment of the archive, if the blockstore has one.
+    ///
+    /// The blocks are read like any other, so this must be called before
+    /// `lowest_cleanup_slot` is raised past them and they are purged.
+    pub fn archive_rooted_slots(&self, to_slot: Slot) -> Result<()> {
+        let archive = match &self.archive {
+            Some(archive) => archive,
+            None => return Ok(()),
+        };
+        let from_slot = archive.last_slot().map_or(0, |last_slot| last_slot + 1);
+        if from_slot > to_slot {
+            return Ok(());
+        }
+        let mut archive_time = Measure::start("archive_rooted_slots");
+        let blocks = self
+            .rooted_slot_iterator(from_slot)?
+            .take_while(|slot| *slot <= to_slot)
+            .filter_map(|slot| match self.get_complete_block(slot, false) {
+                Ok(block) => Some((slot, block)),
+                Err(err) => {
+                    warn!("Unable to archive rooted slot {}: {:?}", slot, err);
+                    None
+                }
+            });
+        if let Some(slots) = archive.seal_segment(blocks)? {
+            info!("Archived rooted slots {:?}", slots);
+        }
+        archive_time.stop();
+        datapoint_info!(
+            "blockstore-archive",
+            ("from_slot", from_slot as i64, i64),
+            ("to_slot", to_slot as i64, i64),
+            ("archive_us", archive_time.as_us() as i64, i64),
+        );
+        Ok(())
+    }
+
     /// Usually this is paired with .purge_slots() but we can't internally call this in
     /// that function unconditionally. That's because set_max_expired_slot()
     /// expects to purge older slots by the successive chronological order, while .purge_slots()
@@ -360,13 +400,15 @@ impl Blockstore {
             let transactions = slot_entries
                 .into_iter()
                 .flat_map(|entry| entry.transactions);
-            for transaction in transactions {
+            for (transaction_index, transaction) in transactions.enumerate() {
                 if let Some(&signature) = transaction.signatures.get(0) {
                     batch.delete::<cf::TransactionStatus>((0, signature, slot))?;
                     batch.delete::<cf::TransactionStatus>((1, signature, slot))?;
 
                     let meta = self.read_transaction_status((signature, slot))?;
-                    let loaded_addresses = meta.map(|meta| meta.loaded_addresses);
+                    let (loaded_addresses, inner_instructions) = meta
+                        .map(|meta| (meta.loaded_addresses, meta.inner_instructions))
+                        .unzip();
                     let account_keys = AccountKeys::new(
                         transaction.message.static_account_keys(),
                         loaded_addresses.as_ref(),
@@ -376,6 +418,26 @@ impl Blockstore {
                         batch.delete::<cf::AddressSignatures>((0, *pubkey, slot, signature))?;
                         batch.delete::<cf::AddressSignatures>((1, *pubkey, slot, signature))?;
                     }
+
+                    // Inner instructions are only known when the transaction status was
+                    // stored with them, the other entries are left to the primary index purge
+                    let instruction_keys = program_instruction_keys(
+                        &account_keys,
+                        transaction.message.instructions(),
+                        inner_instructions.flatten().as_deref(),
+                    );
+                    let transaction_index = transaction_index as u32;
+                    for (program_id, discriminator) in instruction_keys {
+                        for primary_index in 0..=1 {
+                            batch.delete::<cf::ProgramInstructionSignatures>((
+                                primary_index,
+                                program_id,
+                                discriminator,
+                                slot,
+                                transaction_index,
+                            ))?;
+                        }
+                    }
                 }
             }
         }
@@ -416,6 +478,14 @@ impl Blockstore {
                         purged_index,
                         purged_index,
                     )
+                    .is_ok()
+                & self
+                    .db
+                    .delete_range_cf::<cf::ProgramInstructionSignatures>(
+                        write_batch,
+                        purged_index,
+                        purged_index,
+                    )
                     .is_ok();
         }
         Ok(())
@@ -429,6 +499,7 @@ pub mod tests {
         crate::{
             blockstore::tests::make_slot_entries_with_transactions, get_tmp_ledger_path_auto_delete,
         },
+        assert_matches::assert_matches,
         bincode::serialize,
         solana_entry::entry::next_entry_mut,
         solana_sdk::{
@@ -436,6 +507,7 @@ pub mod tests {
             message::Message,
             transaction::Transaction,
         },
+        solana_transaction_status::InnerInstruction,
     };
 
     #[test]
@@ -464,6 +536,77 @@ pub mod tests {
             });
     }
 
+    #[test]
+    fn test_purge_slots_with_archive() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let archive_dir = tempfile::TempDir::new().unwrap();
+        let blockstore = Blockstore::open_with_options(
+            ledger_path.path(),
+            BlockstoreOptions {
+                archive_path: Some(archive_dir.path().to_path_buf()),
+                ..BlockstoreOptions::default()
+            },
+        )
+        .unwrap();
+
+        for slot in 1..=3 {
+            let entries = make_slot_entries_with_transactions(5);
+            let shreds = entries_to_test_shreds(
+                &entries,
+                slot,
+                slot - 1, // parent_slot
+                true,     // is_full_slot
+                0,        // version
+                true,     // merkle_variant
+            );
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+            for transaction in entries.iter().flat_map(|entry| &entry.transactions) {
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        transaction.signatures[0],
+                        vec![],
+                        vec![],
+                        TransactionStatusMeta {
+                            pre_token_balances: Some(vec![]),
+                            post_token_balances: Some(vec![]),
+                            rewards: Some(vec![]),
+                            ..TransactionStatusMeta::default()
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+        blockstore.set_roots([1, 2].iter()).unwrap();
+
+        let block = blockstore.get_rooted_block(2, false).unwrap();
+        let signature = block.transactions[3].transaction.signatures[0];
+        let transaction = blockstore.get_rooted_transaction(signature).unwrap();
+        assert!(transaction.is_some());
+
+        blockstore.archive_rooted_slots(3).unwrap();
+        blockstore.purge_slots(0, 3, PurgeType::Exact);
+        assert!(blockstore.meta(2).unwrap().is_none());
+
+        // Rooted blocks are read back from the archive
+        let archive = blockstore.archive().unwrap();
+        assert_eq!(archive.num_segments(), 1);
+        assert_eq!(archive.last_slot(), Some(2));
+        assert_eq!(blockstore.get_rooted_block(2, false).unwrap(), block);
+        assert_eq!(
+            blockstore.get_rooted_transaction(signature).unwrap(),
+            transaction
+        );
+        assert_matches!(
+            blockstore.get_rooted_block(3, false),
+            Err(BlockstoreError::SlotNotRooted)
+        );
+
+        // Archiving archived slots again doesn't add segments
+        blockstore.archive_rooted_slots(3).unwrap();
+        assert_eq!(archive.num_segments(), 1);
+    }
+
     #[test]
     fn test_purge_front_of_ledger() {
         let ledger_path = get_tmp_ledger_path_auto_delete!();
@@ -1170,4 +1313,77 @@ pub mod tests {
             .purge_special_columns_exact(&mut write_batch, slot, slot + 1)
             .unwrap();
     }
+
+    #[test]
+    fn test_purge_program_instruction_signatures_exact() {
+        let ledger_path = get_tmp_ledger_path_auto_delete!();
+        let blockstore = Blockstore::open(ledger_path.path()).unwrap();
+
+        for slot in 1..=2 {
+            let entries = make_slot_entries_with_transactions(3);
+            let shreds = entries_to_test_shreds(
+                &entries,
+                slot,
+                slot - 1, // parent_slot
+                true,     // is_full_slot
+                0,        // version
+                true,     // merkle_variant
+            );
+            blockstore.insert_shreds(shreds, None, false).unwrap();
+
+            let transactions = entries.into_iter().flat_map(|entry| entry.transactions);
+            for (transaction_index, transaction) in transactions.enumerate() {
+                // Inner instructions invoking the fee payer, as an extra program id
+                let inner_instructions = vec![InnerInstructions {
+                    index: 0,
+                    instructions: vec![InnerInstruction {
+                        instruction: CompiledInstruction::new_from_raw_parts(0, vec![9], vec![]),
+                        stack_height: Some(2),
+                    }],
+                }];
+                let account_keys =
+                    AccountKeys::new(transaction.message.static_account_keys(), None);
+                let keys = program_instruction_keys(
+                    &account_keys,
+                    transaction.message.instructions(),
+                    Some(&inner_instructions),
+                );
+                assert_eq!(keys.len(), 2);
+                blockstore
+                    .write_transaction_status(
+                        slot,
+                        transaction.signatures[0],
+                        account_keys.iter().collect(),
+                        vec![],
+                        TransactionStatusMeta {
+                            inner_instructions: Some(inner_instructions),
+                            ..TransactionStatusMeta::default()
+                        },
+                    )
+                    .unwrap();
+                blockstore
+                    .write_program_instruction_signatures(
+                        slot,
+                        transaction_index as u32,
+                        transaction.signatures[0],
+                        &keys,
+                    )
+                    .unwrap();
+            }
+        }
+        let program_instruction_slots = |blockstore: &Blockstore| {
+            blockstore
+                .db
+                .iter::<cf::ProgramInstructionSignatures>(IteratorMode::Start)
+                .unwrap()
+                .map(|(index, _)| cf::ProgramInstructionSignatures::slot(index))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(program_instruction_slots(&blockstore).len(), 12);
+
+        blockstore.run_purge(1, 1, PurgeType::Exact).unwrap();
+        let slots = program_instruction_slots(&blockstore);
+        assert_eq!(slots.len(), 6);
+        assert!(slots.iter().all(|slot| *slot == 2));
+    }
 }
@@ -0,0 +1,497 @@
+//! Immutable segment files holding rooted blocks that were purged from the blockstore.
+//!
+//! When [`BlockstoreOptions::archive_path`] is set, `Blockstore::archive_rooted_slots()` seals
+//! the complete rooted blocks that are about to be cleaned up into a new segment, and the
+//! blockstore read paths for rooted blocks and transactions fall back to the archive for slots it
+//! no longer holds.
+//!
+//! A segment holds the blocks of a range of rooted slots, and is laid out as
+//!
+//! ```text
+//! | magic | block | block | ... | block index | signature index | signature filter | footer |
+//! ```
+//!
+//! Each block is a zstd-compressed `ConfirmedBlock` protobuf message, with complete transaction
+//! statuses. The block index is the bincode-serialized list of the slot, offset and length of
+//! each block. The signature index holds a fixed-size record per transaction: its first
+//! signature, slot and position in the block, sorted by signature so that it is binary searched
+//! in place. The signature filter is a bincode-serialized bloom filter of these signatures, kept
+//! in memory so that only the segments likely to hold a signature are searched, at the cost of
+//! about 10 bits per archived transaction. The footer holds the offsets and lengths of the
+//! indexes and the filter, followed by the magic again.
+//!
+//! [`BlockstoreOptions::archive_path`]: crate::blockstore_options::BlockstoreOptions::archive_path
+
+use {
+    crate::blockstore_db::{BlockstoreError, Result},
+    log::*,
+    prost::Message,
+    serde::{Deserialize, Serialize},
+    solana_bloom::bloom::Bloom,
+    solana_sdk::{clock::Slot, signature::Signature},
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::{
+        ConfirmedBlock, ConfirmedTransactionWithStatusMeta, TransactionWithStatusMeta,
+        VersionedConfirmedBlock,
+    },
+    std::{
+        collections::BTreeMap,
+        fs::{self, File},
+        io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+        ops::RangeInclusive,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex, RwLock},
+    },
+};
+
+const SEGMENT_MAGIC: &[u8; 8] = b"SOLSEG01";
+const SEGMENT_EXTENSION: &str = "segment";
+const SEALING_SEGMENT_FILE: &str = "sealing.segment.tmp";
+const FOOTER_SIZE: u64 = 6 * 8 + SEGMENT_MAGIC.len() as u64;
+const SIGNATURE_INDEX_ENTRY_SIZE: u64 = 64 + 8 + 4;
+const SIGNATURE_FILTER_FALSE_RATE: f64 = 0.01;
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct BlockIndexEntry {
+    slot: Slot,
+    offset: u64,
+    len: u64,
+}
+
+fn invalid_segment(path: &Path, reason: &str) -> BlockstoreError {
+    BlockstoreError::Io(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid archive segment {}: {reason}", path.display()),
+    ))
+}
+
+/// A sealed segment, with its block index in memory
+struct ArchiveSegment {
+    path: PathBuf,
+    file: Mutex<File>,
+    blocks: Vec<BlockIndexEntry>,
+    signature_index_offset: u64,
+    num_signatures: u64,
+    signature_filter: Bloom<Signature>,
+}
+
+impl ArchiveSegment {
+    fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let mut segment = Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            blocks: vec![],
+            signature_index_offset: 0,
+            num_signatures: 0,
+            signature_filter: Bloom::default(),
+        };
+        if file_len < SEGMENT_MAGIC.len() as u64 + FOOTER_SIZE
+            || segment.read_at(0, SEGMENT_MAGIC.len() as u64)? != SEGMENT_MAGIC
+        {
+            return Err(invalid_segment(path, "bad header"));
+        }
+        let footer = segment.read_at(file_len - FOOTER_SIZE, FOOTER_SIZE)?;
+        if &footer[48..] != SEGMENT_MAGIC {
+            return Err(invalid_segment(path, "bad footer"));
+        }
+        let footer_field = |index: usize| {
+            u64::from_le_bytes(footer[index * 8..(index + 1) * 8].try_into().unwrap())
+        };
+        let (block_index_offset, block_index_len) = (footer_field(0), footer_field(1));
+        segment.signature_index_offset = footer_field(2);
+        segment.num_signatures = footer_field(3);
+        let (signature_filter_offset, signature_filter_len) = (footer_field(4), footer_field(5));
+        if segment.signature_index_offset + segment.num_signatures * SIGNATURE_INDEX_ENTRY_SIZE
+            != signature_filter_offset
+        {
+            return Err(invalid_segment(path, "bad signature index"));
+        }
+        if signature_filter_offset + signature_filter_len != file_len - FOOTER_SIZE {
+            return Err(invalid_segment(path, "bad signature filter"));
+        }
+        segment.signature_filter =
+            bincode::deserialize(&segment.read_at(signature_filter_offset, signature_filter_len)?)?;
+        segment.blocks =
+            bincode::deserialize(&segment.read_at(block_index_offset, block_index_len)?)?;
+        if segment.blocks.is_empty() {
+            return Err(invalid_segment(path, "no blocks"));
+        }
+        Ok(segment)
+    }
+
+    fn first_slot(&self) -> Slot {
+        self.blocks.first().unwrap().slot
+    }
+
+    fn last_slot(&self) -> Slot {
+        self.blocks.last().unwrap().slot
+    }
+
+    fn read_at(&self, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buffer = vec![0; len as usize];
+        file.read_exact(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    fn get_block(&self, slot: Slot) -> Result<Option<VersionedConfirmedBlock>> {
+        let Ok(position) = self.blocks.binary_search_by_key(&slot, |entry| entry.slot) else {
+            return Ok(None);
+        };
+        let BlockIndexEntry { offset, len, .. } = self.blocks[position];
+        let bytes = zstd::stream::decode_all(&self.read_at(offset, len)?[..])?;
+        let block = ConfirmedBlock::try_from(generated::ConfirmedBlock::decode(&bytes[..])?)?;
+        VersionedConfirmedBlock::try_from(block)
+            .map(Some)
+            .map_err(|_| BlockstoreError::MissingTransactionMetadata)
+    }
+
+    /// The slot and position in the block of the transaction with `signature`
+    fn find_signature(&self, signature: &Signature) -> Result<Option<(Slot, usize)>> {
+        if !self.signature_filter.contains(signature) {
+            return Ok(None);
+        }
+        let (mut low, mut high) = (0, self.num_signatures);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let entry = self.read_at(
+                self.signature_index_offset + middle * SIGNATURE_INDEX_ENTRY_SIZE,
+                SIGNATURE_INDEX_ENTRY_SIZE,
+            )?;
+            match entry[..64].cmp(signature.as_ref()) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => {
+                    let slot = u64::from_le_bytes(entry[64..72].try_into().unwrap());
+                    let index = u32::from_le_bytes(entry[72..76].try_into().unwrap());
+                    return Ok(Some((slot, index as usize)));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Writes the blocks of a new segment to a temporary file, which is moved into place once
+/// every block has been written
+struct SegmentWriter {
+    tmp_path: PathBuf,
+    file: BufWriter<File>,
+    offset: u64,
+    blocks: Vec<BlockIndexEntry>,
+    signatures: Vec<(Signature, Slot, u32)>,
+}
+
+impl SegmentWriter {
+    fn new(tmp_path: PathBuf) -> Result<Self> {
+        let mut file = BufWriter::new(File::create(&tmp_path)?);
+        file.write_all(SEGMENT_MAGIC)?;
+        Ok(Self {
+            tmp_path,
+            file,
+            offset: SEGMENT_MAGIC.len() as u64,
+            blocks: vec![],
+            signatures: vec![],
+        })
+    }
+
+    fn append_block(&mut self, slot: Slot, block: VersionedConfirmedBlock) -> Result<()> {
+        assert!(self.blocks.last().map_or(true, |entry| entry.slot < slot));
+        for (index, transaction) in block.transactions.iter().enumerate() {
+            if let Some(signature) = transaction.transaction.signatures.first() {
+                self.signatures.push((*signature, slot, index as u32));
+            }
+        }
+        let bytes = zstd::bulk::compress(
+            &generated::ConfirmedBlock::from(block).encode_to_vec(),
+            ZSTD_COMPRESSION_LEVEL,
+        )?;
+        self.file.write_all(&bytes)?;
+        self.blocks.push(BlockIndexEntry {
+            slot,
+            offset: self.offset,
+            len: bytes.len() as u64,
+        });
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Write the indexes and move the segment to `archive_path`, returning its path
+    fn finish(mut self, archive_path: &Path) -> Result<PathBuf> {
+        let block_index = bincode::serialize(&self.blocks)?;
+        self.file.write_all(&block_index)?;
+        let block_index_offset = self.offset;
+        let signature_index_offset = block_index_offset + block_index.len() as u64;
+
+        self.signatures
+            .sort_unstable_by(|(a, ..), (b, ..)| a.as_ref().cmp(b.as_ref()));
+        let mut signature_filter = Bloom::random(
+            self.signatures.len(),
+            SIGNATURE_FILTER_FALSE_RATE,
+            usize::MAX,
+        );
+        for (signature, slot, index) in &self.signatures {
+            self.file.write_all(signature.as_ref())?;
+            self.file.write_all(&slot.to_le_bytes())?;
+            self.file.write_all(&index.to_le_bytes())?;
+            signature_filter.add(signature);
+        }
+        let signature_filter_offset =
+            signature_index_offset + self.signatures.len() as u64 * SIGNATURE_INDEX_ENTRY_SIZE;
+        let signature_filter = bincode::serialize(&signature_filter)?;
+        self.file.write_all(&signature_filter)?;
+        for field in [
+            block_index_offset,
+            block_index.len() as u64,
+            signature_index_offset,
+            self.signatures.len() as u64,
+            signature_filter_offset,
+            signature_filter.len() as u64,
+        ] {
+            self.file.write_all(&field.to_le_bytes())?;
+        }
+        self.file.write_all(SEGMENT_MAGIC)?;
+        self.file
+            .into_inner()
+            .map_err(|err| err.into_error())?
+            .sync_all()?;
+
+        let path = archive_path.join(format!(
+            "{:020}-{:020}.{SEGMENT_EXTENSION}",
+            self.blocks.first().unwrap().slot,
+            self.blocks.last().unwrap().slot,
+        ));
+        fs::rename(&self.tmp_path, &path)?;
+        Ok(path)
+    }
+}
+
+/// The segments in an archive directory
+pub struct BlockstoreArchive {
+    archive_path: PathBuf,
+    /// Segments by their first slot
+    segments: RwLock<BTreeMap<Slot, Arc<ArchiveSegment>>>,
+    seal_lock: Mutex<()>,
+}
+
+impl BlockstoreArchive {
+    /// Open the archive in `archive_path`, creating the directory if needed
+    pub fn open(archive_path: &Path) -> Result<Self> {
+        fs::create_dir_all(archive_path)?;
+        let mut segments = BTreeMap::new();
+        for entry in fs::read_dir(archive_path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some(SEGMENT_EXTENSION)
+            {
+                continue;
+            }
+            let segment = ArchiveSegment::open(&path)?;
+            segments.insert(segment.first_slot(), Arc::new(segment));
+        }
+        info!(
+            "Opened blockstore archive at {:?} with {} segments",
+            archive_path,
+            segments.len()
+        );
+        Ok(Self {
+            archive_path: archive_path.to_path_buf(),
+            segments: RwLock::new(segments),
+            seal_lock: Mutex::default(),
+        })
+    }
+
+    pub fn archive_path(&self) -> &Path {
+        &self.archive_path
+    }
+
+    pub fn num_segments(&self) -> usize {
+        self.segments.read().unwrap().len()
+    }
+
+    /// The highest archived slot
+    pub fn last_slot(&self) -> Option<Slot> {
+        self.segments
+            .read()
+            .unwrap()
+            .values()
+            .map(|segment| segment.last_slot())
+            .max()
+    }
+
+    fn segment_containing(&self, slot: Slot) -> Option<Arc<ArchiveSegment>> {
+        self.segments
+            .read()
+            .unwrap()
+            .range(..=slot)
+            .next_back()
+            .filter(|(_, segment)| segment.last_slot() >= slot)
+            .map(|(_, segment)| segment.clone())
+    }
+
+    /// Seal `blocks`, in increasing slot order, into a new segment. Blocks of slots that are
+    /// already archived are skipped. Returns the range of slots of the new segment, if any.
+    pub fn seal_segment(
+        &self,
+        blocks: impl IntoIterator<Item = (Slot, VersionedConfirmedBlock)>,
+    ) -> Result<Option<RangeInclusive<Slot>>> {
+        let _seal_lock = self.seal_lock.lock().unwrap();
+        let mut writer = None;
+        for (slot, block) in blocks {
+            if self.segment_containing(slot).is_some() {
+                continue;
+            }
+            if writer.is_none() {
+                writer = Some(SegmentWriter::new(
+                    self.archive_path.join(SEALING_SEGMENT_FILE),
+                )?);
+            }
+            writer.as_mut().unwrap().append_block(slot, block)?;
+        }
+        let Some(writer) = writer else {
+            return Ok(None);
+        };
+        let segment = ArchiveSegment::open(&writer.finish(&self.archive_path)?)?;
+        let slots = segment.first_slot()..=segment.last_slot();
+        info!(
+            "Sealed archive segment {:?} with {} blocks",
+            segment.path,
+            segment.blocks.len()
+        );
+        self.segments
+            .write()
+            .unwrap()
+            .insert(segment.first_slot(), Arc::new(segment));
+        Ok(Some(slots))
+    }
+
+    pub fn get_block(&self, slot: Slot) -> Result<Option<VersionedConfirmedBlock>> {
+        match self.segment_containing(slot) {
+            Some(segment) => segment.get_block(slot),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<ConfirmedTransactionWithStatusMeta>> {
+        let segments: Vec<_> = self.segments.read().unwrap().values().cloned().collect();
+        // Search the most recent segments first
+        for segment in segments.iter().rev() {
+            let Some((slot, index)) = segment.find_signature(signature)? else {
+                continue;
+            };
+            let Some(mut block) = segment.get_block(slot)? else {
+                return Err(invalid_segment(&segment.path, "missing indexed block"));
+            };
+            if index >= block.transactions.len() {
+                return Err(invalid_segment(&segment.path, "bad transaction index"));
+            }
+            return Ok(Some(ConfirmedTransactionWithStatusMeta {
+                slot,
+                tx_with_meta: TransactionWithStatusMeta::Complete(
+                    block.transactions.swap_remove(index),
+                ),
+                block_time: block.block_time,
+            }));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            hash::Hash, pubkey::Pubkey, signature::Keypair, system_transaction,
+            transaction::VersionedTransaction,
+        },
+        solana_transaction_status::{TransactionStatusMeta, VersionedTransactionWithStatusMeta},
+    };
+
+    fn make_block(slot: Slot, num_transactions: usize) -> VersionedConfirmedBlock {
+        VersionedConfirmedBlock {
+            previous_blockhash: Hash::new_unique().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: slot.saturating_sub(1),
+            transactions: (0..num_transactions)
+                .map(|_| VersionedTransactionWithStatusMeta {
+                    transaction: VersionedTransaction::from(system_transaction::transfer(
+                        &Keypair::new(),
+                        &Pubkey::new_unique(),
+                        1,
+                        Hash::default(),
+                    )),
+                    // Token balances and rewards are stored as empty lists
+                    meta: TransactionStatusMeta {
+                        pre_token_balances: Some(vec![]),
+                        post_token_balances: Some(vec![]),
+                        rewards: Some(vec![]),
+                        ..TransactionStatusMeta::default()
+                    },
+                })
+                .collect(),
+            rewards: vec![],
+            block_time: Some(slot as i64),
+            block_height: Some(slot),
+        }
+    }
+
+    #[test]
+    fn test_seal_and_read_segments() {
+        let archive_dir = tempfile::TempDir::new().unwrap();
+        let archive = BlockstoreArchive::open(archive_dir.path()).unwrap();
+        assert_eq!(archive.seal_segment(vec![]).unwrap(), None);
+
+        let blocks: Vec<_> = [1, 2, 4]
+            .iter()
+            .map(|slot| (*slot, make_block(*slot, 3)))
+            .collect();
+        assert_eq!(archive.seal_segment(blocks.clone()).unwrap(), Some(1..=4));
+        // Already archived blocks are skipped
+        let more_blocks = vec![(4, make_block(4, 1)), (5, make_block(5, 2))];
+        assert_eq!(archive.seal_segment(more_blocks).unwrap(), Some(5..=5));
+        assert_eq!(archive.last_slot(), Some(5));
+
+        // Segments are found again when the archive is reopened
+        let archive = BlockstoreArchive::open(archive_dir.path()).unwrap();
+        assert_eq!(archive.num_segments(), 2);
+        for (slot, block) in &blocks {
+            assert_eq!(archive.get_block(*slot).unwrap().as_ref(), Some(block));
+        }
+        assert_eq!(archive.get_block(3).unwrap(), None);
+        assert_eq!(archive.get_block(6).unwrap(), None);
+
+        // Every archived signature passes the filter of its segment
+        let segment = archive.segment_containing(1).unwrap();
+        for (_, block) in &blocks {
+            for transaction in &block.transactions {
+                assert!(segment
+                    .signature_filter
+                    .contains(&transaction.transaction.signatures[0]));
+            }
+        }
+
+        let (slot, block) = &blocks[1];
+        let transaction = &block.transactions[2];
+        let confirmed = archive
+            .get_transaction(&transaction.transaction.signatures[0])
+            .unwrap()
+            .unwrap();
+        assert_eq!(confirmed.slot, *slot);
+        assert_eq!(confirmed.block_time, block.block_time);
+        assert_eq!(
+            confirmed.tx_with_meta,
+            TransactionWithStatusMeta::Complete(transaction.clone())
+        );
+        assert_eq!(
+            archive.get_transaction(&Signature::new_unique()).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Value-level zstd compression with trained dictionaries for blockstore columns.
+//!
+//! `TransactionStatus` and `Rewards` entries are small protobuf messages that compress poorly
+//! one at a time, even with RocksDB block compression. A zstd dictionary trained from a sample
+//! of a column's values (see `Blockstore::train_compression_dictionary()`) captures what those
+//! values have in common, so each value compresses well on its own.
+//!
+//! Dictionaries are versioned and stored in the blockstore's `compression_dictionary` column,
+//! keyed by version. Once a column has a dictionary, new values of that column are written
+//! compressed with its latest dictionary as
+//!
+//! ```text
+//! | magic (4 bytes) | dictionary version (u64 LE) | uncompressed length (u32 LE) | zstd frame |
+//! ```
+//!
+//! The magic starts with `0xff`, which no protobuf message can start with, so values written
+//! before a dictionary existed are still read as is. Older dictionaries are kept so that values
+//! compressed with them stay readable until `Blockstore::recompress_column()` rewrites them.
+//!
+//! `AddressSignatures` values are a single byte, so that column relies on RocksDB's zstd block
+//! compression, which trains its own dictionary per SST file, instead; see
+//! [`BlockstoreCompressionType::Zstd`].
+//!
+//! [`BlockstoreCompressionType::Zstd`]: crate::blockstore_options::BlockstoreCompressionType::Zstd
+
+use {
+    crate::blockstore_db::{BlockstoreError, Result},
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+        fmt, io,
+        sync::{Arc, RwLock},
+    },
+    zstd::dict::{DecoderDictionary, EncoderDictionary},
+};
+
+const COMPRESSED_VALUE_MAGIC: [u8; 4] = [0xff, b'z', b'd', 0x01];
+const COMPRESSED_VALUE_HEADER_SIZE: usize = COMPRESSED_VALUE_MAGIC.len() + 8 + 4;
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// Default size of a trained dictionary, the same as the zstd command line tool's.
+pub const DEFAULT_DICTIONARY_SIZE: usize = 112_640;
+/// Default number of values sampled from a column to train a dictionary.
+pub const DEFAULT_DICTIONARY_MAX_SAMPLES: usize = 100_000;
+
+/// Trains a zstd dictionary of at most `dictionary_size` bytes from `samples`.
+pub fn train_dictionary<S: AsRef<[u8]>>(samples: &[S], dictionary_size: usize) -> Result<Vec<u8>> {
+    Ok(zstd::dict::from_samples(samples, dictionary_size)?)
+}
+
+/// Returns the dictionary version a value was compressed with, or `None` if the value is not
+/// dictionary compressed.
+pub fn compressed_value_version(value: &[u8]) -> Option<u64> {
+    if value.len() < COMPRESSED_VALUE_HEADER_SIZE || !value.starts_with(&COMPRESSED_VALUE_MAGIC) {
+        return None;
+    }
+    let offset = COMPRESSED_VALUE_MAGIC.len();
+    Some(u64::from_le_bytes(
+        value[offset..offset + 8].try_into().unwrap(),
+    ))
+}
+
+struct Dictionary {
+    version: u64,
+    encoder: EncoderDictionary<'static>,
+}
+
+/// The compression dictionaries of a blockstore, shared by all of its columns.
+#[derive(Default)]
+pub struct CompressionDictionaries {
+    // latest dictionary of each column, used to compress new values
+    encoders: RwLock<HashMap<String, Arc<Dictionary>>>,
+    // every known dictionary, by version
+    decoders: RwLock<HashMap<u64, Arc<DecoderDictionary<'static>>>>,
+}
+
+impl fmt::Debug for CompressionDictionaries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut versions: Vec<_> = self.decoders.read().unwrap().keys().copied().collect();
+        versions.sort_unstable();
+        f.debug_struct("CompressionDictionaries")
+            .field("versions", &versions)
+            .finish()
+    }
+}
+
+impl CompressionDictionaries {
+    /// Registers the dictionary `version`, trained on values of `column`. It becomes the one new
+    /// values of `column` are compressed with unless that column has a more recent dictionary.
+    pub fn insert(&self, version: u64, column: &str, dictionary: &[u8]) {
+        self.decoders
+            .write()
+            .unwrap()
+            .entry(version)
+            .or_insert_with(|| Arc::new(DecoderDictionary::copy(dictionary)));
+
+        let mut encoders = self.encoders.write().unwrap();
+        if encoders
+            .get(column)
+            .map(|latest| latest.version < version)
+            .unwrap_or(true)
+        {
+            let encoder = EncoderDictionary::copy(dictionary, ZSTD_COMPRESSION_LEVEL);
+            encoders.insert(
+                column.to_string(),
+                Arc::new(Dictionary { version, encoder }),
+            );
+        }
+    }
+
+    pub fn contains(&self, version: u64) -> bool {
+        self.decoders.read().unwrap().contains_key(&version)
+    }
+
+    /// Returns the version of the dictionary new values of `column` are compressed with.
+    pub fn latest_version(&self, column: &str) -> Option<u64> {
+        self.encoders
+            .read()
+            .unwrap()
+            .get(column)
+            .map(|latest| latest.version)
+    }
+
+    /// Compresses `value` with the latest dictionary of `column`. Returns `None` if the column
+    /// has no dictionary, or if compressing would not make the value smaller.
+    pub fn compress(&self, column: &str, value: &[u8]) -> Result<Option<Vec<u8>>> {
+        let dictionary = match self.encoders.read().unwrap().get(column) {
+            Some(dictionary) => Arc::clone(dictionary),
+            None => return Ok(None),
+        };
+        let frame = zstd::bulk::Compressor::with_prepared_dictionary(&dictionary.encoder)?
+            .compress(value)?;
+        if COMPRESSED_VALUE_HEADER_SIZE + frame.len() >= value.len() {
+            return Ok(None);
+        }
+
+        let mut compressed = Vec::with_capacity(COMPRESSED_VALUE_HEADER_SIZE + frame.len());
+        compressed.extend_from_slice(&COMPRESSED_VALUE_MAGIC);
+        compressed.extend_from_slice(&dictionary.version.to_le_bytes());
+        compressed.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        compressed.extend_from_slice(&frame);
+        Ok(Some(compressed))
+    }
+
+    /// Returns the uncompressed form of a value read from a compressible column, which is the
+    /// value itself if it is not dictionary compressed.
+    pub fn decompress<'a>(&self, value: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        let version = match compressed_value_version(value) {
+            Some(version) => version,
+            None => return Ok(Cow::Borrowed(value)),
+        };
+        let dictionary = self
+            .decoders
+            .read()
+            .unwrap()
+            .get(&version)
+            .cloned()
+            .ok_or(BlockstoreError::UnknownCompressionDictionary(version))?;
+
+        let offset = COMPRESSED_VALUE_MAGIC.len() + 8;
+        let len = u32::from_le_bytes(value[offset..offset + 4].try_into().unwrap()) as usize;
+        let decompressed = zstd::bulk::Decompressor::with_prepared_dictionary(&dictionary)?
+            .decompress(&value[COMPRESSED_VALUE_HEADER_SIZE..], len)?;
+        if decompressed.len() != len {
+            return Err(BlockstoreError::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "compressed value length mismatch: expected {len}, got {}",
+                    decompressed.len()
+                ),
+            )));
+        }
+        Ok(Cow::Owned(decompressed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Vec<u8>> {
+        (0..2_000u64)
+            .map(|i| {
+                format!(
+                    "Program 11111111111111111111111111111111 invoke [1] fee {} \
+                     Program log: Instruction: Transfer {} \
+                     Program 11111111111111111111111111111111 success",
+                    5_000 + i % 7,
+                    i * 31,
+                )
+                .into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_decompress() {
+        let samples = samples();
+        let dictionaries = CompressionDictionaries::default();
+        let value = samples[42].clone();
+        assert!(dictionaries.compress("column", &value).unwrap().is_none());
+
+        let dictionary = train_dictionary(&samples, 4096).unwrap();
+        dictionaries.insert(1, "column", &dictionary);
+        assert_eq!(dictionaries.latest_version("column"), Some(1));
+        assert_eq!(dictionaries.latest_version("other"), None);
+
+        let compressed = dictionaries.compress("column", &value).unwrap().unwrap();
+        assert!(compressed.len() < value.len());
+        assert_eq!(compressed_value_version(&compressed), Some(1));
+        assert_eq!(dictionaries.decompress(&compressed).unwrap(), &value[..]);
+        assert!(matches!(
+            dictionaries.decompress(&value).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        // an older dictionary does not replace the latest one, but stays readable
+        dictionaries.insert(3, "column", &dictionary);
+        dictionaries.insert(2, "column", &dictionary);
+        assert_eq!(dictionaries.latest_version("column"), Some(3));
+        assert_eq!(dictionaries.decompress(&compressed).unwrap(), &value[..]);
+
+        assert!(matches!(
+            CompressionDictionaries::default().decompress(&compressed),
+            Err(BlockstoreError::UnknownCompressionDictionary(1))
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 pub use rocksdb::Direction as IteratorDirection;
 use {
     crate::{
+        blockstore_compression::CompressionDictionaries,
         blockstore_meta,
         blockstore_metrics::{
             maybe_enable_rocksdb_perf, report_rocksdb_read_perf, report_rocksdb_write_perf,
@@ -9,7 +10,8 @@ use {
             PERF_METRIC_OP_NAME_WRITE_BATCH,
         },
         blockstore_options::{
-            AccessType, BlockstoreOptions, LedgerColumnOptions, ShredStorageType,
+            AccessType, BlockstoreCompressionType, BlockstoreOptions, LedgerColumnOptions,
+            ShredStorageType,
         },
     },
     bincode::{deserialize, serialize},
@@ -33,6 +35,7 @@ use {
     },
     solana_storage_proto::convert::generated,
     std::{
+        borrow::Cow,
         collections::{HashMap, HashSet},
         ffi::{CStr, CString},
         fs,
@@ -50,6 +53,10 @@ const BLOCKSTORE_METRICS_ERROR: i64 = -1;
 
 const MAX_WRITE_BUFFER_SIZE: u64 = 256 * 1024 * 1024; // 256MB
 const FIFO_WRITE_BUFFER_SIZE: u64 = 2 * MAX_WRITE_BUFFER_SIZE;
+// Size of the zstd dictionaries RocksDB trains per SST file, and of the sample
+// it trains them from
+const ZSTD_MAX_DICT_BYTES: i32 = 16 * 1024;
+const ZSTD_MAX_TRAIN_BYTES: i32 = 100 * ZSTD_MAX_DICT_BYTES;
 
 // Column family for metadata about a leader slot
 const META_CF: &str = "meta";
@@ -76,6 +83,8 @@ const CODE_SHRED_CF: &str = "code_shred";
 const TRANSACTION_STATUS_CF: &str = "transaction_status";
 /// Column family for Address Signatures
 const ADDRESS_SIGNATURES_CF: &str = "address_signatures";
+/// Column family for Program Instruction Signatures
+const PROGRAM_INSTRUCTION_SIGNATURES_CF: &str = "program_instruction_signatures";
 /// Column family for TransactionMemos
 const TRANSACTION_MEMOS_CF: &str = "transaction_memos";
 /// Column family for the Transaction Status Index.
@@ -94,6 +103,8 @@ const BLOCK_HEIGHT_CF: &str = "block_height";
 const PROGRAM_COSTS_CF: &str = "program_costs";
 /// Column family for optimistic slots
 const OPTIMISTIC_SLOTS_CF: &str = "optimistic_slots";
+/// Column family for compression dictionaries
+const COMPRESSION_DICTIONARY_CF: &str = "compression_dictionary";
 
 #[derive(Error, Debug)]
 pub enum BlockstoreError {
@@ -117,6 +128,7 @@ pub enum BlockstoreError {
     SlotUnavailable,
     UnsupportedTransactionVersion,
     MissingTransactionMetadata,
+    UnknownCompressionDictionary(u64),
 }
 pub type Result<T> = std::result::Result<T, BlockstoreError>;
 
@@ -258,6 +270,17 @@ pub mod columns {
     /// * value type: [`blockstore_meta::AddressSignatureMeta`]
     pub struct AddressSignatures;
 
+    #[derive(Debug)]
+    /// The program instruction signatures column
+    ///
+    /// Indexes transactions by the programs they invoke, from top-level and
+    /// inner instructions, and the first 8 bytes of the instruction data.
+    ///
+    /// * index type: `(u64, `[`Pubkey`]`, [u8; 8], `[`Slot`]`, u32)`, the
+    /// last element being the index of the transaction in its block
+    /// * value type: [`Signature`]
+    pub struct ProgramInstructionSignatures;
+
     #[derive(Debug)]
     /// The transaction memos column
     ///
@@ -314,6 +337,17 @@ pub mod columns {
     /// * value type: [`blockstore_meta::OptimisticSlotMetaVersioned`]
     pub struct OptimisticSlots;
 
+    #[derive(Debug)]
+    /// The compression dictionary column
+    ///
+    /// This column family stores the versioned zstd dictionaries used to
+    /// compress the values of other columns (see
+    /// [`crate::blockstore_compression`]).
+    ///
+    /// * index type: `u64` (dictionary version)
+    /// * value type: [`blockstore_meta::CompressionDictionaryMeta`]
+    pub struct CompressionDictionary;
+
     // When adding a new column ...
     // - Add struct below and implement `Column` and `ColumnName` traits
     // - Add descriptor in Rocks::cf_descriptors() and name in Rocks::columns()
@@ -432,6 +466,7 @@ impl Rocks {
             cf_descriptor_shred_code,
             new_cf_descriptor::<TransactionStatus>(options, oldest_slot),
             new_cf_descriptor::<AddressSignatures>(options, oldest_slot),
+            new_cf_descriptor::<ProgramInstructionSignatures>(options, oldest_slot),
             new_cf_descriptor::<TransactionMemos>(options, oldest_slot),
             new_cf_descriptor::<TransactionStatusIndex>(options, oldest_slot),
             new_cf_descriptor::<Rewards>(options, oldest_slot),
@@ -440,6 +475,7 @@ impl Rocks {
             new_cf_descriptor::<BlockHeight>(options, oldest_slot),
             new_cf_descriptor::<ProgramCosts>(options, oldest_slot),
             new_cf_descriptor::<OptimisticSlots>(options, oldest_slot),
+            new_cf_descriptor::<CompressionDictionary>(options, oldest_slot),
         ]
     }
 
@@ -459,6 +495,7 @@ impl Rocks {
             ShredCode::NAME,
             TransactionStatus::NAME,
             AddressSignatures::NAME,
+            ProgramInstructionSignatures::NAME,
             TransactionMemos::NAME,
             TransactionStatusIndex::NAME,
             Rewards::NAME,
@@ -467,6 +504,7 @@ impl Rocks {
             BlockHeight::NAME,
             ProgramCosts::NAME,
             OptimisticSlots::NAME,
+            CompressionDictionary::NAME,
         ]
     }
 
@@ -579,6 +617,12 @@ impl Rocks {
             || self.access_type == AccessType::PrimaryForMaintenance
     }
 
+    fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.db
+            .try_catch_up_with_primary()
+            .map_err(BlockstoreError::RocksDb)
+    }
+
     /// Retrieves the specified RocksDB integer property of the current
     /// column family.
     ///
@@ -629,6 +673,10 @@ impl TypedColumn for columns::AddressSignatures {
     type Type = blockstore_meta::AddressSignatureMeta;
 }
 
+impl TypedColumn for columns::ProgramInstructionSignatures {
+    type Type = Signature;
+}
+
 impl TypedColumn for columns::TransactionMemos {
     type Type = String;
 }
@@ -753,6 +801,52 @@ impl ColumnName for columns::AddressSignatures {
     const NAME: &'static str = ADDRESS_SIGNATURES_CF;
 }
 
+impl Column for columns::ProgramInstructionSignatures {
+    type Index = (u64, Pubkey, [u8; 8], Slot, u32);
+
+    fn key(
+        (index, program_id, discriminator, slot, transaction_index): (
+            u64,
+            Pubkey,
+            [u8; 8],
+            Slot,
+            u32,
+        ),
+    ) -> Vec<u8> {
+        let mut key = vec![0; 8 + 32 + 8 + 8 + 4]; // size_of u64 + size_of Pubkey + discriminator + size_of Slot + size_of u32
+        BigEndian::write_u64(&mut key[0..8], index);
+        key[8..40].clone_from_slice(&program_id.as_ref()[0..32]);
+        key[40..48].clone_from_slice(&discriminator);
+        BigEndian::write_u64(&mut key[48..56], slot);
+        BigEndian::write_u32(&mut key[56..60], transaction_index);
+        key
+    }
+
+    fn index(key: &[u8]) -> (u64, Pubkey, [u8; 8], Slot, u32) {
+        let index = BigEndian::read_u64(&key[0..8]);
+        let program_id = Pubkey::try_from(&key[8..40]).unwrap();
+        let discriminator = <[u8; 8]>::try_from(&key[40..48]).unwrap();
+        let slot = BigEndian::read_u64(&key[48..56]);
+        let transaction_index = BigEndian::read_u32(&key[56..60]);
+        (index, program_id, discriminator, slot, transaction_index)
+    }
+
+    fn primary_index(index: Self::Index) -> u64 {
+        index.0
+    }
+
+    fn slot(index: Self::Index) -> Slot {
+        index.3
+    }
+
+    fn as_index(index: u64) -> Self::Index {
+        (index, Pubkey::default(), [0; 8], 0, 0)
+    }
+}
+impl ColumnName for columns::ProgramInstructionSignatures {
+    const NAME: &'static str = PROGRAM_INSTRUCTION_SIGNATURES_CF;
+}
+
 impl Column for columns::TransactionMemos {
     type Index = Signature;
 
@@ -1019,11 +1113,44 @@ impl TypedColumn for columns::OptimisticSlots {
     type Type = blockstore_meta::OptimisticSlotMetaVersioned;
 }
 
+impl Column for columns::CompressionDictionary {
+    type Index = u64;
+
+    fn key(version: u64) -> Vec<u8> {
+        let mut key = vec![0; 8];
+        BigEndian::write_u64(&mut key[..], version);
+        key
+    }
+
+    fn index(key: &[u8]) -> u64 {
+        BigEndian::read_u64(&key[..8])
+    }
+
+    fn primary_index(_index: Self::Index) -> u64 {
+        unimplemented!()
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+
+    fn as_index(version: u64) -> Self::Index {
+        version
+    }
+}
+impl ColumnName for columns::CompressionDictionary {
+    const NAME: &'static str = COMPRESSION_DICTIONARY_CF;
+}
+impl TypedColumn for columns::CompressionDictionary {
+    type Type = blockstore_meta::CompressionDictionaryMeta;
+}
+
 #[derive(Debug)]
 pub struct Database {
     backend: Arc<Rocks>,
     path: Arc<Path>,
     column_options: Arc<LedgerColumnOptions>,
+    compression_dictionaries: Arc<CompressionDictionaries>,
 }
 
 #[derive(Debug)]
@@ -1034,6 +1161,7 @@ where
     backend: Arc<Rocks>,
     column: PhantomData<C>,
     pub column_options: Arc<LedgerColumnOptions>,
+    compression_dictionaries: Arc<CompressionDictionaries>,
     read_perf_status: PerfSamplingStatus,
     write_perf_status: PerfSamplingStatus,
 }
@@ -1103,11 +1231,14 @@ impl Database {
     pub fn open(path: &Path, options: BlockstoreOptions) -> Result<Self> {
         let column_options = Arc::new(options.column_options.clone());
         let backend = Arc::new(Rocks::open(path, options)?);
+        let compression_dictionaries = Arc::<CompressionDictionaries>::default();
+        load_compression_dictionaries(&backend, &compression_dictionaries)?;
 
         Ok(Database {
             backend,
             path: Arc::from(path),
             column_options,
+            compression_dictionaries,
         })
     }
 
@@ -1163,6 +1294,7 @@ impl Database {
             backend: Arc::clone(&self.backend),
             column: PhantomData,
             column_options: Arc::clone(&self.column_options),
+            compression_dictionaries: Arc::clone(&self.compression_dictionaries),
             read_perf_status: PerfSamplingStatus::default(),
             write_perf_status: PerfSamplingStatus::default(),
         }
@@ -1226,6 +1358,10 @@ impl Database {
         self.backend.is_primary_access()
     }
 
+    pub fn try_catch_up_with_primary(&self) -> Result<()> {
+        self.backend.try_catch_up_with_primary()
+    }
+
     pub fn set_oldest_slot(&self, oldest_slot: Slot) {
         self.backend.oldest_slot.set(oldest_slot);
     }
@@ -1233,6 +1369,29 @@ impl Database {
     pub fn live_files_metadata(&self) -> Result<Vec<LiveFile>> {
         self.backend.live_files_metadata()
     }
+
+    pub fn compression_dictionaries(&self) -> &CompressionDictionaries {
+        &self.compression_dictionaries
+    }
+}
+
+/// Registers the dictionaries of the `CompressionDictionary` column that are not known yet.
+fn load_compression_dictionaries(
+    backend: &Rocks,
+    compression_dictionaries: &CompressionDictionaries,
+) -> Result<()> {
+    use columns::CompressionDictionary;
+
+    let cf = backend.cf_handle(CompressionDictionary::NAME);
+    for pair in backend.iterator_cf::<CompressionDictionary>(cf, IteratorMode::Start) {
+        let (key, value) = pair?;
+        let version = CompressionDictionary::index(&key);
+        if !compression_dictionaries.contains(version) {
+            let meta: blockstore_meta::CompressionDictionaryMeta = deserialize(&value)?;
+            compression_dictionaries.insert(version, &meta.column, &meta.dictionary);
+        }
+    }
+    Ok(())
 }
 
 impl<C> LedgerColumn<C>
@@ -1490,6 +1649,31 @@ impl<C> LedgerColumn<C>
 where
     C: ProtobufColumn + ColumnName,
 {
+    /// Returns the uncompressed form of a raw value of this column.
+    fn decompress<'a>(&self, value: &'a [u8]) -> Result<Cow<'a, [u8]>> {
+        match self.compression_dictionaries.decompress(value) {
+            Err(BlockstoreError::UnknownCompressionDictionary(_)) => {
+                // The dictionary may have been added since the blockstore was opened, e.g. by
+                // the primary that a secondary instance is catching up with
+                load_compression_dictionaries(&self.backend, &self.compression_dictionaries)?;
+                self.compression_dictionaries.decompress(value)
+            }
+            result => result,
+        }
+    }
+
+    /// Deserializes a raw value of this column, as read from an iterator for instance.
+    pub fn deserialize_protobuf_or_bincode<T: DeserializeOwned + Into<C::Type>>(
+        &self,
+        value: &[u8],
+    ) -> Result<C::Type> {
+        let value = self.decompress(value)?;
+        Ok(match C::Type::decode(value.as_ref()) {
+            Ok(value) => value,
+            Err(_) => deserialize::<T>(value.as_ref())?.into(),
+        })
+    }
+
     pub fn get_protobuf_or_bincode<T: DeserializeOwned + Into<C::Type>>(
         &self,
         key: C::Index,
@@ -1509,11 +1693,9 @@ where
         }
 
         if let Some(pinnable_slice) = result? {
-            let value = match C::Type::decode(pinnable_slice.as_ref()) {
-                Ok(value) => value,
-                Err(_) => deserialize::<T>(pinnable_slice.as_ref())?.into(),
-            };
-            Ok(Some(value))
+            Ok(Some(self.deserialize_protobuf_or_bincode::<T>(
+                pinnable_slice.as_ref(),
+            )?))
         } else {
             Ok(None)
         }
@@ -1535,7 +1717,8 @@ where
         }
 
         if let Some(pinnable_slice) = result? {
-            Ok(Some(C::Type::decode(pinnable_slice.as_ref())?))
+            let value = self.decompress(pinnable_slice.as_ref())?;
+            Ok(Some(C::Type::decode(value.as_ref())?))
         } else {
             Ok(None)
         }
@@ -1544,6 +1727,9 @@ where
     pub fn put_protobuf(&self, key: C::Index, value: &C::Type) -> Result<()> {
         let mut buf = Vec::with_capacity(value.encoded_len());
         value.encode(&mut buf)?;
+        if let Some(compressed) = self.compression_dictionaries.compress(C::NAME, &buf)? {
+            buf = compressed;
+        }
 
         let is_perf_enabled = maybe_enable_rocksdb_perf(
             self.column_options.rocks_perf_sample_interval,
@@ -1709,12 +1895,26 @@ fn process_cf_options_advanced<C: 'static + Column + ColumnName>(
     cf_options: &mut Options,
     column_options: &LedgerColumnOptions,
 ) {
-    if should_enable_compression::<C>() {
+    if should_enable_compression::<C>(&column_options.compression_type) {
         cf_options.set_compression_type(
             column_options
                 .compression_type
                 .to_rocksdb_compression_type(),
         );
+        if matches!(
+            column_options.compression_type,
+            BlockstoreCompressionType::Zstd
+        ) {
+            // Have RocksDB train a dictionary per SST file from sampled blocks, which mostly
+            // helps columns whose keys dominate, such as AddressSignatures
+            cf_options.set_compression_options(
+                -14,   // window_bits, RocksDB's default
+                32767, // level, RocksDB's default (zstd level 3)
+                0,     // strategy
+                ZSTD_MAX_DICT_BYTES,
+            );
+            cf_options.set_zstd_max_train_bytes(ZSTD_MAX_TRAIN_BYTES);
+        }
     }
 }
 
@@ -1850,6 +2050,7 @@ fn should_exclude_from_compaction(cf_name: &str) -> bool {
         columns::TransactionStatusIndex::NAME,
         columns::ProgramCosts::NAME,
         columns::TransactionMemos::NAME,
+        columns::CompressionDictionary::NAME,
     ]
     .into_iter()
     .collect();
@@ -1857,15 +2058,51 @@ fn should_exclude_from_compaction(cf_name: &str) -> bool {
     no_compaction_cfs.get(cf_name).is_some()
 }
 
-// Returns true if the column family enables compression.
-fn should_enable_compression<C: 'static + Column + ColumnName>() -> bool {
-    C::NAME == columns::TransactionStatus::NAME
+// Returns true if the column family enables compression. AddressSignatures and Rewards are
+// only compressed with zstd, so that the ledgers using the other compression types keep their
+// format and CPU usage.
+fn should_enable_compression<C: 'static + Column + ColumnName>(
+    compression_type: &BlockstoreCompressionType,
+) -> bool {
+    if C::NAME == columns::TransactionStatus::NAME
+        || C::NAME == columns::ProgramInstructionSignatures::NAME
+    {
+        return true;
+    }
+    (C::NAME == columns::AddressSignatures::NAME || C::NAME == columns::Rewards::NAME)
+        && matches!(compression_type, BlockstoreCompressionType::Zstd)
 }
 
 #[cfg(test)]
 pub mod tests {
     use {super::*, crate::blockstore_db::columns::ShredData};
 
+    #[test]
+    fn test_should_enable_compression() {
+        for compression_type in [
+            BlockstoreCompressionType::None,
+            BlockstoreCompressionType::Lz4,
+            BlockstoreCompressionType::Zstd,
+        ] {
+            let is_zstd = matches!(compression_type, BlockstoreCompressionType::Zstd);
+            assert!(should_enable_compression::<columns::TransactionStatus>(
+                &compression_type
+            ));
+            assert!(should_enable_compression::<
+                columns::ProgramInstructionSignatures,
+            >(&compression_type));
+            assert_eq!(
+                should_enable_compression::<columns::AddressSignatures>(&compression_type),
+                is_zstd
+            );
+            assert_eq!(
+                should_enable_compression::<columns::Rewards>(&compression_type),
+                is_zstd
+            );
+            assert!(!should_enable_compression::<ShredData>(&compression_type));
+        }
+    }
+
     #[test]
     fn test_compaction_filter() {
         // this doesn't implement Clone...
@@ -1938,7 +2175,7 @@ pub mod tests {
 
     #[test]
     fn test_should_exclude_from_compaction() {
-        // currently there are three CFs excluded from compaction:
+        // currently there are four CFs excluded from compaction:
         assert!(should_exclude_from_compaction(
             columns::TransactionStatusIndex::NAME
         ));
@@ -1946,6 +2183,9 @@ pub mod tests {
         assert!(should_exclude_from_compaction(
             columns::TransactionMemos::NAME
         ));
+        assert!(should_exclude_from_compaction(
+            columns::CompressionDictionary::NAME
+        ));
         assert!(!should_exclude_from_compaction("something else"));
     }
 }
@@ -423,6 +423,17 @@ pub struct ProgramCost {
     pub cost: u64,
 }
 
+/// A zstd dictionary trained from the values of a column, see
+/// [`crate::blockstore_compression`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CompressionDictionaryMeta {
+    /// Name of the column family whose values the dictionary was trained on.
+    pub column: String,
+    /// Number of values sampled to train the dictionary.
+    pub num_samples: u64,
+    pub dictionary: Vec<u8>,
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
 pub struct OptimisticSlotMetaV0 {
     pub hash: Hash,
@@ -1,6 +1,6 @@
 use {
     rocksdb::{DBCompressionType as RocksCompressionType, DBRecoveryMode},
-    std::path::Path,
+    std::path::{Path, PathBuf},
 };
 
 pub struct BlockstoreOptions {
@@ -11,6 +11,8 @@ pub struct BlockstoreOptions {
     // Whether to allow unlimited number of open files. Default: true.
     pub enforce_ulimit_nofile: bool,
     pub column_options: LedgerColumnOptions,
+    // The directory that purged rooted blocks are archived into. Default: None.
+    pub archive_path: Option<PathBuf>,
 }
 
 impl Default for BlockstoreOptions {
@@ -23,6 +25,7 @@ impl Default for BlockstoreOptions {
             recovery_mode: None,
             enforce_ulimit_nofile: true,
             column_options: LedgerColumnOptions::default(),
+            archive_path: None,
         }
     }
 }
@@ -118,6 +121,7 @@ impl LedgerColumnOptions {
             BlockstoreCompressionType::Snappy => "Snappy",
             BlockstoreCompressionType::Lz4 => "Lz4",
             BlockstoreCompressionType::Zlib => "Zlib",
+            BlockstoreCompressionType::Zstd => "Zstd",
         }
     }
 }
@@ -247,6 +251,7 @@ pub enum BlockstoreCompressionType {
     Snappy,
     Lz4,
     Zlib,
+    Zstd,
 }
 
 impl Default for BlockstoreCompressionType {
@@ -262,6 +267,7 @@ impl BlockstoreCompressionType {
             Self::Snappy => RocksCompressionType::Snappy,
             Self::Lz4 => RocksCompressionType::Lz4,
             Self::Zlib => RocksCompressionType::Zlib,
+            Self::Zstd => RocksCompressionType::Zstd,
         }
     }
 }
@@ -34,6 +34,7 @@ use {
         prioritization_fee_cache::PrioritizationFeeCache,
         runtime_config::RuntimeConfig,
         transaction_batch::TransactionBatch,
+        transaction_trace::TransactionTraceConfig,
         vote_account::VoteAccountsHashMap,
         vote_sender_types::ReplayVoteSender,
     },
@@ -623,6 +624,8 @@ pub struct ProcessOptions {
     /// true if after processing the contents of the blockstore at startup, we should run an accounts hash calc
     /// This is useful for debugging.
     pub run_final_accounts_hash_calc: bool,
+    /// Transactions to trace while replaying, see `Bank::transaction_trace_config`
+    pub transaction_trace_config: Option<TransactionTraceConfig>,
 }
 
 pub fn test_process_blockstore(
@@ -743,6 +746,10 @@ pub fn process_blockstore_from_root(
     assert_eq!(bank_forks.read().unwrap().banks().len(), 1);
     let bank = bank_forks.read().unwrap().root_bank();
     assert!(bank.parent().is_none());
+    if let Some(transaction_trace_config) = &opts.transaction_trace_config {
+        // shared with the banks replayed from the root one
+        *bank.transaction_trace_config.write().unwrap() = transaction_trace_config.clone();
+    }
 
     let start_slot = bank.slot();
     info!("Processing ledger from slot {}...", start_slot);
@@ -1769,13 +1776,15 @@ pub mod tests {
         rand::{thread_rng, Rng},
         solana_entry::entry::{create_ticks, next_entry, next_entry_mut},
         solana_runtime::{
+            account_set::AccountSet,
             genesis_utils::{
                 self, create_genesis_config_with_vote_accounts, ValidatorVoteKeypairs,
             },
+            lazy_account_loader::{AccountSetLazyAccountSource, LazyAccountLoader},
             vote_account::VoteAccount,
         },
         solana_sdk::{
-            account::{AccountSharedData, WritableAccount},
+            account::{Account, AccountSharedData, WritableAccount},
             epoch_schedule::EpochSchedule,
             feature_set,
             hash::Hash,
@@ -1783,7 +1792,7 @@ pub mod tests {
             pubkey::Pubkey,
             signature::{Keypair, Signer},
             system_instruction::SystemError,
-            system_transaction,
+            system_program, system_transaction,
             transaction::{Transaction, TransactionError},
         },
         solana_vote_program::{
@@ -2827,6 +2836,49 @@ pub mod tests {
         assert_eq!(bank.get_balance(&keypair3.pubkey()), 2);
     }
 
+    #[test]
+    fn test_process_entries_without_lazy_fetches() {
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config(1000);
+        let lazy = Pubkey::new_unique();
+        let source = AccountSetLazyAccountSource::from(AccountSet {
+            slot: 0,
+            accounts: vec![(lazy, Account::new(10, 0, &system_program::id()))],
+        });
+        let loader = Arc::new(LazyAccountLoader::new(Box::new(source)));
+        let runtime_config = Arc::new(RuntimeConfig {
+            lazy_account_loader: Some(loader.clone()),
+            ..RuntimeConfig::default()
+        });
+        let bank = Arc::new(Bank::new_with_runtime_config_for_tests(
+            &genesis_config,
+            runtime_config,
+        ));
+
+        // replaying a transfer to an account the bank doesn't have creates it instead of
+        // fetching it from the lazy account source, like the bank that produced the slot did
+        let entry = next_entry(
+            &bank.last_blockhash(),
+            1,
+            vec![system_transaction::transfer(
+                &mint_keypair,
+                &lazy,
+                5,
+                bank.last_blockhash(),
+            )],
+        );
+        assert_eq!(
+            process_entries_for_tests(&bank, vec![entry], false, None, None),
+            Ok(())
+        );
+        assert_eq!(bank.get_balance(&lazy), 5);
+        assert!(!bank.is_lazy_fetched_account(&lazy));
+        assert_eq!(loader.fetched_count(), 0);
+    }
+
     #[test]
     fn test_process_entries_2_txes_collision_and_error() {
         let GenesisConfigInfo {
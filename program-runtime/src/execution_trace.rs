@@ -0,0 +1,147 @@
+//! Structured trace of the instructions, cross-program invocations and
+//! syscalls executed by a transaction, for debugging replays.
+//!
+//! An [`ExecutionTracer`] is handed to the `InvokeContext` of the transaction
+//! to trace, which records an event when each instruction starts and ends,
+//! including the ones invoked through CPI, and when each syscall returns.
+
+use {
+    serde::Serialize,
+    solana_sdk::{instruction::InstructionError, pubkey::Pubkey},
+    std::{cell::RefCell, rc::Rc},
+};
+
+/// State of the VM registers `r0` to `r10` and of the program counter before
+/// the execution of a BPF instruction.
+pub type VmTraceEntry = [u64; 12];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TracedInstructionAccount {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ExecutionTraceEvent {
+    /// An instruction starts, at stack height 1 for the instructions of the
+    /// transaction message and above for CPIs
+    InstructionStart {
+        stack_height: usize,
+        program_id: String,
+        accounts: Vec<TracedInstructionAccount>,
+        /// Base64 encoded instruction data
+        data: String,
+        compute_units_remaining: u64,
+    },
+    /// An instruction ends. The compute units include those of the nested
+    /// invocations.
+    InstructionEnd {
+        stack_height: usize,
+        program_id: String,
+        compute_units_consumed: u64,
+        error: Option<String>,
+        /// BPF instructions executed by the program itself, excluding the
+        /// nested invocations, when the VM trace is recorded
+        #[serde(skip_serializing_if = "Option::is_none")]
+        vm_trace: Option<Vec<VmTraceEntry>>,
+    },
+    /// A syscall returns. The compute units of `SyscallInvokeSigned*` include
+    /// those of the invoked instruction, whose events precede this one.
+    Syscall {
+        stack_height: usize,
+        name: &'static str,
+        compute_units_consumed: u64,
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct ExecutionTracer {
+    record_vm_trace: bool,
+    events: Vec<ExecutionTraceEvent>,
+    // Length of the VM trace log when each instruction of the invocation stack
+    // started, so that each one only keeps its own BPF instructions
+    vm_trace_starts: Vec<usize>,
+}
+
+impl ExecutionTracer {
+    /// Creates a tracer, which also records the state of the VM before each
+    /// BPF instruction if `record_vm_trace` is set. This makes the program
+    /// loader compile the invoked programs with instruction tracing enabled,
+    /// bypassing the executor cache.
+    pub fn new_ref(record_vm_trace: bool) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            record_vm_trace,
+            ..Self::default()
+        }))
+    }
+
+    pub fn record_vm_trace(&self) -> bool {
+        self.record_vm_trace
+    }
+
+    pub fn events(&self) -> &[ExecutionTraceEvent] {
+        &self.events
+    }
+
+    pub fn take_events(&mut self) -> Vec<ExecutionTraceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn trace_instruction_start(
+        &mut self,
+        stack_height: usize,
+        program_id: &Pubkey,
+        accounts: Vec<TracedInstructionAccount>,
+        data: &[u8],
+        compute_units_remaining: u64,
+        vm_trace_len: usize,
+    ) {
+        self.vm_trace_starts.push(vm_trace_len);
+        self.events.push(ExecutionTraceEvent::InstructionStart {
+            stack_height,
+            program_id: program_id.to_string(),
+            accounts,
+            data: base64::encode(data),
+            compute_units_remaining,
+        });
+    }
+
+    /// Records the end of the innermost started instruction, moving the BPF
+    /// instructions it executed out of `vm_trace_log`.
+    pub fn trace_instruction_end(
+        &mut self,
+        stack_height: usize,
+        program_id: &Pubkey,
+        compute_units_consumed: u64,
+        result: &Result<(), InstructionError>,
+        vm_trace_log: &mut Vec<VmTraceEntry>,
+    ) {
+        let vm_trace_start = self.vm_trace_starts.pop().unwrap_or(vm_trace_log.len());
+        let vm_trace = vm_trace_log.split_off(vm_trace_start.min(vm_trace_log.len()));
+        self.events.push(ExecutionTraceEvent::InstructionEnd {
+            stack_height,
+            program_id: program_id.to_string(),
+            compute_units_consumed,
+            error: result.as_ref().err().map(|err| err.to_string()),
+            vm_trace: self.record_vm_trace.then_some(vm_trace),
+        });
+    }
+
+    pub fn trace_syscall(
+        &mut self,
+        stack_height: usize,
+        name: &'static str,
+        compute_units_consumed: u64,
+        error: Option<String>,
+    ) {
+        self.events.push(ExecutionTraceEvent::Syscall {
+            stack_height,
+            name,
+            compute_units_consumed,
+            error,
+        });
+    }
+}
@@ -2,6 +2,7 @@ use {
     crate::{
         accounts_data_meter::AccountsDataMeter,
         compute_budget::ComputeBudget,
+        execution_trace::{ExecutionTracer, TracedInstructionAccount},
         executor_cache::TransactionExecutorCache,
         ic_logger_msg, ic_msg,
         log_collector::LogCollector,
@@ -106,6 +107,7 @@ pub struct InvokeContext<'a> {
     pub sysvar_cache: Cow<'a, SysvarCache>,
     pub trace_log: Vec<[u64; 12]>,
     log_collector: Option<Rc<RefCell<LogCollector>>>,
+    execution_tracer: Option<Rc<RefCell<ExecutionTracer>>>,
     compute_budget: ComputeBudget,
     current_compute_budget: ComputeBudget,
     compute_meter: RefCell<u64>,
@@ -126,6 +128,7 @@ impl<'a> InvokeContext<'a> {
         builtin_programs: &'a [BuiltinProgram],
         sysvar_cache: Cow<'a, SysvarCache>,
         log_collector: Option<Rc<RefCell<LogCollector>>>,
+        execution_tracer: Option<Rc<RefCell<ExecutionTracer>>>,
         compute_budget: ComputeBudget,
         tx_executor_cache: Rc<RefCell<TransactionExecutorCache>>,
         feature_set: Arc<FeatureSet>,
@@ -141,6 +144,7 @@ impl<'a> InvokeContext<'a> {
             sysvar_cache,
             trace_log: Vec::new(),
             log_collector,
+            execution_tracer,
             current_compute_budget: compute_budget,
             compute_budget,
             compute_meter: RefCell::new(compute_budget.compute_unit_limit),
@@ -182,6 +186,7 @@ impl<'a> InvokeContext<'a> {
             builtin_programs,
             Cow::Owned(sysvar_cache),
             Some(LogCollector::new_ref()),
+            None,
             ComputeBudget::default(),
             Rc::new(RefCell::new(TransactionExecutorCache::default())),
             Arc::new(FeatureSet::all_enabled()),
@@ -676,36 +681,111 @@ impl<'a> InvokeContext<'a> {
         self.transaction_context
             .get_next_instruction_context()?
             .configure(program_indices, instruction_accounts, instruction_data);
-        self.push()?;
-        self.process_executable_chain(compute_units_consumed, timings)
-            .and_then(|_| {
-                if self
-                    .feature_set
-                    .is_active(&enable_early_verification_of_account_modifications::id())
-                {
-                    Ok(())
-                } else {
-                    // Verify the called program has not misbehaved
-                    let mut verify_callee_time = Measure::start("verify_callee_time");
-                    let result = if is_top_level_instruction {
-                        self.verify(instruction_accounts, program_indices)
+        let traced_program_id = self.trace_instruction_start(
+            nesting_level.saturating_add(1),
+            instruction_data,
+            instruction_accounts,
+            program_indices,
+        );
+        let pre_remaining_units = self.get_remaining();
+        let result = self.push().and_then(|()| {
+            self.process_executable_chain(compute_units_consumed, timings)
+                .and_then(|_| {
+                    if self
+                        .feature_set
+                        .is_active(&enable_early_verification_of_account_modifications::id())
+                    {
+                        Ok(())
                     } else {
-                        self.verify_and_update(instruction_accounts, false)
-                    };
-                    verify_callee_time.stop();
-                    saturating_add_assign!(
-                        timings
-                            .execute_accessories
-                            .process_instructions
-                            .verify_callee_us,
-                        verify_callee_time.as_us()
-                    );
-                    result
-                }
+                        // Verify the called program has not misbehaved
+                        let mut verify_callee_time = Measure::start("verify_callee_time");
+                        let result = if is_top_level_instruction {
+                            self.verify(instruction_accounts, program_indices)
+                        } else {
+                            self.verify_and_update(instruction_accounts, false)
+                        };
+                        verify_callee_time.stop();
+                        saturating_add_assign!(
+                            timings
+                                .execute_accessories
+                                .process_instructions
+                                .verify_callee_us,
+                            verify_callee_time.as_us()
+                        );
+                        result
+                    }
+                })
+                // MUST pop if and only if `push` succeeded, independent of `result`.
+                // Thus, the `.and()` instead of an `.and_then()`.
+                .and(self.pop())
+        });
+        if let Some(program_id) = traced_program_id {
+            self.trace_instruction_end(
+                nesting_level.saturating_add(1),
+                &program_id,
+                pre_remaining_units.saturating_sub(self.get_remaining()),
+                &result,
+            );
+        }
+        result
+    }
+
+    /// Records the start of an instruction if the transaction is traced, and
+    /// returns its program id
+    fn trace_instruction_start(
+        &self,
+        stack_height: usize,
+        instruction_data: &[u8],
+        instruction_accounts: &[InstructionAccount],
+        program_indices: &[IndexOfAccount],
+    ) -> Option<Pubkey> {
+        let execution_tracer = self.execution_tracer.as_ref()?;
+        let program_id = program_indices.last().and_then(|index_in_transaction| {
+            self.transaction_context
+                .get_key_of_account_at_index(*index_in_transaction)
+                .ok()
+        })?;
+        let accounts = instruction_accounts
+            .iter()
+            .filter_map(|instruction_account| {
+                let pubkey = self
+                    .transaction_context
+                    .get_key_of_account_at_index(instruction_account.index_in_transaction)
+                    .ok()?;
+                Some(TracedInstructionAccount {
+                    pubkey: pubkey.to_string(),
+                    is_signer: instruction_account.is_signer,
+                    is_writable: instruction_account.is_writable,
+                })
             })
-            // MUST pop if and only if `push` succeeded, independent of `result`.
-            // Thus, the `.and()` instead of an `.and_then()`.
-            .and(self.pop())
+            .collect();
+        execution_tracer.borrow_mut().trace_instruction_start(
+            stack_height,
+            program_id,
+            accounts,
+            instruction_data,
+            self.get_remaining(),
+            self.trace_log.len(),
+        );
+        Some(*program_id)
+    }
+
+    fn trace_instruction_end(
+        &mut self,
+        stack_height: usize,
+        program_id: &Pubkey,
+        compute_units_consumed: u64,
+        result: &Result<(), InstructionError>,
+    ) {
+        if let Some(execution_tracer) = &self.execution_tracer {
+            execution_tracer.borrow_mut().trace_instruction_end(
+                stack_height,
+                program_id,
+                compute_units_consumed,
+                result,
+                &mut self.trace_log,
+            );
+        }
     }
 
     /// Calls the instruction's program entrypoint method
@@ -774,6 +854,20 @@ impl<'a> InvokeContext<'a> {
         self.log_collector.clone()
     }
 
+    /// Get this invocation's ExecutionTracer, if the transaction is traced
+    pub fn get_execution_tracer(&self) -> Option<Rc<RefCell<ExecutionTracer>>> {
+        self.execution_tracer.clone()
+    }
+
+    /// Whether the programs should record the state of the VM before each BPF
+    /// instruction into `trace_log`
+    pub fn is_vm_tracing_enabled(&self) -> bool {
+        self.execution_tracer
+            .as_ref()
+            .map(|execution_tracer| execution_tracer.borrow().record_vm_trace())
+            .unwrap_or(false)
+    }
+
     /// Consume compute units
     pub fn consume_checked(&self, amount: u64) -> Result<(), InstructionError> {
         let mut compute_meter = self.compute_meter.borrow_mut();
@@ -1004,7 +1098,7 @@ pub fn mock_process_instruction(
 mod tests {
     use {
         super::*,
-        crate::compute_budget,
+        crate::{compute_budget, execution_trace::ExecutionTraceEvent},
         serde::{Deserialize, Serialize},
         solana_sdk::account::WritableAccount,
     };
@@ -1353,6 +1447,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_process_instruction_execution_trace() {
+        let callee_program_id = solana_sdk::pubkey::new_rand();
+        let builtin_programs = &[BuiltinProgram {
+            program_id: callee_program_id,
+            process_instruction: mock_process_instruction,
+        }];
+        let loader_account = AccountSharedData::new(0, 0, &native_loader::id());
+        let mut program_account = AccountSharedData::new(1, 0, &native_loader::id());
+        program_account.set_executable(true);
+        let accounts = vec![
+            (
+                solana_sdk::pubkey::new_rand(),
+                AccountSharedData::new(42, 1, &callee_program_id),
+            ),
+            (
+                solana_sdk::pubkey::new_rand(),
+                AccountSharedData::new(84, 1, &solana_sdk::pubkey::new_rand()),
+            ),
+            (
+                solana_sdk::pubkey::new_rand(),
+                AccountSharedData::new(168, 1, &solana_sdk::pubkey::new_rand()),
+            ),
+            (callee_program_id, program_account),
+            (solana_sdk::pubkey::new_rand(), loader_account),
+        ];
+        let metas = vec![
+            AccountMeta::new(accounts.get(0).unwrap().0, false),
+            AccountMeta::new(accounts.get(1).unwrap().0, false),
+            AccountMeta::new_readonly(accounts.get(2).unwrap().0, false),
+        ];
+        let caller_instruction_accounts = (0..4)
+            .map(|instruction_account_index| InstructionAccount {
+                index_in_transaction: instruction_account_index,
+                index_in_caller: instruction_account_index,
+                index_in_callee: instruction_account_index,
+                is_signer: false,
+                is_writable: instruction_account_index < 2,
+            })
+            .collect::<Vec<_>>();
+        let mut transaction_context =
+            TransactionContext::new(accounts, Some(Rent::default()), 2, 18);
+        let mut invoke_context =
+            InvokeContext::new_mock(&mut transaction_context, builtin_programs);
+        let execution_tracer = ExecutionTracer::new_ref(false);
+        invoke_context.execution_tracer = Some(execution_tracer.clone());
+        assert!(!invoke_context.is_vm_tracing_enabled());
+
+        let compute_units_to_consume = 10;
+        let expected_results = vec![Ok(()), Err(InstructionError::GenericError)];
+        for expected_result in expected_results.iter() {
+            invoke_context
+                .transaction_context
+                .get_next_instruction_context()
+                .unwrap()
+                .configure(&[4], &caller_instruction_accounts, &[]);
+            invoke_context.push().unwrap();
+            let instruction = Instruction::new_with_bincode(
+                callee_program_id,
+                &MockInstruction::ConsumeComputeUnits {
+                    compute_units_to_consume,
+                    desired_result: expected_result.clone(),
+                },
+                metas.clone(),
+            );
+            let (instruction_accounts, program_indices) = invoke_context
+                .prepare_instruction(&instruction, &[])
+                .unwrap();
+            let result = invoke_context.process_instruction(
+                &instruction.data,
+                &instruction_accounts,
+                &program_indices,
+                &mut 0,
+                &mut ExecuteTimings::default(),
+            );
+            assert_eq!(&result, expected_result);
+            invoke_context.pop().unwrap();
+        }
+
+        // only the invoked instructions are traced, not the caller pushed above
+        let events = execution_tracer.borrow_mut().take_events();
+        assert_eq!(events.len(), 4);
+        for (events, expected_result) in events.chunks(2).zip(expected_results) {
+            match &events[0] {
+                ExecutionTraceEvent::InstructionStart {
+                    stack_height,
+                    program_id,
+                    accounts,
+                    ..
+                } => {
+                    assert_eq!(*stack_height, 2);
+                    assert_eq!(program_id, &callee_program_id.to_string());
+                    assert_eq!(accounts.len(), 3);
+                    assert_eq!(accounts[0].pubkey, metas[0].pubkey.to_string());
+                    assert!(accounts[0].is_writable);
+                    assert!(!accounts[2].is_writable);
+                }
+                event => panic!("unexpected event {event:?}"),
+            }
+            assert_eq!(
+                events[1],
+                ExecutionTraceEvent::InstructionEnd {
+                    stack_height: 2,
+                    program_id: callee_program_id.to_string(),
+                    compute_units_consumed: compute_units_to_consume,
+                    error: expected_result.err().map(|err| err.to_string()),
+                    vm_trace: None,
+                }
+            );
+        }
+    }
+
     #[test]
     fn test_invoke_context_compute_budget() {
         let accounts = vec![(solana_sdk::pubkey::new_rand(), AccountSharedData::default())];
@@ -15,6 +15,7 @@ use {
         stable_log, timings::ExecuteTimings,
     },
     solana_runtime::{
+        account_set::AccountSet,
         accounts_background_service::{AbsRequestSender, SnapshotRequestType},
         bank::Bank,
         bank_forks::BankForks,
@@ -532,6 +533,11 @@ impl ProgramTest {
             .push((address, AccountSharedData::from(account)));
     }
 
+    /// Add the accounts of an account set, see `Bank::export_account_set`
+    pub fn add_account_set(&mut self, account_set: &AccountSet) {
+        self.accounts.extend(account_set.shared_accounts());
+    }
+
     /// Add an account to the test environment with the account data in the provided `filename`
     pub fn add_account_with_file_data(
         &mut self,
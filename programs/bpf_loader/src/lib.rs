@@ -126,6 +126,7 @@ fn create_executor_from_bytes(
     programdata: &[u8],
     use_jit: bool,
     reject_deployment_of_broken_elfs: bool,
+    enable_instruction_tracing: bool,
 ) -> Result<Arc<BpfExecutor>, InstructionError> {
     let mut register_syscalls_time = Measure::start("register_syscalls_time");
     let disable_deploy_of_alloc_free_syscall = reject_deployment_of_broken_elfs
@@ -135,7 +136,7 @@ fn create_executor_from_bytes(
         compute_budget,
         reject_deployment_of_broken_elfs,
         disable_deploy_of_alloc_free_syscall,
-        false,
+        enable_instruction_tracing,
     )
     .map_err(|e| {
         ic_logger_msg!(log_collector, "Failed to register syscalls: {}", e);
@@ -186,6 +187,7 @@ pub fn create_executor_from_account(
     program: &BorrowedAccount,
     programdata: &BorrowedAccount,
     use_jit: bool,
+    enable_instruction_tracing: bool,
 ) -> Result<(Arc<dyn Executor>, Option<CreateMetrics>), InstructionError> {
     if !check_loader_id(program.get_owner()) {
         ic_logger_msg!(
@@ -247,6 +249,7 @@ pub fn create_executor_from_account(
             .ok_or(InstructionError::AccountDataTooSmall)?,
         use_jit,
         false, /* reject_deployment_of_broken_elfs */
+        enable_instruction_tracing,
     )?;
     if let Some(mut tx_executor_cache) = tx_executor_cache {
         tx_executor_cache.set(*program.get_key(), executor.clone(), false);
@@ -413,15 +416,19 @@ fn process_instruction_common(
                 first_instruction_account,
             )?)
         };
+        // Programs traced instruction by instruction are interpreted by an executor compiled
+        // for it, which must not be shared with the rest of the replay through the cache
+        let enable_instruction_tracing = invoke_context.is_vm_tracing_enabled();
         let mut get_or_create_executor_time = Measure::start("get_or_create_executor_time");
         let (executor, create_executor_metrics) = create_executor_from_account(
             &invoke_context.feature_set,
             invoke_context.get_compute_budget(),
             log_collector,
-            Some(invoke_context.tx_executor_cache.borrow_mut()),
+            (!enable_instruction_tracing).then(|| invoke_context.tx_executor_cache.borrow_mut()),
             &program,
             programdata.as_ref().unwrap_or(&program),
-            use_jit,
+            use_jit && !enable_instruction_tracing,
+            enable_instruction_tracing,
         )?;
         drop(program);
         drop(programdata);
@@ -652,7 +659,8 @@ fn process_loader_upgradeable_instruction(
                     .get(buffer_data_offset..)
                     .ok_or(InstructionError::AccountDataTooSmall)?,
                 use_jit,
-                true,
+                true,  /* reject_deployment_of_broken_elfs */
+                false, /* enable_instruction_tracing */
             )?;
             drop(buffer);
             create_executor_metrics.program_id = new_program_id.to_string();
@@ -839,7 +847,8 @@ fn process_loader_upgradeable_instruction(
                     .get(buffer_data_offset..)
                     .ok_or(InstructionError::AccountDataTooSmall)?,
                 use_jit,
-                true,
+                true,  /* reject_deployment_of_broken_elfs */
+                false, /* enable_instruction_tracing */
             )?;
             drop(buffer);
             create_executor_metrics.program_id = new_program_id.to_string();
@@ -1340,7 +1349,8 @@ fn process_loader_instruction(
                 &mut create_executor_metrics,
                 program.get_data(),
                 use_jit,
-                true,
+                true,  /* reject_deployment_of_broken_elfs */
+                false, /* enable_instruction_tracing */
             )?;
             create_executor_metrics.program_id = program.get_key().to_string();
             create_executor_metrics.submit_datapoint(&mut invoke_context.timings);
@@ -459,9 +459,26 @@ macro_rules! declare_syscall {
                 memory_mapping: &mut MemoryMapping,
                 result: &mut ProgramResult,
             ) {
+                let compute_units_remaining =
+                    solana_rbpf::vm::ContextObject::get_remaining(invoke_context);
                 let converted_result: ProgramResult = Self::inner_call(
                     invoke_context, arg_a, arg_b, arg_c, arg_d, arg_e, memory_mapping,
                 ).into();
+                if let Some(execution_tracer) = invoke_context.get_execution_tracer() {
+                    let compute_units_consumed = compute_units_remaining.saturating_sub(
+                        solana_rbpf::vm::ContextObject::get_remaining(invoke_context),
+                    );
+                    let error = match &converted_result {
+                        ProgramResult::Err(err) => Some(err.to_string()),
+                        _ => None,
+                    };
+                    execution_tracer.borrow_mut().trace_syscall(
+                        invoke_context.get_stack_height(),
+                        stringify!($name),
+                        compute_units_consumed,
+                        error,
+                    );
+                }
                 *result = converted_result;
             }
         }
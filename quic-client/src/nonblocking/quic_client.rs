@@ -23,11 +23,16 @@ use {
         transport::Result as TransportResult,
     },
     solana_streamer::{
-        nonblocking::quic::ALPN_TPU_PROTOCOL_ID, tls_certificates::new_self_signed_tls_certificate,
+        nonblocking::quic::{
+            ALPN_TPU_PROTOCOL_ID, CONNECTION_CLOSE_CODE_DISALLOWED,
+            CONNECTION_CLOSE_CODE_DROPPED_ENTRY, CONNECTION_CLOSE_CODE_EXCEED_MAX_STREAM_COUNT,
+            CONNECTION_CLOSE_CODE_TOO_MANY,
+        },
+        tls_certificates::new_self_signed_tls_certificate,
     },
     solana_tpu_client::{
-        connection_cache_stats::ConnectionCacheStats, nonblocking::tpu_connection::TpuConnection,
-        tpu_connection::ClientStats,
+        connection_cache_stats::ConnectionCacheStats, leader_send_stats::LeaderSendOutcome,
+        nonblocking::tpu_connection::TpuConnection, tpu_connection::ClientStats,
     },
     std::{
         net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
@@ -83,6 +88,42 @@ pub enum QuicError {
     ConnectError(#[from] ConnectError),
 }
 
+impl QuicError {
+    /// Classify the error by what the leader did with the connection or stream
+    pub fn send_outcome(&self) -> LeaderSendOutcome {
+        match self {
+            Self::WriteError(WriteError::Stopped(_)) => LeaderSendOutcome::Throttled,
+            Self::WriteError(WriteError::ConnectionLost(connection_error)) => {
+                connection_error_send_outcome(connection_error)
+            }
+            Self::WriteError(_) => LeaderSendOutcome::Failed,
+            Self::ConnectionError(connection_error) => {
+                connection_error_send_outcome(connection_error)
+            }
+            Self::ConnectError(_) => LeaderSendOutcome::Failed,
+        }
+    }
+}
+
+fn connection_error_send_outcome(connection_error: &ConnectionError) -> LeaderSendOutcome {
+    match connection_error {
+        ConnectionError::TimedOut => LeaderSendOutcome::TimedOut,
+        ConnectionError::ApplicationClosed(close) => {
+            match u32::try_from(close.error_code.into_inner()) {
+                Ok(CONNECTION_CLOSE_CODE_DISALLOWED) => LeaderSendOutcome::Refused,
+                Ok(
+                    CONNECTION_CLOSE_CODE_DROPPED_ENTRY
+                    | CONNECTION_CLOSE_CODE_EXCEED_MAX_STREAM_COUNT
+                    | CONNECTION_CLOSE_CODE_TOO_MANY,
+                ) => LeaderSendOutcome::Throttled,
+                _ => LeaderSendOutcome::Failed,
+            }
+        }
+        ConnectionError::ConnectionClosed(_) | ConnectionError::Reset => LeaderSendOutcome::Refused,
+        _ => LeaderSendOutcome::Failed,
+    }
+}
+
 impl From<QuicError> for ClientErrorKind {
     fn from(quic_error: QuicError) -> Self {
         Self::Custom(format!("{quic_error:?}"))
@@ -467,6 +508,20 @@ impl QuicClient {
         stats: &ClientStats,
         connection_stats: Arc<ConnectionCacheStats>,
     ) -> Result<(), ClientErrorKind>
+    where
+        T: AsRef<[u8]>,
+    {
+        self._send_batch(buffers, stats, connection_stats)
+            .await
+            .map_err(Into::<ClientErrorKind>::into)
+    }
+
+    async fn _send_batch<T>(
+        &self,
+        buffers: &[T],
+        stats: &ClientStats,
+        connection_stats: Arc<ConnectionCacheStats>,
+    ) -> Result<(), QuicError>
     where
         T: AsRef<[u8]>,
     {
@@ -486,8 +541,7 @@ impl QuicClient {
         }
         let connection = self
             ._send_buffer(buffers[0].as_ref(), stats, connection_stats)
-            .await
-            .map_err(Into::<ClientErrorKind>::into)?;
+            .await?;
 
         // Used to avoid dereferencing the Arc multiple times below
         // by just getting a reference to the NewConnection once
@@ -507,10 +561,7 @@ impl QuicClient {
             .collect();
 
         for f in futures {
-            f.await
-                .into_iter()
-                .try_for_each(|res| res)
-                .map_err(Into::<ClientErrorKind>::into)?;
+            f.await.into_iter().try_for_each(|res| res)?;
         }
         Ok(())
     }
@@ -584,6 +635,30 @@ impl TpuConnection for QuicTpuConnection {
         Ok(())
     }
 
+    async fn send_wire_transaction_batch_with_outcome<T>(
+        &self,
+        buffers: &[T],
+    ) -> (TransportResult<()>, LeaderSendOutcome)
+    where
+        T: AsRef<[u8]> + Send + Sync,
+    {
+        let stats = ClientStats::default();
+        let len = buffers.len();
+        let res = self
+            .client
+            ._send_batch(buffers, &stats, self.connection_stats.clone())
+            .await;
+        self.connection_stats
+            .add_client_stats(&stats, len, res.is_ok());
+        match res {
+            Ok(()) => (Ok(()), LeaderSendOutcome::Accepted),
+            Err(err) => {
+                let outcome = err.send_outcome();
+                (Err(ClientErrorKind::from(err).into()), outcome)
+            }
+        }
+    }
+
     async fn send_wire_transaction<T>(&self, wire_transaction: T) -> TransportResult<()>
     where
         T: AsRef<[u8]> + Send + Sync,
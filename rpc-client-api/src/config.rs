@@ -223,6 +223,18 @@ pub struct RpcSignaturesForAddressConfig {
     pub min_context_slot: Option<Slot>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcSignaturesForProgramInstructionConfig {
+    pub discriminator: Option<String>, // Up to 8 bytes as base-58 string
+    pub before: Option<String>,        // Signature as base-58 string
+    pub until: Option<String>,         // Signature as base-58 string
+    pub limit: Option<usize>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+    pub min_context_slot: Option<Slot>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcEncodingConfigWrapper<T> {
@@ -338,3 +350,14 @@ pub struct RpcContextConfig {
     pub commitment: Option<CommitmentConfig>,
     pub min_context_slot: Option<Slot>,
 }
+
+/// Selects the accounts a prioritization fee estimate is made for: the
+/// listed accounts, plus the writable accounts of `transaction` if provided
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPriorityFeeEstimateConfig {
+    pub account_keys: Option<Vec<String>>,
+    pub transaction: Option<String>,
+    pub encoding: Option<UiTransactionEncoding>,
+    pub num_blocks: Option<usize>,
+}
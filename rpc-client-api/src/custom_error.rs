@@ -24,6 +24,7 @@ pub const JSON_RPC_SERVER_ERROR_TRANSACTION_SIGNATURE_LEN_MISMATCH: i64 = -32013
 pub const JSON_RPC_SERVER_ERROR_BLOCK_STATUS_NOT_AVAILABLE_YET: i64 = -32014;
 pub const JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION: i64 = -32015;
 pub const JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED: i64 = -32016;
+pub const JSON_RPC_SERVER_ERROR_PROGRAM_INSTRUCTION_INDEX_NOT_AVAILABLE: i64 = -32017;
 
 #[derive(Error, Debug)]
 pub enum RpcCustomError {
@@ -65,6 +66,8 @@ pub enum RpcCustomError {
     UnsupportedTransactionVersion(u8),
     #[error("MinContextSlotNotReached")]
     MinContextSlotNotReached { context_slot: Slot },
+    #[error("ProgramInstructionIndexNotAvailable")]
+    ProgramInstructionIndexNotAvailable,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -206,6 +209,13 @@ impl From<RpcCustomError> for Error {
                     context_slot,
                 })),
             },
+            RpcCustomError::ProgramInstructionIndexNotAvailable => Self {
+                code: ErrorCode::ServerError(
+                    JSON_RPC_SERVER_ERROR_PROGRAM_INSTRUCTION_INDEX_NOT_AVAILABLE,
+                ),
+                message: "Program instruction index is not available from this node".to_string(),
+                data: None,
+            },
         }
     }
 }
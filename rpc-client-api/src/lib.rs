@@ -6,8 +6,10 @@ pub mod custom_error;
 pub mod deprecated_config;
 pub mod error_object;
 pub mod filter;
+pub mod mock_response;
 pub mod request;
 pub mod response;
+pub mod response_encoding;
 pub mod version_req;
 
 #[macro_use]
@@ -80,6 +80,7 @@ pub enum RpcRequest {
     GetRecentBlockhash,
     GetRecentPerformanceSamples,
     GetRecentPrioritizationFees,
+    GetPriorityFeeEstimate,
     GetHighestSnapshotSlot,
     #[deprecated(
         since = "1.9.0",
@@ -105,6 +106,7 @@ pub enum RpcRequest {
     GetTokenSupply,
     GetTransaction,
     GetTransactionCount,
+    GetTransactionSendStatus,
     GetVersion,
     GetVoteAccounts,
     IsBlockhashValid,
@@ -160,6 +162,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetRecentBlockhash => "getRecentBlockhash",
             RpcRequest::GetRecentPerformanceSamples => "getRecentPerformanceSamples",
             RpcRequest::GetRecentPrioritizationFees => "getRecentPrioritizationFees",
+            RpcRequest::GetPriorityFeeEstimate => "getPriorityFeeEstimate",
             RpcRequest::GetHighestSnapshotSlot => "getHighestSnapshotSlot",
             RpcRequest::GetSnapshotSlot => "getSnapshotSlot",
             RpcRequest::GetSignaturesForAddress => "getSignaturesForAddress",
@@ -181,6 +184,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetTokenLargestAccounts => "getTokenLargestAccounts",
             RpcRequest::GetTransaction => "getTransaction",
             RpcRequest::GetTransactionCount => "getTransactionCount",
+            RpcRequest::GetTransactionSendStatus => "getTransactionSendStatus",
             RpcRequest::GetVersion => "getVersion",
             RpcRequest::GetVoteAccounts => "getVoteAccounts",
             RpcRequest::IsBlockhashValid => "isBlockhashValid",
@@ -551,6 +551,37 @@ pub struct RpcPrioritizationFee {
     pub prioritization_fee: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPriorityFeeEstimate {
+    pub p25: u64,
+    pub p50: u64,
+    pub p75: u64,
+    pub p90: u64,
+    pub num_blocks: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RpcTransactionSendState {
+    Pending,
+    Rooted,
+    Expired,
+    MaxRetriesExceeded,
+    Failed,
+    Dropped,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransactionSendStatus {
+    pub state: RpcTransactionSendState,
+    pub attempts: usize,
+    pub leaders: Vec<String>,
+    pub last_error: Option<String>,
+    pub compute_unit_price: u64,
+}
+
 #[cfg(test)]
 pub mod tests {
 
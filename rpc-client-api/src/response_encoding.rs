@@ -0,0 +1,240 @@
+//! Alternatives to JSON for RPC responses, negotiated with the HTTP `Accept`
+//! and `Accept-Encoding` request headers.
+//!
+//! A client lists the binary encodings it can decode in `Accept`, in order of
+//! preference, after `application/json`. The server answers with a binary
+//! encoding only for the methods that support it, and with JSON otherwise,
+//! so clients must check the `Content-Type` of every response. Errors are
+//! always JSON.
+
+use {
+    crate::request::RpcRequest,
+    serde_json::Value,
+    solana_account_decoder::UiAccountEncoding,
+    solana_sdk::{account::Account, clock::Slot, pubkey::Pubkey},
+    solana_transaction_status::TransactionDetails,
+};
+
+pub const JSON_CONTENT_TYPE: &str = "application/json";
+pub const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+pub const BINCODE_CONTENT_TYPE: &str = "application/x-bincode";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcBinaryEncoding {
+    /// `getBlock` as a `solana.storage.confirmed_block.ConfirmedBlock` message
+    Protobuf,
+    /// `getProgramAccounts` as [`RpcBincodeKeyedAccounts`] and
+    /// `getMultipleAccounts` as [`RpcBincodeMultipleAccounts`]
+    Bincode,
+}
+
+impl RpcBinaryEncoding {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Protobuf => PROTOBUF_CONTENT_TYPE,
+            Self::Bincode => BINCODE_CONTENT_TYPE,
+        }
+    }
+
+    /// Parse a `Content-Type` header value, ignoring its parameters
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match media_type(content_type) {
+            PROTOBUF_CONTENT_TYPE => Some(Self::Protobuf),
+            BINCODE_CONTENT_TYPE => Some(Self::Bincode),
+            _ => None,
+        }
+    }
+
+    pub fn supports_method(&self, method: &str) -> bool {
+        match self {
+            Self::Protobuf => method == "getBlock",
+            Self::Bincode => method == "getProgramAccounts" || method == "getMultipleAccounts",
+        }
+    }
+
+    /// The binary encodings listed in an `Accept` header value, in order
+    pub fn parse_accept(accept: &str) -> Vec<Self> {
+        accept
+            .split(',')
+            .filter(|media_range| !is_refused(media_range))
+            .filter_map(Self::from_content_type)
+            .collect()
+    }
+
+    /// The binary encoding a client may request for `request`, if any.
+    ///
+    /// Binary responses carry the raw block or accounts, which the client
+    /// encodes as requested, so they are only worth it when the JSON response
+    /// would carry all of them too: blocks with full transaction details, and
+    /// whole accounts encoded as base64. The server answers other requests
+    /// with JSON.
+    pub fn for_request(request: RpcRequest, params: &Value) -> Option<Self> {
+        match request {
+            RpcRequest::GetBlock => {
+                let transaction_details = params
+                    .get(1)
+                    .and_then(|config| config.get("transactionDetails"))
+                    .cloned()
+                    .map(serde_json::from_value::<TransactionDetails>)
+                    .transpose()
+                    .ok()?
+                    .unwrap_or_default();
+                (transaction_details == TransactionDetails::Full).then_some(Self::Protobuf)
+            }
+            RpcRequest::GetProgramAccounts | RpcRequest::GetMultipleAccounts => {
+                let config = params.get(1)?;
+                let encoding =
+                    serde_json::from_value::<UiAccountEncoding>(config.get("encoding")?.clone())
+                        .ok()?;
+                (matches!(
+                    encoding,
+                    UiAccountEncoding::Base64 | UiAccountEncoding::Base64Zstd
+                ) && config.get("dataSlice").map_or(true, Value::is_null))
+                .then_some(Self::Bincode)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Compression applied to a response body, as in the `Content-Encoding` header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpcContentEncoding {
+    Zstd,
+    Gzip,
+}
+
+impl RpcContentEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    pub fn from_content_encoding(content_encoding: &str) -> Option<Self> {
+        match content_encoding.trim() {
+            "zstd" => Some(Self::Zstd),
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    /// The preferred compression accepted by an `Accept-Encoding` header
+    /// value; zstd is preferred over gzip regardless of order
+    pub fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let accepted = accept_encoding
+            .split(',')
+            .filter(|coding| !is_refused(coding))
+            .filter_map(|coding| Self::from_content_encoding(media_type(coding)))
+            .collect::<Vec<_>>();
+        [Self::Zstd, Self::Gzip]
+            .into_iter()
+            .find(|encoding| accepted.contains(encoding))
+    }
+}
+
+/// `getProgramAccounts` response in [`RpcBinaryEncoding::Bincode`]; the
+/// context is included whether or not `withContext` was requested
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcBincodeKeyedAccounts {
+    pub slot: Slot,
+    pub api_version: String,
+    pub accounts: Vec<(Pubkey, Account)>,
+}
+
+/// `getMultipleAccounts` response in [`RpcBinaryEncoding::Bincode`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcBincodeMultipleAccounts {
+    pub slot: Slot,
+    pub api_version: String,
+    pub accounts: Vec<Option<Account>>,
+}
+
+fn media_type(value: &str) -> &str {
+    value.split(';').next().unwrap_or_default().trim()
+}
+
+fn is_refused(value: &str) -> bool {
+    value
+        .split(';')
+        .skip(1)
+        .any(|param| matches!(param.trim(), "q=0" | "q=0.0" | "q=0.00" | "q=0.000"))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serde_json::json};
+
+    #[test]
+    fn test_negotiation() {
+        assert_eq!(
+            RpcBinaryEncoding::parse_accept(
+                "application/json, application/x-bincode;q=0.9, application/x-protobuf;q=0"
+            ),
+            vec![RpcBinaryEncoding::Bincode]
+        );
+        assert_eq!(
+            RpcBinaryEncoding::from_content_type("application/x-protobuf; charset=binary"),
+            Some(RpcBinaryEncoding::Protobuf)
+        );
+        assert_eq!(
+            RpcBinaryEncoding::from_content_type(JSON_CONTENT_TYPE),
+            None
+        );
+
+        assert_eq!(
+            RpcContentEncoding::negotiate("gzip, deflate, zstd"),
+            Some(RpcContentEncoding::Zstd)
+        );
+        assert_eq!(
+            RpcContentEncoding::negotiate("gzip;q=1.0, zstd;q=0"),
+            Some(RpcContentEncoding::Gzip)
+        );
+        assert_eq!(RpcContentEncoding::negotiate("br, deflate"), None);
+    }
+
+    #[test]
+    fn test_binary_encoding_for_request() {
+        assert_eq!(
+            RpcBinaryEncoding::for_request(RpcRequest::GetBlock, &json!([1])),
+            Some(RpcBinaryEncoding::Protobuf)
+        );
+        assert_eq!(
+            RpcBinaryEncoding::for_request(
+                RpcRequest::GetBlock,
+                &json!([1, {"transactionDetails": "signatures"}])
+            ),
+            None
+        );
+        assert_eq!(
+            RpcBinaryEncoding::for_request(
+                RpcRequest::GetMultipleAccounts,
+                &json!([[], {"encoding": "base64+zstd"}])
+            ),
+            Some(RpcBinaryEncoding::Bincode)
+        );
+        assert_eq!(
+            RpcBinaryEncoding::for_request(
+                RpcRequest::GetMultipleAccounts,
+                &json!([[], {"encoding": "base64", "dataSlice": {"offset": 0, "length": 8}}])
+            ),
+            None
+        );
+        assert_eq!(
+            RpcBinaryEncoding::for_request(
+                RpcRequest::GetProgramAccounts,
+                &json!(["11111111111111111111111111111111", {"encoding": "jsonParsed"}])
+            ),
+            None
+        );
+        assert_eq!(
+            RpcBinaryEncoding::for_request(RpcRequest::GetProgramAccounts, &json!(["1111"])),
+            None
+        );
+        assert_eq!(
+            RpcBinaryEncoding::for_request(RpcRequest::GetSlot, &Value::Null),
+            None
+        );
+    }
+}
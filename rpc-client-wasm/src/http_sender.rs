@@ -0,0 +1,212 @@
+//! [`RpcSender`] over a pluggable [`HttpTransport`].
+
+#[cfg(feature = "reqwest-transport")]
+use crate::http_transport::ReqwestTransport;
+use {
+    crate::{http_transport::HttpTransport, rpc_sender::*},
+    async_trait::async_trait,
+    log::*,
+    solana_rpc_client_api::{
+        client_error::Result,
+        custom_error,
+        error_object::RpcErrorObject,
+        request::{RpcError, RpcRequest, RpcResponseErrorData},
+        response::RpcSimulateTransactionResult,
+    },
+    std::sync::atomic::{AtomicU64, Ordering},
+};
+
+pub struct HttpSender<T: HttpTransport> {
+    transport: T,
+    url: String,
+    request_id: AtomicU64,
+}
+
+#[cfg(feature = "reqwest-transport")]
+impl HttpSender<ReqwestTransport> {
+    /// Create an HTTP RPC sender using `reqwest`.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899, as in
+    /// "http://localhost:8899".
+    pub fn new<U: ToString>(url: U) -> Self {
+        Self::new_with_transport(url, ReqwestTransport::new())
+    }
+}
+
+/// [`RpcSender`] over HTTP.
+///
+/// Requests rejected with "429 Too Many Requests" are not retried, as there is
+/// no timer that works on every wasm runtime; the error is returned instead.
+impl<T: HttpTransport> HttpSender<T> {
+    /// Create an HTTP RPC sender that posts requests with `transport`.
+    pub fn new_with_transport<U: ToString>(url: U, transport: T) -> Self {
+        Self {
+            transport,
+            url: url.to_string(),
+            request_id: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: HttpTransport> RpcSender for HttpSender<T> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let request_json = request.build_request_json(request_id, params).to_string();
+
+        let response = self.transport.post_json(&self.url, request_json).await?;
+        if !response.is_success() {
+            return Err(RpcError::RpcRequestError(format!(
+                "HTTP status {} from {}: {}",
+                response.status, self.url, response.body
+            ))
+            .into());
+        }
+
+        let mut json = serde_json::from_str::<serde_json::Value>(&response.body)?;
+        if json["error"].is_object() {
+            return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
+                Ok(rpc_error_object) => {
+                    let data =
+                        rpc_response_error_data(rpc_error_object.code, &json["error"]["data"]);
+                    Err(RpcError::RpcResponseError {
+                        code: rpc_error_object.code,
+                        message: rpc_error_object.message,
+                        data,
+                    }
+                    .into())
+                }
+                Err(err) => Err(RpcError::RpcRequestError(format!(
+                    "Failed to deserialize RPC error response: {} [{}]",
+                    serde_json::to_string(&json["error"]).unwrap(),
+                    err
+                ))
+                .into()),
+            };
+        }
+        Ok(json["result"].take())
+    }
+
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+}
+
+fn rpc_response_error_data(code: i64, data: &serde_json::Value) -> RpcResponseErrorData {
+    match code {
+        custom_error::JSON_RPC_SERVER_ERROR_SEND_TRANSACTION_PREFLIGHT_FAILURE => {
+            match serde_json::from_value::<RpcSimulateTransactionResult>(data.clone()) {
+                Ok(data) => RpcResponseErrorData::SendTransactionPreflightFailure(data),
+                Err(err) => {
+                    debug!(
+                        "Failed to deserialize RpcSimulateTransactionResult: {:?}",
+                        err
+                    );
+                    RpcResponseErrorData::Empty
+                }
+            }
+        }
+        custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY => {
+            match serde_json::from_value::<custom_error::NodeUnhealthyErrorData>(data.clone()) {
+                Ok(custom_error::NodeUnhealthyErrorData { num_slots_behind }) => {
+                    RpcResponseErrorData::NodeUnhealthy { num_slots_behind }
+                }
+                Err(_err) => RpcResponseErrorData::Empty,
+            }
+        }
+        _ => RpcResponseErrorData::Empty,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::http_transport::HttpResponse, assert_matches::assert_matches,
+        serde_json::json, solana_rpc_client_api::client_error::ErrorKind, std::sync::Mutex,
+    };
+
+    struct FixedTransport {
+        response: HttpResponse,
+        requests: Mutex<Vec<String>>,
+    }
+
+    impl FixedTransport {
+        fn new(status: u16, body: serde_json::Value) -> Self {
+            Self {
+                response: HttpResponse {
+                    status,
+                    body: body.to_string(),
+                },
+                requests: Mutex::default(),
+            }
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl HttpTransport for FixedTransport {
+        async fn post_json(&self, _url: &str, body: String) -> Result<HttpResponse> {
+            self.requests.lock().unwrap().push(body);
+            Ok(self.response.clone())
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_http_sender_result() {
+        let transport = FixedTransport::new(200, json!({"jsonrpc": "2.0", "result": 42, "id": 0}));
+        let http_sender = HttpSender::new_with_transport("http://localhost:8899", transport);
+        let result = http_sender
+            .send(RpcRequest::GetSlot, serde_json::Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(result, json!(42));
+
+        let requests = http_sender.transport.requests.lock().unwrap();
+        let request: serde_json::Value = serde_json::from_str(&requests[0]).unwrap();
+        assert_eq!(request["method"], "getSlot");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_http_sender_errors() {
+        let transport = FixedTransport::new(
+            200,
+            json!({
+                "jsonrpc": "2.0",
+                "error": {
+                    "code": custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                    "message": "Node is behind by 42 slots",
+                    "data": {"numSlotsBehind": 42},
+                },
+                "id": 0,
+            }),
+        );
+        let http_sender = HttpSender::new_with_transport("http://localhost:8899", transport);
+        let err = http_sender
+            .send(RpcRequest::GetHealth, serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err.kind(),
+            ErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::NodeUnhealthy {
+                    num_slots_behind: Some(42)
+                },
+                ..
+            })
+        );
+
+        let transport = FixedTransport::new(429, json!("Too many requests"));
+        let http_sender = HttpSender::new_with_transport("http://localhost:8899", transport);
+        let err = http_sender
+            .send(RpcRequest::GetSlot, serde_json::Value::Null)
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err.kind(),
+            ErrorKind::RpcError(RpcError::RpcRequestError(_))
+        );
+    }
+}
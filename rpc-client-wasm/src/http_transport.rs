@@ -0,0 +1,81 @@
+//! The HTTP layer underneath [`HttpSender`](crate::http_sender::HttpSender).
+#[cfg(feature = "reqwest-transport")]
+use reqwest::header::{self, CONTENT_TYPE};
+use {async_trait::async_trait, solana_rpc_client_api::client_error::Result};
+
+/// The status and body of an HTTP response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Posts JSON-RPC requests over HTTP.
+///
+/// Implement this to send requests with an HTTP client that isn't `reqwest`,
+/// such as the `fetch` binding of a specific JavaScript runtime.
+#[async_trait(?Send)]
+pub trait HttpTransport {
+    /// POST `body`, a JSON document, to `url`.
+    async fn post_json(&self, url: &str, body: String) -> Result<HttpResponse>;
+}
+
+/// [`HttpTransport`] built on `reqwest`, which uses `fetch` when compiled for
+/// wasm32.
+#[cfg(feature = "reqwest-transport")]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "reqwest-transport")]
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        let mut default_headers = header::HeaderMap::new();
+        default_headers.append(
+            header::HeaderName::from_static("solana-client"),
+            header::HeaderValue::from_str(
+                format!("rust-wasm/{}", solana_version::Version::default()).as_str(),
+            )
+            .unwrap(),
+        );
+        let client = reqwest::Client::builder()
+            .default_headers(default_headers)
+            .build()
+            .expect("build rpc client");
+        Self::new_with_client(client)
+    }
+
+    pub fn new_with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[cfg(feature = "reqwest-transport")]
+impl Default for ReqwestTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "reqwest-transport")]
+#[async_trait(?Send)]
+impl HttpTransport for ReqwestTransport {
+    async fn post_json(&self, url: &str, body: String) -> Result<HttpResponse> {
+        let response = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?;
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+        Ok(HttpResponse { status, body })
+    }
+}
@@ -0,0 +1,14 @@
+#![allow(clippy::integer_arithmetic)]
+//! A lean, typed asynchronous client for the Solana JSON-RPC API.
+//!
+//! Unlike `solana-rpc-client`, this crate doesn't depend on `tokio` or on
+//! blocking HTTP, so it compiles for `wasm32-unknown-unknown` and can be used
+//! from browsers and edge runtimes. Requests and responses are the types
+//! defined in `solana-rpc-client-api`, and the HTTP layer is pluggable through
+//! the [`HttpTransport`](http_transport::HttpTransport) trait.
+
+pub mod http_sender;
+pub mod http_transport;
+pub mod mock_sender;
+pub mod rpc_client;
+pub mod rpc_sender;
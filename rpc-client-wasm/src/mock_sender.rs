@@ -0,0 +1,55 @@
+//! An [`RpcSender`] used for unit testing [`RpcClient`](crate::rpc_client::RpcClient).
+
+pub use solana_rpc_client_api::mock_response::PUBKEY;
+use {
+    crate::rpc_sender::*,
+    async_trait::async_trait,
+    serde_json::Value,
+    solana_rpc_client_api::{
+        client_error::Result, mock_response::mock_response, request::RpcRequest,
+    },
+    std::{collections::HashMap, sync::RwLock},
+};
+
+pub type Mocks = HashMap<RpcRequest, Value>;
+pub struct MockSender {
+    mocks: RwLock<Mocks>,
+    url: String,
+}
+
+/// An [`RpcSender`] used for unit testing [`RpcClient`](crate::rpc_client::RpcClient).
+///
+/// It returns the same responses as the `MockSender` of `solana-rpc-client`:
+/// the `url` constructor argument is a directive such as "succeeds" or
+/// "fails" that is interpreted by [`mock_response`], and custom responses can
+/// be configured by providing [`Mocks`] to [`MockSender::new_with_mocks`].
+impl MockSender {
+    pub fn new<U: ToString>(url: U) -> Self {
+        Self::new_with_mocks(url, Mocks::default())
+    }
+
+    pub fn new_with_mocks<U: ToString>(url: U, mocks: Mocks) -> Self {
+        Self {
+            url: url.to_string(),
+            mocks: RwLock::new(mocks),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl RpcSender for MockSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        if let Some(value) = self.mocks.write().unwrap().remove(&request) {
+            return Ok(value);
+        }
+        mock_response(&self.url, request, params)
+    }
+
+    fn url(&self) -> String {
+        format!("MockSender: {}", self.url)
+    }
+}
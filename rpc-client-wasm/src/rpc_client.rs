@@ -0,0 +1,881 @@
+//! Communication with a Solana node over RPC from WebAssembly.
+//!
+//! [`RpcClient`] is a typed asynchronous client that covers the RPC methods
+//! most browser and edge applications need. Any other method can be called
+//! with the generic [`send`](RpcClient::send) method and the request and
+//! response types of `solana-rpc-client-api`.
+
+#[cfg(feature = "reqwest-transport")]
+use crate::http_transport::ReqwestTransport;
+pub use crate::mock_sender::Mocks;
+use {
+    crate::{
+        http_sender::HttpSender, http_transport::HttpTransport, mock_sender::MockSender,
+        rpc_sender::*,
+    },
+    bincode::serialize,
+    serde::Serialize,
+    serde_json::{json, Value},
+    solana_account_decoder::{UiAccount, UiAccountEncoding},
+    solana_rpc_client_api::{
+        client_error::{
+            Error as ClientError, ErrorKind as ClientErrorKind, Result as ClientResult,
+        },
+        config::{
+            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcRequestAirdropConfig,
+            RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+        },
+        request::{RpcError, RpcRequest},
+        response::*,
+    },
+    solana_sdk::{
+        account::Account,
+        clock::Slot,
+        commitment_config::CommitmentConfig,
+        epoch_info::EpochInfo,
+        hash::Hash,
+        message::{v0, Message as LegacyMessage},
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::{self, Transaction, VersionedTransaction},
+    },
+    solana_transaction_status::{
+        EncodedConfirmedTransactionWithStatusMeta, TransactionStatus, UiTransactionEncoding,
+    },
+    std::str::FromStr,
+};
+
+/// Trait used to add support for versioned messages to RPC APIs while
+/// retaining backwards compatibility
+pub trait SerializableMessage: Serialize {}
+impl SerializableMessage for LegacyMessage {}
+impl SerializableMessage for v0::Message {}
+
+/// Trait used to add support for versioned transactions to RPC APIs while
+/// retaining backwards compatibility
+pub trait SerializableTransaction: Serialize {
+    fn get_signature(&self) -> &Signature;
+}
+impl SerializableTransaction for Transaction {
+    fn get_signature(&self) -> &Signature {
+        &self.signatures[0]
+    }
+}
+impl SerializableTransaction for VersionedTransaction {
+    fn get_signature(&self) -> &Signature {
+        &self.signatures[0]
+    }
+}
+
+/// A client of a remote Solana node that compiles for WebAssembly.
+///
+/// `RpcClient` communicates with a Solana node over JSON-RPC, sending
+/// requests through an [`RpcSender`]: usually an [`HttpSender`] over a
+/// pluggable [`HttpTransport`], or a [`MockSender`] in tests.
+///
+/// Unlike `solana_rpc_client::nonblocking::rpc_client::RpcClient` it doesn't
+/// map requests and commitment levels for nodes older than 1.9, and it
+/// doesn't wait on confirmations, as there is no portable timer on wasm32.
+/// Its futures are not `Send`.
+pub struct RpcClient {
+    sender: Box<dyn RpcSender>,
+    commitment_config: CommitmentConfig,
+}
+
+impl RpcClient {
+    /// Create an `RpcClient` from an [`RpcSender`] and a default commitment.
+    pub fn new_sender<T: RpcSender + 'static>(
+        sender: T,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self {
+            sender: Box::new(sender),
+            commitment_config,
+        }
+    }
+
+    /// Create an HTTP `RpcClient` that sends requests with `reqwest`.
+    ///
+    /// The URL is an HTTP URL, usually for port 8899, as in
+    /// "http://localhost:8899". The client has a default [commitment level]
+    /// of [`Finalized`](solana_sdk::commitment_config::CommitmentLevel::Finalized).
+    ///
+    /// [commitment level]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    #[cfg(feature = "reqwest-transport")]
+    pub fn new(url: String) -> Self {
+        Self::new_with_commitment(url, CommitmentConfig::default())
+    }
+
+    /// Create an HTTP `RpcClient` with a specified commitment level that
+    /// sends requests with `reqwest`.
+    #[cfg(feature = "reqwest-transport")]
+    pub fn new_with_commitment(url: String, commitment_config: CommitmentConfig) -> Self {
+        Self::new_with_transport(url, ReqwestTransport::new(), commitment_config)
+    }
+
+    /// Create an HTTP `RpcClient` that posts requests with `transport`.
+    pub fn new_with_transport<T: HttpTransport + 'static>(
+        url: String,
+        transport: T,
+        commitment_config: CommitmentConfig,
+    ) -> Self {
+        Self::new_sender(
+            HttpSender::new_with_transport(url, transport),
+            commitment_config,
+        )
+    }
+
+    /// Create a mock `RpcClient`.
+    ///
+    /// See the `MockSender` of `solana-rpc-client` for the meaning of `url`;
+    /// both clients return the same mock responses.
+    pub fn new_mock(url: String) -> Self {
+        Self::new_sender(MockSender::new(url), CommitmentConfig::default())
+    }
+
+    /// Create a mock `RpcClient` that returns the given responses to the
+    /// given requests.
+    pub fn new_mock_with_mocks(url: String, mocks: Mocks) -> Self {
+        Self::new_sender(
+            MockSender::new_with_mocks(url, mocks),
+            CommitmentConfig::default(),
+        )
+    }
+
+    /// Get the configured url of the client's sender
+    pub fn url(&self) -> String {
+        self.sender.url()
+    }
+
+    /// Get the configured default [commitment level][cl].
+    ///
+    /// [cl]: https://docs.solana.com/developing/clients/jsonrpc-api#configuring-state-commitment
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment_config
+    }
+
+    pub async fn get_version(&self) -> ClientResult<RpcVersionInfo> {
+        self.send(RpcRequest::GetVersion, Value::Null).await
+    }
+
+    pub async fn get_health(&self) -> ClientResult<()> {
+        self.send::<String>(RpcRequest::GetHealth, Value::Null)
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn get_slot(&self) -> ClientResult<Slot> {
+        self.get_slot_with_commitment(self.commitment()).await
+    }
+
+    pub async fn get_slot_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Slot> {
+        self.send(RpcRequest::GetSlot, json!([commitment_config]))
+            .await
+    }
+
+    pub async fn get_block_height(&self) -> ClientResult<u64> {
+        self.get_block_height_with_commitment(self.commitment())
+            .await
+    }
+
+    pub async fn get_block_height_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<u64> {
+        self.send(RpcRequest::GetBlockHeight, json!([commitment_config]))
+            .await
+    }
+
+    pub async fn get_epoch_info(&self) -> ClientResult<EpochInfo> {
+        self.get_epoch_info_with_commitment(self.commitment()).await
+    }
+
+    pub async fn get_epoch_info_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<EpochInfo> {
+        self.send(RpcRequest::GetEpochInfo, json!([commitment_config]))
+            .await
+    }
+
+    pub async fn get_balance(&self, pubkey: &Pubkey) -> ClientResult<u64> {
+        Ok(self
+            .get_balance_with_commitment(pubkey, self.commitment())
+            .await?
+            .value)
+    }
+
+    pub async fn get_balance_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<u64> {
+        self.send(
+            RpcRequest::GetBalance,
+            json!([pubkey.to_string(), commitment_config]),
+        )
+        .await
+    }
+
+    pub async fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.get_account_with_commitment(pubkey, self.commitment())
+            .await?
+            .value
+            .ok_or_else(|| RpcError::ForUser(format!("AccountNotFound: pubkey={pubkey}")).into())
+    }
+
+    pub async fn get_account_with_commitment(
+        &self,
+        pubkey: &Pubkey,
+        commitment_config: CommitmentConfig,
+    ) -> RpcResult<Option<Account>> {
+        // Plain base64, so that accounts can be decoded without zstd
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment_config),
+            data_slice: None,
+            min_context_slot: None,
+        };
+        self.get_account_with_config(pubkey, config).await
+    }
+
+    pub async fn get_account_with_config(
+        &self,
+        pubkey: &Pubkey,
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Option<Account>> {
+        let result_json: Value = self
+            .send(
+                RpcRequest::GetAccountInfo,
+                json!([pubkey.to_string(), config]),
+            )
+            .await
+            .map_err(|err| {
+                Into::<ClientError>::into(RpcError::ForUser(format!(
+                    "AccountNotFound: pubkey={pubkey}: {err}"
+                )))
+            })?;
+        if result_json.is_null() {
+            return Err(RpcError::ForUser(format!("AccountNotFound: pubkey={pubkey}")).into());
+        }
+        let Response {
+            context,
+            value: rpc_account,
+        } = serde_json::from_value::<Response<Option<UiAccount>>>(result_json)?;
+        Ok(Response {
+            context,
+            value: rpc_account.and_then(|rpc_account| rpc_account.decode()),
+        })
+    }
+
+    pub async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> ClientResult<Vec<Option<Account>>> {
+        Ok(self
+            .get_multiple_accounts_with_config(
+                pubkeys,
+                RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(self.commitment()),
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+            )
+            .await?
+            .value)
+    }
+
+    pub async fn get_multiple_accounts_with_config(
+        &self,
+        pubkeys: &[Pubkey],
+        config: RpcAccountInfoConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        let config = RpcAccountInfoConfig {
+            commitment: config.commitment.or_else(|| Some(self.commitment())),
+            ..config
+        };
+        let pubkeys: Vec<_> = pubkeys.iter().map(|pubkey| pubkey.to_string()).collect();
+        let Response {
+            context,
+            value: accounts,
+        } = self
+            .send::<Response<Vec<Option<UiAccount>>>>(
+                RpcRequest::GetMultipleAccounts,
+                json!([pubkeys, config]),
+            )
+            .await?;
+        Ok(Response {
+            context,
+            value: accounts
+                .into_iter()
+                .map(|rpc_account| rpc_account.and_then(|a| a.decode()))
+                .collect(),
+        })
+    }
+
+    pub async fn get_program_accounts_with_config(
+        &self,
+        pubkey: &Pubkey,
+        mut config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        if config.account_config.commitment.is_none() {
+            config.account_config.commitment = Some(self.commitment());
+        }
+        let accounts = self
+            .send::<OptionalContext<Vec<RpcKeyedAccount>>>(
+                RpcRequest::GetProgramAccounts,
+                json!([pubkey.to_string(), config]),
+            )
+            .await?
+            .parse_value();
+        parse_keyed_accounts(accounts, RpcRequest::GetProgramAccounts)
+    }
+
+    pub async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> ClientResult<u64> {
+        self.send(
+            RpcRequest::GetMinimumBalanceForRentExemption,
+            json!([data_len]),
+        )
+        .await
+    }
+
+    pub async fn get_latest_blockhash(&self) -> ClientResult<Hash> {
+        let (blockhash, _) = self
+            .get_latest_blockhash_with_commitment(self.commitment())
+            .await?;
+        Ok(blockhash)
+    }
+
+    pub async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        let RpcBlockhash {
+            blockhash,
+            last_valid_block_height,
+        } = self
+            .send::<Response<RpcBlockhash>>(RpcRequest::GetLatestBlockhash, json!([commitment]))
+            .await?
+            .value;
+        let blockhash = blockhash.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Hash".to_string()).into(),
+                RpcRequest::GetLatestBlockhash,
+            )
+        })?;
+        Ok((blockhash, last_valid_block_height))
+    }
+
+    pub async fn is_blockhash_valid(
+        &self,
+        blockhash: &Hash,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<bool> {
+        Ok(self
+            .send::<Response<bool>>(
+                RpcRequest::IsBlockhashValid,
+                json!([blockhash.to_string(), commitment]),
+            )
+            .await?
+            .value)
+    }
+
+    pub async fn get_fee_for_message(
+        &self,
+        message: &impl SerializableMessage,
+    ) -> ClientResult<u64> {
+        let serialized_encoded = serialize_and_encode(message, UiTransactionEncoding::Base64)?;
+        self.send::<Response<Option<u64>>>(
+            RpcRequest::GetFeeForMessage,
+            json!([serialized_encoded, self.commitment()]),
+        )
+        .await?
+        .value
+        .ok_or_else(|| ClientErrorKind::Custom("Invalid blockhash".to_string()).into())
+    }
+
+    pub async fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<transaction::Result<()>>> {
+        Ok(self
+            .get_signature_statuses(&[*signature])
+            .await?
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+            .filter(|status| status.satisfies_commitment(self.commitment()))
+            .map(|status| status.status))
+    }
+
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> RpcResult<Vec<Option<TransactionStatus>>> {
+        let signatures: Vec<_> = signatures.iter().map(|s| s.to_string()).collect();
+        self.send(RpcRequest::GetSignatureStatuses, json!([signatures]))
+            .await
+    }
+
+    pub async fn get_transaction(
+        &self,
+        signature: &Signature,
+        encoding: UiTransactionEncoding,
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta> {
+        self.send(
+            RpcRequest::GetTransaction,
+            json!([signature.to_string(), encoding]),
+        )
+        .await
+    }
+
+    pub async fn get_transaction_with_config(
+        &self,
+        signature: &Signature,
+        config: RpcTransactionConfig,
+    ) -> ClientResult<EncodedConfirmedTransactionWithStatusMeta> {
+        self.send(
+            RpcRequest::GetTransaction,
+            json!([signature.to_string(), config]),
+        )
+        .await
+    }
+
+    pub async fn send_transaction(
+        &self,
+        transaction: &impl SerializableTransaction,
+    ) -> ClientResult<Signature> {
+        self.send_transaction_with_config(
+            transaction,
+            RpcSendTransactionConfig {
+                preflight_commitment: Some(self.commitment().commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn send_transaction_with_config(
+        &self,
+        transaction: &impl SerializableTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base64);
+        let config = RpcSendTransactionConfig {
+            encoding: Some(encoding),
+            preflight_commitment: Some(config.preflight_commitment.unwrap_or_default()),
+            ..config
+        };
+        let serialized_encoded = serialize_and_encode(transaction, encoding)?;
+        let signature_base58_str: String = self
+            .send(
+                RpcRequest::SendTransaction,
+                json!([serialized_encoded, config]),
+            )
+            .await?;
+
+        let signature = signature_base58_str
+            .parse::<Signature>()
+            .map_err(|err| Into::<ClientError>::into(RpcError::ParseError(err.to_string())))?;
+        // A mismatching RPC response signature indicates an issue with the RPC node, and
+        // should not be passed along to confirmation methods.
+        if signature != *transaction.get_signature() {
+            Err(RpcError::RpcRequestError(format!(
+                "RPC node returned mismatched signature {:?}, expected {:?}",
+                signature,
+                transaction.get_signature()
+            ))
+            .into())
+        } else {
+            Ok(*transaction.get_signature())
+        }
+    }
+
+    pub async fn simulate_transaction(
+        &self,
+        transaction: &impl SerializableTransaction,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        self.simulate_transaction_with_config(
+            transaction,
+            RpcSimulateTransactionConfig {
+                commitment: Some(self.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn simulate_transaction_with_config(
+        &self,
+        transaction: &impl SerializableTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> RpcResult<RpcSimulateTransactionResult> {
+        let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Base64);
+        let config = RpcSimulateTransactionConfig {
+            encoding: Some(encoding),
+            commitment: Some(config.commitment.unwrap_or_default()),
+            ..config
+        };
+        let serialized_encoded = serialize_and_encode(transaction, encoding)?;
+        self.send(
+            RpcRequest::SimulateTransaction,
+            json!([serialized_encoded, config]),
+        )
+        .await
+    }
+
+    pub async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> ClientResult<Signature> {
+        self.request_airdrop_with_config(
+            pubkey,
+            lamports,
+            RpcRequestAirdropConfig {
+                commitment: Some(self.commitment()),
+                ..RpcRequestAirdropConfig::default()
+            },
+        )
+        .await
+    }
+
+    pub async fn request_airdrop_with_config(
+        &self,
+        pubkey: &Pubkey,
+        lamports: u64,
+        config: RpcRequestAirdropConfig,
+    ) -> ClientResult<Signature> {
+        let signature: String = self
+            .send(
+                RpcRequest::RequestAirdrop,
+                json!([pubkey.to_string(), lamports, config]),
+            )
+            .await?;
+        Signature::from_str(&signature).map_err(|err| {
+            ClientErrorKind::Custom(format!("signature deserialization failed: {err}")).into()
+        })
+    }
+
+    pub async fn send<T>(&self, request: RpcRequest, params: Value) -> ClientResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        assert!(params.is_array() || params.is_null());
+
+        let response = self
+            .sender
+            .send(request, params)
+            .await
+            .map_err(|err| err.into_with_request(request))?;
+        serde_json::from_value(response)
+            .map_err(|err| ClientError::new_with_request(err.into(), request))
+    }
+}
+
+fn serialize_and_encode<T>(input: &T, encoding: UiTransactionEncoding) -> ClientResult<String>
+where
+    T: serde::ser::Serialize,
+{
+    let serialized = serialize(input)
+        .map_err(|e| ClientErrorKind::Custom(format!("Serialization failed: {e}")))?;
+    let encoded = match encoding {
+        UiTransactionEncoding::Base58 => bs58::encode(serialized).into_string(),
+        UiTransactionEncoding::Base64 => base64::encode(serialized),
+        _ => {
+            return Err(ClientErrorKind::Custom(format!(
+                "unsupported encoding: {encoding}. Supported encodings: base58, base64"
+            ))
+            .into())
+        }
+    };
+    Ok(encoded)
+}
+
+fn parse_keyed_accounts(
+    accounts: Vec<RpcKeyedAccount>,
+    request: RpcRequest,
+) -> ClientResult<Vec<(Pubkey, Account)>> {
+    let mut pubkey_accounts: Vec<(Pubkey, Account)> = Vec::with_capacity(accounts.len());
+    for RpcKeyedAccount { pubkey, account } in accounts.into_iter() {
+        let pubkey = pubkey.parse().map_err(|_| {
+            ClientError::new_with_request(
+                RpcError::ParseError("Pubkey".to_string()).into(),
+                request,
+            )
+        })?;
+        pubkey_accounts.push((
+            pubkey,
+            account.decode().ok_or_else(|| {
+                ClientError::new_with_request(
+                    RpcError::ParseError("Account from rpc".to_string()).into(),
+                    request,
+                )
+            })?,
+        ));
+    }
+    Ok(pubkey_accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::mock_sender::PUBKEY,
+        assert_matches::assert_matches,
+        solana_sdk::{
+            instruction::InstructionError,
+            signature::{Keypair, Signer},
+            system_transaction,
+            transaction::TransactionError,
+        },
+    };
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_send_transaction() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let key = Keypair::new();
+        let to = solana_sdk::pubkey::new_rand();
+        let blockhash = Hash::default();
+        let tx = system_transaction::transfer(&key, &to, 50, blockhash);
+
+        let signature = rpc_client.send_transaction(&tx).await;
+        assert_eq!(signature.unwrap(), tx.signatures[0]);
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+
+        let signature = rpc_client.send_transaction(&tx).await;
+        assert!(signature.is_err());
+
+        // Test bad signature returned from rpc node
+        let rpc_client = RpcClient::new_mock("malicious".to_string());
+        let signature = rpc_client.send_transaction(&tx).await;
+        assert!(signature.is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_simulate_transaction() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let tx = system_transaction::transfer(
+            &Keypair::new(),
+            &solana_sdk::pubkey::new_rand(),
+            50,
+            Hash::default(),
+        );
+        let result = rpc_client.simulate_transaction(&tx).await.unwrap();
+        assert!(result.value.err.is_none());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_latest_blockhash() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let expected_blockhash: Hash = PUBKEY.parse().unwrap();
+
+        let blockhash = rpc_client
+            .get_latest_blockhash()
+            .await
+            .expect("blockhash ok");
+        assert_eq!(blockhash, expected_blockhash);
+        let (_, last_valid_block_height) = rpc_client
+            .get_latest_blockhash_with_commitment(CommitmentConfig::confirmed())
+            .await
+            .unwrap();
+        assert_eq!(last_valid_block_height, 1234);
+
+        let rpc_client = RpcClient::new_mock("fails".to_string());
+        assert!(rpc_client.get_latest_blockhash().await.is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_custom_request() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        let slot = rpc_client.get_slot().await.unwrap();
+        assert_eq!(slot, 0);
+
+        let custom_slot = rpc_client
+            .send::<Slot>(RpcRequest::Custom { method: "getSlot" }, Value::Null)
+            .await
+            .unwrap();
+
+        assert_eq!(slot, custom_slot);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_signature_status() {
+        let signature = Signature::default();
+
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        let status = rpc_client.get_signature_status(&signature).await.unwrap();
+        assert_eq!(status, Some(Ok(())));
+
+        let rpc_client = RpcClient::new_mock("sig_not_found".to_string());
+        let status = rpc_client.get_signature_status(&signature).await.unwrap();
+        assert_eq!(status, None);
+
+        let rpc_client = RpcClient::new_mock("account_in_use".to_string());
+        let status = rpc_client.get_signature_status(&signature).await.unwrap();
+        assert_eq!(status, Some(Err(TransactionError::AccountInUse)));
+
+        let rpc_client = RpcClient::new_mock("instruction_error".to_string());
+        let status = rpc_client.get_signature_status(&signature).await.unwrap();
+        assert_eq!(
+            status,
+            Some(Err(TransactionError::InstructionError(
+                0,
+                InstructionError::UninitializedAccount
+            )))
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_node_state() {
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+
+        assert_eq!(rpc_client.get_block_height().await.unwrap(), 1234);
+        assert_eq!(rpc_client.get_epoch_info().await.unwrap().absolute_slot, 34);
+        assert_eq!(
+            rpc_client.get_balance(&Pubkey::new_unique()).await.unwrap(),
+            50
+        );
+        assert_eq!(
+            rpc_client
+                .get_minimum_balance_for_rent_exemption(100)
+                .await
+                .unwrap(),
+            20
+        );
+        assert!(rpc_client
+            .get_version()
+            .await
+            .unwrap()
+            .solana_core
+            .starts_with(&solana_version::Version::default().to_string()));
+        assert_eq!(
+            rpc_client
+                .request_airdrop(&Pubkey::new_unique(), 42)
+                .await
+                .unwrap(),
+            Signature::new(&[8; 64])
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_account() {
+        let pubkey = Pubkey::new_unique();
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        assert_matches!(
+            rpc_client.get_account(&pubkey).await.unwrap_err().kind(),
+            ClientErrorKind::RpcError(RpcError::ForUser(_))
+        );
+        assert_eq!(
+            rpc_client
+                .get_multiple_accounts(&[pubkey, Pubkey::new_unique()])
+                .await
+                .unwrap(),
+            vec![None, None]
+        );
+
+        let account = Account {
+            lamports: 1_000_000,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mocks: Mocks = [(
+            RpcRequest::GetAccountInfo,
+            serde_json::to_value(Response {
+                context: RpcResponseContext {
+                    slot: 1,
+                    api_version: None,
+                },
+                value: UiAccount::encode(&pubkey, &account, UiAccountEncoding::Base64, None, None),
+            })
+            .unwrap(),
+        )]
+        .into_iter()
+        .collect();
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        assert_eq!(rpc_client.get_account(&pubkey).await.unwrap(), account);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_get_program_accounts_with_config() {
+        let program_id = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: 1_000_000,
+            data: vec![],
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let keyed_account = RpcKeyedAccount {
+            pubkey: pubkey.to_string(),
+            account: UiAccount::encode(&pubkey, &account, UiAccountEncoding::Base64, None, None),
+        };
+        let expected_result = vec![(pubkey, account)];
+        let config = RpcProgramAccountsConfig {
+            filters: None,
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: None,
+                commitment: None,
+                min_context_slot: None,
+            },
+            with_context: None,
+        };
+        // Test: without context
+        {
+            let mocks: Mocks = [(
+                RpcRequest::GetProgramAccounts,
+                serde_json::to_value(OptionalContext::NoContext(vec![keyed_account.clone()]))
+                    .unwrap(),
+            )]
+            .into_iter()
+            .collect();
+            let rpc_client = RpcClient::new_mock_with_mocks("mock_client".to_string(), mocks);
+            let result = rpc_client
+                .get_program_accounts_with_config(&program_id, config.clone())
+                .await
+                .unwrap();
+            assert_eq!(expected_result, result);
+        }
+
+        // Test: with context
+        {
+            let mocks: Mocks = [(
+                RpcRequest::GetProgramAccounts,
+                serde_json::to_value(OptionalContext::Context(Response {
+                    context: RpcResponseContext {
+                        slot: 1,
+                        api_version: None,
+                    },
+                    value: vec![keyed_account],
+                }))
+                .unwrap(),
+            )]
+            .into_iter()
+            .collect();
+            let rpc_client = RpcClient::new_mock_with_mocks("mock_client".to_string(), mocks);
+            let result = rpc_client
+                .get_program_accounts_with_config(
+                    &program_id,
+                    RpcProgramAccountsConfig {
+                        with_context: Some(true),
+                        ..config
+                    },
+                )
+                .await
+                .unwrap();
+            assert_eq!(expected_result, result);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! A transport for RPC calls.
+use {
+    async_trait::async_trait,
+    solana_rpc_client_api::{client_error::Result, request::RpcRequest},
+};
+
+/// A transport for RPC calls.
+///
+/// `RpcSender` implements the underlying transport of requests to, and
+/// responses from, a Solana node, and is used primarily by [`RpcClient`].
+///
+/// Its futures are not required to be `Send`, as HTTP clients backed by the
+/// browser's `fetch` are not.
+///
+/// [`RpcClient`]: crate::rpc_client::RpcClient
+#[async_trait(?Send)]
+pub trait RpcSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value>;
+    fn url(&self) -> String;
+}
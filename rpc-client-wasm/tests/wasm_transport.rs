@@ -0,0 +1,41 @@
+//! Tests of the `reqwest` transport compiled for wasm32, where it posts requests with `fetch`.
+//!
+//! Run with `wasm-bindgen-test-runner` as the wasm32 test runner, see `ci/test-stable.sh`. Set
+//! `SOLANA_RPC_WASM_TEST_URL` at build time to also send requests to a running RPC node.
+#![cfg(all(target_arch = "wasm32", feature = "reqwest-transport"))]
+
+use {
+    solana_rpc_client_api::client_error::ErrorKind,
+    solana_rpc_client_wasm::{
+        http_transport::{HttpTransport, ReqwestTransport},
+        rpc_client::RpcClient,
+    },
+    wasm_bindgen_test::wasm_bindgen_test,
+};
+
+#[wasm_bindgen_test]
+async fn test_reqwest_transport_connection_error() {
+    // Nothing listens on port 1
+    let url = "http://127.0.0.1:1";
+    let err = ReqwestTransport::new()
+        .post_json(url, "{}".to_string())
+        .await
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Reqwest(_)));
+
+    let rpc_client = RpcClient::new(url.to_string());
+    let err = rpc_client.get_slot().await.unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Reqwest(_)));
+}
+
+#[wasm_bindgen_test]
+async fn test_reqwest_transport_rpc_node() {
+    let url = match option_env!("SOLANA_RPC_WASM_TEST_URL") {
+        Some(url) => url,
+        None => return,
+    };
+    let rpc_client = RpcClient::new(url.to_string());
+    let version = rpc_client.get_version().await.unwrap();
+    assert!(!version.solana_core.is_empty());
+    rpc_client.get_latest_blockhash().await.unwrap();
+}
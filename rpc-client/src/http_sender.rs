@@ -4,18 +4,32 @@ use {
     crate::rpc_sender::*,
     async_trait::async_trait,
     log::*,
+    prost::Message,
     reqwest::{
         self,
-        header::{self, CONTENT_TYPE, RETRY_AFTER},
+        header::{self, ACCEPT, ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE, RETRY_AFTER},
         StatusCode,
     },
+    serde_json::json,
+    solana_account_decoder::{UiAccount, UiAccountEncoding},
     solana_rpc_client_api::{
         client_error::Result,
+        config::{
+            RpcAccountInfoConfig, RpcBlockConfig, RpcEncodingConfigWrapper,
+            RpcProgramAccountsConfig,
+        },
         custom_error,
         error_object::RpcErrorObject,
         request::{RpcError, RpcRequest, RpcResponseErrorData},
-        response::RpcSimulateTransactionResult,
+        response::{RpcKeyedAccount, RpcSimulateTransactionResult},
+        response_encoding::{
+            RpcBinaryEncoding, RpcBincodeKeyedAccounts, RpcBincodeMultipleAccounts,
+            RpcContentEncoding, JSON_CONTENT_TYPE,
+        },
     },
+    solana_sdk::pubkey::Pubkey,
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::{BlockEncodingOptions, ConfirmedBlock, UiTransactionEncoding},
     std::{
         sync::{
             atomic::{AtomicU64, Ordering},
@@ -26,6 +40,9 @@ use {
     tokio::time::sleep,
 };
 
+/// Compressions accepted in responses; reqwest decodes all but zstd
+const ACCEPT_ENCODING_VALUE: &str = "zstd, gzip, br, deflate";
+
 pub struct HttpSender {
     client: Arc<reqwest::Client>,
     url: String,
@@ -117,7 +134,15 @@ impl RpcSender for HttpSender {
         let mut stats_updater = StatsUpdater::new(&self.stats);
 
         let request_id = self.request_id.fetch_add(1, Ordering::Relaxed);
+        let binary_encoding = RpcBinaryEncoding::for_request(request, &params);
+        let binary_params = binary_encoding.map(|_| params.clone());
         let request_json = request.build_request_json(request_id, params).to_string();
+        let accept = match binary_encoding {
+            Some(binary_encoding) => {
+                format!("{JSON_CONTENT_TYPE}, {}", binary_encoding.content_type())
+            }
+            None => JSON_CONTENT_TYPE.to_string(),
+        };
 
         let mut too_many_requests_retries = 5;
         loop {
@@ -126,7 +151,9 @@ impl RpcSender for HttpSender {
                 let request_json = request_json.clone();
                 client
                     .post(&self.url)
-                    .header(CONTENT_TYPE, "application/json")
+                    .header(CONTENT_TYPE, JSON_CONTENT_TYPE)
+                    .header(ACCEPT, &accept)
+                    .header(ACCEPT_ENCODING, ACCEPT_ENCODING_VALUE)
                     .body(request_json)
                     .send()
                     .await
@@ -160,7 +187,31 @@ impl RpcSender for HttpSender {
                 return Err(response.error_for_status().unwrap_err().into());
             }
 
-            let mut json = response.json::<serde_json::Value>().await?;
+            let header_value = |name| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+                    .unwrap_or_default()
+            };
+            let content_type = header_value(CONTENT_TYPE);
+            let content_encoding = header_value(CONTENT_ENCODING);
+            let mut body = response.bytes().await?.to_vec();
+            // reqwest decompresses gzip, brotli and deflate itself
+            if RpcContentEncoding::from_content_encoding(&content_encoding)
+                == Some(RpcContentEncoding::Zstd)
+            {
+                body = zstd::decode_all(body.as_slice())?;
+            }
+            if let (Some(binary_encoding), Some(params)) = (
+                RpcBinaryEncoding::from_content_type(&content_type),
+                &binary_params,
+            ) {
+                return decode_binary_result(binary_encoding, request, params, &body);
+            }
+
+            let mut json = serde_json::from_slice::<serde_json::Value>(&body)?;
             if json["error"].is_object() {
                 return match serde_json::from_value::<RpcErrorObject>(json["error"].clone()) {
                     Ok(rpc_error_object) => {
@@ -209,9 +260,99 @@ impl RpcSender for HttpSender {
     }
 }
 
+/// Turn a binary response into the `result` of the equivalent JSON response
+fn decode_binary_result(
+    binary_encoding: RpcBinaryEncoding,
+    request: RpcRequest,
+    params: &serde_json::Value,
+    body: &[u8],
+) -> Result<serde_json::Value> {
+    let parse_error = |err: String| RpcError::ParseError(format!("{binary_encoding:?}: {err}"));
+    let config = params.get(1).cloned().unwrap_or_default();
+    match (binary_encoding, request) {
+        (RpcBinaryEncoding::Protobuf, RpcRequest::GetBlock) => {
+            let config =
+                serde_json::from_value::<Option<RpcEncodingConfigWrapper<RpcBlockConfig>>>(config)?
+                    .map(|config| config.convert_to_current())
+                    .unwrap_or_default();
+            let confirmed_block = generated::ConfirmedBlock::decode(body)
+                .map_err(|err| parse_error(err.to_string()))?;
+            let confirmed_block = ConfirmedBlock::try_from(confirmed_block)
+                .map_err(|err| parse_error(err.to_string()))?;
+            let encoded_block = confirmed_block
+                .encode_with_options(
+                    config.encoding.unwrap_or(UiTransactionEncoding::Json),
+                    BlockEncodingOptions {
+                        transaction_details: config.transaction_details.unwrap_or_default(),
+                        show_rewards: config.rewards.unwrap_or(true),
+                        max_supported_transaction_version: config.max_supported_transaction_version,
+                    },
+                )
+                .map_err(|err| parse_error(err.to_string()))?;
+            Ok(serde_json::to_value(encoded_block)?)
+        }
+        (RpcBinaryEncoding::Bincode, RpcRequest::GetProgramAccounts) => {
+            let config = serde_json::from_value::<Option<RpcProgramAccountsConfig>>(config)?
+                .unwrap_or_default();
+            let encoding = config
+                .account_config
+                .encoding
+                .unwrap_or(UiAccountEncoding::Binary);
+            let RpcBincodeKeyedAccounts {
+                slot,
+                api_version,
+                accounts,
+            } = bincode::deserialize(body).map_err(|err| parse_error(err.to_string()))?;
+            let accounts = accounts
+                .into_iter()
+                .map(|(pubkey, account)| RpcKeyedAccount {
+                    pubkey: pubkey.to_string(),
+                    account: UiAccount::encode(&pubkey, &account, encoding, None, None),
+                })
+                .collect::<Vec<_>>();
+            Ok(if config.with_context.unwrap_or_default() {
+                json!({
+                    "context": {"slot": slot, "apiVersion": api_version},
+                    "value": accounts,
+                })
+            } else {
+                serde_json::to_value(accounts)?
+            })
+        }
+        (RpcBinaryEncoding::Bincode, RpcRequest::GetMultipleAccounts) => {
+            let config =
+                serde_json::from_value::<Option<RpcAccountInfoConfig>>(config)?.unwrap_or_default();
+            let encoding = config.encoding.unwrap_or(UiAccountEncoding::Base64);
+            let RpcBincodeMultipleAccounts {
+                slot,
+                api_version,
+                accounts,
+            } = bincode::deserialize(body).map_err(|err| parse_error(err.to_string()))?;
+            // The pubkey is only used to parse accounts, which is never requested here
+            let accounts = accounts
+                .into_iter()
+                .map(|account| {
+                    account.map(|account| {
+                        UiAccount::encode(&Pubkey::default(), &account, encoding, None, None)
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(json!({
+                "context": {"slot": slot, "apiVersion": api_version},
+                "value": accounts,
+            }))
+        }
+        _ => Err(parse_error(format!("unexpected response to {request}")).into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {
+        super::*,
+        solana_sdk::{account::Account, hash::Hash},
+        solana_transaction_status::TransactionDetails,
+    };
 
     #[tokio::test(flavor = "multi_thread")]
     async fn http_sender_on_tokio_multi_thread() {
@@ -228,4 +369,89 @@ mod tests {
             .send(RpcRequest::GetVersion, serde_json::Value::Null)
             .await;
     }
+
+    #[test]
+    fn test_decode_binary_result() {
+        let confirmed_block = ConfirmedBlock {
+            previous_blockhash: Hash::new_unique().to_string(),
+            blockhash: Hash::new_unique().to_string(),
+            parent_slot: 41,
+            transactions: vec![],
+            rewards: vec![],
+            block_time: Some(1_234),
+            block_height: Some(40),
+        };
+        let body = generated::ConfirmedBlock::from(confirmed_block.clone()).encode_to_vec();
+        let params = json!([42, {"encoding": "base64", "rewards": false}]);
+        let result = decode_binary_result(
+            RpcBinaryEncoding::Protobuf,
+            RpcRequest::GetBlock,
+            &params,
+            &body,
+        )
+        .unwrap();
+        let expected = confirmed_block
+            .encode_with_options(
+                UiTransactionEncoding::Base64,
+                BlockEncodingOptions {
+                    transaction_details: TransactionDetails::Full,
+                    show_rewards: false,
+                    max_supported_transaction_version: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(result, serde_json::to_value(expected).unwrap());
+
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: 42,
+            data: vec![1, 2, 3],
+            ..Account::default()
+        };
+        let body = bincode::serialize(&RpcBincodeKeyedAccounts {
+            slot: 7,
+            api_version: "1.15.0".to_string(),
+            accounts: vec![(pubkey, account.clone())],
+        })
+        .unwrap();
+        let params = json!([pubkey.to_string(), {"encoding": "base64", "withContext": true}]);
+        let result = decode_binary_result(
+            RpcBinaryEncoding::Bincode,
+            RpcRequest::GetProgramAccounts,
+            &params,
+            &body,
+        )
+        .unwrap();
+        assert_eq!(result["context"]["slot"], 7);
+        assert_eq!(result["value"][0]["pubkey"], pubkey.to_string());
+        assert_eq!(
+            result["value"][0]["account"]["data"],
+            json!([base64::encode([1, 2, 3]), "base64"])
+        );
+
+        let body = bincode::serialize(&RpcBincodeMultipleAccounts {
+            slot: 7,
+            api_version: "1.15.0".to_string(),
+            accounts: vec![None, Some(account)],
+        })
+        .unwrap();
+        let result = decode_binary_result(
+            RpcBinaryEncoding::Bincode,
+            RpcRequest::GetMultipleAccounts,
+            &json!([[], {"encoding": "base64"}]),
+            &body,
+        )
+        .unwrap();
+        assert_eq!(result["value"][0], serde_json::Value::Null);
+        assert_eq!(result["value"][1]["lamports"], 42);
+        assert_eq!(result["value"][1]["space"], 3);
+
+        assert!(decode_binary_result(
+            RpcBinaryEncoding::Bincode,
+            RpcRequest::GetMultipleAccounts,
+            &json!([[], {"encoding": "base64"}]),
+            &[0xff],
+        )
+        .is_err());
+    }
 }
@@ -8,8 +8,5 @@ pub mod rpc_sender;
 pub mod spinner;
 
 pub mod mock_sender_for_cli {
-    /// Magic `SIGNATURE` value used by `solana-cli` unit tests.
-    /// Please don't use this constant.
-    pub const SIGNATURE: &str =
-        "43yNSFC6fYTuPgTNFFhF4axw7AfWxB2BPdurme8yrsWEYwm8299xh8n6TAHjGymiSub1XtyxTNyd9GBfY2hxoBw8";
+    pub use solana_rpc_client_api::mock_response::SIGNATURE;
 }
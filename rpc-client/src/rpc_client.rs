@@ -2951,6 +2951,45 @@ impl RpcClient {
         self.invoke((self.rpc_client.as_ref()).get_recent_prioritization_fees(addresses))
     }
 
+    /// Returns percentiles of the prioritization fees paid in recent blocks.
+    ///
+    /// The estimate covers the accounts listed in the config, plus the writable
+    /// accounts of the serialized transaction if one is provided. Each
+    /// percentile is the highest among those of all transactions in the
+    /// blocks and those of the transactions locking each account as writable.
+    ///
+    /// # RPC Reference
+    ///
+    /// This method corresponds directly to the [`getPriorityFeeEstimate`] RPC method.
+    ///
+    /// [`getPriorityFeeEstimate`]: https://docs.solana.com/developing/clients/jsonrpc-api#getpriorityfeeestimate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_rpc_client_api::{
+    /// #     client_error::Error,
+    /// #     config::RpcPriorityFeeEstimateConfig,
+    /// # };
+    /// # use solana_rpc_client::rpc_client::RpcClient;
+    /// # use solana_sdk::signature::{Keypair, Signer};
+    /// # let rpc_client = RpcClient::new_mock("succeeds".to_string());
+    /// # let alice = Keypair::new();
+    /// let config = RpcPriorityFeeEstimateConfig {
+    ///     account_keys: Some(vec![alice.pubkey().to_string()]),
+    ///     num_blocks: Some(20),
+    ///     ..RpcPriorityFeeEstimateConfig::default()
+    /// };
+    /// let estimate = rpc_client.get_priority_fee_estimate(config)?;
+    /// # Ok::<(), Error>(())
+    /// ```
+    pub fn get_priority_fee_estimate(
+        &self,
+        config: RpcPriorityFeeEstimateConfig,
+    ) -> ClientResult<RpcPriorityFeeEstimate> {
+        self.invoke((self.rpc_client.as_ref()).get_priority_fee_estimate(config))
+    }
+
     /// Returns the identity pubkey for the current node.
     ///
     /// # RPC Reference
@@ -3,8 +3,14 @@ use {
         nonblocking::tpu_client::{LeaderTpuService, TpuClient},
         tpu_client::TpuClientConfig,
     },
+    solana_quic_client::QuicPool,
     solana_sdk::{clock::DEFAULT_MS_PER_SLOT, pubkey::Pubkey, system_transaction},
     solana_test_validator::TestValidatorGenesis,
+    solana_tpu_client::{
+        leader_send_stats::LeaderSendOutcome,
+        nonblocking::tpu_client::TpuClient as QuicTpuClient,
+        tpu_connection_cache::{TpuConnectionCache, DEFAULT_TPU_CONNECTION_POOL_SIZE},
+    },
     std::sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -45,6 +51,59 @@ async fn test_tpu_send_transaction() {
     tpu_client.shutdown().await;
 }
 
+#[tokio::test]
+async fn test_tpu_send_transaction_with_report() {
+    let (test_validator, mint_keypair) = TestValidatorGenesis::default().start_async().await;
+    let rpc_client = Arc::new(test_validator.get_async_rpc_client());
+    let connection_cache =
+        Arc::new(TpuConnectionCache::<QuicPool>::new(DEFAULT_TPU_CONNECTION_POOL_SIZE).unwrap());
+    let mut tpu_client = QuicTpuClient::new_with_connection_cache(
+        rpc_client.clone(),
+        &test_validator.rpc_pubsub_url(),
+        TpuClientConfig::default(),
+        connection_cache,
+    )
+    .await
+    .unwrap();
+
+    let recent_blockhash = rpc_client.get_latest_blockhash().await.unwrap();
+    let tx =
+        system_transaction::transfer(&mint_keypair, &Pubkey::new_unique(), 42, recent_blockhash);
+    let report = tpu_client.send_transaction_with_report(&tx).await;
+    assert!(report.accepted());
+    assert!(report.skipped_leaders.is_empty());
+    assert!(report.rpc_fallback_result.is_none());
+    let accepted = report
+        .leader_results
+        .iter()
+        .find(|result| result.outcome == LeaderSendOutcome::Accepted)
+        .unwrap();
+    assert!(accepted.error.is_none());
+    assert!(
+        tpu_client
+            .leader_send_stats()
+            .counts(&accepted.leader)
+            .unwrap()
+            .accepted
+            >= 1
+    );
+
+    let timeout = Duration::from_secs(5);
+    let now = Instant::now();
+    let signatures = vec![tx.signatures[0]];
+    loop {
+        assert!(now.elapsed() < timeout);
+        let statuses = rpc_client
+            .get_signature_statuses(&signatures)
+            .await
+            .unwrap();
+        if statuses.value.get(0).is_some() {
+            break;
+        }
+    }
+    tpu_client.shutdown().await;
+}
+
 #[tokio::test]
 async fn test_tpu_cache_slot_updates() {
     let (test_validator, _) = TestValidatorGenesis::default().start_async().await;
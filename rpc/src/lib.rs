@@ -9,12 +9,16 @@ pub mod rpc_completed_slots_service;
 pub mod rpc_health;
 pub mod rpc_pubsub;
 pub mod rpc_pubsub_service;
+mod rpc_response_encoding;
 pub mod rpc_service;
 pub mod rpc_subscription_tracker;
 pub mod rpc_subscriptions;
 pub mod transaction_notifier_interface;
 pub mod transaction_status_service;
 
+#[macro_use]
+extern crate lazy_static;
+
 #[macro_use]
 extern crate log;
 
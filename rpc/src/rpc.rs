@@ -20,7 +20,7 @@ use {
         cluster_info::ClusterInfo, legacy_contact_info::LegacyContactInfo as ContactInfo,
     },
     solana_ledger::{
-        blockstore::{Blockstore, SignatureInfosForAddress},
+        blockstore::{Blockstore, SignatureInfosForAddress, PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN},
         blockstore_db::BlockstoreError,
         blockstore_meta::{PerfSample, PerfSampleV1, PerfSampleV2},
         get_tmp_ledger_path,
@@ -41,6 +41,7 @@ use {
             MAX_RPC_VOTE_ACCOUNT_INFO_EPOCH_CREDITS_HISTORY, NUM_LARGEST_ACCOUNTS,
         },
         response::{Response as RpcResponse, *},
+        response_encoding::{RpcBincodeKeyedAccounts, RpcBincodeMultipleAccounts},
     },
     solana_runtime::{
         accounts::AccountAddressFilter,
@@ -51,12 +52,15 @@ use {
         inline_spl_token::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
         inline_spl_token_2022::{self, ACCOUNTTYPE_ACCOUNT},
         non_circulating_supply::calculate_non_circulating_supply,
-        prioritization_fee_cache::PrioritizationFeeCache,
+        prioritization_fee_cache::{
+            PrioritizationFeeCache, PrioritizationFeeEstimate, MAX_NUM_RECENT_BLOCKS,
+        },
         snapshot_config::SnapshotConfig,
         snapshot_utils,
+        transaction_priority_details::GetTransactionPriorityDetails,
     },
     solana_sdk::{
-        account::{AccountSharedData, ReadableAccount},
+        account::{Account, AccountSharedData, ReadableAccount},
         account_utils::StateMut,
         clock::{Slot, UnixTimestamp, MAX_RECENT_BLOCKHASHES},
         commitment_config::{CommitmentConfig, CommitmentLevel},
@@ -79,8 +83,11 @@ use {
         },
     },
     solana_send_transaction_service::{
-        send_transaction_service::{SendTransactionService, TransactionInfo},
+        send_transaction_service::{self, SendTransactionService, TransactionInfo},
         tpu_info::NullTpuInfo,
+        transaction_send_status::{
+            TransactionSendState, TransactionSendStatus, TransactionSendStatusCache,
+        },
     },
     solana_stake_program,
     solana_storage_bigtable::Error as StorageError,
@@ -110,6 +117,7 @@ use {
         },
         time::Duration,
     },
+    tokio::runtime::Runtime,
 };
 
 type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
@@ -117,6 +125,12 @@ type RpcCustomResult<T> = std::result::Result<T, RpcCustomError>;
 pub const MAX_REQUEST_BODY_SIZE: usize = 50 * (1 << 10); // 50kB
 pub const PERFORMANCE_SAMPLES_LIMIT: usize = 720;
 
+lazy_static! {
+    // Shared by the processors built for unit tests, which may be dropped from within an async
+    // test where dropping their own runtime would panic
+    static ref TEST_RUNTIME: Arc<Runtime> = Arc::new(Runtime::new().expect("Runtime"));
+}
+
 fn new_response<T>(bank: &Bank, value: T) -> RpcResponse<T> {
     RpcResponse {
         context: RpcResponseContext::new(bank.slot()),
@@ -138,6 +152,7 @@ fn is_finalized(
 pub struct JsonRpcConfig {
     pub enable_rpc_transaction_history: bool,
     pub enable_extended_tx_metadata_storage: bool,
+    pub enable_program_instruction_index: bool,
     pub faucet_addr: Option<SocketAddr>,
     pub health_check_slot_distance: u64,
     pub rpc_bigtable_config: Option<RpcBigtableConfig>,
@@ -149,6 +164,7 @@ pub struct JsonRpcConfig {
     pub obsolete_v1_7_api: bool,
     pub rpc_scan_and_fix_roots: bool,
     pub max_request_body_size: Option<usize>,
+    pub enable_response_encodings: bool,
 }
 
 impl JsonRpcConfig {
@@ -201,6 +217,8 @@ pub struct JsonRpcRequestProcessor {
     leader_schedule_cache: Arc<LeaderScheduleCache>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
     prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+    transaction_send_status_cache: Arc<TransactionSendStatusCache>,
+    runtime: Arc<Runtime>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
 
@@ -307,6 +325,7 @@ impl JsonRpcRequestProcessor {
         leader_schedule_cache: Arc<LeaderScheduleCache>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
         prioritization_fee_cache: Arc<PrioritizationFeeCache>,
+        runtime: Arc<Runtime>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (sender, receiver) = unbounded();
         (
@@ -328,6 +347,8 @@ impl JsonRpcRequestProcessor {
                 leader_schedule_cache,
                 max_complete_transaction_status_slot,
                 prioritization_fee_cache,
+                transaction_send_status_cache: Arc::<TransactionSendStatusCache>::default(),
+                runtime,
             },
             receiver,
         )
@@ -356,14 +377,19 @@ impl JsonRpcRequestProcessor {
         });
         let tpu_address = cluster_info.my_contact_info().tpu;
         let (sender, receiver) = unbounded();
-        SendTransactionService::new::<NullTpuInfo>(
+        let transaction_send_status_cache = Arc::<TransactionSendStatusCache>::default();
+        SendTransactionService::new_with_config::<NullTpuInfo>(
             tpu_address,
             &bank_forks,
             None,
             receiver,
             &connection_cache,
-            1000,
-            1,
+            send_transaction_service::Config {
+                retry_rate_ms: 1000,
+                leader_forward_count: 1,
+                ..send_transaction_service::Config::default()
+            },
+            transaction_send_status_cache.clone(),
         );
 
         Self {
@@ -396,9 +422,15 @@ impl JsonRpcRequestProcessor {
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
             prioritization_fee_cache: Arc::new(PrioritizationFeeCache::default()),
+            transaction_send_status_cache,
+            runtime: TEST_RUNTIME.clone(),
         }
     }
 
+    pub(crate) fn transaction_send_status_cache(&self) -> Arc<TransactionSendStatusCache> {
+        self.transaction_send_status_cache.clone()
+    }
+
     pub fn get_account_info(
         &self,
         pubkey: &Pubkey,
@@ -444,6 +476,24 @@ impl JsonRpcRequestProcessor {
         Ok(new_response(&bank, accounts))
     }
 
+    /// The accounts of `getMultipleAccounts`, unencoded, for bincode responses
+    pub fn get_raw_multiple_accounts(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        config: RpcContextConfig,
+    ) -> Result<RpcBincodeMultipleAccounts> {
+        let bank = self.get_bank_with_config(config)?;
+        let accounts = pubkeys
+            .iter()
+            .map(|pubkey| bank.get_account(pubkey).map(Account::from))
+            .collect();
+        Ok(RpcBincodeMultipleAccounts {
+            slot: bank.slot(),
+            api_version: RpcApiVersion::default().to_string(),
+            accounts,
+        })
+    }
+
     pub fn get_minimum_balance_for_rent_exemption(
         &self,
         data_len: usize,
@@ -457,7 +507,7 @@ impl JsonRpcRequestProcessor {
         &self,
         program_id: &Pubkey,
         config: Option<RpcAccountInfoConfig>,
-        mut filters: Vec<RpcFilterType>,
+        filters: Vec<RpcFilterType>,
         with_context: bool,
     ) -> Result<OptionalContext<Vec<RpcKeyedAccount>>> {
         let RpcAccountInfoConfig {
@@ -471,16 +521,7 @@ impl JsonRpcRequestProcessor {
             min_context_slot,
         })?;
         let encoding = encoding.unwrap_or(UiAccountEncoding::Binary);
-        optimize_filters(&mut filters);
-        let keyed_accounts = {
-            if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_owner(&bank, program_id, &owner, filters)?
-            } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
-                self.get_filtered_spl_token_accounts_by_mint(&bank, program_id, &mint, filters)?
-            } else {
-                self.get_filtered_program_accounts(&bank, program_id, filters)?
-            }
-        };
+        let keyed_accounts = self.get_keyed_program_accounts(&bank, program_id, filters)?;
         let accounts = if is_known_spl_token_id(program_id)
             && encoding == UiAccountEncoding::JsonParsed
         {
@@ -502,6 +543,45 @@ impl JsonRpcRequestProcessor {
         })
     }
 
+    /// The accounts of `getProgramAccounts`, unencoded, for bincode responses
+    pub fn get_raw_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: RpcContextConfig,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<RpcBincodeKeyedAccounts> {
+        let bank = self.get_bank_with_config(config)?;
+        let accounts = self
+            .get_keyed_program_accounts(&bank, program_id, filters)?
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey, Account::from(account)))
+            .collect();
+        Ok(RpcBincodeKeyedAccounts {
+            slot: bank.slot(),
+            api_version: RpcApiVersion::default().to_string(),
+            accounts,
+        })
+    }
+
+    fn get_keyed_program_accounts(
+        &self,
+        bank: &Arc<Bank>,
+        program_id: &Pubkey,
+        mut filters: Vec<RpcFilterType>,
+    ) -> Result<Vec<(Pubkey, AccountSharedData)>> {
+        optimize_filters(&mut filters);
+        let keyed_accounts = {
+            if let Some(owner) = get_spl_token_owner_filter(program_id, &filters) {
+                self.get_filtered_spl_token_accounts_by_owner(bank, program_id, &owner, filters)?
+            } else if let Some(mint) = get_spl_token_mint_filter(program_id, &filters) {
+                self.get_filtered_spl_token_accounts_by_mint(bank, program_id, &mint, filters)?
+            } else {
+                self.get_filtered_program_accounts(bank, program_id, filters)?
+            }
+        };
+        Ok(keyed_accounts)
+    }
+
     pub async fn get_inflation_reward(
         &self,
         addresses: Vec<Pubkey>,
@@ -1064,17 +1144,33 @@ impl JsonRpcRequestProcessor {
         slot: Slot,
         config: Option<RpcEncodingConfigWrapper<RpcBlockConfig>>,
     ) -> Result<Option<UiConfirmedBlock>> {
+        let config = config
+            .map(|config| config.convert_to_current())
+            .unwrap_or_default();
+        let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
+        let encoding_options = BlockEncodingOptions {
+            transaction_details: config.transaction_details.unwrap_or_default(),
+            show_rewards: config.rewards.unwrap_or(true),
+            max_supported_transaction_version: config.max_supported_transaction_version,
+        };
+        self.get_confirmed_block(slot, config.commitment)
+            .await?
+            .map(|confirmed_block| {
+                confirmed_block
+                    .encode_with_options(encoding, encoding_options)
+                    .map_err(|err| RpcCustomError::from(err).into())
+            })
+            .transpose()
+    }
+
+    /// The block at `slot`, as served by `getBlock` before it is encoded
+    pub async fn get_confirmed_block(
+        &self,
+        slot: Slot,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Option<ConfirmedBlock>> {
         if self.config.enable_rpc_transaction_history {
-            let config = config
-                .map(|config| config.convert_to_current())
-                .unwrap_or_default();
-            let encoding = config.encoding.unwrap_or(UiTransactionEncoding::Json);
-            let encoding_options = BlockEncodingOptions {
-                transaction_details: config.transaction_details.unwrap_or_default(),
-                show_rewards: config.rewards.unwrap_or(true),
-                max_supported_transaction_version: config.max_supported_transaction_version,
-            };
-            let commitment = config.commitment.unwrap_or_default();
+            let commitment = commitment.unwrap_or_default();
             check_is_at_least_confirmed(commitment)?;
 
             // Block is old enough to be finalized
@@ -1088,40 +1184,33 @@ impl JsonRpcRequestProcessor {
                 self.check_status_is_complete(slot)?;
                 let result = self.blockstore.get_rooted_block(slot, true);
                 self.check_blockstore_root(&result, slot)?;
-                let encode_block = |confirmed_block: ConfirmedBlock| -> Result<UiConfirmedBlock> {
-                    let mut encoded_block = confirmed_block
-                        .encode_with_options(encoding, encoding_options)
-                        .map_err(RpcCustomError::from)?;
+                let fix_genesis_block = |mut confirmed_block: ConfirmedBlock| {
                     if slot == 0 {
-                        encoded_block.block_time = Some(self.genesis_creation_time());
-                        encoded_block.block_height = Some(0);
+                        confirmed_block.block_time = Some(self.genesis_creation_time());
+                        confirmed_block.block_height = Some(0);
                     }
-                    Ok(encoded_block)
+                    confirmed_block
                 };
                 if result.is_err() {
                     if let Some(bigtable_ledger_storage) = &self.bigtable_ledger_storage {
                         let bigtable_result =
                             bigtable_ledger_storage.get_confirmed_block(slot).await;
                         self.check_bigtable_result(&bigtable_result)?;
-                        return bigtable_result.ok().map(encode_block).transpose();
+                        return Ok(bigtable_result.ok().map(fix_genesis_block));
                     }
                 }
                 self.check_slot_cleaned_up(&result, slot)?;
-                return result
-                    .ok()
-                    .map(ConfirmedBlock::from)
-                    .map(encode_block)
-                    .transpose();
+                return Ok(result.ok().map(ConfirmedBlock::from).map(fix_genesis_block));
             } else if commitment.is_confirmed() {
                 // Check if block is confirmed
                 let confirmed_bank = self.bank(Some(CommitmentConfig::confirmed()));
                 if confirmed_bank.status_cache_ancestors().contains(&slot) {
                     self.check_status_is_complete(slot)?;
                     let result = self.blockstore.get_complete_block(slot, true);
-                    return result
+                    return Ok(result
                         .ok()
                         .map(ConfirmedBlock::from)
-                        .map(|mut confirmed_block| -> Result<UiConfirmedBlock> {
+                        .map(|mut confirmed_block| {
                             if confirmed_block.block_time.is_none()
                                 || confirmed_block.block_height.is_none()
                             {
@@ -1136,12 +1225,8 @@ impl JsonRpcRequestProcessor {
                                     }
                                 }
                             }
-
-                            Ok(confirmed_block
-                                .encode_with_options(encoding, encoding_options)
-                                .map_err(RpcCustomError::from)?)
-                        })
-                        .transpose();
+                            confirmed_block
+                        }));
                 }
             }
         } else {
@@ -1680,6 +1765,81 @@ impl JsonRpcRequestProcessor {
         }
     }
 
+    pub async fn get_signatures_for_program_instruction(
+        &self,
+        program_id: Pubkey,
+        discriminator: Vec<u8>,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+        config: RpcContextConfig,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let commitment = config.commitment.unwrap_or_default();
+        check_is_at_least_confirmed(commitment)?;
+
+        if !self.config.enable_rpc_transaction_history {
+            return Err(RpcCustomError::TransactionHistoryNotAvailable.into());
+        }
+        if !self.config.enable_program_instruction_index {
+            return Err(RpcCustomError::ProgramInstructionIndexNotAvailable.into());
+        }
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let highest_slot = if commitment.is_confirmed() {
+            let confirmed_bank = self.get_bank_with_config(config)?;
+            confirmed_bank.slot()
+        } else {
+            let min_context_slot = config.min_context_slot.unwrap_or_default();
+            if highest_confirmed_root < min_context_slot {
+                return Err(RpcCustomError::MinContextSlotNotReached {
+                    context_slot: highest_confirmed_root,
+                }
+                .into());
+            }
+            highest_confirmed_root
+        };
+
+        // The index scan can walk many entries, keep it off the event loop
+        let blockstore = self.blockstore.clone();
+        let results = self
+            .runtime
+            .spawn_blocking(move || {
+                blockstore.get_confirmed_signatures_for_program_instruction(
+                    program_id,
+                    &discriminator,
+                    highest_slot,
+                    before,
+                    until,
+                    limit,
+                )
+            })
+            .await
+            .map_err(|_| Error::internal_error())?
+            .map_err(|err| Error::invalid_params(format!("{err}")))?;
+
+        Ok(results
+            .into_iter()
+            .map(|x| {
+                let mut item: RpcConfirmedTransactionStatusWithSignature = x.into();
+                if item.slot <= highest_confirmed_root {
+                    item.confirmation_status = Some(TransactionConfirmationStatus::Finalized);
+                } else {
+                    item.confirmation_status = Some(TransactionConfirmationStatus::Confirmed);
+                    if item.block_time.is_none() {
+                        let r_bank_forks = self.bank_forks.read().unwrap();
+                        item.block_time = r_bank_forks
+                            .get(item.slot)
+                            .map(|bank| bank.clock().unix_timestamp);
+                    }
+                }
+                item
+            })
+            .collect())
+    }
+
     pub async fn get_first_available_block(&self) -> Slot {
         let slot = self
             .blockstore
@@ -2169,6 +2329,39 @@ impl JsonRpcRequestProcessor {
             })
             .collect())
     }
+
+    fn get_priority_fee_estimate(
+        &self,
+        pubkeys: Vec<Pubkey>,
+        num_blocks: usize,
+    ) -> Result<RpcPriorityFeeEstimate> {
+        let PrioritizationFeeEstimate {
+            p25,
+            p50,
+            p75,
+            p90,
+            num_blocks,
+        } = self
+            .prioritization_fee_cache
+            .get_prioritization_fee_estimate(&pubkeys, num_blocks)
+            .unwrap_or_default();
+        Ok(RpcPriorityFeeEstimate {
+            p25,
+            p50,
+            p75,
+            p90,
+            num_blocks,
+        })
+    }
+
+    fn get_transaction_send_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<RpcTransactionSendStatus> {
+        self.transaction_send_status_cache
+            .get(signature)
+            .map(rpc_transaction_send_status_from_transaction_send_status)
+    }
 }
 
 fn optimize_filters(filters: &mut [RpcFilterType]) {
@@ -2210,6 +2403,36 @@ pub fn verify_pubkey(input: &str) -> Result<Pubkey> {
         .map_err(|e| Error::invalid_params(format!("Invalid param: {e:?}")))
 }
 
+pub(crate) fn verify_multiple_accounts_pubkeys(
+    config: &JsonRpcConfig,
+    pubkey_strs: Vec<String>,
+) -> Result<Vec<Pubkey>> {
+    let max_multiple_accounts = config
+        .max_multiple_accounts
+        .unwrap_or(MAX_MULTIPLE_ACCOUNTS);
+    if pubkey_strs.len() > max_multiple_accounts {
+        return Err(Error::invalid_params(format!(
+            "Too many inputs provided; max {max_multiple_accounts}"
+        )));
+    }
+    pubkey_strs
+        .into_iter()
+        .map(|pubkey_str| verify_pubkey(&pubkey_str))
+        .collect()
+}
+
+pub(crate) fn verify_program_accounts_filters(filters: &[RpcFilterType]) -> Result<()> {
+    if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
+        return Err(Error::invalid_params(format!(
+            "Too many filters provided; max {MAX_GET_PROGRAM_ACCOUNT_FILTERS}"
+        )));
+    }
+    for filter in filters {
+        verify_filter(filter)?;
+    }
+    Ok(())
+}
+
 fn verify_hash(input: &str) -> Result<Hash> {
     input
         .parse()
@@ -2258,6 +2481,31 @@ fn verify_and_parse_signatures_for_address_params(
     Ok((address, before, until, limit))
 }
 
+fn verify_and_parse_signatures_for_program_instruction_params(
+    program_id: String,
+    discriminator: Option<String>,
+    before: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+) -> Result<(Pubkey, Vec<u8>, Option<Signature>, Option<Signature>, usize)> {
+    let (program_id, before, until, limit) =
+        verify_and_parse_signatures_for_address_params(program_id, before, until, limit)?;
+    let discriminator = discriminator
+        .map(|discriminator| {
+            bs58::decode(discriminator)
+                .into_vec()
+                .map_err(|err| Error::invalid_params(format!("Invalid discriminator: {err}")))
+        })
+        .transpose()?
+        .unwrap_or_default();
+    if discriminator.len() > PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN {
+        return Err(Error::invalid_params(format!(
+            "Invalid discriminator; max {PROGRAM_INSTRUCTION_DISCRIMINATOR_LEN} bytes"
+        )));
+    }
+    Ok((program_id, discriminator, before, until, limit))
+}
+
 pub(crate) fn check_is_at_least_confirmed(commitment: CommitmentConfig) -> Result<()> {
     if !commitment.is_at_least_confirmed() {
         return Err(Error::invalid_params(
@@ -2273,19 +2521,29 @@ fn get_encoded_account(
     encoding: UiAccountEncoding,
     data_slice: Option<UiDataSliceConfig>,
 ) -> Result<Option<UiAccount>> {
-    match bank.get_account(pubkey) {
-        Some(account) => {
-            let response = if is_known_spl_token_id(account.owner())
-                && encoding == UiAccountEncoding::JsonParsed
-            {
-                get_parsed_token_account(bank.clone(), pubkey, account)
-            } else {
-                encode_account(&account, pubkey, encoding, data_slice)?
-            };
-            Ok(Some(response))
-        }
-        None => Ok(None),
+    // accounts a lazy-state validator doesn't have yet are read through its lazy account source
+    // without storing them
+    let (account, lazy_fetched) = match bank.get_account(pubkey) {
+        Some(account) => (account, bank.is_lazy_fetched_account(pubkey)),
+        None => match bank
+            .get_lazy_accounts([pubkey])
+            .into_iter()
+            .find(|(fetched, _)| fetched == pubkey)
+        {
+            Some((_, account)) => (account, true),
+            None => return Ok(None),
+        },
+    };
+    let mut response =
+        if is_known_spl_token_id(account.owner()) && encoding == UiAccountEncoding::JsonParsed {
+            get_parsed_token_account(bank.clone(), pubkey, account)
+        } else {
+            encode_account(&account, pubkey, encoding, data_slice)?
+        };
+    if lazy_fetched {
+        response.lazy_fetched = Some(true);
     }
+    Ok(Some(response))
 }
 
 fn encode_account<T: ReadableAccount>(
@@ -2461,8 +2719,9 @@ fn _send_transaction(
     last_valid_block_height: u64,
     durable_nonce_info: Option<(Pubkey, Hash)>,
     max_retries: Option<usize>,
+    compute_unit_price: u64,
 ) -> Result<String> {
-    let transaction_info = TransactionInfo::new(
+    let mut transaction_info = TransactionInfo::new(
         signature,
         wire_transaction,
         last_valid_block_height,
@@ -2470,6 +2729,7 @@ fn _send_transaction(
         max_retries,
         None,
     );
+    transaction_info.compute_unit_price = compute_unit_price;
     meta.transaction_sender
         .lock()
         .unwrap()
@@ -3018,19 +3278,7 @@ pub mod rpc_accounts {
                 pubkey_strs.len()
             );
 
-            let max_multiple_accounts = meta
-                .config
-                .max_multiple_accounts
-                .unwrap_or(MAX_MULTIPLE_ACCOUNTS);
-            if pubkey_strs.len() > max_multiple_accounts {
-                return Err(Error::invalid_params(format!(
-                    "Too many inputs provided; max {max_multiple_accounts}"
-                )));
-            }
-            let pubkeys = pubkey_strs
-                .into_iter()
-                .map(|pubkey_str| verify_pubkey(&pubkey_str))
-                .collect::<Result<Vec<_>>>()?;
+            let pubkeys = verify_multiple_accounts_pubkeys(&meta.config, pubkey_strs)?;
             meta.get_multiple_accounts(pubkeys, config)
         }
 
@@ -3170,14 +3418,7 @@ pub mod rpc_accounts_scan {
             } else {
                 (None, vec![], false)
             };
-            if filters.len() > MAX_GET_PROGRAM_ACCOUNT_FILTERS {
-                return Err(Error::invalid_params(format!(
-                    "Too many filters provided; max {MAX_GET_PROGRAM_ACCOUNT_FILTERS}"
-                )));
-            }
-            for filter in &filters {
-                verify_filter(filter)?;
-            }
+            verify_program_accounts_filters(&filters)?;
             meta.get_program_accounts(&program_id, config, filters, with_context)
         }
 
@@ -3367,6 +3608,14 @@ pub mod rpc_full {
             config: Option<RpcSignaturesForAddressConfig>,
         ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>;
 
+        #[rpc(meta, name = "getSignaturesForProgramInstruction")]
+        fn get_signatures_for_program_instruction(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcSignaturesForProgramInstructionConfig>,
+        ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>;
+
         #[rpc(meta, name = "getFirstAvailableBlock")]
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>>;
 
@@ -3406,6 +3655,20 @@ pub mod rpc_full {
             meta: Self::Metadata,
             pubkey_strs: Option<Vec<String>>,
         ) -> Result<Vec<RpcPrioritizationFee>>;
+
+        #[rpc(meta, name = "getTransactionSendStatus")]
+        fn get_transaction_send_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcTransactionSendStatus>>;
+
+        #[rpc(meta, name = "getPriorityFeeEstimate")]
+        fn get_priority_fee_estimate(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcPriorityFeeEstimateConfig>,
+        ) -> Result<RpcPriorityFeeEstimate>;
     }
 
     pub struct FullImpl;
@@ -3575,6 +3838,7 @@ pub mod rpc_full {
                 last_valid_block_height,
                 None,
                 None,
+                0,
             )
         }
 
@@ -3626,6 +3890,10 @@ pub mod rpc_full {
                 last_valid_block_height =
                     preflight_bank.block_height() + MAX_RECENT_BLOCKHASHES as u64;
             }
+            let compute_unit_price = transaction
+                .get_transaction_priority_details()
+                .map(|details| details.priority)
+                .unwrap_or_default();
 
             if !skip_preflight {
                 verify_transaction(&transaction, &preflight_bank.feature_set)?;
@@ -3685,6 +3953,7 @@ pub mod rpc_full {
                 last_valid_block_height,
                 durable_nonce_info,
                 max_retries,
+                compute_unit_price,
             )
         }
 
@@ -3901,6 +4170,51 @@ pub mod rpc_full {
             }
         }
 
+        fn get_signatures_for_program_instruction(
+            &self,
+            meta: Self::Metadata,
+            program_id_str: String,
+            config: Option<RpcSignaturesForProgramInstructionConfig>,
+        ) -> BoxFuture<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>> {
+            debug!(
+                "get_signatures_for_program_instruction rpc request received: {:?}",
+                program_id_str
+            );
+            let RpcSignaturesForProgramInstructionConfig {
+                discriminator,
+                before,
+                until,
+                limit,
+                commitment,
+                min_context_slot,
+            } = config.unwrap_or_default();
+            let verification = verify_and_parse_signatures_for_program_instruction_params(
+                program_id_str,
+                discriminator,
+                before,
+                until,
+                limit,
+            );
+
+            match verification {
+                Err(err) => Box::pin(future::err(err)),
+                Ok((program_id, discriminator, before, until, limit)) => Box::pin(async move {
+                    meta.get_signatures_for_program_instruction(
+                        program_id,
+                        discriminator,
+                        before,
+                        until,
+                        limit,
+                        RpcContextConfig {
+                            commitment,
+                            min_context_slot,
+                        },
+                    )
+                    .await
+                }),
+            }
+        }
+
         fn get_first_available_block(&self, meta: Self::Metadata) -> BoxFuture<Result<Slot>> {
             debug!("get_first_available_block rpc request received");
             Box::pin(async move { Ok(meta.get_first_available_block().await) })
@@ -4004,6 +4318,101 @@ pub mod rpc_full {
                 .collect::<Result<Vec<_>>>()?;
             meta.get_recent_prioritization_fees(pubkeys)
         }
+
+        fn get_priority_fee_estimate(
+            &self,
+            meta: Self::Metadata,
+            config: Option<RpcPriorityFeeEstimateConfig>,
+        ) -> Result<RpcPriorityFeeEstimate> {
+            let RpcPriorityFeeEstimateConfig {
+                account_keys,
+                transaction,
+                encoding,
+                num_blocks,
+            } = config.unwrap_or_default();
+            let account_keys = account_keys.unwrap_or_default();
+            debug!(
+                "get_priority_fee_estimate rpc request received: {:?} pubkeys",
+                account_keys.len()
+            );
+            let num_blocks = num_blocks.unwrap_or(MAX_NUM_RECENT_BLOCKS as usize);
+            if num_blocks == 0 || num_blocks > MAX_NUM_RECENT_BLOCKS as usize {
+                return Err(Error::invalid_params(format!(
+                    "Invalid numBlocks; max {MAX_NUM_RECENT_BLOCKS}"
+                )));
+            }
+            let mut pubkeys = account_keys
+                .into_iter()
+                .map(|pubkey_str| verify_pubkey(&pubkey_str))
+                .collect::<Result<Vec<_>>>()?;
+            if let Some(transaction) = transaction {
+                let tx_encoding = encoding.unwrap_or(UiTransactionEncoding::Base58);
+                let binary_encoding = tx_encoding.into_binary_encoding().ok_or_else(|| {
+                    Error::invalid_params(format!(
+                        "unsupported encoding: {tx_encoding}. Supported encodings: base58, base64"
+                    ))
+                })?;
+                let (_, unsanitized_tx) =
+                    decode_and_deserialize::<VersionedTransaction>(transaction, binary_encoding)?;
+                let bank = &*meta.bank(None);
+                let transaction = sanitize_transaction(unsanitized_tx, bank)?;
+                pubkeys.extend(
+                    transaction
+                        .get_account_locks_unchecked()
+                        .writable
+                        .into_iter()
+                        .copied(),
+                );
+            }
+            pubkeys.sort_unstable();
+            pubkeys.dedup();
+            if pubkeys.len() > MAX_TX_ACCOUNT_LOCKS {
+                return Err(Error::invalid_params(format!(
+                    "Too many inputs provided; max {MAX_TX_ACCOUNT_LOCKS}"
+                )));
+            }
+            meta.get_priority_fee_estimate(pubkeys, num_blocks)
+        }
+
+        fn get_transaction_send_status(
+            &self,
+            meta: Self::Metadata,
+            signature_str: String,
+        ) -> Result<Option<RpcTransactionSendStatus>> {
+            debug!(
+                "get_transaction_send_status rpc request received: {:?}",
+                signature_str
+            );
+            let signature = verify_signature(&signature_str)?;
+            Ok(meta.get_transaction_send_status(&signature))
+        }
+    }
+}
+
+fn rpc_transaction_send_status_from_transaction_send_status(
+    status: TransactionSendStatus,
+) -> RpcTransactionSendStatus {
+    let TransactionSendStatus {
+        state,
+        attempts,
+        leaders,
+        last_error,
+        compute_unit_price,
+    } = status;
+    let state = match state {
+        TransactionSendState::Pending => RpcTransactionSendState::Pending,
+        TransactionSendState::Rooted => RpcTransactionSendState::Rooted,
+        TransactionSendState::Expired => RpcTransactionSendState::Expired,
+        TransactionSendState::MaxRetriesExceeded => RpcTransactionSendState::MaxRetriesExceeded,
+        TransactionSendState::Failed => RpcTransactionSendState::Failed,
+        TransactionSendState::Dropped => RpcTransactionSendState::Dropped,
+    };
+    RpcTransactionSendStatus {
+        state,
+        attempts,
+        leaders: leaders.iter().map(|leader| leader.to_string()).collect(),
+        last_error,
+        compute_unit_price,
     }
 }
 
@@ -4578,6 +4987,7 @@ pub fn populate_blockstore_for_tests(
             None,
             blockstore,
             false,
+            true,
             &Arc::new(AtomicBool::new(false)),
         );
 
@@ -4629,6 +5039,7 @@ pub mod tests {
         solana_rpc_client_api::{
             custom_error::{
                 JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+                JSON_RPC_SERVER_ERROR_PROGRAM_INSTRUCTION_INDEX_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
                 JSON_RPC_SERVER_ERROR_UNSUPPORTED_TRANSACTION_VERSION,
             },
@@ -4790,6 +5201,7 @@ pub mod tests {
                 Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
                 max_complete_transaction_status_slot.clone(),
                 Arc::new(PrioritizationFeeCache::default()),
+                TEST_RUNTIME.clone(),
             )
             .0;
 
@@ -6367,6 +6779,67 @@ pub mod tests {
         assert_eq!(error["code"], ErrorCode::InvalidParams.code());
     }
 
+    #[test]
+    fn test_rpc_get_transaction_send_status() {
+        let genesis = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis.genesis_config));
+        let connection_cache = Arc::new(ConnectionCache::default());
+        let meta = JsonRpcRequestProcessor::new_from_bank(
+            &bank,
+            SocketAddrSpace::Unspecified,
+            connection_cache,
+        );
+
+        let mut io = MetaIoHandler::default();
+        io.extend_with(rpc_full::FullImpl.to_delegate());
+
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let tx = system_transaction::transfer(
+            &genesis.mint_keypair,
+            &bob_pubkey,
+            1,
+            bank.last_blockhash(),
+        );
+        let signature = tx.signatures[0];
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getTransactionSendStatus","params":["{signature}"]}}"#
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["result"], Value::Null);
+
+        let tx_serialized_encoded = bs58::encode(serialize(&tx).unwrap()).into_string();
+        let send_req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"sendTransaction","params":["{tx_serialized_encoded}", {{"skipPreflight": true}}]}}"#
+        );
+        let res = io.handle_request_sync(&send_req, meta.clone());
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["result"], signature.to_string());
+
+        // The send-transaction-service picks the transaction up asynchronously
+        let mut status = None;
+        for _ in 0..100 {
+            let res = io.handle_request_sync(&req, meta.clone());
+            let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+            status =
+                serde_json::from_value::<Option<RpcTransactionSendStatus>>(json["result"].clone())
+                    .unwrap();
+            if status.is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let status = status.expect("transaction send status");
+        assert_eq!(status.state, RpcTransactionSendState::Pending);
+        assert_eq!(status.compute_unit_price, 0);
+
+        let bad_req = r#"{"jsonrpc":"2.0","id":1,"method":"getTransactionSendStatus","params":["notasignature"]}"#;
+        let res = io.handle_request_sync(bad_req, meta);
+        let json: Value = serde_json::from_str(&res.unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], ErrorCode::InvalidParams.code());
+    }
+
     #[test]
     fn test_rpc_send_transaction_preflight() {
         let exit = Arc::new(AtomicBool::new(false));
@@ -6409,6 +6882,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            TEST_RUNTIME.clone(),
         );
         let connection_cache = Arc::new(ConnectionCache::default());
         SendTransactionService::new::<NullTpuInfo>(
@@ -6676,6 +7150,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            TEST_RUNTIME.clone(),
         );
         let connection_cache = Arc::new(ConnectionCache::default());
         SendTransactionService::new::<NullTpuInfo>(
@@ -6743,6 +7218,77 @@ pub mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_get_signatures_for_program_instruction() {
+        let rpc = RpcHandler::start_with_config(JsonRpcConfig {
+            enable_rpc_transaction_history: true,
+            enable_program_instruction_index: true,
+            ..JsonRpcConfig::default()
+        });
+        let signatures = rpc.create_test_transactions_and_populate_blockstore();
+
+        // Both test transactions are system transfers, most recent first
+        let transfer_discriminator = bs58::encode([2, 0, 0, 0]).into_string();
+        let request = create_test_request(
+            "getSignaturesForProgramInstruction",
+            Some(json!([
+                system_program::id().to_string(),
+                {"discriminator": transfer_discriminator},
+            ])),
+        );
+        let result: Vec<RpcConfirmedTransactionStatusWithSignature> =
+            parse_success_result(rpc.handle_request_sync(request));
+        let result_signatures: Vec<_> = result
+            .iter()
+            .map(|info| Signature::from_str(&info.signature).unwrap())
+            .collect();
+        assert_eq!(
+            result_signatures,
+            signatures.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert!(result[0].err.is_some());
+        assert!(result[1].err.is_none());
+
+        let request = create_test_request(
+            "getSignaturesForProgramInstruction",
+            Some(json!([
+                system_program::id().to_string(),
+                {"discriminator": bs58::encode([0]).into_string(), "limit": 1},
+            ])),
+        );
+        let result: Vec<RpcConfirmedTransactionStatusWithSignature> =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert!(result.is_empty());
+
+        let request = create_test_request(
+            "getSignaturesForProgramInstruction",
+            Some(json!([
+                system_program::id().to_string(),
+                {"discriminator": bs58::encode([0; 9]).into_string()},
+            ])),
+        );
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            response,
+            (
+                ErrorCode::InvalidParams.code(),
+                "Invalid discriminator; max 8 bytes".to_string()
+            )
+        );
+
+        // The index is opt-in
+        let rpc = RpcHandler::start();
+        let request = create_test_request(
+            "getSignaturesForProgramInstruction",
+            Some(json!([system_program::id().to_string()])),
+        );
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            response.0,
+            JSON_RPC_SERVER_ERROR_PROGRAM_INSTRUCTION_INDEX_NOT_AVAILABLE
+        );
+    }
+
     #[test]
     fn test_get_block_with_versioned_tx() {
         let rpc = RpcHandler::start();
@@ -8295,6 +8841,7 @@ pub mod tests {
             Arc::new(LeaderScheduleCache::default()),
             Arc::new(AtomicU64::default()),
             Arc::new(PrioritizationFeeCache::default()),
+            TEST_RUNTIME.clone(),
         );
 
         let mut io = MetaIoHandler::default();
@@ -8753,4 +9300,96 @@ pub mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_rpc_get_priority_fee_estimate() {
+        let rpc = RpcHandler::start();
+        let slot0 = rpc.working_bank().slot();
+        let account0 = Pubkey::new_unique();
+        let account1 = Pubkey::new_unique();
+        let account2 = Pubkey::new_unique();
+        let price = 42;
+        let priced_transaction = Transaction::new_unsigned(Message::new(
+            &[
+                system_instruction::transfer(&account0, &account1, 1),
+                ComputeBudgetInstruction::set_compute_unit_price(price),
+            ],
+            Some(&account0),
+        ));
+        rpc.update_prioritization_fee_cache(vec![
+            priced_transaction.clone(),
+            Transaction::new_unsigned(Message::new(
+                &[system_instruction::transfer(&account0, &account2, 1)],
+                Some(&account0),
+            )),
+        ]);
+
+        // Nothing is estimated before a block is finalized
+        let request = create_test_request("getPriorityFeeEstimate", None);
+        let response: RpcPriorityFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(response, RpcPriorityFeeEstimate::default());
+
+        let cache = rpc.get_prioritization_fee_cache();
+        cache.finalize_priority_fee(slot0);
+        while cache.available_block_count() < 1 {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+
+        let request = create_test_request("getPriorityFeeEstimate", None);
+        let response: RpcPriorityFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            response,
+            RpcPriorityFeeEstimate {
+                p25: 0,
+                p50: 0,
+                p75: price,
+                p90: price,
+                num_blocks: 1,
+            }
+        );
+
+        let request = create_test_request(
+            "getPriorityFeeEstimate",
+            Some(json!([{"accountKeys": [account1.to_string()]}])),
+        );
+        let response: RpcPriorityFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(
+            response,
+            RpcPriorityFeeEstimate {
+                p25: price,
+                p50: price,
+                p75: price,
+                p90: price,
+                num_blocks: 1,
+            }
+        );
+
+        // The writable accounts of a transaction are included in the estimate
+        let request = create_test_request(
+            "getPriorityFeeEstimate",
+            Some(json!([{
+                "transaction": base64::encode(serialize(&priced_transaction).unwrap()),
+                "encoding": "base64",
+            }])),
+        );
+        let response: RpcPriorityFeeEstimate =
+            parse_success_result(rpc.handle_request_sync(request));
+        assert_eq!(response.p25, price);
+
+        let request = create_test_request(
+            "getPriorityFeeEstimate",
+            Some(json!([{"numBlocks": MAX_NUM_RECENT_BLOCKS + 1}])),
+        );
+        let response = parse_failure_response(rpc.handle_request_sync(request));
+        assert_eq!(
+            response,
+            (
+                ErrorCode::InvalidParams.code(),
+                format!("Invalid numBlocks; max {MAX_NUM_RECENT_BLOCKS}"),
+            )
+        );
+    }
 }
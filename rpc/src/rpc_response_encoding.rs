@@ -0,0 +1,552 @@
+//! Binary and compressed responses of the JSON RPC service.
+//!
+//! See [`solana_rpc_client_api::response_encoding`] for how they are
+//! negotiated. Requests that accept them are taken over from the JSON-RPC
+//! server by [`ResponseEncoder::on_request`]; the supported methods are
+//! answered in their binary encoding, and everything else is handled by the
+//! server's `MetaIoHandler` as usual, then compressed if the client allows it.
+
+use {
+    crate::rpc::{
+        verify_multiple_accounts_pubkeys, verify_program_accounts_filters, verify_pubkey,
+        JsonRpcConfig, JsonRpcRequestProcessor, MAX_REQUEST_BODY_SIZE,
+    },
+    flate2::{write::GzEncoder, Compression},
+    jsonrpc_core::{Call, Error, MetaIoHandler, MethodCall, Output, Params, Request, Result},
+    jsonrpc_http_server::{
+        hyper::{self, body::HttpBody, header},
+        RequestMiddlewareAction,
+    },
+    prost::Message,
+    serde::de::DeserializeOwned,
+    serde_json::Value,
+    solana_rpc_client_api::{
+        config::{
+            RpcAccountInfoConfig, RpcBlockConfig, RpcContextConfig, RpcEncodingConfigWrapper,
+            RpcProgramAccountsConfig,
+        },
+        custom_error::RpcCustomError,
+        response_encoding::{RpcBinaryEncoding, RpcContentEncoding, JSON_CONTENT_TYPE},
+    },
+    solana_sdk::{clock::Slot, transaction::TransactionVersion},
+    solana_storage_proto::convert::generated,
+    solana_transaction_status::{
+        ConfirmedBlock, EncodeError, TransactionDetails, TransactionWithStatusMeta,
+    },
+    std::io::Write,
+};
+
+/// Responses smaller than this are sent uncompressed
+const MIN_COMPRESSED_RESPONSE_SIZE: usize = 1024;
+/// Favor throughput; responses are compressed on the RPC event loop
+const ZSTD_COMPRESSION_LEVEL: i32 = 1;
+
+#[derive(Clone)]
+pub(crate) struct ResponseEncoder {
+    io: MetaIoHandler<JsonRpcRequestProcessor>,
+    request_processor: JsonRpcRequestProcessor,
+    config: JsonRpcConfig,
+}
+
+impl ResponseEncoder {
+    pub fn new(
+        io: MetaIoHandler<JsonRpcRequestProcessor>,
+        request_processor: JsonRpcRequestProcessor,
+        config: JsonRpcConfig,
+    ) -> Self {
+        Self {
+            io,
+            request_processor,
+            config,
+        }
+    }
+
+    /// Take over JSON-RPC requests that accept a binary or compressed response
+    pub fn on_request(&self, request: hyper::Request<hyper::Body>) -> RequestMiddlewareAction {
+        let header_value = |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+        };
+        if request.method() != hyper::Method::POST
+            || !header_value(header::CONTENT_TYPE).starts_with(JSON_CONTENT_TYPE)
+        {
+            return request.into();
+        }
+        let binary_encodings = RpcBinaryEncoding::parse_accept(header_value(header::ACCEPT));
+        let content_encoding = RpcContentEncoding::negotiate(header_value(header::ACCEPT_ENCODING));
+        if binary_encodings.is_empty() && content_encoding.is_none() {
+            return request.into();
+        }
+
+        let response_encoder = self.clone();
+        RequestMiddlewareAction::Respond {
+            should_validate_hosts: true,
+            response: Box::pin(async move {
+                Ok(response_encoder
+                    .respond(request.into_body(), &binary_encodings, content_encoding)
+                    .await)
+            }),
+        }
+    }
+
+    async fn respond(
+        &self,
+        body: hyper::Body,
+        binary_encodings: &[RpcBinaryEncoding],
+        content_encoding: Option<RpcContentEncoding>,
+    ) -> hyper::Response<hyper::Body> {
+        let max_request_body_size = self
+            .config
+            .max_request_body_size
+            .unwrap_or(MAX_REQUEST_BODY_SIZE);
+        let body = match read_body(body, max_request_body_size).await {
+            Ok(body) => body,
+            Err(status) => {
+                return hyper::Response::builder()
+                    .status(status)
+                    .body(hyper::Body::empty())
+                    .unwrap()
+            }
+        };
+
+        let (content_type, response) = match self.handle_binary(&body, binary_encodings).await {
+            Some(response) => response,
+            None => {
+                let request = String::from_utf8_lossy(&body);
+                let response = self
+                    .io
+                    .handle_request(&request, self.request_processor.clone())
+                    .await
+                    .unwrap_or_default();
+                (JSON_CONTENT_TYPE, response.into_bytes())
+            }
+        };
+
+        let mut builder = hyper::Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::VARY, "Accept, Accept-Encoding")
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+        let response = match content_encoding {
+            Some(content_encoding) if response.len() >= MIN_COMPRESSED_RESPONSE_SIZE => {
+                match compress(content_encoding, &response) {
+                    Ok(compressed) => {
+                        builder =
+                            builder.header(header::CONTENT_ENCODING, content_encoding.as_str());
+                        compressed
+                    }
+                    Err(err) => {
+                        warn!("failed to compress rpc response: {:?}", err);
+                        response
+                    }
+                }
+            }
+            _ => response,
+        };
+        builder.body(hyper::Body::from(response)).unwrap()
+    }
+
+    /// Answer a single call to a method supporting one of `binary_encodings`.
+    ///
+    /// Returns the content type and body of the response, which is JSON for
+    /// errors and null results, or None if the request must be handled as
+    /// JSON, including when its params can't be parsed.
+    async fn handle_binary(
+        &self,
+        body: &[u8],
+        binary_encodings: &[RpcBinaryEncoding],
+    ) -> Option<(&'static str, Vec<u8>)> {
+        if !self.config.full_api {
+            return None;
+        }
+        let Ok(Request::Single(Call::MethodCall(method_call))) = serde_json::from_slice(body)
+        else {
+            return None;
+        };
+        let MethodCall {
+            jsonrpc,
+            method,
+            params,
+            id,
+        } = method_call;
+        let binary_encoding = *binary_encodings
+            .iter()
+            .find(|binary_encoding| binary_encoding.supports_method(&method))?;
+
+        let result = match (binary_encoding, method.as_str()) {
+            (RpcBinaryEncoding::Protobuf, "getBlock") => self.get_block_protobuf(params).await?,
+            (RpcBinaryEncoding::Bincode, "getProgramAccounts") => {
+                self.get_program_accounts_bincode(params)?
+            }
+            (RpcBinaryEncoding::Bincode, "getMultipleAccounts") => {
+                self.get_multiple_accounts_bincode(params)?
+            }
+            _ => return None,
+        };
+        match result {
+            Ok(Some(response)) => Some((binary_encoding.content_type(), response)),
+            Ok(None) => {
+                let output = Output::from(Ok(Value::Null), id, jsonrpc);
+                Some((JSON_CONTENT_TYPE, serde_json::to_vec(&output).ok()?))
+            }
+            Err(err) => {
+                let output = Output::from(Err(err), id, jsonrpc);
+                Some((JSON_CONTENT_TYPE, serde_json::to_vec(&output).ok()?))
+            }
+        }
+    }
+
+    async fn get_block_protobuf(&self, params: Params) -> Option<Result<Option<Vec<u8>>>> {
+        let (slot, config) =
+            parse_params::<Slot, RpcEncodingConfigWrapper<RpcBlockConfig>>(params)?;
+        let config = config
+            .map(|config| config.convert_to_current())
+            .unwrap_or_default();
+        if config.transaction_details.unwrap_or_default() != TransactionDetails::Full {
+            return None;
+        }
+        debug!("get_block rpc request received: {:?} (protobuf)", slot);
+        let result = self
+            .request_processor
+            .get_confirmed_block(slot, config.commitment)
+            .await;
+        Some(result.and_then(|confirmed_block| {
+            confirmed_block
+                .map(|confirmed_block| {
+                    verify_transaction_versions(
+                        &confirmed_block,
+                        config.max_supported_transaction_version,
+                    )?;
+                    Ok(generated::ConfirmedBlock::from(confirmed_block).encode_to_vec())
+                })
+                .transpose()
+        }))
+    }
+
+    fn get_program_accounts_bincode(&self, params: Params) -> Option<Result<Option<Vec<u8>>>> {
+        let (program_id_str, config) = parse_params::<String, RpcProgramAccountsConfig>(params)?;
+        debug!(
+            "get_program_accounts rpc request received: {:?} (bincode)",
+            program_id_str
+        );
+        let (config, filters) = match config {
+            Some(config) => (config.account_config, config.filters.unwrap_or_default()),
+            None => (RpcAccountInfoConfig::default(), vec![]),
+        };
+        let config = context_config(config)?;
+        Some((|| {
+            let program_id = verify_pubkey(&program_id_str)?;
+            verify_program_accounts_filters(&filters)?;
+            let accounts =
+                self.request_processor
+                    .get_raw_program_accounts(&program_id, config, filters)?;
+            serialize_bincode(&accounts).map(Some)
+        })())
+    }
+
+    fn get_multiple_accounts_bincode(&self, params: Params) -> Option<Result<Option<Vec<u8>>>> {
+        let (pubkey_strs, config) = parse_params::<Vec<String>, RpcAccountInfoConfig>(params)?;
+        let config = context_config(config.unwrap_or_default())?;
+        debug!(
+            "get_multiple_accounts rpc request received: {:?} (bincode)",
+            pubkey_strs.len()
+        );
+        Some((|| {
+            let pubkeys = verify_multiple_accounts_pubkeys(&self.config, pubkey_strs)?;
+            let accounts = self
+                .request_processor
+                .get_raw_multiple_accounts(pubkeys, config)?;
+            serialize_bincode(&accounts).map(Some)
+        })())
+    }
+}
+
+/// Parse the `[argument, config]` params shared by the binary methods
+fn parse_params<T: DeserializeOwned, C: DeserializeOwned>(
+    params: Params,
+) -> Option<(T, Option<C>)> {
+    let Params::Array(mut params) = params else {
+        return None;
+    };
+    if params.is_empty() || params.len() > 2 {
+        return None;
+    }
+    let config = if params.len() == 2 {
+        serde_json::from_value::<Option<C>>(params.pop()?).ok()?
+    } else {
+        None
+    };
+    let argument = serde_json::from_value(params.pop()?).ok()?;
+    Some((argument, config))
+}
+
+/// Accounts are always sent whole, so requests for a data slice are handled
+/// as JSON
+fn context_config(config: RpcAccountInfoConfig) -> Option<RpcContextConfig> {
+    let RpcAccountInfoConfig {
+        data_slice,
+        commitment,
+        min_context_slot,
+        ..
+    } = config;
+    data_slice.is_none().then_some(RpcContextConfig {
+        commitment,
+        min_context_slot,
+    })
+}
+
+/// Fail as `encode_with_options` would for transactions the client can't
+/// handle, since the client only encodes the block once it is received
+fn verify_transaction_versions(
+    confirmed_block: &ConfirmedBlock,
+    max_supported_transaction_version: Option<u8>,
+) -> Result<()> {
+    for transaction in &confirmed_block.transactions {
+        if let TransactionWithStatusMeta::Complete(transaction) = transaction {
+            if let TransactionVersion::Number(version) = transaction.transaction.version() {
+                if max_supported_transaction_version.map_or(true, |max| version > max) {
+                    return Err(
+                        RpcCustomError::from(EncodeError::UnsupportedTransactionVersion(version))
+                            .into(),
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn serialize_bincode<T: serde::Serialize>(value: &T) -> Result<Vec<u8>> {
+    bincode::serialize(value).map_err(|err| {
+        warn!("failed to serialize rpc response: {:?}", err);
+        Error::internal_error()
+    })
+}
+
+async fn read_body(
+    mut body: hyper::Body,
+    max_request_body_size: usize,
+) -> std::result::Result<Vec<u8>, hyper::StatusCode> {
+    let mut request = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| hyper::StatusCode::BAD_REQUEST)?;
+        if request.len() + chunk.len() > max_request_body_size {
+            return Err(hyper::StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        request.extend_from_slice(&chunk);
+    }
+    Ok(request)
+}
+
+fn compress(content_encoding: RpcContentEncoding, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    match content_encoding {
+        RpcContentEncoding::Zstd => zstd::encode_all(data, ZSTD_COMPRESSION_LEVEL),
+        RpcContentEncoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::rpc::{rpc_accounts::*, rpc_accounts_scan::*, rpc_full::*},
+        flate2::read::GzDecoder,
+        solana_client::connection_cache::ConnectionCache,
+        solana_ledger::genesis_utils::create_genesis_config,
+        solana_rpc_client_api::response_encoding::{
+            RpcBincodeKeyedAccounts, RpcBincodeMultipleAccounts,
+        },
+        solana_runtime::bank::Bank,
+        solana_sdk::{
+            account::{Account, AccountSharedData},
+            pubkey::Pubkey,
+        },
+        solana_streamer::socket::SocketAddrSpace,
+        std::{io::Read, sync::Arc},
+    };
+
+    fn new_response_encoder(bank: &Arc<Bank>) -> ResponseEncoder {
+        let request_processor = JsonRpcRequestProcessor::new_from_bank(
+            bank,
+            SocketAddrSpace::Unspecified,
+            Arc::new(ConnectionCache::default()),
+        );
+        let mut io = MetaIoHandler::default();
+        io.extend_with(AccountsDataImpl.to_delegate());
+        io.extend_with(AccountsScanImpl.to_delegate());
+        io.extend_with(FullImpl.to_delegate());
+        ResponseEncoder::new(io, request_processor, JsonRpcConfig::default_for_test())
+    }
+
+    async fn send(
+        response_encoder: &ResponseEncoder,
+        request: Value,
+        binary_encodings: &[RpcBinaryEncoding],
+        content_encoding: Option<RpcContentEncoding>,
+    ) -> (hyper::http::response::Parts, Vec<u8>) {
+        let response = response_encoder
+            .respond(
+                hyper::Body::from(request.to_string()),
+                binary_encodings,
+                content_encoding,
+            )
+            .await;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body).await.unwrap().to_vec();
+        (parts, body)
+    }
+
+    #[tokio::test]
+    async fn test_bincode_accounts() {
+        let genesis = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis.genesis_config));
+        let owner = Pubkey::new_unique();
+        let pubkey = Pubkey::new_unique();
+        let account = AccountSharedData::create(42, vec![1, 2, 3, 4, 5], owner, false, 0);
+        bank.store_account(&pubkey, &account);
+        let response_encoder = new_response_encoder(&bank);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [
+                [pubkey.to_string(), Pubkey::new_unique().to_string()],
+                {"encoding": "base64"},
+            ],
+        });
+        let (parts, body) = send(
+            &response_encoder,
+            request,
+            &[RpcBinaryEncoding::Bincode],
+            None,
+        )
+        .await;
+        assert_eq!(
+            parts.headers[header::CONTENT_TYPE],
+            RpcBinaryEncoding::Bincode.content_type()
+        );
+        let response: RpcBincodeMultipleAccounts = bincode::deserialize(&body).unwrap();
+        assert_eq!(response.slot, bank.slot());
+        assert_eq!(
+            response.accounts,
+            vec![Some(Account::from(account.clone())), None]
+        );
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getProgramAccounts",
+            "params": [owner.to_string(), {"encoding": "base64"}],
+        });
+        let (_parts, body) = send(
+            &response_encoder,
+            request,
+            &[RpcBinaryEncoding::Bincode],
+            None,
+        )
+        .await;
+        let response: RpcBincodeKeyedAccounts = bincode::deserialize(&body).unwrap();
+        assert_eq!(response.accounts, vec![(pubkey, Account::from(account))]);
+
+        // Data slices are only served as JSON
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getProgramAccounts",
+            "params": [
+                owner.to_string(),
+                {"encoding": "base64", "dataSlice": {"offset": 1, "length": 2}},
+            ],
+        });
+        let (parts, body) = send(
+            &response_encoder,
+            request,
+            &[RpcBinaryEncoding::Bincode],
+            None,
+        )
+        .await;
+        assert_eq!(parts.headers[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+        let response: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["result"][0]["account"]["data"][0], "AgM=");
+
+        // Errors are JSON
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getProgramAccounts",
+            "params": ["not a pubkey"],
+        });
+        let (parts, body) = send(
+            &response_encoder,
+            request,
+            &[RpcBinaryEncoding::Bincode],
+            None,
+        )
+        .await;
+        assert_eq!(parts.headers[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+        let response: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["id"], 1);
+        assert!(response["error"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_json() {
+        let genesis = create_genesis_config(100);
+        let bank = Arc::new(Bank::new_for_tests(&genesis.genesis_config));
+        let pubkey = Pubkey::new_unique();
+        let account = AccountSharedData::create(42, vec![7; 4096], Pubkey::default(), false, 0);
+        bank.store_account(&pubkey, &account);
+        let response_encoder = new_response_encoder(&bank);
+
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getAccountInfo",
+            "params": [pubkey.to_string(), {"encoding": "base64"}],
+        });
+        let (_parts, uncompressed) = send(&response_encoder, request.clone(), &[], None).await;
+
+        let (parts, body) = send(
+            &response_encoder,
+            request.clone(),
+            &[RpcBinaryEncoding::Bincode],
+            Some(RpcContentEncoding::Gzip),
+        )
+        .await;
+        assert_eq!(parts.headers[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+        assert_eq!(parts.headers[header::CONTENT_ENCODING], "gzip");
+        let mut decompressed = Vec::new();
+        GzDecoder::new(body.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, uncompressed);
+
+        let (parts, body) = send(
+            &response_encoder,
+            request,
+            &[],
+            Some(RpcContentEncoding::Zstd),
+        )
+        .await;
+        assert_eq!(parts.headers[header::CONTENT_ENCODING], "zstd");
+        assert_eq!(zstd::decode_all(body.as_slice()).unwrap(), uncompressed);
+
+        // Small responses are sent as is
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": "getSlot"});
+        let (parts, _body) = send(
+            &response_encoder,
+            request,
+            &[],
+            Some(RpcContentEncoding::Zstd),
+        )
+        .await;
+        assert!(parts.headers.get(header::CONTENT_ENCODING).is_none());
+    }
+}
@@ -11,6 +11,7 @@ use {
         },
         rpc_cache::LargestAccountsCache,
         rpc_health::*,
+        rpc_response_encoding::ResponseEncoder,
     },
     crossbeam_channel::unbounded,
     jsonrpc_core::{futures::prelude::*, MetaIoHandler},
@@ -43,6 +44,7 @@ use {
     solana_storage_bigtable::CredentialType,
     std::{
         collections::HashSet,
+        io::SeekFrom,
         net::SocketAddr,
         path::{Path, PathBuf},
         sync::{
@@ -51,6 +53,7 @@ use {
         },
         thread::{self, Builder, JoinHandle},
     },
+    tokio::io::AsyncSeekExt,
     tokio_util::codec::{BytesCodec, FramedRead},
 };
 
@@ -74,6 +77,7 @@ struct RpcRequestMiddleware {
     snapshot_config: Option<SnapshotConfig>,
     bank_forks: Arc<RwLock<BankForks>>,
     health: Arc<RpcHealth>,
+    response_encoder: Option<ResponseEncoder>,
 }
 
 impl RpcRequestMiddleware {
@@ -82,6 +86,7 @@ impl RpcRequestMiddleware {
         snapshot_config: Option<SnapshotConfig>,
         bank_forks: Arc<RwLock<BankForks>>,
         health: Arc<RpcHealth>,
+        response_encoder: Option<ResponseEncoder>,
     ) -> Self {
         Self {
             ledger_path,
@@ -96,6 +101,7 @@ impl RpcRequestMiddleware {
             snapshot_config,
             bank_forks,
             health,
+            response_encoder,
         }
     }
 
@@ -189,7 +195,20 @@ impl RpcRequestMiddleware {
         }
     }
 
-    fn process_file_get(&self, path: &str) -> RequestMiddlewareAction {
+    /// Parses a `Range: bytes=<start>-` header, as sent to resume a partial download
+    fn range_start(request: &hyper::Request<hyper::Body>) -> Option<u64> {
+        request
+            .headers()
+            .get(hyper::header::RANGE)?
+            .to_str()
+            .ok()?
+            .strip_prefix("bytes=")?
+            .strip_suffix('-')?
+            .parse()
+            .ok()
+    }
+
+    fn process_file_get(&self, path: &str, range_start: Option<u64>) -> RequestMiddlewareAction {
         let filename = {
             let stem = Self::strip_leading_slash(path).expect("path already verified");
             match path {
@@ -204,29 +223,55 @@ impl RpcRequestMiddleware {
             }
         };
 
-        let file_length = std::fs::metadata(&filename)
-            .map(|m| m.len())
-            .unwrap_or(0)
-            .to_string();
-        info!("get {} -> {:?} ({} bytes)", path, filename, file_length);
+        let file_length = std::fs::metadata(&filename).map(|m| m.len()).unwrap_or(0);
+        // Ranges past the end of the file are ignored, and the whole file is sent
+        let range_start = range_start.filter(|range_start| *range_start < file_length);
+        info!(
+            "get {} -> {:?} ({} bytes, from byte {})",
+            path,
+            filename,
+            file_length,
+            range_start.unwrap_or(0)
+        );
         RequestMiddlewareAction::Respond {
             should_validate_hosts: true,
-            response: Box::pin(async {
+            response: Box::pin(async move {
                 match Self::open_no_follow(filename).await {
                     Err(err) => Ok(if err.kind() == std::io::ErrorKind::NotFound {
                         Self::not_found()
                     } else {
                         Self::internal_server_error()
                     }),
-                    Ok(file) => {
+                    Ok(mut file) => {
+                        let response = match range_start {
+                            Some(range_start) => {
+                                if file.seek(SeekFrom::Start(range_start)).await.is_err() {
+                                    return Ok(Self::internal_server_error());
+                                }
+                                hyper::Response::builder()
+                                    .status(hyper::StatusCode::PARTIAL_CONTENT)
+                                    .header(
+                                        hyper::header::CONTENT_LENGTH,
+                                        file_length - range_start,
+                                    )
+                                    .header(
+                                        hyper::header::CONTENT_RANGE,
+                                        format!(
+                                            "bytes {}-{}/{}",
+                                            range_start,
+                                            file_length - 1,
+                                            file_length
+                                        ),
+                                    )
+                            }
+                            None => hyper::Response::builder()
+                                .header(hyper::header::CONTENT_LENGTH, file_length),
+                        };
                         let stream =
                             FramedRead::new(file, BytesCodec::new()).map_ok(|b| b.freeze());
                         let body = hyper::Body::wrap_stream(stream);
 
-                        Ok(hyper::Response::builder()
-                            .header(hyper::header::CONTENT_LENGTH, file_length)
-                            .body(body)
-                            .unwrap())
+                        Ok(response.body(body).unwrap())
                     }
                 }
             }),
@@ -299,13 +344,15 @@ impl RequestMiddleware for RpcRequestMiddleware {
                 .unwrap()
                 .into()
         } else if self.is_file_get_path(request.uri().path()) {
-            self.process_file_get(request.uri().path())
+            self.process_file_get(request.uri().path(), Self::range_start(&request))
         } else if request.uri().path() == "/health" {
             hyper::Response::builder()
                 .status(hyper::StatusCode::OK)
                 .body(hyper::Body::from(self.health_check()))
                 .unwrap()
                 .into()
+        } else if let Some(response_encoder) = &self.response_encoder {
+            response_encoder.on_request(request)
         } else {
             request.into()
         }
@@ -451,6 +498,7 @@ impl JsonRpcService {
         let max_request_body_size = config
             .max_request_body_size
             .unwrap_or(MAX_REQUEST_BODY_SIZE);
+        let response_encoder_config = config.enable_response_encodings.then(|| config.clone());
         let (request_processor, receiver) = JsonRpcRequestProcessor::new(
             config,
             snapshot_config.clone(),
@@ -468,6 +516,7 @@ impl JsonRpcService {
             leader_schedule_cache,
             current_transaction_status_slot,
             prioritization_fee_cache,
+            runtime.clone(),
         );
 
         let leader_info =
@@ -479,6 +528,7 @@ impl JsonRpcService {
             receiver,
             &connection_cache,
             send_transaction_service_config,
+            request_processor.transaction_send_status_cache(),
         ));
 
         #[cfg(test)]
@@ -507,11 +557,15 @@ impl JsonRpcService {
                     io.extend_with(rpc_obsolete_v1_7::ObsoleteV1_7Impl.to_delegate());
                 }
 
+                let response_encoder = response_encoder_config.map(|config| {
+                    ResponseEncoder::new(io.clone(), request_processor.clone(), config)
+                });
                 let request_middleware = RpcRequestMiddleware::new(
                     ledger_path,
                     snapshot_config,
                     bank_forks.clone(),
                     health.clone(),
+                    response_encoder,
                 );
                 let server = ServerBuilder::with_meta_extractor(
                     io,
@@ -719,12 +773,14 @@ mod tests {
             None,
             bank_forks.clone(),
             RpcHealth::stub(),
+            None,
         );
         let rrm_with_snapshot_config = RpcRequestMiddleware::new(
             PathBuf::from("/"),
             Some(SnapshotConfig::default()),
             bank_forks,
             RpcHealth::stub(),
+            None,
         );
 
         assert!(rrm.is_file_get_path(DEFAULT_GENESIS_DOWNLOAD_PATH));
@@ -823,10 +879,11 @@ mod tests {
             None,
             create_bank_forks(),
             RpcHealth::stub(),
+            None,
         );
 
         // File does not exist => request should fail.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -841,7 +898,7 @@ mod tests {
         }
 
         // Normal file exist => request should succeed.
-        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
         if let RequestMiddlewareAction::Respond { response, .. } = action {
             let response = runtime.block_on(response);
             let response = response.unwrap();
@@ -850,6 +907,23 @@ mod tests {
             panic!("Unexpected RequestMiddlewareAction variant");
         }
 
+        // Range request => only the end of the file is sent.
+        let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, Some(7));
+        if let RequestMiddlewareAction::Respond { response, .. } = action {
+            let response = runtime.block_on(response).unwrap();
+            assert_eq!(response.status(), 206);
+            assert_eq!(
+                response.headers()[hyper::header::CONTENT_RANGE],
+                "bytes 7-11/12"
+            );
+            let body = runtime
+                .block_on(hyper::body::to_bytes(response.into_body()))
+                .unwrap();
+            assert_eq!(&body[..], b"be ok");
+        } else {
+            panic!("Unexpected RequestMiddlewareAction variant");
+        }
+
         #[cfg(unix)]
         {
             std::fs::remove_file(&genesis_path).unwrap();
@@ -860,7 +934,7 @@ mod tests {
             symlink::symlink_file("wrong", &genesis_path).unwrap();
 
             // File is a symbolic link => request should fail.
-            let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH);
+            let action = rrm.process_file_get(DEFAULT_GENESIS_DOWNLOAD_PATH, None);
             if let RequestMiddlewareAction::Respond { response, .. } = action {
                 let response = runtime.block_on(response);
                 let response = response.unwrap();
@@ -878,6 +952,7 @@ mod tests {
             None,
             create_bank_forks(),
             RpcHealth::stub(),
+            None,
         );
         assert_eq!(rm.health_check(), "ok");
     }
@@ -902,7 +977,8 @@ mod tests {
             startup_verification_complete,
         ));
 
-        let rm = RpcRequestMiddleware::new(PathBuf::from("/"), None, create_bank_forks(), health);
+        let rm =
+            RpcRequestMiddleware::new(PathBuf::from("/"), None, create_bank_forks(), health, None);
 
         // No account hashes for this node or any known validators
         assert_eq!(rm.health_check(), "unknown");
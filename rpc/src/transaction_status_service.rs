@@ -3,7 +3,7 @@ use {
     crossbeam_channel::{Receiver, RecvTimeoutError},
     itertools::izip,
     solana_ledger::{
-        blockstore::Blockstore,
+        blockstore::{program_instruction_keys, Blockstore},
         blockstore_processor::{TransactionStatusBatch, TransactionStatusMessage},
     },
     solana_runtime::bank::{DurableNonceFee, TransactionExecutionDetails},
@@ -33,6 +33,7 @@ impl TransactionStatusService {
         transaction_notifier: Option<TransactionNotifierLock>,
         blockstore: Arc<Blockstore>,
         enable_extended_tx_metadata_storage: bool,
+        enable_program_instruction_index: bool,
         exit: &Arc<AtomicBool>,
     ) -> Self {
         let exit = exit.clone();
@@ -50,6 +51,7 @@ impl TransactionStatusService {
                     transaction_notifier.clone(),
                     &blockstore,
                     enable_extended_tx_metadata_storage,
+                    enable_program_instruction_index,
                 ) {
                     break;
                 }
@@ -65,6 +67,7 @@ impl TransactionStatusService {
         transaction_notifier: Option<TransactionNotifierLock>,
         blockstore: &Arc<Blockstore>,
         enable_extended_tx_metadata_storage: bool,
+        enable_program_instruction_index: bool,
     ) -> Result<(), RecvTimeoutError> {
         match write_transaction_status_receiver.recv_timeout(Duration::from_secs(1))? {
             TransactionStatusMessage::Batch(TransactionStatusBatch {
@@ -180,6 +183,17 @@ impl TransactionStatusService {
                             );
                         }
 
+                        // Index inner instructions before they are possibly dropped below
+                        let instruction_keys = (enable_rpc_transaction_history
+                            && enable_program_instruction_index)
+                            .then(|| {
+                                program_instruction_keys(
+                                    &transaction.message().account_keys(),
+                                    transaction.message().instructions(),
+                                    transaction_status_meta.inner_instructions.as_deref(),
+                                )
+                            });
+
                         if !(enable_extended_tx_metadata_storage || transaction_notifier.is_some())
                         {
                             transaction_status_meta.log_messages.take();
@@ -203,6 +217,19 @@ impl TransactionStatusService {
                                     transaction_status_meta,
                                 )
                                 .expect("Expect database write to succeed: TransactionStatus");
+
+                            if let Some(instruction_keys) = instruction_keys {
+                                blockstore
+                                    .write_program_instruction_signatures(
+                                        slot,
+                                        transaction_index as u32,
+                                        *transaction.signature(),
+                                        &instruction_keys,
+                                    )
+                                    .expect(
+                                        "Expect database write to succeed: ProgramInstructionSignatures",
+                                    );
+                            }
                         }
                     }
                 }
@@ -440,6 +467,7 @@ pub(crate) mod tests {
             Some(test_notifier.clone()),
             blockstore,
             false,
+            false,
             &exit,
         );
 
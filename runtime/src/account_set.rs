@@ -0,0 +1,169 @@
+//! A portable set of accounts exported from a bank
+//!
+//! `Bank::export_account_set` collects the accounts owned by some programs and some individual
+//! accounts, along with the program data accounts of the upgradeable programs among them. The set
+//! can be written to a file and loaded into a genesis config, a test validator or a program test,
+//! so that a test starts from a realistic slice of an existing cluster's state.
+//!
+//! The file is a zstd compressed, bincode serialized `VersionedAccountSet`.
+
+use {
+    bincode::Options,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        account::{Account, AccountSharedData, ReadableAccount},
+        clock::Slot,
+        genesis_config::GenesisConfig,
+        pubkey::Pubkey,
+    },
+    std::{
+        borrow::Cow,
+        collections::HashSet,
+        fs::File,
+        io::{self, BufReader, BufWriter, Write},
+        path::Path,
+    },
+    thiserror::Error,
+};
+
+pub const ACCOUNT_SET_FILE_EXTENSION: &str = "accountset";
+const ACCOUNT_SET_COMPRESSION_LEVEL: i32 = 3;
+/// upper bound of the size of a deserialized account set
+const MAX_ACCOUNT_SET_SIZE: u64 = 32 * 1024 * 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum AccountSetError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("serialization error: {0}")]
+    Serialize(#[from] bincode::Error),
+}
+
+/// Which accounts of a bank to export
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountSetFilter {
+    /// every account owned by one of these programs
+    pub owners: HashSet<Pubkey>,
+    /// these accounts, if they exist
+    pub pubkeys: HashSet<Pubkey>,
+}
+
+impl AccountSetFilter {
+    pub fn is_match(&self, pubkey: &Pubkey, account: &impl ReadableAccount) -> bool {
+        self.pubkeys.contains(pubkey) || self.owners.contains(account.owner())
+    }
+}
+
+/// Accounts exported from a bank, sorted by pubkey
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountSet {
+    /// slot of the bank the accounts were exported from
+    pub slot: Slot,
+    pub accounts: Vec<(Pubkey, Account)>,
+}
+
+/// The format of an account set file. New versions are added as new variants.
+#[derive(Serialize, Deserialize, Debug)]
+enum VersionedAccountSet<'a> {
+    V1(Cow<'a, AccountSet>),
+}
+
+impl AccountSet {
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), AccountSetError> {
+        let file = File::create(path)?;
+        let mut encoder =
+            zstd::stream::Encoder::new(BufWriter::new(file), ACCOUNT_SET_COMPRESSION_LEVEL)?;
+        bincode::serialize_into(&mut encoder, &VersionedAccountSet::V1(Cow::Borrowed(self)))?;
+        let mut writer = encoder.finish()?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, AccountSetError> {
+        let file = File::open(path)?;
+        let decoder = zstd::stream::Decoder::new(BufReader::new(file))?;
+        let versioned: VersionedAccountSet = bincode::options()
+            .with_limit(MAX_ACCOUNT_SET_SIZE)
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .deserialize_from(decoder)?;
+        match versioned {
+            VersionedAccountSet::V1(account_set) => Ok(account_set.into_owned()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// the accounts, in the form most test environments add them
+    pub fn shared_accounts(&self) -> impl Iterator<Item = (Pubkey, AccountSharedData)> + '_ {
+        self.accounts
+            .iter()
+            .map(|(pubkey, account)| (*pubkey, AccountSharedData::from(account.clone())))
+    }
+
+    /// add the accounts to 'genesis_config', replacing accounts with the same pubkeys
+    pub fn add_to_genesis(&self, genesis_config: &mut GenesisConfig) {
+        for (pubkey, account) in &self.accounts {
+            genesis_config.add_account(*pubkey, AccountSharedData::from(account.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, tempfile::TempDir};
+
+    #[test]
+    fn test_account_set_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir
+            .path()
+            .join("test")
+            .with_extension(ACCOUNT_SET_FILE_EXTENSION);
+        let owner = Pubkey::new_unique();
+        let account_set = AccountSet {
+            slot: 42,
+            accounts: (0..3)
+                .map(|i| {
+                    (
+                        Pubkey::new_unique(),
+                        Account {
+                            lamports: i + 1,
+                            data: vec![i as u8; i as usize * 10],
+                            owner,
+                            executable: i == 2,
+                            rent_epoch: i,
+                        },
+                    )
+                })
+                .collect(),
+        };
+        account_set.write_to_file(&path).unwrap();
+        let read = AccountSet::read_from_file(&path).unwrap();
+        assert_eq!(read, account_set);
+
+        let mut genesis_config = GenesisConfig::default();
+        read.add_to_genesis(&mut genesis_config);
+        for (pubkey, account) in &account_set.accounts {
+            assert_eq!(genesis_config.accounts.get(pubkey), Some(account));
+        }
+
+        let filter = AccountSetFilter {
+            owners: HashSet::from([owner]),
+            ..AccountSetFilter::default()
+        };
+        assert!(filter.is_match(&Pubkey::new_unique(), &account_set.accounts[0].1));
+        assert!(!AccountSetFilter::default()
+            .is_match(&account_set.accounts[0].0, &account_set.accounts[0].1));
+
+        std::fs::write(&path, b"not an account set").unwrap();
+        assert!(AccountSet::read_from_file(&path).is_err());
+    }
+}
@@ -117,6 +117,18 @@ impl AccountStorage {
             .is_none());
     }
 
+    /// replace the append vec at 'slot' with 'new_store', which holds the same accounts at the same offsets.
+    /// 'new_store' has the same id, so the accounts index does not change.
+    pub(crate) fn replace(&self, slot: Slot, new_store: Arc<AccountStorageEntry>) {
+        assert!(self.shrink_in_progress_map.is_empty());
+        let mut entry = self
+            .map
+            .get_mut(&slot)
+            .expect("no pre-existing storage for replaced slot");
+        assert_eq!(entry.id, new_store.append_vec_id());
+        entry.storage = new_store;
+    }
+
     /// called when shrinking begins on a slot and append vec.
     /// When 'ShrinkInProgress' is dropped by caller, the old store will be removed from the storage map.
     /// Fails if there are no existing stores at the slot.
@@ -395,6 +407,27 @@ pub(crate) mod tests {
         storage.insert(0, sample);
     }
 
+    #[test]
+    fn test_replace() {
+        let storage = AccountStorage::default();
+        let id = 1;
+        storage.insert(0, storage.get_test_storage_with_id(id));
+        let sample = storage.get_test_storage_with_id(id);
+        storage.replace(0, sample.clone());
+        assert!(Arc::ptr_eq(
+            &sample,
+            &storage.get_account_storage_entry(0, id).unwrap()
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "left == right")]
+    fn test_replace_fail_different_id() {
+        let storage = AccountStorage::default();
+        storage.insert(0, storage.get_test_storage_with_id(1));
+        storage.replace(0, storage.get_test_storage_with_id(2));
+    }
+
     #[test]
     #[should_panic(expected = "duplicate call")]
     fn test_shrinking_in_progress_fail3() {
@@ -343,9 +343,12 @@ impl Accounts {
                                     programdata_address,
                                 }) = account.state()
                                 {
-                                    if let Some((programdata_account, _)) = self
-                                        .accounts_db
-                                        .load_with_fixed_root(ancestors, &programdata_address)
+                                    if let Some(programdata_account) = self
+                                        .load_with_fixed_root_or_override(
+                                            ancestors,
+                                            &programdata_address,
+                                            account_overrides,
+                                        )
                                     {
                                         account_deps
                                             .push((programdata_address, programdata_account));
@@ -391,6 +394,7 @@ impl Accounts {
                         &mut accounts,
                         instruction.program_id_index as IndexOfAccount,
                         error_counters,
+                        account_overrides,
                     )
                 })
                 .collect::<Result<Vec<Vec<IndexOfAccount>>>>()?;
@@ -459,6 +463,7 @@ impl Accounts {
         accounts: &mut Vec<TransactionAccount>,
         mut program_account_index: IndexOfAccount,
         error_counters: &mut TransactionErrorMetrics,
+        account_overrides: Option<&AccountOverrides>,
     ) -> Result<Vec<IndexOfAccount>> {
         let mut account_indices = Vec::new();
         let mut program_id = match accounts.get(program_account_index as usize) {
@@ -476,11 +481,12 @@ impl Accounts {
             }
             depth += 1;
 
-            program_account_index = match self
-                .accounts_db
-                .load_with_fixed_root(ancestors, &program_id)
-            {
-                Some((program_account, _)) => {
+            program_account_index = match self.load_with_fixed_root_or_override(
+                ancestors,
+                &program_id,
+                account_overrides,
+            ) {
+                Some(program_account) => {
                     let account_index = accounts.len() as IndexOfAccount;
                     accounts.push((program_id, program_account));
                     account_index
@@ -505,11 +511,12 @@ impl Accounts {
                     programdata_address,
                 }) = program.state()
                 {
-                    let programdata_account_index = match self
-                        .accounts_db
-                        .load_with_fixed_root(ancestors, &programdata_address)
-                    {
-                        Some((programdata_account, _)) => {
+                    let programdata_account_index = match self.load_with_fixed_root_or_override(
+                        ancestors,
+                        &programdata_address,
+                        account_overrides,
+                    ) {
+                        Some(programdata_account) => {
                             let account_index = accounts.len() as IndexOfAccount;
                             accounts.push((programdata_address, programdata_account));
                             account_index
@@ -531,6 +538,23 @@ impl Accounts {
         Ok(account_indices)
     }
 
+    /// the account at 'pubkey' from 'account_overrides' if it's overridden there, otherwise from
+    /// the accounts db
+    fn load_with_fixed_root_or_override(
+        &self,
+        ancestors: &Ancestors,
+        pubkey: &Pubkey,
+        account_overrides: Option<&AccountOverrides>,
+    ) -> Option<AccountSharedData> {
+        match account_overrides.and_then(|overrides| overrides.get(pubkey)) {
+            Some(account) => Some(account.clone()),
+            None => self
+                .accounts_db
+                .load_with_fixed_root(ancestors, pubkey)
+                .map(|(account, _)| account),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn load_accounts(
         &self,
@@ -2425,6 +2449,7 @@ mod tests {
                 &mut vec![(keypair.pubkey(), account)],
                 0,
                 &mut error_counters,
+                None,
             ),
             Err(TransactionError::ProgramAccountNotFound)
         );
@@ -26,8 +26,9 @@ use {
         accounts_cache::{AccountsCache, CachedAccount, SlotCache},
         accounts_hash::{
             AccountsDeltaHash, AccountsHash, AccountsHasher, CalcAccountsHashConfig,
-            CalculateHashIntermediate, HashStats, ZeroLamportAccounts,
+            CalculateHashIntermediate, HashStats, ZeroLamportAccounts, MERKLE_FANOUT,
         },
+        accounts_hash_dump::{AccountsHashBin, AccountsHashDump},
         accounts_index::{
             AccountIndexGetResult, AccountSecondaryIndexes, AccountsIndex, AccountsIndexConfig,
             AccountsIndexRootsStats, AccountsIndexScanResult, IndexKey, IndexValue, IsCached,
@@ -53,8 +54,12 @@ use {
         read_only_accounts_cache::ReadOnlyAccountsCache,
         rent_collector::RentCollector,
         rent_paying_accounts_by_partition::RentPayingAccountsByPartition,
+        rooted_account_deltas::{RootedAccountDeltaSender, RootedAccountDeltas},
         sorted_storages::SortedStorages,
         storable_accounts::StorableAccounts,
+        tiered_storage::{
+            write_cold_storage_file, TieredStorageConfig, COLD_STORAGE_FILE_EXTENSION,
+        },
         verify_accounts_hash_in_background::VerifyAccountsHashInBackground,
     },
     blake3::traits::digest::Digest,
@@ -421,6 +426,8 @@ pub const ACCOUNTS_DB_CONFIG_FOR_TESTING: AccountsDbConfig = AccountsDbConfig {
     filler_accounts_config: FillerAccountsConfig::const_default(),
     write_cache_limit_bytes: None,
     ancient_append_vec_offset: None,
+    tiered_storage: None,
+    rooted_account_delta_sender: None,
     skip_initial_hash_calc: false,
     exhaustively_verify_refcounts: false,
 };
@@ -430,6 +437,8 @@ pub const ACCOUNTS_DB_CONFIG_FOR_BENCHMARKS: AccountsDbConfig = AccountsDbConfig
     filler_accounts_config: FillerAccountsConfig::const_default(),
     write_cache_limit_bytes: None,
     ancient_append_vec_offset: None,
+    tiered_storage: None,
+    rooted_account_delta_sender: None,
     skip_initial_hash_calc: false,
     exhaustively_verify_refcounts: false,
 };
@@ -487,6 +496,10 @@ pub struct AccountsDbConfig {
     /// if None, ancient append vecs are set to ANCIENT_APPEND_VEC_DEFAULT_OFFSET
     /// Some(offset) means include slots up to (max_slot - (slots_per_epoch - 'offset'))
     pub ancient_append_vec_offset: Option<i64>,
+    /// Some if ancient append vecs that no longer receive accounts are moved to cold storage
+    pub tiered_storage: Option<TieredStorageConfig>,
+    /// Some if the account writes of each slot are sent here once the slot is rooted
+    pub rooted_account_delta_sender: Option<RootedAccountDeltaSender>,
     pub skip_initial_hash_calc: bool,
     pub exhaustively_verify_refcounts: bool,
 }
@@ -783,6 +796,14 @@ impl<'a> LoadedAccountAccessor<'a> {
     }
 }
 
+/// An account stored in a slot, with the hash it contributes to the slot's accounts delta hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PubkeyHashAccount {
+    pub pubkey: Pubkey,
+    pub hash: Hash,
+    pub account: AccountSharedData,
+}
+
 pub enum LoadedAccount<'a> {
     Stored(StoredAccountMeta<'a>),
     Cached(Cow<'a, CachedAccount>),
@@ -1188,6 +1209,10 @@ pub const EXPIRATION_TTL_SECONDS: u64 = 1800;
 
 impl RecycleStores {
     fn add_entry(&mut self, new_entry: Arc<AccountStorageEntry>) {
+        if new_entry.accounts.is_cold() {
+            // cold storage is read only, so it cannot be recycled
+            return;
+        }
         self.total_bytes += new_entry.total_bytes();
         self.entries.push((Instant::now(), new_entry))
     }
@@ -1199,6 +1224,9 @@ impl RecycleStores {
     fn add_entries(&mut self, new_entries: Vec<Arc<AccountStorageEntry>>) {
         let now = Instant::now();
         for new_entry in new_entries {
+            if new_entry.accounts.is_cold() {
+                continue;
+            }
             self.total_bytes += new_entry.total_bytes();
             self.entries.push((now, new_entry));
         }
@@ -1270,6 +1298,9 @@ pub struct AccountsDb {
     /// Some(offset) means for slots up to (max_slot - (slots_per_epoch - 'offset')), put them in ancient append vecs
     pub ancient_append_vec_offset: Option<i64>,
 
+    /// Some iff finished ancient append vecs are rewritten as compressed cold storage files
+    pub tiered_storage: Option<TieredStorageConfig>,
+
     /// true iff we want to skip the initial hash calculation on startup
     pub skip_initial_hash_calc: bool,
 
@@ -1367,6 +1398,9 @@ pub struct AccountsDb {
     /// GeyserPlugin accounts update notifier
     accounts_update_notifier: Option<AccountsUpdateNotifier>,
 
+    /// Some if account writes are buffered until their slot is rooted
+    pub(crate) rooted_account_deltas: Option<RootedAccountDeltas>,
+
     filler_accounts_config: FillerAccountsConfig,
     pub filler_account_suffix: Option<Pubkey>,
 
@@ -1829,6 +1863,8 @@ struct ShrinkAncientStats {
     random_shrink: AtomicU64,
     slots_considered: AtomicU64,
     ancient_scanned: AtomicU64,
+    cold_storages_created: AtomicU64,
+    cold_storage_written_bytes: AtomicU64,
 }
 
 #[derive(Debug, Default)]
@@ -2095,6 +2131,16 @@ impl ShrinkAncientStats {
                     self.ancient_scanned.swap(0, Ordering::Relaxed) as i64,
                     i64
                 ),
+                (
+                    "cold_storages_created",
+                    self.cold_storages_created.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
+                (
+                    "cold_storage_written_bytes",
+                    self.cold_storage_written_bytes.swap(0, Ordering::Relaxed) as i64,
+                    i64
+                ),
                 (
                     "total_us",
                     self.total_us.swap(0, Ordering::Relaxed) as i64,
@@ -2298,6 +2344,7 @@ impl AccountsDb {
             accounts_hash_complete_one_epoch_old: RwLock::default(),
             skip_initial_hash_calc: false,
             ancient_append_vec_offset: None,
+            tiered_storage: None,
             accounts_index,
             storage: AccountStorage::default(),
             accounts_cache: AccountsCache::default(),
@@ -2341,6 +2388,7 @@ impl AccountsDb {
             dirty_stores: DashMap::default(),
             zero_lamport_accounts_to_purge_after_full_snapshot: DashSet::default(),
             accounts_update_notifier: None,
+            rooted_account_deltas: None,
             filler_accounts_config: FillerAccountsConfig::default(),
             filler_account_suffix: None,
             log_dead_slots: AtomicBool::new(true),
@@ -2404,6 +2452,25 @@ impl AccountsDb {
             .map(|config| config.ancient_append_vec_offset)
             .unwrap_or(ANCIENT_APPEND_VEC_DEFAULT_OFFSET);
 
+        let tiered_storage = accounts_db_config
+            .as_ref()
+            .and_then(|config| config.tiered_storage);
+
+        let rooted_account_delta_sender = accounts_db_config
+            .as_ref()
+            .and_then(|config| config.rooted_account_delta_sender.clone());
+        let rooted_account_deltas = (rooted_account_delta_sender.is_some()
+            || accounts_update_notifier
+                .as_ref()
+                .map(|notifier| {
+                    notifier
+                        .read()
+                        .unwrap()
+                        .rooted_account_delta_notifications_enabled()
+                })
+                .unwrap_or_default())
+        .then(|| RootedAccountDeltas::new(rooted_account_delta_sender));
+
         let exhaustively_verify_refcounts = accounts_db_config
             .as_ref()
             .map(|config| config.exhaustively_verify_refcounts)
@@ -2419,10 +2486,12 @@ impl AccountsDb {
             paths,
             skip_initial_hash_calc,
             ancient_append_vec_offset,
+            tiered_storage,
             cluster_type: Some(*cluster_type),
             account_indexes,
             shrink_ratio,
             accounts_update_notifier,
+            rooted_account_deltas,
             filler_accounts_config,
             filler_account_suffix,
             write_cache_limit_bytes: accounts_db_config
@@ -4320,6 +4389,12 @@ impl AccountsDb {
                     .fetch_add(1, Ordering::Relaxed);
                 return true;
             }
+            if accounts.is_cold() {
+                // cold storage is read only, so this slot cannot receive accounts from newer slots.
+                // Newer slots will be squashed into a new ancient append vec.
+                *current_ancient = CurrentAncientAppendVec::default();
+                return false;
+            }
             // this slot is ancient and can become the 'current' ancient for other slots to be squashed into
             *current_ancient = CurrentAncientAppendVec::new(slot, Arc::clone(storage));
             return false; // we're done with this slot - this slot IS the ancient append vec
@@ -4346,7 +4421,7 @@ impl AccountsDb {
         let mut ancient_slot_pubkeys = AncientSlotPubkeys::default();
 
         let len = sorted_slots.len();
-        for slot in sorted_slots {
+        for slot in sorted_slots.iter().copied() {
             let old_storage = match self.get_storage_to_move_to_ancient_append_vec(
                 slot,
                 &mut current_ancient,
@@ -4379,6 +4454,18 @@ impl AccountsDb {
 
         self.handle_dropped_roots_for_ancient(dropped_roots);
 
+        if let Some(config) = self.tiered_storage.as_ref() {
+            // the current ancient append vec may still receive accounts. All others are finished.
+            let current_ancient_slot = current_ancient
+                .slot_and_append_vec
+                .as_ref()
+                .map(|(slot, _)| *slot);
+            sorted_slots
+                .into_iter()
+                .filter(|slot| Some(*slot) != current_ancient_slot)
+                .for_each(|slot| self.move_ancient_to_cold_storage(slot, config));
+        }
+
         total.stop();
         self.shrink_ancient_stats
             .total_us
@@ -4391,6 +4478,95 @@ impl AccountsDb {
         }
     }
 
+    /// rewrite the ancient append vec at 'slot' as a compressed cold storage file.
+    /// The cold storage keeps the id and offsets of the append vec, so the accounts index is unchanged.
+    fn move_ancient_to_cold_storage(&self, slot: Slot, config: &TieredStorageConfig) {
+        let old_storage = match self.storage.get_slot_storage_entry(slot) {
+            Some(storage) if is_ancient(&storage.accounts) && !storage.accounts.is_cold() => {
+                storage
+            }
+            _ => return,
+        };
+        self.move_to_cold_storage(slot, &old_storage, config);
+    }
+
+    /// replace the storage of 'slot' by a cold storage file holding the same contents
+    fn move_to_cold_storage(
+        &self,
+        slot: Slot,
+        old_storage: &Arc<AccountStorageEntry>,
+        config: &TieredStorageConfig,
+    ) {
+        let new_storage = match Self::create_cold_storage(old_storage, config) {
+            Ok(new_storage) => new_storage,
+            Err(err) => {
+                warn!("ancient_append_vec: failed to move slot {slot} to cold storage: {err}");
+                return;
+            }
+        };
+        self.shrink_ancient_stats
+            .cold_storages_created
+            .fetch_add(1, Ordering::Relaxed);
+        self.shrink_ancient_stats
+            .cold_storage_written_bytes
+            .fetch_add(
+                std::fs::metadata(new_storage.get_path())
+                    .map(|m| m.len())
+                    .unwrap_or_default(),
+                Ordering::Relaxed,
+            );
+
+        {
+            // hold the status lock so no account can be removed from the old storage while its
+            // counts are copied and it is replaced
+            let count_and_status = old_storage.count_and_status.write().unwrap();
+            *new_storage.count_and_status.write().unwrap() =
+                (count_and_status.0, AccountStorageStatus::Full);
+            new_storage
+                .approx_store_count
+                .store(old_storage.approx_stored_count(), Ordering::Relaxed);
+            new_storage
+                .alive_bytes
+                .store(old_storage.alive_bytes(), Ordering::SeqCst);
+            self.storage.replace(slot, Arc::new(new_storage));
+        }
+        info!(
+            "ancient_append_vec: moved slot {} to cold storage, {} bytes",
+            slot,
+            old_storage.written_bytes()
+        );
+    }
+
+    /// write the contents of 'storage' to a cold storage file next to its append vec and open it,
+    /// with the same slot and id
+    fn create_cold_storage(
+        storage: &AccountStorageEntry,
+        config: &TieredStorageConfig,
+    ) -> Result<AccountStorageEntry, IoError> {
+        let (slot, id) = (storage.slot(), storage.append_vec_id());
+        let path = storage.get_path().with_file_name(format!(
+            "{}.{}",
+            AppendVec::file_name(slot, id),
+            COLD_STORAGE_FILE_EXTENSION
+        ));
+        let accounts = storage
+            .flush()
+            .and_then(|_| write_cold_storage_file(&storage.accounts, &path, config))
+            .and_then(|_| AppendVec::new_from_cold_file(&path));
+        match accounts {
+            Ok((accounts, num_accounts)) => Ok(AccountStorageEntry::new_existing(
+                slot,
+                id,
+                accounts,
+                num_accounts,
+            )),
+            Err(err) => {
+                let _ = std::fs::remove_file(&path);
+                Err(err)
+            }
+        }
+    }
+
     /// put entire alive contents of 'old_storage' into the current ancient append vec or a newly created ancient append vec
     fn combine_one_store_into_ancient(
         &self,
@@ -5811,6 +5987,11 @@ impl AccountsDb {
             "Trying to remove accounts for rooted slots {rooted_slots:?}"
         );
 
+        if let Some(rooted_account_deltas) = &self.rooted_account_deltas {
+            // another version of these slots may be replayed, their writes must not be mixed
+            rooted_account_deltas.discard(remove_slots.iter().map(|(slot, _)| *slot));
+        }
+
         let RemoveUnrootedSlotsSynchronization {
             slots_under_contention,
             signal,
@@ -6782,6 +6963,109 @@ impl AccountsDb {
         Ok((accounts_hash, total_lamports))
     }
 
+    /// Calculate the accounts hash the way `calculate_accounts_hash_from_index` does, along with
+    /// the number of accounts, lamports and merkle root of the accounts of each of 'bins' pubkey
+    /// bins. The lamports and hash of each account in 'account_bins' are included too, so two dumps
+    /// can be compared down to the accounts.
+    pub fn dump_accounts_hash(
+        &self,
+        max_slot: Slot,
+        ancestors: &Ancestors,
+        bins: usize,
+        account_bins: &BTreeSet<usize>,
+    ) -> AccountsHashDump {
+        let keys: Vec<_> = self
+            .accounts_index
+            .account_maps
+            .iter()
+            .flat_map(|map| {
+                let mut keys = map.keys();
+                keys.sort_unstable(); // hashmap is not ordered, but bins are relative to each other
+                keys
+            })
+            .collect();
+
+        let get_accounts = || {
+            keys.par_chunks(MERKLE_FANOUT.pow(4))
+                .map(|pubkeys| {
+                    pubkeys
+                        .iter()
+                        .filter(|pubkey| !self.is_filler_account(pubkey))
+                        .filter_map(|pubkey| {
+                            match self
+                                .accounts_index
+                                .get(pubkey, Some(ancestors), Some(max_slot))
+                            {
+                                AccountIndexGetResult::Found(lock, index) => {
+                                    let (slot, account_info) = &lock.slot_list()[index];
+                                    if account_info.is_zero_lamport() {
+                                        return None;
+                                    }
+                                    self.get_account_accessor(
+                                        *slot,
+                                        pubkey,
+                                        &account_info.storage_location(),
+                                    )
+                                    .get_loaded_account()
+                                    .map(|loaded_account| {
+                                        (
+                                            *pubkey,
+                                            loaded_account.lamports(),
+                                            loaded_account.loaded_hash(),
+                                        )
+                                    })
+                                }
+                                AccountIndexGetResult::NotFound => None,
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .flatten()
+                .collect::<Vec<_>>()
+        };
+        let accounts = self.thread_pool_clean.install(get_accounts);
+
+        let bin_calculator = PubkeyBinCalculator24::new(bins);
+        let mut accounts_by_bin = vec![vec![]; bins];
+        for account in &accounts {
+            accounts_by_bin[bin_calculator.bin_from_pubkey(&account.0)].push(account);
+        }
+
+        let mut dump = AccountsHashDump {
+            slot: max_slot,
+            account_bins: account_bins.clone(),
+            ..AccountsHashDump::default()
+        };
+        for (bin, accounts) in accounts_by_bin.into_iter().enumerate() {
+            let lamports = Self::checked_sum_for_capitalization(
+                accounts.iter().map(|(_, lamports, _)| *lamports),
+            );
+            if account_bins.contains(&bin) {
+                dump.accounts.extend(
+                    accounts
+                        .iter()
+                        .map(|(pubkey, lamports, hash)| (*pubkey, (bin, (*lamports, *hash)))),
+                );
+            }
+            dump.bins.push(AccountsHashBin {
+                count: accounts.len() as u64,
+                lamports,
+                hash: if accounts.is_empty() {
+                    Hash::default()
+                } else {
+                    AccountsHasher::compute_merkle_root_loop(accounts, MERKLE_FANOUT, |account| {
+                        &account.2
+                    })
+                },
+            });
+        }
+        dump.total_lamports =
+            Self::checked_sum_for_capitalization(dump.bins.iter().map(|bin| bin.lamports));
+        dump.accounts_hash =
+            AccountsHasher::calculate_hash(vec![accounts.into_iter().map(|a| a.2).collect()]).0;
+        dump
+    }
+
     pub fn get_accounts_hash(&self, slot: Slot) -> AccountsHash {
         let bank_hashes = self.bank_hashes.read().unwrap();
         let bank_hash_info = bank_hashes.get(&slot).unwrap();
@@ -7514,6 +7798,44 @@ impl AccountsDb {
         (hashes, scan.as_us(), accumulate)
     }
 
+    /// Returns the accounts stored in `slot` along with their hashes, which are the inputs of
+    /// the slot's accounts delta hash, sorted by pubkey
+    pub fn get_pubkey_hash_account_for_slot(&self, slot: Slot) -> Vec<PubkeyHashAccount> {
+        let scan_result: ScanStorageResult<
+            PubkeyHashAccount,
+            DashMap<Pubkey, (Hash, AccountSharedData)>,
+        > = self.scan_account_storage(
+            slot,
+            |loaded_account: LoadedAccount| {
+                // Cache only has one version per key, don't need to worry about versioning
+                Some(PubkeyHashAccount {
+                    pubkey: *loaded_account.pubkey(),
+                    hash: loaded_account.loaded_hash(),
+                    account: loaded_account.take_account(),
+                })
+            },
+            |accum: &DashMap<Pubkey, (Hash, AccountSharedData)>, loaded_account: LoadedAccount| {
+                let pubkey = *loaded_account.pubkey();
+                let loaded_hash = loaded_account.loaded_hash();
+                accum.insert(pubkey, (loaded_hash, loaded_account.take_account()));
+            },
+        );
+
+        let mut accounts: Vec<_> = match scan_result {
+            ScanStorageResult::Cached(cached_result) => cached_result,
+            ScanStorageResult::Stored(stored_result) => stored_result
+                .into_iter()
+                .map(|(pubkey, (hash, account))| PubkeyHashAccount {
+                    pubkey,
+                    hash,
+                    account,
+                })
+                .collect(),
+        };
+        accounts.sort_unstable_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        accounts
+    }
+
     /// Calculate accounts delta hash for `slot`
     ///
     /// As part of calculating the accounts delta hash, get a list of accounts modified this slot
@@ -9196,6 +9518,7 @@ pub mod tests {
             append_vec::{test_utils::TempFile, AccountMeta, StoredMeta},
             cache_hash_data_stats::CacheHashDataStats,
             inline_spl_token,
+            tiered_storage::DEFAULT_TIERED_STORAGE_COMPRESSION_LEVEL,
         },
         assert_matches::assert_matches,
         itertools::Itertools,
@@ -10027,6 +10350,26 @@ pub mod tests {
 
         let expected_accounts_hash = AccountsHash(expected_hash);
         assert_eq!(result, (expected_accounts_hash, sum));
+
+        // the same storages from cold storage
+        let cold_storages = storages
+            .iter()
+            .map(|storage| {
+                let cold = AccountsDb::create_cold_storage(storage, &TEST_TIERED_STORAGE_CONFIG);
+                Arc::new(cold.unwrap())
+            })
+            .collect::<Vec<_>>();
+        assert!(cold_storages
+            .iter()
+            .all(|storage| storage.accounts.is_cold()));
+        let result = db
+            .calculate_accounts_hash_from_storages(
+                &CalcAccountsHashConfig::default(),
+                &get_storage_refs(&cold_storages),
+                HashStats::default(),
+            )
+            .unwrap();
+        assert_eq!(result, (expected_accounts_hash, sum));
     }
 
     fn sample_storage() -> (Vec<Arc<AccountStorageEntry>>, usize, Slot) {
@@ -11422,7 +11765,7 @@ pub mod tests {
 
     #[test]
     fn test_accounts_db_serialize1() {
-        for pass in 0..2 {
+        for (pass, tiered_storage) in [(0, false), (1, false), (1, true)] {
             solana_logger::setup();
             let accounts = AccountsDb::new_single_for_tests();
             let mut pubkeys: Vec<Pubkey> = vec![];
@@ -11495,6 +11838,16 @@ pub mod tests {
             check_storage(&accounts, 1, 11);
             check_storage(&accounts, 2, 31);
 
+            if tiered_storage {
+                // snapshot the accounts from cold storage
+                accounts.move_slots_to_cold_storage_for_tests(0..=latest_slot);
+                check_storage(&accounts, 0, 78);
+                check_storage(&accounts, 1, 11);
+                check_storage(&accounts, 2, 31);
+                check_accounts(&accounts, &pubkeys[35..], 0, 65, 37);
+                check_accounts(&accounts, &pubkeys1, 1, 10, 1);
+            }
+
             let daccounts = reconstruct_accounts_db_via_serialization(&accounts, latest_slot);
 
             assert_eq!(
@@ -12111,6 +12464,50 @@ pub mod tests {
         assert_eq!(bank_hash.stats.num_executable_accounts, 1);
     }
 
+    #[test]
+    fn test_get_pubkey_hash_account_for_slot() {
+        let db = AccountsDb::new(Vec::new(), &ClusterType::Development);
+        let slot: Slot = 1;
+        let mut accounts: Vec<_> = (0..3)
+            .map(|lamports| {
+                (
+                    solana_sdk::pubkey::new_rand(),
+                    AccountSharedData::new(lamports + 1, 4, &Pubkey::default()),
+                )
+            })
+            .collect();
+        for (pubkey, account) in &accounts {
+            db.store_for_tests(slot, &[(pubkey, account)]);
+        }
+        // the latest version of an account is the one returned
+        accounts[0].1.set_lamports(42);
+        db.store_for_tests(slot, &[(&accounts[0].0, &accounts[0].1)]);
+        accounts.sort_unstable_by_key(|(pubkey, _)| *pubkey);
+
+        let check = |db: &AccountsDb| {
+            let pubkey_hash_accounts = db.get_pubkey_hash_account_for_slot(slot);
+            let mut pubkey_hashes = db.get_pubkey_hash_for_slot(slot).0;
+            pubkey_hashes.sort_unstable_by_key(|(pubkey, _)| *pubkey);
+            assert_eq!(
+                pubkey_hash_accounts
+                    .iter()
+                    .map(|item| (item.pubkey, item.account.clone()))
+                    .collect::<Vec<_>>(),
+                accounts
+            );
+            assert_eq!(
+                pubkey_hash_accounts
+                    .iter()
+                    .map(|item| (item.pubkey, item.hash))
+                    .collect::<Vec<_>>(),
+                pubkey_hashes
+            );
+        };
+        check(&db);
+        db.add_root_and_flush_write_cache(slot);
+        check(&db);
+    }
+
     // this test tests check_hash=true, which is unsupported behavior at the moment. It cannot be enabled by anything but these tests.
     #[ignore]
     #[test]
@@ -15778,7 +16175,24 @@ pub mod tests {
         assert_eq!(result, expected_alive_roots, "extra: {extra}");
     }
 
+    /// block size small enough for the test accounts to span several blocks
+    const TEST_TIERED_STORAGE_CONFIG: TieredStorageConfig = TieredStorageConfig {
+        compression_level: DEFAULT_TIERED_STORAGE_COMPRESSION_LEVEL,
+        block_size: 1024,
+    };
+
     impl AccountsDb {
+        /// useful to run tests written prior to introduction of tiered storage against cold
+        /// storage, whether or not the storages are ancient
+        fn move_slots_to_cold_storage_for_tests(&self, slots: impl Iterator<Item = Slot>) {
+            for slot in slots {
+                if let Some(storage) = self.storage.get_slot_storage_entry(slot) {
+                    self.move_to_cold_storage(slot, &storage, &TEST_TIERED_STORAGE_CONFIG);
+                    assert!(self.get_storage_for_slot(slot).unwrap().accounts.is_cold());
+                }
+            }
+        }
+
         /// useful to adapt tests written prior to introduction of the write cache
         /// to use the write cache
         pub fn add_root_and_flush_write_cache(&self, slot: Slot) {
@@ -17176,6 +17590,80 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_combine_ancient_slots_tiered_storage() {
+        let num_slots = 3;
+        let (mut db, slot1) = create_db_with_storages_and_index(true, num_slots);
+        db.tiered_storage = Some(TieredStorageConfig {
+            block_size: 1,
+            ..TieredStorageConfig::default()
+        });
+        let slots = slot1..(slot1 + num_slots as Slot);
+        let originals = get_all_accounts(&db, slots.clone());
+        let accounts_hash = |db: &AccountsDb| {
+            let storages = slots
+                .clone()
+                .filter_map(|slot| db.get_storage_for_slot(slot))
+                .collect::<Vec<_>>();
+            db.calculate_accounts_hash_from_storages(
+                &CalcAccountsHashConfig::default(),
+                &SortedStorages::new(&storages),
+                HashStats::default(),
+            )
+            .unwrap()
+        };
+        let original_accounts_hash = accounts_hash(&db);
+
+        // create ancient append vecs in the first 2 slots
+        db.combine_ancient_slots(vec![slot1], CAN_RANDOMLY_SHRINK_FALSE);
+        db.combine_ancient_slots(vec![slot1 + 1], CAN_RANDOMLY_SHRINK_FALSE);
+        let id = db.get_storage_for_slot(slot1).unwrap().append_vec_id();
+
+        // the last slot is combined into the ancient append vec of the second slot.
+        // the ancient append vec of the first slot is finished, so it is moved to cold storage.
+        db.combine_ancient_slots(slots.clone().collect(), CAN_RANDOMLY_SHRINK_FALSE);
+        let cold = db.get_storage_for_slot(slot1).unwrap();
+        assert!(cold.accounts.is_cold());
+        assert!(is_ancient(&cold.accounts));
+        assert_eq!(cold.append_vec_id(), id);
+        assert!(!db
+            .get_storage_for_slot(slot1 + 1)
+            .unwrap()
+            .accounts
+            .is_cold());
+        assert!(db.get_storage_for_slot(slot1 + 2).is_none());
+
+        compare_all_accounts(&originals, &get_all_accounts(&db, slots.clone()));
+        let ancestors = Ancestors::from(slots.clone().collect::<Vec<_>>());
+        for (pubkey, account) in &originals {
+            let (loaded, _) = db.load_without_fixed_root(&ancestors, pubkey).unwrap();
+            assert!(accounts_equal(account, &loaded));
+        }
+        assert_eq!(accounts_hash(&db), original_accounts_hash);
+
+        // cold storage is never combined into
+        db.combine_ancient_slots(slots.clone().collect(), CAN_RANDOMLY_SHRINK_FALSE);
+        assert!(db.get_storage_for_slot(slot1).unwrap().accounts.is_cold());
+        assert_eq!(accounts_hash(&db), original_accounts_hash);
+
+        // snapshots hold the decompressed contents of cold storage
+        let snapshot_slot = slot1 + 1;
+        db.add_root(slot1);
+        db.add_root(snapshot_slot);
+        db.bank_hashes
+            .write()
+            .unwrap()
+            .insert(snapshot_slot, BankHashInfo::default());
+        let db = reconstruct_accounts_db_via_serialization(&db, snapshot_slot);
+        assert!(!db.get_storage_for_slot(slot1).unwrap().accounts.is_cold());
+        compare_all_accounts(&originals, &get_all_accounts(&db, slots.clone()));
+        for (pubkey, account) in &originals {
+            let (loaded, _) = db.load_without_fixed_root(&ancestors, pubkey).unwrap();
+            assert!(accounts_equal(account, &loaded));
+        }
+        assert_eq!(accounts_hash(&db), original_accounts_hash);
+    }
+
     fn populate_index(db: &AccountsDb, slots: Range<Slot>) {
         slots.into_iter().for_each(|slot| {
             if let Some(storage) = db.get_storage_for_slot(slot) {
@@ -39,24 +39,32 @@ impl AccountsDb {
     /// Notify the plugins of of account data when AccountsDb is restored from a snapshot. The data is streamed
     /// in the reverse order of the slots so that an account is only streamed once. At a slot, if the accounts is updated
     /// multiple times only the last write (with highest write_version) is notified.
+    /// Without account data notifications, e.g. if only rooted account deltas are notified, the
+    /// accounts are not scanned and only the end of the restore is notified.
     pub fn notify_account_restore_from_snapshot(&self) {
-        if self.accounts_update_notifier.is_none() {
-            return;
-        }
-
-        let mut slots = self.storage.all_slots();
-        let mut notified_accounts: HashSet<Pubkey> = HashSet::default();
-        let mut notify_stats = GeyserPluginNotifyAtSnapshotRestoreStats::default();
+        let accounts_update_notifier = match &self.accounts_update_notifier {
+            Some(accounts_update_notifier) => accounts_update_notifier,
+            None => return,
+        };
 
-        slots.sort_by(|a, b| b.cmp(a));
-        for slot in slots {
-            self.notify_accounts_in_slot(slot, &mut notified_accounts, &mut notify_stats);
+        let account_data_notifications_enabled = accounts_update_notifier
+            .read()
+            .unwrap()
+            .account_data_notifications_enabled();
+        if account_data_notifications_enabled {
+            let mut slots = self.storage.all_slots();
+            let mut notified_accounts: HashSet<Pubkey> = HashSet::default();
+            let mut notify_stats = GeyserPluginNotifyAtSnapshotRestoreStats::default();
+
+            slots.sort_by(|a, b| b.cmp(a));
+            for slot in slots {
+                self.notify_accounts_in_slot(slot, &mut notified_accounts, &mut notify_stats);
+            }
+            notify_stats.report();
         }
 
-        let accounts_update_notifier = self.accounts_update_notifier.as_ref().unwrap();
         let notifier = &accounts_update_notifier.read().unwrap();
         notifier.notify_end_of_restore_from_snapshot();
-        notify_stats.report();
     }
 
     pub fn notify_account_at_accounts_update<P>(
@@ -69,16 +77,48 @@ impl AccountsDb {
     ) where
         P: Iterator<Item = u64>,
     {
+        if self.accounts_update_notifier.is_none() && self.rooted_account_deltas.is_none() {
+            return;
+        }
+        let write_version = write_version_producer.next().unwrap();
         if let Some(accounts_update_notifier) = &self.accounts_update_notifier {
             let notifier = &accounts_update_notifier.read().unwrap();
-            notifier.notify_account_update(
-                slot,
-                account,
-                txn_signature,
-                pubkey,
-                write_version_producer.next().unwrap(),
-            );
+            if notifier.account_data_notifications_enabled() {
+                notifier.notify_account_update(slot, account, txn_signature, pubkey, write_version);
+            }
+        }
+        if let Some(rooted_account_deltas) = &self.rooted_account_deltas {
+            rooted_account_deltas.record(slot, pubkey, account, *txn_signature, write_version);
+        }
+    }
+
+    /// Notify the account writes of newly rooted slots, given as (slot, parent) in slot order.
+    /// Writes buffered for older slots that were not rooted are discarded.
+    pub fn notify_rooted_account_deltas(&self, rooted_slots: &[(Slot, Option<Slot>)]) {
+        let rooted_account_deltas = match &self.rooted_account_deltas {
+            Some(rooted_account_deltas) => rooted_account_deltas,
+            None => return,
+        };
+
+        let mut measure = Measure::start("accountsdb-notify-rooted-account-deltas");
+        let deltas = rooted_account_deltas.take_rooted(rooted_slots);
+        let num_accounts: usize = deltas.iter().map(|delta| delta.accounts.len()).sum();
+        for delta in deltas {
+            if let Some(accounts_update_notifier) = &self.accounts_update_notifier {
+                let notifier = &accounts_update_notifier.read().unwrap();
+                if notifier.rooted_account_delta_notifications_enabled() {
+                    notifier.notify_rooted_account_delta(&delta);
+                }
+            }
+            rooted_account_deltas.send(delta);
         }
+        measure.stop();
+        datapoint_debug!(
+            "accountsdb_notify_rooted_account_deltas",
+            ("slots", rooted_slots.len(), i64),
+            ("accounts", num_accounts, i64),
+            ("elapsed_us", measure.as_us(), i64),
+        );
     }
 
     fn notify_accounts_in_slot(
@@ -171,7 +211,9 @@ pub mod tests {
                 AccountsUpdateNotifier, AccountsUpdateNotifierInterface,
             },
             append_vec::StoredAccountMeta,
+            rooted_account_deltas::{RootedAccountDelta, RootedAccountDeltas},
         },
+        crossbeam_channel::unbounded,
         dashmap::DashMap,
         solana_sdk::{
             account::{AccountSharedData, ReadableAccount},
@@ -195,6 +237,8 @@ pub mod tests {
     struct GeyserTestPlugin {
         pub accounts_notified: DashMap<Pubkey, Vec<(Slot, AccountSharedData)>>,
         pub is_startup_done: AtomicBool,
+        pub rooted_account_deltas: RwLock<Vec<RootedAccountDelta>>,
+        pub account_data_notifications_disabled: bool,
     }
 
     impl AccountsUpdateNotifierInterface for GeyserTestPlugin {
@@ -225,6 +269,21 @@ pub mod tests {
         fn notify_end_of_restore_from_snapshot(&self) {
             self.is_startup_done.store(true, Ordering::Relaxed);
         }
+
+        fn notify_rooted_account_delta(&self, delta: &RootedAccountDelta) {
+            self.rooted_account_deltas
+                .write()
+                .unwrap()
+                .push(delta.clone());
+        }
+
+        fn account_data_notifications_enabled(&self) -> bool {
+            !self.account_data_notifications_disabled
+        }
+
+        fn rooted_account_delta_notifications_enabled(&self) -> bool {
+            true
+        }
     }
 
     #[test]
@@ -410,4 +469,110 @@ pub mod tests {
         );
         assert_eq!(notifier.accounts_notified.get(&key3).unwrap()[0].0, slot1);
     }
+
+    #[test]
+    fn test_notify_rooted_account_deltas() {
+        let mut accounts = AccountsDb::new_single_for_tests_with_caching();
+        let notifier = Arc::new(RwLock::new(GeyserTestPlugin::default()));
+        accounts.set_geyser_plugin_notifer(Some(notifier.clone()));
+        let (sender, receiver) = unbounded();
+        accounts.rooted_account_deltas = Some(RootedAccountDeltas::new(Some(sender)));
+
+        // slot 1 is on an abandoned fork, slots 0 and 2 are rooted
+        let key1 = solana_sdk::pubkey::new_rand();
+        let key2 = solana_sdk::pubkey::new_rand();
+        let account =
+            |lamports| AccountSharedData::new(lamports, 1, AccountSharedData::default().owner());
+        accounts.store_cached((0, &[(&key1, &account(1))][..]), None);
+        accounts.store_cached((0, &[(&key1, &account(2))][..]), None);
+        accounts.store_cached((1, &[(&key2, &account(3))][..]), None);
+        accounts.store_cached((2, &[(&key2, &account(4)), (&key1, &account(5))][..]), None);
+        // every write is still notified as it happens
+        assert_eq!(
+            notifier
+                .read()
+                .unwrap()
+                .accounts_notified
+                .get(&key1)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        accounts.notify_rooted_account_deltas(&[(0, None), (2, Some(0))]);
+
+        let check_deltas = |deltas: &[RootedAccountDelta]| {
+            assert_eq!(deltas.len(), 2);
+            assert_eq!((deltas[0].slot, deltas[0].parent), (0, None));
+            assert_eq!(deltas[0].accounts.len(), 1);
+            assert_eq!(deltas[0].accounts[0].pubkey, key1);
+            assert_eq!(deltas[0].accounts[0].account.lamports(), 2);
+            assert_eq!((deltas[1].slot, deltas[1].parent), (2, Some(0)));
+            assert_eq!(
+                deltas[1]
+                    .accounts
+                    .iter()
+                    .map(|account| (account.pubkey, account.account.lamports()))
+                    .collect::<Vec<_>>(),
+                vec![(key2, 4), (key1, 5)]
+            );
+        };
+        check_deltas(
+            &notifier
+                .read()
+                .unwrap()
+                .rooted_account_deltas
+                .read()
+                .unwrap(),
+        );
+        check_deltas(&receiver.try_iter().collect::<Vec<_>>());
+
+        // the writes of the abandoned slot were discarded
+        accounts.notify_rooted_account_deltas(&[(3, Some(2))]);
+        let delta = receiver.try_recv().unwrap();
+        assert_eq!(delta.slot, 3);
+        assert!(delta.accounts.is_empty());
+        assert!(accounts
+            .rooted_account_deltas
+            .as_ref()
+            .unwrap()
+            .take_rooted(&[(1, Some(0))])[0]
+            .accounts
+            .is_empty());
+    }
+
+    #[test]
+    fn test_notify_rooted_account_deltas_only() {
+        let mut accounts = AccountsDb::new_single_for_tests_with_caching();
+        let key1 = solana_sdk::pubkey::new_rand();
+        let account =
+            |lamports| AccountSharedData::new(lamports, 1, AccountSharedData::default().owner());
+        accounts.store_uncached(0, &[(&key1, &account(1))]);
+
+        let notifier = Arc::new(RwLock::new(GeyserTestPlugin {
+            account_data_notifications_disabled: true,
+            ..GeyserTestPlugin::default()
+        }));
+        accounts.set_geyser_plugin_notifer(Some(notifier.clone()));
+        accounts.rooted_account_deltas = Some(RootedAccountDeltas::new(None));
+
+        // the restored accounts are not notified, only the end of the restore
+        accounts.notify_account_restore_from_snapshot();
+        assert!(notifier.read().unwrap().accounts_notified.is_empty());
+        assert!(notifier
+            .read()
+            .unwrap()
+            .is_startup_done
+            .load(Ordering::Relaxed));
+
+        // writes are only notified once rooted
+        accounts.store_cached((1, &[(&key1, &account(2))][..]), None);
+        assert!(notifier.read().unwrap().accounts_notified.is_empty());
+        accounts.notify_rooted_account_deltas(&[(1, Some(0))]);
+        let notifier = notifier.read().unwrap();
+        let rooted_account_deltas = notifier.rooted_account_deltas.read().unwrap();
+        assert_eq!(rooted_account_deltas.len(), 1);
+        assert_eq!(rooted_account_deltas[0].accounts[0].pubkey, key1);
+        assert_eq!(rooted_account_deltas[0].accounts[0].account.lamports(), 2);
+    }
 }
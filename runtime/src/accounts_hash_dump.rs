@@ -0,0 +1,291 @@
+//! A dump of the accounts hash, broken down by pubkey bin
+//!
+//! When two nodes disagree on an accounts hash, comparing the hash, lamports and number of
+//! accounts of each pubkey bin shows which bins differ. Dumping the accounts of these bins then
+//! narrows the mismatch down to individual accounts.
+//!
+//! The dump is a text file, one item per line:
+//! ```text
+//! slot <slot>
+//! accounts_hash <hash>
+//! total_lamports <lamports>
+//! bins <number of bins>
+//! account_bins <comma separated bins whose accounts are dumped>
+//! bin <bin> <count> <lamports> <hash>
+//! account <bin> <pubkey> <lamports> <hash>
+//! ```
+//! Bins without accounts are omitted.
+
+use {
+    solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        io::{self, BufRead, Write},
+        str::FromStr,
+    },
+};
+
+/// The accounts of one pubkey bin
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AccountsHashBin {
+    pub count: u64,
+    pub lamports: u64,
+    /// merkle root of the hashes of the accounts in the bin
+    pub hash: Hash,
+}
+
+/// (lamports, hash) of an account
+pub type AccountsHashDumpAccount = (u64, Hash);
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountsHashDump {
+    pub slot: Slot,
+    pub accounts_hash: Hash,
+    pub total_lamports: u64,
+    /// one per pubkey bin, see `PubkeyBinCalculator24`
+    pub bins: Vec<AccountsHashBin>,
+    /// bins whose accounts are in 'accounts'
+    pub account_bins: BTreeSet<usize>,
+    pub accounts: BTreeMap<Pubkey, (usize, AccountsHashDumpAccount)>,
+}
+
+/// Where two dumps differ
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountsHashDumpDiff {
+    pub mismatched_bins: Vec<usize>,
+    /// accounts of mismatched bins dumped in both dumps, which differ or are missing from one
+    pub mismatched_accounts: Vec<(
+        Pubkey,
+        Option<AccountsHashDumpAccount>,
+        Option<AccountsHashDumpAccount>,
+    )>,
+}
+
+fn invalid_data(line: usize, error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line + 1, error),
+    )
+}
+
+fn parse<T: FromStr>(line: usize, value: Option<&str>) -> io::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .ok_or_else(|| invalid_data(line, "missing value"))?
+        .parse()
+        .map_err(|err| invalid_data(line, err))
+}
+
+impl AccountsHashDump {
+    pub fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "slot {}", self.slot)?;
+        writeln!(writer, "accounts_hash {}", self.accounts_hash)?;
+        writeln!(writer, "total_lamports {}", self.total_lamports)?;
+        writeln!(writer, "bins {}", self.bins.len())?;
+        writeln!(
+            writer,
+            "account_bins {}",
+            self.account_bins
+                .iter()
+                .map(|bin| bin.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        )?;
+        for (bin, accounts) in self.bins.iter().enumerate() {
+            if accounts.count > 0 {
+                writeln!(
+                    writer,
+                    "bin {} {} {} {}",
+                    bin, accounts.count, accounts.lamports, accounts.hash
+                )?;
+            }
+        }
+        for (pubkey, (bin, (lamports, hash))) in &self.accounts {
+            writeln!(writer, "account {bin} {pubkey} {lamports} {hash}")?;
+        }
+        Ok(())
+    }
+
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut dump = AccountsHashDump::default();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut values = line.split_whitespace();
+            match values.next() {
+                None => continue,
+                Some("slot") => dump.slot = parse(i, values.next())?,
+                Some("accounts_hash") => dump.accounts_hash = parse(i, values.next())?,
+                Some("total_lamports") => dump.total_lamports = parse(i, values.next())?,
+                Some("bins") => {
+                    let bins: usize = parse(i, values.next())?;
+                    dump.bins = vec![AccountsHashBin::default(); bins];
+                }
+                Some("account_bins") => {
+                    dump.account_bins = values
+                        .next()
+                        .unwrap_or_default()
+                        .split(',')
+                        .filter(|bin| !bin.is_empty())
+                        .map(|bin| parse(i, Some(bin)))
+                        .collect::<io::Result<_>>()?;
+                }
+                Some("bin") => {
+                    let bin: usize = parse(i, values.next())?;
+                    let accounts = AccountsHashBin {
+                        count: parse(i, values.next())?,
+                        lamports: parse(i, values.next())?,
+                        hash: parse(i, values.next())?,
+                    };
+                    *dump
+                        .bins
+                        .get_mut(bin)
+                        .ok_or_else(|| invalid_data(i, format!("bin {bin} out of range")))? =
+                        accounts;
+                }
+                Some("account") => {
+                    let bin = parse(i, values.next())?;
+                    let pubkey = parse(i, values.next())?;
+                    let account = (parse(i, values.next())?, parse(i, values.next())?);
+                    dump.accounts.insert(pubkey, (bin, account));
+                }
+                Some(key) => return Err(invalid_data(i, format!("unknown key {key}"))),
+            }
+        }
+        Ok(dump)
+    }
+
+    /// compare with 'other', which must use the same number of bins
+    pub fn diff(&self, other: &Self) -> Result<AccountsHashDumpDiff, String> {
+        if self.bins.len() != other.bins.len() {
+            return Err(format!(
+                "the dumps have different numbers of bins: {} and {}",
+                self.bins.len(),
+                other.bins.len()
+            ));
+        }
+        let mismatched_bins: Vec<_> = self
+            .bins
+            .iter()
+            .zip(other.bins.iter())
+            .enumerate()
+            .filter_map(|(bin, (bin1, bin2))| (bin1 != bin2).then_some(bin))
+            .collect();
+
+        let accounts_of_bin = |dump: &'_ Self, bin| {
+            dump.accounts
+                .iter()
+                .filter(move |(_, (account_bin, _))| *account_bin == bin)
+                .map(|(pubkey, (_, account))| (*pubkey, *account))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let mut mismatched_accounts = Vec::default();
+        for bin in &mismatched_bins {
+            if !self.account_bins.contains(bin) || !other.account_bins.contains(bin) {
+                continue;
+            }
+            let accounts1 = accounts_of_bin(self, *bin);
+            let accounts2 = accounts_of_bin(other, *bin);
+            let pubkeys: BTreeSet<_> = accounts1.keys().chain(accounts2.keys()).collect();
+            for pubkey in pubkeys {
+                let account1 = accounts1.get(pubkey).copied();
+                let account2 = accounts2.get(pubkey).copied();
+                if account1 != account2 {
+                    mismatched_accounts.push((*pubkey, account1, account2));
+                }
+            }
+        }
+        Ok(AccountsHashDumpDiff {
+            mismatched_bins,
+            mismatched_accounts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::hash::hash};
+
+    fn new_dump(lamports: &[u64]) -> AccountsHashDump {
+        let bins = 4;
+        let mut dump = AccountsHashDump {
+            slot: 5,
+            accounts_hash: hash(&[1]),
+            total_lamports: lamports.iter().sum(),
+            bins: vec![AccountsHashBin::default(); bins],
+            account_bins: BTreeSet::from([1, 2]),
+            accounts: BTreeMap::default(),
+        };
+        for (i, lamports) in lamports.iter().enumerate() {
+            let bin = i % bins;
+            let pubkey = Pubkey::new_from_array([i as u8; 32]);
+            let account_hash = hash(&lamports.to_le_bytes());
+            dump.bins[bin].count += 1;
+            dump.bins[bin].lamports += lamports;
+            dump.bins[bin].hash =
+                hash(&[dump.bins[bin].hash.as_ref(), account_hash.as_ref()].concat());
+            if dump.account_bins.contains(&bin) {
+                dump.accounts
+                    .insert(pubkey, (bin, (*lamports, account_hash)));
+            }
+        }
+        dump
+    }
+
+    #[test]
+    fn test_accounts_hash_dump_write_read() {
+        let dump = new_dump(&[1, 2, 3, 4, 5, 6]);
+        let mut buffer = Vec::default();
+        dump.write(&mut buffer).unwrap();
+        assert_eq!(AccountsHashDump::read(&buffer[..]).unwrap(), dump);
+
+        let empty = AccountsHashDump {
+            bins: vec![AccountsHashBin::default(); 2],
+            ..AccountsHashDump::default()
+        };
+        let mut buffer = Vec::default();
+        empty.write(&mut buffer).unwrap();
+        assert_eq!(AccountsHashDump::read(&buffer[..]).unwrap(), empty);
+
+        assert!(AccountsHashDump::read(
+            &b"bins 2\nbin 2 1 1 11111111111111111111111111111111\n"[..]
+        )
+        .is_err());
+        assert!(AccountsHashDump::read(&b"slot x\n"[..]).is_err());
+        assert!(AccountsHashDump::read(&b"unknown 1\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_accounts_hash_dump_diff() {
+        let dump1 = new_dump(&[1, 2, 3, 4, 5, 6]);
+        assert_eq!(dump1.diff(&dump1).unwrap(), AccountsHashDumpDiff::default());
+
+        // accounts 1 and 5 are in bin 1, account 3 in bin 3
+        let dump2 = new_dump(&[1, 20, 3, 40, 5]);
+        let diff = dump1.diff(&dump2).unwrap();
+        assert_eq!(diff.mismatched_bins, vec![1, 3]);
+        // accounts of bin 3 were not dumped
+        assert_eq!(
+            diff.mismatched_accounts,
+            vec![
+                (
+                    Pubkey::new_from_array([1; 32]),
+                    Some((2, hash(&2u64.to_le_bytes()))),
+                    Some((20, hash(&20u64.to_le_bytes())))
+                ),
+                (
+                    Pubkey::new_from_array([5; 32]),
+                    Some((6, hash(&6u64.to_le_bytes()))),
+                    None
+                ),
+            ]
+        );
+
+        let dump3 = AccountsHashDump {
+            bins: vec![AccountsHashBin::default(); 2],
+            ..AccountsHashDump::default()
+        };
+        assert!(dump1.diff(&dump3).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use {
         accounts_index_storage::{AccountsIndexStorage, Startup},
         ancestors::Ancestors,
         bucket_map_holder::{Age, BucketMapHolder},
+        bucket_map_holder_stats::AccountsIndexMemoryStats,
         contains::Contains,
         in_mem_accounts_index::InMemAccountsIndex,
         inline_spl_token::{self, GenericTokenAccount},
@@ -178,6 +179,8 @@ pub enum IndexLimitMb {
     Unspecified,
     /// limit was specified, use disk index for rest
     Limit(usize),
+    /// budget was specified, use disk index for rest and tune the in-mem index at runtime to stay close to it
+    Budget(usize),
     /// in-mem-only was specified, no disk index
     InMemOnly,
 }
@@ -1559,6 +1562,11 @@ impl<T: IndexValue> AccountsIndex<T> {
         self.account_maps.len()
     }
 
+    /// memory use of the index and the settings used to stay within its memory budget
+    pub fn memory_stats(&self) -> AccountsIndexMemoryStats {
+        self.storage.storage.memory_stats()
+    }
+
     // Same functionally to upsert, but:
     // 1. operates on a batch of items
     // 2. holds the write lock for the duration of adding the items
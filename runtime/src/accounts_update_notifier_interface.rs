@@ -1,5 +1,5 @@
 use {
-    crate::append_vec::StoredAccountMeta,
+    crate::{append_vec::StoredAccountMeta, rooted_account_deltas::RootedAccountDelta},
     solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey, signature::Signature},
     std::sync::{Arc, RwLock},
 };
@@ -21,6 +21,22 @@ pub trait AccountsUpdateNotifierInterface: std::fmt::Debug {
 
     /// Notified when all accounts have been notified when restoring from a snapshot.
     fn notify_end_of_restore_from_snapshot(&self);
+
+    /// Notified once a slot is rooted, with the last write of every account updated in the slot.
+    /// Slots are notified in order, and slots of abandoned forks are never notified.
+    fn notify_rooted_account_delta(&self, _delta: &RootedAccountDelta) {}
+
+    /// true if account writes and the accounts restored from a snapshot should be notified
+    /// through `notify_account_update` and `notify_account_restore_from_snapshot`
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    /// true if account writes should be buffered until their slot is rooted
+    /// and notified through `notify_rooted_account_delta`
+    fn rooted_account_delta_notifications_enabled(&self) -> bool {
+        false
+    }
 }
 
 pub type AccountsUpdateNotifier = Arc<RwLock<dyn AccountsUpdateNotifierInterface + Sync + Send>>;
@@ -86,8 +86,9 @@ pub fn get_ancient_append_vec_capacity() -> u64 {
 }
 
 /// is this a max-size append vec designed to be used as an ancient append vec?
+/// cold storages are always ancient append vecs that were moved to tiered storage
 pub fn is_ancient(storage: &AppendVec) -> bool {
-    storage.capacity() >= get_ancient_append_vec_capacity()
+    storage.is_cold() || storage.capacity() >= get_ancient_append_vec_capacity()
 }
 
 #[cfg(test)]
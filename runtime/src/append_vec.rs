@@ -5,7 +5,10 @@
 //! <https://docs.solana.com/implemented-proposals/persistent-account-storage>
 
 use {
-    crate::storable_accounts::StorableAccounts,
+    crate::{
+        storable_accounts::StorableAccounts,
+        tiered_storage::{ColdStorage, ColdStorageIndex},
+    },
     log::*,
     memmap2::MmapMut,
     serde::{Deserialize, Serialize},
@@ -296,6 +299,10 @@ pub struct AppendVec {
 
     /// True if the file should automatically be deleted when this AppendVec is dropped.
     remove_on_drop: bool,
+
+    /// Some if the contents live in a compressed cold storage file at `path`. `map` is then a
+    /// scratch file that the file's blocks are decompressed into as they are read.
+    cold: Option<ColdStorage>,
 }
 
 lazy_static! {
@@ -371,6 +378,7 @@ impl AppendVec {
             current_len: AtomicUsize::new(initial_len),
             file_size: size as u64,
             remove_on_drop: true,
+            cold: None,
         }
     }
 
@@ -403,10 +411,17 @@ impl AppendVec {
     }
 
     pub fn flush(&self) -> io::Result<()> {
+        if self.is_cold() {
+            return Ok(());
+        }
         self.map.flush()
     }
 
     pub fn reset(&self) {
+        if self.is_cold() {
+            // cold storage is read only, its accounts are never overwritten
+            return;
+        }
         // This mutex forces append to be single threaded, but concurrent with reads
         // See UNSAFE usage in `append_ptr`
         let _lock = self.append_lock.lock().unwrap();
@@ -430,6 +445,11 @@ impl AppendVec {
         self.file_size
     }
 
+    /// true if the contents live in a compressed cold storage file
+    pub fn is_cold(&self) -> bool {
+        self.cold.is_some()
+    }
+
     pub fn file_name(slot: Slot, id: impl std::fmt::Display) -> String {
         format!("{slot}.{id}")
     }
@@ -482,9 +502,59 @@ impl AppendVec {
             current_len: AtomicUsize::new(current_len),
             file_size,
             remove_on_drop: true,
+            cold: None,
         })
     }
 
+    /// Opens a cold storage file written by `tiered_storage::write_cold_storage_file`.
+    /// The returned AppendVec is read only: it holds exactly the contents of the file.
+    pub fn new_from_cold_file<P: AsRef<Path>>(path: P) -> io::Result<(Self, usize)> {
+        let cold = ColdStorage::open(path.as_ref())?;
+        let len = cold.len();
+        Self::sanitize_len_and_size(len, len.max(1))?;
+        let num_accounts = cold.index().accounts.len();
+
+        // Blocks are decompressed in place on first read, pages of blocks never read stay unused.
+        // The scratch file is unlinked, it only backs the map so that the kernel can evict the
+        // decompressed pages like those of any append vec, instead of holding them in memory.
+        let scratch_dir = path
+            .as_ref()
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let scratch = tempfile::tempfile_in(scratch_dir)?;
+        scratch.set_len(len.max(1) as u64)?;
+        let map = unsafe { MmapMut::map_mut(&scratch)? };
+        APPEND_VEC_MMAPPED_FILES_OPEN.fetch_add(1, Ordering::Relaxed);
+
+        Ok((
+            AppendVec {
+                path: path.as_ref().to_path_buf(),
+                map,
+                append_lock: Mutex::new(()),
+                current_len: AtomicUsize::new(len),
+                file_size: len as u64,
+                remove_on_drop: true,
+                cold: Some(cold),
+            },
+            num_accounts,
+        ))
+    }
+
+    /// the index of the cold storage file holding the contents, if any
+    pub fn cold_index(&self) -> Option<&ColdStorageIndex> {
+        self.cold.as_ref().map(|cold| cold.index())
+    }
+
+    /// Write the `len()` bytes of contents to `writer`.
+    /// Cold contents are decompressed as they are written and not kept in memory.
+    pub fn write_contents(&self, writer: &mut impl Write) -> io::Result<()> {
+        match &self.cold {
+            Some(cold) => cold.write_contents(writer),
+            None => writer.write_all(&self.map[..self.len()]),
+        }
+    }
+
     fn sanitize_layout_and_length(&self) -> (bool, usize) {
         let mut offset = 0;
 
@@ -510,11 +580,16 @@ impl AppendVec {
     /// doesn't overrun the internal buffer. Otherwise return None.
     /// Also return the offset of the first byte after the requested data that
     /// falls on a 64-byte boundary.
-    fn get_slice(&self, offset: usize, size: usize) -> Option<(&[u8], usize)> {
+    pub(crate) fn get_slice(&self, offset: usize, size: usize) -> Option<(&[u8], usize)> {
         let (next, overflow) = offset.overflowing_add(size);
         if overflow || next > self.len() {
             return None;
         }
+        if let Some(cold) = &self.cold {
+            //UNSAFE: self.map is len() bytes long and lives as long as self.cold.
+            //Blocks are only written by 'load', before any reader sees their contents.
+            unsafe { cold.load(offset..next, self.map.as_ptr() as *mut u8) };
+        }
         let data = &self.map[offset..next];
         let next = u64_align!(next);
 
@@ -716,7 +791,10 @@ pub mod tests {
             self.current_len.store(len, Ordering::Release);
         }
 
-        fn append_account_test(&self, data: &(StoredMeta, AccountSharedData)) -> Option<usize> {
+        pub(crate) fn append_account_test(
+            &self,
+            data: &(StoredMeta, AccountSharedData),
+        ) -> Option<usize> {
             let slot_ignored = Slot::MAX;
             let accounts = [(&data.0.pubkey, &data.1)];
             let slice = &accounts[..];
@@ -49,6 +49,7 @@ use {
             ACCOUNTS_DB_CONFIG_FOR_TESTING,
         },
         accounts_hash::AccountsHash,
+        accounts_hash_dump::AccountsHashDump,
         accounts_index::{AccountSecondaryIndexes, IndexKey, ScanConfig, ScanResult, ZeroLamport},
         accounts_update_notifier_interface::AccountsUpdateNotifier,
         ancestors::{Ancestors, AncestorsForSerialization},
@@ -74,6 +75,7 @@ use {
         system_instruction_processor::{get_system_account_kind, SystemAccountKind},
         transaction_batch::TransactionBatch,
         transaction_error_metrics::TransactionErrorMetrics,
+        transaction_trace::{TransactionTrace, TransactionTraceConfig},
         vote_account::{VoteAccount, VoteAccountsHashMap},
         vote_parser,
     },
@@ -91,6 +93,7 @@ use {
     solana_program_runtime::{
         accounts_data_meter::MAX_ACCOUNTS_DATA_LEN,
         compute_budget::{self, ComputeBudget},
+        execution_trace::ExecutionTracer,
         executor::Executor,
         executor_cache::{
             BankExecutorCache, TransactionExecutorCache, TxBankExecutorCacheDiff,
@@ -162,7 +165,7 @@ use {
     std::{
         borrow::Cow,
         cell::RefCell,
-        collections::{HashMap, HashSet},
+        collections::{BTreeSet, HashMap, HashSet},
         convert::{TryFrom, TryInto},
         fmt, mem,
         ops::{Deref, RangeInclusive},
@@ -189,8 +192,10 @@ pub struct VerifyBankHash {
     pub store_hash_raw_data_for_debug: bool,
 }
 
+mod account_set;
 mod address_lookup_table;
 mod builtin_programs;
+mod lazy_accounts;
 mod metrics;
 mod sysvar_cache;
 mod transaction_account_state_info;
@@ -847,6 +852,7 @@ impl PartialEq for Bank {
             transaction_debug_keys: _,
             transaction_log_collector_config: _,
             transaction_log_collector: _,
+            transaction_trace_config: _,
             feature_set: _,
             drop_callback: _,
             freeze_started: _,
@@ -1091,6 +1097,10 @@ pub struct Bank {
     // `transaction_log_collector_config`
     pub transaction_log_collector: Arc<RwLock<TransactionLogCollector>>,
 
+    // Global configuration for which transactions are executed with an execution tracer, and
+    // where their traces are sent
+    pub transaction_trace_config: Arc<RwLock<TransactionTraceConfig>>,
+
     pub feature_set: Arc<FeatureSet>,
 
     /// callback function only to be called when dropping and should only be called once
@@ -1307,6 +1317,7 @@ impl Bank {
             transaction_log_collector_config: Arc::<RwLock<TransactionLogCollectorConfig>>::default(
             ),
             transaction_log_collector: Arc::<RwLock<TransactionLogCollector>>::default(),
+            transaction_trace_config: Arc::<RwLock<TransactionTraceConfig>>::default(),
             feature_set: Arc::<FeatureSet>::default(),
             drop_callback: RwLock::new(OptionalDropCallback(None)),
             freeze_started: AtomicBool::default(),
@@ -1586,6 +1597,7 @@ impl Bank {
             transaction_debug_keys,
             transaction_log_collector_config,
             transaction_log_collector: Arc::new(RwLock::new(TransactionLogCollector::default())),
+            transaction_trace_config: parent.transaction_trace_config.clone(),
             feature_set: Arc::clone(&feature_set),
             drop_callback: RwLock::new(OptionalDropCallback(
                 parent
@@ -1895,6 +1907,7 @@ impl Bank {
             transaction_debug_keys: debug_keys,
             transaction_log_collector_config: new(),
             transaction_log_collector: new(),
+            transaction_trace_config: new(),
             feature_set: Arc::clone(&feature_set),
             drop_callback: RwLock::new(OptionalDropCallback(None)),
             freeze_started: AtomicBool::new(fields.hash != Hash::default()),
@@ -3799,6 +3812,10 @@ impl Bank {
 
     fn get_account_overrides_for_simulation(&self, account_keys: &AccountKeys) -> AccountOverrides {
         let mut account_overrides = AccountOverrides::default();
+        // simulations read the lazy accounts through without storing them
+        for (pubkey, account) in self.get_lazy_accounts(account_keys.iter()) {
+            account_overrides.set_account(&pubkey, Some(account));
+        }
         let slot_history_id = sysvar::slot_history::id();
         if account_keys.iter().any(|pubkey| *pubkey == slot_history_id) {
             let current_account = self.get_account_with_fixed_root(&slot_history_id);
@@ -4077,6 +4094,7 @@ impl Bank {
             &program,
             programdata.as_ref().unwrap_or(&program),
             self.runtime_config.bpf_jit,
+            false, // enable_instruction_tracing
         )
         .map(|(executor, _create_executor_metrics)| executor)
         .map_err(|err| TransactionError::InstructionError(0, err))
@@ -4106,6 +4124,7 @@ impl Bank {
         timings: &mut ExecuteTimings,
         error_counters: &mut TransactionErrorMetrics,
         log_messages_bytes_limit: Option<usize>,
+        transaction_trace_config: Option<&TransactionTraceConfig>,
     ) -> TransactionExecutionResult {
         let mut get_tx_executor_cache_time = Measure::start("get_tx_executor_cache_time");
         let tx_executor_cache = self.get_tx_executor_cache(&loaded_transaction.accounts);
@@ -4116,6 +4135,14 @@ impl Bank {
         );
 
         let prev_accounts_data_len = self.load_accounts_data_size();
+        let execution_tracer = transaction_trace_config
+            .filter(|config| config.should_trace(tx))
+            .map(|config| {
+                (
+                    ExecutionTracer::new_ref(config.record_vm_trace),
+                    loaded_transaction.accounts.clone(),
+                )
+            });
         let transaction_accounts = std::mem::take(&mut loaded_transaction.accounts);
         let mut transaction_context = TransactionContext::new(
             transaction_accounts,
@@ -4172,6 +4199,9 @@ impl Bank {
             &mut transaction_context,
             self.rent_collector.rent,
             log_collector.clone(),
+            execution_tracer
+                .as_ref()
+                .map(|(execution_tracer, _)| execution_tracer.clone()),
             tx_executor_cache.clone(),
             self.feature_set.clone(),
             compute_budget,
@@ -4262,6 +4292,24 @@ impl Bank {
             accounts_data_len_delta = status.as_ref().map_or(0, |_| accounts_resize_delta);
         }
 
+        if let Some((execution_tracer, pre_accounts)) = execution_tracer {
+            let trace = TransactionTrace::new(
+                self.slot(),
+                tx,
+                &status,
+                executed_units,
+                execution_tracer.borrow_mut().take_events(),
+                &pre_accounts,
+                &loaded_transaction.accounts,
+            );
+            if let Some(sender) = transaction_trace_config.and_then(|config| config.sender.as_ref())
+            {
+                if let Err(err) = sender.send(trace) {
+                    warn!("failed to send the trace of {}: {}", tx.signature(), err);
+                }
+            }
+        }
+
         let return_data = if enable_return_data_recording {
             if let Some(end_index) = return_data.data.iter().rposition(|&x| x != 0) {
                 let end_index = end_index.saturating_add(1);
@@ -4366,6 +4414,11 @@ impl Bank {
 
         let mut execution_time = Measure::start("execution_time");
         let mut signature_count: u64 = 0;
+        let transaction_trace_config = self.transaction_trace_config.read().unwrap();
+        let transaction_trace_config = transaction_trace_config
+            .sender
+            .is_some()
+            .then_some(&*transaction_trace_config);
 
         let execution_results: Vec<TransactionExecutionResult> = loaded_transactions
             .iter_mut()
@@ -4413,6 +4466,7 @@ impl Bank {
                         timings,
                         &mut error_counters,
                         log_messages_bytes_limit,
+                        transaction_trace_config,
                     )
                 }
             })
@@ -6031,6 +6085,7 @@ impl Bank {
             Ok(batch) => batch,
             Err(err) => return TransactionExecutionResult::NotExecuted(err),
         };
+        self.load_lazy_accounts_for_batch(&batch);
 
         let (
             TransactionResults {
@@ -6094,6 +6149,7 @@ impl Bank {
         txs: Vec<VersionedTransaction>,
     ) -> Result<Vec<Result<()>>> {
         let batch = self.prepare_entry_batch(txs)?;
+        self.load_lazy_accounts_for_batch(&batch);
         Ok(self.process_transaction_batch(&batch))
     }
 
@@ -7003,6 +7059,21 @@ impl Bank {
         self.update_accounts_hash(CalcAccountsHashDataSource::IndexForTests, false, false)
     }
 
+    /// The accounts hash of this bank, broken down into 'bins' pubkey bins, with the accounts of
+    /// 'account_bins' included. See `AccountsDb::dump_accounts_hash`.
+    pub fn dump_accounts_hash(
+        &self,
+        bins: usize,
+        account_bins: &BTreeSet<usize>,
+    ) -> AccountsHashDump {
+        self.rc.accounts.accounts_db.dump_accounts_hash(
+            self.slot(),
+            &self.ancestors,
+            bins,
+            account_bins,
+        )
+    }
+
     /// A snapshot bank should be purged of 0 lamport accounts which are not part of the hash
     /// calculation and could shield other real accounts.
     pub fn verify_snapshot_bank(
@@ -7477,7 +7548,15 @@ impl Bank {
         let mut newly_activated = HashSet::new();
         let slot = self.slot();
 
+        // The features deactivated by the runtime config are neither activated nor requested
+        for feature_id in &self.runtime_config.deactivated_features {
+            active.remove(feature_id);
+            inactive.insert(*feature_id);
+        }
         for feature_id in &self.feature_set.inactive {
+            if inactive.contains(feature_id) {
+                continue;
+            }
             let mut activated = None;
             if let Some(mut account) = self.get_account_with_fixed_root(feature_id) {
                 if let Some(mut feature) = feature::from_account(&account) {
@@ -7923,6 +8002,7 @@ pub(crate) mod tests {
         rand::Rng,
         solana_program_runtime::{
             compute_budget::MAX_COMPUTE_UNIT_LIMIT,
+            execution_trace::ExecutionTraceEvent,
             invoke_context::{mock_process_instruction, InvokeContext},
             prioritization_fee::{PrioritizationFeeDetails, PrioritizationFeeType},
         },
@@ -14610,6 +14690,54 @@ pub(crate) mod tests {
         info!("results: {:?}", results);
     }
 
+    #[test]
+    fn test_dump_accounts_hash() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(sol_to_lamports(1.));
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let pubkeys: Vec<_> = (0..10).map(|_| Pubkey::new_unique()).collect();
+        for (i, pubkey) in pubkeys.iter().enumerate() {
+            bank.store_account(
+                pubkey,
+                &AccountSharedData::new(i as u64 + 1, 0, &system_program::id()),
+            );
+        }
+        bank.freeze();
+        bank.squash();
+        bank.force_flush_accounts_cache();
+        let accounts_hash = bank.update_accounts_hash_for_tests();
+
+        let bins = 4;
+        let all_bins = BTreeSet::from_iter(0..bins);
+        let dump = bank.dump_accounts_hash(bins, &BTreeSet::from([0]));
+        assert_eq!(dump.slot, bank.slot());
+        assert_eq!(dump.accounts_hash, accounts_hash.0);
+        assert_eq!(dump.total_lamports, bank.capitalization());
+        assert_eq!(dump.bins.len(), bins);
+        assert_eq!(dump.accounts.len() as u64, dump.bins[0].count);
+        assert!(dump.accounts.values().all(|(bin, _)| *bin == 0));
+
+        // a changed account is found by diffing the dumps
+        let dump = bank.dump_accounts_hash(bins, &all_bins);
+        let bank1 = new_from_parent(&bank);
+        bank1.store_account(
+            &pubkeys[0],
+            &AccountSharedData::new(100, 0, &system_program::id()),
+        );
+        let dump1 = bank1.dump_accounts_hash(bins, &all_bins);
+        let diff = dump.diff(&dump1).unwrap();
+        let (_, account, account1) = diff
+            .mismatched_accounts
+            .iter()
+            .find(|(pubkey, _, _)| *pubkey == pubkeys[0])
+            .unwrap();
+        assert_eq!(account.unwrap().0, 1);
+        assert_eq!(account1.unwrap().0, 100);
+        assert!(!diff
+            .mismatched_accounts
+            .iter()
+            .any(|(pubkey, _, _)| *pubkey == pubkeys[1]));
+    }
+
     #[test]
     fn test_bank_hash_consistency() {
         solana_logger::setup();
@@ -16288,6 +16416,35 @@ pub(crate) mod tests {
         assert!(bank.feature_set.is_active(&test_feature));
     }
 
+    #[test]
+    fn test_compute_active_feature_set_deactivated_features() {
+        let (mut genesis_config, _mint_keypair) = create_genesis_config(100_000);
+        let active_feature = feature_set::credits_auto_rewind::id();
+        let requested_feature = feature_set::deprecate_rewards_sysvar::id();
+        activate_feature(&mut genesis_config, active_feature);
+        let runtime_config = Arc::new(RuntimeConfig {
+            deactivated_features: [active_feature, requested_feature].into_iter().collect(),
+            ..RuntimeConfig::default()
+        });
+        let bank0 = Arc::new(Bank::new_with_runtime_config_for_tests(
+            &genesis_config,
+            runtime_config,
+        ));
+        assert!(!bank0.feature_set.is_active(&active_feature));
+
+        // Neither activated at the next epoch, nor requested
+        bank0.store_account(
+            &requested_feature,
+            &feature::create_account(&Feature::default(), 42),
+        );
+        let slot = genesis_config.epoch_schedule.get_first_slot_in_epoch(1);
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), slot);
+        assert!(!bank1.feature_set.is_active(&active_feature));
+        assert!(!bank1.feature_set.is_active(&requested_feature));
+        let feature = feature::from_account(&bank1.get_account(&requested_feature).unwrap());
+        assert_eq!(feature.unwrap().activated_at, None);
+    }
+
     #[test]
     fn test_program_replacement() {
         let mut bank = create_simple_test_bank(0);
@@ -18556,6 +18713,65 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn test_transaction_trace() {
+        let (genesis_config, mint_keypair) = create_genesis_config(sol_to_lamports(1.));
+        let bank = Arc::new(Bank::new_for_tests(&genesis_config));
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let to = Pubkey::new_unique();
+        *bank.transaction_trace_config.write().unwrap() = TransactionTraceConfig {
+            addresses: HashSet::from([to]),
+            sender: Some(sender),
+            ..TransactionTraceConfig::default()
+        };
+
+        let amount = genesis_config.rent.minimum_balance(0);
+
+        // only the transactions that reference a traced address are traced
+        bank.transfer(amount, &mint_keypair, &Pubkey::new_unique())
+            .unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        // the config is shared with the child banks
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), 1);
+        let signature = bank.transfer(amount, &mint_keypair, &to).unwrap();
+        let trace = receiver.try_recv().unwrap();
+        assert_eq!(trace.slot, 1);
+        assert_eq!(trace.signature, signature.to_string());
+        assert_eq!(trace.error, None);
+        assert!(trace.compute_units_consumed > 0);
+        assert!(matches!(
+            &trace.events[..],
+            [
+                ExecutionTraceEvent::InstructionStart {
+                    stack_height: 1,
+                    ..
+                },
+                ExecutionTraceEvent::InstructionEnd {
+                    stack_height: 1,
+                    error: None,
+                    ..
+                },
+            ]
+        ));
+
+        assert_eq!(trace.accounts.len(), 3);
+        let from = &trace.accounts[0];
+        assert_eq!(from.pubkey, mint_keypair.pubkey().to_string());
+        assert!(from.is_writable);
+        let to = &trace.accounts[1];
+        assert_eq!(to.pre.lamports, 0);
+        assert_eq!(to.post.as_ref().unwrap().lamports, amount);
+        assert_eq!(
+            from.pre.lamports - from.post.as_ref().unwrap().lamports,
+            amount
+        );
+        let system_program = &trace.accounts[2];
+        assert!(!system_program.is_writable);
+        assert!(system_program.post.is_none());
+        assert!(system_program.pre.data.is_none());
+    }
+
     /// Test processing a good transaction correctly modifies the accounts data size
     #[test]
     fn test_accounts_data_size_with_good_transaction() {
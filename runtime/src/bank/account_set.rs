@@ -0,0 +1,122 @@
+use {
+    super::Bank,
+    crate::{
+        account_set::{AccountSet, AccountSetFilter},
+        accounts_index::{ScanConfig, ScanResult},
+    },
+    solana_sdk::{
+        account::{Account, AccountSharedData, ReadableAccount},
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        pubkey::Pubkey,
+    },
+    std::collections::BTreeMap,
+};
+
+impl Bank {
+    /// Export the accounts matching 'filter', along with the program data accounts of the
+    /// upgradeable programs among them, so the program can be executed where the set is imported.
+    pub fn export_account_set(&self, filter: &AccountSetFilter) -> ScanResult<AccountSet> {
+        let mut accounts = BTreeMap::<Pubkey, AccountSharedData>::new();
+        for owner in &filter.owners {
+            accounts.extend(self.get_program_accounts(owner, &ScanConfig::default())?);
+        }
+        for pubkey in &filter.pubkeys {
+            if let Some(account) = self.get_account_with_fixed_root(pubkey) {
+                accounts.insert(*pubkey, account);
+            }
+        }
+
+        let programdata_addresses = accounts
+            .values()
+            .filter(|account| bpf_loader_upgradeable::check_id(account.owner()))
+            .filter_map(|account| match account.deserialize_data() {
+                Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) => Some(programdata_address),
+                _ => None,
+            })
+            .filter(|programdata_address| !accounts.contains_key(programdata_address))
+            .collect::<Vec<_>>();
+        for programdata_address in programdata_addresses {
+            if let Some(account) = self.get_account_with_fixed_root(&programdata_address) {
+                accounts.insert(programdata_address, account);
+            }
+        }
+
+        Ok(AccountSet {
+            slot: self.slot(),
+            accounts: accounts
+                .into_iter()
+                .map(|(pubkey, account)| (pubkey, Account::from(account)))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_sdk::{
+            account::WritableAccount, genesis_config::create_genesis_config, system_program,
+        },
+        std::collections::HashSet,
+    };
+
+    #[test]
+    fn test_export_account_set() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(100_000);
+        let bank = Bank::new_for_tests(&genesis_config);
+
+        let owner = Pubkey::new_unique();
+        let owned = Pubkey::new_unique();
+        bank.store_account(&owned, &AccountSharedData::new(1, 10, &owner));
+        let listed = Pubkey::new_unique();
+        bank.store_account(
+            &listed,
+            &AccountSharedData::new(2, 0, &system_program::id()),
+        );
+        let not_exported = Pubkey::new_unique();
+        bank.store_account(
+            &not_exported,
+            &AccountSharedData::new(3, 0, &system_program::id()),
+        );
+
+        // an upgradeable program, whose program data account is not otherwise matched
+        let program = Pubkey::new_unique();
+        let programdata_address = Pubkey::new_unique();
+        let mut program_account = AccountSharedData::new_data(
+            4,
+            &UpgradeableLoaderState::Program {
+                programdata_address,
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap();
+        program_account.set_executable(true);
+        bank.store_account(&program, &program_account);
+        let programdata_account = AccountSharedData::new(5, 100, &bpf_loader_upgradeable::id());
+        bank.store_account(&programdata_address, &programdata_account);
+
+        let filter = AccountSetFilter {
+            owners: HashSet::from([owner]),
+            pubkeys: HashSet::from([listed, program, Pubkey::new_unique()]),
+        };
+        let account_set = bank.export_account_set(&filter).unwrap();
+        assert_eq!(account_set.slot, bank.slot());
+        let mut expected = vec![
+            (owned, bank.get_account(&owned).unwrap()),
+            (listed, bank.get_account(&listed).unwrap()),
+            (program, program_account),
+            (programdata_address, programdata_account),
+        ];
+        expected.sort_unstable_by_key(|(pubkey, _)| *pubkey);
+        assert_eq!(
+            account_set.accounts,
+            expected
+                .into_iter()
+                .map(|(pubkey, account)| (pubkey, Account::from(account)))
+                .collect::<Vec<_>>()
+        );
+    }
+}
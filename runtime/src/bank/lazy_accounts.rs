@@ -0,0 +1,235 @@
+use {
+    super::Bank,
+    crate::{
+        accounts_index::AccountIndexGetResult, lazy_account_loader::LazyAccountLoader,
+        transaction_batch::TransactionBatch,
+    },
+    log::*,
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        pubkey::Pubkey,
+    },
+};
+
+impl Bank {
+    /// Fetch the accounts among 'pubkeys' this bank doesn't have from the lazy account source of
+    /// the runtime config, along with the program data accounts of the upgradeable programs among
+    /// them. The fetched accounts are stored in this bank's slot, adding their lamports to the
+    /// capitalization. Only the banking stage and the `Bank::process_transaction(s)` entry points
+    /// store fetched accounts; ledger replay never fetches, so replayed banks keep the state they
+    /// were produced with, and simulation and RPC reads use `get_lazy_accounts` instead. Frozen
+    /// banks and banks without a lazy account loader are left alone.
+    pub fn load_lazy_accounts<'a>(&self, pubkeys: impl IntoIterator<Item = &'a Pubkey>) {
+        let loader = match &self.runtime_config.lazy_account_loader {
+            Some(loader) => loader,
+            None => return,
+        };
+        if self.is_frozen() {
+            return;
+        }
+        let _fetch_lock = loader.lock_fetch();
+        for (pubkey, account) in self.fetch_lazy_accounts(loader, pubkeys) {
+            debug!("fetched lazy account {} into slot {}", pubkey, self.slot());
+            self.store_account_and_update_capitalization(&pubkey, &account);
+            loader.record_fetched(pubkey, self.slot());
+        }
+    }
+
+    /// `load_lazy_accounts` for the accounts of the transactions of 'batch'
+    pub fn load_lazy_accounts_for_batch(&self, batch: &TransactionBatch) {
+        if self.runtime_config.lazy_account_loader.is_none() {
+            return;
+        }
+        self.load_lazy_accounts(
+            batch
+                .sanitized_transactions()
+                .iter()
+                .flat_map(|tx| tx.message().account_keys().iter()),
+        );
+    }
+
+    /// Fetch the accounts among 'pubkeys' this bank doesn't have, like `load_lazy_accounts`, but
+    /// without storing them, so frozen banks can be read through too
+    pub fn get_lazy_accounts<'a>(
+        &self,
+        pubkeys: impl IntoIterator<Item = &'a Pubkey>,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        match &self.runtime_config.lazy_account_loader {
+            Some(loader) => self.fetch_lazy_accounts(loader, pubkeys),
+            None => vec![],
+        }
+    }
+
+    /// true if 'pubkey' was fetched from the lazy account source into this bank or an ancestor
+    pub fn is_lazy_fetched_account(&self, pubkey: &Pubkey) -> bool {
+        self.runtime_config
+            .lazy_account_loader
+            .as_ref()
+            .map(|loader| loader.is_fetched(pubkey, &self.ancestors))
+            .unwrap_or_default()
+    }
+
+    fn fetch_lazy_accounts<'a>(
+        &self,
+        loader: &LazyAccountLoader,
+        pubkeys: impl IntoIterator<Item = &'a Pubkey>,
+    ) -> Vec<(Pubkey, AccountSharedData)> {
+        let mut pubkeys: Vec<Pubkey> = pubkeys.into_iter().copied().collect();
+        let mut fetched = Vec::new();
+        while let Some(pubkey) = pubkeys.pop() {
+            if self.has_account_index_entry(&pubkey)
+                || loader.is_fetched(&pubkey, &self.ancestors)
+                || fetched.iter().any(|(fetched, _)| *fetched == pubkey)
+            {
+                continue;
+            }
+            let account = match loader.fetch(&pubkey) {
+                Some(account) => account,
+                None => continue,
+            };
+            if bpf_loader_upgradeable::check_id(account.owner()) {
+                if let Ok(UpgradeableLoaderState::Program {
+                    programdata_address,
+                }) = account.deserialize_data()
+                {
+                    pubkeys.push(programdata_address);
+                }
+            }
+            fetched.push((pubkey, account));
+        }
+        fetched
+    }
+
+    /// true if the accounts index has an entry for 'pubkey' on this fork, including the
+    /// zero-lamport entries of closed accounts, which must not be fetched again
+    fn has_account_index_entry(&self, pubkey: &Pubkey) -> bool {
+        matches!(
+            self.rc
+                .accounts
+                .accounts_db
+                .accounts_index
+                .get(pubkey, Some(&self.ancestors), None),
+            AccountIndexGetResult::Found(..)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            account_set::AccountSet,
+            lazy_account_loader::{AccountSetLazyAccountSource, LazyAccountLoader},
+            runtime_config::RuntimeConfig,
+        },
+        solana_sdk::{
+            account::Account, genesis_config::create_genesis_config, signature::Signer,
+            system_program, system_transaction, transaction::SanitizedTransaction,
+        },
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn test_load_lazy_accounts() {
+        let (genesis_config, mint_keypair) = create_genesis_config(1_000_000);
+        let lazy = Pubkey::new_unique();
+        let closed = Pubkey::new_unique();
+        let simulated = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let programdata_address = Pubkey::new_unique();
+        let mut program_account = Account::new_data(
+            3,
+            &UpgradeableLoaderState::Program {
+                programdata_address,
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap();
+        program_account.executable = true;
+        let source = AccountSetLazyAccountSource::from(AccountSet {
+            slot: 0,
+            accounts: vec![
+                (lazy, Account::new(10, 0, &system_program::id())),
+                (closed, Account::new(20, 0, &system_program::id())),
+                (simulated, Account::new(30, 0, &system_program::id())),
+                (program, program_account),
+                (
+                    programdata_address,
+                    Account::new(4, 100, &bpf_loader_upgradeable::id()),
+                ),
+            ],
+        });
+        let runtime_config = Arc::new(RuntimeConfig {
+            lazy_account_loader: Some(Arc::new(LazyAccountLoader::new(Box::new(source)))),
+            ..RuntimeConfig::default()
+        });
+        let bank0 = Arc::new(Bank::new_with_runtime_config_for_tests(
+            &genesis_config,
+            runtime_config,
+        ));
+        bank0.freeze();
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let capitalization = bank1.capitalization();
+
+        // a transfer to a lazy account fetches it first
+        let tx = system_transaction::transfer(&mint_keypair, &lazy, 5, bank1.last_blockhash());
+        bank1.process_transaction(&tx).unwrap();
+        assert_eq!(bank1.get_balance(&lazy), 15);
+        assert!(bank1.is_lazy_fetched_account(&lazy));
+        assert!(!bank1.is_lazy_fetched_account(&mint_keypair.pubkey()));
+        assert_eq!(bank1.capitalization(), capitalization + 10);
+
+        // the program data account of a program is fetched along with it
+        bank1.load_lazy_accounts([&program]);
+        assert_eq!(bank1.get_balance(&program), 3);
+        assert_eq!(bank1.get_balance(&programdata_address), 4);
+        assert!(bank1.is_lazy_fetched_account(&programdata_address));
+        assert_eq!(bank1.capitalization(), capitalization + 17);
+
+        // accounts closed on this fork aren't fetched again
+        bank1.store_account(&closed, &AccountSharedData::default());
+        bank1.load_lazy_accounts([&closed]);
+        assert_eq!(bank1.get_account(&closed), None);
+        assert!(!bank1.is_lazy_fetched_account(&closed));
+        assert_eq!(bank1.capitalization(), capitalization + 17);
+
+        // descendants see the fetched accounts, other forks don't
+        bank1.freeze();
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        assert!(bank2.is_lazy_fetched_account(&lazy));
+        let bank3 = Bank::new_from_parent(&bank0, &Pubkey::default(), 3);
+        assert!(!bank3.is_lazy_fetched_account(&lazy));
+        assert_eq!(bank3.get_account(&lazy), None);
+
+        // frozen banks don't fetch
+        bank0.load_lazy_accounts([&lazy]);
+        assert_eq!(bank0.get_account(&lazy), None);
+
+        // but simulations and reads on them fetch without storing
+        let tx = system_transaction::transfer(&mint_keypair, &simulated, 5, bank1.last_blockhash());
+        let result =
+            bank1.simulate_transaction(SanitizedTransaction::from_transaction_for_tests(tx));
+        assert!(result.result.is_ok());
+        assert_eq!(
+            result
+                .post_simulation_accounts
+                .iter()
+                .find(|(pubkey, _)| *pubkey == simulated)
+                .map(|(_, account)| account.lamports()),
+            Some(35)
+        );
+        assert_eq!(
+            bank1.get_lazy_accounts([&simulated]),
+            vec![(
+                simulated,
+                AccountSharedData::from(Account::new(30, 0, &system_program::id()))
+            )]
+        );
+        assert_eq!(bank1.get_lazy_accounts([&lazy, &closed]), vec![]);
+        assert_eq!(bank1.get_account(&simulated), None);
+        assert!(!bank1.is_lazy_fetched_account(&simulated));
+        assert_eq!(bank1.capitalization(), capitalization + 17);
+    }
+}
@@ -234,6 +234,7 @@ impl BankForks {
         highest_confirmed_root: Option<Slot>,
     ) -> (Vec<Arc<Bank>>, SetRootMetrics) {
         let old_epoch = self.root_bank().epoch();
+        let old_root = self.root();
         self.root.store(root, Ordering::Relaxed);
 
         let root_bank = self
@@ -381,6 +382,18 @@ impl BankForks {
             total_squash_accounts_store_ms += squash_timing.squash_accounts_store_ms as i64;
             total_squash_cache_ms += squash_timing.squash_cache_ms as i64;
         }
+        // the parents of the root bank still include the previous root, which was already notified
+        let rooted_slots: Vec<_> = banks
+            .iter()
+            .rev()
+            .filter(|bank| bank.slot() > old_root)
+            .map(|bank| (bank.slot(), Some(bank.parent_slot())))
+            .collect();
+        root_bank
+            .rc
+            .accounts
+            .accounts_db
+            .notify_rooted_account_deltas(&rooted_slots);
         let new_tx_count = root_bank.transaction_count();
         let accounts_data_len = root_bank.load_accounts_data_size() as i64;
         let mut prune_time = Measure::start("set_root::prune");
@@ -634,13 +647,20 @@ mod tests {
     use {
         super::*,
         crate::{
+            accounts_db::{
+                AccountShrinkThreshold, AccountsDbConfig, ACCOUNTS_DB_CONFIG_FOR_TESTING,
+            },
+            accounts_index::AccountSecondaryIndexes,
             bank::tests::update_vote_account_timestamp,
             epoch_accounts_hash::EpochAccountsHash,
             genesis_utils::{
                 create_genesis_config, create_genesis_config_with_leader, GenesisConfigInfo,
             },
+            rooted_account_deltas::RootedAccountDelta,
+            runtime_config::RuntimeConfig,
         },
         solana_sdk::{
+            account::{AccountSharedData, ReadableAccount},
             clock::UnixTimestamp,
             epoch_schedule::EpochSchedule,
             hash::Hash,
@@ -896,6 +916,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bank_forks_set_root_rooted_account_deltas() {
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let bank0 = Bank::new_with_paths(
+            &genesis_config,
+            Arc::<RuntimeConfig>::default(),
+            Vec::new(),
+            None,
+            None,
+            AccountSecondaryIndexes::default(),
+            AccountShrinkThreshold::default(),
+            false,
+            Some(AccountsDbConfig {
+                rooted_account_delta_sender: Some(sender),
+                ..ACCOUNTS_DB_CONFIG_FOR_TESTING
+            }),
+            None,
+            &Arc::default(),
+        );
+        let mut bank_forks = BankForks::new(bank0);
+        let store = |bank: &Bank, pubkey: &Pubkey, lamports| {
+            bank.store_account(
+                pubkey,
+                &AccountSharedData::new(lamports, 0, &Pubkey::default()),
+            )
+        };
+        let key1 = Pubkey::new_unique();
+        let key2 = Pubkey::new_unique();
+
+        // slot 2 is on an abandoned fork
+        let bank1 = bank_forks.insert(Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 1));
+        store(&bank1, &key1, 1);
+        store(&bank1, &key1, 2);
+        let bank2 = bank_forks.insert(Bank::new_from_parent(&bank_forks[0], &Pubkey::default(), 2));
+        store(&bank2, &key2, 3);
+        let bank3 = bank_forks.insert(Bank::new_from_parent(&bank1, &Pubkey::default(), 3));
+        store(&bank3, &key2, 4);
+
+        bank_forks.set_root(3, &AbsRequestSender::default(), None);
+        let deltas: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(
+            deltas
+                .iter()
+                .map(|delta| (delta.slot, delta.parent))
+                .collect::<Vec<_>>(),
+            vec![(1, Some(0)), (3, Some(1))]
+        );
+        let lamports = |delta: &RootedAccountDelta, pubkey| {
+            delta
+                .accounts
+                .iter()
+                .find(|account| &account.pubkey == pubkey)
+                .map(|account| account.account.lamports())
+        };
+        assert_eq!(lamports(&deltas[0], &key1), Some(2));
+        assert_eq!(lamports(&deltas[0], &key2), None);
+        assert_eq!(lamports(&deltas[1], &key2), Some(4));
+
+        // the previous root is not notified again
+        let bank4 = bank_forks.insert(Bank::new_from_parent(&bank3, &Pubkey::default(), 4));
+        store(&bank4, &key1, 5);
+        bank_forks.set_root(4, &AbsRequestSender::default(), None);
+        let deltas: Vec<_> = receiver.try_iter().collect();
+        assert_eq!(deltas.len(), 1);
+        assert_eq!((deltas[0].slot, deltas[0].parent), (4, Some(3)));
+        assert_eq!(lamports(&deltas[0], &key1), Some(5));
+    }
+
     #[test]
     fn test_bank_forks_with_highest_confirmed_root() {
         let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
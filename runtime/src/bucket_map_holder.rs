@@ -1,7 +1,7 @@
 use {
     crate::{
         accounts_index::{AccountsIndexConfig, IndexLimitMb, IndexValue},
-        bucket_map_holder_stats::BucketMapHolderStats,
+        bucket_map_holder_stats::{AccountsIndexMemoryStats, BucketMapHolderStats},
         in_mem_accounts_index::InMemAccountsIndex,
         waitable_condvar::WaitableCondvar,
     },
@@ -14,7 +14,7 @@ use {
     std::{
         fmt::Debug,
         sync::{
-            atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
+            atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
             Arc,
         },
         time::Duration,
@@ -27,6 +27,18 @@ const AGE_MS: u64 = DEFAULT_MS_PER_SLOT; // match one age per slot time
 // 10 GB limit for in-mem idx. In practice, we don't get this high. This tunes how aggressively to save items we expect to use soon.
 pub const DEFAULT_DISK_INDEX: Option<usize> = Some(10_000);
 
+const DEFAULT_AGE_TO_STAY_IN_CACHE: Age = 5;
+/// upper bound of the ages to stay in cache when tuning to a memory budget, unless specified
+const DEFAULT_MAX_AGE_TO_STAY_IN_CACHE_WITH_BUDGET: Age = 25;
+/// # ages between adjustments made to stay within a memory budget
+const AGES_PER_MEMORY_BUDGET_TUNING: Age = 10;
+/// aim to finish flushing all bins in this % of the age interval
+const DEFAULT_FLUSH_TARGET_PERCENT: u64 = 90;
+/// flush faster while the in-mem index exceeds its memory budget
+const OVER_BUDGET_FLUSH_TARGET_PERCENT: u64 = 50;
+/// grow the cache while under budget if more than this % of lookups had to be loaded from disk
+const MAX_PERCENT_LOOKUPS_LOADED_FROM_DISK: u64 = 1;
+
 pub struct BucketMapHolder<T: IndexValue> {
     pub disk: Option<BucketMap<(Slot, T)>>,
 
@@ -59,8 +71,20 @@ pub struct BucketMapHolder<T: IndexValue> {
     pub mem_budget_mb: Option<usize>,
 
     /// how many ages should elapse from the last time an item is used where the item will remain in the cache
+    /// This is the upper bound when 'mem_budget_tuning' is true.
     pub ages_to_stay_in_cache: Age,
 
+    /// true if 'current_ages_to_stay_in_cache' and 'flush_target_percent' are tuned at runtime
+    /// to keep the in-mem index close to 'mem_budget_mb'
+    pub mem_budget_tuning: bool,
+    /// ages to stay in cache currently in use, <= 'ages_to_stay_in_cache'
+    current_ages_to_stay_in_cache: AtomicU8,
+    /// % of the age interval in which bg threads aim to flush all bins
+    flush_target_percent: AtomicU64,
+    /// lookups counted at the last memory budget tuning
+    last_lookups_found_in_mem: AtomicU64,
+    last_lookups_loaded_from_disk: AtomicU64,
+
     /// startup is a special time for flush to focus on moving everything to disk as fast and efficiently as possible
     /// with less thread count limitations. LRU and access patterns are not important. Freeing memory
     /// and writing to disk in parallel are.
@@ -88,8 +112,14 @@ impl<T: IndexValue> BucketMapHolder<T> {
         let previous = self.count_buckets_flushed.swap(0, Ordering::AcqRel);
         // fetch_add is defined to wrap.
         // That's what we want. 0..255, then back to 0.
-        self.age.fetch_add(1, Ordering::Release);
-        self.future_age_to_flush.fetch_add(1, Ordering::Release);
+        let age = self.age.fetch_add(1, Ordering::Release).wrapping_add(1);
+        if self.mem_budget_tuning && age % AGES_PER_MEMORY_BUDGET_TUNING == 0 {
+            self.tune_memory_budget();
+        }
+        self.future_age_to_flush.store(
+            age.wrapping_add(self.current_ages_to_stay_in_cache()),
+            Ordering::Release,
+        );
         self.future_age_to_flush_cached
             .fetch_add(1, Ordering::Release);
         assert!(
@@ -101,6 +131,125 @@ impl<T: IndexValue> BucketMapHolder<T> {
         self.wait_dirty_or_aged.notify_all(); // notify all because we can age scan in parallel
     }
 
+    /// ages to stay in cache currently in use
+    pub fn current_ages_to_stay_in_cache(&self) -> Age {
+        self.current_ages_to_stay_in_cache.load(Ordering::Acquire)
+    }
+
+    /// estimated # bytes used by the in-mem index
+    fn estimate_mem_bytes(&self) -> usize {
+        self.stats.count_in_mem.load(Ordering::Relaxed)
+            * InMemAccountsIndex::<T>::approx_size_of_one_entry()
+    }
+
+    /// adjust the ages to stay in cache and the flush rate from the memory used by the in-mem index
+    /// and the lookups which had to be loaded from disk since the last tuning
+    fn tune_memory_budget(&self) {
+        let mem_budget_mb = match self.mem_budget_mb {
+            Some(mem_budget_mb) => mem_budget_mb,
+            None => return,
+        };
+        let lookups_found_in_mem = self.stats.lookups_found_in_mem.load(Ordering::Relaxed);
+        let lookups_loaded_from_disk = self.stats.lookups_loaded_from_disk.load(Ordering::Relaxed);
+        let found_in_mem = lookups_found_in_mem.saturating_sub(
+            self.last_lookups_found_in_mem
+                .swap(lookups_found_in_mem, Ordering::Relaxed),
+        );
+        let loaded_from_disk = lookups_loaded_from_disk.saturating_sub(
+            self.last_lookups_loaded_from_disk
+                .swap(lookups_loaded_from_disk, Ordering::Relaxed),
+        );
+
+        let ages = self.current_ages_to_stay_in_cache();
+        let (new_ages, flush_target_percent) = Self::next_memory_budget_settings(
+            ages,
+            self.ages_to_stay_in_cache,
+            self.estimate_mem_bytes(),
+            mem_budget_mb * 1024 * 1024,
+            found_in_mem,
+            loaded_from_disk,
+        );
+        if new_ages > ages {
+            self.stats
+                .budget_ages_increased
+                .fetch_add(1, Ordering::Relaxed);
+        } else if new_ages < ages {
+            self.stats
+                .budget_ages_decreased
+                .fetch_add(1, Ordering::Relaxed);
+        }
+        self.current_ages_to_stay_in_cache
+            .store(new_ages, Ordering::Release);
+        self.flush_target_percent
+            .store(flush_target_percent, Ordering::Relaxed);
+    }
+
+    /// return the (ages to stay in cache, flush target percent) to use next.
+    /// Over budget, items are kept in the cache for fewer ages and flushed faster.
+    /// Comfortably under budget, items are kept longer if too many lookups had to go to disk.
+    fn next_memory_budget_settings(
+        ages: Age,
+        max_ages: Age,
+        estimate_mem_bytes: usize,
+        budget_bytes: usize,
+        found_in_mem: u64,
+        loaded_from_disk: u64,
+    ) -> (Age, u64) {
+        if estimate_mem_bytes >= budget_bytes {
+            (
+                ages.saturating_sub(1).max(1),
+                OVER_BUDGET_FLUSH_TARGET_PERCENT,
+            )
+        } else if estimate_mem_bytes < budget_bytes / 10 * 9
+            && loaded_from_disk * 100
+                > (found_in_mem + loaded_from_disk) * MAX_PERCENT_LOOKUPS_LOADED_FROM_DISK
+        {
+            (
+                ages.saturating_add(1).min(max_ages),
+                DEFAULT_FLUSH_TARGET_PERCENT,
+            )
+        } else {
+            (ages, DEFAULT_FLUSH_TARGET_PERCENT)
+        }
+    }
+
+    /// memory use of the index and the current memory budget settings
+    pub fn memory_stats(&self) -> AccountsIndexMemoryStats {
+        let in_mem_per_bucket_counts = (0..self.bins)
+            .map(|bin| self.stats.count_in_bucket(bin))
+            .collect::<Vec<_>>();
+        let (min_in_bin_mem, max_in_bin_mem, _, _) =
+            BucketMapHolderStats::get_stats(in_mem_per_bucket_counts);
+        let count_on_disk = self
+            .disk
+            .as_ref()
+            .map(|disk| {
+                (0..self.bins)
+                    .map(|bin| disk.get_bucket_from_index(bin).bucket_len() as usize)
+                    .sum()
+            })
+            .unwrap_or_default();
+        AccountsIndexMemoryStats {
+            bins: self.bins,
+            disk_index_enabled: self.is_disk_index_enabled(),
+            mem_budget_mb: self.mem_budget_mb,
+            mem_budget_tuning: self.mem_budget_tuning,
+            estimate_mem_bytes: self.estimate_mem_bytes() as u64,
+            count: self.stats.total_count(),
+            count_in_mem: self.stats.count_in_mem.load(Ordering::Relaxed),
+            count_on_disk,
+            min_in_bin_mem,
+            max_in_bin_mem,
+            ages_to_stay_in_cache: self.current_ages_to_stay_in_cache(),
+            max_ages_to_stay_in_cache: self.ages_to_stay_in_cache,
+            flush_target_percent: self.flush_target_percent.load(Ordering::Relaxed),
+            lookups_found_in_mem: self.stats.lookups_found_in_mem.load(Ordering::Relaxed),
+            lookups_loaded_from_disk: self.stats.lookups_loaded_from_disk.load(Ordering::Relaxed),
+            budget_ages_increased: self.stats.budget_ages_increased.load(Ordering::Relaxed),
+            budget_ages_decreased: self.stats.budget_ages_decreased.load(Ordering::Relaxed),
+        }
+    }
+
     pub fn future_age_to_flush(&self, is_cached: bool) -> Age {
         if is_cached {
             &self.future_age_to_flush_cached
@@ -193,11 +342,23 @@ impl<T: IndexValue> BucketMapHolder<T> {
     }
 
     pub fn new(bins: usize, config: &Option<AccountsIndexConfig>, threads: usize) -> Self {
-        const DEFAULT_AGE_TO_STAY_IN_CACHE: Age = 5;
+        let mem_budget_tuning = matches!(
+            config.as_ref().map(|config| &config.index_limit_mb),
+            Some(IndexLimitMb::Budget(_))
+        );
         let ages_to_stay_in_cache = config
             .as_ref()
             .and_then(|config| config.ages_to_stay_in_cache)
-            .unwrap_or(DEFAULT_AGE_TO_STAY_IN_CACHE);
+            .unwrap_or(if mem_budget_tuning {
+                DEFAULT_MAX_AGE_TO_STAY_IN_CACHE_WITH_BUDGET
+            } else {
+                DEFAULT_AGE_TO_STAY_IN_CACHE
+            });
+        let current_ages_to_stay_in_cache = if mem_budget_tuning {
+            ages_to_stay_in_cache.min(DEFAULT_AGE_TO_STAY_IN_CACHE)
+        } else {
+            ages_to_stay_in_cache
+        };
 
         let mut bucket_config = BucketMapConfig::new(bins);
         bucket_config.drives = config.as_ref().and_then(|config| config.drives.clone());
@@ -208,6 +369,8 @@ impl<T: IndexValue> BucketMapHolder<T> {
         {
             // creator said to use disk idx with a specific limit
             IndexLimitMb::Limit(mb) => Some(*mb),
+            // creator said to use disk idx and tune the in-mem idx to stay close to a budget
+            IndexLimitMb::Budget(mb) => Some(*mb),
             // creator said InMemOnly, so no disk index
             IndexLimitMb::InMemOnly => None,
             // whatever started us didn't specify whether to use the acct idx
@@ -241,11 +404,16 @@ impl<T: IndexValue> BucketMapHolder<T> {
         Self {
             disk,
             ages_to_stay_in_cache,
+            mem_budget_tuning,
+            current_ages_to_stay_in_cache: AtomicU8::new(current_ages_to_stay_in_cache),
+            flush_target_percent: AtomicU64::new(DEFAULT_FLUSH_TARGET_PERCENT),
+            last_lookups_found_in_mem: AtomicU64::default(),
+            last_lookups_loaded_from_disk: AtomicU64::default(),
             count_buckets_flushed: AtomicUsize::default(),
             // age = 0
             age: AtomicU8::default(),
             // future age = age (=0) + ages_to_stay_in_cache
-            future_age_to_flush: AtomicU8::new(ages_to_stay_in_cache),
+            future_age_to_flush: AtomicU8::new(current_ages_to_stay_in_cache),
             // effectively age (0) - 1. So, the oldest possible age from 'now'
             future_age_to_flush_cached: AtomicU8::new(0_u8.wrapping_sub(1)),
             stats: BucketMapHolderStats::new(bins),
@@ -282,7 +450,8 @@ impl<T: IndexValue> BucketMapHolder<T> {
         elapsed_ms: u64,
         bins_flushed: u64,
     ) -> Option<u64> {
-        let target_percent = 90; // aim to finish in 90% of the allocated time
+        // aim to finish in this % of the allocated time
+        let target_percent = self.flush_target_percent.load(Ordering::Relaxed);
         let remaining_ms = (interval_ms * target_percent / 100).saturating_sub(elapsed_ms);
         let remaining_bins = (self.bins as u64).saturating_sub(bins_flushed);
         if remaining_bins == 0 || remaining_ms == 0 || elapsed_ms == 0 || bins_flushed == 0 {
@@ -502,6 +671,100 @@ pub mod tests {
         assert!(test.is_disk_index_enabled());
     }
 
+    #[test]
+    fn test_next_memory_budget_settings() {
+        let budget = 1_000;
+        let max_ages = 10;
+        let next = BucketMapHolder::<u64>::next_memory_budget_settings;
+        // over budget: keep items for fewer ages and flush faster
+        assert_eq!(
+            next(5, max_ages, budget, budget, 100, 0),
+            (4, OVER_BUDGET_FLUSH_TARGET_PERCENT)
+        );
+        assert_eq!(
+            next(1, max_ages, budget * 2, budget, 0, 100),
+            (1, OVER_BUDGET_FLUSH_TARGET_PERCENT)
+        );
+        // under budget with too many lookups going to disk: keep items longer
+        assert_eq!(
+            next(5, max_ages, budget / 2, budget, 90, 10),
+            (6, DEFAULT_FLUSH_TARGET_PERCENT)
+        );
+        assert_eq!(
+            next(max_ages, max_ages, budget / 2, budget, 90, 10),
+            (max_ages, DEFAULT_FLUSH_TARGET_PERCENT)
+        );
+        // under budget with few lookups going to disk, or close to budget: no change
+        assert_eq!(
+            next(5, max_ages, budget / 2, budget, 1_000, 1),
+            (5, DEFAULT_FLUSH_TARGET_PERCENT)
+        );
+        assert_eq!(
+            next(5, max_ages, budget / 2, budget, 0, 0),
+            (5, DEFAULT_FLUSH_TARGET_PERCENT)
+        );
+        assert_eq!(
+            next(5, max_ages, budget * 95 / 100, budget, 90, 10),
+            (5, DEFAULT_FLUSH_TARGET_PERCENT)
+        );
+    }
+
+    #[test]
+    fn test_memory_budget_tuning() {
+        let bins = 1;
+        let config = AccountsIndexConfig {
+            index_limit_mb: IndexLimitMb::Budget(1),
+            ..AccountsIndexConfig::default()
+        };
+        let test = BucketMapHolder::<u64>::new(bins, &Some(config), 1);
+        assert!(test.is_disk_index_enabled());
+        assert!(test.mem_budget_tuning);
+        assert_eq!(
+            test.ages_to_stay_in_cache,
+            DEFAULT_MAX_AGE_TO_STAY_IN_CACHE_WITH_BUDGET
+        );
+        assert_eq!(
+            test.current_ages_to_stay_in_cache(),
+            DEFAULT_AGE_TO_STAY_IN_CACHE
+        );
+        let increment_ages = |ages: Age| {
+            for _ in 0..ages {
+                test.count_buckets_flushed
+                    .fetch_add(bins, Ordering::Release);
+                test.increment_age();
+            }
+        };
+
+        // lookups go to disk while under budget
+        test.stats
+            .lookups_loaded_from_disk
+            .fetch_add(10, Ordering::Relaxed);
+        increment_ages(AGES_PER_MEMORY_BUDGET_TUNING);
+        let ages = DEFAULT_AGE_TO_STAY_IN_CACHE + 1;
+        assert_eq!(test.current_ages_to_stay_in_cache(), ages);
+        assert_eq!(
+            test.future_age_to_flush(false),
+            test.current_age().wrapping_add(ages)
+        );
+
+        // no more lookups went to disk
+        increment_ages(AGES_PER_MEMORY_BUDGET_TUNING);
+        assert_eq!(test.current_ages_to_stay_in_cache(), ages);
+
+        // over budget
+        let over_budget = 1024 * 1024 / InMemAccountsIndex::<u64>::approx_size_of_one_entry() + 1;
+        test.stats.add_mem_count(0, over_budget);
+        increment_ages(AGES_PER_MEMORY_BUDGET_TUNING);
+        let stats = test.memory_stats();
+        assert_eq!(stats.ages_to_stay_in_cache, ages - 1);
+        assert_eq!(stats.flush_target_percent, OVER_BUDGET_FLUSH_TARGET_PERCENT);
+        assert_eq!(stats.budget_ages_increased, 1);
+        assert_eq!(stats.budget_ages_decreased, 1);
+        assert_eq!(stats.count_in_mem, over_budget);
+        assert_eq!(stats.mem_budget_mb, Some(1));
+        assert!(stats.estimate_mem_bytes >= 1024 * 1024);
+    }
+
     #[test]
     fn test_age_time() {
         solana_logger::setup();
@@ -53,6 +53,39 @@ pub struct BucketMapHolderStats {
     bins: u64,
     pub estimate_mem: AtomicU64,
     pub flush_should_evict_us: AtomicU64,
+    /// lookups of entries found in the in-mem index. Never reset, used to tune a memory budget.
+    pub lookups_found_in_mem: AtomicU64,
+    /// lookups of entries which had to be loaded from disk. Never reset, used to tune a memory budget.
+    pub lookups_loaded_from_disk: AtomicU64,
+    /// # times the memory budget tuning increased the ages to stay in cache
+    pub budget_ages_increased: AtomicU64,
+    /// # times the memory budget tuning decreased the ages to stay in cache
+    pub budget_ages_decreased: AtomicU64,
+}
+
+/// snapshot of the accounts index memory use and of the decisions made to stay within its memory budget
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountsIndexMemoryStats {
+    pub bins: usize,
+    pub disk_index_enabled: bool,
+    /// how much mb the in-mem index is allowed to use. Rest goes to disk.
+    pub mem_budget_mb: Option<usize>,
+    /// true if ages to stay in cache and flush rate are tuned at runtime to stay within 'mem_budget_mb'
+    pub mem_budget_tuning: bool,
+    pub estimate_mem_bytes: u64,
+    pub count: usize,
+    pub count_in_mem: usize,
+    pub count_on_disk: usize,
+    pub min_in_bin_mem: usize,
+    pub max_in_bin_mem: usize,
+    pub ages_to_stay_in_cache: u8,
+    pub max_ages_to_stay_in_cache: u8,
+    /// % of an age interval in which all bins are aimed to be flushed
+    pub flush_target_percent: u64,
+    pub lookups_found_in_mem: u64,
+    pub lookups_loaded_from_disk: u64,
+    pub budget_ages_increased: u64,
+    pub budget_ages_decreased: u64,
 }
 
 impl BucketMapHolderStats {
@@ -127,7 +160,7 @@ impl BucketMapHolderStats {
     }
 
     /// return min, max, sum, median of data
-    fn get_stats(mut data: Vec<usize>) -> (usize, usize, usize, usize) {
+    pub(crate) fn get_stats(mut data: Vec<usize>) -> (usize, usize, usize, usize) {
         if data.is_empty() {
             (0, 0, 0, 0)
         } else {
@@ -464,6 +497,21 @@ impl BucketMapHolderStats {
                         .swap(0, Ordering::Relaxed),
                     i64
                 ),
+                (
+                    "ages_to_stay_in_cache",
+                    storage.current_ages_to_stay_in_cache(),
+                    i64
+                ),
+                (
+                    "budget_ages_increased",
+                    self.budget_ages_increased.load(Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "budget_ages_decreased",
+                    self.budget_ages_decreased.load(Ordering::Relaxed),
+                    i64
+                ),
             );
         } else {
             datapoint_info!(
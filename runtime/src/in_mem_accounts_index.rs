@@ -245,6 +245,7 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
                 Some(_) => {
                     Self::update_time_stat(&self.stats().load_disk_found_us, m);
                     Self::update_stat(&self.stats().load_disk_found_count, 1);
+                    Self::update_stat(&self.stats().lookups_loaded_from_disk, 1);
                 }
                 None => {
                     Self::update_time_stat(&self.stats().load_disk_missing_us, m);
@@ -290,6 +291,7 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
 
         let stats = self.stats();
         let (count, time) = if found {
+            Self::update_stat(&stats.lookups_found_in_mem, 1);
             (&stats.gets_from_mem, &stats.get_mem_us)
         } else {
             (&stats.gets_missing, &stats.get_missing_us)
@@ -519,6 +521,7 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
     fn update_entry_stats(&self, stopped_measure: Measure, found: bool) {
         let stats = self.stats();
         let (count, time) = if found {
+            Self::update_stat(&stats.lookups_found_in_mem, 1);
             (&stats.entries_from_mem, &stats.entry_mem_us)
         } else {
             (&stats.entries_missing, &stats.entry_missing_us)
@@ -920,7 +923,7 @@ impl<T: IndexValue> InMemAccountsIndex<T> {
     }
 
     /// assumes 1 entry in the slot list. Ignores overhead of the HashMap and such
-    fn approx_size_of_one_entry() -> usize {
+    pub(crate) fn approx_size_of_one_entry() -> usize {
         std::mem::size_of::<T>()
             + std::mem::size_of::<Pubkey>()
             + std::mem::size_of::<AccountMapEntry<T>>()
@@ -0,0 +1,189 @@
+//! Read-through loading of the accounts a lazy-state validator doesn't have
+//!
+//! A lazy-state validator starts with little or none of a cluster's state. When a transaction
+//! references an account its bank doesn't have, the account is fetched from a
+//! `LazyAccountSource`, such as an RPC endpoint, an account set file or a local stand-in service,
+//! and stored in the write cache in the slot of that bank. Only that fork and its descendants see
+//! the fetched account. Simulations and RPC reads fetch accounts without storing them. See
+//! `Bank::load_lazy_accounts` and `Bank::get_lazy_accounts`.
+
+use {
+    crate::{account_set::AccountSet, ancestors::Ancestors},
+    log::*,
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        clock::Slot,
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        error::Error,
+        fmt::Debug,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex, MutexGuard, RwLock,
+        },
+    },
+};
+
+pub type LazyAccountSourceResult = Result<Option<AccountSharedData>, Box<dyn Error + Send + Sync>>;
+
+/// Where a lazy-state validator fetches the accounts it doesn't have from
+pub trait LazyAccountSource: Debug + Send + Sync {
+    /// the account at 'pubkey', or None if the source doesn't have it either
+    fn get_account(&self, pubkey: &Pubkey) -> LazyAccountSourceResult;
+}
+
+/// The accounts of an account set file, see `Bank::export_account_set`
+#[derive(Debug, Default)]
+pub struct AccountSetLazyAccountSource {
+    accounts: HashMap<Pubkey, Account>,
+}
+
+impl From<AccountSet> for AccountSetLazyAccountSource {
+    fn from(account_set: AccountSet) -> Self {
+        Self {
+            accounts: account_set.accounts.into_iter().collect(),
+        }
+    }
+}
+
+impl LazyAccountSource for AccountSetLazyAccountSource {
+    fn get_account(&self, pubkey: &Pubkey) -> LazyAccountSourceResult {
+        Ok(self
+            .accounts
+            .get(pubkey)
+            .map(|account| AccountSharedData::from(account.clone())))
+    }
+}
+
+#[derive(Debug)]
+pub struct LazyAccountLoader {
+    source: Box<dyn LazyAccountSource>,
+    /// the slots each account was fetched into
+    fetched: RwLock<HashMap<Pubkey, HashSet<Slot>>>,
+    /// accounts the source doesn't have, which are not asked for again
+    missing: RwLock<HashSet<Pubkey>>,
+    /// held while fetching, so concurrent batches don't store the same account twice
+    fetch_lock: Mutex<()>,
+    fetched_count: AtomicU64,
+    source_errors: AtomicU64,
+}
+
+impl LazyAccountLoader {
+    pub fn new(source: Box<dyn LazyAccountSource>) -> Self {
+        Self {
+            source,
+            fetched: RwLock::default(),
+            missing: RwLock::default(),
+            fetch_lock: Mutex::default(),
+            fetched_count: AtomicU64::default(),
+            source_errors: AtomicU64::default(),
+        }
+    }
+
+    /// true if 'pubkey' was fetched into one of 'ancestors'
+    pub fn is_fetched(&self, pubkey: &Pubkey, ancestors: &Ancestors) -> bool {
+        self.fetched
+            .read()
+            .unwrap()
+            .get(pubkey)
+            .map(|slots| slots.iter().any(|slot| ancestors.contains_key(slot)))
+            .unwrap_or_default()
+    }
+
+    /// number of accounts fetched from the source
+    pub fn fetched_count(&self) -> u64 {
+        self.fetched_count.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn lock_fetch(&self) -> MutexGuard<'_, ()> {
+        self.fetch_lock.lock().unwrap()
+    }
+
+    /// Fetch 'pubkey' from the source. Accounts the source doesn't have are remembered, source
+    /// errors are not, so the account is asked for again later.
+    pub(crate) fn fetch(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        if self.missing.read().unwrap().contains(pubkey) {
+            return None;
+        }
+        match self.source.get_account(pubkey) {
+            Ok(Some(account)) => Some(account),
+            Ok(None) => {
+                self.missing.write().unwrap().insert(*pubkey);
+                None
+            }
+            Err(err) => {
+                self.source_errors.fetch_add(1, Ordering::Relaxed);
+                warn!("Failed to fetch lazy account {}: {}", pubkey, err);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn record_fetched(&self, pubkey: Pubkey, slot: Slot) {
+        self.fetched_count.fetch_add(1, Ordering::Relaxed);
+        self.fetched
+            .write()
+            .unwrap()
+            .entry(pubkey)
+            .or_default()
+            .insert(slot);
+        datapoint_info!(
+            "lazy_account_loader-fetch",
+            ("slot", slot, i64),
+            ("fetched_count", self.fetched_count(), i64),
+            (
+                "source_errors",
+                self.source_errors.load(Ordering::Relaxed),
+                i64
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk::system_program};
+
+    #[derive(Debug, Default)]
+    struct FailingSource;
+
+    impl LazyAccountSource for FailingSource {
+        fn get_account(&self, _pubkey: &Pubkey) -> LazyAccountSourceResult {
+            Err("source unavailable".into())
+        }
+    }
+
+    #[test]
+    fn test_lazy_account_loader() {
+        let pubkey = Pubkey::new_unique();
+        let account = Account::new(42, 0, &system_program::id());
+        let loader =
+            LazyAccountLoader::new(Box::new(AccountSetLazyAccountSource::from(AccountSet {
+                slot: 0,
+                accounts: vec![(pubkey, account.clone())],
+            })));
+        assert_eq!(
+            loader.fetch(&pubkey),
+            Some(AccountSharedData::from(account))
+        );
+        let missing = Pubkey::new_unique();
+        assert_eq!(loader.fetch(&missing), None);
+        assert!(loader.missing.read().unwrap().contains(&missing));
+
+        let ancestors = Ancestors::from(vec![3, 4]);
+        assert!(!loader.is_fetched(&pubkey, &ancestors));
+        loader.record_fetched(pubkey, 5);
+        assert!(!loader.is_fetched(&pubkey, &ancestors));
+        loader.record_fetched(pubkey, 4);
+        assert!(loader.is_fetched(&pubkey, &ancestors));
+        assert_eq!(loader.fetched_count(), 2);
+
+        // failures are not remembered as missing
+        let loader = LazyAccountLoader::new(Box::<FailingSource>::default());
+        assert_eq!(loader.fetch(&pubkey), None);
+        assert!(loader.missing.read().unwrap().is_empty());
+        assert_eq!(loader.source_errors.load(Ordering::Relaxed), 1);
+    }
+}
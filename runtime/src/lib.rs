@@ -7,12 +7,14 @@ extern crate lazy_static;
 pub mod account_info;
 pub mod account_overrides;
 pub mod account_rent_state;
+pub mod account_set;
 pub mod account_storage;
 pub mod accounts;
 pub mod accounts_background_service;
 pub mod accounts_cache;
 pub mod accounts_db;
 pub mod accounts_hash;
+pub mod accounts_hash_dump;
 pub mod accounts_index;
 pub mod accounts_index_storage;
 pub mod accounts_update_notifier_interface;
@@ -43,6 +45,7 @@ pub mod in_mem_accounts_index;
 pub mod inline_spl_associated_token_account;
 pub mod inline_spl_token;
 pub mod inline_spl_token_2022;
+pub mod lazy_account_loader;
 pub mod loader_utils;
 pub mod message_processor;
 pub mod non_circulating_supply;
@@ -55,11 +58,13 @@ pub mod rent_collector;
 mod rent_paying_accounts_by_partition;
 mod rolling_bit_field;
 pub mod root_bank_cache;
+pub mod rooted_account_deltas;
 pub mod runtime_config;
 pub mod secondary_index;
 pub mod serde_snapshot;
 mod shared_buffer_reader;
 pub mod snapshot_archive_info;
+pub mod snapshot_archive_store;
 pub mod snapshot_config;
 pub mod snapshot_hash;
 pub mod snapshot_minimizer;
@@ -74,9 +79,11 @@ pub mod static_ids;
 pub mod status_cache;
 mod storable_accounts;
 mod system_instruction_processor;
+pub mod tiered_storage;
 pub mod transaction_batch;
 pub mod transaction_error_metrics;
 pub mod transaction_priority_details;
+pub mod transaction_trace;
 mod verify_accounts_hash_in_background;
 pub mod vote_account;
 pub mod vote_parser;
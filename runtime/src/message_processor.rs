@@ -3,6 +3,7 @@ use {
     solana_measure::measure::Measure,
     solana_program_runtime::{
         compute_budget::ComputeBudget,
+        execution_trace::ExecutionTracer,
         executor_cache::TransactionExecutorCache,
         invoke_context::{BuiltinProgram, InvokeContext},
         log_collector::LogCollector,
@@ -57,6 +58,7 @@ impl MessageProcessor {
         transaction_context: &mut TransactionContext,
         rent: Rent,
         log_collector: Option<Rc<RefCell<LogCollector>>>,
+        execution_tracer: Option<Rc<RefCell<ExecutionTracer>>>,
         tx_executor_cache: Rc<RefCell<TransactionExecutorCache>>,
         feature_set: Arc<FeatureSet>,
         compute_budget: ComputeBudget,
@@ -73,6 +75,7 @@ impl MessageProcessor {
             builtin_programs,
             Cow::Borrowed(sysvar_cache),
             log_collector,
+            execution_tracer,
             compute_budget,
             tx_executor_cache,
             feature_set,
@@ -311,6 +314,7 @@ mod tests {
             &mut transaction_context,
             rent_collector.rent,
             None,
+            None,
             tx_executor_cache.clone(),
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -361,6 +365,7 @@ mod tests {
             &mut transaction_context,
             rent_collector.rent,
             None,
+            None,
             tx_executor_cache.clone(),
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -401,6 +406,7 @@ mod tests {
             &mut transaction_context,
             rent_collector.rent,
             None,
+            None,
             tx_executor_cache,
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -538,6 +544,7 @@ mod tests {
             &mut transaction_context,
             rent_collector.rent,
             None,
+            None,
             tx_executor_cache.clone(),
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -572,6 +579,7 @@ mod tests {
             &mut transaction_context,
             rent_collector.rent,
             None,
+            None,
             tx_executor_cache.clone(),
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -603,6 +611,7 @@ mod tests {
             &mut transaction_context,
             rent_collector.rent,
             None,
+            None,
             tx_executor_cache,
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -683,6 +692,7 @@ mod tests {
             &mut transaction_context,
             RentCollector::default().rent,
             None,
+            None,
             Rc::new(RefCell::new(TransactionExecutorCache::default())),
             Arc::new(FeatureSet::all_enabled()),
             ComputeBudget::default(),
@@ -123,6 +123,8 @@ pub enum PrioritizationFeeError {
 /// block; and the minimum fee for each writable account in all transactions in this block. The only relevant
 /// write account minimum fees are those greater than the block minimum transaction fee, because the minimum fee needed to land
 /// a transaction is determined by Max( min_transaction_fee, min_writable_account_fees(key), ...)
+/// The fee of every transaction, overall and per writable account, is also kept so that
+/// percentiles can be estimated from recent blocks.
 #[derive(Debug)]
 pub struct PrioritizationFee {
     // The minimum prioritization fee of transactions that landed in this block.
@@ -131,6 +133,14 @@ pub struct PrioritizationFee {
     // The minimum prioritization fee of each writable account in transactions in this block.
     min_writable_account_fees: HashMap<Pubkey, u64>,
 
+    // The prioritization fee of every transaction in this block, sorted when the block is
+    // completed.
+    transaction_fees: Vec<u64>,
+
+    // The prioritization fees of the transactions write-locking each account in this block,
+    // sorted when the block is completed.
+    writable_account_fees: HashMap<Pubkey, Vec<u64>>,
+
     // Default to `false`, set to `true` when a block is completed, therefore the minimum fees recorded
     // are finalized, and can be made available for use (e.g., RPC query)
     is_finalized: bool,
@@ -144,6 +154,8 @@ impl Default for PrioritizationFee {
         PrioritizationFee {
             min_transaction_fee: u64::MAX,
             min_writable_account_fees: HashMap::new(),
+            transaction_fees: Vec::new(),
+            writable_account_fees: HashMap::new(),
             is_finalized: false,
             metrics: PrioritizationFeeMetrics::default(),
         }
@@ -163,6 +175,7 @@ impl PrioritizationFee {
                     self.min_transaction_fee = transaction_fee;
                 }
 
+                self.transaction_fees.push(transaction_fee);
                 for write_account in writable_accounts.iter() {
                     self.min_writable_account_fees
                         .entry(*write_account)
@@ -170,6 +183,10 @@ impl PrioritizationFee {
                             *write_lock_fee = std::cmp::min(*write_lock_fee, transaction_fee)
                         })
                         .or_insert(transaction_fee);
+                    self.writable_account_fees
+                        .entry(*write_account)
+                        .or_default()
+                        .push(transaction_fee);
                 }
 
                 self.metrics
@@ -198,6 +215,10 @@ impl PrioritizationFee {
             return Err(PrioritizationFeeError::BlockIsAlreadyFinalized);
         }
         self.prune_irrelevant_writable_accounts();
+        self.transaction_fees.sort_unstable();
+        self.writable_account_fees
+            .values_mut()
+            .for_each(|fees| fees.sort_unstable());
         self.is_finalized = true;
         Ok(())
     }
@@ -214,6 +235,17 @@ impl PrioritizationFee {
         self.min_writable_account_fees.iter()
     }
 
+    /// The prioritization fees of all transactions in the block, sorted once the block is finalized
+    pub fn get_transaction_fees(&self) -> &[u64] {
+        &self.transaction_fees
+    }
+
+    /// The prioritization fees of the transactions that write-lock `key`, sorted once the block is
+    /// finalized
+    pub fn get_writable_account_fee_distribution(&self, key: &Pubkey) -> Option<&[u64]> {
+        self.writable_account_fees.get(key).map(Vec::as_slice)
+    }
+
     pub fn get_writable_accounts_count(&self) -> usize {
         self.min_writable_account_fees.len()
     }
@@ -364,6 +396,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fee_distributions() {
+        let write_account_a = Pubkey::new_unique();
+        let write_account_b = Pubkey::new_unique();
+
+        let mut prioritization_fee = PrioritizationFee::default();
+        assert!(prioritization_fee.update(9, &[write_account_a]).is_ok());
+        assert!(prioritization_fee
+            .update(2, &[write_account_a, write_account_b])
+            .is_ok());
+        assert!(prioritization_fee.update(5, &[write_account_a]).is_ok());
+        assert!(prioritization_fee.mark_block_completed().is_ok());
+
+        // distributions survive pruning, and are sorted
+        assert!(prioritization_fee
+            .get_writable_account_fee(&write_account_b)
+            .is_none());
+        assert_eq!(prioritization_fee.get_transaction_fees(), &[2, 5, 9]);
+        assert_eq!(
+            prioritization_fee
+                .get_writable_account_fee_distribution(&write_account_a)
+                .unwrap(),
+            &[2, 5, 9]
+        );
+        assert_eq!(
+            prioritization_fee
+                .get_writable_account_fee_distribution(&write_account_b)
+                .unwrap(),
+            &[2]
+        );
+        assert!(prioritization_fee
+            .get_writable_account_fee_distribution(&Pubkey::new_unique())
+            .is_none());
+    }
+
     #[test]
     fn test_mark_block_completed() {
         let mut prioritization_fee = PrioritizationFee::default();
@@ -23,7 +23,50 @@ use {
 /// The maximum number of blocks to keep in `PrioritizationFeeCache`, ie.
 /// the amount of history generally desired to estimate the prioritization fee needed to
 /// land a transaction in the current block.
-const MAX_NUM_RECENT_BLOCKS: u64 = 150;
+pub const MAX_NUM_RECENT_BLOCKS: u64 = 150;
+
+/// Percentiles of the prioritization fees paid in recent blocks, see
+/// `PrioritizationFeeCache::get_prioritization_fee_estimate()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PrioritizationFeeEstimate {
+    pub p25: u64,
+    pub p50: u64,
+    pub p75: u64,
+    pub p90: u64,
+    /// The number of finalized blocks the percentiles were computed over
+    pub num_blocks: usize,
+}
+
+impl PrioritizationFeeEstimate {
+    fn from_fees(fees: &[u64], num_blocks: usize) -> Self {
+        Self {
+            p25: percentile(fees, 25),
+            p50: percentile(fees, 50),
+            p75: percentile(fees, 75),
+            p90: percentile(fees, 90),
+            num_blocks,
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        Self {
+            p25: self.p25.max(other.p25),
+            p50: self.p50.max(other.p50),
+            p75: self.p75.max(other.p75),
+            p90: self.p90.max(other.p90),
+            num_blocks: self.num_blocks,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted `fees`, 0 if there are none
+fn percentile(fees: &[u64], percentile: usize) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    let rank = (percentile * fees.len() + 99) / 100;
+    fees[rank.saturating_sub(1).min(fees.len() - 1)]
+}
 
 #[derive(Debug, Default)]
 struct PrioritizationFeeCacheMetrics {
@@ -378,6 +421,64 @@ impl PrioritizationFeeCache {
             .flatten()
             .collect()
     }
+
+    /// Estimate the prioritization fee needed to land a transaction write-locking
+    /// `account_keys`, from the fees paid in the last `max_num_blocks` finalized blocks.
+    ///
+    /// Each percentile is the highest of that percentile over all transactions, and over the
+    /// transactions write-locking each of `account_keys`, in the same way that
+    /// `get_prioritization_fees()` combines minimum fees. Returns None if no block has been
+    /// finalized yet.
+    pub fn get_prioritization_fee_estimate(
+        &self,
+        account_keys: &[Pubkey],
+        max_num_blocks: usize,
+    ) -> Option<PrioritizationFeeEstimate> {
+        let cache = self.cache.read().unwrap();
+        let mut blocks = cache
+            .iter()
+            .filter_map(|(slot, prioritization_fee)| {
+                let prioritization_fee = prioritization_fee.lock().unwrap();
+                prioritization_fee
+                    .is_finalized()
+                    .then(|| (*slot, prioritization_fee))
+            })
+            .collect::<Vec<_>>();
+        if blocks.is_empty() {
+            return None;
+        }
+        blocks.sort_unstable_by(|(slot_a, _), (slot_b, _)| slot_b.cmp(slot_a));
+        blocks.truncate(max_num_blocks);
+        let num_blocks = blocks.len();
+
+        let collect_fees = |fees_of_block: &dyn Fn(&PrioritizationFee) -> Option<&[u64]>| {
+            let mut fees = blocks
+                .iter()
+                .filter_map(|(_slot, prioritization_fee)| fees_of_block(prioritization_fee))
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+            fees.sort_unstable();
+            fees
+        };
+        let block_estimate = PrioritizationFeeEstimate::from_fees(
+            &collect_fees(&|prioritization_fee| Some(prioritization_fee.get_transaction_fees())),
+            num_blocks,
+        );
+        Some(
+            account_keys
+                .iter()
+                .fold(block_estimate, |estimate, account_key| {
+                    let account_fees = collect_fees(&|prioritization_fee| {
+                        prioritization_fee.get_writable_account_fee_distribution(account_key)
+                    });
+                    estimate.max(PrioritizationFeeEstimate::from_fees(
+                        &account_fees,
+                        num_blocks,
+                    ))
+                }),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -795,4 +896,95 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 50), 0);
+        assert_eq!(percentile(&[7], 25), 7);
+        let fees = (1..=100).collect::<Vec<_>>();
+        assert_eq!(percentile(&fees, 25), 25);
+        assert_eq!(percentile(&fees, 90), 90);
+        assert_eq!(percentile(&[1, 2, 3], 50), 2);
+        assert_eq!(percentile(&[1, 2, 3], 90), 3);
+    }
+
+    #[test]
+    fn test_get_prioritization_fee_estimate() {
+        let write_account_a = Pubkey::new_unique();
+        let write_account_b = Pubkey::new_unique();
+
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let bank0 = Bank::new_for_benches(&genesis_config);
+        let bank_forks = BankForks::new(bank0);
+        let bank = bank_forks.working_bank();
+        let collector = solana_sdk::pubkey::new_rand();
+        let bank1 = Arc::new(Bank::new_from_parent(&bank, &collector, 1));
+        let bank2 = Arc::new(Bank::new_from_parent(&bank, &collector, 2));
+
+        let mut prioritization_fee_cache = PrioritizationFeeCache::default();
+        assert!(prioritization_fee_cache
+            .get_prioritization_fee_estimate(&[], 10)
+            .is_none());
+
+        let txs = (1..=4)
+            .map(|fee| {
+                build_sanitized_transaction_for_test(fee, &Pubkey::new_unique(), &write_account_a)
+            })
+            .collect::<Vec<_>>();
+        sync_update(&mut prioritization_fee_cache, bank1, txs.iter());
+        sync_finalize_priority_fee_for_test(&mut prioritization_fee_cache, 1);
+
+        let txs = vec![
+            build_sanitized_transaction_for_test(100, &Pubkey::new_unique(), &write_account_b),
+            build_sanitized_transaction_for_test(0, &Pubkey::new_unique(), &Pubkey::new_unique()),
+        ];
+        sync_update(&mut prioritization_fee_cache, bank2, txs.iter());
+        sync_finalize_priority_fee_for_test(&mut prioritization_fee_cache, 2);
+
+        // all transactions of both blocks: [0, 1, 2, 3, 4, 100]
+        assert_eq!(
+            prioritization_fee_cache.get_prioritization_fee_estimate(&[], 10),
+            Some(PrioritizationFeeEstimate {
+                p25: 1,
+                p50: 2,
+                p75: 4,
+                p90: 100,
+                num_blocks: 2,
+            })
+        );
+        // only the last block: [0, 100]
+        assert_eq!(
+            prioritization_fee_cache.get_prioritization_fee_estimate(&[], 1),
+            Some(PrioritizationFeeEstimate {
+                p25: 0,
+                p50: 0,
+                p75: 100,
+                p90: 100,
+                num_blocks: 1,
+            })
+        );
+        // account a: [1, 2, 3, 4], below the overall p90
+        assert_eq!(
+            prioritization_fee_cache.get_prioritization_fee_estimate(&[write_account_a], 10),
+            Some(PrioritizationFeeEstimate {
+                p25: 1,
+                p50: 2,
+                p75: 4,
+                p90: 100,
+                num_blocks: 2,
+            })
+        );
+        // account b: [100]
+        assert_eq!(
+            prioritization_fee_cache
+                .get_prioritization_fee_estimate(&[write_account_a, write_account_b], 10),
+            Some(PrioritizationFeeEstimate {
+                p25: 100,
+                p50: 100,
+                p75: 100,
+                p90: 100,
+                num_blocks: 2,
+            })
+        );
+    }
 }
@@ -0,0 +1,175 @@
+//! Account writes of rooted slots
+//!
+//! `AccountsUpdateNotifierInterface::notify_account_update` is called as accounts are stored, on
+//! every fork. When rooted account deltas are enabled, AccountsDb also buffers the account writes
+//! of each unrooted slot. Once `BankForks::set_root` roots a slot, the buffered writes of the slot
+//! are notified as a single `RootedAccountDelta`: the last write of each account, ordered by write
+//! version. Deltas are notified in slot order, and the writes of slots that can no longer be rooted
+//! are discarded, so abandoned forks are never notified.
+
+use {
+    crossbeam_channel::{Receiver, Sender},
+    dashmap::DashMap,
+    solana_sdk::{account::AccountSharedData, clock::Slot, pubkey::Pubkey, signature::Signature},
+    std::collections::{hash_map::Entry, HashMap},
+};
+
+/// The last write of an account in a rooted slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootedAccount {
+    pub pubkey: Pubkey,
+    pub account: AccountSharedData,
+    pub write_version: u64,
+    /// First signature of the transaction that wrote the account, if any
+    pub txn_signature: Option<Signature>,
+}
+
+/// The accounts written in a rooted slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootedAccountDelta {
+    pub slot: Slot,
+    /// The previous rooted slot
+    pub parent: Option<Slot>,
+    /// One per account written in the slot, ordered by write version
+    pub accounts: Vec<RootedAccount>,
+}
+
+pub type RootedAccountDeltaSender = Sender<RootedAccountDelta>;
+pub type RootedAccountDeltaReceiver = Receiver<RootedAccountDelta>;
+
+/// The account writes of unrooted slots, and where the deltas of rooted slots are sent
+#[derive(Debug, Default)]
+pub(crate) struct RootedAccountDeltas {
+    slots: DashMap<Slot, HashMap<Pubkey, RootedAccount>>,
+    sender: Option<RootedAccountDeltaSender>,
+}
+
+impl RootedAccountDeltas {
+    pub(crate) fn new(sender: Option<RootedAccountDeltaSender>) -> Self {
+        Self {
+            slots: DashMap::default(),
+            sender,
+        }
+    }
+
+    /// buffer a write of 'pubkey' in 'slot'. Only the write with the highest write version is kept.
+    pub(crate) fn record(
+        &self,
+        slot: Slot,
+        pubkey: &Pubkey,
+        account: &AccountSharedData,
+        txn_signature: Option<&Signature>,
+        write_version: u64,
+    ) {
+        let rooted_account = RootedAccount {
+            pubkey: *pubkey,
+            account: account.clone(),
+            write_version,
+            txn_signature: txn_signature.copied(),
+        };
+        match self.slots.entry(slot).or_default().entry(*pubkey) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().write_version < write_version {
+                    entry.insert(rooted_account);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(rooted_account);
+            }
+        }
+    }
+
+    /// return the deltas of 'rooted_slots', which are (slot, parent) in slot order.
+    /// Writes buffered for older slots that were not rooted are discarded: they are on abandoned forks.
+    pub(crate) fn take_rooted(
+        &self,
+        rooted_slots: &[(Slot, Option<Slot>)],
+    ) -> Vec<RootedAccountDelta> {
+        let deltas = rooted_slots
+            .iter()
+            .map(|(slot, parent)| {
+                let mut accounts = self
+                    .slots
+                    .remove(slot)
+                    .map(|(_, accounts)| accounts.into_values().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                accounts.sort_unstable_by_key(|account| account.write_version);
+                RootedAccountDelta {
+                    slot: *slot,
+                    parent: *parent,
+                    accounts,
+                }
+            })
+            .collect();
+        if let Some((root, _)) = rooted_slots.last() {
+            self.slots.retain(|slot, _| slot > root);
+        }
+        deltas
+    }
+
+    /// discard the writes buffered for 'slots', which are being removed without being rooted
+    pub(crate) fn discard(&self, slots: impl Iterator<Item = Slot>) {
+        slots.for_each(|slot| {
+            self.slots.remove(&slot);
+        });
+    }
+
+    pub(crate) fn send(&self, delta: RootedAccountDelta) {
+        if let Some(sender) = &self.sender {
+            // the receiver may have been dropped, deltas are then no longer needed
+            let _ = sender.send(delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crossbeam_channel::unbounded};
+
+    #[test]
+    fn test_rooted_account_deltas() {
+        let (sender, receiver) = unbounded();
+        let deltas = RootedAccountDeltas::new(Some(sender));
+        let pubkey1 = Pubkey::new_unique();
+        let pubkey2 = Pubkey::new_unique();
+        let account = |lamports| AccountSharedData::new(lamports, 0, &Pubkey::default());
+
+        // slot 1 is on an abandoned fork, slots 2 and 3 are rooted
+        deltas.record(1, &pubkey1, &account(1), None, 1);
+        deltas.record(2, &pubkey1, &account(2), None, 3);
+        deltas.record(2, &pubkey2, &account(3), None, 2);
+        // a lower write version does not replace a higher one
+        deltas.record(2, &pubkey1, &account(4), None, 0);
+        deltas.record(3, &pubkey1, &account(5), None, 4);
+        deltas.record(3, &pubkey1, &account(6), None, 5);
+        deltas.record(4, &pubkey2, &account(7), None, 6);
+
+        let rooted = deltas.take_rooted(&[(2, Some(0)), (3, Some(2))]);
+        assert_eq!(rooted.len(), 2);
+        assert_eq!((rooted[0].slot, rooted[0].parent), (2, Some(0)));
+        assert_eq!(
+            rooted[0]
+                .accounts
+                .iter()
+                .map(|account| (account.pubkey, account.write_version))
+                .collect::<Vec<_>>(),
+            vec![(pubkey2, 2), (pubkey1, 3)]
+        );
+        assert_eq!(rooted[0].accounts[1].account, account(2));
+        assert_eq!((rooted[1].slot, rooted[1].parent), (3, Some(2)));
+        assert_eq!(rooted[1].accounts.len(), 1);
+        assert_eq!(rooted[1].accounts[0].account, account(6));
+
+        // the abandoned slot was discarded, the newer slot is still buffered
+        assert!(!deltas.slots.contains_key(&1));
+        assert!(deltas.slots.contains_key(&4));
+        deltas.discard(std::iter::once(4));
+        assert!(deltas.slots.is_empty());
+
+        // a rooted slot without writes has an empty delta
+        let rooted = deltas.take_rooted(&[(5, Some(3))]);
+        assert!(rooted[0].accounts.is_empty());
+        rooted.into_iter().for_each(|delta| deltas.send(delta));
+        assert_eq!(receiver.try_recv().unwrap().slot, 5);
+    }
+}
@@ -1,4 +1,9 @@
-use solana_program_runtime::compute_budget::ComputeBudget;
+use {
+    crate::lazy_account_loader::LazyAccountLoader,
+    solana_program_runtime::compute_budget::ComputeBudget,
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashSet, sync::Arc},
+};
 
 /// Encapsulates flags that can be used to tweak the runtime behavior.
 #[derive(AbiExample, Debug, Default, Clone)]
@@ -7,4 +12,9 @@ pub struct RuntimeConfig {
     pub compute_budget: Option<ComputeBudget>,
     pub log_messages_bytes_limit: Option<usize>,
     pub transaction_account_lock_limit: Option<usize>,
+    /// fetch the accounts the banks don't have from here, see `Bank::load_lazy_accounts`
+    pub lazy_account_loader: Option<Arc<LazyAccountLoader>>,
+    /// features kept inactive whatever their accounts say, to compare replays with and without
+    /// them
+    pub deactivated_features: HashSet<Pubkey>,
 }
@@ -48,7 +48,13 @@ fn copy_append_vecs<P: AsRef<Path>>(
         let storage_path = storage_entry.get_path();
         let file_name = AppendVec::file_name(storage_entry.slot(), storage_entry.append_vec_id());
         let output_path = output_dir.as_ref().join(file_name);
-        std::fs::copy(storage_path, &output_path)?;
+        if storage_entry.accounts.is_cold() {
+            storage_entry
+                .accounts
+                .write_contents(&mut std::fs::File::create(&output_path)?)?;
+        } else {
+            std::fs::copy(storage_path, &output_path)?;
+        }
 
         // Read new file into append-vec and build new entry
         let (append_vec, num_accounts) =
@@ -55,6 +55,12 @@ pub struct SnapshotArchiveInfo {
     pub archive_format: ArchiveFormat,
 }
 
+impl SnapshotArchiveInfoGetter for SnapshotArchiveInfo {
+    fn snapshot_archive_info(&self) -> &SnapshotArchiveInfo {
+        self
+    }
+}
+
 /// Information about a full snapshot archive: its path, slot, hash, and archive format
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct FullSnapshotArchiveInfo(SnapshotArchiveInfo);
@@ -0,0 +1,392 @@
+//! Stores that snapshot archives are published to and fetched from, other than the validators
+//!
+//! A store holds the archives under their file names, along with a manifest of the archives it
+//! holds. The manifest records the size and SHA-256 of every archive, which are verified when
+//! the archive is fetched.
+//!
+//! The manifest only vouches for the integrity of the transfer: the snapshot hash in an archive's
+//! file name must still be checked against the snapshot hashes of trusted validators, and the
+//! snapshot is verified against its hash when it is loaded.
+
+use {
+    crate::{snapshot_archive_info::SnapshotArchiveInfoGetter, snapshot_package::SnapshotType},
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        clock::Slot,
+        hash::{Hash, Hasher},
+    },
+    std::{
+        fmt::Debug,
+        fs::{self, File},
+        io::{self, Read},
+        path::{Path, PathBuf},
+    },
+};
+
+/// Key of the manifest in a store
+pub const SNAPSHOT_ARCHIVE_MANIFEST_KEY: &str = "snapshot-archives.json";
+
+/// A store of objects, addressed by keys
+pub trait SnapshotArchiveStore: Debug + Send + Sync {
+    /// Stores the contents of the file at `path` under `key`
+    fn put_file(&self, key: &str, path: &Path) -> io::Result<()>;
+
+    /// Stores `bytes` under `key`
+    fn put_bytes(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Writes the object stored under `key` to the file at `path`
+    fn get_file(&self, key: &str, path: &Path) -> io::Result<()>;
+
+    /// Returns the object stored under `key`, or `None` if there is none
+    fn get_bytes(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Removes the object stored under `key`, if any
+    fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+/// A store in a directory of the local file system, or of a mounted network file system
+#[derive(Debug)]
+pub struct DirectorySnapshotArchiveStore {
+    dir: PathBuf,
+}
+
+impl DirectorySnapshotArchiveStore {
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        if key.is_empty() || key.contains('/') || key.starts_with('.') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid key: {key}"),
+            ));
+        }
+        Ok(self.dir.join(key))
+    }
+
+    /// Writes to a temporary file first, so that readers never see a partial object
+    fn write_atomically(
+        &self,
+        key: &str,
+        write: impl FnOnce(&Path) -> io::Result<()>,
+    ) -> io::Result<()> {
+        let path = self.path(key)?;
+        let tmp_path = self.dir.join(format!(".{key}.tmp"));
+        write(&tmp_path)?;
+        fs::rename(tmp_path, path)
+    }
+}
+
+impl SnapshotArchiveStore for DirectorySnapshotArchiveStore {
+    fn put_file(&self, key: &str, path: &Path) -> io::Result<()> {
+        self.write_atomically(key, |tmp_path| fs::copy(path, tmp_path).map(|_| ()))
+    }
+
+    fn put_bytes(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.write_atomically(key, |tmp_path| fs::write(tmp_path, bytes))
+    }
+
+    fn get_file(&self, key: &str, path: &Path) -> io::Result<()> {
+        fs::copy(self.path(key)?, path).map(|_| ())
+    }
+
+    fn get_bytes(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(self.path(key)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(key)?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A snapshot archive held by a store
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSnapshotArchive {
+    /// Key of the archive, which is its file name
+    pub file_name: String,
+    pub slot: Slot,
+    /// Slot of the full snapshot an incremental snapshot is based on
+    pub base_slot: Option<Slot>,
+    /// Snapshot hash, as in the file name
+    pub hash: String,
+    pub size: u64,
+    /// SHA-256 of the archive file, in base58
+    pub sha256: String,
+}
+
+/// The snapshot archives held by a store, from oldest to newest
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotArchiveManifest {
+    pub full: Vec<StoredSnapshotArchive>,
+    /// Incremental snapshot archives, all based on the newest full snapshot archive
+    pub incremental: Vec<StoredSnapshotArchive>,
+}
+
+impl SnapshotArchiveManifest {
+    /// Finds an archive by slot and snapshot hash
+    pub fn find(
+        &self,
+        snapshot_type: SnapshotType,
+        slot: Slot,
+        hash: &Hash,
+    ) -> Option<&StoredSnapshotArchive> {
+        let (archives, base_slot) = match snapshot_type {
+            SnapshotType::FullSnapshot => (&self.full, None),
+            SnapshotType::IncrementalSnapshot(base_slot) => (&self.incremental, Some(base_slot)),
+        };
+        let hash = hash.to_string();
+        archives.iter().find(|archive| {
+            archive.slot == slot && archive.hash == hash && archive.base_slot == base_slot
+        })
+    }
+}
+
+fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Loads the manifest of `store`, which is empty if nothing was published yet
+pub fn load_snapshot_archive_manifest(
+    store: &dyn SnapshotArchiveStore,
+) -> io::Result<SnapshotArchiveManifest> {
+    match store.get_bytes(SNAPSHOT_ARCHIVE_MANIFEST_KEY)? {
+        Some(bytes) => serde_json::from_slice(&bytes).map_err(invalid_data),
+        None => Ok(SnapshotArchiveManifest::default()),
+    }
+}
+
+/// Returns the size and SHA-256 of a file
+fn file_size_and_sha256(path: &Path) -> io::Result<(u64, Hash)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::default();
+    let mut buffer = vec![0; 1024 * 1024];
+    let mut size = 0;
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.hash(&buffer[..n]);
+        size += n as u64;
+    }
+    Ok((size, hasher.result()))
+}
+
+/// Uploads a snapshot archive to `store` and records it in the manifest. Archives that no longer
+/// need to be retained are removed from the store, following the retention rules of the
+/// snapshot archive directories.
+pub fn publish_snapshot_archive(
+    store: &dyn SnapshotArchiveStore,
+    snapshot_archive_info: &impl SnapshotArchiveInfoGetter,
+    snapshot_type: SnapshotType,
+    maximum_full_snapshot_archives_to_retain: usize,
+    maximum_incremental_snapshot_archives_to_retain: usize,
+) -> io::Result<()> {
+    let path = snapshot_archive_info.path();
+    let file_name = path
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .ok_or_else(|| invalid_data(format!("invalid archive path: {}", path.display())))?
+        .to_string();
+    let (size, sha256) = file_size_and_sha256(path)?;
+    store.put_file(&file_name, path)?;
+
+    let archive = StoredSnapshotArchive {
+        file_name,
+        slot: snapshot_archive_info.slot(),
+        base_slot: match snapshot_type {
+            SnapshotType::FullSnapshot => None,
+            SnapshotType::IncrementalSnapshot(base_slot) => Some(base_slot),
+        },
+        hash: snapshot_archive_info.hash().0.to_string(),
+        size,
+        sha256: sha256.to_string(),
+    };
+    let mut manifest = load_snapshot_archive_manifest(store)?;
+    let mut removed = vec![];
+    match snapshot_type {
+        SnapshotType::FullSnapshot => {
+            manifest.full.retain(|stored| stored.slot != archive.slot);
+            manifest.full.push(archive);
+            // Incremental snapshot archives are only retained for the newest full snapshot
+            removed.append(&mut manifest.incremental);
+        }
+        SnapshotType::IncrementalSnapshot(_) => {
+            manifest
+                .incremental
+                .retain(|stored| stored.slot != archive.slot);
+            manifest.incremental.push(archive);
+        }
+    }
+    let num_full = manifest.full.len();
+    removed.extend(
+        manifest
+            .full
+            .drain(..num_full.saturating_sub(maximum_full_snapshot_archives_to_retain.max(1))),
+    );
+    let num_incremental = manifest.incremental.len();
+    removed.extend(manifest.incremental.drain(
+        ..num_incremental.saturating_sub(maximum_incremental_snapshot_archives_to_retain.max(1)),
+    ));
+
+    // Record the new archive before removing the old ones, so that the manifest never refers to
+    // a removed archive
+    let bytes = serde_json::to_vec_pretty(&manifest).map_err(invalid_data)?;
+    store.put_bytes(SNAPSHOT_ARCHIVE_MANIFEST_KEY, &bytes)?;
+    for stored in removed {
+        if let Err(err) = store.delete(&stored.file_name) {
+            warn!(
+                "Failed to remove {} from the snapshot archive store: {err}",
+                stored.file_name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Downloads an archive of the manifest of `store` into `dir`, verifying its size and SHA-256.
+/// Returns the path of the archive.
+pub fn fetch_snapshot_archive(
+    store: &dyn SnapshotArchiveStore,
+    archive: &StoredSnapshotArchive,
+    dir: &Path,
+) -> io::Result<PathBuf> {
+    let path = dir.join(&archive.file_name);
+    if path.file_name().and_then(|file_name| file_name.to_str()) != Some(&archive.file_name) {
+        return Err(invalid_data(format!(
+            "invalid archive file name: {}",
+            archive.file_name
+        )));
+    }
+    fs::create_dir_all(dir)?;
+    let tmp_path = dir.join(format!("tmp-{}", archive.file_name));
+    store.get_file(&archive.file_name, &tmp_path)?;
+    let (size, sha256) = file_size_and_sha256(&tmp_path)?;
+    if size != archive.size || sha256.to_string() != archive.sha256 {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(invalid_data(format!(
+            "{} does not match the manifest: size {size}, sha256 {sha256}, \
+             expected size {}, sha256 {}",
+            archive.file_name, archive.size, archive.sha256,
+        )));
+    }
+    fs::rename(&tmp_path, &path)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            snapshot_archive_info::{FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo},
+            snapshot_hash::SnapshotHash,
+            snapshot_utils::{
+                build_full_snapshot_archive_path, build_incremental_snapshot_archive_path,
+                ArchiveFormat,
+            },
+        },
+        tempfile::TempDir,
+    };
+
+    fn write_full_archive(dir: &Path, slot: Slot) -> FullSnapshotArchiveInfo {
+        let path = build_full_snapshot_archive_path(
+            dir,
+            slot,
+            &SnapshotHash(Hash::new_unique()),
+            ArchiveFormat::Tar,
+        );
+        fs::write(&path, format!("full {slot}")).unwrap();
+        FullSnapshotArchiveInfo::new_from_path(path).unwrap()
+    }
+
+    fn write_incremental_archive(
+        dir: &Path,
+        base_slot: Slot,
+        slot: Slot,
+    ) -> IncrementalSnapshotArchiveInfo {
+        let path = build_incremental_snapshot_archive_path(
+            dir,
+            base_slot,
+            slot,
+            &SnapshotHash(Hash::new_unique()),
+            ArchiveFormat::Tar,
+        );
+        fs::write(&path, format!("incremental {base_slot} {slot}")).unwrap();
+        IncrementalSnapshotArchiveInfo::new_from_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_publish_and_fetch_snapshot_archives() {
+        let archives_dir = TempDir::new().unwrap();
+        let store_dir = TempDir::new().unwrap();
+        let store = DirectorySnapshotArchiveStore::new(store_dir.path()).unwrap();
+        assert_eq!(
+            load_snapshot_archive_manifest(&store).unwrap(),
+            SnapshotArchiveManifest::default()
+        );
+
+        let full_100 = write_full_archive(archives_dir.path(), 100);
+        publish_snapshot_archive(&store, &full_100, SnapshotType::FullSnapshot, 2, 2).unwrap();
+        for slot in [110, 120, 130] {
+            let incremental = write_incremental_archive(archives_dir.path(), 100, slot);
+            publish_snapshot_archive(
+                &store,
+                &incremental,
+                SnapshotType::IncrementalSnapshot(100),
+                2,
+                2,
+            )
+            .unwrap();
+        }
+        let manifest = load_snapshot_archive_manifest(&store).unwrap();
+        assert_eq!(manifest.full.len(), 1);
+        assert_eq!(
+            manifest
+                .incremental
+                .iter()
+                .map(|archive| archive.slot)
+                .collect::<Vec<_>>(),
+            vec![120, 130]
+        );
+        // The manifest, the full snapshot archive and the retained incremental snapshot archives
+        assert_eq!(fs::read_dir(store_dir.path()).unwrap().count(), 4);
+
+        // A new full snapshot prunes the incremental snapshots of the previous one
+        let full_200 = write_full_archive(archives_dir.path(), 200);
+        publish_snapshot_archive(&store, &full_200, SnapshotType::FullSnapshot, 2, 2).unwrap();
+        let manifest = load_snapshot_archive_manifest(&store).unwrap();
+        assert_eq!(manifest.full.len(), 2);
+        assert!(manifest.incremental.is_empty());
+        assert_eq!(fs::read_dir(store_dir.path()).unwrap().count(), 3);
+
+        let fetch_dir = TempDir::new().unwrap();
+        let stored = manifest
+            .find(SnapshotType::FullSnapshot, 200, &full_200.hash().0)
+            .unwrap();
+        let path = fetch_snapshot_archive(&store, stored, fetch_dir.path()).unwrap();
+        assert_eq!(path.file_name(), full_200.path().file_name());
+        assert_eq!(fs::read(path).unwrap(), fs::read(full_200.path()).unwrap());
+        assert!(manifest
+            .find(SnapshotType::FullSnapshot, 200, &Hash::new_unique())
+            .is_none());
+
+        // Corrupted archives are rejected
+        fs::write(store_dir.path().join(&stored.file_name), "corrupted").unwrap();
+        let err = fetch_snapshot_archive(&store, stored, fetch_dir.path()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
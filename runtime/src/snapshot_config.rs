@@ -1,7 +1,10 @@
 use {
-    crate::snapshot_utils::{self, ArchiveFormat, SnapshotVersion},
+    crate::{
+        snapshot_archive_store::SnapshotArchiveStore,
+        snapshot_utils::{self, ArchiveFormat, SnapshotVersion},
+    },
     solana_sdk::clock::Slot,
-    std::path::PathBuf,
+    std::{path::PathBuf, sync::Arc},
 };
 
 /// Snapshot configuration and runtime information
@@ -43,6 +46,10 @@ pub struct SnapshotConfig {
 
     // Thread niceness adjustment for snapshot packager service
     pub packager_thread_niceness_adj: i8,
+
+    /// Store that snapshot archives are published to once created, and that bootstrap fetches
+    /// snapshot archives from
+    pub archive_store: Option<Arc<dyn SnapshotArchiveStore>>,
 }
 
 impl Default for SnapshotConfig {
@@ -64,6 +71,7 @@ impl Default for SnapshotConfig {
                 snapshot_utils::DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
             accounts_hash_debug_verify: false,
             packager_thread_niceness_adj: 0,
+            archive_store: None,
         }
     }
 }
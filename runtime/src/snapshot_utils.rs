@@ -65,8 +65,13 @@ use {
 };
 
 mod archive_format;
+mod chunked_archive;
+mod snapshot_accounts_diff;
+mod snapshot_archive_reader;
 mod snapshot_storage_rebuilder;
-pub use archive_format::*;
+pub use {
+    archive_format::*, chunked_archive::*, snapshot_accounts_diff::*, snapshot_archive_reader::*,
+};
 
 pub const SNAPSHOT_STATUS_CACHE_FILENAME: &str = "status_cache";
 pub const SNAPSHOT_ARCHIVE_DOWNLOAD_DIR: &str = "remote";
@@ -81,8 +86,8 @@ pub const BANK_SNAPSHOT_PRE_FILENAME_EXTENSION: &str = "pre";
 pub const MAX_BANK_SNAPSHOTS_TO_RETAIN: usize = 8; // Save some bank snapshots but not too many
 pub const DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 2;
 pub const DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN: usize = 4;
-pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4)$";
-pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|tar\.gz|tar\.lz4)$";
+pub const FULL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^snapshot-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|chunked\.tar\.zst|tar\.gz|tar\.lz4)$";
+pub const INCREMENTAL_SNAPSHOT_ARCHIVE_FILENAME_REGEX: &str = r"^incremental-snapshot-(?P<base>[[:digit:]]+)-(?P<slot>[[:digit:]]+)-(?P<hash>[[:alnum:]]+)\.(?P<ext>tar|tar\.bz2|tar\.zst|chunked\.tar\.zst|tar\.gz|tar\.lz4)$";
 
 #[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
@@ -247,6 +252,9 @@ pub enum SnapshotError {
 
     #[error("snapshot slot deltas are invalid: {0}")]
     VerifySlotDeltas(#[from] VerifySlotDeltasError),
+
+    #[error("invalid chunked snapshot archive {}: {1}", .0.display())]
+    InvalidChunkedArchive(PathBuf, String),
 }
 pub type Result<T> = std::result::Result<T, SnapshotError>;
 
@@ -449,6 +457,22 @@ pub fn archive_snapshot_package(
             storage.append_vec_id(),
         ));
 
+        if storage.accounts.is_cold() {
+            // Archives hold plain AppendVecs, so cold storage is decompressed into the staging directory
+            let mut output_file = fs::File::create(&output_path).map_err(|e| {
+                SnapshotError::IoWithSourceAndFile(
+                    e,
+                    "create cold storage contents",
+                    output_path.clone(),
+                )
+            })?;
+            storage
+                .accounts
+                .write_contents(&mut output_file)
+                .map_err(|e| SnapshotError::IoWithSource(e, "write cold storage contents"))?;
+            continue;
+        }
+
         // `storage_path` - The file path where the AppendVec itself is located
         // `output_path` - The file path where the AppendVec will be placed in the staging directory.
         let storage_path =
@@ -516,6 +540,17 @@ pub fn archive_snapshot_package(
                 let (_output, result) = encoder.finish();
                 result?
             }
+            ArchiveFormat::TarZstdChunked => {
+                let mut archive =
+                    ChunkedArchiveBuilder::new(archive_file, 0, DEFAULT_ARCHIVE_CHUNK_SIZE);
+                archive.append_path_with_name(&staging_dir.as_ref().join("version"), "version")?;
+                archive.append_dir_all("snapshots", &staging_dir.as_ref().join("snapshots"))?;
+                // Keep the version and bank fields in their own chunk, so that they can be read
+                // before any storage
+                archive.finish_chunk()?;
+                archive.append_dir_all("accounts", &staging_dir.as_ref().join("accounts"))?;
+                archive.finish()?;
+            }
             ArchiveFormat::Tar => {
                 do_archive_files(&mut archive_file)?;
             }
@@ -1271,11 +1306,11 @@ fn verify_bank_against_expected_slot_hash(
 }
 
 /// Spawns a thread for unpacking a snapshot
-fn spawn_unpack_snapshot_thread(
+fn spawn_unpack_snapshot_thread<R: Read + Send + 'static>(
     file_sender: Sender<PathBuf>,
     account_paths: Arc<Vec<PathBuf>>,
     ledger_dir: Arc<PathBuf>,
-    mut archive: Archive<SharedBufferReader>,
+    mut archive: Archive<R>,
     parallel_selector: Option<ParallelSelector>,
     thread_index: usize,
 ) -> JoinHandle<()> {
@@ -1302,9 +1337,31 @@ fn streaming_unarchive_snapshot(
     snapshot_archive_path: PathBuf,
     archive_format: ArchiveFormat,
     num_threads: usize,
-) -> Vec<JoinHandle<()>> {
+) -> Result<Vec<JoinHandle<()>>> {
     let account_paths = Arc::new(account_paths);
     let ledger_dir = Arc::new(ledger_dir);
+    if archive_format == ArchiveFormat::TarZstdChunked {
+        // Each thread decompresses its own chunks, so all entries are unpacked
+        let index = ChunkedArchiveIndex::read_from(&snapshot_archive_path)?;
+        return Ok(ChunkedArchiveReader::divide(
+            &snapshot_archive_path,
+            &index.chunks,
+            num_threads,
+        )
+        .into_iter()
+        .enumerate()
+        .map(|(thread_index, reader)| {
+            spawn_unpack_snapshot_thread(
+                file_sender.clone(),
+                account_paths.clone(),
+                ledger_dir.clone(),
+                Archive::new(reader),
+                None,
+                thread_index,
+            )
+        })
+        .collect());
+    }
     let shared_buffer = untar_snapshot_create_shared_buffer(&snapshot_archive_path, archive_format);
 
     // All shared buffer readers need to be created before the threads are spawned
@@ -1316,7 +1373,7 @@ fn streaming_unarchive_snapshot(
         })
         .collect();
 
-    archives
+    Ok(archives
         .into_iter()
         .enumerate()
         .map(|(thread_index, archive)| {
@@ -1334,7 +1391,7 @@ fn streaming_unarchive_snapshot(
                 thread_index,
             )
         })
-        .collect()
+        .collect())
 }
 
 /// Perform the common tasks when unarchiving a snapshot.  Handles creating the temporary
@@ -1367,7 +1424,7 @@ where
         snapshot_archive_path.as_ref().to_path_buf(),
         archive_format,
         parallel_divisions,
-    );
+    )?;
 
     let num_rebuilder_threads = num_cpus::get_physical()
         .saturating_sub(parallel_divisions)
@@ -1783,6 +1840,34 @@ fn unpack_snapshot_local(
     Ok(unpacked_append_vec_map)
 }
 
+/// Unpacks a chunked snapshot archive, with each of the `parallel_divisions` workers
+/// decompressing its own chunks
+fn unpack_chunked_snapshot_local(
+    snapshot_archive_path: &Path,
+    ledger_dir: &Path,
+    account_paths: &[PathBuf],
+    parallel_divisions: usize,
+) -> Result<UnpackedAppendVecMap> {
+    assert!(parallel_divisions > 0);
+    let index = ChunkedArchiveIndex::read_from(snapshot_archive_path)?;
+
+    let all_unpacked_append_vec_map =
+        ChunkedArchiveReader::divide(snapshot_archive_path, &index.chunks, parallel_divisions)
+            .into_par_iter()
+            .map(|reader| {
+                let mut archive = Archive::new(reader);
+                unpack_snapshot(&mut archive, ledger_dir, account_paths, None)
+            })
+            .collect::<Vec<_>>();
+
+    let mut unpacked_append_vec_map = UnpackedAppendVecMap::new();
+    for h in all_unpacked_append_vec_map {
+        unpacked_append_vec_map.extend(h?);
+    }
+
+    Ok(unpacked_append_vec_map)
+}
+
 fn untar_snapshot_create_shared_buffer(
     snapshot_tar: &Path,
     archive_format: ArchiveFormat,
@@ -1791,7 +1876,7 @@ fn untar_snapshot_create_shared_buffer(
     match archive_format {
         ArchiveFormat::TarBzip2 => SharedBuffer::new(BzDecoder::new(BufReader::new(open_file()))),
         ArchiveFormat::TarGzip => SharedBuffer::new(GzDecoder::new(BufReader::new(open_file()))),
-        ArchiveFormat::TarZstd => SharedBuffer::new(
+        ArchiveFormat::TarZstd | ArchiveFormat::TarZstdChunked => SharedBuffer::new(
             zstd::stream::read::Decoder::new(BufReader::new(open_file())).unwrap(),
         ),
         ArchiveFormat::TarLz4 => {
@@ -1808,6 +1893,14 @@ fn untar_snapshot_in<P: AsRef<Path>>(
     archive_format: ArchiveFormat,
     parallel_divisions: usize,
 ) -> Result<UnpackedAppendVecMap> {
+    if archive_format == ArchiveFormat::TarZstdChunked {
+        return unpack_chunked_snapshot_local(
+            snapshot_tar.as_ref(),
+            unpack_dir,
+            account_paths,
+            parallel_divisions,
+        );
+    }
     let shared_buffer = untar_snapshot_create_shared_buffer(snapshot_tar.as_ref(), archive_format);
     unpack_snapshot_local(shared_buffer, unpack_dir, account_paths, parallel_divisions)
 }
@@ -2609,6 +2702,18 @@ mod tests {
             .unwrap(),
             (45, SnapshotHash(Hash::default()), ArchiveFormat::TarLz4)
         );
+        assert_eq!(
+            parse_full_snapshot_archive_filename(&format!(
+                "snapshot-46-{}.chunked.tar.zst",
+                Hash::default()
+            ))
+            .unwrap(),
+            (
+                46,
+                SnapshotHash(Hash::default()),
+                ArchiveFormat::TarZstdChunked
+            )
+        );
 
         assert!(parse_full_snapshot_archive_filename("invalid").is_err());
         assert!(
@@ -3316,6 +3421,18 @@ mod tests {
     /// bank possible, so the contents of the snapshot archive will be quite minimal.
     #[test]
     fn test_roundtrip_bank_to_and_from_full_snapshot_simple() {
+        do_test_roundtrip_bank_to_and_from_full_snapshot_simple(ArchiveFormat::Tar);
+    }
+
+    /// Test roundtrip of bank to a chunked full snapshot, which is unpacked chunk by chunk
+    #[test]
+    fn test_roundtrip_bank_to_and_from_chunked_full_snapshot() {
+        do_test_roundtrip_bank_to_and_from_full_snapshot_simple(ArchiveFormat::TarZstdChunked);
+    }
+
+    fn do_test_roundtrip_bank_to_and_from_full_snapshot_simple(
+        snapshot_archive_format: ArchiveFormat,
+    ) {
         solana_logger::setup();
         let genesis_config = GenesisConfig::default();
         let original_bank = Bank::new_for_tests(&genesis_config);
@@ -3653,7 +3770,6 @@ mod tests {
         let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
         let full_snapshot_archives_dir = tempfile::TempDir::new().unwrap();
         let incremental_snapshot_archives_dir = tempfile::TempDir::new().unwrap();
-        let snapshot_archive_format = ArchiveFormat::Tar;
 
         let full_snapshot_slot = slot;
         bank_to_full_snapshot_archive(
@@ -3,12 +3,14 @@ use {
     strum::Display,
 };
 
-pub const SUPPORTED_ARCHIVE_COMPRESSION: &[&str] = &["bz2", "gzip", "zstd", "lz4", "tar", "none"];
+pub const SUPPORTED_ARCHIVE_COMPRESSION: &[&str] =
+    &["bz2", "gzip", "zstd", "zstd-chunked", "lz4", "tar", "none"];
 pub const DEFAULT_ARCHIVE_COMPRESSION: &str = "zstd";
 
 pub const TAR_BZIP2_EXTENSION: &str = "tar.bz2";
 pub const TAR_GZIP_EXTENSION: &str = "tar.gz";
 pub const TAR_ZSTD_EXTENSION: &str = "tar.zst";
+pub const TAR_ZSTD_CHUNKED_EXTENSION: &str = "chunked.tar.zst";
 pub const TAR_LZ4_EXTENSION: &str = "tar.lz4";
 pub const TAR_EXTENSION: &str = "tar";
 
@@ -18,6 +20,8 @@ pub enum ArchiveFormat {
     TarBzip2,
     TarGzip,
     TarZstd,
+    /// zstd compressed in independent chunks, with an index for parallel and random access
+    TarZstdChunked,
     TarLz4,
     Tar,
 }
@@ -29,6 +33,7 @@ impl ArchiveFormat {
             ArchiveFormat::TarBzip2 => TAR_BZIP2_EXTENSION,
             ArchiveFormat::TarGzip => TAR_GZIP_EXTENSION,
             ArchiveFormat::TarZstd => TAR_ZSTD_EXTENSION,
+            ArchiveFormat::TarZstdChunked => TAR_ZSTD_CHUNKED_EXTENSION,
             ArchiveFormat::TarLz4 => TAR_LZ4_EXTENSION,
             ArchiveFormat::Tar => TAR_EXTENSION,
         }
@@ -39,6 +44,7 @@ impl ArchiveFormat {
             "bz2" => Some(ArchiveFormat::TarBzip2),
             "gzip" => Some(ArchiveFormat::TarGzip),
             "zstd" => Some(ArchiveFormat::TarZstd),
+            "zstd-chunked" => Some(ArchiveFormat::TarZstdChunked),
             "lz4" => Some(ArchiveFormat::TarLz4),
             "tar" | "none" => Some(ArchiveFormat::Tar),
             _ => None,
@@ -56,6 +62,7 @@ impl TryFrom<&str> for ArchiveFormat {
             TAR_BZIP2_EXTENSION => Ok(ArchiveFormat::TarBzip2),
             TAR_GZIP_EXTENSION => Ok(ArchiveFormat::TarGzip),
             TAR_ZSTD_EXTENSION => Ok(ArchiveFormat::TarZstd),
+            TAR_ZSTD_CHUNKED_EXTENSION => Ok(ArchiveFormat::TarZstdChunked),
             TAR_LZ4_EXTENSION => Ok(ArchiveFormat::TarLz4),
             TAR_EXTENSION => Ok(ArchiveFormat::Tar),
             _ => Err(ParseError::InvalidExtension(extension.to_string())),
@@ -96,6 +103,10 @@ mod tests {
         assert_eq!(ArchiveFormat::TarBzip2.extension(), TAR_BZIP2_EXTENSION);
         assert_eq!(ArchiveFormat::TarGzip.extension(), TAR_GZIP_EXTENSION);
         assert_eq!(ArchiveFormat::TarZstd.extension(), TAR_ZSTD_EXTENSION);
+        assert_eq!(
+            ArchiveFormat::TarZstdChunked.extension(),
+            TAR_ZSTD_CHUNKED_EXTENSION
+        );
         assert_eq!(ArchiveFormat::TarLz4.extension(), TAR_LZ4_EXTENSION);
         assert_eq!(ArchiveFormat::Tar.extension(), TAR_EXTENSION);
     }
@@ -114,6 +125,10 @@ mod tests {
             ArchiveFormat::try_from(TAR_ZSTD_EXTENSION),
             Ok(ArchiveFormat::TarZstd)
         );
+        assert_eq!(
+            ArchiveFormat::try_from(TAR_ZSTD_CHUNKED_EXTENSION),
+            Ok(ArchiveFormat::TarZstdChunked)
+        );
         assert_eq!(
             ArchiveFormat::try_from(TAR_LZ4_EXTENSION),
             Ok(ArchiveFormat::TarLz4)
@@ -142,6 +157,10 @@ mod tests {
             ArchiveFormat::from_str(TAR_ZSTD_EXTENSION),
             Ok(ArchiveFormat::TarZstd)
         );
+        assert_eq!(
+            ArchiveFormat::from_str(TAR_ZSTD_CHUNKED_EXTENSION),
+            Ok(ArchiveFormat::TarZstdChunked)
+        );
         assert_eq!(
             ArchiveFormat::from_str(TAR_LZ4_EXTENSION),
             Ok(ArchiveFormat::TarLz4)
@@ -162,6 +181,7 @@ mod tests {
             Some(ArchiveFormat::TarBzip2),
             Some(ArchiveFormat::TarGzip),
             Some(ArchiveFormat::TarZstd),
+            Some(ArchiveFormat::TarZstdChunked),
             Some(ArchiveFormat::TarLz4),
             Some(ArchiveFormat::Tar),
             Some(ArchiveFormat::Tar),
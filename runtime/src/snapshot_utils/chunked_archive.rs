@@ -0,0 +1,482 @@
+//! Snapshot archives compressed in independently addressable chunks
+//!
+//! A chunked archive holds the same tar stream as the other archive formats, split at entry
+//! boundaries into chunks that are each compressed as an independent zstd frame. The archive ends
+//! with an index of the chunks and of the files they hold, stored in a zstd skippable frame.
+//!
+//! zstd decoders decode concatenated frames and ignore skippable ones, so a chunked archive is also
+//! a valid `.tar.zst` archive. The index allows its chunks to be decompressed in parallel, and a
+//! single file to be read without decompressing the rest of the archive.
+
+use {
+    super::{Result, SnapshotError},
+    bincode::Options,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::VecDeque,
+        fs::{self, File},
+        io::{self, BufReader, Read, Seek, SeekFrom, Take, Write},
+        mem,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Size of the uncompressed tar stream after which a chunk is closed. Files are never split
+/// between chunks, so a chunk holding a larger file is as large as that file.
+pub(crate) const DEFAULT_ARCHIVE_CHUNK_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Ends a chunked archive, after the length of its index
+const CHUNKED_ARCHIVE_MAGIC: &[u8; 8] = b"SOLCHNK1";
+/// Length of the index, followed by `CHUNKED_ARCHIVE_MAGIC`
+const CHUNKED_ARCHIVE_FOOTER_SIZE: u64 = 16;
+/// The first of the magic numbers of zstd skippable frames, 0x184D2A50 to 0x184D2A5F
+const ZSTD_SKIPPABLE_FRAME_MAGIC: u32 = 0x184D2A50;
+/// Skippable frame magic number and frame size
+const ZSTD_SKIPPABLE_FRAME_HEADER_SIZE: u64 = 8;
+
+/// The chunks of a chunked archive, in archive order
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkedArchiveIndex {
+    pub chunks: Vec<ArchiveChunk>,
+}
+
+/// A compressed chunk of a chunked archive
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveChunk {
+    /// Offset of the chunk in the archive file
+    pub offset: u64,
+    pub compressed_len: u64,
+    /// Length of the part of the tar stream held by the chunk
+    pub uncompressed_len: u64,
+    /// The files held by the chunk, in tar stream order
+    pub entries: Vec<ArchiveChunkEntry>,
+}
+
+/// A file held by a chunk of a chunked archive
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveChunkEntry {
+    /// Path of the file in the archive, e.g. `accounts/42.7`
+    pub path: String,
+    /// Offset of the contents of the file in the uncompressed chunk
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl ChunkedArchiveIndex {
+    /// Reads and checks the index at the end of a chunked archive
+    pub fn read_from(archive_path: &Path) -> Result<Self> {
+        let invalid = |reason: String| {
+            SnapshotError::InvalidChunkedArchive(archive_path.to_path_buf(), reason)
+        };
+        let mut file = File::open(archive_path)?;
+        let archive_len = file.metadata()?.len();
+        if archive_len < ZSTD_SKIPPABLE_FRAME_HEADER_SIZE + CHUNKED_ARCHIVE_FOOTER_SIZE {
+            return Err(invalid("too short".to_string()));
+        }
+
+        let mut footer = [0u8; CHUNKED_ARCHIVE_FOOTER_SIZE as usize];
+        file.seek(SeekFrom::End(-(CHUNKED_ARCHIVE_FOOTER_SIZE as i64)))?;
+        file.read_exact(&mut footer)?;
+        let (index_len, magic) = footer.split_at(8);
+        if magic != CHUNKED_ARCHIVE_MAGIC {
+            return Err(invalid("index not found".to_string()));
+        }
+        let index_len = u64::from_le_bytes(index_len.try_into().unwrap());
+        let index_offset = archive_len
+            .checked_sub(index_len + CHUNKED_ARCHIVE_FOOTER_SIZE)
+            .filter(|offset| *offset >= ZSTD_SKIPPABLE_FRAME_HEADER_SIZE)
+            .ok_or_else(|| invalid(format!("index length {index_len} is too large")))?;
+
+        file.seek(SeekFrom::Start(index_offset))?;
+        let index: Self = bincode::options()
+            .with_limit(index_len)
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .deserialize_from((&mut file).take(index_len))?;
+        index
+            .check(index_offset - ZSTD_SKIPPABLE_FRAME_HEADER_SIZE)
+            .map_err(invalid)?;
+        Ok(index)
+    }
+
+    /// Checks that the chunks are contiguous up to `chunks_end`, and hold their entries
+    fn check(&self, chunks_end: u64) -> std::result::Result<(), String> {
+        let mut offset = 0;
+        for (i, chunk) in self.chunks.iter().enumerate() {
+            if chunk.offset != offset {
+                return Err(format!("chunk {i} is at {}, not {offset}", chunk.offset));
+            }
+            offset = offset
+                .checked_add(chunk.compressed_len)
+                .ok_or_else(|| format!("chunk {i} is too large"))?;
+            let entries_in_chunk = chunk.entries.iter().all(|entry| {
+                entry
+                    .offset
+                    .checked_add(entry.size)
+                    .map(|end| end <= chunk.uncompressed_len)
+                    .unwrap_or(false)
+            });
+            if !entries_in_chunk {
+                return Err(format!("chunk {i} does not hold all its entries"));
+            }
+        }
+        if offset != chunks_end {
+            return Err(format!(
+                "chunks end at {offset}, not at the index {chunks_end}"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Finds the chunk holding the file at `path` in the archive
+    pub fn find_entry(&self, path: &str) -> Option<(&ArchiveChunk, &ArchiveChunkEntry)> {
+        self.chunks.iter().find_map(|chunk| {
+            chunk
+                .entries
+                .iter()
+                .find(|entry| entry.path == path)
+                .map(|entry| (chunk, entry))
+        })
+    }
+}
+
+/// Reads the contents of the file at `path` in a chunked archive, only decompressing the chunk
+/// that holds it
+pub fn read_chunked_archive_entry(
+    archive_path: &Path,
+    index: &ChunkedArchiveIndex,
+    path: &str,
+) -> Result<Vec<u8>> {
+    let (chunk, entry) = index.find_entry(path).ok_or_else(|| {
+        SnapshotError::InvalidChunkedArchive(
+            archive_path.to_path_buf(),
+            format!("{path} not found"),
+        )
+    })?;
+    let mut reader = ChunkedArchiveReader::new(archive_path, vec![chunk.clone()]);
+    io::copy(&mut (&mut reader).take(entry.offset), &mut io::sink())?;
+    let mut data = Vec::with_capacity(entry.size as usize);
+    reader.take(entry.size).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Reads the tar stream of some of the chunks of a chunked archive, in order
+///
+/// Chunks end at entry boundaries, so the chunks read do not need to be contiguous.
+pub(crate) struct ChunkedArchiveReader {
+    archive_path: PathBuf,
+    chunks: VecDeque<ArchiveChunk>,
+    current: Option<(
+        ArchiveChunk,
+        u64,
+        Take<zstd::Decoder<'static, BufReader<Take<File>>>>,
+    )>,
+}
+
+impl ChunkedArchiveReader {
+    pub(crate) fn new(archive_path: &Path, chunks: Vec<ArchiveChunk>) -> Self {
+        Self {
+            archive_path: archive_path.to_path_buf(),
+            chunks: chunks.into(),
+            current: None,
+        }
+    }
+
+    /// Divides `chunks` between `divisions` readers, chunk `i` going to reader `i % divisions`
+    pub(crate) fn divide(
+        archive_path: &Path,
+        chunks: &[ArchiveChunk],
+        divisions: usize,
+    ) -> Vec<Self> {
+        (0..divisions)
+            .map(|index| {
+                let chunks = chunks
+                    .iter()
+                    .skip(index)
+                    .step_by(divisions)
+                    .cloned()
+                    .collect();
+                Self::new(archive_path, chunks)
+            })
+            .collect()
+    }
+
+    fn open_chunk(
+        &self,
+        chunk: &ArchiveChunk,
+    ) -> io::Result<Take<zstd::Decoder<'static, BufReader<Take<File>>>>> {
+        let mut file = File::open(&self.archive_path)?;
+        file.seek(SeekFrom::Start(chunk.offset))?;
+        let decoder = zstd::Decoder::new(file.take(chunk.compressed_len))?.single_frame();
+        // Bound the decompressed size by the index, which is checked against unpacking limits
+        Ok(decoder.take(chunk.uncompressed_len))
+    }
+}
+
+impl Read for ChunkedArchiveReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.current.is_none() {
+                let Some(chunk) = self.chunks.pop_front() else {
+                    return Ok(0);
+                };
+                let decoder = self.open_chunk(&chunk)?;
+                self.current = Some((chunk, 0, decoder));
+            }
+            let (chunk, read_len, decoder) = self.current.as_mut().unwrap();
+            let n = decoder.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                *read_len += n as u64;
+                return Ok(n);
+            }
+            if *read_len != chunk.uncompressed_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "chunk at {} of {} holds {read_len} bytes, not {}",
+                        chunk.offset,
+                        self.archive_path.display(),
+                        chunk.uncompressed_len,
+                    ),
+                ));
+            }
+            self.current = None;
+        }
+    }
+}
+
+/// Writes a chunked archive. Files are appended like with a [`tar::Builder`], and a chunk is
+/// closed once it holds at least `chunk_size` bytes of the tar stream.
+pub(crate) struct ChunkedArchiveBuilder<W: Write + Seek> {
+    builder: tar::Builder<ChunkWriter<W>>,
+    chunk_size: u64,
+}
+
+impl<W: Write + Seek> ChunkedArchiveBuilder<W> {
+    pub(crate) fn new(writer: W, level: i32, chunk_size: u64) -> Self {
+        let builder = tar::Builder::new(ChunkWriter {
+            writer: Some(writer),
+            encoder: None,
+            level,
+            chunk: ArchiveChunk::default(),
+            index: ChunkedArchiveIndex::default(),
+        });
+        Self {
+            builder,
+            chunk_size,
+        }
+    }
+
+    /// Appends the file at `path` as `name`, following symlinks
+    pub(crate) fn append_path_with_name(&mut self, path: &Path, name: &str) -> io::Result<()> {
+        let size = fs::metadata(path)?.len();
+        self.builder.append_path_with_name(path, name)?;
+        // The contents of the file are followed by padding up to the next 512 bytes block
+        let padding = (512 - size % 512) % 512;
+        let chunk = &mut self.builder.get_mut().chunk;
+        chunk.entries.push(ArchiveChunkEntry {
+            path: name.to_string(),
+            offset: chunk.uncompressed_len - padding - size,
+            size,
+        });
+        if chunk.uncompressed_len >= self.chunk_size {
+            self.finish_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Appends the directory at `path` as `name`, and its contents in file name order
+    pub(crate) fn append_dir_all(&mut self, name: &str, path: &Path) -> io::Result<()> {
+        self.builder.append_dir(name, path)?;
+        let mut file_names = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<Vec<_>>>()?;
+        file_names.sort();
+        for file_name in file_names {
+            let path = path.join(&file_name);
+            let name = format!("{name}/{}", file_name.to_string_lossy());
+            if fs::metadata(&path)?.is_dir() {
+                self.append_dir_all(&name, &path)?;
+            } else {
+                self.append_path_with_name(&path, &name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes the current chunk, so that the next files are appended to a new one
+    pub(crate) fn finish_chunk(&mut self) -> io::Result<()> {
+        self.builder.get_mut().finish_chunk()
+    }
+
+    /// Terminates the tar stream, then writes the index
+    pub(crate) fn finish(self) -> io::Result<W> {
+        self.builder.into_inner()?.finish()
+    }
+}
+
+/// Compresses the tar stream written by a [`tar::Builder`] into chunks
+struct ChunkWriter<W: Write + Seek> {
+    /// The archive, while no chunk is being compressed
+    writer: Option<W>,
+    encoder: Option<zstd::Encoder<'static, W>>,
+    level: i32,
+    chunk: ArchiveChunk,
+    index: ChunkedArchiveIndex,
+}
+
+impl<W: Write + Seek> ChunkWriter<W> {
+    fn finish_chunk(&mut self) -> io::Result<()> {
+        let Some(encoder) = self.encoder.take() else {
+            return Ok(());
+        };
+        let mut writer = encoder.finish()?;
+        let offset = writer.stream_position()?;
+        self.chunk.compressed_len = offset - self.chunk.offset;
+        let chunk = mem::replace(
+            &mut self.chunk,
+            ArchiveChunk {
+                offset,
+                ..ArchiveChunk::default()
+            },
+        );
+        self.index.chunks.push(chunk);
+        self.writer = Some(writer);
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        self.finish_chunk()?;
+        let mut writer = self.writer.take().unwrap();
+        let index = bincode::serialize(&self.index)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        let frame_size = u32::try_from(index.len() as u64 + CHUNKED_ARCHIVE_FOOTER_SIZE)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "chunked archive index too large"))?;
+        writer.write_all(&ZSTD_SKIPPABLE_FRAME_MAGIC.to_le_bytes())?;
+        writer.write_all(&frame_size.to_le_bytes())?;
+        writer.write_all(&index)?;
+        writer.write_all(&(index.len() as u64).to_le_bytes())?;
+        writer.write_all(CHUNKED_ARCHIVE_MAGIC)?;
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write + Seek> Write for ChunkWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoder.is_none() {
+            let writer = self.writer.take().unwrap();
+            self.encoder = Some(zstd::Encoder::new(writer, self.level)?);
+        }
+        let n = self.encoder.as_mut().unwrap().write(buf)?;
+        self.chunk.uncompressed_len += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match (&mut self.encoder, &mut self.writer) {
+            (Some(encoder), _) => encoder.flush(),
+            (None, Some(writer)) => writer.flush(),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::io::Cursor, tar::Archive};
+
+    fn read_entries(reader: impl Read) -> Vec<(String, Vec<u8>)> {
+        let mut archive = Archive::new(reader);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                (path, data)
+            })
+            .filter(|(_path, data)| !data.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_chunked_archive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let files = [
+            ("version", vec![1u8; 5]),
+            ("accounts/1.1", vec![2u8; 700]),
+            ("accounts/2.2", vec![3u8; 1024]),
+            ("accounts/3.3", vec![4u8; 3]),
+        ];
+        fs::create_dir(dir.path().join("accounts")).unwrap();
+        for (name, data) in &files {
+            fs::write(dir.path().join(name), data).unwrap();
+        }
+
+        let archive_path = dir.path().join("archive");
+        let mut builder = ChunkedArchiveBuilder::new(File::create(&archive_path).unwrap(), 0, 1);
+        builder
+            .append_path_with_name(&dir.path().join("version"), "version")
+            .unwrap();
+        builder
+            .append_dir_all("accounts", &dir.path().join("accounts"))
+            .unwrap();
+        builder.finish().unwrap();
+        let expected: Vec<_> = files
+            .iter()
+            .map(|(name, data)| (name.to_string(), data.clone()))
+            .collect();
+
+        // The archive is a valid .tar.zst archive
+        let archive = fs::read(&archive_path).unwrap();
+        let decoder = zstd::Decoder::new(Cursor::new(archive)).unwrap();
+        assert_eq!(read_entries(decoder), expected);
+
+        // One chunk per file, and one for the end of the tar stream
+        let index = ChunkedArchiveIndex::read_from(&archive_path).unwrap();
+        assert_eq!(index.chunks.len(), 5);
+        for (name, data) in &files {
+            assert_eq!(
+                &read_chunked_archive_entry(&archive_path, &index, name).unwrap(),
+                data
+            );
+        }
+        assert!(read_chunked_archive_entry(&archive_path, &index, "accounts/4.4").is_err());
+
+        // Chunks can be read separately, in any grouping
+        let mut entries: Vec<_> = ChunkedArchiveReader::divide(&archive_path, &index.chunks, 3)
+            .into_iter()
+            .flat_map(read_entries)
+            .collect();
+        entries.sort();
+        assert_eq!(entries, expected);
+    }
+
+    #[test]
+    fn test_chunked_archive_invalid_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let accounts_dir = dir.path().join("accounts");
+        fs::create_dir(&accounts_dir).unwrap();
+        fs::write(accounts_dir.join("1.1"), [1u8; 10]).unwrap();
+        let archive_path = dir.path().join("archive");
+        let mut builder = ChunkedArchiveBuilder::new(
+            File::create(&archive_path).unwrap(),
+            0,
+            DEFAULT_ARCHIVE_CHUNK_SIZE,
+        );
+        builder.append_dir_all("accounts", &accounts_dir).unwrap();
+        builder.finish().unwrap();
+        assert!(ChunkedArchiveIndex::read_from(&archive_path).is_ok());
+
+        // Truncating the archive loses the index
+        let archive = fs::read(&archive_path).unwrap();
+        fs::write(&archive_path, &archive[..archive.len() - 1]).unwrap();
+        assert!(matches!(
+            ChunkedArchiveIndex::read_from(&archive_path),
+            Err(SnapshotError::InvalidChunkedArchive(..))
+        ));
+    }
+}
@@ -0,0 +1,239 @@
+//! Compares the accounts of two snapshots, by streaming them out of their archives
+
+use {
+    super::{scan_snapshot_archive_accounts, Result, SnapshotAccountsFilter},
+    crate::append_vec::StoredAccountMeta,
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        hash::{hashv, Hash},
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::HashMap,
+        path::{Path, PathBuf},
+    },
+};
+
+/// The state of an account in the base snapshot of a diff, without its data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotAccountSummary {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub executable: bool,
+    pub rent_epoch: Epoch,
+    pub data_len: u64,
+    pub data_hash: Hash,
+}
+
+impl From<&StoredAccountMeta<'_>> for SnapshotAccountSummary {
+    fn from(account: &StoredAccountMeta) -> Self {
+        Self {
+            lamports: account.account_meta.lamports,
+            owner: account.account_meta.owner,
+            executable: account.account_meta.executable,
+            rent_epoch: account.account_meta.rent_epoch,
+            data_len: account.meta.data_len,
+            data_hash: hashv(&[account.data]),
+        }
+    }
+}
+
+/// How an account differs between the base and the target snapshot of a diff
+#[derive(Debug)]
+pub enum SnapshotAccountDiff<'a, 'b> {
+    /// The account only exists in the target snapshot, where it was last written at `slot`
+    Added {
+        slot: Slot,
+        account: &'a StoredAccountMeta<'b>,
+    },
+    /// The account only exists in the base snapshot
+    Removed { base: SnapshotAccountSummary },
+    /// The account was written with different contents at `slot` of the target snapshot
+    Modified {
+        base: SnapshotAccountSummary,
+        slot: Slot,
+        account: &'a StoredAccountMeta<'b>,
+    },
+}
+
+impl SnapshotAccountDiff<'_, '_> {
+    /// Lamports of the account in the target snapshot, minus its lamports in the base snapshot
+    pub fn lamports_delta(&self) -> i128 {
+        match self {
+            Self::Added { account, .. } => account.account_meta.lamports as i128,
+            Self::Removed { base } => -(base.lamports as i128),
+            Self::Modified { base, account, .. } => {
+                account.account_meta.lamports as i128 - base.lamports as i128
+            }
+        }
+    }
+
+    /// Data length of the account in the target snapshot, minus its length in the base snapshot
+    pub fn data_len_delta(&self) -> i128 {
+        match self {
+            Self::Added { account, .. } => account.meta.data_len as i128,
+            Self::Removed { base } => -(base.data_len as i128),
+            Self::Modified { base, account, .. } => {
+                account.meta.data_len as i128 - base.data_len as i128
+            }
+        }
+    }
+}
+
+/// Calls `visitor` with every account matching `filter` that differs between the base and the
+/// target snapshots. Accounts that were modified and then restored to their base state are not
+/// visited.
+///
+/// Each snapshot is given by the paths of its full snapshot archive, optionally followed by an
+/// incremental snapshot archive, as for [`scan_snapshot_archive_accounts()`]. The accounts of the
+/// base snapshot are held in memory as [`SnapshotAccountSummary`]s while the target snapshot is
+/// scanned.
+pub fn diff_snapshot_archives(
+    base_snapshot_archive_paths: &[PathBuf],
+    target_snapshot_archive_paths: &[PathBuf],
+    unpack_dir: &Path,
+    filter: &SnapshotAccountsFilter,
+    mut visitor: impl FnMut(&Pubkey, SnapshotAccountDiff),
+) -> Result<()> {
+    let mut base_accounts = HashMap::new();
+    scan_snapshot_archive_accounts(
+        base_snapshot_archive_paths,
+        unpack_dir,
+        filter,
+        |pubkey, _slot, account| {
+            base_accounts.insert(*pubkey, SnapshotAccountSummary::from(account));
+        },
+    )?;
+
+    scan_snapshot_archive_accounts(
+        target_snapshot_archive_paths,
+        unpack_dir,
+        filter,
+        |pubkey, slot, account| match base_accounts.remove(pubkey) {
+            None => visitor(pubkey, SnapshotAccountDiff::Added { slot, account }),
+            Some(base) if base != SnapshotAccountSummary::from(account) => visitor(
+                pubkey,
+                SnapshotAccountDiff::Modified {
+                    base,
+                    slot,
+                    account,
+                },
+            ),
+            Some(_) => (),
+        },
+    )?;
+
+    for (pubkey, base) in base_accounts {
+        visitor(&pubkey, SnapshotAccountDiff::Removed { base });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            bank::Bank,
+            genesis_utils::create_genesis_config,
+            snapshot_utils::{
+                bank_to_full_snapshot_archive, ArchiveFormat,
+                DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            },
+        },
+        solana_sdk::account::AccountSharedData,
+        std::sync::Arc,
+    };
+
+    fn archive_bank(
+        bank: &Bank,
+        bank_snapshots_dir: &Path,
+        snapshot_archives_dir: &Path,
+    ) -> PathBuf {
+        bank_to_full_snapshot_archive(
+            bank_snapshots_dir,
+            bank,
+            None,
+            snapshot_archives_dir,
+            snapshot_archives_dir,
+            ArchiveFormat::Tar,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap()
+        .path()
+        .clone()
+    }
+
+    #[test]
+    fn test_diff_snapshot_archives() {
+        let (genesis_config, _mint_keypair) = create_genesis_config(1_000_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let owner = Pubkey::new_unique();
+        let (modified, removed, added, unchanged) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        for pubkey in [modified, removed, unchanged] {
+            bank0.store_account(&pubkey, &AccountSharedData::new(10, 8, &owner));
+        }
+        bank0.fill_bank_with_ticks_for_tests();
+
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+        let base_archive = archive_bank(
+            &bank0,
+            bank_snapshots_dir.path(),
+            snapshot_archives_dir.path(),
+        );
+
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::new_unique(), 1));
+        bank1.store_account(&modified, &AccountSharedData::new(15, 4, &owner));
+        bank1.store_account(&removed, &AccountSharedData::default());
+        bank1.store_account(&added, &AccountSharedData::new(7, 3, &owner));
+        // Stored again, but with the same contents
+        bank1.store_account(&unchanged, &AccountSharedData::new(10, 8, &owner));
+        bank1.fill_bank_with_ticks_for_tests();
+        let target_archive = archive_bank(
+            &bank1,
+            bank_snapshots_dir.path(),
+            snapshot_archives_dir.path(),
+        );
+
+        let unpack_dir = tempfile::TempDir::new().unwrap();
+        let mut diffs = HashMap::new();
+        diff_snapshot_archives(
+            &[base_archive],
+            &[target_archive],
+            unpack_dir.path(),
+            &SnapshotAccountsFilter {
+                pubkeys: None,
+                owners: Some([owner].into()),
+            },
+            |pubkey, diff| {
+                let kind = match diff {
+                    SnapshotAccountDiff::Added { slot, .. } => format!("added at {slot}"),
+                    SnapshotAccountDiff::Removed { .. } => "removed".to_string(),
+                    SnapshotAccountDiff::Modified { slot, .. } => format!("modified at {slot}"),
+                };
+                diffs.insert(
+                    *pubkey,
+                    (kind, diff.lamports_delta(), diff.data_len_delta()),
+                );
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            diffs,
+            HashMap::from([
+                (modified, ("modified at 1".to_string(), 5, -4)),
+                (removed, ("removed".to_string(), -10, -8)),
+                (added, ("added at 1".to_string(), 7, 3)),
+            ])
+        );
+    }
+}
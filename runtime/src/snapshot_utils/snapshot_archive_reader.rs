@@ -0,0 +1,440 @@
+//! Streams the accounts stored in snapshot archives, without rebuilding an `AccountsDb`
+//!
+//! Archives are unpacked one file at a time with [`streaming_unpack_snapshot()`], and every
+//! storage is scanned as an [`AppendVec`] as soon as it is unpacked, then removed. Neither the
+//! unpacked archive nor an accounts index are ever held in full. The chunks of chunked archives
+//! are decompressed in parallel, once the chunk holding the bank fields has been unpacked.
+
+use {
+    super::{
+        chunked_archive::{ChunkedArchiveIndex, ChunkedArchiveReader},
+        get_io_error,
+        snapshot_storage_rebuilder::{
+            get_slot_and_append_vec_id, get_snapshot_file_kind, SnapshotFileKind,
+        },
+        snapshot_version_from_file, untar_snapshot_create_shared_buffer, ArchiveFormat, Result,
+        SnapshotError, SnapshotVersion,
+    },
+    crate::{
+        append_vec::{AppendVec, StoredAccountMeta},
+        hardened_unpack::streaming_unpack_snapshot,
+        serde_snapshot::{self, snapshot_storage_lengths_from_fields, SerdeStyle},
+        shared_buffer_reader::SharedBufferReader,
+        snapshot_archive_info::{
+            FullSnapshotArchiveInfo, IncrementalSnapshotArchiveInfo, SnapshotArchiveInfoGetter,
+        },
+    },
+    crossbeam_channel::{bounded, Sender},
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{HashMap, HashSet},
+        fs::{self, File},
+        io::BufReader,
+        path::{Path, PathBuf},
+        thread::{self, Builder},
+    },
+    tar::Archive,
+};
+
+/// Number of unpacked files waiting to be scanned, which bounds the disk space used by unpacking
+const MAX_UNPACKED_FILES_IN_FLIGHT: usize = 16;
+
+/// Selects the accounts visited by [`scan_snapshot_archive_accounts()`]
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotAccountsFilter {
+    /// Only visit these accounts, if set
+    pub pubkeys: Option<HashSet<Pubkey>>,
+    /// Only visit the accounts owned by these programs, if set
+    pub owners: Option<HashSet<Pubkey>>,
+}
+
+impl SnapshotAccountsFilter {
+    fn matches_pubkey(&self, pubkey: &Pubkey) -> bool {
+        self.pubkeys
+            .as_ref()
+            .map(|pubkeys| pubkeys.contains(pubkey))
+            .unwrap_or(true)
+    }
+
+    fn matches_owner(&self, owner: &Pubkey) -> bool {
+        self.owners
+            .as_ref()
+            .map(|owners| owners.contains(owner))
+            .unwrap_or(true)
+    }
+}
+
+/// Where a version of an account is stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct StoredVersion {
+    slot: Slot,
+    write_version: u64,
+    append_vec_id: usize,
+    offset: usize,
+}
+
+impl StoredVersion {
+    fn is_newer_than(&self, other: &Self) -> bool {
+        (self.slot, self.write_version, self.offset)
+            > (other.slot, other.write_version, other.offset)
+    }
+}
+
+fn archive_format_from_path(snapshot_archive_path: &Path) -> Result<ArchiveFormat> {
+    let path = snapshot_archive_path.to_path_buf();
+    FullSnapshotArchiveInfo::new_from_path(path.clone())
+        .map(|info| info.archive_format())
+        .or_else(|_| {
+            IncrementalSnapshotArchiveInfo::new_from_path(path).map(|info| info.archive_format())
+        })
+}
+
+/// Calls `visitor` with the slot, id and contents of every storage of a snapshot archive, in
+/// archive order. Storages hold all the versions of the accounts written in their slot, see
+/// [`scan_snapshot_archive_accounts()`] to only visit the latest version of each account.
+///
+/// Files are unpacked into `unpack_dir`, and removed once scanned.
+pub fn scan_snapshot_archive_storages(
+    snapshot_archive_path: &Path,
+    unpack_dir: &Path,
+    mut visitor: impl FnMut(Slot, usize, &AppendVec),
+) -> Result<()> {
+    let archive_format = archive_format_from_path(snapshot_archive_path)?;
+    let (file_sender, file_receiver) = bounded(MAX_UNPACKED_FILES_IN_FLIGHT);
+    let snapshot_archive_path = snapshot_archive_path.to_path_buf();
+    let unpack_dir = unpack_dir.to_path_buf();
+    let unpack_thread = Builder::new()
+        .name("solSnapshotRdr".to_string())
+        .spawn(move || {
+            if archive_format == ArchiveFormat::TarZstdChunked {
+                return streaming_unpack_chunked_snapshot(
+                    &snapshot_archive_path,
+                    &unpack_dir,
+                    &file_sender,
+                );
+            }
+            let shared_buffer =
+                untar_snapshot_create_shared_buffer(&snapshot_archive_path, archive_format);
+            let mut archive = Archive::new(SharedBufferReader::new(&shared_buffer));
+            streaming_unpack_snapshot(
+                &mut archive,
+                &unpack_dir,
+                &[unpack_dir.clone()],
+                None,
+                &file_sender,
+            )
+            .map_err(SnapshotError::from)
+        })
+        .unwrap();
+
+    let mut snapshot_version = None;
+    let mut storage_lengths = None;
+    let mut result = Ok(());
+    // Keep receiving after an error, so that the unpacking thread runs to completion
+    for path in file_receiver.iter() {
+        if result.is_ok() {
+            result = scan_unpacked_file(
+                &path,
+                &mut snapshot_version,
+                &mut storage_lengths,
+                &mut visitor,
+            );
+        }
+        let _ = fs::remove_file(&path);
+    }
+    unpack_thread.join().unwrap()?;
+    result
+}
+
+/// Unpacks the first chunk of a chunked archive, which holds the version and bank fields, then
+/// the other chunks in parallel
+fn streaming_unpack_chunked_snapshot(
+    snapshot_archive_path: &Path,
+    unpack_dir: &Path,
+    file_sender: &Sender<PathBuf>,
+) -> Result<()> {
+    let index = ChunkedArchiveIndex::read_from(snapshot_archive_path)?;
+    let Some((first_chunk, chunks)) = index.chunks.split_first() else {
+        return Ok(());
+    };
+    let unpack = |reader: ChunkedArchiveReader| {
+        streaming_unpack_snapshot(
+            &mut Archive::new(reader),
+            unpack_dir,
+            &[unpack_dir.to_path_buf()],
+            None,
+            file_sender,
+        )
+    };
+    unpack(ChunkedArchiveReader::new(
+        snapshot_archive_path,
+        vec![first_chunk.clone()],
+    ))?;
+
+    let unpack = &unpack;
+    let divisions = num_cpus::get_physical().min(MAX_UNPACKED_FILES_IN_FLIGHT);
+    thread::scope(|scope| {
+        let unpack_threads: Vec<_> =
+            ChunkedArchiveReader::divide(snapshot_archive_path, chunks, divisions)
+                .into_iter()
+                .enumerate()
+                .map(|(thread_index, reader)| {
+                    Builder::new()
+                        .name(format!("solSnapshotRdr{thread_index:02}"))
+                        .spawn_scoped(scope, move || unpack(reader))
+                        .unwrap()
+                })
+                .collect();
+        unpack_threads
+            .into_iter()
+            .try_for_each(|unpack_thread| unpack_thread.join().unwrap())
+    })?;
+    Ok(())
+}
+
+fn scan_unpacked_file(
+    path: &Path,
+    snapshot_version: &mut Option<SnapshotVersion>,
+    storage_lengths: &mut Option<HashMap<Slot, HashMap<usize, usize>>>,
+    visitor: &mut impl FnMut(Slot, usize, &AppendVec),
+) -> Result<()> {
+    let filename = path
+        .file_name()
+        .and_then(|filename| filename.to_str())
+        .ok_or_else(|| SnapshotError::FileNameToStrError(path.to_path_buf()))?;
+    match get_snapshot_file_kind(filename) {
+        Some(SnapshotFileKind::Version) => {
+            let version = snapshot_version_from_file(path)?;
+            *snapshot_version =
+                Some(version.parse().map_err(|_| {
+                    get_io_error(&format!("unsupported snapshot version: {version}"))
+                })?);
+        }
+        Some(SnapshotFileKind::BankFields) => {
+            let version = snapshot_version
+                .ok_or_else(|| get_io_error("snapshot version not found before bank fields"))?;
+            let mut snapshot_stream = BufReader::new(File::open(path)?);
+            *storage_lengths = Some(match version {
+                SnapshotVersion::V1_2_0 => {
+                    let (_bank_fields, accounts_fields) = serde_snapshot::fields_from_stream(
+                        SerdeStyle::Newer,
+                        &mut snapshot_stream,
+                    )?;
+                    snapshot_storage_lengths_from_fields(&accounts_fields)
+                }
+            });
+        }
+        Some(SnapshotFileKind::Storage) => {
+            let storage_lengths = storage_lengths
+                .as_ref()
+                .ok_or_else(|| get_io_error("bank fields not found before storages"))?;
+            let (slot, append_vec_id) = get_slot_and_append_vec_id(filename);
+            let current_len = storage_lengths
+                .get(&slot)
+                .and_then(|lengths| lengths.get(&append_vec_id))
+                .ok_or_else(|| get_io_error(&format!("storage {filename} not in bank fields")))?;
+            let (append_vec, _num_accounts) = AppendVec::new_from_file(path, *current_len)?;
+            visitor(slot, append_vec_id, &append_vec);
+        }
+        None => (),
+    }
+    Ok(())
+}
+
+/// Calls `visitor` with the latest version of every account of the snapshot archives that
+/// matches `filter`. Accounts whose latest version has zero lamports were deleted, and are not
+/// visited.
+///
+/// `snapshot_archive_paths` holds a full snapshot archive, optionally followed by an incremental
+/// snapshot archive based on it. The archives are scanned twice: once to find where the latest
+/// version of each account is stored, then to visit it. Only those locations are held in memory.
+pub fn scan_snapshot_archive_accounts(
+    snapshot_archive_paths: &[PathBuf],
+    unpack_dir: &Path,
+    filter: &SnapshotAccountsFilter,
+    mut visitor: impl FnMut(&Pubkey, Slot, &StoredAccountMeta),
+) -> Result<()> {
+    let mut latest_versions = HashMap::<Pubkey, StoredVersion>::new();
+    for snapshot_archive_path in snapshot_archive_paths {
+        scan_snapshot_archive_storages(
+            snapshot_archive_path,
+            unpack_dir,
+            |slot, append_vec_id, append_vec| {
+                for account in append_vec.account_iter() {
+                    if !filter.matches_pubkey(account.pubkey()) {
+                        continue;
+                    }
+                    let version = StoredVersion {
+                        slot,
+                        write_version: account.meta.write_version_obsolete,
+                        append_vec_id,
+                        offset: account.offset,
+                    };
+                    latest_versions
+                        .entry(*account.pubkey())
+                        .and_modify(|latest| {
+                            if version.is_newer_than(latest) {
+                                *latest = version;
+                            }
+                        })
+                        .or_insert(version);
+                }
+            },
+        )?;
+    }
+
+    for snapshot_archive_path in snapshot_archive_paths {
+        scan_snapshot_archive_storages(
+            snapshot_archive_path,
+            unpack_dir,
+            |slot, append_vec_id, append_vec| {
+                for account in append_vec.account_iter() {
+                    let is_latest = latest_versions
+                        .get(account.pubkey())
+                        .map(|latest| {
+                            latest.slot == slot
+                                && latest.append_vec_id == append_vec_id
+                                && latest.offset == account.offset
+                        })
+                        .unwrap_or(false);
+                    if is_latest
+                        && account.account_meta.lamports != 0
+                        && filter.matches_owner(&account.account_meta.owner)
+                    {
+                        visitor(account.pubkey(), slot, &account);
+                    }
+                }
+            },
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            bank::Bank,
+            genesis_utils::create_genesis_config,
+            snapshot_utils::{
+                bank_to_full_snapshot_archive, DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+                DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            },
+        },
+        solana_sdk::{
+            account::{AccountSharedData, ReadableAccount},
+            hash::Hash,
+        },
+        std::sync::Arc,
+    };
+
+    #[test]
+    fn test_scan_snapshot_archive_accounts() {
+        do_test_scan_snapshot_archive_accounts(ArchiveFormat::TarZstd);
+    }
+
+    #[test]
+    fn test_scan_chunked_snapshot_archive_accounts() {
+        do_test_scan_snapshot_archive_accounts(ArchiveFormat::TarZstdChunked);
+    }
+
+    fn do_test_scan_snapshot_archive_accounts(archive_format: ArchiveFormat) {
+        let (genesis_config, _mint_keypair) = create_genesis_config(1_000_000);
+        let bank0 = Arc::new(Bank::new_for_tests(&genesis_config));
+        let owner = Pubkey::new_unique();
+        let other_owner = Pubkey::new_unique();
+        let (updated, deleted, reassigned, untouched) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        for pubkey in [updated, deleted, reassigned, untouched] {
+            bank0.store_account(&pubkey, &AccountSharedData::new(1, 8, &owner));
+        }
+        while !bank0.is_complete() {
+            bank0.register_tick(&Hash::new_unique());
+        }
+
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::new_unique(), 1));
+        bank1.store_account(&updated, &AccountSharedData::new(2, 16, &owner));
+        bank1.store_account(&deleted, &AccountSharedData::default());
+        bank1.store_account(&reassigned, &AccountSharedData::new(3, 8, &other_owner));
+        while !bank1.is_complete() {
+            bank1.register_tick(&Hash::new_unique());
+        }
+
+        let bank_snapshots_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archives_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_archive_info = bank_to_full_snapshot_archive(
+            &bank_snapshots_dir,
+            &bank1,
+            None,
+            snapshot_archives_dir.path(),
+            snapshot_archives_dir.path(),
+            archive_format,
+            DEFAULT_MAX_FULL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+            DEFAULT_MAX_INCREMENTAL_SNAPSHOT_ARCHIVES_TO_RETAIN,
+        )
+        .unwrap();
+
+        let unpack_dir = tempfile::TempDir::new().unwrap();
+        let scan = |filter: &SnapshotAccountsFilter| {
+            let mut accounts = HashMap::new();
+            scan_snapshot_archive_accounts(
+                &[snapshot_archive_info.path().clone()],
+                unpack_dir.path(),
+                filter,
+                |pubkey, slot, account| {
+                    assert!(accounts
+                        .insert(*pubkey, (slot, account.clone_account()))
+                        .is_none());
+                },
+            )
+            .unwrap();
+            accounts
+        };
+
+        // Without a filter, every account of the bank is visited once, at its latest version
+        let accounts = scan(&SnapshotAccountsFilter::default());
+        assert!(accounts.len() > 3);
+        for (pubkey, (_slot, account)) in &accounts {
+            assert_eq!(bank1.get_account(pubkey).as_ref(), Some(account));
+        }
+        assert!(!accounts.contains_key(&deleted));
+
+        let accounts = scan(&SnapshotAccountsFilter {
+            pubkeys: None,
+            owners: Some(HashSet::from([owner])),
+        });
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[&updated].0, 1);
+        assert_eq!(accounts[&updated].1.lamports(), 2);
+        assert_eq!(accounts[&untouched].0, 0);
+
+        let accounts = scan(&SnapshotAccountsFilter {
+            pubkeys: Some(HashSet::from([reassigned, deleted])),
+            owners: None,
+        });
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[&reassigned].1.owner(), &other_owner);
+
+        // Unpacked files are removed once scanned
+        assert!(walk_files(unpack_dir.path()).is_empty());
+    }
+
+    fn walk_files(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .flat_map(|entry| {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    walk_files(&path)
+                } else {
+                    vec![path]
+                }
+            })
+            .collect()
+    }
+}
@@ -368,14 +368,14 @@ impl SnapshotStorageRebuilder {
 
 /// Used to determine if a filename is structured like a version file, bank file, or storage file
 #[derive(PartialEq, Debug)]
-enum SnapshotFileKind {
+pub(super) enum SnapshotFileKind {
     Version,
     BankFields,
     Storage,
 }
 
 /// Determines `SnapshotFileKind` for `filename` if any
-fn get_snapshot_file_kind(filename: &str) -> Option<SnapshotFileKind> {
+pub(super) fn get_snapshot_file_kind(filename: &str) -> Option<SnapshotFileKind> {
     if VERSION_FILE_REGEX.is_match(filename) {
         Some(SnapshotFileKind::Version)
     } else if BANK_FIELDS_FILE_REGEX.is_match(filename) {
@@ -0,0 +1,371 @@
+//! Tiered storage for cold accounts
+//!
+//! Ancient append vecs hold accounts that are rarely written and seldom read, yet an append vec
+//! keeps its whole contents mmapped. Once an ancient append vec no longer receives accounts, it can
+//! be rewritten as a cold storage file: the append vec contents are split into blocks of whole
+//! accounts, each compressed as an independent zstd frame, and followed by a per-file index.
+//!
+//! A cold storage keeps the layout of the append vec it was written from, so the offsets held by
+//! the accounts index stay valid. A block is decompressed the first time an account in it is read,
+//! into an unlinked scratch file mapped in place of the append vec file, so that the kernel can
+//! evict decompressed pages as it does those of append vecs.
+//!
+//! File layout:
+//! `[block 0] .. [block n] [bincode ColdStorageIndex] [index length: u64 LE] [COLD_STORAGE_MAGIC]`
+
+use {
+    crate::append_vec::AppendVec,
+    bincode::Options,
+    serde::{Deserialize, Serialize},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        fs::File,
+        io::{self, BufWriter, Read, Seek, SeekFrom, Write},
+        ops::Range,
+        path::Path,
+        sync::{Mutex, Once},
+    },
+};
+
+/// Extension appended to the append vec file name of a cold storage file, e.g. `42.7.cold`
+pub const COLD_STORAGE_FILE_EXTENSION: &str = "cold";
+
+pub const DEFAULT_TIERED_STORAGE_COMPRESSION_LEVEL: i32 = 3;
+/// Size of the append vec contents after which a block is closed. Accounts are never split
+/// between blocks, so a block holding a larger account is as large as that account.
+pub const DEFAULT_TIERED_STORAGE_BLOCK_SIZE: usize = 256 * 1024;
+
+/// Ends a cold storage file, after the length of its index
+const COLD_STORAGE_MAGIC: &[u8; 8] = b"SOLCOLD1";
+/// Length of the index, followed by `COLD_STORAGE_MAGIC`
+const COLD_STORAGE_FOOTER_SIZE: u64 = 16;
+
+/// How finished ancient append vecs are moved to cold storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TieredStorageConfig {
+    /// zstd compression level of the blocks
+    pub compression_level: i32,
+    pub block_size: usize,
+}
+
+impl Default for TieredStorageConfig {
+    fn default() -> Self {
+        Self {
+            compression_level: DEFAULT_TIERED_STORAGE_COMPRESSION_LEVEL,
+            block_size: DEFAULT_TIERED_STORAGE_BLOCK_SIZE,
+        }
+    }
+}
+
+/// The blocks and accounts of a cold storage file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColdStorageIndex {
+    /// Length of the append vec contents held by the file
+    pub len: u64,
+    /// The blocks, in append vec order
+    pub blocks: Vec<ColdStorageBlock>,
+    /// (pubkey, offset) of every account in the file, sorted
+    pub accounts: Vec<(Pubkey, u64)>,
+}
+
+/// A compressed block of a cold storage file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColdStorageBlock {
+    /// Offset of the first account of the block in the append vec contents
+    pub offset: u64,
+    pub len: u64,
+    /// Offset of the block in the file
+    pub file_offset: u64,
+    pub compressed_len: u64,
+}
+
+impl ColdStorageIndex {
+    /// Offsets of the accounts stored for `pubkey`
+    pub fn account_offsets<'a>(&'a self, pubkey: &'a Pubkey) -> impl Iterator<Item = usize> + 'a {
+        let start = self.accounts.partition_point(|(key, _)| key < pubkey);
+        self.accounts[start..]
+            .iter()
+            .take_while(move |(key, _)| key == pubkey)
+            .map(|(_, offset)| *offset as usize)
+    }
+
+    /// Indexes of the blocks holding any of the append vec contents in `range`
+    fn blocks_in_range(&self, range: Range<usize>) -> Range<usize> {
+        let (start, end) = (range.start as u64, range.end as u64);
+        let first = self
+            .blocks
+            .partition_point(|block| block.offset + block.len <= start);
+        let last = self.blocks.partition_point(|block| block.offset < end);
+        first..last.max(first)
+    }
+
+    /// Checks that the blocks are contiguous, both in the file up to `blocks_end` and in the
+    /// append vec contents up to `len`
+    fn check(&self, blocks_end: u64) -> Result<(), String> {
+        let (mut offset, mut file_offset) = (0, 0);
+        for (i, block) in self.blocks.iter().enumerate() {
+            if block.offset != offset || block.file_offset != file_offset {
+                return Err(format!("block {i} is not contiguous"));
+            }
+            offset = offset
+                .checked_add(block.len)
+                .ok_or_else(|| format!("block {i} is too large"))?;
+            file_offset = file_offset
+                .checked_add(block.compressed_len)
+                .ok_or_else(|| format!("block {i} is too large"))?;
+        }
+        if offset != self.len || file_offset != blocks_end {
+            return Err(format!(
+                "blocks end at {offset} in {file_offset} bytes, not at {} in {blocks_end} bytes",
+                self.len
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Writes the contents of `append_vec` to a cold storage file at `path`
+pub fn write_cold_storage_file(
+    append_vec: &AppendVec,
+    path: &Path,
+    config: &TieredStorageConfig,
+) -> io::Result<ColdStorageIndex> {
+    let len = append_vec.len();
+    let mut accounts = vec![];
+    let mut block_starts = vec![];
+    for account in append_vec.account_iter() {
+        let starts_block = block_starts
+            .last()
+            .map(|block_start| account.offset - block_start >= config.block_size)
+            .unwrap_or(true);
+        if starts_block {
+            block_starts.push(account.offset);
+        }
+        accounts.push((*account.pubkey(), account.offset as u64));
+    }
+    accounts.sort_unstable();
+
+    let mut file = BufWriter::new(File::create(path)?);
+    let mut index = ColdStorageIndex {
+        len: len as u64,
+        blocks: Vec::with_capacity(block_starts.len()),
+        accounts,
+    };
+    let mut file_offset = 0;
+    for (i, start) in block_starts.iter().enumerate() {
+        let end = block_starts.get(i + 1).copied().unwrap_or(len);
+        let (contents, _) = append_vec
+            .get_slice(*start, end - start)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "account out of bounds"))?;
+        let compressed = zstd::bulk::compress(contents, config.compression_level)?;
+        file.write_all(&compressed)?;
+        index.blocks.push(ColdStorageBlock {
+            offset: *start as u64,
+            len: (end - start) as u64,
+            file_offset,
+            compressed_len: compressed.len() as u64,
+        });
+        file_offset += compressed.len() as u64;
+    }
+
+    let encoded_index = bincode::options()
+        .with_fixint_encoding()
+        .serialize(&index)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    file.write_all(&encoded_index)?;
+    file.write_all(&(encoded_index.len() as u64).to_le_bytes())?;
+    file.write_all(COLD_STORAGE_MAGIC)?;
+    file.flush()?;
+    file.get_ref().sync_all()?;
+    Ok(index)
+}
+
+/// The backing of an append vec whose contents live in a cold storage file
+#[derive(Debug)]
+pub(crate) struct ColdStorage {
+    file: Mutex<File>,
+    index: ColdStorageIndex,
+    /// One per block, completed once the block is decompressed
+    loaded: Vec<Once>,
+}
+
+impl ColdStorage {
+    /// Opens the cold storage file at `path` and reads its index
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let invalid = |reason: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid cold storage file {}: {reason}", path.display()),
+            )
+        };
+        let mut file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        if file_len < COLD_STORAGE_FOOTER_SIZE {
+            return Err(invalid("too short".to_string()));
+        }
+
+        let mut footer = [0u8; COLD_STORAGE_FOOTER_SIZE as usize];
+        file.seek(SeekFrom::End(-(COLD_STORAGE_FOOTER_SIZE as i64)))?;
+        file.read_exact(&mut footer)?;
+        let (index_len, magic) = footer.split_at(8);
+        if magic != COLD_STORAGE_MAGIC {
+            return Err(invalid("index not found".to_string()));
+        }
+        let index_len = u64::from_le_bytes(index_len.try_into().unwrap());
+        let index_offset = file_len
+            .checked_sub(index_len)
+            .and_then(|offset| offset.checked_sub(COLD_STORAGE_FOOTER_SIZE))
+            .ok_or_else(|| invalid(format!("index length {index_len} is too large")))?;
+
+        file.seek(SeekFrom::Start(index_offset))?;
+        let index: ColdStorageIndex = bincode::options()
+            .with_limit(index_len)
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .deserialize_from((&mut file).take(index_len))
+            .map_err(|err| invalid(err.to_string()))?;
+        index.check(index_offset).map_err(invalid)?;
+
+        let loaded = index.blocks.iter().map(|_| Once::new()).collect();
+        Ok(Self {
+            file: Mutex::new(file),
+            index,
+            loaded,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.index.len as usize
+    }
+
+    pub(crate) fn index(&self) -> &ColdStorageIndex {
+        &self.index
+    }
+
+    /// Decompresses the blocks holding the append vec contents in `range` into `contents`, unless
+    /// they already were.
+    ///
+    /// # Safety
+    ///
+    /// `contents` must point to a buffer of `self.len()` bytes that outlives `self`. The bytes of
+    /// a block are only written here, before its `Once` completes.
+    pub(crate) unsafe fn load(&self, range: Range<usize>, contents: *mut u8) {
+        for i in self.index.blocks_in_range(range) {
+            self.loaded[i].call_once(|| {
+                let block = &self.index.blocks[i];
+                let decompressed = self.read_block(block).unwrap_or_else(|err| {
+                    panic!("failed to read block {i} of cold storage: {err}");
+                });
+                std::ptr::copy_nonoverlapping(
+                    decompressed.as_ptr(),
+                    contents.add(block.offset as usize),
+                    decompressed.len(),
+                );
+            });
+        }
+    }
+
+    /// Writes the decompressed append vec contents to `writer`, leaving the blocks as they are
+    pub(crate) fn write_contents(&self, writer: &mut impl Write) -> io::Result<()> {
+        for block in &self.index.blocks {
+            writer.write_all(&self.read_block(block)?)?;
+        }
+        Ok(())
+    }
+
+    fn read_block(&self, block: &ColdStorageBlock) -> io::Result<Vec<u8>> {
+        let mut compressed = vec![0; block.compressed_len as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(block.file_offset))?;
+            file.read_exact(&mut compressed)?;
+        }
+        let decompressed = zstd::bulk::decompress(&compressed, block.len as usize)?;
+        if decompressed.len() as u64 != block.len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "block decompressed to {} bytes, not {}",
+                    decompressed.len(),
+                    block.len
+                ),
+            ));
+        }
+        Ok(decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            ancient_append_vecs::is_ancient,
+            append_vec::test_utils::{create_test_account, get_append_vec_path},
+        },
+        std::fs,
+    };
+
+    #[test]
+    fn test_cold_storage_roundtrip() {
+        let path = get_append_vec_path("test_cold_storage_roundtrip");
+        let append_vec = AppendVec::new(&path.path, true, 1024 * 1024);
+        for sample in 0..50 {
+            let (mut meta, account) = create_test_account(sample);
+            meta.pubkey = Pubkey::new_unique();
+            append_vec.append_account_test(&(meta, account)).unwrap();
+        }
+
+        let cold_path = path.path.with_file_name("test_cold_storage_roundtrip.cold");
+        let config = TieredStorageConfig {
+            compression_level: DEFAULT_TIERED_STORAGE_COMPRESSION_LEVEL,
+            block_size: 1024,
+        };
+        let index = write_cold_storage_file(&append_vec, &cold_path, &config).unwrap();
+        assert!(index.blocks.len() > 1);
+        assert_eq!(index.accounts.len(), 50);
+
+        let (cold, num_accounts) = AppendVec::new_from_cold_file(&cold_path).unwrap();
+        assert!(cold.is_cold());
+        assert!(is_ancient(&cold));
+        assert_eq!(num_accounts, 50);
+        assert_eq!(cold.len(), append_vec.len());
+        assert_eq!(cold.remaining_bytes(), 0);
+
+        let accounts = append_vec.accounts(0);
+        for account in &accounts {
+            assert_eq!(
+                cold.cold_index()
+                    .unwrap()
+                    .account_offsets(account.pubkey())
+                    .collect::<Vec<_>>(),
+                vec![account.offset]
+            );
+        }
+        // read the blocks out of order
+        for account in accounts.iter().rev() {
+            let (cold_account, next) = cold.get_account(account.offset).unwrap();
+            assert_eq!(cold_account.meta, account.meta);
+            assert_eq!(cold_account.clone_account(), account.clone_account());
+            assert_eq!(cold_account.hash, account.hash);
+            assert_eq!(next, account.offset + account.stored_size);
+        }
+
+        let mut contents = vec![];
+        cold.write_contents(&mut contents).unwrap();
+        assert_eq!(
+            contents,
+            append_vec.get_slice(0, append_vec.len()).unwrap().0
+        );
+    }
+
+    #[test]
+    fn test_cold_storage_invalid() {
+        let path = get_append_vec_path("test_cold_storage_invalid");
+        fs::write(&path.path, b"not a cold storage file").unwrap();
+        assert_eq!(
+            ColdStorage::open(&path.path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
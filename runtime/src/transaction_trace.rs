@@ -0,0 +1,137 @@
+//! Execution traces of selected transactions, for debugging replays.
+//!
+//! When the [`TransactionTraceConfig`] of a bank selects a transaction, the
+//! bank executes it with an [`ExecutionTracer`] and sends a
+//! [`TransactionTrace`] holding the traced events and the state of the
+//! transaction accounts before and after execution.
+//!
+//! [`ExecutionTracer`]: solana_program_runtime::execution_trace::ExecutionTracer
+
+use {
+    crossbeam_channel::Sender,
+    solana_program_runtime::execution_trace::ExecutionTraceEvent,
+    solana_sdk::{
+        account::{AccountSharedData, ReadableAccount},
+        clock::Slot,
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::{Result, SanitizedTransaction},
+        transaction_context::TransactionAccount,
+    },
+    std::collections::HashSet,
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct TransactionTraceConfig {
+    /// Transactions to trace by signature
+    pub signatures: HashSet<Signature>,
+    /// Transactions to trace by account, typically a program id, referenced
+    /// by their message
+    pub addresses: HashSet<Pubkey>,
+    /// Also record the state of the VM before each BPF instruction
+    pub record_vm_trace: bool,
+    /// Where the traces are sent, tracing is disabled when `None`
+    pub sender: Option<Sender<TransactionTrace>>,
+}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl solana_frozen_abi::abi_example::AbiExample for TransactionTraceConfig {
+    fn example() -> Self {
+        Self::default()
+    }
+}
+
+impl TransactionTraceConfig {
+    pub fn should_trace(&self, tx: &SanitizedTransaction) -> bool {
+        self.sender.is_some()
+            && (self.signatures.contains(tx.signature())
+                || tx
+                    .message()
+                    .account_keys()
+                    .iter()
+                    .any(|key| self.addresses.contains(key)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TracedAccountState {
+    pub lamports: u64,
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data_len: usize,
+    /// Base64 encoded account data, omitted for executable accounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl From<&AccountSharedData> for TracedAccountState {
+    fn from(account: &AccountSharedData) -> Self {
+        Self {
+            lamports: account.lamports(),
+            owner: account.owner().to_string(),
+            executable: account.executable(),
+            rent_epoch: account.rent_epoch(),
+            data_len: account.data().len(),
+            data: (!account.executable()).then(|| base64::encode(account.data())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TracedAccount {
+    pub pubkey: String,
+    pub is_writable: bool,
+    pub pre: TracedAccountState,
+    /// State after execution, only for writable accounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<TracedAccountState>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransactionTrace {
+    pub slot: Slot,
+    pub signature: String,
+    pub error: Option<String>,
+    pub compute_units_consumed: u64,
+    pub events: Vec<ExecutionTraceEvent>,
+    pub accounts: Vec<TracedAccount>,
+}
+
+impl TransactionTrace {
+    pub fn new(
+        slot: Slot,
+        tx: &SanitizedTransaction,
+        status: &Result<()>,
+        compute_units_consumed: u64,
+        events: Vec<ExecutionTraceEvent>,
+        pre_accounts: &[TransactionAccount],
+        post_accounts: &[TransactionAccount],
+    ) -> Self {
+        let message = tx.message();
+        let accounts = pre_accounts
+            .iter()
+            .enumerate()
+            .map(|(index, (pubkey, pre_account))| {
+                let is_writable = message.is_writable(index);
+                TracedAccount {
+                    pubkey: pubkey.to_string(),
+                    is_writable,
+                    pre: pre_account.into(),
+                    post: is_writable
+                        .then(|| post_accounts.get(index))
+                        .flatten()
+                        .map(|(_, post_account)| post_account.into()),
+                }
+            })
+            .collect();
+        Self {
+            slot,
+            signature: tx.signature().to_string(),
+            error: status.as_ref().err().map(|err| err.to_string()),
+            compute_units_consumed,
+            events,
+            accounts,
+        }
+    }
+}
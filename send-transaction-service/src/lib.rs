@@ -1,6 +1,7 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod send_transaction_service;
 pub mod tpu_info;
+pub mod transaction_send_status;
 
 #[macro_use]
 extern crate solana_metrics;
@@ -1,7 +1,14 @@
 use {
-    crate::tpu_info::TpuInfo,
+    crate::{
+        tpu_info::TpuInfo,
+        transaction_send_status::{
+            TransactionSendState, TransactionSendStatusCache,
+            FINISHED_TRANSACTION_STATUS_RETENTION_MS,
+        },
+    },
     crossbeam_channel::{Receiver, RecvTimeoutError},
     log::*,
+    serde_derive::{Deserialize, Serialize},
     solana_client::{connection_cache::ConnectionCache, tpu_connection::TpuConnection},
     solana_measure::measure::Measure,
     solana_metrics::datapoint_warn,
@@ -15,7 +22,9 @@ use {
             hash_map::{Entry, HashMap},
             HashSet,
         },
+        fs,
         net::SocketAddr,
+        path::{Path, PathBuf},
         sync::{
             atomic::{AtomicBool, AtomicU64, Ordering},
             Arc, Mutex, RwLock,
@@ -65,6 +74,8 @@ pub struct TransactionInfo {
     pub last_valid_block_height: u64,
     pub durable_nonce_info: Option<(Pubkey, Hash)>,
     pub max_retries: Option<usize>,
+    /// The compute unit price requested by the transaction, used to order retries
+    pub compute_unit_price: u64,
     retries: usize,
     /// Last time the transaction was sent
     last_sent_time: Option<Instant>,
@@ -85,12 +96,55 @@ impl TransactionInfo {
             last_valid_block_height,
             durable_nonce_info,
             max_retries,
+            compute_unit_price: 0,
             retries: 0,
             last_sent_time,
         }
     }
 }
 
+/// The on-disk representation of a transaction waiting in the retry queue.
+#[derive(Serialize, Deserialize)]
+struct PersistedTransactionInfo {
+    signature: Signature,
+    wire_transaction: Vec<u8>,
+    last_valid_block_height: u64,
+    durable_nonce_info: Option<(Pubkey, Hash)>,
+    max_retries: Option<usize>,
+    compute_unit_price: u64,
+    retries: usize,
+}
+
+impl From<&TransactionInfo> for PersistedTransactionInfo {
+    fn from(transaction_info: &TransactionInfo) -> Self {
+        Self {
+            signature: transaction_info.signature,
+            wire_transaction: transaction_info.wire_transaction.clone(),
+            last_valid_block_height: transaction_info.last_valid_block_height,
+            durable_nonce_info: transaction_info.durable_nonce_info,
+            max_retries: transaction_info.max_retries,
+            compute_unit_price: transaction_info.compute_unit_price,
+            retries: transaction_info.retries,
+        }
+    }
+}
+
+impl From<PersistedTransactionInfo> for TransactionInfo {
+    fn from(persisted: PersistedTransactionInfo) -> Self {
+        Self {
+            signature: persisted.signature,
+            wire_transaction: persisted.wire_transaction,
+            last_valid_block_height: persisted.last_valid_block_height,
+            durable_nonce_info: persisted.durable_nonce_info,
+            max_retries: persisted.max_retries,
+            compute_unit_price: persisted.compute_unit_price,
+            retries: persisted.retries,
+            // Resend as soon as the service is back up
+            last_sent_time: None,
+        }
+    }
+}
+
 #[derive(Default, Debug, PartialEq, Eq)]
 struct ProcessTransactionsResult {
     rooted: u64,
@@ -111,6 +165,8 @@ pub struct Config {
     pub batch_size: usize,
     /// How frequently batches are sent
     pub batch_send_rate_ms: u64,
+    /// File the retry queue is persisted to, so that pending transactions survive a restart
+    pub persist_path: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -122,6 +178,7 @@ impl Default for Config {
             service_max_retries: DEFAULT_SERVICE_MAX_RETRIES,
             batch_size: DEFAULT_TRANSACTION_BATCH_SIZE,
             batch_send_rate_ms: DEFAULT_BATCH_SEND_RATE_MS,
+            persist_path: None,
         }
     }
 }
@@ -224,6 +281,12 @@ struct SendTransactionServiceStats {
 
     /// Count of transactions failed
     failed_transactions: AtomicU64,
+
+    /// Count of lower priority transactions evicted from a full retry queue
+    evicted_transactions: AtomicU64,
+
+    /// Count of failures to persist the retry queue
+    persist_failure_count: AtomicU64,
 }
 
 #[derive(Default)]
@@ -314,6 +377,16 @@ impl SendTransactionServiceStatsReport {
                     "failed-tx",
                     self.stats.failed_transactions.swap(0, Ordering::Relaxed),
                     i64
+                ),
+                (
+                    "evicted-tx",
+                    self.stats.evicted_transactions.swap(0, Ordering::Relaxed),
+                    i64
+                ),
+                (
+                    "persist-failure-count",
+                    self.stats.persist_failure_count.swap(0, Ordering::Relaxed),
+                    i64
                 )
             );
         }
@@ -345,6 +418,7 @@ impl SendTransactionService {
             receiver,
             connection_cache,
             config,
+            Arc::<TransactionSendStatusCache>::default(),
         )
     }
 
@@ -355,10 +429,22 @@ impl SendTransactionService {
         receiver: Receiver<TransactionInfo>,
         connection_cache: &Arc<ConnectionCache>,
         config: Config,
+        send_status_cache: Arc<TransactionSendStatusCache>,
     ) -> Self {
         let stats_report = Arc::new(SendTransactionServiceStatsReport::default());
 
-        let retry_transactions = Arc::new(Mutex::new(HashMap::new()));
+        let persisted_transactions = config
+            .persist_path
+            .as_ref()
+            .map(|persist_path| Self::load_persisted_transactions(persist_path))
+            .unwrap_or_default();
+        for transaction_info in persisted_transactions.values() {
+            send_status_cache.record_received(
+                transaction_info.signature,
+                transaction_info.compute_unit_price,
+            );
+        }
+        let retry_transactions = Arc::new(Mutex::new(persisted_transactions));
 
         let leader_info_provider = Arc::new(Mutex::new(CurrentLeaderInfo::new(leader_info)));
 
@@ -370,6 +456,7 @@ impl SendTransactionService {
             connection_cache.clone(),
             config.clone(),
             retry_transactions.clone(),
+            send_status_cache.clone(),
             stats_report.clone(),
             exit.clone(),
         );
@@ -381,6 +468,7 @@ impl SendTransactionService {
             connection_cache.clone(),
             config,
             retry_transactions,
+            send_status_cache,
             stats_report,
             exit.clone(),
         );
@@ -399,6 +487,7 @@ impl SendTransactionService {
         connection_cache: Arc<ConnectionCache>,
         config: Config,
         retry_transactions: Arc<Mutex<HashMap<Signature, TransactionInfo>>>,
+        send_status_cache: Arc<TransactionSendStatusCache>,
         stats_report: Arc<SendTransactionServiceStatsReport>,
         exit: Arc<AtomicBool>,
     ) -> JoinHandle<()> {
@@ -435,6 +524,10 @@ impl SendTransactionService {
                                 .unwrap()
                                 .contains_key(&transaction_info.signature)
                             {
+                                send_status_cache.record_received(
+                                    transaction_info.signature,
+                                    transaction_info.compute_unit_price,
+                                );
                                 entry.or_insert(transaction_info);
                                 new_transaction = true;
                             }
@@ -460,6 +553,7 @@ impl SendTransactionService {
                         leader_info_provider.lock().unwrap().get_leader_info(),
                         &connection_cache,
                         &config,
+                        &send_status_cache,
                         stats,
                     );
                     let last_sent_time = Instant::now();
@@ -469,18 +563,25 @@ impl SendTransactionService {
                         let transactions_to_retry = transactions.len();
                         let mut transactions_added_to_retry: usize = 0;
                         for (signature, mut transaction_info) in transactions.drain() {
-                            let retry_len = retry_transactions.len();
-                            let entry = retry_transactions.entry(signature);
-                            if let Entry::Vacant(_) = entry {
-                                if retry_len >= MAX_TRANSACTION_QUEUE_SIZE {
-                                    datapoint_warn!("send_transaction_service-queue-overflow");
-                                    break;
-                                } else {
-                                    transaction_info.last_sent_time = Some(last_sent_time);
-                                    saturating_add_assign!(transactions_added_to_retry, 1);
-                                    entry.or_insert(transaction_info);
-                                }
+                            if retry_transactions.contains_key(&signature) {
+                                continue;
+                            }
+                            if retry_transactions.len() >= MAX_TRANSACTION_QUEUE_SIZE
+                                && !Self::evict_lower_priority_transaction(
+                                    &mut retry_transactions,
+                                    transaction_info.compute_unit_price,
+                                    &send_status_cache,
+                                    stats,
+                                )
+                            {
+                                datapoint_warn!("send_transaction_service-queue-overflow");
+                                send_status_cache
+                                    .record_finished(&signature, TransactionSendState::Dropped);
+                                continue;
                             }
+                            transaction_info.last_sent_time = Some(last_sent_time);
+                            saturating_add_assign!(transactions_added_to_retry, 1);
+                            retry_transactions.insert(signature, transaction_info);
                         }
                         stats.retry_queue_overflow.fetch_add(
                             transactions_to_retry.saturating_sub(transactions_added_to_retry)
@@ -506,6 +607,7 @@ impl SendTransactionService {
         connection_cache: Arc<ConnectionCache>,
         config: Config,
         retry_transactions: Arc<Mutex<HashMap<Signature, TransactionInfo>>>,
+        send_status_cache: Arc<TransactionSendStatusCache>,
         stats_report: Arc<SendTransactionServiceStatsReport>,
         exit: Arc<AtomicBool>,
     ) -> JoinHandle<()> {
@@ -513,6 +615,8 @@ impl SendTransactionService {
             "Starting send-transaction-service::retry_thread with config {:?}",
             config
         );
+        // signatures of the retry queue last written to `config.persist_path`
+        let mut persisted_signatures = HashSet::new();
         Builder::new()
             .name("solStxRetry".to_string())
             .spawn(move || loop {
@@ -545,10 +649,39 @@ impl SendTransactionService {
                         &leader_info_provider,
                         &connection_cache,
                         &config,
+                        &send_status_cache,
                         stats,
                     );
                     stats_report.report();
                 }
+                // Only rewrite the file when transactions were added or removed. The retry
+                // counts alone changing doesn't warrant the disk I/O, and the file is written
+                // after releasing the lock so the receive thread isn't held up.
+                let persisted = (config.persist_path.is_some()
+                    && Self::retry_queue_changed(&transactions, &persisted_signatures))
+                .then(|| {
+                    persisted_signatures = transactions.keys().copied().collect();
+                    transactions
+                        .values()
+                        .map(PersistedTransactionInfo::from)
+                        .collect::<Vec<_>>()
+                });
+                drop(transactions);
+                if let (Some(persist_path), Some(persisted)) = (&config.persist_path, persisted) {
+                    if let Err(err) = Self::persist_transactions(persist_path, &persisted) {
+                        warn!(
+                            "Failed to persist transactions to {}: {}",
+                            persist_path.display(),
+                            err
+                        );
+                        stats.persist_failure_count.fetch_add(1, Ordering::Relaxed);
+                        // write it again on the next pass
+                        persisted_signatures.clear();
+                    }
+                }
+                send_status_cache.purge_finished(Duration::from_millis(
+                    FINISHED_TRANSACTION_STATUS_RETENTION_MS,
+                ));
             })
             .unwrap()
     }
@@ -560,19 +693,111 @@ impl SendTransactionService {
         leader_info: Option<&T>,
         connection_cache: &Arc<ConnectionCache>,
         config: &Config,
+        send_status_cache: &TransactionSendStatusCache,
         stats: &SendTransactionServiceStats,
     ) {
         // Processing the transactions in batch
         let addresses = Self::get_tpu_addresses(tpu_address, leader_info, config);
 
+        let mut transactions = transactions.values().collect::<Vec<_>>();
+        Self::sort_by_priority(&mut transactions);
+        let signatures = transactions
+            .iter()
+            .map(|transaction_info| &transaction_info.signature)
+            .collect::<Vec<_>>();
         let wire_transactions = transactions
             .iter()
-            .map(|(_, transaction_info)| transaction_info.wire_transaction.as_ref())
+            .map(|transaction_info| transaction_info.wire_transaction.as_ref())
             .collect::<Vec<&[u8]>>();
 
         for address in &addresses {
-            Self::send_transactions(address, &wire_transactions, connection_cache, stats);
+            let result =
+                Self::send_transactions(address, &wire_transactions, connection_cache, stats);
+            send_status_cache.record_sent(signatures.iter().copied(), address, &result);
+        }
+    }
+
+    /// Order transactions so that the ones paying the highest compute unit price go first.
+    fn sort_by_priority(transactions: &mut [&TransactionInfo]) {
+        transactions.sort_by(|a, b| b.compute_unit_price.cmp(&a.compute_unit_price));
+    }
+
+    /// Make room in a full retry queue by evicting its lowest priority transaction, if that
+    /// transaction pays a lower compute unit price than `compute_unit_price`.
+    fn evict_lower_priority_transaction(
+        retry_transactions: &mut HashMap<Signature, TransactionInfo>,
+        compute_unit_price: u64,
+        send_status_cache: &TransactionSendStatusCache,
+        stats: &SendTransactionServiceStats,
+    ) -> bool {
+        let lowest_priority = retry_transactions
+            .values()
+            .min_by_key(|transaction_info| transaction_info.compute_unit_price)
+            .filter(|transaction_info| transaction_info.compute_unit_price < compute_unit_price)
+            .map(|transaction_info| transaction_info.signature);
+        if let Some(signature) = lowest_priority {
+            retry_transactions.remove(&signature);
+            send_status_cache.record_finished(&signature, TransactionSendState::Dropped);
+            stats.evicted_transactions.fetch_add(1, Ordering::Relaxed);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Load the retry queue persisted by a previous instance of the service.
+    fn load_persisted_transactions(persist_path: &Path) -> HashMap<Signature, TransactionInfo> {
+        if !persist_path.exists() {
+            return HashMap::new();
         }
+        let persisted: Vec<PersistedTransactionInfo> = match fs::read(persist_path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| bincode::deserialize(&data).map_err(|err| err.to_string()))
+        {
+            Ok(persisted) => persisted,
+            Err(err) => {
+                warn!(
+                    "Ignoring unreadable persisted transactions at {}: {}",
+                    persist_path.display(),
+                    err
+                );
+                return HashMap::new();
+            }
+        };
+        info!(
+            "Loaded {} persisted transactions from {}",
+            persisted.len(),
+            persist_path.display()
+        );
+        persisted
+            .into_iter()
+            .take(MAX_TRANSACTION_QUEUE_SIZE)
+            .map(|persisted| (persisted.signature, TransactionInfo::from(persisted)))
+            .collect()
+    }
+
+    /// Whether transactions were added to or removed from the retry queue since it was
+    /// persisted with `persisted_signatures`.
+    fn retry_queue_changed(
+        transactions: &HashMap<Signature, TransactionInfo>,
+        persisted_signatures: &HashSet<Signature>,
+    ) -> bool {
+        transactions.len() != persisted_signatures.len()
+            || transactions
+                .keys()
+                .any(|signature| !persisted_signatures.contains(signature))
+    }
+
+    /// Atomically replace the persisted retry queue with `persisted`.
+    fn persist_transactions(
+        persist_path: &Path,
+        persisted: &[PersistedTransactionInfo],
+    ) -> std::io::Result<()> {
+        let data = bincode::serialize(persisted)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+        let temp_path = persist_path.with_extension("tmp");
+        fs::write(&temp_path, data)?;
+        fs::rename(&temp_path, persist_path)
     }
 
     /// Retry transactions sent before.
@@ -584,6 +809,7 @@ impl SendTransactionService {
         leader_info_provider: &Arc<Mutex<CurrentLeaderInfo<T>>>,
         connection_cache: &Arc<ConnectionCache>,
         config: &Config,
+        send_status_cache: &TransactionSendStatusCache,
         stats: &SendTransactionServiceStats,
     ) -> ProcessTransactionsResult {
         let mut result = ProcessTransactionsResult::default();
@@ -597,6 +823,7 @@ impl SendTransactionService {
             }
             if root_bank.has_signature(signature) {
                 info!("Transaction is rooted: {}", signature);
+                send_status_cache.record_finished(signature, TransactionSendState::Rooted);
                 result.rooted += 1;
                 stats.rooted_transactions.fetch_add(1, Ordering::Relaxed);
                 return false;
@@ -613,6 +840,7 @@ impl SendTransactionService {
                     nonce_account::verify_nonce_account(&nonce_account, &durable_nonce);
                 if verify_nonce_account.is_none() && signature_status.is_none() && expired {
                     info!("Dropping expired durable-nonce transaction: {}", signature);
+                    send_status_cache.record_finished(signature, TransactionSendState::Expired);
                     result.expired += 1;
                     stats.expired_transactions.fetch_add(1, Ordering::Relaxed);
                     return false;
@@ -620,6 +848,7 @@ impl SendTransactionService {
             }
             if transaction_info.last_valid_block_height < root_bank.block_height() {
                 info!("Dropping expired transaction: {}", signature);
+                send_status_cache.record_finished(signature, TransactionSendState::Expired);
                 result.expired += 1;
                 stats.expired_transactions.fetch_add(1, Ordering::Relaxed);
                 return false;
@@ -633,6 +862,8 @@ impl SendTransactionService {
             if let Some(max_retries) = max_retries {
                 if transaction_info.retries >= max_retries {
                     info!("Dropping transaction due to max retries: {}", signature);
+                    send_status_cache
+                        .record_finished(signature, TransactionSendState::MaxRetriesExceeded);
                     result.max_retries_elapsed += 1;
                     stats
                         .transactions_exceeding_max_retries
@@ -667,6 +898,7 @@ impl SendTransactionService {
                 Some((_slot, status)) => {
                     if status.is_err() {
                         info!("Dropping failed transaction: {}", signature);
+                        send_status_cache.record_finished(signature, TransactionSendState::Failed);
                         result.failed += 1;
                         stats.failed_transactions.fetch_add(1, Ordering::Relaxed);
                         false
@@ -679,21 +911,38 @@ impl SendTransactionService {
         });
 
         if !batched_transactions.is_empty() {
-            // Processing the transactions in batch
-            let wire_transactions = transactions
+            // Processing the transactions in batch, highest priority first
+            let mut prioritized_transactions = transactions
                 .iter()
                 .filter(|(signature, _)| batched_transactions.contains(signature))
-                .map(|(_, transaction_info)| transaction_info.wire_transaction.as_ref())
-                .collect::<Vec<&[u8]>>();
+                .map(|(_, transaction_info)| transaction_info)
+                .collect::<Vec<_>>();
+            Self::sort_by_priority(&mut prioritized_transactions);
 
-            let iter = wire_transactions.chunks(config.batch_size);
+            let iter = prioritized_transactions.chunks(config.batch_size);
             for chunk in iter {
+                let wire_transactions = chunk
+                    .iter()
+                    .map(|transaction_info| transaction_info.wire_transaction.as_ref())
+                    .collect::<Vec<&[u8]>>();
                 let mut leader_info_provider = leader_info_provider.lock().unwrap();
                 let leader_info = leader_info_provider.get_leader_info();
                 let addresses = Self::get_tpu_addresses(tpu_address, leader_info, config);
 
                 for address in &addresses {
-                    Self::send_transactions(address, chunk, connection_cache, stats);
+                    let result = Self::send_transactions(
+                        address,
+                        &wire_transactions,
+                        connection_cache,
+                        stats,
+                    );
+                    send_status_cache.record_sent(
+                        chunk
+                            .iter()
+                            .map(|transaction_info| &transaction_info.signature),
+                        address,
+                        &result,
+                    );
                 }
             }
         }
@@ -724,7 +973,7 @@ impl SendTransactionService {
         wire_transactions: &[&[u8]],
         connection_cache: &Arc<ConnectionCache>,
         stats: &SendTransactionServiceStats,
-    ) {
+    ) -> Result<(), TransportError> {
         let mut measure = Measure::start("send-us");
         let result = if wire_transactions.len() == 1 {
             Self::send_transaction(tpu_address, wire_transactions[0], connection_cache)
@@ -732,7 +981,7 @@ impl SendTransactionService {
             Self::send_transactions_with_metrics(tpu_address, wire_transactions, connection_cache)
         };
 
-        if let Err(err) = result {
+        if let Err(err) = &result {
             warn!(
                 "Failed to send transaction transaction to {}: {:?}",
                 tpu_address, err
@@ -743,6 +992,7 @@ impl SendTransactionService {
         measure.stop();
         stats.send_us.fetch_add(measure.as_us(), Ordering::Relaxed);
         stats.send_attempt_count.fetch_add(1, Ordering::Relaxed);
+        result
     }
 
     fn get_tpu_addresses<'a, T: TpuInfo>(
@@ -852,6 +1102,7 @@ mod test {
         info!("Expired transactions are dropped...");
         let leader_info_provider = Arc::new(Mutex::new(CurrentLeaderInfo::new(None)));
         let stats = SendTransactionServiceStats::default();
+        let send_status_cache = TransactionSendStatusCache::default();
         transactions.insert(
             Signature::default(),
             TransactionInfo::new(
@@ -872,6 +1123,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -903,6 +1155,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -934,6 +1187,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -965,6 +1219,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert_eq!(transactions.len(), 1);
@@ -998,6 +1253,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert_eq!(transactions.len(), 1);
@@ -1041,6 +1297,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert_eq!(transactions.len(), 1);
@@ -1060,6 +1317,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -1135,6 +1393,7 @@ mod test {
         );
         let leader_info_provider = Arc::new(Mutex::new(CurrentLeaderInfo::new(None)));
         let stats = SendTransactionServiceStats::default();
+        let send_status_cache = TransactionSendStatusCache::default();
         let connection_cache = Arc::new(ConnectionCache::default());
         let result = SendTransactionService::process_transactions::<NullTpuInfo>(
             &working_bank,
@@ -1144,6 +1403,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -1174,6 +1434,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -1206,6 +1467,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -1236,6 +1498,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -1267,6 +1530,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert!(transactions.is_empty());
@@ -1298,6 +1562,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert_eq!(transactions.len(), 1);
@@ -1331,6 +1596,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert_eq!(transactions.len(), 1);
@@ -1361,6 +1627,7 @@ mod test {
             &leader_info_provider,
             &connection_cache,
             &config,
+            &send_status_cache,
             &stats,
         );
         assert_eq!(transactions.len(), 0);
@@ -1372,4 +1639,156 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn test_retry_queue_changed() {
+        let mut transactions = HashMap::new();
+        let mut persisted_signatures = HashSet::new();
+        assert!(!SendTransactionService::retry_queue_changed(
+            &transactions,
+            &persisted_signatures
+        ));
+
+        let signature = Signature::new(&[1; 64]);
+        transactions.insert(
+            signature,
+            TransactionInfo::new(signature, vec![], 0, None, None, None),
+        );
+        assert!(SendTransactionService::retry_queue_changed(
+            &transactions,
+            &persisted_signatures
+        ));
+        persisted_signatures.insert(signature);
+        assert!(!SendTransactionService::retry_queue_changed(
+            &transactions,
+            &persisted_signatures
+        ));
+
+        // retries alone don't change the queue
+        transactions.get_mut(&signature).unwrap().retries += 1;
+        assert!(!SendTransactionService::retry_queue_changed(
+            &transactions,
+            &persisted_signatures
+        ));
+
+        // swapping a transaction for another keeps the length, but is a change
+        transactions.clear();
+        let other = Signature::new(&[2; 64]);
+        transactions.insert(
+            other,
+            TransactionInfo::new(other, vec![], 0, None, None, None),
+        );
+        assert!(SendTransactionService::retry_queue_changed(
+            &transactions,
+            &persisted_signatures
+        ));
+    }
+
+    #[test]
+    fn test_persist_and_load_transactions() {
+        let persist_dir = tempfile::TempDir::new().unwrap();
+        let persist_path = persist_dir.path().join("pending_transactions");
+        assert!(SendTransactionService::load_persisted_transactions(&persist_path).is_empty());
+
+        let mut transactions = HashMap::new();
+        for i in 1..=3u8 {
+            let signature = Signature::new(&[i; 64]);
+            let mut transaction_info = TransactionInfo::new(
+                signature,
+                vec![i; 10],
+                42,
+                Some((Pubkey::new_unique(), Hash::new_unique())),
+                Some(i as usize),
+                Some(Instant::now()),
+            );
+            transaction_info.compute_unit_price = i as u64 * 1_000;
+            transaction_info.retries = 1;
+            transactions.insert(signature, transaction_info);
+        }
+        let persisted = transactions
+            .values()
+            .map(PersistedTransactionInfo::from)
+            .collect::<Vec<_>>();
+        SendTransactionService::persist_transactions(&persist_path, &persisted).unwrap();
+
+        let loaded = SendTransactionService::load_persisted_transactions(&persist_path);
+        assert_eq!(loaded.len(), transactions.len());
+        for (signature, transaction_info) in &transactions {
+            let loaded_info = loaded.get(signature).unwrap();
+            assert_eq!(loaded_info.signature, transaction_info.signature);
+            assert_eq!(
+                loaded_info.wire_transaction,
+                transaction_info.wire_transaction
+            );
+            assert_eq!(
+                loaded_info.last_valid_block_height,
+                transaction_info.last_valid_block_height
+            );
+            assert_eq!(
+                loaded_info.durable_nonce_info,
+                transaction_info.durable_nonce_info
+            );
+            assert_eq!(loaded_info.max_retries, transaction_info.max_retries);
+            assert_eq!(
+                loaded_info.compute_unit_price,
+                transaction_info.compute_unit_price
+            );
+            assert_eq!(loaded_info.retries, transaction_info.retries);
+            // Loaded transactions are resent right away
+            assert!(loaded_info.last_sent_time.is_none());
+        }
+
+        // A corrupt file is ignored
+        fs::write(&persist_path, [1, 2, 3]).unwrap();
+        assert!(SendTransactionService::load_persisted_transactions(&persist_path).is_empty());
+    }
+
+    #[test]
+    fn test_evict_lower_priority_transaction() {
+        let stats = SendTransactionServiceStats::default();
+        let send_status_cache = TransactionSendStatusCache::default();
+        let mut transactions = HashMap::new();
+        for i in 1..=3u8 {
+            let signature = Signature::new(&[i; 64]);
+            let mut transaction_info =
+                TransactionInfo::new(signature, vec![], 42, None, None, None);
+            transaction_info.compute_unit_price = i as u64;
+            send_status_cache.record_received(signature, transaction_info.compute_unit_price);
+            transactions.insert(signature, transaction_info);
+        }
+
+        // Nothing pays less than the lowest priority transaction
+        assert!(!SendTransactionService::evict_lower_priority_transaction(
+            &mut transactions,
+            1,
+            &send_status_cache,
+            &stats,
+        ));
+        assert_eq!(transactions.len(), 3);
+
+        assert!(SendTransactionService::evict_lower_priority_transaction(
+            &mut transactions,
+            2,
+            &send_status_cache,
+            &stats,
+        ));
+        assert_eq!(transactions.len(), 2);
+        let evicted = Signature::new(&[1; 64]);
+        assert!(!transactions.contains_key(&evicted));
+        assert_eq!(
+            send_status_cache.get(&evicted).unwrap().state,
+            TransactionSendState::Dropped
+        );
+        assert_eq!(stats.evicted_transactions.load(Ordering::Relaxed), 1);
+
+        let mut prioritized = transactions.values().collect::<Vec<_>>();
+        SendTransactionService::sort_by_priority(&mut prioritized);
+        assert_eq!(
+            prioritized
+                .iter()
+                .map(|transaction_info| transaction_info.compute_unit_price)
+                .collect::<Vec<_>>(),
+            vec![3, 2]
+        );
+    }
 }
@@ -0,0 +1,197 @@
+//! Per-transaction forwarding status kept by the send-transaction-service,
+//! so RPC clients can find out what happened to a transaction they sent.
+
+use {
+    solana_sdk::{signature::Signature, transport::TransportError},
+    std::{
+        collections::HashMap,
+        net::SocketAddr,
+        sync::RwLock,
+        time::{Duration, Instant},
+    },
+};
+
+/// How long the status of a transaction that left the retry queue is kept.
+pub const FINISHED_TRANSACTION_STATUS_RETENTION_MS: u64 = 60_000;
+
+/// Maximum number of distinct leaders remembered per transaction.
+const MAX_LEADERS_PER_TRANSACTION: usize = 32;
+
+/// Maximum number of tracked transactions; oldest finished entries are purged first.
+const MAX_TRACKED_TRANSACTIONS: usize = 20_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionSendState {
+    /// The transaction is still in the retry queue
+    Pending,
+    /// The transaction was found in a rooted bank
+    Rooted,
+    /// The blockhash or durable nonce of the transaction expired
+    Expired,
+    /// The transaction was dropped after exhausting its retries
+    MaxRetriesExceeded,
+    /// The transaction landed with an error
+    Failed,
+    /// The transaction could not be added to the retry queue
+    Dropped,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionSendStatus {
+    pub state: TransactionSendState,
+    /// Number of times the transaction was sent, counting each leader separately
+    pub attempts: usize,
+    /// Distinct TPU addresses the transaction was sent to, in first-sent order
+    pub leaders: Vec<SocketAddr>,
+    /// The last error returned while sending the transaction, if any
+    pub last_error: Option<String>,
+    /// The compute unit price the transaction was prioritized with
+    pub compute_unit_price: u64,
+}
+
+impl TransactionSendStatus {
+    fn new(compute_unit_price: u64) -> Self {
+        Self {
+            state: TransactionSendState::Pending,
+            attempts: 0,
+            leaders: vec![],
+            last_error: None,
+            compute_unit_price,
+        }
+    }
+}
+
+struct TrackedTransaction {
+    status: TransactionSendStatus,
+    last_updated: Instant,
+}
+
+/// Shared cache of the send status of recently received transactions.
+#[derive(Default)]
+pub struct TransactionSendStatusCache {
+    transactions: RwLock<HashMap<Signature, TrackedTransaction>>,
+}
+
+impl TransactionSendStatusCache {
+    pub fn get(&self, signature: &Signature) -> Option<TransactionSendStatus> {
+        self.transactions
+            .read()
+            .unwrap()
+            .get(signature)
+            .map(|tracked| tracked.status.clone())
+    }
+
+    pub(crate) fn record_received(&self, signature: Signature, compute_unit_price: u64) {
+        let mut transactions = self.transactions.write().unwrap();
+        transactions
+            .entry(signature)
+            .or_insert_with(|| TrackedTransaction {
+                status: TransactionSendStatus::new(compute_unit_price),
+                last_updated: Instant::now(),
+            });
+    }
+
+    pub(crate) fn record_sent<'a>(
+        &self,
+        signatures: impl IntoIterator<Item = &'a Signature>,
+        tpu_address: &SocketAddr,
+        result: &Result<(), TransportError>,
+    ) {
+        let now = Instant::now();
+        let mut transactions = self.transactions.write().unwrap();
+        for signature in signatures {
+            if let Some(tracked) = transactions.get_mut(signature) {
+                let status = &mut tracked.status;
+                status.attempts = status.attempts.saturating_add(1);
+                if !status.leaders.contains(tpu_address)
+                    && status.leaders.len() < MAX_LEADERS_PER_TRANSACTION
+                {
+                    status.leaders.push(*tpu_address);
+                }
+                if let Err(err) = result {
+                    status.last_error = Some(err.to_string());
+                }
+                tracked.last_updated = now;
+            }
+        }
+    }
+
+    pub(crate) fn record_finished(&self, signature: &Signature, state: TransactionSendState) {
+        if let Some(tracked) = self.transactions.write().unwrap().get_mut(signature) {
+            tracked.status.state = state;
+            tracked.last_updated = Instant::now();
+        }
+    }
+
+    /// Forget finished transactions older than `retention`, and if the cache is still too
+    /// large, the oldest finished transactions regardless of age.
+    pub(crate) fn purge_finished(&self, retention: Duration) {
+        let now = Instant::now();
+        let mut transactions = self.transactions.write().unwrap();
+        transactions.retain(|_, tracked| {
+            tracked.status.state == TransactionSendState::Pending
+                || now.duration_since(tracked.last_updated) < retention
+        });
+        if transactions.len() > MAX_TRACKED_TRANSACTIONS {
+            let mut finished = transactions
+                .iter()
+                .filter(|(_, tracked)| tracked.status.state != TransactionSendState::Pending)
+                .map(|(signature, tracked)| (tracked.last_updated, *signature))
+                .collect::<Vec<_>>();
+            finished.sort_unstable();
+            let excess = transactions.len() - MAX_TRACKED_TRANSACTIONS;
+            for (_, signature) in finished.into_iter().take(excess) {
+                transactions.remove(&signature);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_send_status_cache() {
+        let cache = TransactionSendStatusCache::default();
+        let signature = Signature::new(&[1; 64]);
+        let leader1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let leader2: SocketAddr = "127.0.0.1:8002".parse().unwrap();
+        assert!(cache.get(&signature).is_none());
+
+        cache.record_received(signature, 42);
+        assert_eq!(
+            cache.get(&signature).unwrap(),
+            TransactionSendStatus::new(42)
+        );
+
+        cache.record_sent([&signature], &leader1, &Ok(()));
+        cache.record_sent(
+            [&signature],
+            &leader2,
+            &Err(TransportError::Custom("refused".to_string())),
+        );
+        cache.record_sent([&signature], &leader1, &Ok(()));
+        let status = cache.get(&signature).unwrap();
+        assert_eq!(status.attempts, 3);
+        assert_eq!(status.leaders, vec![leader1, leader2]);
+        assert_eq!(
+            status.last_error.as_deref(),
+            Some("transport custom error: refused")
+        );
+
+        // Pending transactions are never purged
+        cache.purge_finished(Duration::ZERO);
+        assert!(cache.get(&signature).is_some());
+
+        cache.record_finished(&signature, TransactionSendState::Rooted);
+        assert_eq!(
+            cache.get(&signature).unwrap().state,
+            TransactionSendState::Rooted
+        );
+        cache.purge_finished(Duration::from_secs(60));
+        assert!(cache.get(&signature).is_some());
+        cache.purge_finished(Duration::ZERO);
+        assert!(cache.get(&signature).is_none());
+    }
+}
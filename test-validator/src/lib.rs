@@ -22,9 +22,17 @@ use {
     solana_rpc::{rpc::JsonRpcConfig, rpc_pubsub_service::PubSubConfig},
     solana_rpc_client::{nonblocking, rpc_client::RpcClient},
     solana_runtime::{
-        accounts_db::AccountsDbConfig, accounts_index::AccountsIndexConfig, bank_forks::BankForks,
+        account_set::AccountSet,
+        accounts_db::AccountsDbConfig,
+        accounts_index::AccountsIndexConfig,
+        bank_forks::BankForks,
         genesis_utils::create_genesis_config_with_leader_ex,
-        hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE, runtime_config::RuntimeConfig,
+        hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
+        lazy_account_loader::{
+            AccountSetLazyAccountSource, LazyAccountLoader, LazyAccountSource,
+            LazyAccountSourceResult,
+        },
+        runtime_config::RuntimeConfig,
         snapshot_config::SnapshotConfig,
     },
     solana_sdk::{
@@ -50,7 +58,7 @@ use {
     std::{
         collections::{HashMap, HashSet},
         ffi::OsStr,
-        fmt::Display,
+        fmt::{self, Debug, Display},
         fs::{self, remove_dir_all, File},
         io::Read,
         net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -98,6 +106,36 @@ impl Default for TestValidatorNodeConfig {
     }
 }
 
+/// Fetches accounts from an RPC endpoint, for `TestValidatorGenesis::lazy_from`
+pub struct RpcLazyAccountSource {
+    rpc_client: RpcClient,
+}
+
+impl RpcLazyAccountSource {
+    pub fn new(url: String) -> Self {
+        Self {
+            rpc_client: RpcClient::new(url),
+        }
+    }
+}
+
+impl Debug for RpcLazyAccountSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcLazyAccountSource")
+            .field("url", &self.rpc_client.url())
+            .finish()
+    }
+}
+
+impl LazyAccountSource for RpcLazyAccountSource {
+    fn get_account(&self, pubkey: &Pubkey) -> LazyAccountSourceResult {
+        let response = self
+            .rpc_client
+            .get_account_with_commitment(pubkey, CommitmentConfig::confirmed())?;
+        Ok(response.value.map(AccountSharedData::from))
+    }
+}
+
 pub struct TestValidatorGenesis {
     fee_rate_governor: FeeRateGovernor,
     ledger_path: Option<PathBuf>,
@@ -125,6 +163,7 @@ pub struct TestValidatorGenesis {
     pub log_messages_bytes_limit: Option<usize>,
     pub transaction_account_lock_limit: Option<usize>,
     pub tpu_enable_udp: bool,
+    lazy_account_loader: Option<Arc<LazyAccountLoader>>,
 }
 
 impl Default for TestValidatorGenesis {
@@ -156,6 +195,7 @@ impl Default for TestValidatorGenesis {
             log_messages_bytes_limit: Option::<usize>::default(),
             transaction_account_lock_limit: Option::<usize>::default(),
             tpu_enable_udp: DEFAULT_TPU_ENABLE_UDP,
+            lazy_account_loader: Option::<Arc<LazyAccountLoader>>::default(),
         }
     }
 }
@@ -188,6 +228,28 @@ impl TestValidatorGenesis {
         self
     }
 
+    /// Fetch the accounts the validator doesn't have from `source` when a transaction first
+    /// references them, see `Bank::load_lazy_accounts`
+    pub fn lazy_account_source(&mut self, source: Box<dyn LazyAccountSource>) -> &mut Self {
+        self.lazy_account_loader = Some(Arc::new(LazyAccountLoader::new(source)));
+        self
+    }
+
+    /// Fetch the accounts the validator doesn't have from `lazy_from`, either the URL of an RPC
+    /// endpoint or local stand-in service, or the path of an account set file
+    pub fn lazy_from(&mut self, lazy_from: &str) -> Result<&mut Self, String> {
+        let source: Box<dyn LazyAccountSource> =
+            if lazy_from.starts_with("http://") || lazy_from.starts_with("https://") {
+                Box::new(RpcLazyAccountSource::new(lazy_from.to_string()))
+            } else {
+                let account_set = AccountSet::read_from_file(lazy_from)
+                    .map_err(|err| format!("Unable to load account set {lazy_from}: {err}"))?;
+                Box::new(AccountSetLazyAccountSource::from(account_set))
+            };
+        info!("Fetching missing accounts lazily from {}", lazy_from);
+        Ok(self.lazy_account_source(source))
+    }
+
     pub fn fee_rate_governor(&mut self, fee_rate_governor: FeeRateGovernor) -> &mut Self {
         self.fee_rate_governor = fee_rate_governor;
         self
@@ -385,6 +447,26 @@ impl TestValidatorGenesis {
         Ok(self)
     }
 
+    /// Add the accounts of an account set file, see `Bank::export_account_set`
+    pub fn add_account_set_files<T, P>(&mut self, files: T) -> Result<&mut Self, String>
+    where
+        T: IntoIterator<Item = P>,
+        P: AsRef<Path> + Display,
+    {
+        for file in files {
+            let account_set = AccountSet::read_from_file(&file)
+                .map_err(|err| format!("Unable to load account set {file}: {err}"))?;
+            info!(
+                "Loading {} accounts from account set {} of slot {}",
+                account_set.len(),
+                file,
+                account_set.slot
+            );
+            self.add_accounts(account_set.shared_accounts());
+        }
+        Ok(self)
+    }
+
     /// Add an account to the test environment with the account data in the provided `filename`
     pub fn add_account_with_file_data(
         &mut self,
@@ -788,6 +870,8 @@ impl TestValidator {
                 }),
             log_messages_bytes_limit: config.log_messages_bytes_limit,
             transaction_account_lock_limit: config.transaction_account_lock_limit,
+            lazy_account_loader: config.lazy_account_loader.clone(),
+            ..RuntimeConfig::default()
         };
 
         let mut validator_config = ValidatorConfig {
@@ -0,0 +1,225 @@
+//! Per-leader accounting of transaction sends, used by the `TpuClient` to report where a
+//! transaction went and to stop sending to leaders that keep refusing its connections.
+
+use {
+    solana_sdk::transport::{Result as TransportResult, TransportError},
+    std::{
+        collections::HashMap,
+        io,
+        net::SocketAddr,
+        sync::RwLock,
+        time::{Duration, Instant},
+    },
+};
+
+/// Default number of consecutive refused sends after which a leader is skipped
+pub const DEFAULT_MAX_CONSECUTIVE_REFUSALS: u64 = 3;
+
+/// Default time a leader that keeps refusing sends is skipped for
+pub const DEFAULT_REFUSED_LEADER_BACKOFF: Duration = Duration::from_secs(10);
+
+/// What happened to a transaction sent to a single leader
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LeaderSendOutcome {
+    /// The leader accepted the transaction
+    Accepted,
+    /// The leader refused the connection or stream
+    Refused,
+    /// The leader closed the connection or stream because of rate limiting
+    Throttled,
+    /// Connecting or sending to the leader timed out
+    TimedOut,
+    /// The send failed for another reason
+    Failed,
+}
+
+impl LeaderSendOutcome {
+    /// Classify the result of a send from its error alone, for connections that can't tell why
+    /// a send failed. See `TpuConnection::send_wire_transaction_batch_with_outcome`.
+    pub fn from_result(result: &TransportResult<()>) -> Self {
+        match result {
+            Ok(()) => Self::Accepted,
+            Err(TransportError::IoError(err)) => match err.kind() {
+                io::ErrorKind::TimedOut => Self::TimedOut,
+                io::ErrorKind::ConnectionRefused => Self::Refused,
+                _ => Self::Failed,
+            },
+            Err(_) => Self::Failed,
+        }
+    }
+
+    fn is_refusal(&self) -> bool {
+        matches!(self, Self::Refused | Self::Throttled)
+    }
+}
+
+/// Running totals of send outcomes for one leader
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LeaderSendCounts {
+    pub accepted: u64,
+    pub refused: u64,
+    pub throttled: u64,
+    pub timed_out: u64,
+    pub failed: u64,
+    /// Refused or throttled sends since the last accepted one
+    pub consecutive_refusals: u64,
+}
+
+#[derive(Default)]
+struct LeaderSendRecord {
+    counts: LeaderSendCounts,
+    skip_until: Option<Instant>,
+}
+
+/// Send outcomes of all leaders a `TpuClient` has sent to
+pub struct LeaderSendStats {
+    max_consecutive_refusals: u64,
+    refused_leader_backoff: Duration,
+    leaders: RwLock<HashMap<SocketAddr, LeaderSendRecord>>,
+}
+
+impl Default for LeaderSendStats {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_CONSECUTIVE_REFUSALS,
+            DEFAULT_REFUSED_LEADER_BACKOFF,
+        )
+    }
+}
+
+impl LeaderSendStats {
+    pub fn new(max_consecutive_refusals: u64, refused_leader_backoff: Duration) -> Self {
+        Self {
+            max_consecutive_refusals,
+            refused_leader_backoff,
+            leaders: RwLock::default(),
+        }
+    }
+
+    pub fn record(&self, leader: &SocketAddr, outcome: LeaderSendOutcome) {
+        let mut leaders = self.leaders.write().unwrap();
+        let record = leaders.entry(*leader).or_default();
+        let counts = &mut record.counts;
+        match outcome {
+            LeaderSendOutcome::Accepted => counts.accepted += 1,
+            LeaderSendOutcome::Refused => counts.refused += 1,
+            LeaderSendOutcome::Throttled => counts.throttled += 1,
+            LeaderSendOutcome::TimedOut => counts.timed_out += 1,
+            LeaderSendOutcome::Failed => counts.failed += 1,
+        }
+        if outcome.is_refusal() {
+            counts.consecutive_refusals += 1;
+            if self.max_consecutive_refusals > 0
+                && counts.consecutive_refusals >= self.max_consecutive_refusals
+            {
+                // Once the backoff elapses the leader is tried again, and skipped right away
+                // if it is still refusing.
+                record.skip_until = Some(Instant::now() + self.refused_leader_backoff);
+            }
+        } else if outcome == LeaderSendOutcome::Accepted {
+            counts.consecutive_refusals = 0;
+            record.skip_until = None;
+        }
+    }
+
+    pub fn counts(&self, leader: &SocketAddr) -> Option<LeaderSendCounts> {
+        self.leaders
+            .read()
+            .unwrap()
+            .get(leader)
+            .map(|record| record.counts.clone())
+    }
+
+    pub fn is_skipped(&self, leader: &SocketAddr) -> bool {
+        let now = Instant::now();
+        self.leaders
+            .read()
+            .unwrap()
+            .get(leader)
+            .and_then(|record| record.skip_until)
+            .map(|skip_until| now < skip_until)
+            .unwrap_or(false)
+    }
+
+    /// Split `leaders` into the ones to send to and the ones currently skipped. If every leader
+    /// is skipped, they are all sent to anyway.
+    pub fn partition_leaders(
+        &self,
+        leaders: Vec<SocketAddr>,
+    ) -> (Vec<SocketAddr>, Vec<SocketAddr>) {
+        let (send_to, skipped): (Vec<_>, Vec<_>) = leaders
+            .into_iter()
+            .partition(|leader| !self.is_skipped(leader));
+        if send_to.is_empty() {
+            (skipped, vec![])
+        } else {
+            (send_to, skipped)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leader_send_outcome_from_result() {
+        assert_eq!(
+            LeaderSendOutcome::from_result(&Ok(())),
+            LeaderSendOutcome::Accepted
+        );
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        assert_eq!(
+            LeaderSendOutcome::from_result(&Err(refused.into())),
+            LeaderSendOutcome::Refused
+        );
+        let timed_out = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+        assert_eq!(
+            LeaderSendOutcome::from_result(&Err(timed_out.into())),
+            LeaderSendOutcome::TimedOut
+        );
+        assert_eq!(
+            LeaderSendOutcome::from_result(&Err(TransportError::Custom("error".to_string()))),
+            LeaderSendOutcome::Failed
+        );
+    }
+
+    #[test]
+    fn test_leader_send_stats_skips_refusing_leaders() {
+        let stats = LeaderSendStats::new(2, Duration::from_secs(60));
+        let leader1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let leader2: SocketAddr = "127.0.0.1:8002".parse().unwrap();
+
+        stats.record(&leader1, LeaderSendOutcome::Refused);
+        stats.record(&leader2, LeaderSendOutcome::Accepted);
+        assert!(!stats.is_skipped(&leader1));
+
+        // Timeouts neither count towards nor reset the refusal streak
+        stats.record(&leader1, LeaderSendOutcome::TimedOut);
+        stats.record(&leader1, LeaderSendOutcome::Throttled);
+        assert!(stats.is_skipped(&leader1));
+        assert_eq!(
+            stats.counts(&leader1).unwrap(),
+            LeaderSendCounts {
+                refused: 1,
+                throttled: 1,
+                timed_out: 1,
+                consecutive_refusals: 2,
+                ..LeaderSendCounts::default()
+            }
+        );
+        assert_eq!(
+            stats.partition_leaders(vec![leader1, leader2]),
+            (vec![leader2], vec![leader1])
+        );
+        // All leaders skipped, send to them anyway
+        assert_eq!(
+            stats.partition_leaders(vec![leader1]),
+            (vec![leader1], vec![])
+        );
+
+        stats.record(&leader1, LeaderSendOutcome::Accepted);
+        assert!(!stats.is_skipped(&leader1));
+        assert_eq!(stats.counts(&leader1).unwrap().consecutive_refusals, 0);
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::integer_arithmetic)]
 
 pub mod connection_cache_stats;
+pub mod leader_send_stats;
 pub mod nonblocking;
 pub mod tpu_client;
 pub mod tpu_connection;
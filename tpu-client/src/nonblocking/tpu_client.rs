@@ -8,6 +8,7 @@ use {
 };
 use {
     crate::{
+        leader_send_stats::{LeaderSendOutcome, LeaderSendStats},
         nonblocking::tpu_connection::TpuConnection,
         tpu_client::{RecentLeaderSlots, TpuClientConfig, MAX_FANOUT_SLOTS},
         tpu_connection_cache::{
@@ -28,7 +29,7 @@ use {
         commitment_config::CommitmentConfig,
         epoch_info::EpochInfo,
         pubkey::Pubkey,
-        signature::SignerError,
+        signature::{Signature, SignerError},
         transaction::Transaction,
         transport::{Result as TransportResult, TransportError},
     },
@@ -251,6 +252,35 @@ impl LeaderTpuCache {
     }
 }
 
+/// The result of sending a transaction to one leader
+#[derive(Debug)]
+pub struct LeaderSendResult {
+    pub leader: SocketAddr,
+    pub outcome: LeaderSendOutcome,
+    pub error: Option<TransportError>,
+}
+
+/// What happened to a transaction sent with `TpuClient::send_transaction_with_report`
+#[derive(Debug, Default)]
+pub struct TpuSendReport {
+    /// The leaders the transaction was sent to, and how each of them responded
+    pub leader_results: Vec<LeaderSendResult>,
+    /// Upcoming leaders that were not sent to because they keep refusing sends
+    pub skipped_leaders: Vec<SocketAddr>,
+    /// Set if no leader accepted the transaction and it was forwarded to the fallback RPC node
+    pub rpc_fallback_result: Option<ClientResult<Signature>>,
+}
+
+impl TpuSendReport {
+    /// Whether a leader or the fallback RPC node accepted the transaction
+    pub fn accepted(&self) -> bool {
+        self.leader_results
+            .iter()
+            .any(|result| result.outcome == LeaderSendOutcome::Accepted)
+            || matches!(self.rpc_fallback_result, Some(Ok(_)))
+    }
+}
+
 /// Client which sends transactions directly to the current leader's TPU port over UDP.
 /// The client uses RPC to determine the current leader and fetch node contact info
 pub struct TpuClient<P: ConnectionPool> {
@@ -259,6 +289,8 @@ pub struct TpuClient<P: ConnectionPool> {
     exit: Arc<AtomicBool>,
     rpc_client: Arc<RpcClient>,
     connection_cache: Arc<TpuConnectionCache<P>>,
+    leader_send_stats: Arc<LeaderSendStats>,
+    rpc_fallback_client: Option<RpcClient>,
 }
 
 async fn send_wire_transaction_to_addr<P: ConnectionPool>(
@@ -274,9 +306,10 @@ async fn send_wire_transaction_batch_to_addr<P: ConnectionPool>(
     connection_cache: &TpuConnectionCache<P>,
     addr: &SocketAddr,
     wire_transactions: &[Vec<u8>],
-) -> TransportResult<()> {
+) -> (TransportResult<()>, LeaderSendOutcome) {
     let conn = connection_cache.get_nonblocking_connection(addr);
-    conn.send_wire_transaction_batch(wire_transactions).await
+    conn.send_wire_transaction_batch_with_outcome(wire_transactions)
+        .await
 }
 
 impl<P: ConnectionPool> TpuClient<P> {
@@ -302,15 +335,80 @@ impl<P: ConnectionPool> TpuClient<P> {
         self.try_send_wire_transaction(wire_transaction).await
     }
 
+    /// Serialize and send transaction to the current and upcoming leader TPUs according to fanout
+    /// size, reporting what each leader did with it. Leaders that keep refusing sends are
+    /// skipped, and if no leader accepts the transaction it is forwarded to the fallback RPC
+    /// node, if one is configured.
+    pub async fn send_transaction_with_report(&self, transaction: &Transaction) -> TpuSendReport {
+        let wire_transaction = serialize(transaction).expect("serialization should succeed");
+        let (leaders, skipped_leaders) = self.leader_tpu_sockets();
+        // Sent as a batch of one, as single sends don't report failures
+        let futures = leaders
+            .iter()
+            .map(|addr| {
+                send_wire_transaction_batch_to_addr(
+                    &self.connection_cache,
+                    addr,
+                    std::slice::from_ref(&wire_transaction),
+                )
+            })
+            .collect::<Vec<_>>();
+        let results: Vec<(TransportResult<()>, LeaderSendOutcome)> = join_all(futures).await;
+
+        let leader_results = leaders
+            .into_iter()
+            .zip(results)
+            .map(|(leader, (result, outcome))| {
+                self.leader_send_stats.record(&leader, outcome);
+                LeaderSendResult {
+                    leader,
+                    outcome,
+                    error: result.err(),
+                }
+            })
+            .collect();
+        let mut report = TpuSendReport {
+            leader_results,
+            skipped_leaders,
+            rpc_fallback_result: None,
+        };
+        if !report.accepted() {
+            if let Some(rpc_fallback_client) = &self.rpc_fallback_client {
+                debug!(
+                    "No leader accepted transaction {}, forwarding it to {}",
+                    transaction.signatures[0],
+                    rpc_fallback_client.url()
+                );
+                report.rpc_fallback_result =
+                    Some(rpc_fallback_client.send_transaction(transaction).await);
+            }
+        }
+        report
+    }
+
+    /// Send outcomes of the leaders this client has reported on
+    pub fn leader_send_stats(&self) -> &Arc<LeaderSendStats> {
+        &self.leader_send_stats
+    }
+
+    /// The upcoming leader TPUs to send to, and the ones skipped because they keep refusing sends
+    fn leader_tpu_sockets(&self) -> (Vec<SocketAddr>, Vec<SocketAddr>) {
+        let leaders = self
+            .leader_tpu_service
+            .leader_tpu_sockets(self.fanout_slots);
+        self.leader_send_stats.partition_leaders(leaders)
+    }
+
     /// Send a wire transaction to the current and upcoming leader TPUs according to fanout size
     /// Returns the last error if all sends fail
+    ///
+    /// Single sends are fire-and-forget, so they are not recorded in `leader_send_stats`, only
+    /// `send_transaction_with_report` and `try_send_wire_transaction_batch` sends are.
     pub async fn try_send_wire_transaction(
         &self,
         wire_transaction: Vec<u8>,
     ) -> TransportResult<()> {
-        let leaders = self
-            .leader_tpu_service
-            .leader_tpu_sockets(self.fanout_slots);
+        let (leaders, _skipped_leaders) = self.leader_tpu_sockets();
         let futures = leaders
             .iter()
             .map(|addr| {
@@ -352,9 +450,7 @@ impl<P: ConnectionPool> TpuClient<P> {
         &self,
         wire_transactions: Vec<Vec<u8>>,
     ) -> TransportResult<()> {
-        let leaders = self
-            .leader_tpu_service
-            .leader_tpu_sockets(self.fanout_slots);
+        let (leaders, _skipped_leaders) = self.leader_tpu_sockets();
         let futures = leaders
             .iter()
             .map(|addr| {
@@ -365,7 +461,15 @@ impl<P: ConnectionPool> TpuClient<P> {
                 )
             })
             .collect::<Vec<_>>();
-        let results: Vec<TransportResult<()>> = join_all(futures).await;
+        let results: Vec<TransportResult<()>> = join_all(futures)
+            .await
+            .into_iter()
+            .zip(leaders.iter())
+            .map(|((result, outcome), leader)| {
+                self.leader_send_stats.record(leader, outcome);
+                result
+            })
+            .collect();
 
         let mut last_error: Option<TransportError> = None;
         let mut some_success = false;
@@ -411,12 +515,22 @@ impl<P: ConnectionPool> TpuClient<P> {
         let leader_tpu_service =
             LeaderTpuService::new(rpc_client.clone(), websocket_url, exit.clone()).await?;
 
+        let leader_send_stats = Arc::new(LeaderSendStats::new(
+            config.max_consecutive_refusals,
+            config.refused_leader_backoff,
+        ));
+        let rpc_fallback_client = config
+            .rpc_fallback_url
+            .map(|url| RpcClient::new_with_commitment(url, rpc_client.commitment()));
+
         Ok(Self {
             fanout_slots: config.fanout_slots.clamp(1, MAX_FANOUT_SLOTS),
             leader_tpu_service,
             exit,
             rpc_client,
             connection_cache,
+            leader_send_stats,
+            rpc_fallback_client,
         })
     }
 
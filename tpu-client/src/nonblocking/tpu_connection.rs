@@ -1,6 +1,7 @@
 //! Trait defining async send functions, to be used for UDP or QUIC sending
 
 use {
+    crate::leader_send_stats::LeaderSendOutcome,
     async_trait::async_trait,
     solana_sdk::{transaction::VersionedTransaction, transport::Result as TransportResult},
     std::net::SocketAddr,
@@ -26,4 +27,19 @@ pub trait TpuConnection {
     async fn send_wire_transaction_batch<T>(&self, buffers: &[T]) -> TransportResult<()>
     where
         T: AsRef<[u8]> + Send + Sync;
+
+    /// Send a batch like `send_wire_transaction_batch`, along with what the leader did with it.
+    /// Connections that know why a send failed override this, the default only looks at the
+    /// error of the send.
+    async fn send_wire_transaction_batch_with_outcome<T>(
+        &self,
+        buffers: &[T],
+    ) -> (TransportResult<()>, LeaderSendOutcome)
+    where
+        T: AsRef<[u8]> + Send + Sync,
+    {
+        let result = self.send_wire_transaction_batch(buffers).await;
+        let outcome = LeaderSendOutcome::from_result(&result);
+        (result, outcome)
+    }
 }
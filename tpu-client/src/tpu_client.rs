@@ -1,6 +1,9 @@
-pub use crate::nonblocking::tpu_client::TpuSenderError;
+pub use crate::nonblocking::tpu_client::{TpuSendReport, TpuSenderError};
+#[cfg(feature = "spinner")]
+use solana_sdk::{message::Message, signers::Signers, transaction::TransactionError};
 use {
     crate::{
+        leader_send_stats::{DEFAULT_MAX_CONSECUTIVE_REFUSALS, DEFAULT_REFUSED_LEADER_BACKOFF},
         nonblocking::tpu_client::TpuClient as NonblockingTpuClient,
         tpu_connection_cache::{ConnectionPool, TpuConnectionCache},
     },
@@ -11,13 +14,9 @@ use {
         collections::VecDeque,
         net::UdpSocket,
         sync::{Arc, RwLock},
+        time::Duration,
     },
 };
-#[cfg(feature = "spinner")]
-use {
-    solana_sdk::{message::Message, signers::Signers, transaction::TransactionError},
-    tokio::time::Duration,
-};
 
 pub mod temporary_pub {
     use super::*;
@@ -45,12 +44,23 @@ pub struct TpuClientConfig {
     /// The range of upcoming slots to include when determining which
     /// leaders to send transactions to (min: 1, max: `MAX_FANOUT_SLOTS`)
     pub fanout_slots: u64,
+    /// Number of consecutive refused or throttled sends after which a leader is
+    /// skipped (0 disables skipping)
+    pub max_consecutive_refusals: u64,
+    /// How long a leader that keeps refusing sends is skipped for
+    pub refused_leader_backoff: Duration,
+    /// RPC node that transactions are forwarded to by `send_transaction_with_report`
+    /// when no leader accepted them
+    pub rpc_fallback_url: Option<String>,
 }
 
 impl Default for TpuClientConfig {
     fn default() -> Self {
         Self {
             fanout_slots: DEFAULT_FANOUT_SLOTS,
+            max_consecutive_refusals: DEFAULT_MAX_CONSECUTIVE_REFUSALS,
+            refused_leader_backoff: DEFAULT_REFUSED_LEADER_BACKOFF,
+            rpc_fallback_url: None,
         }
     }
 }
@@ -76,6 +86,12 @@ impl<P: ConnectionPool> TpuClient<P> {
         self.invoke(self.tpu_client.send_wire_transaction(wire_transaction))
     }
 
+    /// Serialize and send transaction to the current and upcoming leader TPUs according to fanout
+    /// size, reporting what each leader did with it
+    pub fn send_transaction_with_report(&self, transaction: &Transaction) -> TpuSendReport {
+        self.invoke(self.tpu_client.send_transaction_with_report(transaction))
+    }
+
     /// Serialize and send transaction to the current and upcoming leader TPUs according to fanout
     /// size
     /// Returns the last error if all sends fail
@@ -99,6 +115,9 @@ impl<P: ConnectionPool> TpuClient<P> {
 
     /// Send a wire transaction to the current and upcoming leader TPUs according to fanout size
     /// Returns the last error if all sends fail
+    ///
+    /// Like the other single sends, this is not recorded in the leader send stats, see
+    /// `NonblockingTpuClient::try_send_wire_transaction`.
     pub fn try_send_wire_transaction(&self, wire_transaction: Vec<u8>) -> TransportResult<()> {
         self.invoke(self.tpu_client.try_send_wire_transaction(wire_transaction))
     }
@@ -14,7 +14,10 @@ use {
     },
     solana_rpc::rpc::verify_pubkey,
     solana_rpc_client_api::{config::RpcAccountIndex, custom_error::RpcCustomError},
-    solana_runtime::{accounts_index::AccountIndex, bank_forks::BankForks},
+    solana_runtime::{
+        accounts_index::AccountIndex, bank_forks::BankForks,
+        bucket_map_holder_stats::AccountsIndexMemoryStats,
+    },
     solana_sdk::{
         exit::Exit,
         pubkey::Pubkey,
@@ -143,6 +146,115 @@ impl Display for AdminRpcContactInfo {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminRpcAccountsIndexStats {
+    pub bins: usize,
+    pub disk_index_enabled: bool,
+    pub mem_budget_mb: Option<usize>,
+    pub mem_budget_tuning: bool,
+    pub estimate_mem_bytes: u64,
+    pub count: usize,
+    pub count_in_mem: usize,
+    pub count_on_disk: usize,
+    pub min_in_bin_mem: usize,
+    pub max_in_bin_mem: usize,
+    pub ages_to_stay_in_cache: u8,
+    pub max_ages_to_stay_in_cache: u8,
+    pub flush_target_percent: u64,
+    pub lookups_found_in_mem: u64,
+    pub lookups_loaded_from_disk: u64,
+    pub budget_ages_increased: u64,
+    pub budget_ages_decreased: u64,
+}
+
+impl From<AccountsIndexMemoryStats> for AdminRpcAccountsIndexStats {
+    fn from(stats: AccountsIndexMemoryStats) -> Self {
+        let AccountsIndexMemoryStats {
+            bins,
+            disk_index_enabled,
+            mem_budget_mb,
+            mem_budget_tuning,
+            estimate_mem_bytes,
+            count,
+            count_in_mem,
+            count_on_disk,
+            min_in_bin_mem,
+            max_in_bin_mem,
+            ages_to_stay_in_cache,
+            max_ages_to_stay_in_cache,
+            flush_target_percent,
+            lookups_found_in_mem,
+            lookups_loaded_from_disk,
+            budget_ages_increased,
+            budget_ages_decreased,
+        } = stats;
+        Self {
+            bins,
+            disk_index_enabled,
+            mem_budget_mb,
+            mem_budget_tuning,
+            estimate_mem_bytes,
+            count,
+            count_in_mem,
+            count_on_disk,
+            min_in_bin_mem,
+            max_in_bin_mem,
+            ages_to_stay_in_cache,
+            max_ages_to_stay_in_cache,
+            flush_target_percent,
+            lookups_found_in_mem,
+            lookups_loaded_from_disk,
+            budget_ages_increased,
+            budget_ages_decreased,
+        }
+    }
+}
+
+impl Display for AdminRpcAccountsIndexStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Bins: {}", self.bins)?;
+        writeln!(f, "Disk Index Enabled: {}", self.disk_index_enabled)?;
+        match self.mem_budget_mb {
+            Some(mem_budget_mb) => writeln!(
+                f,
+                "Memory Budget: {} MB{}",
+                mem_budget_mb,
+                if self.mem_budget_tuning {
+                    " (tuned)"
+                } else {
+                    ""
+                }
+            )?,
+            None => writeln!(f, "Memory Budget: none")?,
+        }
+        writeln!(f, "Estimated Memory: {} bytes", self.estimate_mem_bytes)?;
+        writeln!(f, "Entries: {}", self.count)?;
+        writeln!(
+            f,
+            "Entries In Memory: {} (per bin min {}, max {})",
+            self.count_in_mem, self.min_in_bin_mem, self.max_in_bin_mem
+        )?;
+        writeln!(f, "Entries On Disk: {}", self.count_on_disk)?;
+        writeln!(
+            f,
+            "Ages To Stay In Cache: {} (max {})",
+            self.ages_to_stay_in_cache, self.max_ages_to_stay_in_cache
+        )?;
+        writeln!(f, "Flush Target: {}%", self.flush_target_percent)?;
+        writeln!(f, "Lookups Found In Memory: {}", self.lookups_found_in_mem)?;
+        writeln!(
+            f,
+            "Lookups Loaded From Disk: {}",
+            self.lookups_loaded_from_disk
+        )?;
+        writeln!(
+            f,
+            "Budget Adjustments: {} increased, {} decreased",
+            self.budget_ages_increased, self.budget_ages_decreased
+        )
+    }
+}
+
 impl Display for AdminRpcRepairWhitelist {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Repair whitelist: {:?}", &self.whitelist)
@@ -206,6 +318,9 @@ pub trait AdminRpc {
     #[rpc(meta, name = "setRepairWhitelist")]
     fn set_repair_whitelist(&self, meta: Self::Metadata, whitelist: Vec<Pubkey>) -> Result<()>;
 
+    #[rpc(meta, name = "accountsIndexStats")]
+    fn accounts_index_stats(&self, meta: Self::Metadata) -> Result<AdminRpcAccountsIndexStats>;
+
     #[rpc(meta, name = "getSecondaryIndexKeySize")]
     fn get_secondary_index_key_size(
         &self,
@@ -380,6 +495,18 @@ impl AdminRpc for AdminRpcImpl {
         })
     }
 
+    fn accounts_index_stats(&self, meta: Self::Metadata) -> Result<AdminRpcAccountsIndexStats> {
+        meta.with_post_init(|post_init| {
+            let bank = post_init.bank_forks.read().unwrap().root_bank();
+            Ok(bank
+                .accounts()
+                .accounts_db
+                .accounts_index
+                .memory_stats()
+                .into())
+        })
+    }
+
     fn get_secondary_index_key_size(
         &self,
         meta: Self::Metadata,
@@ -707,6 +834,24 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_accounts_index_stats() {
+        let rpc = RpcHandler::start_with_config(TestConfig::default());
+        let bank = rpc.root_bank();
+        let RpcHandler { io, meta, .. } = rpc;
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"accountsIndexStats"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let stats: AdminRpcAccountsIndexStats =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        let accounts_index = &bank.accounts().accounts_db.accounts_index;
+        assert_eq!(stats.bins, accounts_index.bins());
+        assert_eq!(stats.count, accounts_index.memory_stats().count,);
+        assert!(stats.count > 0);
+    }
+
     #[test]
     fn test_secondary_index_key_sizes() {
         for secondary_index_enabled in [true, false] {
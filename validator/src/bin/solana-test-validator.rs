@@ -240,6 +240,11 @@ fn main() {
         .unwrap_or_default()
         .collect();
 
+    let account_sets: Vec<_> = matches
+        .values_of("account_set")
+        .unwrap_or_default()
+        .collect();
+
     let accounts_to_clone: HashSet<_> = pubkeys_of(&matches, "clone_account")
         .map(|v| v.into_iter().collect())
         .unwrap_or_default();
@@ -414,8 +419,21 @@ fn main() {
             println!("Error: add_accounts_from_directories failed: {e}");
             exit(1);
         })
+        .add_account_set_files(&account_sets)
+        .unwrap_or_else(|e| {
+            println!("Error: add_account_set_files failed: {e}");
+            exit(1);
+        })
         .deactivate_features(&features_to_deactivate);
 
+    if let Some(lazy_from) = matches.value_of("lazy_from") {
+        let lazy_from = normalize_to_url_if_moniker(lazy_from);
+        if let Err(e) = genesis.lazy_from(&lazy_from) {
+            println!("Error: lazy_from failed: {e}");
+            exit(1);
+        }
+    }
+
     genesis.rpc_config(JsonRpcConfig {
         enable_rpc_transaction_history: true,
         enable_extended_tx_metadata_storage: true,
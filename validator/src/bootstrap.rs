@@ -3,7 +3,9 @@ use {
     rand::{seq::SliceRandom, thread_rng, Rng},
     rayon::prelude::*,
     solana_core::validator::{ValidatorConfig, ValidatorStartProgress},
-    solana_download_utils::{download_snapshot_archive, DownloadProgressRecord},
+    solana_download_utils::{
+        download_snapshot_archive, download_snapshot_archive_from_store, DownloadProgressRecord,
+    },
     solana_genesis_utils::download_then_check_genesis_hash,
     solana_gossip::{
         cluster_info::{ClusterInfo, Node},
@@ -1255,6 +1257,22 @@ fn download_snapshot(
         desired_snapshot_hash.0,
         solana_runtime::snapshot_hash::SnapshotHash(desired_snapshot_hash.1),
     );
+    // The desired snapshot hash matches the known validators' ones, so the archive store only
+    // has to be trusted for availability
+    if let Some(archive_store) = validator_config.snapshot_config.archive_store.as_ref() {
+        match download_snapshot_archive_from_store(
+            archive_store.as_ref(),
+            full_snapshot_archives_dir,
+            incremental_snapshot_archives_dir,
+            desired_snapshot_hash,
+            snapshot_type,
+            maximum_full_snapshot_archives_to_retain,
+            maximum_incremental_snapshot_archives_to_retain,
+        ) {
+            Ok(_) => return Ok(()),
+            Err(err) => warn!("{}, downloading from {} instead", err, rpc_contact_info.id),
+        }
+    }
     download_snapshot_archive(
         &rpc_contact_info.rpc,
         full_snapshot_archives_dir,
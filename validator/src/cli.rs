@@ -228,6 +228,15 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .help("Include CPI inner instructions, logs, and return data in \
                        the historical transaction info stored"),
         )
+        .arg(
+            Arg::with_name("enable_program_instruction_index")
+                .long("enable-program-instruction-index")
+                .requires("enable_rpc_transaction_history")
+                .takes_value(false)
+                .help("Index historical transactions by the programs and instruction \
+                       discriminators of their top-level and inner instructions, \
+                       enabling the 'getSignaturesForProgramInstruction' API"),
+        )
         .arg(
             Arg::with_name("rpc_max_multiple_accounts")
                 .long("rpc-max-multiple-accounts")
@@ -548,6 +557,17 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 /* .default_value() intentionally not used here! */
                 .help("Keep this amount of shreds in root slots."),
         )
+        .arg(
+            Arg::with_name("ledger_archive_path")
+                .long("ledger-archive-path")
+                .value_name("DIR")
+                .takes_value(true)
+                .requires("enable_rpc_transaction_history")
+                .help("Archive rooted blocks into immutable segment files in this directory \
+                       before they are purged from the ledger, and serve historical blocks \
+                       and transactions over JSON RPC from them.  \
+                       Only blocks with complete transaction history are archived"),
+        )
         .arg(
             Arg::with_name("rocksdb_shred_compaction")
                 .long("rocksdb-shred-compaction")
@@ -582,11 +602,12 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .long("rocksdb-ledger-compression")
                 .value_name("COMPRESSION_TYPE")
                 .takes_value(true)
-                .possible_values(&["none", "lz4", "snappy", "zlib"])
+                .possible_values(&["none", "lz4", "snappy", "zlib", "zstd"])
                 .default_value(&default_args.rocksdb_ledger_compression)
                 .help("The compression alrogithm that is used to compress \
-                       transaction status data.  \
-                       Turning on compression can save ~10% of the ledger size."),
+                       transaction status, address signature and rewards data.  \
+                       Turning on compression can save ~10% of the ledger size. \
+                       zstd also trains a dictionary per SST file."),
         )
         .arg(
             Arg::with_name("rocksdb_perf_sample_interval")
@@ -1004,6 +1025,14 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .default_value(&default_args.rpc_send_transaction_batch_size)
                 .help("The size of transactions to be sent in batch."),
         )
+        .arg(
+            Arg::with_name("rpc_send_transaction_persist_path")
+                .long("rpc-send-persist-path")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Persist transactions waiting to be retried by the rpc service to this file, \
+                       so that they are still retried after a restart"),
+        )
         .arg(
             Arg::with_name("rpc_scan_and_fix_roots")
                 .long("rpc-scan-and-fix-roots")
@@ -1020,6 +1049,15 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .default_value(&default_args.rpc_max_request_body_size)
                 .help("The maximum request body size accepted by rpc service"),
         )
+        .arg(
+            Arg::with_name("enable_rpc_response_encodings")
+                .long("enable-rpc-response-encodings")
+                .takes_value(false)
+                .help("Answer getBlock with protobuf, getProgramAccounts and \
+                       getMultipleAccounts with bincode, and compress responses with \
+                       zstd or gzip, when requested by the client's Accept and \
+                       Accept-Encoding headers"),
+        )
         .arg(
             Arg::with_name("enable_accountsdb_repl")
                 .long("enable-accountsdb-repl")
@@ -1082,6 +1120,39 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(true)
                 .help("Snapshot archive format to use."),
         )
+        .arg(
+            Arg::with_name("snapshot_archive_store")
+                .long("snapshot-archive-store")
+                .value_name("DIR_OR_S3_URL")
+                .takes_value(true)
+                .help(
+                    "Publish the snapshot archives this node creates to this store, and fetch \
+                     snapshot archives from it at startup. The store is either a directory or \
+                     an S3 bucket given as s3://BUCKET[/PREFIX], accessed with the \
+                     AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY environment variables if set. \
+                     Fetched archives must match the snapshot hashes of the known validators",
+                ),
+        )
+        .arg(
+            Arg::with_name("snapshot_archive_store_s3_endpoint")
+                .long("snapshot-archive-store-s3-endpoint")
+                .value_name("URL")
+                .takes_value(true)
+                .requires("snapshot_archive_store")
+                .help(
+                    "Endpoint of the S3-compatible object storage of --snapshot-archive-store \
+                     [default: the AWS endpoint of --snapshot-archive-store-s3-region]",
+                ),
+        )
+        .arg(
+            Arg::with_name("snapshot_archive_store_s3_region")
+                .long("snapshot-archive-store-s3-region")
+                .value_name("REGION")
+                .takes_value(true)
+                .requires("snapshot_archive_store")
+                .default_value("us-east-1")
+                .help("Region of the S3 bucket of --snapshot-archive-store"),
+        )
         .arg(
             Arg::with_name("max_genesis_archive_unpacked_size")
                 .long("max-genesis-archive-unpacked-size")
@@ -1191,6 +1262,13 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .help("AppendVecs that are older than (slots_per_epoch - SLOT-OFFSET) are squashed together.")
                 .hidden(true),
         )
+        .arg(
+            Arg::with_name("accounts_db_tiered_storage")
+                .long("accounts-db-tiered-storage")
+                .requires("accounts_db_ancient_append_vecs")
+                .help("Move ancient AppendVecs that no longer receive accounts to compressed cold storage files.")
+                .hidden(true),
+        )
         .arg(
             Arg::with_name("accounts_db_cache_limit_mb")
                 .long("accounts-db-cache-limit-mb")
@@ -1215,11 +1293,23 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                 .takes_value(true)
                 .help("How much memory the accounts index can consume. If this is exceeded, some account index entries will be stored on disk."),
         )
+        .arg(
+            Arg::with_name("accounts_index_memory_budget_mb")
+                .long("accounts-index-memory-budget-mb")
+                .value_name("MEGABYTES")
+                .validator(is_parsable::<usize>)
+                .takes_value(true)
+                .conflicts_with("accounts_index_memory_limit_mb")
+                .help("How much memory the accounts index should aim to consume. How long entries \
+                       stay in memory and how fast they are flushed to disk are tuned at runtime \
+                       to stay close to this budget."),
+        )
         .arg(
             Arg::with_name("disable_accounts_disk_index")
                 .long("disable-accounts-disk-index")
                 .help("Disable the disk-based accounts index if it is enabled by default.")
                 .conflicts_with("accounts_index_memory_limit_mb")
+                .conflicts_with("accounts_index_memory_budget_mb")
         )
         .arg(
             Arg::with_name("accounts_index_bins")
@@ -1404,6 +1494,18 @@ pub fn app<'a>(version: &'a str, default_args: &'a DefaultArgs) -> App<'a, 'a> {
                         .help("Output display mode")
                 )
         )
+        .subcommand(
+            SubCommand::with_name("accounts-index-stats")
+                .about("Display the memory use of the validator's accounts index")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("MODE")
+                        .possible_values(&["json", "json-compact"])
+                        .help("Output display mode")
+                )
+        )
         .subcommand(
             SubCommand::with_name("repair-whitelist")
                 .about("Manage the validator's repair protocol whitelist")
@@ -2048,6 +2150,31 @@ pub fn test_app<'a>(version: &'a str, default_args: &'a DefaultTestArgs) -> App<
                         If the ledger already exists then this parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("account_set")
+                .long("account-set")
+                .value_name("FILE")
+                .takes_value(true)
+                .multiple(true)
+                .help(
+                    "Load all the accounts from the account set FILE \
+                        (see `solana-ledger-tool export-account-set`). \
+                        If the ledger already exists then this parameter is silently ignored",
+                ),
+        )
+        .arg(
+            Arg::with_name("lazy_from")
+                .long("lazy-from")
+                .value_name("URL_OR_FILE")
+                .takes_value(true)
+                .help(
+                    "Fetch the accounts the validator doesn't have when a transaction first \
+                        references them, from an RPC URL (or moniker), such as a cluster or a \
+                        local stand-in service, or from an account set FILE. \
+                        Fetched accounts are only stored on the fork that fetched them, \
+                        and RPC reports them with `lazyFetched: true`",
+                ),
+        )
         .arg(
             Arg::with_name("no_bpf_jit")
                 .long("no-bpf-jit")
@@ -15,6 +15,7 @@ use {
         tpu::DEFAULT_TPU_COALESCE_MS,
         validator::{is_snapshot_config_valid, Validator, ValidatorConfig, ValidatorStartProgress},
     },
+    solana_download_utils::s3_snapshot_archive_store::snapshot_archive_store_from_location,
     solana_gossip::{cluster_info::Node, legacy_contact_info::LegacyContactInfo as ContactInfo},
     solana_ledger::blockstore_options::{
         BlockstoreCompressionType, BlockstoreRecoveryMode, LedgerColumnOptions, ShredStorageType,
@@ -36,6 +37,7 @@ use {
         runtime_config::RuntimeConfig,
         snapshot_config::{SnapshotConfig, SnapshotUsage},
         snapshot_utils::{self, ArchiveFormat, SnapshotVersion},
+        tiered_storage::TieredStorageConfig,
     },
     solana_sdk::{
         clock::{Slot, DEFAULT_S_PER_SLOT},
@@ -551,6 +553,31 @@ pub fn main() {
             }
             return;
         }
+        ("accounts-index-stats", Some(subcommand_matches)) => {
+            let output_mode = subcommand_matches.value_of("output");
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let accounts_index_stats = admin_rpc_service::runtime()
+                .block_on(async move { admin_client.await?.accounts_index_stats().await })
+                .unwrap_or_else(|err| {
+                    eprintln!("Accounts index stats query failed: {err}");
+                    exit(1);
+                });
+            if let Some(mode) = output_mode {
+                match mode {
+                    "json" => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&accounts_index_stats).unwrap()
+                    ),
+                    "json-compact" => {
+                        print!("{}", serde_json::to_string(&accounts_index_stats).unwrap())
+                    }
+                    _ => unreachable!(),
+                }
+            } else {
+                print!("{accounts_index_stats}");
+            }
+            return;
+        }
         ("init", _) => Operation::Initialize,
         ("exit", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
@@ -1000,14 +1027,17 @@ pub fn main() {
         accounts_index_config.bins = Some(bins);
     }
 
-    accounts_index_config.index_limit_mb =
-        if let Some(limit) = value_t!(matches, "accounts_index_memory_limit_mb", usize).ok() {
-            IndexLimitMb::Limit(limit)
-        } else if matches.is_present("disable_accounts_disk_index") {
-            IndexLimitMb::InMemOnly
-        } else {
-            IndexLimitMb::Unspecified
-        };
+    accounts_index_config.index_limit_mb = if let Some(limit) =
+        value_t!(matches, "accounts_index_memory_limit_mb", usize).ok()
+    {
+        IndexLimitMb::Limit(limit)
+    } else if let Some(budget) = value_t!(matches, "accounts_index_memory_budget_mb", usize).ok() {
+        IndexLimitMb::Budget(budget)
+    } else if matches.is_present("disable_accounts_disk_index") {
+        IndexLimitMb::InMemOnly
+    } else {
+        IndexLimitMb::Unspecified
+    };
 
     {
         let mut accounts_index_paths: Vec<PathBuf> = if matches.is_present("accounts_index_path") {
@@ -1043,6 +1073,9 @@ pub fn main() {
             .ok()
             .map(|mb| mb * MB as u64),
         ancient_append_vec_offset: value_t!(matches, "accounts_db_ancient_append_vecs", i64).ok(),
+        tiered_storage: matches
+            .is_present("accounts_db_tiered_storage")
+            .then(TieredStorageConfig::default),
         exhaustively_verify_refcounts: matches.is_present("accounts_db_verify_refcounts"),
         ..AccountsDbConfig::default()
     };
@@ -1120,6 +1153,8 @@ pub fn main() {
             enable_rpc_transaction_history: matches.is_present("enable_rpc_transaction_history"),
             enable_extended_tx_metadata_storage: matches.is_present("enable_cpi_and_log_storage")
                 || matches.is_present("enable_extended_tx_metadata_storage"),
+            enable_program_instruction_index: matches
+                .is_present("enable_program_instruction_index"),
             rpc_bigtable_config,
             faucet_addr: matches.value_of("rpc_faucet_addr").map(|address| {
                 solana_net_utils::parse_host_port(address).expect("failed to parse faucet address")
@@ -1145,6 +1180,7 @@ pub fn main() {
                 "rpc_max_request_body_size",
                 usize
             )),
+            enable_response_encodings: matches.is_present("enable_rpc_response_encodings"),
         },
         geyser_plugin_config_files,
         rpc_addrs: value_t!(matches, "rpc_port", u16).ok().map(|rpc_port| {
@@ -1211,6 +1247,9 @@ pub fn main() {
             ),
             batch_send_rate_ms: rpc_send_batch_send_rate_ms,
             batch_size: rpc_send_batch_size,
+            persist_path: matches
+                .value_of("rpc_send_transaction_persist_path")
+                .map(PathBuf::from),
         },
         no_poh_speed_test: matches.is_present("no_poh_speed_test"),
         no_os_memory_stats_reporting: matches.is_present("no_os_memory_stats_reporting"),
@@ -1349,6 +1388,20 @@ pub fn main() {
             .unwrap_or_else(|| panic!("Archive format not recognized: {archive_format_str}"))
     };
 
+    let snapshot_archive_store = matches.value_of("snapshot_archive_store").map(|location| {
+        snapshot_archive_store_from_location(
+            location,
+            matches.value_of("snapshot_archive_store_s3_endpoint"),
+            matches
+                .value_of("snapshot_archive_store_s3_region")
+                .unwrap(),
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to open the snapshot archive store: {err}");
+            exit(1);
+        })
+    });
+
     let snapshot_version =
         matches
             .value_of("snapshot_version")
@@ -1392,6 +1445,7 @@ pub fn main() {
         maximum_incremental_snapshot_archives_to_retain,
         accounts_hash_debug_verify: validator_config.accounts_db_test_hash_calculation,
         packager_thread_niceness_adj: snapshot_packager_niceness_adj,
+        archive_store: snapshot_archive_store,
     };
 
     validator_config.accounts_hash_interval_slots =
@@ -1428,6 +1482,8 @@ pub fn main() {
         }
         validator_config.max_ledger_shreds = Some(limit_ledger_size);
     }
+    validator_config.ledger_archive_path =
+        matches.value_of("ledger_archive_path").map(PathBuf::from);
 
     configure_banking_trace_dir_byte_limit(&mut validator_config, &matches);
 
@@ -1439,6 +1495,7 @@ pub fn main() {
                 "snappy" => BlockstoreCompressionType::Snappy,
                 "lz4" => BlockstoreCompressionType::Lz4,
                 "zlib" => BlockstoreCompressionType::Zlib,
+                "zstd" => BlockstoreCompressionType::Zstd,
                 _ => panic!("Unsupported ledger_compression: {ledger_compression_string}"),
             },
         },